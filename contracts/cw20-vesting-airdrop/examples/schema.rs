@@ -3,8 +3,9 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use cw20_vesting_airdrop::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
-    MerkleRootResponse, QueryMsg,
+    ClaimableAmountResponse, ClaimableFromSnapshotResponse, ConfigResponse, ExecuteMsg,
+    InstantiateMsg, IsClaimedResponse, LatestStageResponse, MerkleRootResponse, PauseInfoResponse,
+    QueryMsg, ReclaimedAmountResponse,
 };
 
 fn main() {
@@ -18,6 +19,10 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(LatestStageResponse), &out_dir);
     export_schema(&schema_for!(MerkleRootResponse), &out_dir);
+    export_schema(&schema_for!(ReclaimedAmountResponse), &out_dir);
     export_schema(&schema_for!(IsClaimedResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PauseInfoResponse), &out_dir);
+    export_schema(&schema_for!(ClaimableAmountResponse), &out_dir);
+    export_schema(&schema_for!(ClaimableFromSnapshotResponse), &out_dir);
 }
@@ -1,46 +1,93 @@
+use bech32::{ToBase32, Variant};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Uint128, WasmMsg,
+    attr, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Reply,
+    Response, StdError, StdResult, SubMsg, SubMsgResult, Timestamp, Uint128, WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
-use cw20_vesting::ExecuteMsg as Cw20ExecuteMsg;
+use cw20::BalanceResponse;
+use cw20_vesting::{ExecuteMsg as Cw20ExecuteMsg, QueryMsg as Cw20QueryMsg};
+use cw_core_interface::voting::{
+    Query as DaoQuery, TotalPowerAtHeightResponse, VotingPowerAtHeightResponse,
+};
 use cw_utils::{Expiration, Scheduled};
-use sha2::Digest;
-use std::convert::TryInto;
+use ripemd::{Digest as _, Ripemd160};
+use sha2::Digest as _;
+use wynd_decisions::msg::{ExecuteMsg as DecisionsExecuteMsg, RecordMsg};
 use wynd_utils::{Curve, ScalableCurve};
 
 use crate::error::ContractError;
+use crate::merkle::{decode_hash, verify_proof};
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
-    MerkleRootResponse, MigrateMsg, QueryMsg, TotalClaimedResponse,
+    AirdropMode, ClaimableAmountResponse, ClaimableFromSnapshotResponse, ConfigResponse,
+    ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse, MerkleRootResponse,
+    MigrateMsg, PauseInfoResponse, QueryMsg, ReclaimedAmountResponse, TotalClaimedResponse,
 };
 use crate::state::{
-    Config, StageAmounts, StageDetails, CLAIM, CONFIG, LATEST_STAGE, MERKLE_ROOT, STAGE_AMOUNTS,
-    STAGE_DETAILS,
+    Config, GovernanceSnapshot, PauseInfo, SlashSchedule, StageAmounts, StageDetails, CLAIM,
+    CONFIG, DEFAULT_EMERGENCY_TIMELOCK, DEFAULT_EXPIRATION_GRACE_PERIOD, LATEST_STAGE, MERKLE_ROOT,
+    PAUSED, RECLAIMED_AMOUNT, STAGE_AMOUNTS, STAGE_DETAILS,
 };
 
 // Version info, for migration info
 const CONTRACT_NAME: &str = "crates.io:cw20-vesting-airdrop";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Reply id of failed decision records
+const RECORD_DECISION_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    if let Some(schedule) = &msg.slash_schedule {
+        if schedule.slash_pct_per_day > Decimal::one() || schedule.max_slash_pct > Decimal::one() {
+            return Err(ContractError::InvalidSlashSchedule {});
+        }
+    }
+
     let owner = msg
         .owner
         .map_or(Ok(info.sender), |o| deps.api.addr_validate(&o))?;
 
+    let governance_snapshot = match msg.airdrop_mode {
+        AirdropMode::Merkle {} => None,
+        AirdropMode::GovernanceSnapshot {
+            snapshot_height,
+            staking_contract,
+        } => {
+            // the voting power must not change anymore after instantiation
+            if snapshot_height >= env.block.height {
+                return Err(ContractError::InvalidSnapshotHeight {
+                    height: snapshot_height,
+                });
+            }
+            Some(GovernanceSnapshot {
+                snapshot_height,
+                staking_contract: deps.api.addr_validate(&staking_contract)?,
+            })
+        }
+    };
+
     let config = Config {
         owner: Some(owner),
         cw20_token_address: deps.api.addr_validate(&msg.cw20_token_address)?,
+        emergency_timelock: msg.emergency_timelock.unwrap_or(DEFAULT_EMERGENCY_TIMELOCK),
+        expiration_grace_period: msg
+            .expiration_grace_period
+            .unwrap_or(DEFAULT_EXPIRATION_GRACE_PERIOD),
+        slash_schedule: msg.slash_schedule,
+        governance_snapshot,
+        decisions_contract: msg
+            .decisions_contract
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?,
     };
     CONFIG.save(deps.storage, &config)?;
 
@@ -80,10 +127,76 @@ pub fn execute(
             amount,
             proof,
         } => execute_claim(deps, env, info, stage, amount, proof),
+        ExecuteMsg::ClaimFor {
+            stage,
+            recipient,
+            amount,
+            proof,
+            pubkey,
+            signature,
+        } => execute_claim_for(
+            deps, env, info, stage, recipient, amount, proof, pubkey, signature,
+        ),
         ExecuteMsg::Burn { stage } => execute_burn(deps, env, info, stage),
         ExecuteMsg::ClawBack { stage, recipient } => {
             execute_clawback(deps, env, info, stage, Some(recipient))
         }
+        ExecuteMsg::Pause {} => execute_pause(deps, env, info),
+        ExecuteMsg::Resume {} => execute_resume(deps, info),
+        ExecuteMsg::ReclaimUnclaimed { recipient } => {
+            execute_reclaim_airdrop(deps, env, info, recipient)
+        }
+        ExecuteMsg::EmergencyWithdraw { recipient } => {
+            execute_emergency_withdraw(deps, env, info, recipient)
+        }
+        ExecuteMsg::UpdateDecisionsContract { address } => {
+            execute_update_decisions_contract(deps, info, address)
+        }
+    }
+}
+
+pub fn execute_update_decisions_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut cfg = assert_owner(deps.as_ref(), &info.sender)?;
+    cfg.decisions_contract = address
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let mut res = Response::new().add_attribute("action", "update_decisions_contract");
+    if let Some(decisions_contract) = cfg.decisions_contract {
+        res = res.add_attribute("decisions_contract", decisions_contract);
+    }
+    Ok(res)
+}
+
+/// Records a decision in the configured decisions contract, if any.
+/// A failing record only adds a warning, so it never blocks the airdrop.
+fn record_decision(cfg: &Config, record: RecordMsg) -> StdResult<Option<SubMsg>> {
+    cfg.decisions_contract
+        .as_ref()
+        .map(|decisions_contract| {
+            let msg = WasmMsg::Execute {
+                contract_addr: decisions_contract.to_string(),
+                msg: to_binary(&DecisionsExecuteMsg::Record(record))?,
+                funds: vec![],
+            };
+            Ok(SubMsg::reply_on_error(msg, RECORD_DECISION_REPLY_ID))
+        })
+        .transpose()
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match (msg.id, msg.result) {
+        (RECORD_DECISION_REPLY_ID, SubMsgResult::Err(err)) => Ok(Response::new()
+            .add_attribute("warning", "decision_not_recorded")
+            .add_attribute("error", err)),
+        (RECORD_DECISION_REPLY_ID, SubMsgResult::Ok(_)) => Ok(Response::new()),
+        (id, _) => Err(ContractError::UnknownReplyId { id }),
     }
 }
 
@@ -120,27 +233,28 @@ pub fn execute_register_merkle_root(
     _env: Env,
     info: MessageInfo,
     merkle_root: String,
-    expiration: Expiration,
+    expiration: Option<Expiration>,
     start: Scheduled,
     total_amount: Uint128,
     vesting: Option<ScalableCurve>,
 ) -> Result<Response, ContractError> {
-    let cfg = CONFIG.load(deps.storage)?;
-
     // if owner set validate, otherwise unauthorized
-    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
-    if info.sender != owner {
-        return Err(ContractError::Unauthorized {});
-    }
+    let cfg = assert_owner(deps.as_ref(), &info.sender)?;
 
     // check vesting valid
     if let Some(v) = vesting.as_ref() {
         v.validate_monotonic_decreasing()?;
     }
 
-    // check merkle root length
-    let mut root_buf: [u8; 32] = [0; 32];
-    hex::decode_to_slice(&merkle_root, &mut root_buf)?;
+    let expiration = match expiration {
+        Some(expiration) => expiration,
+        None => default_expiration(&start, vesting.as_ref(), cfg.expiration_grace_period)
+            .ok_or(ContractError::MissingExpiration {})?,
+    };
+    validate_expiration(&expiration, &start, vesting.as_ref())?;
+
+    // check merkle root length, and always store it hex-encoded
+    let merkle_root = hex::encode(decode_hash(&merkle_root)?);
 
     let stage = LATEST_STAGE.update(deps.storage, |stage| -> StdResult<_> { Ok(stage + 1) })?;
 
@@ -160,7 +274,20 @@ pub fn execute_register_merkle_root(
     };
     STAGE_AMOUNTS.save(deps.storage, stage, &amounts)?;
 
-    Ok(Response::new().add_attributes(vec![
+    let record = record_decision(
+        &cfg,
+        RecordMsg {
+            title: format!("Airdrop stage {} registered", stage),
+            body: format!(
+                "Airdrop stage {} with merkle root {} distributes {} tokens until {}",
+                stage, merkle_root, total_amount, expiration
+            ),
+            url: None,
+            hash: Some(merkle_root.clone()),
+        },
+    )?;
+
+    Ok(Response::new().add_submessages(record).add_attributes(vec![
         attr("action", "register_merkle_root"),
         attr("stage", stage.to_string()),
         attr("merkle_root", merkle_root),
@@ -168,6 +295,46 @@ pub fn execute_register_merkle_root(
     ]))
 }
 
+/// Expires the grace period after the end of the vesting schedule, or after the start
+/// of the stage if there is none. None if neither is given as a time.
+fn default_expiration(
+    start: &Scheduled,
+    vesting: Option<&ScalableCurve>,
+    grace_period: u64,
+) -> Option<Expiration> {
+    let end = match (vesting.and_then(ScalableCurve::time_range), start) {
+        (Some((_, end)), _) => Timestamp::from_seconds(end),
+        (None, Scheduled::AtTime(start)) => *start,
+        (None, Scheduled::AtHeight(_)) => return None,
+    };
+    Some(Expiration::AtTime(end.plus_seconds(grace_period)))
+}
+
+/// Ensures the stage does not expire before it starts or before its vesting begins.
+fn validate_expiration(
+    expiration: &Expiration,
+    start: &Scheduled,
+    vesting: Option<&ScalableCurve>,
+) -> Result<(), ContractError> {
+    let before_start = |start: Scheduled| match (expiration, &start) {
+        (Expiration::AtHeight(exp), Scheduled::AtHeight(start)) => exp < start,
+        (Expiration::AtTime(exp), Scheduled::AtTime(start)) => exp < start,
+        _ => false,
+    };
+    let vesting_start = vesting
+        .and_then(ScalableCurve::time_range)
+        .map(|(begin, _)| Scheduled::AtTime(Timestamp::from_seconds(begin)));
+    for start in std::iter::once(*start).chain(vesting_start) {
+        if before_start(start) {
+            return Err(ContractError::ExpirationBeforeStart {
+                expiration: *expiration,
+                start,
+            });
+        }
+    }
+    Ok(())
+}
+
 pub fn execute_claim(
     deps: DepsMut,
     env: Env,
@@ -176,6 +343,71 @@ pub fn execute_claim(
     amount: Uint128,
     proof: Vec<String>,
 ) -> Result<Response, ContractError> {
+    claim(deps, env, info.sender, stage, amount, proof)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_claim_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+    recipient: String,
+    amount: Uint128,
+    proof: Vec<String>,
+    pubkey: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    let prefix = recipient
+        .as_str()
+        .rsplit_once('1')
+        .map(|(prefix, _)| prefix)
+        .ok_or(ContractError::PubkeyMismatch {})?;
+    if pubkey_to_address(&pubkey, prefix)? != recipient.as_str() {
+        return Err(ContractError::PubkeyMismatch {});
+    }
+
+    let payload = claim_for_payload(&env.contract.address, stage, &recipient, amount);
+    let hash = sha2::Sha256::digest(payload.as_bytes());
+    let valid = deps
+        .api
+        .secp256k1_verify(&hash, &signature, &pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !valid {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    let res = claim(deps, env, recipient, stage, amount, proof)?;
+    Ok(res.add_attribute("relayer", info.sender))
+}
+
+/// The message a recipient signs to authorize `ExecuteMsg::ClaimFor`
+pub fn claim_for_payload(contract: &Addr, stage: u8, recipient: &Addr, amount: Uint128) -> String {
+    format!("claim_for:{}:{}:{}:{}", contract, stage, recipient, amount)
+}
+
+/// Derives the bech32 address of a secp256k1 public key, the same way the Cosmos SDK does
+fn pubkey_to_address(pubkey: &[u8], prefix: &str) -> Result<String, ContractError> {
+    let hash = Ripemd160::digest(sha2::Sha256::digest(pubkey));
+    bech32::encode(prefix, hash.to_base32(), Variant::Bech32)
+        .map_err(|_| ContractError::PubkeyMismatch {})
+}
+
+/// Claims the airdrop of `recipient`, once it is authorized
+fn claim(
+    deps: DepsMut,
+    env: Env,
+    recipient: Addr,
+    stage: u8,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> Result<Response, ContractError> {
+    if PAUSED.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::Paused {});
+    }
+
     let StageDetails {
         expiration,
         start,
@@ -192,39 +424,40 @@ pub fn execute_claim(
     }
 
     // verify not claimed
-    let claimed = CLAIM.may_load(deps.storage, (&info.sender, stage))?;
+    let claimed = CLAIM.may_load(deps.storage, (&recipient, stage))?;
     if claimed.is_some() {
         return Err(ContractError::Claimed {});
     }
 
     let config = CONFIG.load(deps.storage)?;
-    let merkle_root = MERKLE_ROOT.load(deps.storage, stage)?;
-
-    let user_input = format!("{}{}", info.sender, amount);
-    let hash = sha2::Sha256::digest(user_input.as_bytes())
-        .as_slice()
-        .try_into()
-        .map_err(|_| ContractError::WrongLength {})?;
-
-    let hash = proof.into_iter().try_fold(hash, |hash, p| {
-        let mut proof_buf = [0; 32];
-        hex::decode_to_slice(p, &mut proof_buf)?;
-        let mut hashes = [hash, proof_buf];
-        hashes.sort_unstable();
-        sha2::Sha256::digest(&hashes.concat())
-            .as_slice()
-            .try_into()
-            .map_err(|_| ContractError::WrongLength {})
-    })?;
+    let amount = match &config.governance_snapshot {
+        Some(snapshot) => {
+            let total = STAGE_AMOUNTS.load(deps.storage, stage)?.total;
+            let (power, total_power) = query_snapshot_power(deps.as_ref(), snapshot, &recipient)?;
+            if power.is_zero() {
+                return Err(ContractError::NoSnapshotPower {
+                    height: snapshot.snapshot_height,
+                });
+            }
+            total.multiply_ratio(power, total_power)
+        }
+        None => {
+            let merkle_root = MERKLE_ROOT.load(deps.storage, stage)?;
+            let user_input = format!("{}{}", recipient, amount);
+            verify_proof(&decode_hash(&merkle_root)?, &user_input, &proof)?;
+            amount
+        }
+    };
 
-    let mut root_buf: [u8; 32] = [0; 32];
-    hex::decode_to_slice(merkle_root, &mut root_buf)?;
-    if root_buf != hash {
-        return Err(ContractError::VerificationFailed {});
-    }
+    // late claims are slashed, the difference stays in the contract
+    let original_amount = amount;
+    let amount = match &config.slash_schedule {
+        Some(schedule) => schedule.effective_amount(amount, env.block.time),
+        None => amount,
+    };
 
     // record the claim (individually and total)
-    CLAIM.save(deps.storage, (&info.sender, stage), &true)?;
+    CLAIM.save(deps.storage, (&recipient, stage), &true)?;
     STAGE_AMOUNTS.update::<_, ContractError>(deps.storage, stage, |old| {
         let mut val = old.ok_or_else(|| StdError::not_found("stage_amounts"))?;
         // TODO: ensure we cannot claim more than total
@@ -233,8 +466,8 @@ pub fn execute_claim(
     })?;
 
     let scaled = vesting.map(|v| v.scale(amount));
-    let msg = transfer_msg(&info.sender, amount, scaled);
-    let res = Response::new()
+    let msg = transfer_msg(&recipient, amount, scaled);
+    let mut res = Response::new()
         .add_message(WasmMsg::Execute {
             contract_addr: config.cw20_token_address.to_string(),
             msg: to_binary(&msg)?,
@@ -243,12 +476,37 @@ pub fn execute_claim(
         .add_attributes(vec![
             attr("action", "claim"),
             attr("stage", stage.to_string()),
-            attr("address", info.sender),
+            attr("address", recipient),
             attr("amount", amount),
         ]);
+    if amount < original_amount {
+        res = res.add_attribute("slashed", original_amount - amount);
+    }
     Ok(res)
 }
 
+/// Returns the voting power of `address` and the total voting power at the snapshot height
+fn query_snapshot_power(
+    deps: Deps,
+    snapshot: &GovernanceSnapshot,
+    address: &Addr,
+) -> StdResult<(Uint128, Uint128)> {
+    let power: VotingPowerAtHeightResponse = deps.querier.query_wasm_smart(
+        &snapshot.staking_contract,
+        &DaoQuery::VotingPowerAtHeight {
+            address: address.to_string(),
+            height: Some(snapshot.snapshot_height),
+        },
+    )?;
+    let total: TotalPowerAtHeightResponse = deps.querier.query_wasm_smart(
+        &snapshot.staking_contract,
+        &DaoQuery::TotalPowerAtHeight {
+            height: Some(snapshot.snapshot_height),
+        },
+    )?;
+    Ok((power.power, total.power))
+}
+
 // create a message to be dispatched
 fn transfer_msg(rcpt: &Addr, amount: Uint128, vesting: Option<Curve>) -> Cw20ExecuteMsg {
     match vesting {
@@ -256,6 +514,8 @@ fn transfer_msg(rcpt: &Addr, amount: Uint128, vesting: Option<Curve>) -> Cw20Exe
             recipient: rcpt.to_string(),
             amount,
             schedule: v,
+            memo: None,
+            notify: false,
         },
         None => Cw20ExecuteMsg::Transfer {
             recipient: rcpt.to_string(),
@@ -281,11 +541,7 @@ pub fn execute_clawback(
     recipient: Option<String>,
 ) -> Result<Response, ContractError> {
     // authorize owner
-    let cfg = CONFIG.load(deps.storage)?;
-    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
-    if info.sender != owner {
-        return Err(ContractError::Unauthorized {});
-    }
+    let cfg = assert_owner(deps.as_ref(), &info.sender)?;
 
     // make sure is expired
     let StageDetails {
@@ -316,6 +572,32 @@ pub fn execute_clawback(
         },
     };
 
+    let (title, body) = match &recipient {
+        Some(rcpt) => (
+            format!("Airdrop stage {} clawed back", stage),
+            format!(
+                "{} unclaimed tokens of airdrop stage {} were sent to {}",
+                balance_to_burn, stage, rcpt
+            ),
+        ),
+        None => (
+            format!("Airdrop stage {} burned", stage),
+            format!(
+                "{} unclaimed tokens of airdrop stage {} were burned",
+                balance_to_burn, stage
+            ),
+        ),
+    };
+    let record = record_decision(
+        &cfg,
+        RecordMsg {
+            title,
+            body,
+            url: None,
+            hash: Some(MERKLE_ROOT.load(deps.storage, stage)?),
+        },
+    )?;
+
     // Burn the tokens and response
     let mut res = match recipient {
         Some(rcpt) => Response::new()
@@ -330,6 +612,7 @@ pub fn execute_clawback(
             funds: vec![],
             msg: to_binary(&msg)?,
         })
+        .add_submessages(record)
         .add_attributes(vec![
             attr("stage", stage.to_string()),
             attr("address", info.sender),
@@ -339,8 +622,144 @@ pub fn execute_clawback(
     Ok(res)
 }
 
+/// Loads the config and makes sure the sender is the owner
+fn assert_owner(deps: Deps, sender: &Addr) -> Result<Config, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    match &cfg.owner {
+        Some(owner) if owner == sender => Ok(cfg),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+pub fn execute_pause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let cfg = assert_owner(deps.as_ref(), &info.sender)?;
+
+    // pausing again must not restart the timelock clock
+    if PAUSED.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::Paused {});
+    }
+
+    let pause = PauseInfo {
+        paused_at: env.block.time,
+        emergency_timelock: cfg.emergency_timelock,
+    };
+    PAUSED.save(deps.storage, &pause)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "pause"),
+        attr("withdrawable_at", pause.withdrawable_at().to_string()),
+    ]))
+}
+
+pub fn execute_resume(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+
+    if PAUSED.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NotPaused {});
+    }
+    PAUSED.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "resume"))
+}
+
+pub fn execute_emergency_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let cfg = assert_owner(deps.as_ref(), &info.sender)?;
+
+    let pause = PAUSED
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NotPaused {})?;
+    let withdrawable_at = pause.withdrawable_at();
+    if env.block.time < withdrawable_at {
+        return Err(ContractError::TimelockNotExpired { withdrawable_at });
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    // sweep everything the contract holds, not only the unclaimed stage amounts
+    let BalanceResponse { balance } = deps.querier.query_wasm_smart(
+        &cfg.cw20_token_address,
+        &Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    if balance.is_zero() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+
+    let msg = transfer_msg(&recipient, balance, None);
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: cfg.cw20_token_address.to_string(),
+            funds: vec![],
+            msg: to_binary(&msg)?,
+        })
+        .add_attributes(vec![
+            attr("action", "emergency_withdraw"),
+            attr("recipient", recipient),
+            attr("amount", balance),
+        ]))
+}
+
+/// The claim deadline has passed once every registered stage is expired
+fn assert_claim_deadline_passed(deps: Deps, env: &Env) -> Result<(), ContractError> {
+    for item in STAGE_DETAILS.range(deps.storage, None, None, Order::Ascending) {
+        let (stage, StageDetails { expiration, .. }) = item?;
+        if !expiration.is_expired(&env.block) {
+            return Err(ContractError::StageNotExpired { stage, expiration });
+        }
+    }
+    Ok(())
+}
+
+pub fn execute_reclaim_airdrop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let cfg = assert_owner(deps.as_ref(), &info.sender)?;
+    assert_claim_deadline_passed(deps.as_ref(), &env)?;
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    let BalanceResponse { balance } = deps.querier.query_wasm_smart(
+        &cfg.cw20_token_address,
+        &Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    if balance.is_zero() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+
+    let reclaimed = RECLAIMED_AMOUNT.may_load(deps.storage)?.unwrap_or_default();
+    RECLAIMED_AMOUNT.save(deps.storage, &(reclaimed + balance))?;
+
+    let msg = transfer_msg(&recipient, balance, None);
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: cfg.cw20_token_address.to_string(),
+            funds: vec![],
+            msg: to_binary(&msg)?,
+        })
+        .add_attributes(vec![
+            attr("action", "reclaim"),
+            attr("recipient", recipient),
+            attr("amount", balance),
+        ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::MerkleRoot { stage } => to_binary(&query_merkle_root(deps, stage)?),
@@ -349,7 +768,81 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_is_claimed(deps, stage, address)?)
         }
         QueryMsg::TotalClaimed { stage } => to_binary(&query_total_claimed(deps, stage)?),
+        QueryMsg::PauseInfo {} => to_binary(&query_pause_info(deps)?),
+        QueryMsg::ReclaimedAmount {} => to_binary(&query_reclaimed_amount(deps)?),
+        QueryMsg::ClaimableAmount {
+            address,
+            stage,
+            amount,
+        } => to_binary(&query_claimable_amount(deps, env, address, stage, amount)?),
+        QueryMsg::ClaimableFromSnapshot { address } => {
+            to_binary(&query_claimable_from_snapshot(deps, env, address)?)
+        }
+    }
+}
+
+pub fn query_claimable_from_snapshot(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<ClaimableFromSnapshotResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let snapshot = config
+        .governance_snapshot
+        .ok_or_else(|| StdError::generic_err("Airdrop is not in governance snapshot mode"))?;
+    let address = deps.api.addr_validate(&address)?;
+    let stage = LATEST_STAGE.load(deps.storage)?;
+
+    let (power, total_power) = query_snapshot_power(deps, &snapshot, &address)?;
+    let claimable = match STAGE_AMOUNTS.may_load(deps.storage, stage)? {
+        Some(amounts)
+            if !power.is_zero() && CLAIM.may_load(deps.storage, (&address, stage))?.is_none() =>
+        {
+            let amount = amounts.total.multiply_ratio(power, total_power);
+            match config.slash_schedule {
+                Some(schedule) => schedule.effective_amount(amount, env.block.time),
+                None => amount,
+            }
+        }
+        _ => Uint128::zero(),
+    };
+
+    Ok(ClaimableFromSnapshotResponse {
+        stage,
+        power,
+        total_power,
+        claimable,
+    })
+}
+
+pub fn query_claimable_amount(
+    deps: Deps,
+    env: Env,
+    address: String,
+    stage: u8,
+    amount: Uint128,
+) -> StdResult<ClaimableAmountResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    if CLAIM.may_load(deps.storage, (&address, stage))?.is_some() {
+        return Ok(ClaimableAmountResponse {
+            claimable: Uint128::zero(),
+            slashed: Uint128::zero(),
+        });
     }
+
+    let claimable = match CONFIG.load(deps.storage)?.slash_schedule {
+        Some(schedule) => schedule.effective_amount(amount, env.block.time),
+        None => amount,
+    };
+    Ok(ClaimableAmountResponse {
+        claimable,
+        slashed: amount - claimable,
+    })
+}
+
+pub fn query_reclaimed_amount(deps: Deps) -> StdResult<ReclaimedAmountResponse> {
+    let reclaimed = RECLAIMED_AMOUNT.may_load(deps.storage)?.unwrap_or_default();
+    Ok(ReclaimedAmountResponse { reclaimed })
 }
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
@@ -357,6 +850,17 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(ConfigResponse {
         owner: cfg.owner.map(|o| o.to_string()),
         cw20_token_address: cfg.cw20_token_address.to_string(),
+        emergency_timelock: cfg.emergency_timelock,
+        expiration_grace_period: cfg.expiration_grace_period,
+        slash_schedule: cfg.slash_schedule,
+        airdrop_mode: match cfg.governance_snapshot {
+            Some(snapshot) => AirdropMode::GovernanceSnapshot {
+                snapshot_height: snapshot.snapshot_height,
+                staking_contract: snapshot.staking_contract.to_string(),
+            },
+            None => AirdropMode::Merkle {},
+        },
+        decisions_contract: cfg.decisions_contract.map(Addr::into_string),
     })
 }
 
@@ -404,6 +908,15 @@ pub fn query_total_claimed(deps: Deps, stage: u8) -> StdResult<TotalClaimedRespo
     Ok(resp)
 }
 
+pub fn query_pause_info(deps: Deps) -> StdResult<PauseInfoResponse> {
+    let pause = PAUSED.may_load(deps.storage)?;
+    Ok(PauseInfoResponse {
+        paused: pause.is_some(),
+        paused_at: pause.as_ref().map(|p| p.paused_at),
+        withdrawable_at: pause.as_ref().map(PauseInfo::withdrawable_at),
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let version = get_contract_version(deps.storage)?;
@@ -418,8 +931,13 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{from_binary, from_slice, CosmosMsg, SubMsg};
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        from_binary, from_slice, ContractResult, CosmosMsg, OwnedDeps, SubMsg, SystemResult,
+        WasmQuery,
+    };
     use serde::Deserialize;
 
     #[test]
@@ -429,6 +947,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "anchor0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -455,6 +978,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: None,
             cw20_token_address: "anchor0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -492,6 +1020,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "anchor0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -549,6 +1082,109 @@ mod tests {
         proofs: Vec<String>,
     }
 
+    const CLAIM_FOR_PUBKEY: &str =
+        "03709bf54e6be616ddaaba1950da5cd50ff23ca4e8c1ca064509c92ecc151e3cb7";
+    const CLAIM_FOR_RECIPIENT: &str = "wasm1yyyyvqkspl3aqm7p4tup9l2qy602urje68w365";
+    /// Merkle root of a tree with the single leaf `CLAIM_FOR_RECIPIENT` claiming 1000
+    const CLAIM_FOR_ROOT: &str = "d5447458a867f26cd32e5ea493f2349e475086493fa3c71ba534e11965de2114";
+    /// Signature of the claim of 1000 in stage 1 by the private key of `CLAIM_FOR_PUBKEY`
+    const CLAIM_FOR_SIGNATURE: &str = "20c0c6d32cd6e7f4d106e91f023f926e58b2381f8a00dbf35b8c0120a7c332fe22f4c3bf537d9f3a95af0d786d364389b42651e9dbebcf698e2a6e9b23bced24";
+    /// Signature of the same payload by another key
+    const CLAIM_FOR_OTHER_SIGNATURE: &str = "03bcea708bf8924c1b10d6cd158c1827ff12d2380769ec287fdcaf98dfa82fc61d8058c8ffcec56b4b8f6f8de53e409cfcd937c46bcea0958433f754277792fd";
+
+    #[test]
+    fn claim_for() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+        let msg = ExecuteMsg::default_merkle_root(CLAIM_FOR_ROOT.to_string());
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        assert_eq!(
+            claim_for_payload(
+                &mock_env().contract.address,
+                1,
+                &Addr::unchecked(CLAIM_FOR_RECIPIENT),
+                Uint128::new(1000)
+            ),
+            format!("claim_for:cosmos2contract:1:{}:1000", CLAIM_FOR_RECIPIENT)
+        );
+        let claim_for = |amount: u128, pubkey: &str, signature: &str| ExecuteMsg::ClaimFor {
+            stage: 1,
+            recipient: CLAIM_FOR_RECIPIENT.to_string(),
+            amount: Uint128::new(amount),
+            proof: vec![],
+            pubkey: Binary::from(hex::decode(pubkey).unwrap()),
+            signature: Binary::from(hex::decode(signature).unwrap()),
+        };
+        let relayer = mock_info("relayer", &[]);
+
+        // signed by another key
+        let msg = claim_for(1000, CLAIM_FOR_PUBKEY, CLAIM_FOR_OTHER_SIGNATURE);
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSignature {});
+        // signed for another amount
+        let msg = claim_for(999, CLAIM_FOR_PUBKEY, CLAIM_FOR_SIGNATURE);
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSignature {});
+        // public key of somebody else
+        let other_pubkey = "02".to_string() + &CLAIM_FOR_PUBKEY[2..];
+        let msg = claim_for(1000, &other_pubkey, CLAIM_FOR_SIGNATURE);
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::PubkeyMismatch {});
+
+        // the relayer claims, the recipient gets the tokens
+        let msg = claim_for(1000, CLAIM_FOR_PUBKEY, CLAIM_FOR_SIGNATURE);
+        let res = execute(deps.as_mut(), mock_env(), relayer.clone(), msg.clone()).unwrap();
+        let expected = SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: CLAIM_FOR_RECIPIENT.to_string(),
+                amount: Uint128::new(1000),
+            })
+            .unwrap(),
+        }));
+        assert_eq!(res.messages, vec![expected]);
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "claim"),
+                attr("stage", "1"),
+                attr("address", CLAIM_FOR_RECIPIENT),
+                attr("amount", "1000"),
+                attr("relayer", "relayer"),
+            ]
+        );
+        let is_claimed = query_is_claimed(deps.as_ref(), 1, CLAIM_FOR_RECIPIENT.to_string());
+        assert!(is_claimed.unwrap().is_claimed);
+
+        // the signature cannot be replayed, neither can the recipient claim again
+        let err = execute(deps.as_mut(), mock_env(), relayer, msg).unwrap_err();
+        assert_eq!(err, ContractError::Claimed {});
+        let msg = ExecuteMsg::Claim {
+            stage: 1,
+            amount: Uint128::new(1000),
+            proof: vec![],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CLAIM_FOR_RECIPIENT, &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Claimed {});
+    }
+
     #[test]
     fn claim() {
         // Run test 1
@@ -558,6 +1194,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -656,17 +1297,124 @@ mod tests {
         assert_eq!(claimed.claimed, test_data.amount);
     }
 
-    const TEST_DATA_1_MULTI: &[u8] =
-        include_bytes!("../testdata/airdrop_stage_1_test_multi_data.json");
+    #[test]
+    fn late_claims_are_slashed() {
+        let mut deps = mock_dependencies();
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+        let now = mock_env().block.time.seconds();
+        let day = 24 * 60 * 60;
+
+        // slashing must not exceed 100%
+        let mut slash_schedule = SlashSchedule {
+            slash_start: now - 3 * day - 1,
+            slash_pct_per_day: Decimal::percent(10),
+            max_slash_pct: Decimal::percent(101),
+        };
+        let init_msg = |slash_schedule: &SlashSchedule| InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: Some(slash_schedule.clone()),
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
+        };
+        let info = mock_info("addr0000", &[]);
+        let msg = init_msg(&slash_schedule);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSlashSchedule {});
 
-    #[derive(Deserialize, Debug)]
-    struct Proof {
-        account: String,
-        amount: Uint128,
-        proofs: Vec<String>,
-    }
+        slash_schedule.max_slash_pct = Decimal::percent(25);
+        let msg = init_msg(&slash_schedule);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(config.slash_schedule, Some(slash_schedule.clone()));
 
-    #[derive(Deserialize, Debug)]
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::default_merkle_root(test_data.root);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // 3 full days late would be 30%, but it is capped at 25%
+        let expected = test_data.amount * Decimal::percent(75);
+        let claimable = |deps: Deps| {
+            query_claimable_amount(
+                deps,
+                mock_env(),
+                test_data.account.clone(),
+                1,
+                test_data.amount,
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            claimable(deps.as_ref()),
+            ClaimableAmountResponse {
+                claimable: expected,
+                slashed: test_data.amount - expected,
+            }
+        );
+
+        // less slashed two days earlier
+        let mut env = mock_env();
+        env.block.time = env.block.time.minus_seconds(2 * day);
+        assert_eq!(
+            slash_schedule.effective_amount(test_data.amount, env.block.time),
+            test_data.amount * Decimal::percent(90)
+        );
+        // nothing slashed before the soft deadline
+        env.block.time = env.block.time.minus_seconds(day);
+        assert_eq!(
+            slash_schedule.effective_amount(test_data.amount, env.block.time),
+            test_data.amount
+        );
+
+        let msg = ExecuteMsg::Claim {
+            amount: test_data.amount,
+            stage: 1u8,
+            proof: test_data.proofs.clone(),
+        };
+        let info = mock_info(test_data.account.as_str(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let transfer = SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: test_data.account.clone(),
+                amount: expected,
+            })
+            .unwrap(),
+        }));
+        assert_eq!(res.messages, vec![transfer]);
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "claim"),
+                attr("stage", "1"),
+                attr("address", test_data.account.clone()),
+                attr("amount", expected),
+                attr("slashed", test_data.amount - expected),
+            ]
+        );
+
+        // only the slashed amount counts as claimed
+        let claimed = query_total_claimed(deps.as_ref(), 1).unwrap();
+        assert_eq!(claimed.claimed, expected);
+
+        // nothing left to claim
+        assert_eq!(claimable(deps.as_ref()).claimable, Uint128::zero());
+    }
+
+    const TEST_DATA_1_MULTI: &[u8] =
+        include_bytes!("../testdata/airdrop_stage_1_test_multi_data.json");
+
+    #[derive(Deserialize, Debug)]
+    struct Proof {
+        account: String,
+        amount: Uint128,
+        proofs: Vec<String>,
+    }
+
+    #[derive(Deserialize, Debug)]
     struct MultipleData {
         total_amount: Uint128,
         total_claimed_amount: Uint128,
@@ -674,6 +1422,70 @@ mod tests {
         accounts: Vec<Proof>,
     }
 
+    #[test]
+    fn claim_with_base64_encoding() {
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+        let to_base64 = |hex_hash: &str| Binary::from(hex::decode(hex_hash).unwrap()).to_base64();
+        let base64_proofs: Vec<String> = test_data.proofs.iter().map(|p| to_base64(p)).collect();
+
+        // root registered in either encoding, claimed with proofs in either encoding
+        for (root, proofs) in [
+            (test_data.root.clone(), base64_proofs.clone()),
+            (to_base64(&test_data.root), test_data.proofs.clone()),
+            (to_base64(&test_data.root), base64_proofs),
+        ] {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                owner: Some("owner0000".to_string()),
+                cw20_token_address: "token0000".to_string(),
+                emergency_timelock: None,
+                slash_schedule: None,
+                airdrop_mode: AirdropMode::Merkle {},
+                decisions_contract: None,
+                expiration_grace_period: None,
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+            let msg = ExecuteMsg::default_merkle_root(root);
+            let info = mock_info("owner0000", &[]);
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            // root is always stored hex-encoded
+            let merkle_root = query_merkle_root(deps.as_ref(), 1).unwrap();
+            assert_eq!(merkle_root.merkle_root, test_data.root);
+
+            let msg = ExecuteMsg::Claim {
+                amount: test_data.amount,
+                stage: 1u8,
+                proof: proofs,
+            };
+            let info = mock_info(test_data.account.as_str(), &[]);
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let is_claimed = query_is_claimed(deps.as_ref(), 1, test_data.account.clone()).unwrap();
+            assert!(is_claimed.is_claimed);
+        }
+    }
+
+    #[test]
+    fn register_invalid_merkle_root() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+        // base64 of 16 bytes is too short for a root
+        let msg = ExecuteMsg::default_merkle_root("AAAAAAAAAAAAAAAAAAAAAA==");
+        let info = mock_info("owner0000", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::WrongLength {});
+    }
+
     #[test]
     fn multiple_claim() {
         // Run test 1
@@ -683,6 +1495,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -748,6 +1565,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -786,6 +1608,8 @@ mod tests {
                     recipient: account.account.clone(),
                     amount: account.amount,
                     schedule: Curve::saturating_linear((start, account.amount.u128()), (end, 0)),
+                    memo: None,
+                    notify: false,
                 })
                 .unwrap(),
             }));
@@ -816,6 +1640,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -829,7 +1658,7 @@ mod tests {
             "5d4f48f147cb6cb742b376dce5626b2a036f69faec10cd73631c791780e150fc",
             123000000,
             Some(Expiration::AtHeight(100)),
-            None,
+            Some(Scheduled::AtHeight(50)),
             None,
         );
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
@@ -851,6 +1680,193 @@ mod tests {
         )
     }
 
+    fn instantiate_with_grace_period(
+        grace_period: Option<u64>,
+    ) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: grace_period,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+        deps
+    }
+
+    #[test]
+    fn stage_expiration_variants() {
+        let mut deps = instantiate_with_grace_period(None);
+        let env = mock_env();
+        let owner = mock_info("owner0000", &[]);
+        let root = "5d4f48f147cb6cb742b376dce5626b2a036f69faec10cd73631c791780e150fc";
+
+        let at_height = Expiration::AtHeight(env.block.height + 100);
+        let at_time = Expiration::AtTime(env.block.time.plus_seconds(500));
+        for (expiration, start) in [
+            (at_height, Scheduled::AtHeight(env.block.height)),
+            (at_time, Scheduled::AtTime(env.block.time)),
+            (Expiration::Never {}, Scheduled::AtHeight(env.block.height)),
+        ] {
+            let msg =
+                ExecuteMsg::register_merkle_root(root, 1000, Some(expiration), Some(start), None);
+            execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+        }
+
+        let claim = |stage| ExecuteMsg::Claim {
+            amount: Uint128::new(5),
+            stage,
+            proof: vec![],
+        };
+        let info = mock_info("addr0000", &[]);
+
+        // nothing expired yet
+        for stage in 1..=3 {
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), claim(stage)).unwrap_err();
+            assert!(!matches!(err, ContractError::StageExpired { .. }));
+        }
+
+        // height based expiration passed
+        let mut env = mock_env();
+        env.block.height += 100;
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), claim(1)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::StageExpired {
+                stage: 1,
+                expiration: at_height
+            }
+        );
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), claim(2)).unwrap_err();
+        assert!(!matches!(err, ContractError::StageExpired { .. }));
+
+        // time based expiration passed
+        env.block.time = env.block.time.plus_seconds(500);
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), claim(2)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::StageExpired {
+                stage: 2,
+                expiration: at_time
+            }
+        );
+
+        // never expires
+        env.block.height += 1_000_000;
+        env.block.time = env.block.time.plus_seconds(1_000_000_000);
+        let err = execute(deps.as_mut(), env, info, claim(3)).unwrap_err();
+        assert!(!matches!(err, ContractError::StageExpired { .. }));
+    }
+
+    #[test]
+    fn stage_expiration_derived_from_schedule() {
+        let grace_period = 7 * 24 * 60 * 60;
+        let mut deps = instantiate_with_grace_period(Some(grace_period));
+        let env = mock_env();
+        let owner = mock_info("owner0000", &[]);
+        let root = "5d4f48f147cb6cb742b376dce5626b2a036f69faec10cd73631c791780e150fc";
+
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(config.expiration_grace_period, grace_period);
+
+        // expires the grace period after the end of the vesting schedule
+        let now = env.block.time.seconds();
+        let vesting = ScalableCurve::linear((now + 100, 100), (now + 1000, 0));
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: root.to_string(),
+            expiration: None,
+            start: Scheduled::AtHeight(env.block.height),
+            total_amount: Uint128::new(1000),
+            vesting: Some(vesting),
+        };
+        execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+        let res = query_merkle_root(deps.as_ref(), 1).unwrap();
+        assert_eq!(
+            res.expiration,
+            Expiration::AtTime(Timestamp::from_seconds(now + 1000 + grace_period))
+        );
+
+        // without a vesting schedule, the grace period starts with the stage
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: root.to_string(),
+            expiration: None,
+            start: Scheduled::AtTime(env.block.time),
+            total_amount: Uint128::new(1000),
+            vesting: None,
+        };
+        execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+        let res = query_merkle_root(deps.as_ref(), 2).unwrap();
+        assert_eq!(
+            res.expiration,
+            Expiration::AtTime(env.block.time.plus_seconds(grace_period))
+        );
+
+        // nothing to derive the expiration from
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: root.to_string(),
+            expiration: None,
+            start: Scheduled::AtHeight(env.block.height),
+            total_amount: Uint128::new(1000),
+            vesting: None,
+        };
+        let err = execute(deps.as_mut(), env, owner, msg).unwrap_err();
+        assert_eq!(err, ContractError::MissingExpiration {});
+    }
+
+    #[test]
+    fn stage_expiration_before_start() {
+        let mut deps = instantiate_with_grace_period(None);
+        let env = mock_env();
+        let owner = mock_info("owner0000", &[]);
+        let root = "5d4f48f147cb6cb742b376dce5626b2a036f69faec10cd73631c791780e150fc";
+
+        let start = Scheduled::AtHeight(env.block.height);
+        let expiration = Expiration::AtHeight(env.block.height - 1);
+        let msg = ExecuteMsg::register_merkle_root(root, 1000, Some(expiration), Some(start), None);
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ExpirationBeforeStart { expiration, start }
+        );
+
+        let start = Scheduled::AtTime(env.block.time);
+        let expiration = Expiration::AtTime(env.block.time.minus_seconds(1));
+        let msg = ExecuteMsg::register_merkle_root(root, 1000, Some(expiration), Some(start), None);
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ExpirationBeforeStart { expiration, start }
+        );
+
+        // the vesting schedule must begin before the expiration as well
+        let now = env.block.time.seconds();
+        let vesting = ScalableCurve::linear((now + 100, 100), (now + 1000, 0));
+        let expiration = Expiration::AtTime(Timestamp::from_seconds(now + 50));
+        let msg = ExecuteMsg::register_merkle_root(
+            root,
+            1000,
+            Some(expiration),
+            Some(Scheduled::AtHeight(env.block.height)),
+            Some(vesting),
+        );
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ExpirationBeforeStart {
+                expiration,
+                start: Scheduled::AtTime(Timestamp::from_seconds(now + 100))
+            }
+        );
+
+        // expiring exactly at the start is allowed
+        let expiration = Expiration::AtHeight(env.block.height);
+        let msg = ExecuteMsg::register_merkle_root(root, 1000, Some(expiration), None, None);
+        execute(deps.as_mut(), env, owner, msg).unwrap();
+    }
+
     #[test]
     fn cant_burn() {
         let mut deps = mock_dependencies();
@@ -858,6 +1874,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -897,6 +1918,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let mut env = mock_env();
@@ -906,7 +1932,7 @@ mod tests {
         let info = mock_info("owner0000", &[]);
         let msg = ExecuteMsg::RegisterMerkleRoot {
             merkle_root: test_data.root,
-            expiration: Expiration::AtHeight(12500),
+            expiration: Some(Expiration::AtHeight(12500)),
             start: ExecuteMsg::default_start(),
             total_amount: Uint128::new(10000),
             vesting: None,
@@ -981,6 +2007,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
         let info = mock_info("addr0000", &[]);
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -988,7 +2019,7 @@ mod tests {
         let info = mock_info("owner0000", &[]);
         let msg = ExecuteMsg::RegisterMerkleRoot {
             merkle_root: test_data.root,
-            expiration: Expiration::AtHeight(12500),
+            expiration: Some(Expiration::AtHeight(12500)),
             start: ExecuteMsg::default_start(),
             total_amount: Uint128::new(10000),
             vesting: None,
@@ -1050,6 +2081,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
         let info = mock_info("addr0000", &[]);
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1059,7 +2095,7 @@ mod tests {
         let info = mock_info("owner0000", &[]);
         let msg = ExecuteMsg::RegisterMerkleRoot {
             merkle_root: test_data.root,
-            expiration: Expiration::AtHeight(12500),
+            expiration: Some(Expiration::AtHeight(12500)),
             start: ExecuteMsg::default_start(),
             total_amount: Uint128::new(10000),
             vesting: Some(vesting),
@@ -1098,6 +2134,8 @@ mod tests {
                 amount: Uint128::new(10000),
                 // 80% to 0% as defined in the curve on register_merkle_root
                 schedule: Curve::saturating_linear((start, 8000), (end, 0)),
+                memo: None,
+                notify: false,
             })
             .unwrap(),
         }));
@@ -1122,6 +2160,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -1164,6 +2207,11 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
         };
 
         let env = mock_env();
@@ -1214,4 +2262,442 @@ mod tests {
         let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
         assert_eq!(res, ContractError::Unauthorized {});
     }
+
+    // instantiates the contract holding 5000 tokens and pauses it right away
+    fn setup_paused_contract(timelock: u64) -> (OwnedDeps<MockStorage, MockApi, MockQuerier>, Env) {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(
+                to_binary(&BalanceResponse {
+                    balance: Uint128::new(5000),
+                })
+                .unwrap(),
+            ))
+        });
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+            emergency_timelock: Some(timelock),
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
+        };
+        let env = mock_env();
+        let info = mock_info("addr0000", &[]);
+        instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let info = mock_info("owner0000", &[]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Pause {}).unwrap();
+        (deps, env)
+    }
+
+    #[test]
+    fn pause_blocks_claims() {
+        let mut deps = mock_dependencies();
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
+        };
+        let env = mock_env();
+        let info = mock_info("addr0000", &[]);
+        instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap().emergency_timelock,
+            DEFAULT_EMERGENCY_TIMELOCK
+        );
+
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::default_merkle_root(test_data.root);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // only owner can pause
+        let info = mock_info("addr0000", &[]);
+        let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Pause {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let info = mock_info("owner0000", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+        // cannot pause twice
+        let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Pause {}).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+
+        let claim = ExecuteMsg::Claim {
+            amount: test_data.amount,
+            stage: 1u8,
+            proof: test_data.proofs,
+        };
+        let info = mock_info(test_data.account.as_str(), &[]);
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), claim.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+
+        // only owner can resume
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::Resume {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let owner = mock_info("owner0000", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            ExecuteMsg::Resume {},
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), env.clone(), owner, ExecuteMsg::Resume {}).unwrap_err();
+        assert_eq!(err, ContractError::NotPaused {});
+
+        // claims work again
+        execute(deps.as_mut(), env, info, claim).unwrap();
+        assert!(
+            query_is_claimed(deps.as_ref(), 1, test_data.account)
+                .unwrap()
+                .is_claimed
+        );
+    }
+
+    #[test]
+    fn reclaim_only_after_claim_deadline() {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(
+                to_binary(&BalanceResponse {
+                    balance: Uint128::new(7000),
+                })
+                .unwrap(),
+            ))
+        });
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::Merkle {},
+            decisions_contract: None,
+            expiration_grace_period: None,
+        };
+        let mut env = mock_env();
+        instantiate(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+        // two stages expiring at different times
+        let owner = mock_info("owner0000", &[]);
+        let first = Expiration::AtHeight(env.block.height + 100);
+        let second = Expiration::AtHeight(env.block.height + 200);
+        for expiration in [first, second] {
+            let msg = ExecuteMsg::register_merkle_root(
+                &test_data.root,
+                ExecuteMsg::default_total(),
+                Some(expiration),
+                None,
+                None,
+            );
+            execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+        }
+
+        let msg = ExecuteMsg::ReclaimUnclaimed {
+            recipient: "treasury0000".to_string(),
+        };
+
+        // not possible while any stage can still be claimed
+        env.block.height += 100;
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::StageNotExpired {
+                stage: 2,
+                expiration: second
+            }
+        );
+
+        env.block.height += 100;
+        // only owner can reclaim
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr0000", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(deps.as_mut(), env, owner, msg).unwrap();
+        let expected = SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury0000".to_string(),
+                amount: Uint128::new(7000),
+            })
+            .unwrap(),
+        }));
+        assert_eq!(res.messages, vec![expected]);
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "reclaim"),
+                attr("recipient", "treasury0000"),
+                attr("amount", "7000"),
+            ]
+        );
+        assert_eq!(
+            query_reclaimed_amount(deps.as_ref()).unwrap().reclaimed,
+            Uint128::new(7000)
+        );
+    }
+
+    #[test]
+    fn emergency_withdraw_after_timelock() {
+        let timelock = 3600;
+        let (mut deps, mut env) = setup_paused_contract(timelock);
+        let paused_at = env.block.time;
+
+        let info = PauseInfo {
+            paused_at,
+            emergency_timelock: timelock,
+        };
+        assert_eq!(
+            query_pause_info(deps.as_ref()).unwrap(),
+            PauseInfoResponse {
+                paused: true,
+                paused_at: Some(paused_at),
+                withdrawable_at: Some(info.withdrawable_at()),
+            }
+        );
+
+        let msg = ExecuteMsg::EmergencyWithdraw {
+            recipient: "rescue0000".to_string(),
+        };
+
+        // too early
+        env.block.time = paused_at.plus_seconds(timelock - 1);
+        let owner = mock_info("owner0000", &[]);
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TimelockNotExpired {
+                withdrawable_at: paused_at.plus_seconds(timelock)
+            }
+        );
+
+        env.block.time = paused_at.plus_seconds(timelock);
+        // only owner can withdraw
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr0000", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(deps.as_mut(), env, owner, msg).unwrap();
+        let expected = SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "rescue0000".to_string(),
+                amount: Uint128::new(5000),
+            })
+            .unwrap(),
+        }));
+        assert_eq!(res.messages, vec![expected]);
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "emergency_withdraw"),
+                attr("recipient", "rescue0000"),
+                attr("amount", "5000"),
+            ]
+        );
+    }
+
+    #[test]
+    fn resume_resets_timelock() {
+        let timelock = 3600;
+        let (mut deps, mut env) = setup_paused_contract(timelock);
+        let first_pause = env.block.time;
+        let owner = mock_info("owner0000", &[]);
+
+        env.block.time = first_pause.plus_seconds(100);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            ExecuteMsg::Resume {},
+        )
+        .unwrap();
+        assert_eq!(
+            query_pause_info(deps.as_ref()).unwrap(),
+            PauseInfoResponse {
+                paused: false,
+                paused_at: None,
+                withdrawable_at: None,
+            }
+        );
+
+        let msg = ExecuteMsg::EmergencyWithdraw {
+            recipient: "rescue0000".to_string(),
+        };
+        // not possible while running
+        env.block.time = first_pause.plus_seconds(timelock);
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NotPaused {});
+
+        // pausing again starts a fresh clock
+        let second_pause = first_pause.plus_seconds(200);
+        env.block.time = second_pause;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+
+        env.block.time = first_pause.plus_seconds(timelock + 1);
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TimelockNotExpired {
+                withdrawable_at: second_pause.plus_seconds(timelock)
+            }
+        );
+
+        env.block.time = second_pause.plus_seconds(timelock);
+        execute(deps.as_mut(), env, owner, msg).unwrap();
+    }
+
+    #[test]
+    fn governance_snapshot_claims() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let snapshot_height = env.block.height - 100;
+        // "alice" has 30% of the power, everyone else nothing
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "staking0000" => {
+                let res = match from_slice(msg).unwrap() {
+                    DaoQuery::VotingPowerAtHeight { address, height } => {
+                        assert_eq!(height, Some(snapshot_height));
+                        let power = if address == "alice" { 300u128 } else { 0 };
+                        to_binary(&VotingPowerAtHeightResponse {
+                            power: Uint128::new(power),
+                            height: snapshot_height,
+                        })
+                    }
+                    DaoQuery::TotalPowerAtHeight { .. } => to_binary(&TotalPowerAtHeightResponse {
+                        power: Uint128::new(1000),
+                        height: snapshot_height,
+                    }),
+                    _ => unimplemented!(),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => unimplemented!(),
+        });
+
+        let init_msg = |snapshot_height| InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+            emergency_timelock: None,
+            slash_schedule: None,
+            airdrop_mode: AirdropMode::GovernanceSnapshot {
+                snapshot_height,
+                staking_contract: "staking0000".to_string(),
+            },
+            decisions_contract: None,
+            expiration_grace_period: None,
+        };
+        // snapshot must be in the past
+        let info = mock_info("addr0000", &[]);
+        let msg = init_msg(env.block.height);
+        let err = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidSnapshotHeight {
+                height: env.block.height
+            }
+        );
+        let msg = init_msg(snapshot_height);
+        let mode = msg.airdrop_mode.clone();
+        instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(query_config(deps.as_ref()).unwrap().airdrop_mode, mode);
+
+        // the root is not used for claims, but still needed to register the stage
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+        let msg = ExecuteMsg::register_merkle_root(test_data.root, 50_000, None, None, None);
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let claimable =
+            query_claimable_from_snapshot(deps.as_ref(), env.clone(), "alice".to_string()).unwrap();
+        assert_eq!(
+            claimable,
+            ClaimableFromSnapshotResponse {
+                stage: 1,
+                power: Uint128::new(300),
+                total_power: Uint128::new(1000),
+                claimable: Uint128::new(15_000),
+            }
+        );
+
+        // amount and proof are ignored
+        let claim = ExecuteMsg::Claim {
+            stage: 1,
+            amount: Uint128::new(50_000),
+            proof: vec![],
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bob", &[]),
+            claim.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NoSnapshotPower {
+                height: snapshot_height
+            }
+        );
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim).unwrap();
+        let expected = SubMsg::new(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "alice".to_string(),
+                amount: Uint128::new(15_000),
+            })
+            .unwrap(),
+        });
+        assert_eq!(res.messages, vec![expected]);
+
+        let claimable =
+            query_claimable_from_snapshot(deps.as_ref(), env, "alice".to_string()).unwrap();
+        assert_eq!(claimable.claimable, Uint128::zero());
+        assert_eq!(
+            query_total_claimed(deps.as_ref(), 1).unwrap().claimed,
+            Uint128::new(15_000)
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{OverflowError, StdError};
+use cosmwasm_std::{OverflowError, StdError, Timestamp};
 use cw_utils::{Expiration, Scheduled};
 use hex::FromHexError;
 use thiserror::Error;
@@ -36,11 +36,50 @@ pub enum ContractError {
     #[error("Airdrop stage {stage} expired at {expiration}")]
     StageExpired { stage: u8, expiration: Expiration },
 
+    #[error("Stage expiration {expiration} is before its start {start}")]
+    ExpirationBeforeStart {
+        expiration: Expiration,
+        start: Scheduled,
+    },
+
+    #[error("Expiration must be set for stages without a time based schedule")]
+    MissingExpiration {},
+
     #[error("Airdrop stage {stage} not expired yet")]
     StageNotExpired { stage: u8, expiration: Expiration },
 
     #[error("Airdrop stage {stage} begins at {start}")]
     StageNotBegun { stage: u8, start: Scheduled },
+
+    #[error("Claims are paused")]
+    Paused {},
+
+    #[error("Claims are not paused")]
+    NotPaused {},
+
+    #[error("Emergency withdrawal not possible before {withdrawable_at}")]
+    TimelockNotExpired { withdrawable_at: Timestamp },
+
+    #[error("No tokens left to withdraw")]
+    NothingToWithdraw {},
+
+    #[error("Slash percentages must not exceed 100%")]
+    InvalidSlashSchedule {},
+
+    #[error("No voting power at snapshot height {height}")]
+    NoSnapshotPower { height: u64 },
+
+    #[error("Snapshot height {height} is not in the past")]
+    InvalidSnapshotHeight { height: u64 },
+
+    #[error("Public key does not belong to the recipient")]
+    PubkeyMismatch {},
+
+    #[error("Invalid claim signature")]
+    InvalidSignature {},
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
 }
 
 impl From<OverflowError> for ContractError {
@@ -19,6 +19,9 @@ pub mod contract;
 /// custom error handler
 mod error;
 
+/// merkle proof verification
+pub mod merkle;
+
 /// custom input output messages
 pub mod msg;
 
@@ -26,3 +29,6 @@ pub mod msg;
 pub mod state;
 
 pub use crate::error::ContractError;
+
+#[cfg(test)]
+mod multitest;
@@ -0,0 +1,111 @@
+use cosmwasm_std::Binary;
+use sha2::Digest;
+use std::convert::TryInto;
+
+use crate::error::ContractError;
+
+/// Length of a hex encoded sha256 hash
+const HEX_HASH_LEN: usize = 64;
+
+/// Decodes a sha256 hash, which can be either hex or base64 encoded.
+/// The encoding is detected by the length of the input, as they differ for 32 bytes.
+pub fn decode_hash(encoded: &str) -> Result<[u8; 32], ContractError> {
+    let mut buf = [0; 32];
+    if encoded.len() == HEX_HASH_LEN {
+        hex::decode_to_slice(encoded, &mut buf)?;
+    } else {
+        buf = Binary::from_base64(encoded)?
+            .as_slice()
+            .try_into()
+            .map_err(|_| ContractError::WrongLength {})?;
+    }
+    Ok(buf)
+}
+
+/// Verifies that the proof leads from the leaf to the given root.
+/// Each proof element can be either hex or base64 encoded.
+pub fn verify_proof(root: &[u8; 32], leaf: &str, proof: &[String]) -> Result<(), ContractError> {
+    let hash = sha2::Sha256::digest(leaf.as_bytes())
+        .as_slice()
+        .try_into()
+        .map_err(|_| ContractError::WrongLength {})?;
+
+    let hash = proof.iter().try_fold(hash, |hash, p| {
+        let mut hashes = [hash, decode_hash(p)?];
+        hashes.sort_unstable();
+        sha2::Sha256::digest(&hashes.concat())
+            .as_slice()
+            .try_into()
+            .map_err(|_| ContractError::WrongLength {})
+    })?;
+
+    if root != &hash {
+        return Err(ContractError::VerificationFailed {});
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEAF: &str = "wasm1k9hwzxs889jpvd7env8z49gad3a3633vg350tq100";
+    const ROOT_HEX: &str = "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88";
+    const ROOT_BASE64: &str = "tFweoosmrbE+QSkzyeBVsB/fdYUwSwDNjxyyIKpsXog=";
+    const PROOF_HEX: [&str; 3] = [
+        "a714186eaedddde26b08b9afda38cf62fdf88d68e3aa0d5a4b55033487fe14a1",
+        "fb57090a813128eeb953a4210dd64ee73d2632b8158231effe2f0a18b2d3b5dd",
+        "c30992d264c74c58b636a31098c6c27a5fc08b3f61b7eafe2a33dcb445822343",
+    ];
+    const PROOF_BASE64: [&str; 3] = [
+        "pxQYbq7d3eJrCLmv2jjPYv34jWjjqg1aS1UDNIf+FKE=",
+        "+1cJCoExKO65U6QhDdZO5z0mMrgVgjHv/i8KGLLTtd0=",
+        "wwmS0mTHTFi2NqMQmMbCel/Aiz9ht+r+KjPctEWCI0M=",
+    ];
+
+    fn proof(encoded: &[&str]) -> Vec<String> {
+        encoded.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn decode_hash_works_with_both_encodings() {
+        let root = decode_hash(ROOT_HEX).unwrap();
+        assert_eq!(hex::encode(root), ROOT_HEX);
+        assert_eq!(decode_hash(ROOT_BASE64).unwrap(), root);
+    }
+
+    #[test]
+    fn decode_hash_rejects_wrong_length() {
+        // hex of 31 bytes is detected as (invalid) base64
+        let short_hex = &ROOT_HEX[..62];
+        assert!(decode_hash(short_hex).is_err());
+        // valid base64, but only 16 bytes
+        let err = decode_hash("AAAAAAAAAAAAAAAAAAAAAA==").unwrap_err();
+        assert_eq!(err, ContractError::WrongLength {});
+        // right length, but not hex
+        let err = decode_hash(&"x".repeat(64)).unwrap_err();
+        assert!(matches!(err, ContractError::Hex(_)));
+    }
+
+    #[test]
+    fn verify_proof_works_with_both_encodings() {
+        let root = decode_hash(ROOT_HEX).unwrap();
+        verify_proof(&root, LEAF, &proof(&PROOF_HEX)).unwrap();
+        verify_proof(&root, LEAF, &proof(&PROOF_BASE64)).unwrap();
+        // encodings can even be mixed
+        let mixed = [PROOF_HEX[0], PROOF_BASE64[1], PROOF_HEX[2]];
+        verify_proof(&root, LEAF, &proof(&mixed)).unwrap();
+    }
+
+    #[test]
+    fn verify_proof_fails_on_wrong_leaf() {
+        let root = decode_hash(ROOT_BASE64).unwrap();
+        let err = verify_proof(
+            &root,
+            "wasm1k9hwzxs889jpvd7env8z49gad3a3633vg350tq101",
+            &proof(&PROOF_BASE64),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::VerificationFailed {});
+    }
+}
@@ -1,15 +1,53 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Binary, Timestamp, Uint128};
 use cw_utils::{Expiration, Scheduled};
 use wynd_utils::ScalableCurve;
 
+use crate::state::SlashSchedule;
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct InstantiateMsg {
     /// Owner if none set to info.sender.
     pub owner: Option<String>,
     pub cw20_token_address: String,
+    /// Seconds the contract must stay paused before an emergency withdrawal
+    /// is possible. Defaults to 48 hours.
+    #[serde(default)]
+    pub emergency_timelock: Option<u64>,
+    /// Seconds after the end of the vesting schedule at which stages registered
+    /// without an expiration expire. Defaults to 30 days.
+    #[serde(default)]
+    pub expiration_grace_period: Option<u64>,
+    /// Reduce the amounts of claims made after a soft deadline
+    #[serde(default)]
+    pub slash_schedule: Option<SlashSchedule>,
+    /// How the allocation of each address is determined. Defaults to merkle proofs.
+    #[serde(default)]
+    pub airdrop_mode: AirdropMode,
+    /// Decisions contract to record stage registrations and clawbacks in
+    #[serde(default)]
+    pub decisions_contract: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AirdropMode {
+    /// Every claim must provide a merkle proof for its amount
+    Merkle {},
+    /// Retroactive airdrop. Every stage is split between the stakers proportionally to
+    /// their voting power at `snapshot_height`. Merkle proofs are not verified.
+    GovernanceSnapshot {
+        snapshot_height: u64,
+        staking_contract: String,
+    },
+}
+
+impl Default for AirdropMode {
+    fn default() -> Self {
+        AirdropMode::Merkle {}
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -21,35 +59,92 @@ pub enum ExecuteMsg {
         new_owner: Option<String>,
     },
     RegisterMerkleRoot {
-        /// MerkleRoot is hex-encoded merkle root.
+        /// MerkleRoot is hex or base64 encoded merkle root. It is stored hex-encoded.
         merkle_root: String,
-        expiration: Expiration,
+        /// Must not be before `start`. If not set, the stage expires the configured
+        /// grace period after the end of its vesting schedule.
+        #[serde(default)]
+        expiration: Option<Expiration>,
         start: Scheduled,
         total_amount: Uint128,
         vesting: Option<ScalableCurve>,
     },
     /// Claim does not check if contract has enough funds, owner must ensure it.
+    /// If a slash schedule is configured, late claims receive a reduced amount.
+    /// In governance snapshot mode, `amount` and `proof` are ignored and the amount
+    /// is calculated from the voting power of the sender.
     Claim {
         stage: u8,
         amount: Uint128,
-        /// Proof is hex-encoded merkle proof.
+        /// Proof is merkle proof, each element either hex or base64 encoded.
         proof: Vec<String>,
     },
+    /// Claim on behalf of `recipient`, e.g. by a relayer paying the gas. The tokens always go to
+    /// `recipient`, who authorizes the claim with a secp256k1 signature.
+    ///
+    /// `pubkey` is the compressed public key of the recipient, its bech32 address (with the
+    /// prefix of `recipient`) must be `recipient`. `signature` is the 64 byte (r, s) signature
+    /// over the SHA-256 hash of the UTF-8 string
+    /// `claim_for:{airdrop contract address}:{stage}:{recipient}:{amount}`,
+    /// e.g. `claim_for:wasm1airdrop...:1:wasm1recipient...:1000`.
+    ClaimFor {
+        stage: u8,
+        recipient: String,
+        amount: Uint128,
+        /// Proof is merkle proof, each element either hex or base64 encoded.
+        proof: Vec<String>,
+        pubkey: Binary,
+        signature: Binary,
+    },
     /// Burn the remaining tokens after expire time (only owner)
     Burn { stage: u8 },
     /// Recycle the remaining tokens to specified address after expire time (only owner).
     /// Don't use Option<String> to avoid typo turning ClawBack into Burn
     ClawBack { stage: u8, recipient: String },
+    /// Stop all claims until resumed (only owner)
+    Pause {},
+    /// Allow claims again (only owner). This resets the emergency timelock.
+    Resume {},
+    /// Send the whole token balance of the contract to recipient (only owner).
+    /// Only possible once the contract has been paused for the full timelock.
+    EmergencyWithdraw { recipient: String },
+    /// Send the whole token balance of the contract to recipient (only owner).
+    /// Only possible once every registered stage has expired.
+    ReclaimUnclaimed { recipient: String },
+    /// Sets the decisions contract stage changes are recorded in, None stops recording
+    /// (only owner)
+    UpdateDecisionsContract { address: Option<String> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
-    MerkleRoot { stage: u8 },
+    MerkleRoot {
+        stage: u8,
+    },
     LatestStage {},
-    IsClaimed { stage: u8, address: String },
-    TotalClaimed { stage: u8 },
+    IsClaimed {
+        stage: u8,
+        address: String,
+    },
+    TotalClaimed {
+        stage: u8,
+    },
+    PauseInfo {},
+    ReclaimedAmount {},
+    /// Amount the address would currently receive when claiming `amount` in the given stage,
+    /// after applying the slash schedule. The proof is not verified.
+    ClaimableAmount {
+        address: String,
+        stage: u8,
+        amount: Uint128,
+    },
+    /// Amount the address would currently receive when claiming the latest stage in
+    /// governance snapshot mode. Fails in merkle mode.
+    ClaimableFromSnapshot {
+        address: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
@@ -57,6 +152,11 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     pub owner: Option<String>,
     pub cw20_token_address: String,
+    pub emergency_timelock: u64,
+    pub expiration_grace_period: u64,
+    pub slash_schedule: Option<SlashSchedule>,
+    pub airdrop_mode: AirdropMode,
+    pub decisions_contract: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -87,6 +187,41 @@ pub struct TotalClaimedResponse {
     pub claimed: Uint128,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PauseInfoResponse {
+    pub paused: bool,
+    /// Start of the current pause, if any
+    pub paused_at: Option<Timestamp>,
+    /// Earliest time an emergency withdrawal can be executed, if paused
+    pub withdrawable_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ReclaimedAmountResponse {
+    /// Total amount reclaimed after the claim deadline
+    pub reclaimed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ClaimableAmountResponse {
+    /// Amount that would be transferred on claim, zero if already claimed
+    pub claimable: Uint128,
+    /// Amount cut from the original allocation by the slash schedule
+    pub slashed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ClaimableFromSnapshotResponse {
+    /// Stage the claimable amount refers to
+    pub stage: u8,
+    /// Voting power of the address at the snapshot height
+    pub power: Uint128,
+    /// Total voting power at the snapshot height
+    pub total_power: Uint128,
+    /// Amount that would be transferred on claim, zero if already claimed
+    pub claimable: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct MigrateMsg {}
 
@@ -118,7 +253,7 @@ impl ExecuteMsg {
     ) -> Self {
         ExecuteMsg::RegisterMerkleRoot {
             merkle_root: root.into(),
-            expiration: expiration.unwrap_or(Expiration::Never {}),
+            expiration: Some(expiration.unwrap_or(Expiration::Never {})),
             start: start.unwrap_or_else(Self::default_start),
             total_amount: Uint128::new(amount),
             vesting,
@@ -0,0 +1,49 @@
+use cosmwasm_std::Event;
+use cw_utils::Expiration;
+
+use super::suite::SuiteBuilder;
+
+const MERKLE_ROOT: &str = "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37";
+
+#[test]
+fn stage_registration_and_clawback_are_recorded() {
+    let mut suite = SuiteBuilder::new().build();
+
+    suite
+        .register_merkle_root(MERKLE_ROOT, 100_000, Expiration::AtHeight(12_400))
+        .unwrap();
+    let decisions = suite.query_decisions().unwrap();
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].title, "Airdrop stage 1 registered");
+    assert_eq!(
+        decisions[0].body,
+        format!(
+            "Airdrop stage 1 with merkle root {} distributes 100000 tokens until {}",
+            MERKLE_ROOT,
+            Expiration::AtHeight(12_400)
+        )
+    );
+    assert_eq!(decisions[0].hash.as_deref(), Some(MERKLE_ROOT));
+
+    suite.advance_blocks(10_000);
+    suite.clawback(1, "treasury").unwrap();
+    let decisions = suite.query_decisions().unwrap();
+    assert_eq!(decisions.len(), 2);
+    assert_eq!(decisions[1].title, "Airdrop stage 1 clawed back");
+    assert_eq!(
+        decisions[1].body,
+        "100000 unclaimed tokens of airdrop stage 1 were sent to treasury"
+    );
+}
+
+#[test]
+fn failed_record_does_not_block_airdrop() {
+    // the decisions contract only accepts records of its owner
+    let mut suite = SuiteBuilder::new().with_foreign_decisions_owner().build();
+
+    let resp = suite
+        .register_merkle_root(MERKLE_ROOT, 100_000, Expiration::Never {})
+        .unwrap();
+    resp.assert_event(&Event::new("wasm").add_attribute("warning", "decision_not_recorded"));
+    assert_eq!(suite.query_decisions().unwrap(), []);
+}
@@ -0,0 +1,265 @@
+use anyhow::Result as AnyResult;
+
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
+use cw20_vesting::{
+    ExecuteMsg as VestingExecuteMsg, InitBalance, InstantiateMsg as VestingInstantiateMsg,
+    MinterInfo,
+};
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use cw_utils::{Expiration, Scheduled};
+use wynd_decisions::msg::{
+    DecisionResponse, InstantiateMsg as DecisionsInstantiateMsg, ListDecisionsResponse,
+    QueryMsg as DecisionsQueryMsg,
+};
+use wynd_stake::msg::{InstantiateMsg as StakeInstantiateMsg, StakeConfig};
+
+use crate::msg::{AirdropMode, ExecuteMsg, InstantiateMsg};
+
+pub const OWNER: &str = "owner";
+pub const AIRDROP_BALANCE: u128 = 1_000_000;
+
+fn contract_airdrop() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply);
+
+    Box::new(contract)
+}
+
+fn contract_decisions() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        wynd_decisions::contract::execute,
+        wynd_decisions::contract::instantiate,
+        wynd_decisions::contract::query,
+    );
+
+    Box::new(contract)
+}
+
+fn contract_stake() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        wynd_stake::contract::execute,
+        wynd_stake::contract::instantiate,
+        wynd_stake::contract::query,
+    )
+    .with_reply(wynd_stake::contract::reply);
+
+    Box::new(contract)
+}
+
+fn contract_vesting() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        cw20_vesting::contract::execute,
+        cw20_vesting::contract::instantiate,
+        cw20_vesting::contract::query,
+    );
+
+    Box::new(contract)
+}
+
+#[derive(Debug, Default)]
+pub struct SuiteBuilder {
+    /// The airdrop contract does not own the decisions contract
+    foreign_decisions_owner: bool,
+}
+
+impl SuiteBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_foreign_decisions_owner(mut self) -> Self {
+        self.foreign_decisions_owner = true;
+        self
+    }
+
+    #[track_caller]
+    pub fn build(self) -> Suite {
+        let mut app = App::default();
+        let admin = Addr::unchecked("admin");
+
+        let vesting_id = app.store_code(contract_vesting());
+        let vesting_contract = app
+            .instantiate_contract(
+                vesting_id,
+                admin.clone(),
+                &VestingInstantiateMsg {
+                    name: "vesting".to_owned(),
+                    symbol: "VEST".to_owned(),
+                    decimals: 9,
+                    initial_balances: vec![InitBalance {
+                        address: OWNER.to_owned(),
+                        amount: Uint128::new(AIRDROP_BALANCE),
+                        vesting: None,
+                        memo: None,
+                        relative_vesting: None,
+                    }],
+                    mint: Some(MinterInfo {
+                        minter: "minter".to_owned(),
+                        cap: None,
+                        burns_reduce_cap: false,
+                    }),
+                    marketing: None,
+                    allowed_vesters: None,
+                    max_curve_complexity: 10,
+                    seeding_open: false,
+                },
+                &[],
+                "vesting",
+                None,
+            )
+            .unwrap();
+
+        let stake_id = app.store_code(contract_stake());
+        let stake_contract = app
+            .instantiate_contract(
+                stake_id,
+                admin.clone(),
+                &StakeInstantiateMsg {
+                    cw20_contract: vesting_contract.to_string(),
+                    tokens_per_power: Uint128::new(1),
+                    min_bond: Uint128::new(1),
+                    stake_config: vec![StakeConfig {
+                        unbonding_period: 1_000,
+                        voting_multiplier: Decimal::one(),
+                        reward_multiplier: Decimal::one(),
+                        exit_vesting: None,
+                    }],
+                    admin: None,
+                    max_hooks: None,
+                    distribution_fee: None,
+                    vesting_voting_discount: None,
+                },
+                &[],
+                "stake",
+                None,
+            )
+            .unwrap();
+
+        let airdrop_id = app.store_code(contract_airdrop());
+        let airdrop_contract = app
+            .instantiate_contract(
+                airdrop_id,
+                admin.clone(),
+                &InstantiateMsg {
+                    owner: Some(OWNER.to_owned()),
+                    cw20_token_address: vesting_contract.to_string(),
+                    emergency_timelock: None,
+                    slash_schedule: None,
+                    airdrop_mode: AirdropMode::Merkle {},
+                    decisions_contract: None,
+                    expiration_grace_period: None,
+                },
+                &[],
+                "airdrop",
+                None,
+            )
+            .unwrap();
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            vesting_contract.clone(),
+            &VestingExecuteMsg::Transfer {
+                recipient: airdrop_contract.to_string(),
+                amount: Uint128::new(AIRDROP_BALANCE),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let decisions_owner = if self.foreign_decisions_owner {
+            OWNER.to_owned()
+        } else {
+            airdrop_contract.to_string()
+        };
+        let decisions_id = app.store_code(contract_decisions());
+        let decisions_contract = app
+            .instantiate_contract(
+                decisions_id,
+                admin,
+                &DecisionsInstantiateMsg {
+                    owner: decisions_owner,
+                    staking_contract: stake_contract.to_string(),
+                },
+                &[],
+                "decisions",
+                None,
+            )
+            .unwrap();
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            airdrop_contract.clone(),
+            &ExecuteMsg::UpdateDecisionsContract {
+                address: Some(decisions_contract.to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+
+        Suite {
+            app,
+            airdrop_contract,
+            decisions_contract,
+        }
+    }
+}
+
+pub struct Suite {
+    pub app: App,
+    airdrop_contract: Addr,
+    decisions_contract: Addr,
+}
+
+impl Suite {
+    pub fn register_merkle_root(
+        &mut self,
+        merkle_root: &str,
+        total_amount: u128,
+        expiration: Expiration,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(OWNER),
+            self.airdrop_contract.clone(),
+            &ExecuteMsg::RegisterMerkleRoot {
+                merkle_root: merkle_root.to_owned(),
+                expiration: Some(expiration),
+                start: Scheduled::AtHeight(0),
+                total_amount: total_amount.into(),
+                vesting: None,
+            },
+            &[],
+        )
+    }
+
+    pub fn clawback(&mut self, stage: u8, recipient: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(OWNER),
+            self.airdrop_contract.clone(),
+            &ExecuteMsg::ClawBack {
+                stage,
+                recipient: recipient.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn advance_blocks(&mut self, blocks: u64) {
+        self.app.update_block(|block| {
+            block.height += blocks;
+            block.time = block.time.plus_seconds(5 * blocks);
+        });
+    }
+
+    pub fn query_decisions(&self) -> AnyResult<Vec<DecisionResponse>> {
+        let resp: ListDecisionsResponse = self.app.wrap().query_wasm_smart(
+            self.decisions_contract.clone(),
+            &DecisionsQueryMsg::ListDecisions {
+                start_after: None,
+                limit: None,
+            },
+        )?;
+        Ok(resp.decisions)
+    }
+}
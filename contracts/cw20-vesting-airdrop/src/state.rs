@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
 use cw_utils::{Expiration, Scheduled};
 use wynd_utils::ScalableCurve;
@@ -11,11 +11,101 @@ pub struct Config {
     /// Owner If None set, contract is frozen.
     pub owner: Option<Addr>,
     pub cw20_token_address: Addr,
+    /// How many seconds the contract must stay paused before the owner can
+    /// sweep the remaining tokens with an emergency withdrawal.
+    #[serde(default = "default_emergency_timelock")]
+    pub emergency_timelock: u64,
+    /// Seconds after the end of its vesting schedule at which a stage registered
+    /// without an explicit expiration expires.
+    #[serde(default = "default_expiration_grace_period")]
+    pub expiration_grace_period: u64,
+    /// Reduces the claimable amounts of late claimers, if set
+    #[serde(default)]
+    pub slash_schedule: Option<SlashSchedule>,
+    /// If set, allocations are derived from the voting power at a past height
+    /// instead of merkle proofs
+    #[serde(default)]
+    pub governance_snapshot: Option<GovernanceSnapshot>,
+    /// If set, registered, burned and clawed back stages are recorded in this decisions contract.
+    /// This contract has to be its owner.
+    #[serde(default)]
+    pub decisions_contract: Option<Addr>,
+}
+
+/// Distributes each stage proportionally to the voting power on a staking contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct GovernanceSnapshot {
+    /// Height at which the voting power is taken
+    pub snapshot_height: u64,
+    /// Contract implementing the `VotingPowerAtHeight` and `TotalPowerAtHeight` queries
+    pub staking_contract: Addr,
+}
+
+/// Reduces unclaimed amounts by a percentage for each full day past a soft deadline
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SlashSchedule {
+    /// Time in seconds after which slashing begins
+    pub slash_start: u64,
+    /// Percentage slashed for each full day past `slash_start`
+    pub slash_pct_per_day: Decimal,
+    /// Upper limit of the total slash
+    pub max_slash_pct: Decimal,
+}
+
+impl SlashSchedule {
+    /// Percentage of the original amount that is slashed at the given time
+    pub fn slash_pct(&self, time: Timestamp) -> Decimal {
+        let days_late = time.seconds().saturating_sub(self.slash_start) / SECONDS_PER_DAY;
+        // the daily percentage is at most 100%, so capping the days avoids overflows
+        let pct =
+            self.slash_pct_per_day * Decimal::from_ratio(days_late.min(u32::MAX as u64), 1u64);
+        pct.min(self.max_slash_pct)
+    }
+
+    /// Amount that can be claimed at the given time, instead of `amount`
+    pub fn effective_amount(&self, amount: Uint128, time: Timestamp) -> Uint128 {
+        amount * (Decimal::one() - self.slash_pct(time))
+    }
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// 48 hours
+pub const DEFAULT_EMERGENCY_TIMELOCK: u64 = 48 * 60 * 60;
+
+fn default_emergency_timelock() -> u64 {
+    DEFAULT_EMERGENCY_TIMELOCK
+}
+
+/// 30 days
+pub const DEFAULT_EXPIRATION_GRACE_PERIOD: u64 = 30 * SECONDS_PER_DAY;
+
+fn default_expiration_grace_period() -> u64 {
+    DEFAULT_EXPIRATION_GRACE_PERIOD
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const LATEST_STAGE: Item<u8> = Item::new("latest_stage");
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PauseInfo {
+    /// When the contract was paused. Resuming clears it, so this is the start of
+    /// the current uninterrupted pause.
+    pub paused_at: Timestamp,
+    /// Timelock in force when the pause started
+    pub emergency_timelock: u64,
+}
+
+impl PauseInfo {
+    /// Earliest time at which an emergency withdrawal can be executed
+    pub fn withdrawable_at(&self) -> Timestamp {
+        self.paused_at.plus_seconds(self.emergency_timelock)
+    }
+}
+
+/// Present only while claims are paused
+pub const PAUSED: Item<PauseInfo> = Item::new("paused");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StageDetails {
     pub expiration: Expiration,
@@ -29,6 +119,9 @@ pub struct StageAmounts {
     pub claimed: Uint128,
 }
 
+/// Total amount sent back to the treasury with `ReclaimUnclaimed`
+pub const RECLAIMED_AMOUNT: Item<Uint128> = Item::new("reclaimed_amount");
+
 pub const STAGE_DETAILS: Map<u8, StageDetails> = Map::new("stage_details");
 pub const STAGE_AMOUNTS: Map<u8, StageAmounts> = Map::new("stage_amounts");
 
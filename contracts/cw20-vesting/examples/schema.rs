@@ -8,8 +8,11 @@ use cw20::{
     DownloadLogoResponse, MarketingInfoResponse, TokenInfoResponse,
 };
 use cw20_vesting::msg::{
-    DelegatedResponse, ExecuteMsg, InstantiateMsg, MinterResponse, QueryMsg,
-    StakingAddressResponse, VestingAllowListResponse, VestingResponse,
+    CirculatingSupplyResponse, DelegateEligibleResponse, DelegatedResponse, ExecuteMsg,
+    InstantiateMsg, LogoUpdaterResponse, MinterResponse, PermitNonceResponse, QueryMsg,
+    ReservationsResponse, SpendingLimitStatusResponse, StakingAddressResponse, SupplyInfoResponse,
+    TotalSupplyResponse, UndelegatableAmountResponse, VestingAllowListResponse,
+    VestingBatchResponse, VestingHistoryResponse, VestingResponse, VestingUnlockTimeResponse,
 };
 
 fn main() {
@@ -29,9 +32,21 @@ fn main() {
     export_schema(&schema_for!(AllAccountsResponse), &out_dir);
     export_schema(&schema_for!(VestingResponse), &out_dir);
     export_schema(&schema_for!(DelegatedResponse), &out_dir);
+    export_schema(&schema_for!(DelegateEligibleResponse), &out_dir);
     export_schema(&schema_for!(VestingAllowListResponse), &out_dir);
     export_schema(&schema_for!(StakingAddressResponse), &out_dir);
+    export_schema(&schema_for!(UndelegatableAmountResponse), &out_dir);
     export_schema(&schema_for!(MinterResponse), &out_dir);
+    export_schema(&schema_for!(TotalSupplyResponse), &out_dir);
+    export_schema(&schema_for!(CirculatingSupplyResponse), &out_dir);
+    export_schema(&schema_for!(SupplyInfoResponse), &out_dir);
+    export_schema(&schema_for!(PermitNonceResponse), &out_dir);
+    export_schema(&schema_for!(ReservationsResponse), &out_dir);
+    export_schema(&schema_for!(VestingHistoryResponse), &out_dir);
+    export_schema(&schema_for!(VestingUnlockTimeResponse), &out_dir);
+    export_schema(&schema_for!(VestingBatchResponse), &out_dir);
+    export_schema(&schema_for!(SpendingLimitStatusResponse), &out_dir);
+    export_schema(&schema_for!(LogoUpdaterResponse), &out_dir);
 
     export_schema(&schema_for!(MarketingInfoResponse), &out_dir);
     export_schema(&schema_for!(DownloadLogoResponse), &out_dir);
@@ -4,8 +4,14 @@ use cosmwasm_std::{
 };
 use cw20::{AllowanceResponse, Cw20ReceiveMsg, Expiration};
 
+use cw_storage_plus::Map;
+
+use crate::contract::{delegate, supply_change_event};
 use crate::error::ContractError;
-use crate::state::{deduct_coins, ALLOWANCES, BALANCES, TOKEN_INFO};
+use crate::state::{
+    apply_spending_limit, deduct_coins, ALLOWANCES, BALANCES, DELEGATION_ALLOWANCES, TOKEN_INFO,
+    TOTAL_SUPPLY_HISTORY,
+};
 
 pub fn execute_increase_allowance(
     deps: DepsMut,
@@ -20,17 +26,12 @@ pub fn execute_increase_allowance(
         return Err(ContractError::CannotSetOwnAccount {});
     }
 
-    ALLOWANCES.update(
+    increase(
         deps.storage,
+        &ALLOWANCES,
         (&info.sender, &spender_addr),
-        |allow| -> StdResult<_> {
-            let mut val = allow.unwrap_or_default();
-            if let Some(exp) = expires {
-                val.expires = exp;
-            }
-            val.allowance += amount;
-            Ok(val)
-        },
+        amount,
+        expires,
     )?;
 
     let res = Response::new().add_attributes(vec![
@@ -55,9 +56,110 @@ pub fn execute_decrease_allowance(
         return Err(ContractError::CannotSetOwnAccount {});
     }
 
-    let key = (&info.sender, &spender_addr);
+    decrease(
+        deps.storage,
+        &ALLOWANCES,
+        (&info.sender, &spender_addr),
+        amount,
+        expires,
+    )?;
+
+    let res = Response::new().add_attributes(vec![
+        attr("action", "decrease_allowance"),
+        attr("owner", info.sender),
+        attr("spender", spender),
+        attr("amount", amount),
+    ]);
+    Ok(res)
+}
+
+pub fn execute_increase_delegation_allowance(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if spender_addr == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+
+    increase(
+        deps.storage,
+        &DELEGATION_ALLOWANCES,
+        (&info.sender, &spender_addr),
+        amount,
+        expires,
+    )?;
+
+    let res = Response::new().add_attributes(vec![
+        attr("action", "increase_delegation_allowance"),
+        attr("owner", info.sender),
+        attr("spender", spender),
+        attr("amount", amount),
+    ]);
+    Ok(res)
+}
+
+pub fn execute_decrease_delegation_allowance(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if spender_addr == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+
+    decrease(
+        deps.storage,
+        &DELEGATION_ALLOWANCES,
+        (&info.sender, &spender_addr),
+        amount,
+        expires,
+    )?;
+
+    let res = Response::new().add_attributes(vec![
+        attr("action", "decrease_delegation_allowance"),
+        attr("owner", info.sender),
+        attr("spender", spender),
+        attr("amount", amount),
+    ]);
+    Ok(res)
+}
+
+pub(crate) fn increase<'a>(
+    storage: &mut dyn Storage,
+    allowances: &Map<'a, (&'a Addr, &'a Addr), AllowanceResponse>,
+    key: (&'a Addr, &'a Addr),
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> StdResult<()> {
+    allowances.update(storage, key, |allow| -> StdResult<_> {
+        let mut val = allow.unwrap_or_default();
+        if let Some(exp) = expires {
+            val.expires = exp;
+        }
+        val.allowance += amount;
+        Ok(val)
+    })?;
+    Ok(())
+}
+
+fn decrease<'a>(
+    storage: &mut dyn Storage,
+    allowances: &Map<'a, (&'a Addr, &'a Addr), AllowanceResponse>,
+    key: (&'a Addr, &'a Addr),
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> StdResult<()> {
     // load value and delete if it hits 0, or update otherwise
-    let mut allowance = ALLOWANCES.load(deps.storage, key)?;
+    let mut allowance = allowances.load(storage, key)?;
     if amount < allowance.allowance {
         // update the new amount
         allowance.allowance = allowance
@@ -67,18 +169,11 @@ pub fn execute_decrease_allowance(
         if let Some(exp) = expires {
             allowance.expires = exp;
         }
-        ALLOWANCES.save(deps.storage, key, &allowance)?;
+        allowances.save(storage, key, &allowance)?;
     } else {
-        ALLOWANCES.remove(deps.storage, key);
+        allowances.remove(storage, key);
     }
-
-    let res = Response::new().add_attributes(vec![
-        attr("action", "decrease_allowance"),
-        attr("owner", info.sender),
-        attr("spender", spender),
-        attr("amount", amount),
-    ]);
-    Ok(res)
+    Ok(())
 }
 
 // this can be used to update a lower allowance - call bucket.update with proper keys
@@ -89,7 +184,34 @@ pub fn deduct_allowance(
     block: &BlockInfo,
     amount: Uint128,
 ) -> Result<AllowanceResponse, ContractError> {
-    ALLOWANCES.update(storage, (owner, spender), |current| {
+    deduct(storage, &ALLOWANCES, (owner, spender), block, amount)
+}
+
+/// Same as `deduct_allowance`, but for the separate delegation allowances
+pub fn deduct_delegation_allowance(
+    storage: &mut dyn Storage,
+    owner: &Addr,
+    spender: &Addr,
+    block: &BlockInfo,
+    amount: Uint128,
+) -> Result<AllowanceResponse, ContractError> {
+    deduct(
+        storage,
+        &DELEGATION_ALLOWANCES,
+        (owner, spender),
+        block,
+        amount,
+    )
+}
+
+fn deduct<'a>(
+    storage: &mut dyn Storage,
+    allowances: &Map<'a, (&'a Addr, &'a Addr), AllowanceResponse>,
+    key: (&'a Addr, &'a Addr),
+    block: &BlockInfo,
+    amount: Uint128,
+) -> Result<AllowanceResponse, ContractError> {
+    allowances.update(storage, key, |current| {
         match current {
             Some(mut a) => {
                 if a.expires.is_expired(block) {
@@ -121,12 +243,15 @@ pub fn execute_transfer_from(
 
     // deduct allowance before doing anything else have enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
+    // spenders move the tokens of the owner, so they count against the owner's limit
+    apply_spending_limit(deps.storage, &env, &owner_addr, amount)?;
 
     // this will handle vesting checks as well
     deduct_coins(deps.storage, &env, &owner_addr, amount)?;
     BALANCES.update(
         deps.storage,
         &rcpt_addr,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
@@ -155,18 +280,27 @@ pub fn execute_burn_from(
 
     // this will handle vesting checks as well
     deduct_coins(deps.storage, &env, &owner_addr, amount)?;
-    // reduce total_supply
-    TOKEN_INFO.update(deps.storage, |mut meta| -> StdResult<_> {
-        meta.total_supply = meta.total_supply.checked_sub(amount)?;
-        Ok(meta)
-    })?;
-
-    let res = Response::new().add_attributes(vec![
-        attr("action", "burn_from"),
-        attr("from", owner),
-        attr("by", info.sender),
-        attr("amount", amount),
-    ]);
+    // reduce total_supply (and cap, if configured)
+    let mut meta = TOKEN_INFO.load(deps.storage)?;
+    let supply_before = meta.total_supply;
+    meta.burn(amount)?;
+    TOKEN_INFO.save(deps.storage, &meta)?;
+    TOTAL_SUPPLY_HISTORY.save(deps.storage, &meta.total_supply, env.block.height)?;
+
+    let res = Response::new()
+        .add_event(supply_change_event(
+            "burn_from",
+            amount,
+            supply_before,
+            meta.total_supply,
+        ))
+        .add_attributes(vec![
+            attr("action", "burn_from"),
+            attr("from", owner),
+            attr("by", info.sender),
+            attr("amount", amount),
+            attr("total_supply", meta.total_supply),
+        ]);
     Ok(res)
 }
 
@@ -184,12 +318,15 @@ pub fn execute_send_from(
 
     // deduct allowance before doing anything else have enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
+    // spenders move the tokens of the owner, so they count against the owner's limit
+    apply_spending_limit(deps.storage, &env, &owner_addr, amount)?;
 
     // this will handle vesting checks as well
     deduct_coins(deps.storage, &env, &owner_addr, amount)?;
     BALANCES.update(
         deps.storage,
         &rcpt_addr,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
@@ -213,6 +350,31 @@ pub fn execute_send_from(
     Ok(res)
 }
 
+pub fn execute_delegate_from(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    amount: Uint128,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+
+    // deduct allowance before doing anything else have enough allowance
+    deduct_delegation_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
+
+    let (token_address, msg) = delegate(deps.storage, &env, &owner_addr, amount, msg)?;
+
+    let res = Response::new().add_message(msg).add_attributes(vec![
+        attr("action", "delegate_from"),
+        attr("from", owner),
+        attr("to", token_address),
+        attr("by", info.sender),
+        attr("amount", amount),
+    ]);
+    Ok(res)
+}
+
 pub fn query_allowance(deps: Deps, owner: String, spender: String) -> StdResult<AllowanceResponse> {
     let owner_addr = deps.api.addr_validate(&owner)?;
     let spender_addr = deps.api.addr_validate(&spender)?;
@@ -222,6 +384,19 @@ pub fn query_allowance(deps: Deps, owner: String, spender: String) -> StdResult<
     Ok(allowance)
 }
 
+pub fn query_delegation_allowance(
+    deps: Deps,
+    owner: String,
+    spender: String,
+) -> StdResult<AllowanceResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let allowance = DELEGATION_ALLOWANCES
+        .may_load(deps.storage, (&owner_addr, &spender_addr))?
+        .unwrap_or_default();
+    Ok(allowance)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,6 +407,8 @@ mod tests {
 
     use crate::contract::{execute, instantiate, query_balance, query_token_info};
     use crate::msg::{ExecuteMsg, InitBalance, InstantiateMsg};
+    use crate::state::{DELEGATED, STAKING};
+    use crate::Cw20ReceiveDelegationMsg;
 
     fn get_balance<T: Into<String>>(deps: Deps, address: T) -> Uint128 {
         query_balance(deps, address.into()).unwrap().balance
@@ -251,11 +428,14 @@ mod tests {
                 address: addr.into(),
                 amount,
                 vesting: None,
+                memo: None,
+                relative_vesting: None,
             }],
             mint: None,
             marketing: None,
             allowed_vesters: None,
             max_curve_complexity: 10,
+            seeding_open: false,
         };
         let info = mock_info("creator", &[]);
         let env = mock_env();
@@ -727,4 +907,176 @@ mod tests {
         let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
         assert_eq!(err, ContractError::Expired {});
     }
+
+    #[test]
+    fn delegate_from_consumes_delegation_allowance() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let owner = String::from("addr0001");
+        let spender = String::from("addr0002");
+        let staking = Addr::unchecked("staking");
+        let start = Uint128::new(10_000);
+        do_instantiate(deps.as_mut(), &owner, start);
+        STAKING.save(deps.as_mut().storage, &staking).unwrap();
+
+        // no allowance to start
+        let msg = ExecuteMsg::DelegateFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(1_000),
+            msg: Binary::default(),
+        };
+        let info = mock_info(spender.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NoAllowance {});
+
+        let msg2 = ExecuteMsg::IncreaseDelegationAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(3_000),
+            expires: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner.as_ref(), &[]),
+            msg2,
+        )
+        .unwrap();
+
+        // delegate some of it
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(
+                Cw20ReceiveDelegationMsg {
+                    sender: owner.clone(),
+                    amount: Uint128::new(1_000),
+                    msg: Binary::default(),
+                }
+                .into_cosmos_msg(&staking)
+                .unwrap()
+            )]
+        );
+        assert_eq!(get_balance(deps.as_ref(), &owner), Uint128::new(9_000));
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(1_000));
+        assert_eq!(
+            DELEGATED
+                .load(&deps.storage, &Addr::unchecked(&owner))
+                .unwrap(),
+            Uint128::new(1_000)
+        );
+        assert_eq!(
+            query_delegation_allowance(deps.as_ref(), owner.clone(), spender.clone())
+                .unwrap()
+                .allowance,
+            Uint128::new(2_000)
+        );
+
+        // cannot delegate more than allowed
+        let msg = ExecuteMsg::DelegateFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(2_001),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+
+        // but exactly the remaining allowance works
+        let msg = ExecuteMsg::DelegateFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(2_000),
+            msg: Binary::default(),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &owner), Uint128::new(7_000));
+        assert_eq!(
+            query_delegation_allowance(deps.as_ref(), owner.clone(), spender.clone())
+                .unwrap()
+                .allowance,
+            Uint128::zero()
+        );
+
+        // set an allowance expiring at the current block
+        let env = mock_env();
+        let msg = ExecuteMsg::IncreaseDelegationAllowance {
+            spender,
+            amount: Uint128::new(1_000),
+            expires: Some(Expiration::AtHeight(env.block.height)),
+        };
+        execute(deps.as_mut(), env, mock_info(owner.as_ref(), &[]), msg).unwrap();
+        let msg = ExecuteMsg::DelegateFrom {
+            owner,
+            amount: Uint128::new(500),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+    }
+
+    #[test]
+    fn delegation_allowances_isolated_from_transfer_allowances() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let owner = String::from("addr0001");
+        let spender = String::from("addr0002");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(10_000));
+        STAKING
+            .save(deps.as_mut().storage, &Addr::unchecked("staking"))
+            .unwrap();
+        let owner_info = mock_info(owner.as_ref(), &[]);
+        let spender_info = mock_info(spender.as_ref(), &[]);
+
+        // transfer allowance doesn't allow delegating
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(1_000),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), owner_info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::DelegateFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(1_000),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), spender_info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoAllowance {});
+        assert_eq!(
+            query_delegation_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap(),
+            AllowanceResponse::default()
+        );
+
+        // and delegation allowance doesn't allow transferring
+        let msg = ExecuteMsg::DecreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(1_000),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), owner_info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::IncreaseDelegationAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(1_000),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), owner_info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: spender.clone(),
+            amount: Uint128::new(1_000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), spender_info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NoAllowance {});
+        assert_eq!(
+            query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap(),
+            AllowanceResponse::default()
+        );
+
+        // decreasing delegation allowance fully removes it
+        let msg = ExecuteMsg::DecreaseDelegationAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(5_000),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), owner_info, msg).unwrap();
+        assert_eq!(
+            query_delegation_allowance(deps.as_ref(), owner, spender).unwrap(),
+            AllowanceResponse::default()
+        );
+    }
 }
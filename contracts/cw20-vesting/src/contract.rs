@@ -1,8 +1,9 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Uint128,
+    to_binary, Addr, Api, Binary, BlockInfo, ContractInfoResponse, CosmosMsg, Deps, DepsMut, Env,
+    Event, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg, SubMsgResult,
+    Uint128, WasmQuery,
 };
 
 use cw2::set_contract_version;
@@ -11,24 +12,42 @@ use cw20::{
     MarketingInfoResponse, TokenInfoResponse,
 };
 
+use cw_storage_plus::Bound;
 use cw_utils::ensure_from_older_version;
-use wynd_utils::Curve;
+use wynd_utils::{Curve, PiecewiseLinear, ScalableCurve};
 
 use crate::allowances::{
-    execute_burn_from, execute_decrease_allowance, execute_increase_allowance, execute_send_from,
-    execute_transfer_from, query_allowance,
+    execute_burn_from, execute_decrease_allowance, execute_decrease_delegation_allowance,
+    execute_delegate_from, execute_increase_allowance, execute_increase_delegation_allowance,
+    execute_send_from, execute_transfer_from, query_allowance, query_delegation_allowance,
+};
+use crate::enumerable::{
+    query_all_accounts, query_all_allowances, query_all_delegation_allowances, query_reservations,
+    query_reservations_by_spender, query_vesting_history,
 };
-use crate::enumerable::{query_all_accounts, query_all_allowances};
 use crate::error::ContractError;
 use crate::msg::{
-    assert_schedule_vests_amount, fully_vested, DelegatedResponse, ExecuteMsg, InitBalance,
-    InstantiateMsg, MaxVestingComplexityResponse, MigrateMsg, MinterResponse, QueryMsg,
-    StakingAddressResponse, VestingAllowListResponse, VestingResponse,
+    assert_schedule_vests_amount, assert_valid_schedule, fully_vested, fully_vested_at,
+    is_valid_name, is_valid_updated_symbol, validate_memo, BatchTransfer,
+    CirculatingSupplyResponse, ComplexityTierResponse, DelegateEligibleResponse, DelegatedResponse,
+    ExecuteMsg, InitBalance, InstantiateMsg, LogoUpdaterResponse, MaxVestingComplexityResponse,
+    MigrateMsg, MinterResponse, QueryMsg, SimulateTransferVestingResponse,
+    SpendingLimitStatusResponse, StakingAddressResponse, SupplyInfoResponse, TotalSupplyResponse,
+    UndelegatableAmountResponse, VestingAllowListResponse, VestingBatchEntry, VestingBatchResponse,
+    VestingResponse, VestingUnlockTimeResponse,
 };
-use crate::receive_delegate::Cw20ReceiveDelegationMsg;
+use crate::permit::{execute_permit, query_permit_nonce};
+use crate::receive_delegate::{Cw20ReceiveDelegationMsg, Cw20ReceiveVestingMsg};
+use crate::reservations::{execute_release_reserved, execute_reserve, execute_seize_reserved};
 use crate::state::{
-    deduct_coins, MinterData, TokenInfo, ALLOWLIST, BALANCES, DELEGATED, LOGO, MARKETING_INFO,
-    MAX_VESTING_COMPLEXITY, STAKING, TOKEN_INFO, VESTING,
+    active_reserved, apply_spending_limit, assert_vesting_policy, balance_lock, deduct_coins,
+    drop_past_steps, max_vesting_complexity, movable_amount, record_vesting_grant, remove_vesting,
+    reserved_total, save_delegated, save_vesting, spending_status, MinterData, ReconcileProgress,
+    SpendingLimit, TokenInfo, VestingGrant, VestingPolicy, ALLOWLIST, BALANCES, COMPLEXITY_TIERS,
+    DELEGATED, LARGEST_VESTING_SIZE, LOGO, LOGO_DOMAINS, LOGO_UPDATER, MARKETING_INFO,
+    MAX_VESTING_COMPLEXITY, POLICY_EXEMPT, RECONCILE_PROGRESS, SEEDER, SNAPSHOT_START_HEIGHT,
+    SPENDING_LIMIT, STAKING, TOKEN_INFO, TOTAL_DELEGATED, TOTAL_LOCKED, TOTAL_SUPPLY_HISTORY,
+    VESTING, VESTING_COUNT, VESTING_POLICY,
 };
 
 // version info for migration info
@@ -36,6 +55,14 @@ const CONTRACT_NAME: &str = "crates.io:cw20-vesting";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const LOGO_SIZE_CAP: usize = 5 * 1024;
+/// Maximum number of transfers in a single batch
+pub const MAX_BATCH_TRANSFERS: usize = 100;
+/// Maximum number of addresses in a single `ExecuteMsg::ClearExpiredVesting`
+pub const MAX_CLEAR_VESTING: usize = 50;
+/// Maximum number of delegations summed up by a single `ExecuteMsg::ReconcileDelegationState`
+pub const MAX_RECONCILE_DELEGATIONS: u32 = 500;
+/// Reply id of `Cw20ReceiveVestingMsg` notifications, which may fail without reverting
+const RECEIVE_VESTING_REPLY_ID: u64 = 1;
 
 /// Checks if data starts with XML preamble
 fn verify_xml_preamble(data: &[u8]) -> Result<(), ContractError> {
@@ -89,15 +116,55 @@ fn verify_png_logo(logo: &[u8]) -> Result<(), ContractError> {
     }
 }
 
+/// Returns the scheme and the lowercase host of the given url, if it has both
+fn parse_url(url: &str) -> Option<(String, String)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(&['/', '?', '#'][..]).next()?;
+    // anything before an `@` is user info, not the host
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    if scheme.is_empty() || host.is_empty() {
+        return None;
+    }
+    Some((scheme.to_ascii_lowercase(), host.to_ascii_lowercase()))
+}
+
+/// Checks the logo url points to one of the allowed domains, if any are set
+fn verify_logo_url(storage: &dyn Storage, url: &str) -> Result<(), ContractError> {
+    let domains = LOGO_DOMAINS.may_load(storage)?.unwrap_or_default();
+    if domains.is_empty() {
+        return Ok(());
+    }
+    match parse_url(url) {
+        Some((scheme, host)) if scheme == "https" && domains.contains(&host) => Ok(()),
+        Some((_, host)) => Err(ContractError::LogoDomainNotAllowed { host }),
+        None => Err(ContractError::LogoDomainNotAllowed {
+            host: url.to_owned(),
+        }),
+    }
+}
+
 /// Checks if passed logo is correct, and if not, returns an error
-fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
+fn verify_logo(storage: &dyn Storage, logo: &Logo) -> Result<(), ContractError> {
     match logo {
         Logo::Embedded(EmbeddedLogo::Svg(logo)) => verify_xml_logo(logo),
         Logo::Embedded(EmbeddedLogo::Png(logo)) => verify_png_logo(logo),
-        Logo::Url(_) => Ok(()), // Any reasonable url validation would be regex based, probably not worth it
+        Logo::Url(url) => verify_logo_url(storage, url),
     }
 }
 
+/// Normalizes the domains for comparison with parsed url hosts
+fn normalize_logo_domains(domains: Vec<String>) -> Vec<String> {
+    let mut domains: Vec<_> = domains
+        .into_iter()
+        .map(|domain| domain.trim().to_ascii_lowercase())
+        .filter(|domain| !domain.is_empty())
+        .collect();
+    domains.sort();
+    domains.dedup();
+    domains
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
@@ -109,16 +176,21 @@ pub fn instantiate(
     // check valid token info
     msg.validate()?;
     let cap = msg.get_cap(&env.block.time);
+    let cap_max = msg.get_curve().map(|curve| Uint128::new(curve.range().1));
 
     // set maximum vesting complexity
     MAX_VESTING_COMPLEXITY.save(deps.storage, &msg.max_curve_complexity)?;
 
     // create initial accounts
-    let total_supply = create_accounts(&mut deps, &env, msg.initial_balances)?;
-
-    if let Some(limit) = cap {
-        if total_supply > limit {
-            return Err(StdError::generic_err("Initial supply greater than cap").into());
+    let total_supply = create_accounts(&mut deps, &env, &info.sender, msg.initial_balances)?;
+
+    if let (Some(cap_now), Some(cap_max)) = (cap, cap_max) {
+        if total_supply > cap_now {
+            return Err(ContractError::InitialSupplyExceedsCap {
+                supply: total_supply,
+                cap_now,
+                cap_max,
+            });
         }
     }
 
@@ -126,6 +198,8 @@ pub fn instantiate(
         Some(m) => Some(MinterData {
             minter: deps.api.addr_validate(&m.minter)?,
             cap: m.cap,
+            burns_reduce_cap: m.burns_reduce_cap,
+            cap_reduction: Uint128::zero(),
         }),
         None => None,
     };
@@ -139,10 +213,16 @@ pub fn instantiate(
         mint,
     };
     TOKEN_INFO.save(deps.storage, &data)?;
+    TOTAL_SUPPLY_HISTORY.save(deps.storage, &total_supply, env.block.height)?;
+    SNAPSHOT_START_HEIGHT.save(deps.storage, &env.block.height)?;
 
     if let Some(marketing) = msg.marketing {
+        let domains = normalize_logo_domains(marketing.allowed_logo_domains);
+        if !domains.is_empty() {
+            LOGO_DOMAINS.save(deps.storage, &domains)?;
+        }
         let logo = if let Some(logo) = marketing.logo {
-            verify_logo(&logo)?;
+            verify_logo(deps.storage, &logo)?;
             LOGO.save(deps.storage, &logo)?;
 
             match logo {
@@ -165,11 +245,15 @@ pub fn instantiate(
         MARKETING_INFO.save(deps.storage, &data)?;
     }
 
+    if msg.seeding_open {
+        SEEDER.save(deps.storage, &info.sender)?;
+    }
+
     // We initially add by default info.sender to the list
     let address_list = match msg.allowed_vesters {
         Some(addrs) => addrs
             .into_iter()
-            .map(|a| deps.api.addr_validate(&a))
+            .map(|a| normalize_addr(deps.api, &a))
             .collect::<StdResult<_>>()?,
         None => vec![info.sender],
     };
@@ -178,19 +262,28 @@ pub fn instantiate(
     Ok(Response::default())
 }
 
+/// Creates the given accounts. Vesting grants are recorded in the vesting history as granted
+/// by `grantor`.
 pub fn create_accounts(
     deps: &mut DepsMut,
     env: &Env,
+    grantor: &Addr,
     accounts: Vec<InitBalance>,
 ) -> Result<Uint128, ContractError> {
     validate_accounts(&accounts)?;
 
     let mut total_supply = Uint128::zero();
     for row in accounts.into_iter() {
+        let schedule = match (row.vesting, row.relative_vesting) {
+            (Some(_), Some(_)) => return Err(ContractError::AmbiguousVestingSchedule {}),
+            (Some(curve), None) => Some(curve),
+            (None, Some(relative)) => Some(relative.to_curve(env.block.time, row.amount)),
+            (None, None) => None,
+        };
         // ensure vesting schedule is valid
-        let vesting = match &row.vesting {
+        let vesting = match &schedule {
             Some(s) => {
-                assert_schedule_vests_amount(s, row.amount)?;
+                assert_schedule_vests_amount(s, row.amount, &env.block)?;
                 if fully_vested(s, &env.block) {
                     None
                 } else {
@@ -204,9 +297,21 @@ pub fn create_accounts(
         if let Some(vest) = vesting {
             let max_complexity = MAX_VESTING_COMPLEXITY.load(deps.storage)?;
             vest.validate_complexity(max_complexity as usize)?;
-            VESTING.save(deps.storage, &address, vest)?;
+            save_vesting(deps.storage, &address, vest, env.block.time.seconds())?;
+        }
+        if schedule.is_some() {
+            record_vesting_grant(
+                deps.storage,
+                &address,
+                &VestingGrant {
+                    memo: row.memo,
+                    amount: row.amount,
+                    grantor: grantor.clone(),
+                    timestamp: env.block.time,
+                },
+            )?;
         }
-        BALANCES.save(deps.storage, &address, &row.amount)?;
+        BALANCES.save(deps.storage, &address, &row.amount, env.block.height)?;
         total_supply += row.amount;
     }
 
@@ -219,10 +324,11 @@ pub fn validate_accounts(accounts: &[InitBalance]) -> Result<(), ContractError>
     addresses.dedup();
 
     if addresses.len() != accounts.len() {
-        Err(ContractError::DuplicateInitialBalanceAddresses {})
-    } else {
-        Ok(())
+        return Err(ContractError::DuplicateInitialBalanceAddresses {});
     }
+    accounts
+        .iter()
+        .try_for_each(|account| validate_memo(account.memo.as_ref()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -232,6 +338,15 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // supply is not final while seeding, so nothing else can happen yet
+    let seeding = matches!(
+        msg,
+        ExecuteMsg::SeedBalances { .. } | ExecuteMsg::FinishSeeding {}
+    );
+    if !seeding && SEEDER.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::SeedingOpen {});
+    }
+
     match msg {
         ExecuteMsg::Transfer { recipient, amount } => {
             execute_transfer(deps, env, info, recipient, amount)
@@ -240,7 +355,27 @@ pub fn execute(
             recipient,
             amount,
             schedule,
-        } => execute_transfer_vesting(deps, env, info, recipient, amount, schedule),
+            memo,
+            notify,
+        } => execute_transfer_vesting(deps, env, info, recipient, amount, schedule, memo, notify),
+        ExecuteMsg::TransferBatch { transfers } => {
+            execute_transfer_batch(deps, env, info, transfers)
+        }
+        ExecuteMsg::TransferVestingBatch {
+            transfers,
+            schedule,
+        } => execute_transfer_vesting_batch(deps, env, info, transfers, schedule),
+        ExecuteMsg::ExtendVestingSchedule {
+            address,
+            additional_steps,
+        } => execute_extend_vesting_schedule(deps, env, info, address, additional_steps),
+        ExecuteMsg::ExtendVesting {
+            address,
+            extend_by_seconds,
+        } => execute_extend_vesting(deps, env, info, address, extend_by_seconds),
+        ExecuteMsg::ClearExpiredVesting { addresses } => {
+            execute_clear_expired_vesting(deps, env, addresses)
+        }
         ExecuteMsg::Burn { amount } => execute_burn(deps, env, info, amount),
         ExecuteMsg::Send {
             contract,
@@ -248,7 +383,25 @@ pub fn execute(
             msg,
         } => execute_send(deps, env, info, contract, amount, msg),
         ExecuteMsg::Mint { recipient, amount } => execute_mint(deps, env, info, recipient, amount),
-        ExecuteMsg::UpdateMinter { minter } => execute_update_minter(deps, env, info, minter),
+        ExecuteMsg::UpdateMinter {
+            minter,
+            burns_reduce_cap,
+        } => execute_update_minter(deps, env, info, minter, burns_reduce_cap),
+        ExecuteMsg::UpdateTokenInfo { name, symbol } => {
+            execute_update_token_info(deps, info, name, symbol)
+        }
+        ExecuteMsg::UpdateMaxVestingComplexity { max } => {
+            execute_update_max_vesting_complexity(deps, info, max)
+        }
+        ExecuteMsg::SetComplexityTier {
+            address,
+            max_complexity,
+        } => execute_set_complexity_tier(deps, info, address, max_complexity),
+        ExecuteMsg::SetSpendingLimit { limit } => execute_set_spending_limit(deps, info, limit),
+        ExecuteMsg::SetVestingPolicy {
+            min_cliff_seconds,
+            max_duration_seconds,
+        } => execute_set_vesting_policy(deps, info, min_cliff_seconds, max_duration_seconds),
         ExecuteMsg::IncreaseAllowance {
             spender,
             amount,
@@ -277,16 +430,132 @@ pub fn execute(
             marketing,
         } => execute_update_marketing(deps, env, info, project, description, marketing),
         ExecuteMsg::UploadLogo(logo) => execute_upload_logo(deps, env, info, logo),
-        ExecuteMsg::AllowVester { address } => execute_add_address(deps, info, address),
+        ExecuteMsg::SetLogoUpdater { address } => execute_set_logo_updater(deps, info, address),
+        ExecuteMsg::UpdateLogoDomains { domains } => {
+            execute_update_logo_domains(deps, info, domains)
+        }
+        ExecuteMsg::AllowVester {
+            address,
+            policy_exempt,
+        } => execute_add_address(deps, info, address, policy_exempt),
         ExecuteMsg::DenyVester { address } => execute_remove_address(deps, info, address),
         ExecuteMsg::UpdateStakingAddress { address } => {
             execute_update_staking_address(deps, info, address)
         }
-        ExecuteMsg::Delegate { amount, msg } => execute_delegate(deps, info, amount, msg),
-        ExecuteMsg::Undelegate { recipient, amount } => {
-            execute_undelegate(deps, env, info, recipient, amount)
+        ExecuteMsg::Delegate { amount, msg } => execute_delegate(deps, env, info, amount, msg),
+        ExecuteMsg::IncreaseDelegationAllowance {
+            spender,
+            amount,
+            expires,
+        } => execute_increase_delegation_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::DecreaseDelegationAllowance {
+            spender,
+            amount,
+            expires,
+        } => execute_decrease_delegation_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::DelegateFrom { owner, amount, msg } => {
+            execute_delegate_from(deps, env, info, owner, amount, msg)
+        }
+        ExecuteMsg::Permit {
+            owner,
+            spender,
+            amount,
+            expires,
+            nonce,
+            signature,
+        } => execute_permit(deps, env, owner, spender, amount, expires, nonce, signature),
+        ExecuteMsg::UndelegateVesting {
+            recipient,
+            amount,
+            schedule,
+        } => execute_undelegate_vesting(deps, env, info, recipient, amount, schedule),
+        ExecuteMsg::Reserve {
+            spender,
+            amount,
+            expires,
+        } => execute_reserve(deps, env, info, spender, amount, expires),
+        ExecuteMsg::SeizeReserved {
+            owner,
+            amount,
+            recipient,
+        } => execute_seize_reserved(deps, env, info, owner, amount, recipient),
+        ExecuteMsg::ReleaseReserved { owner } => execute_release_reserved(deps, env, info, owner),
+        ExecuteMsg::Undelegate {
+            recipient,
+            amount,
+            delegator,
+        } => execute_undelegate(deps, env, info, recipient, amount, delegator),
+        ExecuteMsg::ReconcileDelegationState { limit } => {
+            execute_reconcile_delegation_state(deps, env, info, limit)
+        }
+        ExecuteMsg::SeedBalances { accounts } => execute_seed_balances(deps, env, info, accounts),
+        ExecuteMsg::FinishSeeding {} => execute_finish_seeding(deps, info),
+    }
+}
+
+/// Returns an error unless seeding is open and `sender` is the instantiator
+fn assert_seeder(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    match SEEDER.may_load(storage)? {
+        Some(seeder) if seeder == *sender => Ok(()),
+        Some(_) => Err(ContractError::Unauthorized {}),
+        None => Err(ContractError::SeedingClosed {}),
+    }
+}
+
+pub fn execute_seed_balances(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    accounts: Vec<InitBalance>,
+) -> Result<Response, ContractError> {
+    assert_seeder(deps.storage, &info.sender)?;
+
+    // create_accounts only checks for duplicates within the batch
+    for account in &accounts {
+        let address = deps.api.addr_validate(&account.address)?;
+        if BALANCES.may_load(deps.storage, &address)?.is_some() {
+            return Err(ContractError::DuplicateInitialBalanceAddresses {});
+        }
+    }
+    let count = accounts.len();
+    let seeded = accounts.iter().try_fold(Uint128::zero(), |sum, account| {
+        sum.checked_add(account.amount)
+    })?;
+
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    token_info.total_supply = token_info.total_supply.checked_add(seeded)?;
+    if let Some(cap_now) = token_info.get_cap(&env.block.time) {
+        if token_info.total_supply > cap_now {
+            let cap_max = token_info
+                .mint
+                .as_ref()
+                .and_then(|mint| mint.cap.as_ref())
+                .map_or(cap_now, |cap| Uint128::new(cap.range().1));
+            return Err(ContractError::InitialSupplyExceedsCap {
+                supply: token_info.total_supply,
+                cap_now,
+                cap_max,
+            });
         }
     }
+    create_accounts(&mut deps, &env, &info.sender, accounts)?;
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+    TOTAL_SUPPLY_HISTORY.save(deps.storage, &token_info.total_supply, env.block.height)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "seed_balances")
+        .add_attribute("accounts", count.to_string())
+        .add_attribute("amount", seeded)
+        .add_attribute("total_supply", token_info.total_supply))
+}
+
+pub fn execute_finish_seeding(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_seeder(deps.storage, &info.sender)?;
+    SEEDER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "finish_seeding")
+        .add_attribute("sender", info.sender))
 }
 
 pub fn execute_transfer(
@@ -302,12 +571,14 @@ pub fn execute_transfer(
 
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
 
+    apply_spending_limit(deps.storage, &env, &info.sender, amount)?;
     // this will handle vesting checks as well
     deduct_coins(deps.storage, &env, &info.sender, amount)?;
 
     BALANCES.update(
         deps.storage,
         &rcpt_addr,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
@@ -319,6 +590,7 @@ pub fn execute_transfer(
     Ok(res)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_transfer_vesting(
     deps: DepsMut,
     env: Env,
@@ -326,56 +598,489 @@ pub fn execute_transfer_vesting(
     recipient: String,
     amount: Uint128,
     schedule: Curve,
+    memo: Option<String>,
+    notify: bool,
 ) -> Result<Response, ContractError> {
     // info.sender must be at least on the allow_list to allow execute trasnfer vesting
-    let allow_list = ALLOWLIST.load(deps.storage)?;
-    if !allow_list.contains(&info.sender) {
-        return Err(ContractError::Unauthorized {});
-    }
-
-    if amount == Uint128::zero() {
-        return Err(ContractError::InvalidZeroAmount {});
+    assert_allowed_vester(deps.as_ref(), &info.sender)?;
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmountVestingTransfer {});
     }
-
-    // ensure vesting schedule is valid
-    assert_schedule_vests_amount(&schedule, amount)?;
+    validate_memo(memo.as_ref())?;
 
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    assert_vesting_policy(deps.storage, &env, &info.sender, &schedule)?;
+
+    // only contracts can handle the notification, it is silently skipped for other accounts
+    let notification = if notify && is_contract(deps.as_ref(), &rcpt_addr) {
+        let msg = Cw20ReceiveVestingMsg {
+            sender: info.sender.to_string(),
+            amount,
+            schedule: schedule.clone(),
+        }
+        .into_cosmos_msg(&rcpt_addr)?;
+        Some(SubMsg::reply_on_error(msg, RECEIVE_VESTING_REPLY_ID))
+    } else {
+        None
+    };
 
     // if it is not already fully vested, we store this
-    if !fully_vested(&schedule, &env.block) {
-        let max_complexity = MAX_VESTING_COMPLEXITY.load(deps.storage)?;
-        VESTING.update(
+    if let Some(schedule) =
+        combined_vesting_schedule(deps.as_ref(), &env, &rcpt_addr, amount, schedule)?
+    {
+        save_vesting(
             deps.storage,
             &rcpt_addr,
-            |old| -> Result<_, ContractError> {
-                let schedule = old.map(|old| old.combine(&schedule)).unwrap_or(schedule);
-                // make sure the vesting curve does not get too complex, rendering the account useless
-                schedule.validate_complexity(max_complexity as usize)?;
-                Ok(schedule)
-            },
+            &schedule,
+            env.block.time.seconds(),
         )?;
     }
 
+    apply_spending_limit(deps.storage, &env, &info.sender, amount)?;
     // this will handle vesting checks as well
     deduct_coins(deps.storage, &env, &info.sender, amount)?;
 
     BALANCES.update(
         deps.storage,
         &rcpt_addr,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
-    let res = Response::new()
+    record_vesting_grant(
+        deps.storage,
+        &rcpt_addr,
+        &VestingGrant {
+            memo: memo.clone(),
+            amount,
+            grantor: info.sender.clone(),
+            timestamp: env.block.time,
+        },
+    )?;
+
+    let mut res = Response::new()
         // use same action as we want explorers to show this as a transfer
         .add_attribute("action", "transfer")
         .add_attribute("type", "vesting")
         .add_attribute("from", info.sender)
         .add_attribute("to", recipient)
-        .add_attribute("amount", amount);
+        .add_attribute("amount", amount)
+        .add_submessages(notification);
+    if let Some(memo) = memo {
+        res = res.add_attribute("memo", memo);
+    }
+    Ok(res)
+}
+
+/// Returns true if the address belongs to a contract
+fn is_contract(deps: Deps, addr: &Addr) -> bool {
+    deps.querier
+        .query::<ContractInfoResponse>(
+            &WasmQuery::ContractInfo {
+                contract_addr: addr.to_string(),
+            }
+            .into(),
+        )
+        .is_ok()
+}
+
+/// Validates an address in whatever casing it was given and returns its normalized form.
+/// `addr_validate` alone rejects addresses that are valid but not lowercase.
+fn normalize_addr(api: &dyn Api, address: &str) -> StdResult<Addr> {
+    let normalized = api.addr_humanize(&api.addr_canonicalize(address)?)?;
+    api.addr_validate(normalized.as_str())
+}
+
+/// Checks that `sender` is on the allow list. Entries are compared in their normalized form,
+/// so entries stored in a different casing still match.
+fn assert_allowed_vester(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    let sender = normalize_addr(deps.api, sender.as_str())?;
+    let allowed = ALLOWLIST.load(deps.storage)?.iter().any(
+        |entry| matches!(normalize_addr(deps.api, entry.as_str()), Ok(addr) if addr == sender),
+    );
+    if !allowed {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+/// Rewrites the allow list in normalized form, merging duplicates and dropping entries that are
+/// no longer valid addresses. Policy exemptions follow their entry.
+fn normalize_allow_list(deps: DepsMut) -> Result<Vec<Event>, ContractError> {
+    let mut events = vec![];
+    let entries = match ALLOWLIST.may_load(deps.storage)? {
+        Some(entries) => entries,
+        None => return Ok(events),
+    };
+    let mut allow_list: Vec<Addr> = vec![];
+    for entry in entries {
+        let addr = match normalize_addr(deps.api, entry.as_str()) {
+            Ok(addr) => addr,
+            Err(err) => {
+                POLICY_EXEMPT.remove(deps.storage, &entry);
+                events.push(
+                    Event::new("allowlist_entry_dropped")
+                        .add_attribute("address", entry)
+                        .add_attribute("error", err.to_string()),
+                );
+                continue;
+            }
+        };
+        if addr != entry && POLICY_EXEMPT.has(deps.storage, &entry) {
+            POLICY_EXEMPT.remove(deps.storage, &entry);
+            POLICY_EXEMPT.save(deps.storage, &addr, &())?;
+        }
+        if !allow_list.contains(&addr) {
+            allow_list.push(addr);
+        }
+    }
+    if allow_list.is_empty() {
+        return Err(ContractError::AtLeastOneAddressMustExist {});
+    }
+    ALLOWLIST.save(deps.storage, &allow_list)?;
+    Ok(events)
+}
+
+/// Validates all recipients and amounts of a batch, returning them along with the total amount
+fn validate_batch(
+    api: &dyn Api,
+    transfers: Vec<BatchTransfer>,
+) -> Result<(Vec<(Addr, Uint128)>, Uint128), ContractError> {
+    if transfers.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+    if transfers.len() > MAX_BATCH_TRANSFERS {
+        return Err(ContractError::TooManyTransfers {
+            max: MAX_BATCH_TRANSFERS,
+        });
+    }
+
+    let mut total = Uint128::zero();
+    let transfers = transfers
+        .into_iter()
+        .map(|BatchTransfer { recipient, amount }| {
+            if amount.is_zero() {
+                return Err(ContractError::InvalidZeroAmount {});
+            }
+            total = total.checked_add(amount)?;
+            Ok((api.addr_validate(&recipient)?, amount))
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    Ok((transfers, total))
+}
+
+/// Event emitted for every single transfer of a batch
+fn batch_transfer_event(from: &Addr, to: &Addr, amount: Uint128) -> Event {
+    Event::new("transfer")
+        .add_attribute("from", from)
+        .add_attribute("to", to)
+        .add_attribute("amount", amount)
+}
+
+pub fn execute_transfer_batch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfers: Vec<BatchTransfer>,
+) -> Result<Response, ContractError> {
+    let (transfers, total) = validate_batch(deps.api, transfers)?;
+
+    apply_spending_limit(deps.storage, &env, &info.sender, total)?;
+    // vesting and reservations are checked once against the total
+    deduct_coins(deps.storage, &env, &info.sender, total)?;
+
+    let mut events = Vec::with_capacity(transfers.len());
+    for (rcpt_addr, amount) in &transfers {
+        BALANCES.update(
+            deps.storage,
+            rcpt_addr,
+            env.block.height,
+            |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+        )?;
+        events.push(batch_transfer_event(&info.sender, rcpt_addr, *amount));
+    }
+
+    let res = Response::new()
+        .add_events(events)
+        .add_attribute("action", "transfer_batch")
+        .add_attribute("from", info.sender)
+        .add_attribute("transfers", transfers.len().to_string())
+        .add_attribute("amount", total);
+    Ok(res)
+}
+
+pub fn execute_transfer_vesting_batch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfers: Vec<BatchTransfer>,
+    schedule: ScalableCurve,
+) -> Result<Response, ContractError> {
+    // info.sender must be at least on the allow_list to allow execute trasnfer vesting
+    assert_allowed_vester(deps.as_ref(), &info.sender)?;
+
+    let (transfers, total) = validate_batch(deps.api, transfers)?;
+    let transfers = transfers
+        .into_iter()
+        .map(|(rcpt_addr, amount)| {
+            let scaled = schedule.clone().scale(amount);
+            assert_schedule_vests_amount(&scaled, amount, &env.block)?;
+            assert_vesting_policy(deps.storage, &env, &info.sender, &scaled)?;
+            Ok((rcpt_addr, amount, scaled))
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    apply_spending_limit(deps.storage, &env, &info.sender, total)?;
+    // vesting and reservations are checked once against the total
+    deduct_coins(deps.storage, &env, &info.sender, total)?;
+
+    let mut events = Vec::with_capacity(transfers.len());
+    for (rcpt_addr, amount, scaled) in &transfers {
+        // if it is not already fully vested, we store this
+        if let Some(combined) =
+            combined_vesting_schedule(deps.as_ref(), &env, rcpt_addr, *amount, scaled.clone())?
+        {
+            save_vesting(deps.storage, rcpt_addr, &combined, env.block.time.seconds())?;
+        }
+
+        BALANCES.update(
+            deps.storage,
+            rcpt_addr,
+            env.block.height,
+            |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+        )?;
+        record_vesting_grant(
+            deps.storage,
+            rcpt_addr,
+            &VestingGrant {
+                memo: None,
+                amount: *amount,
+                grantor: info.sender.clone(),
+                timestamp: env.block.time,
+            },
+        )?;
+        events.push(
+            batch_transfer_event(&info.sender, rcpt_addr, *amount).add_attribute("type", "vesting"),
+        );
+    }
+
+    let res = Response::new()
+        .add_events(events)
+        .add_attribute("action", "transfer_batch")
+        .add_attribute("type", "vesting")
+        .add_attribute("from", info.sender)
+        .add_attribute("transfers", transfers.len().to_string())
+        .add_attribute("amount", total);
+    Ok(res)
+}
+
+pub fn execute_clear_expired_vesting(
+    deps: DepsMut,
+    env: Env,
+    addresses: Vec<String>,
+) -> Result<Response, ContractError> {
+    if addresses.len() > MAX_CLEAR_VESTING {
+        return Err(ContractError::TooManyAddresses {
+            max: MAX_CLEAR_VESTING,
+        });
+    }
+
+    let now = env.block.time.seconds();
+    let mut cleared = 0u64;
+    for address in addresses {
+        let address = deps.api.addr_validate(&address)?;
+        if let Some(schedule) = VESTING.may_load(deps.storage, &address)? {
+            if schedule.is_zero_at_range(now, u64::MAX) {
+                remove_vesting(deps.storage, &address, now)?;
+                cleared += 1;
+            }
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "clear_expired_vesting")
+        .add_attribute("cleared", cleared.to_string()))
+}
+
+pub fn execute_extend_vesting_schedule(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    additional_steps: Vec<(u64, Uint128)>,
+) -> Result<Response, ContractError> {
+    // info.sender must be on the allow_list to manage vesting
+    assert_allowed_vester(deps.as_ref(), &info.sender)?;
+
+    let addr = deps.api.addr_validate(&address)?;
+
+    let additional = Curve::PiecewiseLinear(PiecewiseLinear {
+        steps: additional_steps,
+    });
+    assert_valid_schedule(&additional, &env.block)?;
+    // like with vesting transfers, the policy applies to the newly locked part
+    assert_vesting_policy(deps.storage, &env, &info.sender, &additional)?;
+
+    let schedule = match VESTING.may_load(deps.storage, &addr)? {
+        Some(old) => old.combine(&additional)?,
+        None => additional,
+    };
+    assert_storable_schedule(deps.storage, &env.block, &addr, &schedule)?;
+
+    // the account cannot lock more than it has
+    let locked = schedule.value(env.block.time.seconds());
+    let held = BALANCES.may_load(deps.storage, &addr)?.unwrap_or_default()
+        + DELEGATED.may_load(deps.storage, &addr)?.unwrap_or_default();
+    if locked > held {
+        return Err(ContractError::ExtendedVestingExceedsHoldings { locked, held });
+    }
+
+    save_vesting(deps.storage, &addr, &schedule, env.block.time.seconds())?;
+
+    let res = Response::new()
+        .add_attribute("action", "extend_vesting_schedule")
+        .add_attribute("address", address)
+        .add_attribute("schedule_size", schedule.size().to_string());
+    Ok(res)
+}
+
+pub fn execute_extend_vesting(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    extend_by_seconds: u64,
+) -> Result<Response, ContractError> {
+    // info.sender must be on the allow_list to manage vesting
+    assert_allowed_vester(deps.as_ref(), &info.sender)?;
+    if extend_by_seconds == 0 {
+        return Err(ContractError::InvalidVestingExtension {});
+    }
+
+    let addr = deps.api.addr_validate(&address)?;
+    let now = env.block.time.seconds();
+    let schedule = VESTING
+        .may_load(deps.storage, &addr)?
+        .filter(|schedule| !schedule.value(now).is_zero())
+        .ok_or(ContractError::NoVestingToExtend {})?;
+
+    let schedule = delay_vesting_tail(&schedule, now, extend_by_seconds);
+    assert_storable_schedule(deps.storage, &env.block, &addr, &schedule)?;
+    assert_vesting_policy(deps.storage, &env, &info.sender, &schedule)?;
+    save_vesting(deps.storage, &addr, &schedule, env.block.time.seconds())?;
+
+    let res = Response::new()
+        .add_attribute("action", "extend_vesting")
+        .add_attribute("address", address)
+        .add_attribute("extend_by_seconds", extend_by_seconds.to_string())
+        .add_attribute("schedule_size", schedule.size().to_string());
     Ok(res)
 }
 
+/// Moves the part of `schedule` after `now` `offset` seconds to the right, keeping the amount
+/// locked at `now`. Steps before `now` are replaced by a single step at `now`, so the schedule
+/// never gets more complex.
+fn delay_vesting_tail(schedule: &Curve, now: u64, offset: u64) -> Curve {
+    let delayed = schedule.shift_right(offset);
+    let steps = match &delayed {
+        Curve::Constant { .. } => return delayed,
+        Curve::SaturatingLinear(sl) => PiecewiseLinear::from(sl).steps,
+        Curve::PiecewiseLinear(pl) => pl.steps.clone(),
+    };
+    let delayed_now = now.saturating_add(offset);
+    if matches!(steps.first(), Some(&(x, _)) if x > delayed_now) {
+        // nothing vested yet, so the whole schedule is the tail
+        return delayed;
+    }
+
+    let steps = std::iter::once((now, schedule.value(now)))
+        .chain(steps.into_iter().filter(|&(x, _)| x > delayed_now))
+        .collect();
+    Curve::PiecewiseLinear(PiecewiseLinear { steps })
+}
+
+/// Validates a vesting transfer of `amount` to `recipient` and combines the schedule with the
+/// recipient's existing one. Returns `None` if the schedule is already fully vested, so nothing
+/// has to be stored.
+fn combined_vesting_schedule(
+    deps: Deps,
+    env: &Env,
+    recipient: &Addr,
+    amount: Uint128,
+    schedule: Curve,
+) -> Result<Option<Curve>, ContractError> {
+    let schedule = match merge_vesting_schedule(deps, env, recipient, amount, schedule)? {
+        Some(schedule) => schedule,
+        None => return Ok(None),
+    };
+    assert_storable_schedule(deps.storage, &env.block, recipient, &schedule)?;
+    Ok(Some(schedule))
+}
+
+/// Checks a new or changed vesting schedule of `addr` before it is stored. Besides being
+/// valid, it must not get too complex, rendering the account useless.
+fn assert_storable_schedule(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    addr: &Addr,
+    schedule: &Curve,
+) -> Result<(), ContractError> {
+    assert_valid_schedule(schedule, block)?;
+    let max_complexity = max_vesting_complexity(storage, addr)?;
+    schedule.validate_complexity(max_complexity as usize)?;
+    Ok(())
+}
+
+/// Like [`combined_vesting_schedule`], but simplifies the combined schedule if it gets too
+/// complex instead of failing. Used for flows not initiated by the recipient, which must not
+/// fail because of the recipient's existing schedule. The simplified schedule never locks less
+/// than the exact one. The returned flag tells whether the schedule had to be simplified.
+fn simplified_vesting_schedule(
+    deps: Deps,
+    env: &Env,
+    recipient: &Addr,
+    amount: Uint128,
+    schedule: Curve,
+) -> Result<(Option<Curve>, bool), ContractError> {
+    let schedule = match merge_vesting_schedule(deps, env, recipient, amount, schedule)? {
+        Some(schedule) => schedule,
+        None => return Ok((None, false)),
+    };
+    let max_complexity = max_vesting_complexity(deps.storage, recipient)? as usize;
+    if schedule.validate_complexity(max_complexity).is_ok() {
+        return Ok((Some(schedule), false));
+    }
+    let simplified = schedule.simplify_conservative(max_complexity)?;
+    Ok((Some(simplified), true))
+}
+
+/// Validates a vesting transfer of `amount` and adds the schedule to the recipient's existing one.
+/// Returns `None` if the schedule is already fully vested.
+fn merge_vesting_schedule(
+    deps: Deps,
+    env: &Env,
+    recipient: &Addr,
+    amount: Uint128,
+    schedule: Curve,
+) -> Result<Option<Curve>, ContractError> {
+    if amount == Uint128::zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // ensure vesting schedule is valid
+    assert_schedule_vests_amount(&schedule, amount, &env.block)?;
+
+    if fully_vested(&schedule, &env.block) {
+        return Ok(None);
+    }
+
+    let schedule = match VESTING.may_load(deps.storage, recipient)? {
+        Some(old) => old.combine(&schedule)?,
+        None => schedule,
+    };
+    Ok(Some(schedule))
+}
+
 pub fn execute_burn(
     deps: DepsMut,
     env: Env,
@@ -389,19 +1094,42 @@ pub fn execute_burn(
     // lower balance
     // this will handle vesting checks as well
     deduct_coins(deps.storage, &env, &info.sender, amount)?;
-    // reduce total_supply
-    TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
-        info.total_supply = info.total_supply.checked_sub(amount)?;
-        Ok(info)
-    })?;
+    // reduce total_supply (and cap, if configured)
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    let supply_before = token_info.total_supply;
+    token_info.burn(amount)?;
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+    TOTAL_SUPPLY_HISTORY.save(deps.storage, &token_info.total_supply, env.block.height)?;
 
     let res = Response::new()
+        .add_event(supply_change_event(
+            "burn",
+            amount,
+            supply_before,
+            token_info.total_supply,
+        ))
         .add_attribute("action", "burn")
         .add_attribute("from", info.sender)
-        .add_attribute("amount", amount);
+        .add_attribute("amount", amount)
+        .add_attribute("total_supply", token_info.total_supply);
     Ok(res)
 }
 
+/// Event emitted whenever the total supply changes, so it can be tracked without summing up
+/// mints and burns
+pub(crate) fn supply_change_event(
+    op: &str,
+    amount: Uint128,
+    total_supply_before: Uint128,
+    total_supply_after: Uint128,
+) -> Event {
+    Event::new("supply_change")
+        .add_attribute("op", op)
+        .add_attribute("amount", amount)
+        .add_attribute("total_supply_before", total_supply_before)
+        .add_attribute("total_supply_after", total_supply_after)
+}
+
 pub fn execute_mint(
     deps: DepsMut,
     env: Env,
@@ -419,6 +1147,7 @@ pub fn execute_mint(
     }
 
     // update supply and enforce cap
+    let supply_before = config.total_supply;
     config.total_supply += amount;
     if let Some(limit) = config.get_cap(&env.block.time) {
         if config.total_supply > limit {
@@ -426,19 +1155,28 @@ pub fn execute_mint(
         }
     }
     TOKEN_INFO.save(deps.storage, &config)?;
+    TOTAL_SUPPLY_HISTORY.save(deps.storage, &config.total_supply, env.block.height)?;
 
     // add amount to recipient balance
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
     BALANCES.update(
         deps.storage,
         &rcpt_addr,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
     let res = Response::new()
+        .add_event(supply_change_event(
+            "mint",
+            amount,
+            supply_before,
+            config.total_supply,
+        ))
         .add_attribute("action", "mint")
         .add_attribute("to", recipient)
-        .add_attribute("amount", amount);
+        .add_attribute("amount", amount)
+        .add_attribute("total_supply", config.total_supply);
     Ok(res)
 }
 
@@ -447,6 +1185,7 @@ pub fn execute_update_minter(
     _env: Env,
     info: MessageInfo,
     minter: String,
+    burns_reduce_cap: Option<bool>,
 ) -> Result<Response, ContractError> {
     let mut config = TOKEN_INFO.load(deps.storage)?;
     let mint_addr = deps.api.addr_validate(&minter)?;
@@ -457,6 +1196,9 @@ pub fn execute_update_minter(
                 return Err(ContractError::Unauthorized {});
             }
             old.minter = mint_addr;
+            if let Some(burns_reduce_cap) = burns_reduce_cap {
+                old.burns_reduce_cap = burns_reduce_cap;
+            }
         }
         None => return Err(ContractError::Unauthorized {}),
     };
@@ -469,6 +1211,143 @@ pub fn execute_update_minter(
     Ok(res)
 }
 
+pub fn execute_update_token_info(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: Option<String>,
+    symbol: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = TOKEN_INFO.load(deps.storage)?;
+
+    match config.mint.as_ref() {
+        Some(mint) if mint.minter == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    if let Some(name) = name {
+        if !is_valid_name(&name) {
+            return Err(ContractError::InvalidName);
+        }
+        config.name = name;
+    }
+    if let Some(symbol) = symbol {
+        if !is_valid_updated_symbol(&symbol) {
+            return Err(ContractError::InvalidSymbol);
+        }
+        config.symbol = symbol;
+    }
+
+    TOKEN_INFO.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_token_info")
+        .add_attribute("name", config.name)
+        .add_attribute("symbol", config.symbol);
+    Ok(res)
+}
+
+pub fn execute_update_max_vesting_complexity(
+    deps: DepsMut,
+    info: MessageInfo,
+    max: u64,
+) -> Result<Response, ContractError> {
+    let config = TOKEN_INFO.load(deps.storage)?;
+    match config.mint.as_ref() {
+        Some(mint) if mint.minter == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    // lowering the limit could leave existing curves unable to receive any more vesting
+    let old = MAX_VESTING_COMPLEXITY.load(deps.storage)?;
+    if max < old {
+        return Err(ContractError::CannotLowerMaxVestingComplexity { current: old });
+    }
+    MAX_VESTING_COMPLEXITY.save(deps.storage, &max)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_max_vesting_complexity")
+        .add_attribute("old", old.to_string())
+        .add_attribute("new", max.to_string());
+    Ok(res)
+}
+
+pub fn execute_set_complexity_tier(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    max_complexity: u8,
+) -> Result<Response, ContractError> {
+    let config = TOKEN_INFO.load(deps.storage)?;
+    match config.mint.as_ref() {
+        Some(mint) if mint.minter == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    let addr = deps.api.addr_validate(&address)?;
+    COMPLEXITY_TIERS.save(deps.storage, &addr, &max_complexity)?;
+
+    let res = Response::new()
+        .add_attribute("action", "set_complexity_tier")
+        .add_attribute("address", address)
+        .add_attribute("max_complexity", max_complexity.to_string());
+    Ok(res)
+}
+
+pub fn execute_set_spending_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<SpendingLimit>,
+) -> Result<Response, ContractError> {
+    let config = TOKEN_INFO.load(deps.storage)?;
+    match config.mint.as_ref() {
+        Some(mint) if mint.minter == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    if matches!(&limit, Some(l) if l.window_seconds == 0) {
+        return Err(ContractError::InvalidSpendingLimit {});
+    }
+    SPENDING_LIMIT.save(deps.storage, &limit)?;
+
+    let mut res = Response::new().add_attribute("action", "set_spending_limit");
+    if let Some(limit) = limit {
+        res = res
+            .add_attribute("window_seconds", limit.window_seconds.to_string())
+            .add_attribute("max_per_sender", limit.max_per_sender);
+    }
+    Ok(res)
+}
+
+pub fn execute_set_vesting_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    min_cliff_seconds: Option<u64>,
+    max_duration_seconds: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = TOKEN_INFO.load(deps.storage)?;
+    match config.mint.as_ref() {
+        Some(mint) if mint.minter == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    VESTING_POLICY.save(
+        deps.storage,
+        &VestingPolicy {
+            min_cliff_seconds,
+            max_duration_seconds,
+        },
+    )?;
+
+    let mut res = Response::new().add_attribute("action", "set_vesting_policy");
+    if let Some(min_cliff_seconds) = min_cliff_seconds {
+        res = res.add_attribute("min_cliff_seconds", min_cliff_seconds.to_string());
+    }
+    if let Some(max_duration_seconds) = max_duration_seconds {
+        res = res.add_attribute("max_duration_seconds", max_duration_seconds.to_string());
+    }
+    Ok(res)
+}
+
 pub fn execute_send(
     deps: DepsMut,
     env: Env,
@@ -483,12 +1362,14 @@ pub fn execute_send(
 
     let rcpt_addr = deps.api.addr_validate(&contract)?;
 
+    apply_spending_limit(deps.storage, &env, &info.sender, amount)?;
     // move the tokens to the contract
     // this will handle vesting checks as well
     deduct_coins(deps.storage, &env, &info.sender, amount)?;
     BALANCES.update(
         deps.storage,
         &rcpt_addr,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
@@ -520,12 +1401,9 @@ pub fn execute_update_marketing(
         .may_load(deps.storage)?
         .ok_or(ContractError::Unauthorized {})?;
 
-    if marketing_info
-        .marketing
-        .as_ref()
-        .ok_or(ContractError::Unauthorized {})?
-        != &info.sender
-    {
+    // the logo updater cannot hand out the marketing role
+    let is_owner = marketing_role(deps.as_ref(), &marketing_info, &info.sender)?;
+    if !is_owner && marketing.is_some() {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -541,11 +1419,16 @@ pub fn execute_update_marketing(
         None => (),
     }
 
+    let old_marketing = marketing_info.marketing.clone();
     match marketing {
         Some(empty) if empty.trim().is_empty() => marketing_info.marketing = None,
         Some(marketing) => marketing_info.marketing = Some(deps.api.addr_validate(&marketing)?),
         None => (),
     }
+    // the updater was chosen by the old marketing address
+    if marketing_info.marketing != old_marketing {
+        LOGO_UPDATER.remove(deps.storage);
+    }
 
     if marketing_info.project.is_none()
         && marketing_info.description.is_none()
@@ -571,16 +1454,9 @@ pub fn execute_upload_logo(
         .may_load(deps.storage)?
         .ok_or(ContractError::Unauthorized {})?;
 
-    verify_logo(&logo)?;
+    verify_logo(deps.storage, &logo)?;
 
-    if marketing_info
-        .marketing
-        .as_ref()
-        .ok_or(ContractError::Unauthorized {})?
-        != &info.sender
-    {
-        return Err(ContractError::Unauthorized {});
-    }
+    marketing_role(deps.as_ref(), &marketing_info, &info.sender)?;
 
     LOGO.save(deps.storage, &logo)?;
 
@@ -596,23 +1472,90 @@ pub fn execute_upload_logo(
     Ok(res)
 }
 
+/// Returns true if `sender` is the marketing address and false if it is the logo updater.
+/// Anyone else is unauthorized.
+fn marketing_role(
+    deps: Deps,
+    marketing_info: &MarketingInfoResponse,
+    sender: &Addr,
+) -> Result<bool, ContractError> {
+    if marketing_info.marketing.as_ref() == Some(sender) {
+        Ok(true)
+    } else if LOGO_UPDATER.may_load(deps.storage)?.as_ref() == Some(sender) {
+        Ok(false)
+    } else {
+        Err(ContractError::Unauthorized {})
+    }
+}
+
+pub fn execute_set_logo_updater(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: Option<String>,
+) -> Result<Response, ContractError> {
+    let marketing_info = MARKETING_INFO
+        .may_load(deps.storage)?
+        .ok_or(ContractError::Unauthorized {})?;
+    if marketing_info.marketing.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let res = Response::new().add_attribute("action", "set_logo_updater");
+    match address {
+        Some(address) => {
+            let addr = deps.api.addr_validate(&address)?;
+            LOGO_UPDATER.save(deps.storage, &addr)?;
+            Ok(res.add_attribute("logo_updater", addr))
+        }
+        None => {
+            LOGO_UPDATER.remove(deps.storage);
+            Ok(res.add_attribute("logo_updater", "none"))
+        }
+    }
+}
+
+pub fn execute_update_logo_domains(
+    deps: DepsMut,
+    info: MessageInfo,
+    domains: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = TOKEN_INFO.load(deps.storage)?;
+    match config.mint.as_ref() {
+        Some(mint) if mint.minter == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    let domains = normalize_logo_domains(domains);
+    if domains.is_empty() {
+        LOGO_DOMAINS.remove(deps.storage);
+    } else {
+        LOGO_DOMAINS.save(deps.storage, &domains)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_logo_domains")
+        .add_attribute("domains", domains.join(",")))
+}
+
 pub fn execute_add_address(
     deps: DepsMut,
     info: MessageInfo,
     address: String,
+    policy_exempt: bool,
 ) -> Result<Response, ContractError> {
     // info.sender must be at least on the allow_list to add address to the list
+    assert_allowed_vester(deps.as_ref(), &info.sender)?;
     let mut allow_list = ALLOWLIST.load(deps.storage)?;
-    if !allow_list.contains(&info.sender) {
-        return Err(ContractError::Unauthorized {});
-    }
 
     // validate address and ensure unique
-    let addr = deps.api.addr_validate(&address)?;
+    let addr = normalize_addr(deps.api, &address)?;
     if allow_list.contains(&addr) {
         return Err(ContractError::AddressAlreadyExist {});
     }
 
+    if policy_exempt {
+        POLICY_EXEMPT.save(deps.storage, &addr, &())?;
+    }
     // Add the new address to the allow list
     allow_list.push(addr);
     ALLOWLIST.save(deps.storage, &allow_list)?;
@@ -627,13 +1570,11 @@ pub fn execute_remove_address(
     address: String,
 ) -> Result<Response, ContractError> {
     // info.sender must be at least on the allow_list to remove address to the list
+    assert_allowed_vester(deps.as_ref(), &info.sender)?;
     let allow_list = ALLOWLIST.load(deps.storage)?;
-    if !allow_list.contains(&info.sender) {
-        return Err(ContractError::Unauthorized {});
-    }
 
     // validate address and remove
-    let addr = deps.api.addr_validate(&address)?;
+    let addr = normalize_addr(deps.api, &address)?;
     let prev_len = allow_list.len();
     let allow_list: Vec<Addr> = allow_list
         .into_iter()
@@ -649,6 +1590,7 @@ pub fn execute_remove_address(
     }
 
     ALLOWLIST.save(deps.storage, &allow_list)?;
+    POLICY_EXEMPT.remove(deps.storage, &addr);
     let res = Response::new().add_attribute("action", "remove address");
     Ok(res)
 }
@@ -680,110 +1622,328 @@ pub fn execute_update_staking_address(
 
 pub fn execute_delegate(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     amount: Uint128,
     msg: Binary,
 ) -> Result<Response, ContractError> {
+    let (token_address, msg) = delegate(deps.storage, &env, &info.sender, amount, msg)?;
+
+    let res = Response::new()
+        .add_attribute("action", "delegate")
+        .add_attribute("from", &info.sender)
+        .add_attribute("to", &token_address)
+        .add_attribute("amount", amount)
+        .add_message(msg);
+    Ok(res)
+}
+
+/// Moves `amount` of owner's tokens to the staking contract.
+/// Returns the staking address and the message informing it about the delegation.
+pub(crate) fn delegate(
+    storage: &mut dyn Storage,
+    env: &Env,
+    owner: &Addr,
+    amount: Uint128,
+    msg: Binary,
+) -> Result<(Addr, CosmosMsg), ContractError> {
     if amount == Uint128::zero() {
         return Err(ContractError::InvalidZeroAmount {});
     }
 
-    let token_address = match STAKING.load(deps.storage) {
+    let token_address = match STAKING.load(storage) {
         Ok(address) => address,
         Err(_) => return Err(ContractError::StakingAddressNotSet {}),
     };
 
     // this allows to delegate also vested tokens, because vested is included in balance anyway
-    BALANCES.update(deps.storage, &info.sender, |balance| {
-        let balance = balance.unwrap_or_default();
-        balance
+    // reserved tokens have to stay in the balance though
+    let reserved = reserved_total(storage, &env.block, owner)?;
+    BALANCES.update(storage, owner, env.block.height, |balance| {
+        let balance = balance
+            .unwrap_or_default()
             .checked_sub(amount)
-            .map_err(|_| ContractError::NotEnoughToDelegate)
-    })?;
-    // make sure we add it to the other side
-    BALANCES.update(deps.storage, &token_address, |balance| -> StdResult<_> {
-        let balance = balance.unwrap_or_default() + amount;
+            .map_err(|_| ContractError::NotEnoughToDelegate)?;
+        if reserved > balance {
+            return Err(ContractError::CantMoveReservedTokens);
+        }
         Ok(balance)
     })?;
+    // make sure we add it to the other side
+    BALANCES.update(
+        storage,
+        &token_address,
+        env.block.height,
+        |balance| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+    )?;
 
-    DELEGATED.update(
+    let delegated = DELEGATED.may_load(storage, owner)?.unwrap_or_default();
+    save_delegated(storage, owner, delegated + amount, env.block.time.seconds())?;
+    let total_delegated = TOTAL_DELEGATED.may_load(storage)?.unwrap_or_default();
+    TOTAL_DELEGATED.save(storage, &(total_delegated + amount))?;
+
+    let msg = Cw20ReceiveDelegationMsg {
+        sender: owner.into(),
+        amount,
+        msg,
+    }
+    .into_cosmos_msg(&token_address)?;
+    Ok((token_address, msg))
+}
+
+pub fn execute_reconcile_delegation_state(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    match TOKEN_INFO.load(deps.storage)?.mint {
+        Some(mint) if mint.minter == info.sender => (),
+        Some(_) => return Err(ContractError::Unauthorized {}),
+        None => return Err(ContractError::MinterAddressNotSet {}),
+    };
+
+    let staking = match STAKING.load(deps.storage) {
+        Ok(address) => address,
+        Err(_) => return Err(ContractError::StakingAddressNotSet {}),
+    };
+
+    // delegations are summed up over as many calls as needed, continuing where the last one stopped
+    let limit = limit
+        .unwrap_or(MAX_RECONCILE_DELEGATIONS)
+        .min(MAX_RECONCILE_DELEGATIONS) as usize;
+    let progress = RECONCILE_PROGRESS.may_load(deps.storage)?;
+    let start = progress.as_ref().map(|p| Bound::exclusive(&p.last));
+    let page = DELEGATED
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let delegated = page.iter().try_fold(
+        progress.map_or_else(Uint128::zero, |p| p.delegated),
+        |sum, (_, amount)| sum.checked_add(*amount),
+    )?;
+    if page.len() == limit {
+        let last = page[limit - 1].0.clone();
+        RECONCILE_PROGRESS.save(
+            deps.storage,
+            &ReconcileProgress {
+                last: last.clone(),
+                delegated,
+            },
+        )?;
+        return Ok(Response::new()
+            .add_attribute("action", "reconcile_delegation_state")
+            .add_attribute("reconciled_until", last));
+    }
+    RECONCILE_PROGRESS.remove(deps.storage);
+
+    let old_balance = BALANCES
+        .may_load(deps.storage, &staking)?
+        .unwrap_or_default();
+    // the staking contract also holds rewards and slashed tokens, so only a shortfall is repaired
+    let new_balance = old_balance.max(delegated);
+    let shortfall = new_balance - old_balance;
+    if !shortfall.is_zero() {
+        BALANCES.save(deps.storage, &staking, &new_balance, env.block.height)?;
+        let mut token_info = TOKEN_INFO.load(deps.storage)?;
+        token_info.total_supply = token_info.total_supply.checked_add(shortfall)?;
+        if let Some(limit) = token_info.get_cap(&env.block.time) {
+            if token_info.total_supply > limit {
+                return Err(ContractError::CannotExceedCap {});
+            }
+        }
+        TOKEN_INFO.save(deps.storage, &token_info)?;
+        TOTAL_SUPPLY_HISTORY.save(deps.storage, &token_info.total_supply, env.block.height)?;
+    }
+    TOTAL_DELEGATED.save(deps.storage, &delegated)?;
+
+    let res = Response::new()
+        .add_attribute("action", "reconcile_delegation_state")
+        .add_attribute("old_staking_balance", old_balance)
+        .add_attribute("new_staking_balance", new_balance);
+    Ok(res)
+}
+
+pub fn execute_undelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+    delegator: Option<String>,
+) -> Result<Response, ContractError> {
+    let recipient_address = deps.api.addr_validate(&recipient)?;
+    let delegator = delegator
+        .map(|delegator| deps.api.addr_validate(&delegator))
+        .transpose()?;
+    undelegate(
         deps.storage,
+        &env,
         &info.sender,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+        delegator.as_ref().unwrap_or(&recipient_address),
+        &recipient_address,
+        amount,
     )?;
 
-    let res = Response::new()
-        .add_attribute("action", "delegate")
+    let mut res = Response::new()
+        .add_attribute("action", "undelegate")
         .add_attribute("from", &info.sender)
-        .add_attribute("to", &token_address)
-        .add_attribute("amount", amount)
-        .add_message(
-            Cw20ReceiveDelegationMsg {
-                sender: info.sender.into(),
-                amount,
-                msg,
-            }
-            .into_cosmos_msg(token_address)?,
-        );
+        .add_attribute("to", &recipient_address)
+        .add_attribute("amount", amount);
+    if let Some(delegator) = delegator {
+        res = res.add_attribute("delegator", delegator);
+    }
     Ok(res)
 }
 
-pub fn execute_undelegate(
+pub fn execute_undelegate_vesting(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
+    schedule: Curve,
 ) -> Result<Response, ContractError> {
+    let recipient_address = deps.api.addr_validate(&recipient)?;
+    undelegate(
+        deps.storage,
+        &env,
+        &info.sender,
+        &recipient_address,
+        &recipient_address,
+        amount,
+    )?;
+
+    // the recipient did not initiate this, so it must not fail because of their existing schedule
+    let (schedule, simplified) =
+        simplified_vesting_schedule(deps.as_ref(), &env, &recipient_address, amount, schedule)?;
+    // if it is not already fully vested, we store this
+    if let Some(schedule) = schedule {
+        save_vesting(
+            deps.storage,
+            &recipient_address,
+            &schedule,
+            env.block.time.seconds(),
+        )?;
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "undelegate")
+        .add_attribute("type", "vesting")
+        .add_attribute("from", &info.sender)
+        .add_attribute("to", &recipient_address)
+        .add_attribute("amount", amount);
+    if simplified {
+        res = res.add_attribute("vesting_simplified", "true");
+    }
+    Ok(res)
+}
+
+/// Moves `amount` of tokens delegated by `delegator` from the staking contract to `recipient`.
+/// Fails if `sender` is not the staking contract, or if the tokens would go to someone else
+/// while the delegator could not cover their vesting tokens anymore.
+fn undelegate(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    delegator: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
     if amount == Uint128::zero() {
         return Err(ContractError::InvalidZeroAmount {});
     }
 
-    match STAKING.load(deps.storage) {
+    match STAKING.load(storage) {
         Ok(staking) => {
-            if staking != info.sender {
+            if staking != *sender {
                 return Err(ContractError::UnauthorizedUndelegate {});
             }
         }
         Err(_) => return Err(ContractError::StakingAddressNotSet {}),
     };
 
-    let recipient_address = deps.api.addr_validate(&recipient)?;
-
-    if !DELEGATED.has(deps.storage, &recipient_address) {
-        return Err(ContractError::NoTokensDelegated {});
+    let delegated = DELEGATED
+        .may_load(storage, delegator)?
+        .ok_or(ContractError::NoTokensDelegated {})?;
+    let remaining = delegated.checked_sub(amount)?;
+    if delegator != recipient {
+        // tokens still vesting for the delegator must not become liquid for someone else,
+        // so what is left to the delegator has to cover them
+        let vesting = VESTING
+            .may_load(storage, delegator)?
+            .map(|v| v.value(env.block.time.seconds()))
+            .unwrap_or_default();
+        let balance = BALANCES.may_load(storage, delegator)?.unwrap_or_default();
+        if vesting > balance + remaining {
+            return Err(ContractError::CantMoveVestingTokens {
+                effectively_locked: vesting.saturating_sub(remaining),
+            });
+        }
     }
-    DELEGATED.update(
-        deps.storage,
-        &recipient_address,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    deduct_coins(deps.storage, &env, &info.sender, amount)?;
+    save_delegated(storage, delegator, remaining, env.block.time.seconds())?;
+    let total_delegated = TOTAL_DELEGATED.may_load(storage)?.unwrap_or_default();
+    TOTAL_DELEGATED.save(storage, &total_delegated.checked_sub(amount)?)?;
+    deduct_coins(storage, env, sender, amount)?;
     BALANCES.update(
-        deps.storage,
-        &recipient_address,
+        storage,
+        recipient,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
-
-    let res = Response::new()
-        .add_attribute("action", "undelegate")
-        .add_attribute("from", &info.sender)
-        .add_attribute("to", &recipient_address)
-        .add_attribute("amount", amount);
-    Ok(res)
+    Ok(())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::BalanceAt { address, height } => {
+            to_binary(&query_balance_at(deps, address, height)?)
+        }
+        QueryMsg::TotalSupplyAt { height } => to_binary(&query_total_supply_at(deps, height)?),
+        QueryMsg::CirculatingSupply {} => to_binary(&query_circulating_supply(deps, env)?),
+        QueryMsg::SupplyInfo {} => to_binary(&query_supply_info(deps, env)?),
+        QueryMsg::PermitNonce { owner } => to_binary(&query_permit_nonce(deps, owner)?),
+        QueryMsg::Reservations {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_reservations(deps, env, owner, start_after, limit)?),
+        QueryMsg::ReservationsBySpender {
+            spender,
+            start_after,
+            limit,
+        } => to_binary(&query_reservations_by_spender(
+            deps,
+            env,
+            spender,
+            start_after,
+            limit,
+        )?),
         QueryMsg::Vesting { address } => to_binary(&query_vesting(deps, env, address)?),
+        QueryMsg::VestingBatch { addresses } => {
+            to_binary(&query_vesting_batch(deps, env, addresses)?)
+        }
+        QueryMsg::VestingUnlockTime { address, amount } => {
+            to_binary(&query_vesting_unlock_time(deps, env, address, amount)?)
+        }
+        QueryMsg::VestingHistory {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_vesting_history(deps, address, start_after, limit)?),
         QueryMsg::Delegated { address } => to_binary(&query_delegated(deps, address)?),
+        QueryMsg::DelegateEligible { address } => {
+            to_binary(&query_delegate_eligible(deps, env, address)?)
+        }
         QueryMsg::VestingAllowList {} => to_binary(&query_allow_list(deps)?),
         QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
         QueryMsg::MaxVestingComplexity {} => to_binary(&query_max_complexity(deps)?),
+        QueryMsg::ComplexityTier { address } => to_binary(&query_complexity_tier(deps, address)?),
+        QueryMsg::SpendingLimitStatus { address } => {
+            to_binary(&query_spending_limit_status(deps, env, address)?)
+        }
         QueryMsg::Minter {} => to_binary(&query_minter(deps, env)?),
         QueryMsg::Allowance { owner, spender } => {
             to_binary(&query_allowance(deps, owner, spender)?)
@@ -793,14 +1953,42 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         } => to_binary(&query_all_allowances(deps, owner, start_after, limit)?),
-        QueryMsg::AllAccounts { start_after, limit } => {
-            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        QueryMsg::DelegationAllowance { owner, spender } => {
+            to_binary(&query_delegation_allowance(deps, owner, spender)?)
         }
+        QueryMsg::AllDelegationAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_all_delegation_allowances(
+            deps,
+            owner,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::AllAccounts {
+            start_after,
+            limit,
+            exclude,
+        } => to_binary(&query_all_accounts(deps, start_after, limit, exclude)?),
         QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
         QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        QueryMsg::LogoUpdater {} => to_binary(&LogoUpdaterResponse {
+            logo_updater: LOGO_UPDATER.may_load(deps.storage)?,
+        }),
         QueryMsg::StakingAddress {} => to_binary(&query_staking_address(deps)?),
-    }
-}
+        QueryMsg::UndelegatableAmount { staking } => {
+            to_binary(&query_undelegatable_amount(deps, env, staking)?)
+        }
+        QueryMsg::SimulateTransferVesting {
+            recipient,
+            amount,
+            schedule,
+        } => to_binary(&query_simulate_transfer_vesting(
+            deps, env, recipient, amount, schedule,
+        )?),
+    }
+}
 
 pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
     let address = deps.api.addr_validate(&address)?;
@@ -810,12 +1998,197 @@ pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse>
     Ok(BalanceResponse { balance })
 }
 
+/// Makes sure historical data is available for the given height
+fn assert_snapshotted(deps: Deps, height: u64) -> StdResult<()> {
+    let start = SNAPSHOT_START_HEIGHT.may_load(deps.storage)?;
+    match start {
+        Some(start) if height >= start => Ok(()),
+        _ => Err(StdError::generic_err(format!(
+            "No snapshot available for height {}",
+            height
+        ))),
+    }
+}
+
+pub fn query_balance_at(deps: Deps, address: String, height: u64) -> StdResult<BalanceResponse> {
+    assert_snapshotted(deps, height)?;
+    let address = deps.api.addr_validate(&address)?;
+    // snapshots hold the value at the beginning of a block, so look at the following one
+    let balance = BALANCES
+        .may_load_at_height(deps.storage, &address, height + 1)?
+        .unwrap_or_default();
+    Ok(BalanceResponse { balance })
+}
+
+pub fn query_total_supply_at(deps: Deps, height: u64) -> StdResult<TotalSupplyResponse> {
+    assert_snapshotted(deps, height)?;
+    let total_supply = TOTAL_SUPPLY_HISTORY
+        .may_load_at_height(deps.storage, height + 1)?
+        .unwrap_or_default();
+    Ok(TotalSupplyResponse { total_supply })
+}
+
+pub fn query_circulating_supply(deps: Deps, env: Env) -> StdResult<CirculatingSupplyResponse> {
+    let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    Ok(CirculatingSupplyResponse {
+        circulating_supply: circulating_supply(deps, &env, total_supply)?,
+    })
+}
+
+pub fn query_supply_info(deps: Deps, env: Env) -> StdResult<SupplyInfoResponse> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    Ok(SupplyInfoResponse {
+        total_supply: token_info.total_supply,
+        circulating_supply: circulating_supply(deps, &env, token_info.total_supply)?,
+        mint_cap: token_info.get_cap(&env.block.time),
+    })
+}
+
+/// The part of `total_supply` that is neither staked nor locked in vesting schedules
+fn circulating_supply(deps: Deps, env: &Env, total_supply: Uint128) -> StdResult<Uint128> {
+    let staked = match STAKING.may_load(deps.storage)? {
+        Some(staking) => BALANCES
+            .may_load(deps.storage, &staking)?
+            .unwrap_or_default(),
+        None => Uint128::zero(),
+    };
+
+    // delegated vesting tokens are already part of the staking contract's balance,
+    // so only count what is locked in the accounts' own balances
+    let locked = TOTAL_LOCKED
+        .may_load(deps.storage)?
+        .map(|total| total.value(env.block.time.seconds()))
+        .unwrap_or_default();
+
+    Ok(total_supply.saturating_sub(staked).saturating_sub(locked))
+}
+
 pub fn query_vesting(deps: Deps, env: Env, address: String) -> StdResult<VestingResponse> {
     let address = deps.api.addr_validate(&address)?;
-    let schedule = VESTING.may_load(deps.storage, &address)?;
-    let time = env.block.time.seconds();
+    load_vesting(deps.storage, &address, env.block.time.seconds())
+}
+
+/// The vesting schedule of `address` and the amount it locks at `time`
+fn load_vesting(storage: &dyn Storage, address: &Addr, time: u64) -> StdResult<VestingResponse> {
+    let schedule = VESTING.may_load(storage, address)?;
     let locked = schedule.as_ref().map(|c| c.value(time)).unwrap_or_default();
-    Ok(VestingResponse { schedule, locked })
+    let delegated = DELEGATED.may_load(storage, address)?.unwrap_or_default();
+    Ok(VestingResponse {
+        schedule,
+        locked,
+        delegated,
+        effectively_locked: locked.saturating_sub(delegated),
+    })
+}
+
+/// Maximum number of addresses in a single `QueryMsg::VestingBatch`
+pub const MAX_VESTING_BATCH: usize = 50;
+
+pub fn query_vesting_batch(
+    deps: Deps,
+    env: Env,
+    addresses: Vec<String>,
+) -> StdResult<VestingBatchResponse> {
+    if addresses.len() > MAX_VESTING_BATCH {
+        return Err(StdError::generic_err(format!(
+            "Cannot query more than {} addresses at once",
+            MAX_VESTING_BATCH
+        )));
+    }
+
+    let time = env.block.time.seconds();
+    let vestings = addresses
+        .into_iter()
+        .map(|address| {
+            let addr = match deps.api.addr_validate(&address) {
+                Ok(addr) => addr,
+                Err(err) => {
+                    return Ok(VestingBatchEntry {
+                        address,
+                        locked: Uint128::zero(),
+                        schedule_end: None,
+                        has_schedule: false,
+                        error: Some(err.to_string()),
+                    })
+                }
+            };
+            let vesting = load_vesting(deps.storage, &addr, time)?;
+            Ok(VestingBatchEntry {
+                address,
+                locked: vesting.locked,
+                schedule_end: vesting
+                    .schedule
+                    .as_ref()
+                    .and_then(|schedule| schedule.first_time_at_or_below(Uint128::zero())),
+                has_schedule: vesting.schedule.is_some(),
+                error: None,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(VestingBatchResponse { vestings })
+}
+
+pub fn query_vesting_unlock_time(
+    deps: Deps,
+    env: Env,
+    address: String,
+    amount: Uint128,
+) -> StdResult<VestingUnlockTimeResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let delegated = DELEGATED
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let now = env.block.time.seconds();
+
+    // same as in deduct_coins: the vesting amount must not exceed what remains after the transfer
+    let unlock_time = match (balance + delegated).checked_sub(amount) {
+        Ok(remainder) if amount <= balance => match VESTING.may_load(deps.storage, &address)? {
+            Some(schedule) if schedule.value(now) > remainder => schedule
+                .first_time_at_or_below(remainder)
+                .map(|time| time.max(now)),
+            _ => Some(now),
+        },
+        _ => None,
+    };
+    Ok(VestingUnlockTimeResponse { unlock_time })
+}
+
+pub fn query_simulate_transfer_vesting(
+    deps: Deps,
+    env: Env,
+    recipient: String,
+    amount: Uint128,
+    schedule: Curve,
+) -> StdResult<SimulateTransferVestingResponse> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let combined = match combined_vesting_schedule(deps, &env, &recipient, amount, schedule) {
+        Ok(Some(combined)) => Some(combined),
+        // schedule is fully vested, recipient's schedule stays as it is
+        Ok(None) => VESTING.may_load(deps.storage, &recipient)?,
+        Err(err) => {
+            return Ok(SimulateTransferVestingResponse {
+                valid: false,
+                error: Some(err.to_string()),
+                combined_size: 0,
+                locked_now: Uint128::zero(),
+                fully_vested_at: 0,
+            })
+        }
+    };
+
+    Ok(SimulateTransferVestingResponse {
+        valid: true,
+        error: None,
+        combined_size: combined.as_ref().map(Curve::size).unwrap_or_default() as u64,
+        locked_now: combined
+            .as_ref()
+            .map(|c| c.value(env.block.time.seconds()))
+            .unwrap_or_default(),
+        fully_vested_at: combined.as_ref().map(fully_vested_at).unwrap_or_default(),
+    })
 }
 
 pub fn query_delegated(deps: Deps, address: String) -> StdResult<DelegatedResponse> {
@@ -826,6 +2199,27 @@ pub fn query_delegated(deps: Deps, address: String) -> StdResult<DelegatedRespon
     Ok(DelegatedResponse { delegated })
 }
 
+pub fn query_delegate_eligible(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<DelegateEligibleResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let liquid = BALANCES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let already_delegated = DELEGATED
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let reserved = active_reserved(deps.storage, &env.block, &address)?;
+    let balance = liquid + already_delegated;
+    Ok(DelegateEligibleResponse {
+        eligible: balance - already_delegated - reserved,
+        already_delegated,
+        balance,
+    })
+}
+
 pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
     let info = TOKEN_INFO.load(deps.storage)?;
     let res = TokenInfoResponse {
@@ -839,20 +2233,68 @@ pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
 
 pub fn query_max_complexity(deps: Deps) -> StdResult<MaxVestingComplexityResponse> {
     let complexity = MAX_VESTING_COMPLEXITY.load(deps.storage)?;
-    Ok(MaxVestingComplexityResponse { complexity })
+    let largest_curve_size = LARGEST_VESTING_SIZE
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    Ok(MaxVestingComplexityResponse {
+        complexity,
+        largest_curve_size,
+    })
+}
+
+pub fn query_complexity_tier(deps: Deps, address: String) -> StdResult<ComplexityTierResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let tier = COMPLEXITY_TIERS.may_load(deps.storage, &addr)?;
+    let complexity = max_vesting_complexity(deps.storage, &addr)?;
+    Ok(ComplexityTierResponse { complexity, tier })
+}
+
+pub fn query_spending_limit_status(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<SpendingLimitStatusResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let res = match spending_status(deps.storage, &env, &addr)? {
+        Some((limit, window_start, sent)) => SpendingLimitStatusResponse {
+            sent,
+            remaining: Some(limit.max_per_sender.saturating_sub(sent)),
+            window_end: Some(window_start.saturating_add(limit.window_seconds)),
+            limit: Some(limit),
+        },
+        None => SpendingLimitStatusResponse {
+            limit: None,
+            sent: Uint128::zero(),
+            remaining: None,
+            window_end: None,
+        },
+    };
+    Ok(res)
 }
 
 pub fn query_minter(deps: Deps, env: Env) -> StdResult<Option<MinterResponse>> {
     let meta = TOKEN_INFO.load(deps.storage)?;
+    // evaluating a malformed curve can panic, so check it before touching its value
+    if let Some(cap) = meta.mint.as_ref().and_then(|m| m.cap.as_ref()) {
+        cap.validate().map_err(|err| {
+            StdError::generic_err(format!(
+                "Stored cap curve is invalid ({} points): {}",
+                cap.size(),
+                err
+            ))
+        })?;
+    }
+    let current_cap = meta.get_cap(&env.block.time);
+    let remaining_mintable = current_cap.map(|cap| cap.saturating_sub(meta.total_supply));
     let minter = match meta.mint {
-        Some(m) => {
-            let current_cap = m.cap.as_ref().map(|v| v.value(env.block.time.seconds()));
-            Some(MinterResponse {
-                minter: m.minter.into(),
-                cap: m.cap,
-                current_cap,
-            })
-        }
+        Some(m) => Some(MinterResponse {
+            minter: m.minter.into(),
+            cap: m.cap,
+            current_cap,
+            burns_reduce_cap: m.burns_reduce_cap,
+            cap_reduction: m.cap_reduction,
+            remaining_mintable,
+        }),
         None => None,
     };
     Ok(minter)
@@ -888,11 +2330,48 @@ pub fn query_download_logo(deps: Deps) -> StdResult<DownloadLogoResponse> {
 
 pub fn query_staking_address(deps: Deps) -> StdResult<StakingAddressResponse> {
     let address = STAKING.may_load(deps.storage)?;
-    Ok(StakingAddressResponse { address })
+    let total_delegated = TOTAL_DELEGATED.may_load(deps.storage)?.unwrap_or_default();
+    let staking_balance = match &address {
+        Some(address) => BALANCES
+            .may_load(deps.storage, address)?
+            .unwrap_or_default(),
+        None => Uint128::zero(),
+    };
+    Ok(StakingAddressResponse {
+        address,
+        total_delegated,
+        staking_balance,
+        consistent: staking_balance >= total_delegated,
+    })
+}
+
+pub fn query_undelegatable_amount(
+    deps: Deps,
+    env: Env,
+    staking: Option<String>,
+) -> StdResult<UndelegatableAmountResponse> {
+    let staking = match staking {
+        Some(staking) => deps.api.addr_validate(&staking)?,
+        None => STAKING.load(deps.storage)?,
+    };
+    let amount = movable_amount(deps.storage, &env.block, &staking)?;
+    Ok(UndelegatableAmountResponse { staking, amount })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match (msg.id, msg.result) {
+        // the recipient could not handle the notification, but the transfer stands
+        (RECEIVE_VESTING_REPLY_ID, SubMsgResult::Err(err)) => Ok(Response::new()
+            .add_attribute("action", "receive_vesting_failed")
+            .add_attribute("error", err)),
+        (RECEIVE_VESTING_REPLY_ID, SubMsgResult::Ok(_)) => Ok(Response::new()),
+        (id, _) => Err(ContractError::UnknownReplyId { id }),
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     // make sure picewise linear curve is passed in the message
@@ -903,13 +2382,55 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, Co
         }
     };
 
-    TOKEN_INFO.update(deps.storage, |mut token_info| -> StdResult<_> {
+    let token_info = TOKEN_INFO.update(deps.storage, |mut token_info| -> StdResult<_> {
         // We can unwrap because we know cap is set
         token_info.mint.as_mut().unwrap().cap = Some(msg.picewise_linear_curve);
         Ok(token_info)
     })?;
 
-    Ok(Response::new())
+    // Start snapshotting from here on. Balances are migrated lazily: the changelog of an account
+    // is only written on its next balance change, until then its current balance is returned.
+    if SNAPSHOT_START_HEIGHT.may_load(deps.storage)?.is_none() {
+        SNAPSHOT_START_HEIGHT.save(deps.storage, &env.block.height)?;
+        TOTAL_SUPPLY_HISTORY.save(deps.storage, &token_info.total_supply, env.block.height)?;
+    }
+
+    // count the vesting schedules stored before they were counted
+    if VESTING_COUNT.may_load(deps.storage)?.is_none() {
+        let count = VESTING
+            .keys(deps.storage, None, None, Order::Ascending)
+            .count() as u64;
+        VESTING_COUNT.save(deps.storage, &count)?;
+    }
+
+    // sum up the delegations made before they were summed up
+    if TOTAL_DELEGATED.may_load(deps.storage)?.is_none() {
+        let total = DELEGATED
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, amount)| amount))
+            .sum::<StdResult<Uint128>>()?;
+        TOTAL_DELEGATED.save(deps.storage, &total)?;
+    }
+
+    // sum up the vesting locks stored before they were summed up
+    if TOTAL_LOCKED.may_load(deps.storage)?.is_none() {
+        let now = env.block.time.seconds();
+        let mut total = Curve::constant(0);
+        for item in VESTING.range(deps.storage, None, None, Order::Ascending) {
+            let (addr, schedule) = item?;
+            let delegated = DELEGATED.may_load(deps.storage, &addr)?.unwrap_or_default();
+            total = drop_past_steps(
+                total.combine(&balance_lock(Some(&schedule), delegated))?,
+                now,
+            );
+        }
+        TOTAL_LOCKED.save(deps.storage, &total)?;
+    }
+
+    // entries written before addresses were normalized may be stored in a different casing
+    let events = normalize_allow_list(deps)?;
+
+    Ok(Response::new().add_events(events))
 }
 
 #[cfg(test)]
@@ -917,11 +2438,17 @@ mod tests {
     use cosmwasm_std::testing::{
         mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
     };
-    use cosmwasm_std::{attr, coins, from_binary, Addr, CosmosMsg, StdError, SubMsg, WasmMsg};
+    use cosmwasm_std::{
+        attr, coins, from_binary, from_slice, Addr, CosmosMsg, StdError, SubMsg, Timestamp, WasmMsg,
+    };
+    use cw_utils::Expiration;
     use wynd_utils::{Curve, CurveError, PiecewiseLinear};
 
     use super::*;
-    use crate::msg::{InstantiateMarketingInfo, MinterInfo};
+    use crate::msg::{
+        InstantiateMarketingInfo, MinterInfo, RelativeSchedule, VestingHistoryResponse,
+        MAX_MEMO_LENGTH, MAX_VESTING_SECONDS,
+    };
 
     fn get_balance<T: Into<String>>(deps: Deps, address: T) -> Uint128 {
         query_balance(deps, address.into()).unwrap().balance
@@ -946,6 +2473,7 @@ mod tests {
             Some(MinterInfo {
                 minter: minter.to_string(),
                 cap: constant_curve(cap),
+                burns_reduce_cap: false,
             }),
             None,
         )
@@ -972,11 +2500,14 @@ mod tests {
                 address: addr.to_string(),
                 amount,
                 vesting: None,
+                memo: None,
+                relative_vesting: None,
             }],
             mint: mint.clone(),
             marketing: None,
             allowed_vesters: None,
             max_curve_complexity: 10,
+            seeding_open: false,
         };
         let creator_info = match info {
             Some(info) => info,
@@ -1028,11 +2559,14 @@ mod tests {
                     address: String::from("addr0000"),
                     amount,
                     vesting: None,
+                    memo: None,
+                    relative_vesting: None,
                 }],
                 mint: None,
                 marketing: None,
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
             let info = mock_info("creator", &[]);
             let env = mock_env();
@@ -1069,14 +2603,18 @@ mod tests {
                     address: "addr0000".into(),
                     amount,
                     vesting: None,
+                    memo: None,
+                    relative_vesting: None,
                 }],
                 mint: Some(MinterInfo {
                     minter: minter.clone(),
                     cap: Some(limit.clone()),
+                    burns_reduce_cap: false,
                 }),
                 marketing: None,
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
             let info = mock_info("creator", &[]);
             let env = mock_env();
@@ -1102,6 +2640,9 @@ mod tests {
                     minter,
                     cap: Some(limit),
                     current_cap: Some(y),
+                    burns_reduce_cap: false,
+                    cap_reduction: Uint128::zero(),
+                    remaining_mintable: Some(y - amount),
                 }),
             );
         }
@@ -1121,21 +2662,155 @@ mod tests {
                     address: String::from("addr0000"),
                     amount,
                     vesting: None,
+                    memo: None,
+                    relative_vesting: None,
                 }],
                 mint: Some(MinterInfo {
                     minter,
                     cap: Some(limit),
+                    burns_reduce_cap: false,
                 }),
                 marketing: None,
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
             let info = mock_info("creator", &[]);
             let env = mock_env();
             let err = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap_err();
             assert_eq!(
                 err,
-                StdError::generic_err("Initial supply greater than cap").into()
+                ContractError::InitialSupplyExceedsCap {
+                    supply: amount,
+                    cap_now: y,
+                    cap_max: y,
+                }
+            );
+        }
+
+        fn instantiate_with_cap(
+            deps: DepsMut,
+            amount: Uint128,
+            cap: Curve,
+        ) -> Result<Response, ContractError> {
+            let instantiate_msg = InstantiateMsg {
+                name: "Cash Token".to_string(),
+                symbol: "CASH".to_string(),
+                decimals: 9,
+                initial_balances: vec![InitBalance {
+                    address: String::from("addr0000"),
+                    amount,
+                    vesting: None,
+                    memo: None,
+                    relative_vesting: None,
+                }],
+                mint: Some(MinterInfo {
+                    minter: String::from("asmodat"),
+                    cap: Some(cap),
+                    burns_reduce_cap: false,
+                }),
+                marketing: None,
+                allowed_vesters: None,
+                max_curve_complexity: 10,
+                seeding_open: false,
+            };
+            instantiate(deps, mock_env(), mock_info("creator", &[]), instantiate_msg)
+        }
+
+        #[test]
+        fn decreasing_cap_rejected() {
+            let mut deps = mock_dependencies();
+            let now = mock_env().block.time.seconds();
+            let cap = Curve::saturating_linear((now, 20_000), (now + 1000, 10_000));
+            let err = instantiate_with_cap(deps.as_mut(), Uint128::new(5_000), cap).unwrap_err();
+            assert_eq!(err, ContractError::CapCurveMustIncrease {});
+
+            let cap = Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: vec![
+                    (now, Uint128::new(10_000)),
+                    (now + 1000, Uint128::new(20_000)),
+                    (now + 2000, Uint128::new(15_000)),
+                ],
+            });
+            let err = instantiate_with_cap(deps.as_mut(), Uint128::new(5_000), cap).unwrap_err();
+            assert_eq!(err, ContractError::CapCurveMustIncrease {});
+        }
+
+        #[test]
+        fn initial_supply_above_increasing_cap() {
+            let mut deps = mock_dependencies();
+            let now = mock_env().block.time.seconds();
+            // cap starts below the initial supply and only reaches it later
+            let cap = Curve::saturating_linear((now, 5_000), (now + 1000, 50_000));
+            let err = instantiate_with_cap(deps.as_mut(), Uint128::new(10_000), cap).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InitialSupplyExceedsCap {
+                    supply: Uint128::new(10_000),
+                    cap_now: Uint128::new(5_000),
+                    cap_max: Uint128::new(50_000),
+                }
+            );
+        }
+
+        #[test]
+        fn valid_piecewise_cap() {
+            let mut deps = mock_dependencies();
+            let now = mock_env().block.time.seconds();
+            let cap = Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: vec![
+                    (now, Uint128::new(10_000)),
+                    (now + 1000, Uint128::new(20_000)),
+                    (now + 2000, Uint128::new(20_000)),
+                    (now + 3000, Uint128::new(50_000)),
+                ],
+            });
+            instantiate_with_cap(deps.as_mut(), Uint128::new(10_000), cap.clone()).unwrap();
+            let minter = query_minter(deps.as_ref(), mock_env()).unwrap().unwrap();
+            assert_eq!(minter.cap, Some(cap));
+        }
+
+        #[test]
+        fn remaining_mintable() {
+            let mut deps = mock_dependencies();
+            let cap = Curve::constant(50_000);
+            instantiate_with_cap(deps.as_mut(), Uint128::new(10_000), cap).unwrap();
+            let minter = query_minter(deps.as_ref(), mock_env()).unwrap().unwrap();
+            assert_eq!(minter.current_cap, Some(Uint128::new(50_000)));
+            assert_eq!(minter.remaining_mintable, Some(Uint128::new(40_000)));
+
+            // a cap below the supply (e.g. lowered by a migration) saturates at zero
+            let mut info = TOKEN_INFO.load(&deps.storage).unwrap();
+            info.mint.as_mut().unwrap().cap = Some(Curve::constant(8_000));
+            TOKEN_INFO.save(&mut deps.storage, &info).unwrap();
+            let minter = query_minter(deps.as_ref(), mock_env()).unwrap().unwrap();
+            assert_eq!(minter.current_cap, Some(Uint128::new(8_000)));
+            assert_eq!(minter.remaining_mintable, Some(Uint128::zero()));
+
+            // no cap means no limit to report
+            info.mint.as_mut().unwrap().cap = None;
+            TOKEN_INFO.save(&mut deps.storage, &info).unwrap();
+            let minter = query_minter(deps.as_ref(), mock_env()).unwrap().unwrap();
+            assert_eq!(minter.current_cap, None);
+            assert_eq!(minter.remaining_mintable, None);
+        }
+
+        #[test]
+        fn malformed_stored_cap_is_reported() {
+            let mut deps = mock_dependencies();
+            instantiate_with_cap(deps.as_mut(), Uint128::new(10_000), Curve::constant(50_000))
+                .unwrap();
+
+            let mut info = TOKEN_INFO.load(&deps.storage).unwrap();
+            info.mint.as_mut().unwrap().cap =
+                Some(Curve::PiecewiseLinear(PiecewiseLinear { steps: vec![] }));
+            TOKEN_INFO.save(&mut deps.storage, &info).unwrap();
+            let err = query_minter(deps.as_ref(), mock_env()).unwrap_err();
+            assert_eq!(
+                err,
+                StdError::generic_err(
+                    "Stored cap curve is invalid (0 points): No steps defined".to_string()
+                )
             );
         }
 
@@ -1159,17 +2834,22 @@ mod tests {
                         address: addr1.clone(),
                         amount,
                         vesting: None,
+                        memo: None,
+                        relative_vesting: None,
                     },
                     InitBalance {
                         address: addr2.clone(),
                         amount: amount2,
                         vesting: Some(schedule.clone()),
+                        memo: None,
+                        relative_vesting: None,
                     },
                 ],
                 mint: None,
                 marketing: None,
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
             let info = mock_info("creator", &[]);
             let env = mock_env();
@@ -1199,91 +2879,349 @@ mod tests {
         }
 
         #[test]
-        fn init_complex_curve() {
+        fn seed_balances() {
             let mut deps = mock_dependencies();
-            let addr1 = String::from("addr0001");
-            let addr2 = String::from("addr0002");
-            let amount = Uint128::new(11223344);
-            let amount2 = Uint128::new(12345678);
-            // curve is not fully vested yet and complexity is too high
-            let start = mock_env().block.time.seconds();
-            let complexity = 10_000;
-            let steps: Vec<_> = (0..complexity)
-                .map(|x| (start + x, amount2 - Uint128::from(x)))
-                .chain(std::iter::once((start + complexity, Uint128::new(0)))) // fully vest
-                .collect();
-            let schedule = Curve::PiecewiseLinear(PiecewiseLinear {
-                steps: steps.clone(),
-            });
-
-            let instantiate_msg = InstantiateMsg {
-                name: "Cash Token".to_string(),
-                symbol: "CASH".to_string(),
-                decimals: 9,
-                initial_balances: vec![
-                    InitBalance {
-                        address: addr1,
-                        amount,
-                        vesting: None,
-                    },
-                    InitBalance {
-                        address: addr2.clone(),
-                        amount: amount2,
-                        vesting: Some(schedule),
-                    },
-                ],
-                mint: None,
-                marketing: None,
-                allowed_vesters: None,
-                max_curve_complexity: 10,
+            let creator = mock_info("creator", &[]);
+            let balance = |address: &str, amount: u128| InitBalance {
+                address: address.to_string(),
+                amount: Uint128::new(amount),
+                vesting: None,
+                memo: None,
+                relative_vesting: None,
             };
-
-            // should error because curve is too complex
-            let info = mock_info("creator", &[]);
-            let env = mock_env();
-            let error = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap_err();
-            assert_eq!(ContractError::Curve(CurveError::TooComplex), error);
-
-            // shift curve to the left, so it's fully vested already
-            let steps = steps
-                .into_iter()
-                .map(|(x, y)| (x - complexity, y))
-                .collect();
-            let schedule = Curve::PiecewiseLinear(PiecewiseLinear { steps });
-
             let instantiate_msg = InstantiateMsg {
                 name: "Cash Token".to_string(),
                 symbol: "CASH".to_string(),
                 decimals: 9,
-                initial_balances: vec![InitBalance {
-                    address: addr2.clone(),
-                    amount: amount2,
-                    vesting: Some(schedule),
-                }],
-                mint: None,
+                initial_balances: vec![balance("addr0001", 1_000)],
+                mint: Some(MinterInfo {
+                    minter: "minter".to_string(),
+                    cap: Some(Curve::constant(10_000)),
+                    burns_reduce_cap: false,
+                }),
                 marketing: None,
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: true,
             };
+            instantiate(deps.as_mut(), mock_env(), creator.clone(), instantiate_msg).unwrap();
+            let seed = |accounts| ExecuteMsg::SeedBalances { accounts };
 
-            // should *not* error, even though curve is complex, because it's fully vested already
-            let info = mock_info("creator", &[]);
-            let env = mock_env();
-            let res = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap();
-            assert_eq!(0, res.messages.len());
-
-            // vesting is not set for addr2
-            let vesting = query_vesting(deps.as_ref(), mock_env(), addr2).unwrap();
-            assert_eq!(vesting.locked, Uint128::new(0));
-            assert_eq!(vesting.schedule, None);
-        }
+            // nothing but seeding works until it is finished
+            let transfer = ExecuteMsg::Transfer {
+                recipient: "addr0002".to_string(),
+                amount: Uint128::new(1),
+            };
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr0001", &[]),
+                transfer.clone(),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::SeedingOpen {});
+            let mint = ExecuteMsg::Mint {
+                recipient: "addr0002".to_string(),
+                amount: Uint128::new(1),
+            };
+            let err =
+                execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), mint).unwrap_err();
+            assert_eq!(err, ContractError::SeedingOpen {});
 
-        mod marketing {
-            use super::*;
+            // only the instantiator can seed
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr0001", &[]),
+                seed(vec![balance("addr0002", 2_000)]),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
 
-            #[test]
-            fn basic() {
-                let mut deps = mock_dependencies();
+            // seeding in multiple batches
+            let start = mock_env().block.time.seconds();
+            let mut vesting = balance("addr0003", 3_000);
+            vesting.vesting = Some(Curve::saturating_linear((start, 3_000), (start + 100, 0)));
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                creator.clone(),
+                seed(vec![balance("addr0002", 2_000), vesting]),
+            )
+            .unwrap();
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                creator.clone(),
+                seed(vec![balance("addr0004", 500)]),
+            )
+            .unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "addr0003"), Uint128::new(3_000));
+            let vesting = query_vesting(deps.as_ref(), mock_env(), "addr0003".to_string()).unwrap();
+            assert_eq!(vesting.locked, Uint128::new(3_000));
+            let supply = query_token_info(deps.as_ref()).unwrap().total_supply;
+            assert_eq!(supply, Uint128::new(6_500));
+
+            // addresses seeded before cannot be seeded again
+            for address in ["addr0001", "addr0002"] {
+                let err = execute(
+                    deps.as_mut(),
+                    mock_env(),
+                    creator.clone(),
+                    seed(vec![balance("addr0005", 1), balance(address, 1)]),
+                )
+                .unwrap_err();
+                assert_eq!(err, ContractError::DuplicateInitialBalanceAddresses {});
+            }
+
+            // the cap is checked after every batch
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                creator.clone(),
+                seed(vec![balance("addr0005", 3_501)]),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InitialSupplyExceedsCap {
+                    supply: Uint128::new(10_001),
+                    cap_now: Uint128::new(10_000),
+                    cap_max: Uint128::new(10_000),
+                }
+            );
+            let supply = query_token_info(deps.as_ref()).unwrap().total_supply;
+            assert_eq!(supply, Uint128::new(6_500));
+
+            // once finished, seeding is closed for good and everything else works
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr0001", &[]),
+                ExecuteMsg::FinishSeeding {},
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                creator.clone(),
+                ExecuteMsg::FinishSeeding {},
+            )
+            .unwrap();
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                creator,
+                seed(vec![balance("addr0005", 1)]),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::SeedingClosed {});
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr0001", &[]),
+                transfer,
+            )
+            .unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "addr0002"), Uint128::new(2_001));
+        }
+
+        #[test]
+        fn init_relative_vesting() {
+            let addr = String::from("addr0001");
+            let amount = Uint128::new(10_000);
+            let instantiate_msg = |vesting, relative_vesting| InstantiateMsg {
+                name: "Cash Token".to_string(),
+                symbol: "CASH".to_string(),
+                decimals: 9,
+                initial_balances: vec![InitBalance {
+                    address: addr.clone(),
+                    amount,
+                    vesting,
+                    memo: None,
+                    relative_vesting,
+                }],
+                mint: None,
+                marketing: None,
+                allowed_vesters: None,
+                max_curve_complexity: 10,
+                seeding_open: false,
+            };
+            let relative = RelativeSchedule {
+                cliff: 1_000,
+                duration: 4_000,
+            };
+
+            // the absolute schedule is computed for the expected launch time
+            let launch = mock_env();
+            let start = launch.block.time.seconds();
+            let schedule = Curve::saturating_linear((start + 1_000, 10_000), (start + 5_000, 0));
+            let mut absolute_deps = mock_dependencies();
+            instantiate(
+                absolute_deps.as_mut(),
+                launch.clone(),
+                mock_info("creator", &[]),
+                instantiate_msg(Some(schedule.clone()), None),
+            )
+            .unwrap();
+
+            // the relative one is anchored at the actual launch, a day later
+            let mut delayed = mock_env();
+            delayed.block.time = delayed.block.time.plus_seconds(86_400);
+            let mut relative_deps = mock_dependencies();
+            instantiate(
+                relative_deps.as_mut(),
+                delayed.clone(),
+                mock_info("creator", &[]),
+                instantiate_msg(None, Some(relative.clone())),
+            )
+            .unwrap();
+
+            for elapsed in [0, 999, 1_000, 2_000, 4_999, 5_000, 6_000] {
+                let mut env = launch.clone();
+                env.block.time = env.block.time.plus_seconds(elapsed);
+                let absolute = query_vesting(absolute_deps.as_ref(), env, addr.clone()).unwrap();
+                let mut env = delayed.clone();
+                env.block.time = env.block.time.plus_seconds(elapsed);
+                let relative = query_vesting(relative_deps.as_ref(), env, addr.clone()).unwrap();
+                assert_eq!(absolute.locked, relative.locked);
+            }
+            let vesting =
+                query_vesting(relative_deps.as_ref(), delayed.clone(), addr.clone()).unwrap();
+            assert_eq!(vesting.locked, amount);
+            assert_eq!(vesting.schedule.unwrap(), schedule.shift_right(86_400));
+
+            // without a duration, everything is released right after the cliff
+            let mut cliff_deps = mock_dependencies();
+            let cliff_only = RelativeSchedule {
+                cliff: 1_000,
+                duration: 0,
+            };
+            instantiate(
+                cliff_deps.as_mut(),
+                launch.clone(),
+                mock_info("creator", &[]),
+                instantiate_msg(None, Some(cliff_only)),
+            )
+            .unwrap();
+            for (elapsed, locked) in [(0, amount.u128()), (1_000, amount.u128()), (1_001, 0)] {
+                let mut env = launch.clone();
+                env.block.time = env.block.time.plus_seconds(elapsed);
+                let vesting = query_vesting(cliff_deps.as_ref(), env, addr.clone()).unwrap();
+                assert_eq!(vesting.locked, Uint128::new(locked));
+            }
+
+            // only one of the schedules can be given
+            let err = instantiate(
+                mock_dependencies().as_mut(),
+                launch,
+                mock_info("creator", &[]),
+                instantiate_msg(Some(schedule), Some(relative)),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::AmbiguousVestingSchedule {});
+
+            // balances without the new field are still accepted
+            let balance: InitBalance =
+                from_slice(br#"{"address":"addr0001","amount":"10","vesting":null}"#).unwrap();
+            assert_eq!(balance.relative_vesting, None);
+        }
+
+        #[test]
+        fn init_complex_curve() {
+            let mut deps = mock_dependencies();
+            let addr1 = String::from("addr0001");
+            let addr2 = String::from("addr0002");
+            let amount = Uint128::new(11223344);
+            let amount2 = Uint128::new(12345678);
+            // curve is not fully vested yet and complexity is too high
+            let start = mock_env().block.time.seconds();
+            let complexity = 10_000;
+            let steps: Vec<_> = (0..complexity)
+                .map(|x| (start + x, amount2 - Uint128::from(x)))
+                .chain(std::iter::once((start + complexity, Uint128::new(0)))) // fully vest
+                .collect();
+            let schedule = Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: steps.clone(),
+            });
+
+            let instantiate_msg = InstantiateMsg {
+                name: "Cash Token".to_string(),
+                symbol: "CASH".to_string(),
+                decimals: 9,
+                initial_balances: vec![
+                    InitBalance {
+                        address: addr1,
+                        amount,
+                        vesting: None,
+                        memo: None,
+                        relative_vesting: None,
+                    },
+                    InitBalance {
+                        address: addr2.clone(),
+                        amount: amount2,
+                        vesting: Some(schedule),
+                        memo: None,
+                        relative_vesting: None,
+                    },
+                ],
+                mint: None,
+                marketing: None,
+                allowed_vesters: None,
+                max_curve_complexity: 10,
+                seeding_open: false,
+            };
+
+            // should error because curve is too complex
+            let info = mock_info("creator", &[]);
+            let env = mock_env();
+            let error = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap_err();
+            assert_eq!(ContractError::Curve(CurveError::TooComplex), error);
+
+            // shift curve to the left, so it's fully vested already
+            let steps = steps
+                .into_iter()
+                .map(|(x, y)| (x - complexity, y))
+                .collect();
+            let schedule = Curve::PiecewiseLinear(PiecewiseLinear { steps });
+
+            let instantiate_msg = InstantiateMsg {
+                name: "Cash Token".to_string(),
+                symbol: "CASH".to_string(),
+                decimals: 9,
+                initial_balances: vec![InitBalance {
+                    address: addr2.clone(),
+                    amount: amount2,
+                    vesting: Some(schedule),
+                    memo: None,
+                    relative_vesting: None,
+                }],
+                mint: None,
+                marketing: None,
+                allowed_vesters: None,
+                max_curve_complexity: 10,
+                seeding_open: false,
+            };
+
+            // should *not* error, even though curve is complex, because it's fully vested already
+            let info = mock_info("creator", &[]);
+            let env = mock_env();
+            let res = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap();
+            assert_eq!(0, res.messages.len());
+
+            // vesting is not set for addr2
+            let vesting = query_vesting(deps.as_ref(), mock_env(), addr2).unwrap();
+            assert_eq!(vesting.locked, Uint128::new(0));
+            assert_eq!(vesting.schedule, None);
+        }
+
+        mod marketing {
+            use super::*;
+
+            #[test]
+            fn basic() {
+                let mut deps = mock_dependencies();
                 let instantiate_msg = InstantiateMsg {
                     name: "Cash Token".to_string(),
                     symbol: "CASH".to_string(),
@@ -1295,9 +3233,11 @@ mod tests {
                         description: Some("Description".to_owned()),
                         marketing: Some("marketing".to_owned()),
                         logo: Some(Logo::Url("url".to_owned())),
+                        allowed_logo_domains: vec![],
                     }),
                     allowed_vesters: None,
                     max_curve_complexity: 10,
+                    seeding_open: false,
                 };
 
                 let info = mock_info("creator", &[]);
@@ -1337,9 +3277,11 @@ mod tests {
                         description: Some("Description".to_owned()),
                         marketing: Some("m".to_owned()),
                         logo: Some(Logo::Url("url".to_owned())),
+                        allowed_logo_domains: vec![],
                     }),
                     allowed_vesters: None,
                     max_curve_complexity: 10,
+                    seeding_open: false,
                 };
 
                 let info = mock_info("creator", &[]);
@@ -1420,6 +3362,7 @@ mod tests {
             Some(MinterInfo {
                 minter: minter.to_string(),
                 cap: Some(limit.clone()),
+                burns_reduce_cap: false,
             }),
             None,
         );
@@ -1515,6 +3458,7 @@ mod tests {
         let new_minter = "changed".to_string();
         let msg = ExecuteMsg::UpdateMinter {
             minter: new_minter.clone(),
+            burns_reduce_cap: None,
         };
         let info = mock_info(&orig_minter, &[]);
         execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
@@ -1529,388 +3473,3442 @@ mod tests {
     }
 
     #[test]
-    fn instantiate_multiple_accounts() {
+    fn minter_can_update_token_info() {
         let mut deps = mock_dependencies();
-        let amount1 = Uint128::from(11223344u128);
-        let addr1 = String::from("addr0001");
-        let amount2 = Uint128::from(7890987u128);
-        let addr2 = String::from("addr0002");
-        let info = mock_info("creator", &[]);
-        let env = mock_env();
+        let minter = "minter".to_string();
+        do_instantiate_with_minter(
+            deps.as_mut(),
+            &String::from("genesis"),
+            Uint128::new(1234),
+            &minter,
+            None,
+        );
 
-        // Fails with duplicate addresses
-        let instantiate_msg = InstantiateMsg {
-            name: "Bash Shell".to_string(),
-            symbol: "BASH".to_string(),
-            decimals: 6,
-            initial_balances: vec![
-                InitBalance {
-                    address: addr1.clone(),
-                    amount: amount1,
-                    vesting: None,
-                },
-                InitBalance {
-                    address: addr1.clone(),
-                    amount: amount2,
-                    vesting: None,
-                },
-            ],
-            mint: None,
-            marketing: None,
-            allowed_vesters: None,
-            max_curve_complexity: 10,
+        // only minter can update
+        let msg = ExecuteMsg::UpdateTokenInfo {
+            name: Some("Fixed Name".to_string()),
+            symbol: None,
         };
-        let err =
-            instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg).unwrap_err();
-        assert_eq!(err, ContractError::DuplicateInitialBalanceAddresses {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("genesis", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
 
-        // Works with unique addresses
-        let instantiate_msg = InstantiateMsg {
-            name: "Bash Shell".to_string(),
-            symbol: "BASH".to_string(),
-            decimals: 6,
-            initial_balances: vec![
-                InitBalance {
-                    address: addr1.clone(),
-                    amount: amount1,
-                    vesting: None,
-                },
-                InitBalance {
-                    address: addr2.clone(),
-                    amount: amount2,
-                    vesting: None,
-                },
-            ],
-            mint: None,
-            marketing: None,
-            allowed_vesters: None,
-            max_curve_complexity: 10,
+        let info = mock_info(&minter, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(res.attributes[1], ("name", "Fixed Name"));
+        assert_eq!(res.attributes[2], ("symbol", "AUTO"));
+        let token_info = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(token_info.name, "Fixed Name");
+        assert_eq!(token_info.symbol, "AUTO");
+        assert_eq!(token_info.decimals, 3);
+
+        // invalid values are rejected
+        for (name, symbol, expected) in [
+            ("", "FIX", ContractError::InvalidName),
+            ("Fixed", "F", ContractError::InvalidSymbol),
+            ("Fixed", "FIXEDSYMB", ContractError::InvalidSymbol),
+            ("Fixed", "Fix", ContractError::InvalidSymbol),
+            ("Fixed", "FIX-D", ContractError::InvalidSymbol),
+        ] {
+            let msg = ExecuteMsg::UpdateTokenInfo {
+                name: Some(name.to_string()),
+                symbol: Some(symbol.to_string()),
+            };
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, expected);
+        }
+
+        let msg = ExecuteMsg::UpdateTokenInfo {
+            name: None,
+            symbol: Some("FX".to_string()),
         };
-        let res = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(
-            query_token_info(deps.as_ref()).unwrap(),
-            TokenInfoResponse {
-                name: "Bash Shell".to_string(),
-                symbol: "BASH".to_string(),
-                decimals: 6,
-                total_supply: amount1 + amount2,
-            }
-        );
-        assert_eq!(get_balance(deps.as_ref(), addr1), amount1);
-        assert_eq!(get_balance(deps.as_ref(), addr2), amount2);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let token_info = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(token_info.name, "Fixed Name");
+        assert_eq!(token_info.symbol, "FX");
     }
 
     #[test]
-    fn queries_work() {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
-        let addr1 = String::from("addr0001");
-        let amount1 = Uint128::from(12340000u128);
+    fn vesting_reports_delegated_tokens() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user1 = String::from("user1");
+        let user2 = String::from("user2");
+        _do_instantiate(
+            deps.as_mut(),
+            &user1,
+            Uint128::new(10_000),
+            Some(MinterInfo {
+                minter: minter.clone(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            Some(mock_info(&user1, &[])),
+        );
+        let msg = ExecuteMsg::UpdateStakingAddress { address: staking };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
 
-        let expected = do_instantiate(deps.as_mut(), &addr1, amount1);
+        // user2 gets 4_000 locked and 2_000 liquid tokens
+        let start = mock_env().block.time.seconds();
+        let msg = ExecuteMsg::TransferVesting {
+            recipient: user2.clone(),
+            amount: Uint128::new(4_000),
+            schedule: Curve::saturating_linear((start + 1000, 4_000), (start + 2000, 0)),
+            memo: None,
+            notify: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: user2.clone(),
+            amount: Uint128::new(2_000),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap();
+        let delegate = |amount| ExecuteMsg::Delegate {
+            amount: Uint128::new(amount),
+            msg: Binary::default(),
+        };
+        let vesting = |deps: Deps| query_vesting(deps, mock_env(), user2.clone()).unwrap();
 
-        // check meta query
-        let loaded = query_token_info(deps.as_ref()).unwrap();
-        assert_eq!(expected, loaded);
-
-        let _info = mock_info("test", &[]);
-        let env = mock_env();
-        // check balance query (full)
-        let data = query(
-            deps.as_ref(),
-            env.clone(),
-            QueryMsg::Balance { address: addr1 },
+        // delegating less than the locked amount
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&user2, &[]),
+            delegate(1_000),
         )
         .unwrap();
-        let loaded: BalanceResponse = from_binary(&data).unwrap();
-        assert_eq!(loaded.balance, amount1);
+        let res = vesting(deps.as_ref());
+        assert_eq!(res.locked, Uint128::new(4_000));
+        assert_eq!(res.delegated, Uint128::new(1_000));
+        assert_eq!(res.effectively_locked, Uint128::new(3_000));
+
+        // only the liquid part of the balance can be moved
+        let transfer = |amount| ExecuteMsg::Transfer {
+            recipient: user1.clone(),
+            amount: Uint128::new(amount),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&user2, &[]),
+            transfer(2_001),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CantMoveVestingTokens {
+                effectively_locked: Uint128::new(3_000)
+            }
+        );
 
-        // check balance query (empty)
-        let data = query(
-            deps.as_ref(),
-            env,
-            QueryMsg::Balance {
-                address: String::from("addr0002"),
-            },
+        // delegating more than the locked amount
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&user2, &[]),
+            delegate(4_000),
         )
         .unwrap();
-        let loaded: BalanceResponse = from_binary(&data).unwrap();
-        assert_eq!(loaded.balance, Uint128::zero());
+        let res = vesting(deps.as_ref());
+        assert_eq!(res.locked, Uint128::new(4_000));
+        assert_eq!(res.delegated, Uint128::new(5_000));
+        assert_eq!(res.effectively_locked, Uint128::zero());
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&user2, &[]),
+            transfer(1_000),
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &user2), Uint128::zero());
     }
 
     #[test]
-    fn transfer() {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
-        let addr1 = String::from("addr0001");
-        let addr2 = String::from("addr0002");
-        let amount1 = Uint128::from(12340000u128);
-        let transfer = Uint128::from(76543u128);
-        let too_much = Uint128::from(12340321u128);
+    fn circulating_supply_excludes_staked_and_vesting() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user1 = String::from("user1");
+        let user2 = String::from("user2");
+        _do_instantiate(
+            deps.as_mut(),
+            &user1,
+            Uint128::new(10_000),
+            Some(MinterInfo {
+                minter: minter.clone(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            Some(mock_info(&user1, &[])),
+        );
+        let circulating = |deps: Deps| {
+            query_circulating_supply(deps, mock_env())
+                .unwrap()
+                .circulating_supply
+        };
+        assert_eq!(circulating(deps.as_ref()), Uint128::new(10_000));
 
-        do_instantiate(deps.as_mut(), &addr1, amount1);
+        // user2 gets 4_000 tokens, all of them still locked
+        let start = mock_env().block.time.seconds();
+        let msg = ExecuteMsg::TransferVesting {
+            recipient: user2.clone(),
+            amount: Uint128::new(4_000),
+            schedule: Curve::saturating_linear((start + 1000, 4_000), (start + 2000, 0)),
+            memo: None,
+            notify: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap();
+        assert_eq!(circulating(deps.as_ref()), Uint128::new(6_000));
 
-        // cannot transfer nothing
-        let info = mock_info(addr1.as_ref(), &[]);
-        let env = mock_env();
-        let msg = ExecuteMsg::Transfer {
-            recipient: addr2.clone(),
-            amount: Uint128::zero(),
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: staking.clone(),
         };
-        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
-        assert_eq!(err, ContractError::InvalidZeroAmount {});
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
 
-        // cannot send more than we have
-        let info = mock_info(addr1.as_ref(), &[]);
-        let env = mock_env();
-        let msg = ExecuteMsg::Transfer {
-            recipient: addr2.clone(),
-            amount: too_much,
+        // both delegate, user2 delegates vesting tokens
+        for (user, amount) in [(&user1, 2_000u128), (&user2, 1_000u128)] {
+            let msg = ExecuteMsg::Delegate {
+                amount: Uint128::new(amount),
+                msg: Binary::default(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(user, &[]), msg).unwrap();
+        }
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(3_000));
+
+        // 10_000 total - 3_000 staked - (4_000 locked - 1_000 of those delegated)
+        // leaves only user1's remaining balance in circulation
+        assert_eq!(circulating(deps.as_ref()), Uint128::new(4_000));
+        assert_eq!(get_balance(deps.as_ref(), &user1), Uint128::new(4_000));
+
+        // once everything is vested, user2's balance circulates as well
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = query_circulating_supply(deps.as_ref(), env).unwrap();
+        assert_eq!(res.circulating_supply, Uint128::new(7_000));
+    }
+
+    #[test]
+    fn total_locked_follows_vesting_and_delegations() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user1 = String::from("user1");
+        let users = [String::from("user2"), String::from("user3")];
+        _do_instantiate(
+            deps.as_mut(),
+            &user1,
+            Uint128::new(10_000),
+            Some(MinterInfo {
+                minter: minter.clone(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            Some(mock_info(&user1, &[])),
+        );
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: staking.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        let start = mock_env().block.time.seconds();
+        let at = |seconds| {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(start + seconds);
+            env
+        };
+        // the stored total against going through all accounts
+        let assert_locked = |deps: Deps, seconds| {
+            let expected: Uint128 = users
+                .iter()
+                .map(|user| {
+                    let locked = query_vesting(deps, at(seconds), user.clone())
+                        .unwrap()
+                        .locked;
+                    let delegated = DELEGATED
+                        .may_load(deps.storage, &Addr::unchecked(user))
+                        .unwrap()
+                        .unwrap_or_default();
+                    locked.saturating_sub(delegated)
+                })
+                .sum();
+            let total = TOTAL_LOCKED.load(deps.storage).unwrap();
+            assert_eq!(total.value(start + seconds), expected, "at {}", seconds);
         };
-        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
-        assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
 
-        // cannot send from empty account
-        let info = mock_info(addr2.as_ref(), &[]);
-        let env = mock_env();
-        let msg = ExecuteMsg::Transfer {
-            recipient: addr1.clone(),
-            amount: transfer,
+        let schedules = [
+            Curve::saturating_linear((start + 1_000, 4_000), (start + 2_000, 0)),
+            Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: vec![
+                    (start, Uint128::new(3_000)),
+                    (start + 500, Uint128::new(1_000)),
+                    (start + 1_500, Uint128::zero()),
+                ],
+            }),
+        ];
+        for (user, schedule) in users.iter().zip(schedules) {
+            let msg = ExecuteMsg::TransferVesting {
+                recipient: user.clone(),
+                amount: schedule.value(start),
+                schedule,
+                memo: None,
+                notify: false,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap();
+        }
+        for seconds in [0, 250, 500, 1_000, 1_500, 2_000] {
+            assert_locked(deps.as_ref(), seconds);
+        }
+
+        // delegating moves the lock out of the balance, undelegating back in
+        for (user, amount) in users.iter().zip([1_000u128, 2_000]) {
+            let msg = ExecuteMsg::Delegate {
+                amount: Uint128::new(amount),
+                msg: Binary::default(),
+            };
+            execute(deps.as_mut(), at(100), mock_info(user, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::Undelegate {
+            recipient: users[1].clone(),
+            amount: Uint128::new(500),
+            delegator: None,
         };
-        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
-        assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+        execute(deps.as_mut(), at(200), mock_info(&staking, &[]), msg).unwrap();
+        for seconds in [200, 250, 500, 1_000, 1_500, 1_750, 2_000] {
+            assert_locked(deps.as_ref(), seconds);
+        }
 
-        // valid transfer
-        let info = mock_info(addr1.as_ref(), &[]);
-        let env = mock_env();
-        let msg = ExecuteMsg::Transfer {
-            recipient: addr2.clone(),
-            amount: transfer,
+        // extending a schedule replaces its lock
+        let msg = ExecuteMsg::ExtendVesting {
+            address: users[0].clone(),
+            extend_by_seconds: 1_000,
         };
-        let res = execute(deps.as_mut(), env, info, msg).unwrap();
-        assert_eq!(res.messages.len(), 0);
+        execute(deps.as_mut(), at(300), mock_info(&user1, &[]), msg).unwrap();
+        for seconds in [300, 1_000, 2_000, 2_500, 3_000] {
+            assert_locked(deps.as_ref(), seconds);
+        }
 
-        let remainder = amount1.checked_sub(transfer).unwrap();
-        assert_eq!(get_balance(deps.as_ref(), addr1), remainder);
-        assert_eq!(get_balance(deps.as_ref(), addr2), transfer);
+        // once everything is vested and cleared, nothing is left of the schedules
+        let msg = ExecuteMsg::ClearExpiredVesting {
+            addresses: users.to_vec(),
+        };
+        let res = execute(deps.as_mut(), at(3_000), mock_info(&user1, &[]), msg).unwrap();
+        assert_eq!(res.attributes[1], attr("cleared", "2"));
         assert_eq!(
-            query_token_info(deps.as_ref()).unwrap().total_supply,
-            amount1
+            TOTAL_LOCKED.load(&deps.storage).unwrap(),
+            Curve::constant(0)
         );
     }
 
     #[test]
-    fn transfer_vesting() {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
-        let addr1 = String::from("addr0001");
-        let addr2 = String::from("addr0002");
-        let addr3 = String::from("addr0003");
-        let amount1 = Uint128::from(150_000u128);
-
-        // Lets make addr1 the admin of the contract
-        let info = mock_info(addr1.as_ref(), &coins(amount1.u128(), "AUTO"));
-        _do_instantiate(deps.as_mut(), &addr1, amount1, None, Some(info.clone()));
+    fn undelegate_vesting() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user = String::from("user1");
+        do_instantiate_with_minter(deps.as_mut(), &user, Uint128::new(10_000), &minter, None);
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: staking.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Delegate {
+            amount: Uint128::new(6_000),
+            msg: Binary::default(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user, &[]), msg).unwrap();
 
         let start = mock_env().block.time.seconds();
-        let transfer = Uint128::from(100_000u128);
-        // curve will be half-way through (at 40_000 locked) when we call
-        let schedule = Curve::saturating_linear((start - 4000, 80_000), (start + 4000, 0));
-
-        // send vesting tokens
-        let env = mock_env();
-        let msg = ExecuteMsg::TransferVesting {
-            recipient: addr2.clone(),
-            amount: transfer,
-            schedule: schedule.clone(),
+        let msg = ExecuteMsg::UndelegateVesting {
+            recipient: user.clone(),
+            amount: Uint128::new(4_000),
+            schedule: Curve::saturating_linear((start, 4_000), (start + 1000, 0)),
         };
-        execute(deps.as_mut(), env, info, msg).unwrap();
+        // only the staking contract can undelegate
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&user, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::UnauthorizedUndelegate {});
 
-        // ensure we see this here
-        let vesting = query_vesting(deps.as_ref(), mock_env(), addr2.clone()).unwrap();
-        assert_eq!(vesting.locked, Uint128::new(40_000));
-        assert_eq!(vesting.schedule.unwrap(), schedule);
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&staking, &[]), msg).unwrap();
+        assert_eq!(res.attributes[1], attr("type", "vesting"));
+        assert_eq!(get_balance(deps.as_ref(), &user), Uint128::new(8_000));
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(2_000));
+        assert_eq!(
+            query_delegated(deps.as_ref(), user.clone())
+                .unwrap()
+                .delegated,
+            Uint128::new(2_000)
+        );
+        let vesting = query_vesting(deps.as_ref(), mock_env(), user.clone()).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(4_000));
 
-        // and not on the original account
-        let non = query_vesting(deps.as_ref(), mock_env(), addr1.clone()).unwrap();
-        assert_eq!(non.locked, Uint128::zero());
-        assert_eq!(non.schedule, None);
+        // schedule has to vest the undelegated amount
+        let msg = ExecuteMsg::UndelegateVesting {
+            recipient: user,
+            amount: Uint128::new(1_000),
+            schedule: Curve::saturating_linear((start, 2_000), (start + 1000, 0)),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&staking, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::VestsMoreThanSent);
+    }
 
-        // we can send some from the new account
-        let info = mock_info(addr2.as_ref(), &[]);
-        let msg = ExecuteMsg::Transfer {
-            recipient: addr3.clone(),
-            amount: Uint128::new(45_000),
+    #[test]
+    fn undelegate_vesting_simplifies_complex_schedule() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user = String::from("user1");
+        do_instantiate_with_minter(deps.as_mut(), &user, Uint128::new(10_000), &minter, None);
+        let msgs = [
+            ExecuteMsg::UpdateStakingAddress {
+                address: staking.clone(),
+            },
+            ExecuteMsg::SetComplexityTier {
+                address: user.clone(),
+                max_complexity: 4,
+            },
+            ExecuteMsg::Mint {
+                recipient: "creator".to_owned(),
+                amount: Uint128::new(1_000),
+            },
+        ];
+        for msg in msgs {
+            execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::Delegate {
+            amount: Uint128::new(6_000),
+            msg: Binary::default(),
         };
-        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(&user, &[]), msg).unwrap();
 
-        // but vesting schedule will get us next time
-        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
-        assert_eq!(err, ContractError::CantMoveVestingTokens);
+        // every claim from the staking contract vests on its own schedule
+        let start = mock_env().block.time.seconds();
+        let schedules: Vec<_> = (0..3)
+            .map(|i| Curve::saturating_linear((start + i * 200, 1_000), (start + i * 200 + 100, 0)))
+            .collect();
+        let mut exact: Option<Curve> = None;
+        for (i, schedule) in schedules.into_iter().enumerate() {
+            exact = Some(match exact {
+                Some(exact) => exact.combine(&schedule).unwrap(),
+                None => schedule.clone(),
+            });
+            let msg = ExecuteMsg::UndelegateVesting {
+                recipient: user.clone(),
+                amount: Uint128::new(1_000),
+                schedule,
+            };
+            let res = execute(deps.as_mut(), mock_env(), mock_info(&staking, &[]), msg).unwrap();
+            // only the third one exceeds the complexity limit
+            let simplified = res
+                .attributes
+                .iter()
+                .any(|a| a == attr("vesting_simplified", "true"));
+            assert_eq!(simplified, i == 2);
+        }
+        let exact = exact.unwrap();
+        assert_eq!(exact.size(), 6);
 
-        // after a short wait, we can send more
-        let mut env = mock_env();
-        env.block.time = env.block.time.plus_seconds(3000);
-        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        // the simplified schedule never locks less than the exact one and still fully vests
+        let schedule = query_vesting(deps.as_ref(), mock_env(), user.clone())
+            .unwrap()
+            .schedule
+            .unwrap();
+        assert_eq!(schedule.size(), 4);
+        for x in (start..start + 600).step_by(10) {
+            assert!(schedule.value(x) >= exact.value(x));
+        }
+        assert_eq!(schedule.value(start + 500), Uint128::zero());
 
-        // ensure the balances (2 transfers)
-        assert_eq!(
-            get_balance(deps.as_ref(), addr2.clone()),
-            Uint128::new(10_000)
-        );
-        assert_eq!(
-            get_balance(deps.as_ref(), addr3.clone()),
-            Uint128::new(90_000)
-        );
-        // and vesting
-        let vesting = query_vesting(deps.as_ref(), env.clone(), addr2.clone()).unwrap();
-        assert_eq!(vesting.locked, Uint128::new(10_000));
-        assert_eq!(vesting.schedule.unwrap(), schedule);
+        // the account is still usable
+        assert_eq!(get_balance(deps.as_ref(), &user), Uint128::new(7_000));
+        let msg = ExecuteMsg::Transfer {
+            recipient: "creator".to_owned(),
+            amount: Uint128::new(4_000),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user, &[]), msg).unwrap();
 
-        // add more vesting tokens
-        let admin = mock_info(addr1.as_ref(), &coins(amount1.u128(), "AUTO"));
-        let now = env.block.time.seconds();
-        let schedule2 = Curve::saturating_linear((now, 50_000), (now + 1200, 0));
+        // vesting transfers initiated by users still fail instead
         let msg = ExecuteMsg::TransferVesting {
-            recipient: addr2.clone(),
-            amount: Uint128::new(50_000), // all remaining funds
-            schedule: schedule2.clone(),
+            recipient: user.clone(),
+            amount: Uint128::new(1_000),
+            schedule: Curve::saturating_linear((start + 1000, 1_000), (start + 1100, 0)),
+            memo: None,
+            notify: false,
         };
-        execute(deps.as_mut(), env.clone(), admin, msg).unwrap();
-
-        // ensure the balance
-        assert_eq!(
-            get_balance(deps.as_ref(), addr2.clone()),
-            Uint128::new(60_000)
-        );
-        // and vesting
-        let vesting = query_vesting(deps.as_ref(), env.clone(), addr2.clone()).unwrap();
-        assert_eq!(vesting.locked, Uint128::new(60_000));
-        assert_eq!(vesting.schedule.unwrap(), schedule.combine(&schedule2));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Curve(CurveError::TooComplex));
 
-        // go past the end of the vesting period
-        env.block.time = env.block.time.plus_seconds(1200);
+        // everything can be moved once vested
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(500);
         let msg = ExecuteMsg::Transfer {
-            recipient: addr3.clone(),
-            amount: Uint128::new(1_000),
+            recipient: "creator".to_owned(),
+            amount: Uint128::new(3_000),
         };
-        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-
-        // ensure the balances (2 transfers)
-        assert_eq!(
-            get_balance(deps.as_ref(), addr2.clone()),
-            Uint128::new(59_000)
-        );
-        assert_eq!(get_balance(deps.as_ref(), addr3), Uint128::new(91_000));
-        // and vesting deleted
-        let vesting = query_vesting(deps.as_ref(), env, addr2).unwrap();
-        assert_eq!(vesting.locked, Uint128::new(0));
-        assert_eq!(vesting.schedule, None);
+        execute(deps.as_mut(), env, mock_info(&user, &[]), msg).unwrap();
     }
 
     #[test]
-    fn transfer_vesting_error_cases() {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
-        let addr1 = String::from("addr0001");
-        let addr2 = String::from("addr0002");
-        let addr3 = String::from("addr0003");
-        let amount1 = Uint128::from(250_000u128);
+    fn delegate_eligible() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user = String::from("user1");
+        do_instantiate_with_minter(deps.as_mut(), &user, Uint128::new(10_000), &minter, None);
+        let msg = ExecuteMsg::UpdateStakingAddress { address: staking };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+
+        let delegate = |deps: DepsMut, amount: u128| {
+            let msg = ExecuteMsg::Delegate {
+                amount: Uint128::new(amount),
+                msg: Binary::default(),
+            };
+            execute(deps, mock_env(), mock_info(&user, &[]), msg)
+        };
+        let eligible = |deps: Deps| {
+            let res = query_delegate_eligible(deps, mock_env(), user.clone()).unwrap();
+            // holdings never change by delegating
+            assert_eq!(res.balance, Uint128::new(10_000));
+            assert_eq!(res.eligible + res.already_delegated, res.balance);
+            (res.eligible.u128(), res.already_delegated.u128())
+        };
 
-        let info = mock_info(addr1.as_ref(), &coins(amount1.u128(), "AUTO"));
-        _do_instantiate(deps.as_mut(), &addr1, amount1, None, Some(info.clone()));
+        // no delegation
+        assert_eq!(eligible(deps.as_ref()), (10_000, 0));
 
-        let start = mock_env().block.time.seconds();
-        let end = start + 30 * 86_400;
-        let transfer = Uint128::from(100_000u128);
-        // curve will be half-way through (at 40_000 locked) when we call
-        let schedule = Curve::saturating_linear((start - 4000, 80_000), (start + 4000, 0));
+        // partial delegation
+        delegate(deps.as_mut(), 4_000).unwrap();
+        assert_eq!(eligible(deps.as_ref()), (6_000, 4_000));
+        let err = delegate(deps.as_mut(), 6_001).unwrap_err();
+        assert_eq!(err, ContractError::NotEnoughToDelegate);
 
-        // send vesting tokens works fine
-        let msg = ExecuteMsg::TransferVesting {
-            recipient: addr2,
-            amount: transfer,
-            schedule,
+        // fully delegated
+        delegate(deps.as_mut(), 6_000).unwrap();
+        assert_eq!(eligible(deps.as_ref()), (0, 10_000));
+    }
+
+    #[test]
+    fn delegate_eligible_excludes_reserved() {
+        let mut deps = mock_dependencies();
+        let user = String::from("user1");
+        do_instantiate(deps.as_mut(), &user, Uint128::new(10_000));
+        let msg = ExecuteMsg::Reserve {
+            spender: "spender".to_owned(),
+            amount: Uint128::new(3_000),
+            expires: Expiration::AtHeight(mock_env().block.height + 10),
         };
-        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(&user, &[]), msg).unwrap();
 
-        // Unauthorized
+        let res = query_delegate_eligible(deps.as_ref(), mock_env(), user.clone()).unwrap();
+        assert_eq!(res.eligible, Uint128::new(7_000));
+        assert_eq!(res.balance, Uint128::new(10_000));
+
+        // expired reservations no longer count
+        let mut env = mock_env();
+        env.block.height += 10;
+        let res = query_delegate_eligible(deps.as_ref(), env, user).unwrap();
+        assert_eq!(res.eligible, Uint128::new(10_000));
+    }
+
+    #[test]
+    fn undelegatable_amount() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user = String::from("user1");
+        do_instantiate_with_minter(deps.as_mut(), &user, Uint128::new(10_000), &minter, None);
+
+        // fails as long as there is no staking address
+        query_undelegatable_amount(deps.as_ref(), mock_env(), None).unwrap_err();
+
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: staking.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Delegate {
+            amount: Uint128::new(4_000),
+            msg: Binary::default(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user, &[]), msg).unwrap();
+
+        let res = query_undelegatable_amount(deps.as_ref(), mock_env(), None).unwrap();
+        assert_eq!(res.staking, Addr::unchecked(&staking));
+        assert_eq!(res.amount, Uint128::new(4_000));
+        let res =
+            query_undelegatable_amount(deps.as_ref(), mock_env(), Some(user.clone())).unwrap();
+        assert_eq!(res.amount, Uint128::new(6_000));
+
+        // tokens locked on the staking address can not be undelegated
+        save_vesting(
+            deps.as_mut().storage,
+            &Addr::unchecked(&staking),
+            &Curve::constant(1_500),
+            mock_env().block.time.seconds(),
+        )
+        .unwrap();
+        let res = query_undelegatable_amount(deps.as_ref(), mock_env(), None).unwrap();
+        assert_eq!(res.amount, Uint128::new(2_500));
+
+        let undelegate = |deps: DepsMut, amount: u128| {
+            let msg = ExecuteMsg::Undelegate {
+                recipient: user.clone(),
+                amount: Uint128::new(amount),
+                delegator: None,
+            };
+            execute(deps, mock_env(), mock_info(&staking, &[]), msg)
+        };
+        undelegate(deps.as_mut(), 2_500).unwrap();
+        let res = query_undelegatable_amount(deps.as_ref(), mock_env(), None).unwrap();
+        assert_eq!(res.amount, Uint128::zero());
+        undelegate(deps.as_mut(), 1).unwrap_err();
+    }
+
+    #[test]
+    fn reconcile_delegation_state() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user1 = String::from("user1");
+        let user2 = String::from("user2");
+        do_instantiate_with_minter(deps.as_mut(), &user1, Uint128::new(10_000), &minter, None);
+        let msg = ExecuteMsg::Transfer {
+            recipient: user2.clone(),
+            amount: Uint128::new(4_000),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap();
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: staking.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+
+        for (user, amount) in [(&user1, 3_000u128), (&user2, 1_000u128)] {
+            let msg = ExecuteMsg::Delegate {
+                amount: Uint128::new(amount),
+                msg: Binary::default(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(user, &[]), msg).unwrap();
+        }
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(4_000));
+
+        // nothing changes in normal operation
+        let msg = ExecuteMsg::ReconcileDelegationState { limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "reconcile_delegation_state"),
+                attr("old_staking_balance", "4000"),
+                attr("new_staking_balance", "4000"),
+            ]
+        );
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(4_000));
+
+        // artificially create a mismatch
+        BALANCES
+            .save(
+                deps.as_mut().storage,
+                &Addr::unchecked(&staking),
+                &Uint128::new(2_500),
+                mock_env().block.height,
+            )
+            .unwrap();
+
+        // only minter can reconcile
+        let msg = ExecuteMsg::ReconcileDelegationState { limit: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let msg = ExecuteMsg::ReconcileDelegationState { limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "reconcile_delegation_state"),
+                attr("old_staking_balance", "2500"),
+                attr("new_staking_balance", "4000"),
+            ]
+        );
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(4_000));
+        assert_eq!(
+            query_delegated(deps.as_ref(), user1).unwrap().delegated,
+            Uint128::new(3_000)
+        );
+        // the repaired shortfall is added to the supply
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(11_500)
+        );
+    }
+
+    #[test]
+    fn reconcile_keeps_staking_rewards() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user1 = String::from("user1");
+        do_instantiate_with_minter(deps.as_mut(), &user1, Uint128::new(10_000), &minter, None);
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: staking.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Delegate {
+            amount: Uint128::new(3_000),
+            msg: Binary::default(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap();
+        // rewards paid to the staking contract in the staked token
+        let msg = ExecuteMsg::Transfer {
+            recipient: staking.clone(),
+            amount: Uint128::new(1_000),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(4_000));
+
+        let msg = ExecuteMsg::ReconcileDelegationState { limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "reconcile_delegation_state"),
+                attr("old_staking_balance", "4000"),
+                attr("new_staking_balance", "4000"),
+            ]
+        );
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(4_000));
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(10_000)
+        );
+        let resp = query_staking_address(deps.as_ref()).unwrap();
+        assert_eq!(resp.total_delegated, Uint128::new(3_000));
+        assert!(resp.consistent);
+    }
+
+    #[test]
+    fn reconcile_in_pages() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let users = ["user1", "user2", "user3"];
+        do_instantiate_with_minter(deps.as_mut(), users[0], Uint128::new(10_000), &minter, None);
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: staking.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        for user in &users[1..] {
+            let msg = ExecuteMsg::Transfer {
+                recipient: user.to_string(),
+                amount: Uint128::new(2_000),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(users[0], &[]), msg).unwrap();
+        }
+        let delegate = |deps: DepsMut, user: &str, amount: u128| {
+            let msg = ExecuteMsg::Delegate {
+                amount: Uint128::new(amount),
+                msg: Binary::default(),
+            };
+            execute(deps, mock_env(), mock_info(user, &[]), msg).unwrap();
+        };
+        delegate(deps.as_mut(), users[0], 3_000);
+        delegate(deps.as_mut(), users[1], 1_000);
+        delegate(deps.as_mut(), users[2], 500);
+        BALANCES
+            .save(
+                deps.as_mut().storage,
+                &Addr::unchecked(&staking),
+                &Uint128::new(2_500),
+                mock_env().block.height,
+            )
+            .unwrap();
+
+        let reconcile = |deps: DepsMut| {
+            let msg = ExecuteMsg::ReconcileDelegationState { limit: Some(1) };
+            execute(deps, mock_env(), mock_info(&minter, &[]), msg).unwrap()
+        };
+        let res = reconcile(deps.as_mut());
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "reconcile_delegation_state"),
+                attr("reconciled_until", users[0]),
+            ]
+        );
+        // nothing is repaired before all delegations are summed up
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(2_500));
+
+        // delegations change in between, both before and after the cursor
+        delegate(deps.as_mut(), users[0], 1_000);
+        delegate(deps.as_mut(), users[2], 500);
+
+        let res = reconcile(deps.as_mut());
+        assert_eq!(res.attributes[1], attr("reconciled_until", users[1]));
+        let res = reconcile(deps.as_mut());
+        assert_eq!(res.attributes[1], attr("reconciled_until", users[2]));
+        let res = reconcile(deps.as_mut());
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "reconcile_delegation_state"),
+                attr("old_staking_balance", "4000"),
+                attr("new_staking_balance", "6000"),
+            ]
+        );
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(6_000));
+        let resp = query_staking_address(deps.as_ref()).unwrap();
+        assert_eq!(resp.total_delegated, Uint128::new(6_000));
+        assert!(resp.consistent);
+
+        // a new run starts from the beginning
+        let res = reconcile(deps.as_mut());
+        assert_eq!(res.attributes[1], attr("reconciled_until", users[0]));
+    }
+
+    #[test]
+    fn reconcile_respects_cap() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user1 = String::from("user1");
+        do_instantiate_with_minter(
+            deps.as_mut(),
+            &user1,
+            Uint128::new(10_000),
+            &minter,
+            Some(Uint128::new(11_000)),
+        );
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: staking.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Delegate {
+            amount: Uint128::new(3_000),
+            msg: Binary::default(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap();
+
+        let set_staking_balance = |deps: DepsMut, amount: u128| {
+            BALANCES
+                .save(
+                    deps.storage,
+                    &Addr::unchecked(&staking),
+                    &Uint128::new(amount),
+                    mock_env().block.height,
+                )
+                .unwrap();
+        };
+        set_staking_balance(deps.as_mut(), 1_500);
+        let msg = ExecuteMsg::ReconcileDelegationState { limit: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::CannotExceedCap {});
+
+        set_staking_balance(deps.as_mut(), 2_000);
+        let msg = ExecuteMsg::ReconcileDelegationState { limit: None };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &staking), Uint128::new(3_000));
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(11_000)
+        );
+    }
+
+    #[test]
+    fn staking_address_reports_delegation_totals() {
+        let mut deps = mock_dependencies();
+        let minter = String::from("minter");
+        let staking = String::from("staking");
+        let user1 = String::from("user1");
+        let user2 = String::from("user2");
+        do_instantiate_with_minter(deps.as_mut(), &user1, Uint128::new(10_000), &minter, None);
+        let msg = ExecuteMsg::Transfer {
+            recipient: user2.clone(),
+            amount: Uint128::new(4_000),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&user1, &[]), msg).unwrap();
+
+        // nothing wired up yet
+        assert_eq!(
+            query_staking_address(deps.as_ref()).unwrap(),
+            StakingAddressResponse {
+                address: None,
+                total_delegated: Uint128::zero(),
+                staking_balance: Uint128::zero(),
+                consistent: true,
+            }
+        );
+
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: staking.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        let delegate = |deps: DepsMut, user: &str, amount: u128| {
+            let msg = ExecuteMsg::Delegate {
+                amount: Uint128::new(amount),
+                msg: Binary::default(),
+            };
+            execute(deps, mock_env(), mock_info(user, &[]), msg).unwrap();
+        };
+        let undelegate = |deps: DepsMut, user: &str, amount: u128| {
+            let msg = ExecuteMsg::Undelegate {
+                recipient: user.to_owned(),
+                amount: Uint128::new(amount),
+                delegator: None,
+            };
+            execute(deps, mock_env(), mock_info("staking", &[]), msg).unwrap();
+        };
+
+        delegate(deps.as_mut(), &user1, 3_000);
+        delegate(deps.as_mut(), &user2, 1_000);
+        assert_eq!(
+            query_staking_address(deps.as_ref()).unwrap(),
+            StakingAddressResponse {
+                address: Some(Addr::unchecked(&staking)),
+                total_delegated: Uint128::new(4_000),
+                staking_balance: Uint128::new(4_000),
+                consistent: true,
+            }
+        );
+
+        undelegate(deps.as_mut(), &user1, 1_000);
+        undelegate(deps.as_mut(), &user2, 1_000);
+        delegate(deps.as_mut(), &user2, 1_500);
+        let resp = query_staking_address(deps.as_ref()).unwrap();
+        assert_eq!(resp.total_delegated, Uint128::new(3_500));
+        assert_eq!(resp.staking_balance, Uint128::new(3_500));
+        assert!(resp.consistent);
+
+        // artificially create a mismatch
+        BALANCES
+            .save(
+                deps.as_mut().storage,
+                &Addr::unchecked(&staking),
+                &Uint128::new(2_500),
+                mock_env().block.height,
+            )
+            .unwrap();
+        let resp = query_staking_address(deps.as_ref()).unwrap();
+        assert_eq!(resp.total_delegated, Uint128::new(3_500));
+        assert_eq!(resp.staking_balance, Uint128::new(2_500));
+        assert!(!resp.consistent);
+
+        let msg = ExecuteMsg::ReconcileDelegationState { limit: None };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        let resp = query_staking_address(deps.as_ref()).unwrap();
+        assert_eq!(resp.staking_balance, Uint128::new(3_500));
+        assert!(resp.consistent);
+    }
+
+    #[test]
+    fn burns_reduce_cap() {
+        let mut deps = mock_dependencies();
+        let genesis = String::from("genesis");
+        let minter = String::from("minter");
+        let cap = Uint128::new(10_000);
+        _do_instantiate(
+            deps.as_mut(),
+            &genesis,
+            Uint128::new(8_000),
+            Some(MinterInfo {
+                minter: minter.clone(),
+                cap: constant_curve(Some(cap)),
+                burns_reduce_cap: true,
+            }),
+            None,
+        );
+
+        // burn some tokens
+        let info = mock_info(&genesis, &[]);
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(3_000),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // cap got lowered by the burned amount
+        let minter_info = query_minter(deps.as_ref(), mock_env()).unwrap().unwrap();
+        assert!(minter_info.burns_reduce_cap);
+        assert_eq!(minter_info.cap_reduction, Uint128::new(3_000));
+        assert_eq!(minter_info.current_cap, Some(Uint128::new(7_000)));
+
+        // trying to mint back up to the old cap fails
+        let info = mock_info(&minter, &[]);
+        let msg = ExecuteMsg::Mint {
+            recipient: genesis.clone(),
+            amount: Uint128::new(5_000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::CannotExceedCap {});
+
+        // but minting up to the reduced cap works
+        let msg = ExecuteMsg::Mint {
+            recipient: genesis.clone(),
+            amount: Uint128::new(2_000),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(7_000)
+        );
+
+        // after disabling it, burns no longer affect the cap
+        let msg = ExecuteMsg::UpdateMinter {
+            minter: minter.clone(),
+            burns_reduce_cap: Some(false),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(1_000),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&genesis, &[]), msg).unwrap();
+        let minter_info = query_minter(deps.as_ref(), mock_env()).unwrap().unwrap();
+        assert!(!minter_info.burns_reduce_cap);
+        assert_eq!(minter_info.cap_reduction, Uint128::new(3_000));
+        assert_eq!(minter_info.current_cap, Some(Uint128::new(7_000)));
+        let msg = ExecuteMsg::Mint {
+            recipient: genesis,
+            amount: Uint128::new(1_000),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn burn_from_reduces_cap() {
+        let mut deps = mock_dependencies();
+        let owner = String::from("owner");
+        let spender = String::from("spender");
+        let minter = String::from("minter");
+        _do_instantiate(
+            deps.as_mut(),
+            &owner,
+            Uint128::new(10_000),
+            Some(MinterInfo {
+                minter: minter.clone(),
+                cap: constant_curve(Some(Uint128::new(10_000))),
+                burns_reduce_cap: true,
+            }),
+            None,
+        );
+
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(4_000),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let msg = ExecuteMsg::BurnFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(4_000),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+
+        let minter_info = query_minter(deps.as_ref(), mock_env()).unwrap().unwrap();
+        assert_eq!(minter_info.cap_reduction, Uint128::new(4_000));
+        assert_eq!(minter_info.current_cap, Some(Uint128::new(6_000)));
+
+        // supply is at the reduced cap, so nothing more can be minted
+        let msg = ExecuteMsg::Mint {
+            recipient: owner,
+            amount: Uint128::new(1),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::CannotExceedCap {});
+    }
+
+    #[test]
+    fn supply_changes_are_reported() {
+        let mut deps = mock_dependencies();
+        let owner = String::from("owner");
+        let spender = String::from("spender");
+        let minter = String::from("minter");
+        do_instantiate_with_minter(
+            deps.as_mut(),
+            &owner,
+            Uint128::new(10_000),
+            &minter,
+            Some(Uint128::new(20_000)),
+        );
+        let supply_change = |op: &str, amount: u128, before: u128, after: u128| {
+            Event::new("supply_change")
+                .add_attribute("op", op)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("total_supply_before", before.to_string())
+                .add_attribute("total_supply_after", after.to_string())
+        };
+
+        let msg = ExecuteMsg::Mint {
+            recipient: owner.clone(),
+            amount: Uint128::new(3_000),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(res.attributes[3], attr("total_supply", "13000"));
+        assert_eq!(res.events, [supply_change("mint", 3_000, 10_000, 13_000)]);
+
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(1_000),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        assert_eq!(res.attributes[3], attr("total_supply", "12000"));
+        assert_eq!(res.events, [supply_change("burn", 1_000, 13_000, 12_000)]);
+
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(500),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let msg = ExecuteMsg::BurnFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(500),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(res.attributes[4], attr("total_supply", "11500"));
+        assert_eq!(
+            res.events,
+            [supply_change("burn_from", 500, 12_000, 11_500)]
+        );
+
+        // nothing is staked or vesting, so all tokens circulate
+        assert_eq!(
+            query_supply_info(deps.as_ref(), mock_env()).unwrap(),
+            SupplyInfoResponse {
+                total_supply: Uint128::new(11_500),
+                circulating_supply: Uint128::new(11_500),
+                mint_cap: Some(Uint128::new(20_000)),
+            }
+        );
+
+        // vesting tokens only circulate once they are released
+        let msg = ExecuteMsg::AllowVester {
+            address: owner.clone(),
+            policy_exempt: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let start = mock_env().block.time.seconds();
+        let msg = ExecuteMsg::TransferVesting {
+            recipient: "vester".to_string(),
+            amount: Uint128::new(2_000),
+            schedule: Curve::saturating_linear((start + 1_000, 2_000), (start + 2_000, 0)),
+            memo: None,
+            notify: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let circulating = |seconds| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            query_supply_info(deps.as_ref(), env)
+                .unwrap()
+                .circulating_supply
+                .u128()
+        };
+        assert_eq!(circulating(0), 9_500);
+        assert_eq!(circulating(1_500), 10_500);
+        assert_eq!(circulating(2_000), 11_500);
+    }
+
+    #[test]
+    fn instantiate_multiple_accounts() {
+        let mut deps = mock_dependencies();
+        let amount1 = Uint128::from(11223344u128);
+        let addr1 = String::from("addr0001");
+        let amount2 = Uint128::from(7890987u128);
+        let addr2 = String::from("addr0002");
+        let info = mock_info("creator", &[]);
+        let env = mock_env();
+
+        // Fails with duplicate addresses
+        let instantiate_msg = InstantiateMsg {
+            name: "Bash Shell".to_string(),
+            symbol: "BASH".to_string(),
+            decimals: 6,
+            initial_balances: vec![
+                InitBalance {
+                    address: addr1.clone(),
+                    amount: amount1,
+                    vesting: None,
+                    memo: None,
+                    relative_vesting: None,
+                },
+                InitBalance {
+                    address: addr1.clone(),
+                    amount: amount2,
+                    vesting: None,
+                    memo: None,
+                    relative_vesting: None,
+                },
+            ],
+            mint: None,
+            marketing: None,
+            allowed_vesters: None,
+            max_curve_complexity: 10,
+            seeding_open: false,
+        };
+        let err =
+            instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg).unwrap_err();
+        assert_eq!(err, ContractError::DuplicateInitialBalanceAddresses {});
+
+        // Works with unique addresses
+        let instantiate_msg = InstantiateMsg {
+            name: "Bash Shell".to_string(),
+            symbol: "BASH".to_string(),
+            decimals: 6,
+            initial_balances: vec![
+                InitBalance {
+                    address: addr1.clone(),
+                    amount: amount1,
+                    vesting: None,
+                    memo: None,
+                    relative_vesting: None,
+                },
+                InitBalance {
+                    address: addr2.clone(),
+                    amount: amount2,
+                    vesting: None,
+                    memo: None,
+                    relative_vesting: None,
+                },
+            ],
+            mint: None,
+            marketing: None,
+            allowed_vesters: None,
+            max_curve_complexity: 10,
+            seeding_open: false,
+        };
+        let res = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap(),
+            TokenInfoResponse {
+                name: "Bash Shell".to_string(),
+                symbol: "BASH".to_string(),
+                decimals: 6,
+                total_supply: amount1 + amount2,
+            }
+        );
+        assert_eq!(get_balance(deps.as_ref(), addr1), amount1);
+        assert_eq!(get_balance(deps.as_ref(), addr2), amount2);
+    }
+
+    #[test]
+    fn queries_work() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let addr1 = String::from("addr0001");
+        let amount1 = Uint128::from(12340000u128);
+
+        let expected = do_instantiate(deps.as_mut(), &addr1, amount1);
+
+        // check meta query
+        let loaded = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(expected, loaded);
+
+        let _info = mock_info("test", &[]);
+        let env = mock_env();
+        // check balance query (full)
+        let data = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Balance { address: addr1 },
+        )
+        .unwrap();
+        let loaded: BalanceResponse = from_binary(&data).unwrap();
+        assert_eq!(loaded.balance, amount1);
+
+        // check balance query (empty)
+        let data = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Balance {
+                address: String::from("addr0002"),
+            },
+        )
+        .unwrap();
+        let loaded: BalanceResponse = from_binary(&data).unwrap();
+        assert_eq!(loaded.balance, Uint128::zero());
+    }
+
+    #[test]
+    fn transfer() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let amount1 = Uint128::from(12340000u128);
+        let transfer = Uint128::from(76543u128);
+        let too_much = Uint128::from(12340321u128);
+
+        do_instantiate(deps.as_mut(), &addr1, amount1);
+
+        // cannot transfer nothing
+        let info = mock_info(addr1.as_ref(), &[]);
+        let env = mock_env();
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::zero(),
+        };
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidZeroAmount {});
+
+        // cannot send more than we have
+        let info = mock_info(addr1.as_ref(), &[]);
+        let env = mock_env();
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: too_much,
+        };
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+
+        // cannot send from empty account
+        let info = mock_info(addr2.as_ref(), &[]);
+        let env = mock_env();
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr1.clone(),
+            amount: transfer,
+        };
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+
+        // valid transfer
+        let info = mock_info(addr1.as_ref(), &[]);
+        let env = mock_env();
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: transfer,
+        };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(res.messages.len(), 0);
+
+        let remainder = amount1.checked_sub(transfer).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), addr1), remainder);
+        assert_eq!(get_balance(deps.as_ref(), addr2), transfer);
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            amount1
+        );
+    }
+
+    #[test]
+    fn balance_at_tracks_history() {
+        let mut deps = mock_dependencies();
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let minter = String::from("minter");
+        do_instantiate_with_minter(deps.as_mut(), &addr1, Uint128::new(1000), &minter, None);
+
+        let mut env = mock_env();
+        let start = env.block.height;
+
+        // transfer in the next block
+        env.block.height += 1;
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(300),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+
+        // mint and burn a few blocks later
+        env.block.height += 5;
+        let msg = ExecuteMsg::Mint {
+            recipient: addr2.clone(),
+            amount: Uint128::new(500),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+
+        let balance_at = |deps: Deps, addr: &str, height: u64| {
+            query_balance_at(deps, addr.to_string(), height)
+                .unwrap()
+                .balance
+                .u128()
+        };
+        let supply_at = |deps: Deps, height: u64| {
+            query_total_supply_at(deps, height)
+                .unwrap()
+                .total_supply
+                .u128()
+        };
+
+        // historical values are as of the end of the block
+        assert_eq!(balance_at(deps.as_ref(), &addr1, start), 1000);
+        assert_eq!(balance_at(deps.as_ref(), &addr2, start), 0);
+        assert_eq!(balance_at(deps.as_ref(), &addr1, start + 1), 700);
+        assert_eq!(balance_at(deps.as_ref(), &addr2, start + 1), 300);
+        assert_eq!(balance_at(deps.as_ref(), &addr1, start + 5), 700);
+        assert_eq!(balance_at(deps.as_ref(), &addr2, start + 5), 300);
+        assert_eq!(balance_at(deps.as_ref(), &addr1, start + 6), 600);
+        assert_eq!(balance_at(deps.as_ref(), &addr2, start + 6), 800);
+
+        assert_eq!(supply_at(deps.as_ref(), start), 1000);
+        assert_eq!(supply_at(deps.as_ref(), start + 5), 1000);
+        assert_eq!(supply_at(deps.as_ref(), start + 6), 1400);
+
+        // no history before instantiation
+        query_balance_at(deps.as_ref(), addr1, start - 1).unwrap_err();
+        query_total_supply_at(deps.as_ref(), start - 1).unwrap_err();
+    }
+
+    #[test]
+    fn balance_at_after_migration() {
+        let mut deps = mock_dependencies();
+        let addr1 = Addr::unchecked("addr0001");
+        let addr2 = Addr::unchecked("addr0002");
+
+        // set up state as it was before snapshotting
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let legacy_balances: cw_storage_plus::Map<&Addr, Uint128> =
+            cw_storage_plus::Map::new("balance");
+        legacy_balances
+            .save(&mut deps.storage, &addr1, &Uint128::new(1000))
+            .unwrap();
+        TOKEN_INFO
+            .save(
+                &mut deps.storage,
+                &TokenInfo {
+                    name: "Auto Gen".to_string(),
+                    symbol: "AUTO".to_string(),
+                    decimals: 3,
+                    total_supply: Uint128::new(1000),
+                    mint: Some(MinterData {
+                        minter: Addr::unchecked("minter"),
+                        cap: None,
+                        burns_reduce_cap: false,
+                        cap_reduction: Uint128::zero(),
+                    }),
+                },
+            )
+            .unwrap();
+
+        let mut env = mock_env();
+        let migrated = env.block.height;
+        let msg = MigrateMsg {
+            picewise_linear_curve: Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: vec![(0, Uint128::new(5000)), (100, Uint128::new(10000))],
+            }),
+        };
+        migrate(deps.as_mut(), env.clone(), msg).unwrap();
+
+        // accounts without changelog fall back to their current balance
+        let balance = query_balance_at(deps.as_ref(), addr1.to_string(), migrated).unwrap();
+        assert_eq!(balance.balance, Uint128::new(1000));
+        let supply = query_total_supply_at(deps.as_ref(), migrated).unwrap();
+        assert_eq!(supply.total_supply, Uint128::new(1000));
+        query_balance_at(deps.as_ref(), addr1.to_string(), migrated - 1).unwrap_err();
+
+        // the first change writes the changelog
+        env.block.height += 10;
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.to_string(),
+            amount: Uint128::new(400),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(addr1.as_str(), &[]),
+            msg,
+        )
+        .unwrap();
+
+        let balance = query_balance_at(deps.as_ref(), addr1.to_string(), migrated + 5).unwrap();
+        assert_eq!(balance.balance, Uint128::new(1000));
+        let balance = query_balance_at(deps.as_ref(), addr1.to_string(), env.block.height).unwrap();
+        assert_eq!(balance.balance, Uint128::new(600));
+        let balance = query_balance_at(deps.as_ref(), addr2.to_string(), env.block.height).unwrap();
+        assert_eq!(balance.balance, Uint128::new(400));
+    }
+
+    /// Charges storage accesses like the Cosmos SDK with its default `KVGasConfig`, counting
+    /// the gas of the snapshot changelogs and checkpoints separately
+    #[derive(Default)]
+    struct GasMeteredStorage {
+        inner: cosmwasm_std::testing::MockStorage,
+        changelog_gas: std::cell::Cell<u64>,
+        other_gas: std::cell::Cell<u64>,
+    }
+
+    impl GasMeteredStorage {
+        fn charge(&self, key: &[u8], gas: u64) {
+            let is_snapshot = [&b"__checkpoints"[..], &b"__changelog"[..]]
+                .iter()
+                .any(|ns| key.windows(ns.len()).any(|part| part == *ns));
+            let counter = if is_snapshot {
+                &self.changelog_gas
+            } else {
+                &self.other_gas
+            };
+            counter.set(counter.get() + gas);
+        }
+
+        fn take_gas(&self) -> (u64, u64) {
+            (self.changelog_gas.take(), self.other_gas.take())
+        }
+    }
+
+    impl Storage for GasMeteredStorage {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            let value = self.inner.get(key);
+            let len = key.len() + value.as_ref().map_or(0, Vec::len);
+            self.charge(key, 1000 + 3 * len as u64);
+            value
+        }
+
+        fn range<'a>(
+            &'a self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+        ) -> Box<dyn Iterator<Item = cosmwasm_std::Record> + 'a> {
+            Box::new(self.inner.range(start, end, order).map(|(key, value)| {
+                self.charge(&key, 30 + 3 * (key.len() + value.len()) as u64);
+                (key, value)
+            }))
+        }
+
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            self.charge(key, 2000 + 30 * (key.len() + value.len()) as u64);
+            self.inner.set(key, value)
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.charge(key, 1000);
+            self.inner.remove(key)
+        }
+    }
+
+    #[test]
+    fn snapshot_gas_of_transfers() {
+        let mut deps = mock_dependencies();
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        do_instantiate(deps.as_mut(), &addr1, Uint128::new(1000));
+        let mut storage = GasMeteredStorage {
+            inner: std::mem::take(&mut deps.storage),
+            ..Default::default()
+        };
+
+        let mut env = mock_env();
+        let transfer = |storage: &mut GasMeteredStorage, env: &Env| {
+            let deps = DepsMut {
+                storage,
+                api: &deps.api,
+                querier: cosmwasm_std::QuerierWrapper::new(&deps.querier),
+            };
+            let msg = ExecuteMsg::Transfer {
+                recipient: addr2.clone(),
+                amount: Uint128::new(100),
+            };
+            execute(deps, env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        };
+
+        // the first change of both balances in a block writes their changelog
+        env.block.height += 1;
+        transfer(&mut storage, &env);
+        let (changelog_gas, other_gas) = storage.take_gas();
+        println!("first transfer in a block: {changelog_gas} changelog gas, {other_gas} other gas");
+        assert_eq!((changelog_gas, other_gas), (9288, 14779));
+
+        // later ones in the same block only check it exists
+        transfer(&mut storage, &env);
+        let (changelog_gas, other_gas) = storage.take_gas();
+        println!("later transfer in a block: {changelog_gas} changelog gas, {other_gas} other gas");
+        assert_eq!((changelog_gas, other_gas), (2306, 12671));
+    }
+
+    #[test]
+    fn transfer_vesting() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let addr3 = String::from("addr0003");
+        let amount1 = Uint128::from(150_000u128);
+
+        // Lets make addr1 the admin of the contract
+        let info = mock_info(addr1.as_ref(), &coins(amount1.u128(), "AUTO"));
+        _do_instantiate(deps.as_mut(), &addr1, amount1, None, Some(info.clone()));
+
+        let start = mock_env().block.time.seconds();
+        let transfer = Uint128::from(100_000u128);
+        // curve will be half-way through (at 40_000 locked) when we call
+        let schedule = Curve::saturating_linear((start - 4000, 80_000), (start + 4000, 0));
+
+        // send vesting tokens
+        let env = mock_env();
+        let msg = ExecuteMsg::TransferVesting {
+            recipient: addr2.clone(),
+            amount: transfer,
+            schedule: schedule.clone(),
+            memo: None,
+            notify: false,
+        };
+        execute(deps.as_mut(), env, info, msg).unwrap();
+
+        // ensure we see this here
+        let vesting = query_vesting(deps.as_ref(), mock_env(), addr2.clone()).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(40_000));
+        assert_eq!(vesting.schedule.unwrap(), schedule);
+
+        // and not on the original account
+        let non = query_vesting(deps.as_ref(), mock_env(), addr1.clone()).unwrap();
+        assert_eq!(non.locked, Uint128::zero());
+        assert_eq!(non.schedule, None);
+
+        // we can send some from the new account
+        let info = mock_info(addr2.as_ref(), &[]);
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr3.clone(),
+            amount: Uint128::new(45_000),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+        // but vesting schedule will get us next time
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CantMoveVestingTokens {
+                effectively_locked: Uint128::new(40_000)
+            }
+        );
+
+        // after a short wait, we can send more
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(3000);
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // ensure the balances (2 transfers)
+        assert_eq!(
+            get_balance(deps.as_ref(), addr2.clone()),
+            Uint128::new(10_000)
+        );
+        assert_eq!(
+            get_balance(deps.as_ref(), addr3.clone()),
+            Uint128::new(90_000)
+        );
+        // and vesting
+        let vesting = query_vesting(deps.as_ref(), env.clone(), addr2.clone()).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(10_000));
+        assert_eq!(vesting.schedule.unwrap(), schedule);
+
+        // add more vesting tokens
+        let admin = mock_info(addr1.as_ref(), &coins(amount1.u128(), "AUTO"));
+        let now = env.block.time.seconds();
+        let schedule2 = Curve::saturating_linear((now, 50_000), (now + 1200, 0));
+        let msg = ExecuteMsg::TransferVesting {
+            recipient: addr2.clone(),
+            amount: Uint128::new(50_000), // all remaining funds
+            schedule: schedule2.clone(),
+            memo: None,
+            notify: false,
+        };
+        execute(deps.as_mut(), env.clone(), admin, msg).unwrap();
+
+        // ensure the balance
+        assert_eq!(
+            get_balance(deps.as_ref(), addr2.clone()),
+            Uint128::new(60_000)
+        );
+        // and vesting
+        let vesting = query_vesting(deps.as_ref(), env.clone(), addr2.clone()).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(60_000));
+        assert_eq!(
+            vesting.schedule.unwrap(),
+            schedule.combine(&schedule2).unwrap()
+        );
+
+        // go past the end of the vesting period
+        env.block.time = env.block.time.plus_seconds(1200);
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr3.clone(),
+            amount: Uint128::new(1_000),
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // ensure the balances (2 transfers)
+        assert_eq!(
+            get_balance(deps.as_ref(), addr2.clone()),
+            Uint128::new(59_000)
+        );
+        assert_eq!(get_balance(deps.as_ref(), addr3), Uint128::new(91_000));
+        // and vesting deleted
+        let vesting = query_vesting(deps.as_ref(), env, addr2).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(0));
+        assert_eq!(vesting.schedule, None);
+    }
+
+    #[test]
+    fn transfer_vesting_notify_ignored_for_non_contracts() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let amount1 = Uint128::from(150_000u128);
+
+        let info = mock_info(addr1.as_ref(), &[]);
+        _do_instantiate(deps.as_mut(), &addr1, amount1, None, Some(info.clone()));
+
+        let start = mock_env().block.time.seconds();
+        let schedule = Curve::saturating_linear((start, 100_000), (start + 4000, 0));
+        let msg = ExecuteMsg::TransferVesting {
+            recipient: addr2.clone(),
+            amount: Uint128::new(100_000),
+            schedule: schedule.clone(),
+            memo: None,
+            notify: true,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // the mock querier knows no contracts, so there is nobody to notify
+        assert_eq!(res.messages, vec![]);
+        assert_eq!(
+            get_balance(deps.as_ref(), addr2.clone()),
+            Uint128::new(100_000)
+        );
+        let vesting = query_vesting(deps.as_ref(), mock_env(), addr2).unwrap();
+        assert_eq!(vesting.schedule.unwrap(), schedule);
+    }
+
+    #[test]
+    fn transfer_vesting_error_cases() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let addr3 = String::from("addr0003");
+        let amount1 = Uint128::from(250_000u128);
+
+        let info = mock_info(addr1.as_ref(), &coins(amount1.u128(), "AUTO"));
+        _do_instantiate(deps.as_mut(), &addr1, amount1, None, Some(info.clone()));
+
+        let start = mock_env().block.time.seconds();
+        let end = start + 30 * 86_400;
+        let transfer = Uint128::from(100_000u128);
+        // curve will be half-way through (at 40_000 locked) when we call
+        let schedule = Curve::saturating_linear((start - 4000, 80_000), (start + 4000, 0));
+
+        // send vesting tokens works fine
+        let msg = ExecuteMsg::TransferVesting {
+            recipient: addr2,
+            amount: transfer,
+            schedule,
+            memo: None,
+            notify: false,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+        // Unauthorized
         let unauthorized_info = mock_info("addr3", &[]);
         let err = execute(deps.as_mut(), mock_env(), unauthorized_info, msg).unwrap_err();
         assert_eq!(err, ContractError::Unauthorized {});
 
-        // send with over-vesting curve
-        let over_vesting = ExecuteMsg::TransferVesting {
-            recipient: addr3.clone(),
-            amount: Uint128::new(10_000),
-            schedule: Curve::saturating_linear((start, 15_000), (end, 0)),
+        // send with over-vesting curve
+        let over_vesting = ExecuteMsg::TransferVesting {
+            recipient: addr3.clone(),
+            amount: Uint128::new(10_000),
+            schedule: Curve::saturating_linear((start, 15_000), (end, 0)),
+            memo: None,
+            notify: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), over_vesting).unwrap_err();
+        assert_eq!(err, ContractError::VestsMoreThanSent);
+
+        // send with curve that never hits 0
+        let never_vests = ExecuteMsg::TransferVesting {
+            recipient: addr3.clone(),
+            amount: Uint128::new(10_000),
+            schedule: Curve::saturating_linear((start, 10_000), (end, 1_000)),
+            memo: None,
+            notify: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), never_vests).unwrap_err();
+        assert_eq!(err, ContractError::NeverFullyVested);
+
+        // send with curve that never hits 0
+        let const_never_vests = ExecuteMsg::TransferVesting {
+            recipient: addr3.clone(),
+            amount: Uint128::new(10_000),
+            schedule: Curve::constant(2),
+            memo: None,
+            notify: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), const_never_vests).unwrap_err();
+        assert_eq!(err, ContractError::NeverFullyVested);
+
+        // fails with increasing curve
+        let increasing = ExecuteMsg::TransferVesting {
+            recipient: addr3.clone(),
+            amount: Uint128::new(10_000),
+            schedule: Curve::saturating_linear((start, 5_000), (end, 6_000)),
+            memo: None,
+            notify: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), increasing).unwrap_err();
+        assert_eq!(err, ContractError::Curve(CurveError::MonotonicIncreasing));
+
+        // fails with too complex curve
+        let amount = Uint128::new(10_000);
+        let complex = ExecuteMsg::TransferVesting {
+            recipient: addr3.clone(),
+            amount,
+            schedule: Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: (start..end)
+                    .map(|x| (x, amount))
+                    .chain(std::iter::once((end, Uint128::new(0)))) // fully vest
+                    .collect(),
+            }),
+            memo: None,
+            notify: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), complex).unwrap_err();
+        assert_eq!(err, ContractError::Curve(CurveError::TooComplex));
+
+        // works with almost too complex curve
+        let max_complexity = MAX_VESTING_COMPLEXITY.load(&deps.storage).unwrap();
+        let end = start + max_complexity as u64 - 1;
+        let almost_too_complex = ExecuteMsg::TransferVesting {
+            recipient: addr3.clone(),
+            amount,
+            schedule: Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: (start..end)
+                    .map(|x| (x, amount))
+                    .chain(std::iter::once((end, Uint128::new(0)))) // fully vest
+                    .collect(),
+            }),
+            memo: None,
+            notify: false,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), almost_too_complex).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // but fails when adding a simple curve if the combined curve becomes too complex
+        let simple = ExecuteMsg::TransferVesting {
+            recipient: addr3,
+            amount,
+            schedule: Curve::saturating_linear((end, amount.u128()), (end + 1, 0)),
+            memo: None,
+            notify: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, simple).unwrap_err();
+        assert_eq!(err, ContractError::Curve(CurveError::TooComplex));
+    }
+
+    fn batch(transfers: &[(&str, u128)]) -> Vec<BatchTransfer> {
+        transfers
+            .iter()
+            .map(|&(recipient, amount)| BatchTransfer {
+                recipient: recipient.to_string(),
+                amount: amount.into(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn transfer_batch() {
+        let mut deps = mock_dependencies();
+        let admin = "admin";
+        let addr1 = "addr0001";
+        let addr2 = "addr0002";
+        let addr3 = "addr0003";
+        let info = mock_info(admin, &[]);
+        _do_instantiate(
+            deps.as_mut(),
+            admin,
+            Uint128::new(150_000),
+            None,
+            Some(info.clone()),
+        );
+
+        // addr1 gets 100_000 tokens, 40_000 of them still locked
+        let start = mock_env().block.time.seconds();
+        let schedule = Curve::saturating_linear((start - 4000, 80_000), (start + 4000, 0));
+        let msg = ExecuteMsg::TransferVesting {
+            recipient: addr1.to_string(),
+            amount: Uint128::new(100_000),
+            schedule,
+            memo: None,
+            notify: false,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // each transfer alone is fine, but together they move locked tokens
+        let info = mock_info(addr1, &[]);
+        let msg = ExecuteMsg::TransferBatch {
+            transfers: batch(&[(addr2, 31_000), (addr3, 30_000)]),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CantMoveVestingTokens {
+                effectively_locked: Uint128::new(40_000)
+            }
+        );
+
+        // a single invalid entry aborts the whole batch
+        let msg = ExecuteMsg::TransferBatch {
+            transfers: batch(&[(addr2, 10_000), (addr3, 0)]),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidZeroAmount {});
+        let msg = ExecuteMsg::TransferBatch {
+            transfers: batch(&[(addr2, 10_000), ("", 10_000)]),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        let msg = ExecuteMsg::TransferBatch { transfers: vec![] };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyBatch {});
+        let msg = ExecuteMsg::TransferBatch {
+            transfers: batch(&[(addr2, 1); MAX_BATCH_TRANSFERS + 1]),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TooManyTransfers {
+                max: MAX_BATCH_TRANSFERS
+            }
+        );
+        assert_eq!(get_balance(deps.as_ref(), addr1), Uint128::new(100_000));
+        assert_eq!(get_balance(deps.as_ref(), addr2), Uint128::zero());
+
+        // all unlocked tokens can be sent at once, also to the same recipient twice
+        let msg = ExecuteMsg::TransferBatch {
+            transfers: batch(&[(addr2, 30_000), (addr3, 20_000), (addr2, 10_000)]),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "transfer_batch"),
+                attr("from", addr1),
+                attr("transfers", "3"),
+                attr("amount", "60000"),
+            ]
+        );
+        assert_eq!(
+            res.events,
+            vec![
+                Event::new("transfer")
+                    .add_attribute("from", addr1)
+                    .add_attribute("to", addr2)
+                    .add_attribute("amount", "30000"),
+                Event::new("transfer")
+                    .add_attribute("from", addr1)
+                    .add_attribute("to", addr3)
+                    .add_attribute("amount", "20000"),
+                Event::new("transfer")
+                    .add_attribute("from", addr1)
+                    .add_attribute("to", addr2)
+                    .add_attribute("amount", "10000"),
+            ]
+        );
+
+        assert_eq!(get_balance(deps.as_ref(), addr1), Uint128::new(40_000));
+        assert_eq!(get_balance(deps.as_ref(), addr2), Uint128::new(40_000));
+        assert_eq!(get_balance(deps.as_ref(), addr3), Uint128::new(20_000));
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(150_000)
+        );
+    }
+
+    #[test]
+    fn transfer_vesting_extreme_coordinates() {
+        let mut deps = mock_dependencies();
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let amount = Uint128::new(u128::MAX / 2);
+        let info = mock_info(addr1.as_ref(), &[]);
+        _do_instantiate(deps.as_mut(), &addr1, amount, None, Some(info.clone()));
+
+        let start = mock_env().block.time.seconds();
+        let latest_end = start + MAX_VESTING_SECONDS;
+        let transfer = |amount: u128, end: u64| ExecuteMsg::TransferVesting {
+            recipient: addr2.clone(),
+            amount: Uint128::new(amount),
+            schedule: Curve::saturating_linear((start, amount), (end, 0)),
+            memo: None,
+            notify: false,
+        };
+
+        // schedules reaching too far into the future are rejected
+        for end in [latest_end + 1, u64::MAX] {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                transfer(1_000, end),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::VestingEndsTooLate { latest_end });
+        }
+
+        // huge amounts over the longest allowed time are interpolated without overflow
+        let huge = u128::MAX / 4;
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            transfer(huge, latest_end),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            transfer(huge, latest_end - 1),
+        )
+        .unwrap();
+        for seconds in [0, 1, MAX_VESTING_SECONDS / 2, MAX_VESTING_SECONDS - 1] {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            let locked = query_vesting(deps.as_ref(), env, addr2.clone())
+                .unwrap()
+                .locked
+                .u128();
+            let expected = 2 * huge - 2 * (huge / MAX_VESTING_SECONDS as u128) * seconds as u128;
+            assert!(locked.abs_diff(expected) < 2 * huge / MAX_VESTING_SECONDS as u128);
+        }
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(latest_end);
+        let vesting = query_vesting(deps.as_ref(), env, addr2).unwrap();
+        assert_eq!(vesting.locked, Uint128::zero());
+    }
+
+    #[test]
+    fn transfer_vesting_batch() {
+        let mut deps = mock_dependencies();
+        let admin = "admin";
+        let addr1 = "addr0001";
+        let addr2 = "addr0002";
+        let info = mock_info(admin, &[]);
+        _do_instantiate(
+            deps.as_mut(),
+            admin,
+            Uint128::new(100_000),
+            None,
+            Some(info.clone()),
+        );
+
+        // half of each amount is locked, vesting over 1000 seconds
+        let start = mock_env().block.time.seconds();
+        let schedule = ScalableCurve::linear((start, 50), (start + 1000, 0));
+        let msg = ExecuteMsg::TransferVestingBatch {
+            transfers: batch(&[(addr1, 20_000), (addr2, 60_000)]),
+            schedule: schedule.clone(),
+        };
+
+        // only allowed vesters can send it
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(res.events.len(), 2);
+        assert!(res.attributes.contains(&attr("amount", "80000")));
+
+        assert_eq!(get_balance(deps.as_ref(), admin), Uint128::new(20_000));
+        let vesting = query_vesting(deps.as_ref(), mock_env(), addr1.to_string()).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(10_000));
+        assert_eq!(
+            vesting.schedule.unwrap(),
+            schedule.clone().scale(Uint128::new(20_000))
+        );
+        let vesting = query_vesting(deps.as_ref(), mock_env(), addr2.to_string()).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(30_000));
+
+        // the schedule may not lock more than is sent
+        let msg = ExecuteMsg::TransferVestingBatch {
+            transfers: batch(&[(addr1, 10_000)]),
+            schedule: ScalableCurve::linear((start, 150), (start + 1000, 0)),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::VestsMoreThanSent);
+        assert_eq!(get_balance(deps.as_ref(), admin), Uint128::new(20_000));
+    }
+
+    #[test]
+    fn vesting_unlock_time() {
+        let mut deps = mock_dependencies();
+        let admin = "admin";
+        let addr1 = "addr0001";
+        let start = mock_env().block.time.seconds();
+
+        let msg = InstantiateMsg {
+            name: "Cash Token".to_string(),
+            symbol: "CASH".to_string(),
+            decimals: 9,
+            initial_balances: vec![
+                InitBalance {
+                    address: admin.to_string(),
+                    amount: Uint128::new(100_000),
+                    vesting: None,
+                    memo: None,
+                    relative_vesting: None,
+                },
+                InitBalance {
+                    address: addr1.to_string(),
+                    amount: Uint128::new(1_000),
+                    vesting: Some(Curve::saturating_linear((start, 1_000), (start + 1000, 0))),
+                    memo: None,
+                    relative_vesting: None,
+                },
+            ],
+            mint: None,
+            marketing: None,
+            allowed_vesters: None,
+            max_curve_complexity: 10,
+            seeding_open: false,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info(admin, &[]), msg).unwrap();
+        // 500 tokens can be moved right away
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr1.to_string(),
+            amount: Uint128::new(500),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), msg).unwrap();
+
+        let unlock_time = |deps: Deps, time: u64, amount: u128| {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(time);
+            query_vesting_unlock_time(deps, env, addr1.to_string(), Uint128::new(amount))
+                .unwrap()
+                .unlock_time
+        };
+        assert_eq!(unlock_time(deps.as_ref(), start, 500), Some(start));
+        assert_eq!(unlock_time(deps.as_ref(), start, 1_000), Some(start + 500));
+        assert_eq!(unlock_time(deps.as_ref(), start, 1_500), Some(start + 1000));
+        assert_eq!(unlock_time(deps.as_ref(), start, 1_501), None);
+        // never earlier than now
+        assert_eq!(
+            unlock_time(deps.as_ref(), start + 600, 1_000),
+            Some(start + 600)
+        );
+
+        // the transfer fails right before the unlock time and works from then on
+        let msg = ExecuteMsg::Transfer {
+            recipient: admin.to_string(),
+            amount: Uint128::new(1_200),
+        };
+        let unlock = unlock_time(deps.as_ref(), start, 1_200).unwrap();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(unlock - 1);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CantMoveVestingTokens {
+                effectively_locked: Uint128::new(301)
+            }
+        );
+        env.block.time = Timestamp::from_seconds(unlock);
+        execute(deps.as_mut(), env, mock_info(addr1, &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn vesting_batch() {
+        let mut deps = mock_dependencies();
+        let admin = "admin";
+        let addr1 = "addr0001";
+        let start = mock_env().block.time.seconds();
+        let msg = InstantiateMsg {
+            name: "Cash Token".to_string(),
+            symbol: "CASH".to_string(),
+            decimals: 9,
+            initial_balances: vec![
+                InitBalance {
+                    address: admin.to_string(),
+                    amount: Uint128::new(100_000),
+                    vesting: None,
+                    memo: None,
+                    relative_vesting: None,
+                },
+                InitBalance {
+                    address: addr1.to_string(),
+                    amount: Uint128::new(1_000),
+                    vesting: Some(Curve::saturating_linear((start, 1_000), (start + 1000, 0))),
+                    memo: None,
+                    relative_vesting: None,
+                },
+            ],
+            mint: None,
+            marketing: None,
+            allowed_vesters: None,
+            max_curve_complexity: 10,
+            seeding_open: false,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info(admin, &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(start + 400);
+        let addresses = vec![addr1.to_string(), "x".to_string(), admin.to_string()];
+        let res = query_vesting_batch(deps.as_ref(), env.clone(), addresses).unwrap();
+        assert_eq!(
+            res.vestings,
+            [
+                VestingBatchEntry {
+                    address: addr1.to_string(),
+                    locked: Uint128::new(600),
+                    schedule_end: Some(start + 1000),
+                    has_schedule: true,
+                    error: None,
+                },
+                VestingBatchEntry {
+                    address: "x".to_string(),
+                    locked: Uint128::zero(),
+                    schedule_end: None,
+                    has_schedule: false,
+                    error: Some(deps.api.addr_validate("x").unwrap_err().to_string()),
+                },
+                VestingBatchEntry {
+                    address: admin.to_string(),
+                    locked: Uint128::zero(),
+                    schedule_end: None,
+                    has_schedule: false,
+                    error: None,
+                },
+            ]
+        );
+
+        // the number of addresses is capped
+        let addresses = vec![admin.to_string(); MAX_VESTING_BATCH];
+        query_vesting_batch(deps.as_ref(), env.clone(), addresses).unwrap();
+        let addresses = vec![admin.to_string(); MAX_VESTING_BATCH + 1];
+        let err = query_vesting_batch(deps.as_ref(), env, addresses).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Cannot query more than 50 addresses at once")
+        );
+    }
+
+    #[test]
+    fn vesting_history() {
+        let mut deps = mock_dependencies();
+        let admin = "admin";
+        let addr1 = "addr0001";
+        let start = mock_env().block.time.seconds();
+        let schedule = |amount| Curve::saturating_linear((start, amount), (start + 1000, 0));
+
+        // memos of initial balances are validated
+        let mut msg = InstantiateMsg {
+            name: "Cash Token".to_string(),
+            symbol: "CASH".to_string(),
+            decimals: 9,
+            initial_balances: vec![
+                InitBalance {
+                    address: admin.to_string(),
+                    amount: Uint128::new(100_000),
+                    vesting: None,
+                    memo: None,
+                    relative_vesting: None,
+                },
+                InitBalance {
+                    address: addr1.to_string(),
+                    amount: Uint128::new(1_000),
+                    vesting: Some(schedule(1_000)),
+                    memo: Some("x".repeat(MAX_MEMO_LENGTH + 1)),
+                    relative_vesting: None,
+                },
+            ],
+            mint: None,
+            marketing: None,
+            allowed_vesters: None,
+            max_curve_complexity: 10,
+            seeding_open: false,
+        };
+        let info = mock_info(admin, &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MemoTooLong {
+                max: MAX_MEMO_LENGTH
+            }
+        );
+        msg.initial_balances[1].memo = Some("Seed round".to_string());
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // multiple grants to the same recipient
+        let grant = |amount: u128, memo: Option<String>| ExecuteMsg::TransferVesting {
+            recipient: addr1.to_string(),
+            amount: Uint128::new(amount),
+            schedule: schedule(amount),
+            memo,
+            notify: false,
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            grant(2_000, Some("Q3 contributor grant".to_string())),
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&attr("memo", "Q3 contributor grant")));
+        execute(deps.as_mut(), mock_env(), info.clone(), grant(3_000, None)).unwrap();
+        let msg = ExecuteMsg::TransferVestingBatch {
+            transfers: batch(&[(addr1, 4_000)]),
+            schedule: ScalableCurve::linear((start, 100), (start + 1000, 0)),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // memos must not be too long, but may have exactly the maximum length
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            grant(5_000, Some("\u{1F600}".repeat(MAX_MEMO_LENGTH + 1))),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MemoTooLong {
+                max: MAX_MEMO_LENGTH
+            }
+        );
+        let max_memo = "\u{1F600}".repeat(MAX_MEMO_LENGTH);
+        // the history is kept after the schedules are fully vested
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(2000);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            grant(5_000, Some(max_memo.clone())),
+        )
+        .unwrap();
+        assert_eq!(
+            query_vesting(deps.as_ref(), env.clone(), addr1.to_string())
+                .unwrap()
+                .locked,
+            Uint128::zero()
+        );
+
+        let history = |start_after, limit| {
+            query_vesting_history(deps.as_ref(), addr1.to_string(), start_after, limit)
+                .unwrap()
+                .grants
+        };
+        let all = history(None, None);
+        assert_eq!(
+            all.iter()
+                .map(|grant| (grant.seq, grant.amount.u128(), grant.memo.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, 1_000, Some("Seed round".to_string())),
+                (2, 2_000, Some("Q3 contributor grant".to_string())),
+                (3, 3_000, None),
+                (4, 4_000, None),
+                (5, 5_000, Some(max_memo)),
+            ]
+        );
+        assert!(all.iter().all(|grant| grant.grantor == admin));
+        assert_eq!(all[0].timestamp, mock_env().block.time);
+        assert_eq!(all[4].timestamp, env.block.time);
+
+        // paginate
+        assert_eq!(history(None, Some(2)), all[..2]);
+        assert_eq!(history(Some(2), Some(2)), all[2..4]);
+        assert_eq!(history(Some(4), Some(2)), all[4..]);
+        assert_eq!(history(Some(5), None), vec![]);
+
+        // accounts without grants have an empty history
+        let res: VestingHistoryResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::VestingHistory {
+                    address: admin.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(res.grants.is_empty());
+    }
+
+    #[test]
+    fn clear_expired_vesting() {
+        let mut deps = mock_dependencies();
+        let admin = "admin";
+        let addr1 = "addr0001";
+        let addr2 = "addr0002";
+        let addr3 = "addr0003";
+        let info = mock_info(admin, &[]);
+        _do_instantiate(
+            deps.as_mut(),
+            admin,
+            Uint128::new(100_000),
+            None,
+            Some(info.clone()),
+        );
+
+        // addr1 is fully vested after 1000 seconds, addr2 after 5000 seconds
+        let start = mock_env().block.time.seconds();
+        for (addr, end) in [(addr1, start + 1000), (addr2, start + 5000)] {
+            let msg = ExecuteMsg::TransferVesting {
+                recipient: addr.to_string(),
+                amount: Uint128::new(1_000),
+                schedule: Curve::saturating_linear((start, 1_000), (end, 0)),
+                memo: None,
+                notify: false,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        assert_eq!(VESTING_COUNT.load(&deps.storage).unwrap(), 2);
+
+        // anyone can clear, but only fully vested schedules are removed
+        let clear = ExecuteMsg::ClearExpiredVesting {
+            addresses: vec![addr1.to_string(), addr2.to_string(), addr3.to_string()],
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(addr3, &[]),
+            clear.clone(),
+        )
+        .unwrap();
+        assert!(res.attributes.contains(&attr("cleared", "0")));
+        assert_eq!(VESTING_COUNT.load(&deps.storage).unwrap(), 2);
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = execute(deps.as_mut(), env.clone(), mock_info(addr3, &[]), clear).unwrap();
+        assert!(res.attributes.contains(&attr("cleared", "1")));
+        assert!(!VESTING.has(&deps.storage, &Addr::unchecked(addr1)));
+        assert!(VESTING.has(&deps.storage, &Addr::unchecked(addr2)));
+        assert_eq!(VESTING_COUNT.load(&deps.storage).unwrap(), 1);
+        assert_eq!(get_balance(deps.as_ref(), addr1), Uint128::new(1_000));
+
+        // the batch size is limited
+        let msg = ExecuteMsg::ClearExpiredVesting {
+            addresses: vec![addr2.to_string(); MAX_CLEAR_VESTING + 1],
+        };
+        let err = execute(deps.as_mut(), env, mock_info(addr3, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TooManyAddresses {
+                max: MAX_CLEAR_VESTING
+            }
+        );
+    }
+
+    #[test]
+    fn update_max_vesting_complexity() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let minter = String::from("minter");
+        let addr2 = String::from("addr0002");
+        let amount1 = Uint128::from(250_000u128);
+
+        let info = mock_info(minter.as_ref(), &[]);
+        _do_instantiate(
+            deps.as_mut(),
+            &minter,
+            amount1,
+            Some(MinterInfo {
+                minter: minter.clone(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            Some(info.clone()),
+        );
+        let res = query_max_complexity(deps.as_ref()).unwrap();
+        assert_eq!(res.complexity, 10);
+        assert_eq!(res.largest_curve_size, 0);
+
+        // fill up the curve of addr2 to the maximum
+        let start = mock_env().block.time.seconds();
+        let end = start + 9;
+        let amount = Uint128::new(10_000);
+        let complex = ExecuteMsg::TransferVesting {
+            recipient: addr2.clone(),
+            amount,
+            schedule: Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: (start..end)
+                    .map(|x| (x, amount))
+                    .chain(std::iter::once((end, Uint128::zero())))
+                    .collect(),
+            }),
+            memo: None,
+            notify: false,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), complex).unwrap();
+        let res = query_max_complexity(deps.as_ref()).unwrap();
+        assert_eq!(res.largest_curve_size, 10);
+
+        // adding another simple curve is rejected
+        let simple = ExecuteMsg::TransferVesting {
+            recipient: addr2,
+            amount,
+            schedule: Curve::saturating_linear((end, amount.u128()), (end + 1, 0)),
+            memo: None,
+            notify: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), simple.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Curve(CurveError::TooComplex));
+
+        // only minter can update
+        let raise = ExecuteMsg::UpdateMaxVestingComplexity { max: 20 };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr0003", &[]),
+            raise.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // raise works
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), raise).unwrap();
+        assert_eq!(res.attributes[1], ("old", "10"));
+        assert_eq!(res.attributes[2], ("new", "20"));
+        assert_eq!(query_max_complexity(deps.as_ref()).unwrap().complexity, 20);
+
+        // lowering is rejected
+        let lower = ExecuteMsg::UpdateMaxVestingComplexity { max: 15 };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), lower).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotLowerMaxVestingComplexity { current: 20 }
+        );
+
+        // previously rejected transfer now succeeds
+        execute(deps.as_mut(), mock_env(), info, simple).unwrap();
+        let res = query_max_complexity(deps.as_ref()).unwrap();
+        assert_eq!(res.complexity, 20);
+        assert_eq!(res.largest_curve_size, 11);
+    }
+
+    #[test]
+    fn vesting_policy() {
+        let mut deps = mock_dependencies();
+        let minter = "minter";
+        let treasury = "treasury";
+        let exempt = "exempt";
+        let rcpt = "addr0002";
+        _do_instantiate(
+            deps.as_mut(),
+            treasury,
+            Uint128::new(10_000),
+            Some(MinterInfo {
+                minter: minter.to_string(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            Some(mock_info(treasury, &[])),
+        );
+        let transfer = |schedule| ExecuteMsg::TransferVesting {
+            recipient: rcpt.to_string(),
+            amount: Uint128::new(1_000),
+            schedule,
+            memo: None,
+            notify: false,
+        };
+        let start = mock_env().block.time.seconds();
+        let sender = mock_info(treasury, &[]);
+        let set_policy = ExecuteMsg::SetVestingPolicy {
+            min_cliff_seconds: Some(100),
+            max_duration_seconds: Some(1_000),
+        };
+
+        // only the minter can set it
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender.clone(),
+            set_policy.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(minter, &[]),
+            set_policy,
+        )
+        .unwrap();
+
+        // unlocking right away violates the cliff
+        let schedule = Curve::saturating_linear((start, 1_000), (start + 500, 0));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender.clone(),
+            transfer(schedule),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::VestingCliffTooShort {
+                min_cliff_seconds: 100,
+                unlocks_at: start + 1,
+            }
+        );
+
+        // vesting too long violates the duration
+        let schedule = Curve::saturating_linear((start + 100, 1_000), (start + 1_001, 0));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender.clone(),
+            transfer(schedule),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::VestingDurationTooLong {
+                max_duration_seconds: 1_000,
+                fully_vested_at: Some(start + 1_001),
+            }
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender.clone(),
+            transfer(Curve::constant(1_000)),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::VestingDurationTooLong {
+                max_duration_seconds: 1_000,
+                fully_vested_at: None,
+            }
+        );
+
+        // exactly at the bounds is fine
+        let schedule = Curve::saturating_linear((start + 100, 1_000), (start + 1_000, 0));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            sender.clone(),
+            transfer(schedule),
+        )
+        .unwrap();
+
+        // exempt vesters are not checked, until they are denied
+        let msg = ExecuteMsg::AllowVester {
+            address: exempt.to_string(),
+            policy_exempt: true,
+        };
+        execute(deps.as_mut(), mock_env(), sender.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: exempt.to_string(),
+            amount: Uint128::new(2_000),
+        };
+        execute(deps.as_mut(), mock_env(), sender.clone(), msg).unwrap();
+        let exempt_info = mock_info(exempt, &[]);
+        let long = Curve::saturating_linear((start, 1_000), (start + 5_000, 0));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            exempt_info.clone(),
+            transfer(long.clone()),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::DenyVester {
+            address: exempt.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), sender.clone(), msg).unwrap();
+        let msg = ExecuteMsg::AllowVester {
+            address: exempt.to_string(),
+            policy_exempt: false,
+        };
+        execute(deps.as_mut(), mock_env(), sender, msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), exempt_info, transfer(long)).unwrap_err();
+        assert!(matches!(err, ContractError::VestingCliffTooShort { .. }));
+    }
+
+    #[test]
+    fn vesting_policy_covers_extensions() {
+        let mut deps = mock_dependencies();
+        let minter = "minter";
+        let treasury = "treasury";
+        let rcpt = "addr0002";
+        _do_instantiate(
+            deps.as_mut(),
+            treasury,
+            Uint128::new(10_000),
+            Some(MinterInfo {
+                minter: minter.to_string(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            Some(mock_info(treasury, &[])),
+        );
+        let start = mock_env().block.time.seconds();
+        let sender = mock_info(treasury, &[]);
+        let msg = ExecuteMsg::Transfer {
+            recipient: rcpt.to_string(),
+            amount: Uint128::new(5_000),
+        };
+        execute(deps.as_mut(), mock_env(), sender.clone(), msg).unwrap();
+        let msg = ExecuteMsg::SetVestingPolicy {
+            min_cliff_seconds: Some(100),
+            max_duration_seconds: Some(1_000),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(minter, &[]), msg).unwrap();
+        let extend_schedule = |steps: &[(u64, u128)]| ExecuteMsg::ExtendVestingSchedule {
+            address: rcpt.to_string(),
+            additional_steps: steps
+                .iter()
+                .map(|&(x, y)| (start + x, Uint128::new(y)))
+                .collect(),
+        };
+        let extend = ExecuteMsg::ExtendVesting {
+            address: rcpt.to_string(),
+            extend_by_seconds: 1,
+        };
+
+        // the added steps are checked like a vesting transfer
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender.clone(),
+            extend_schedule(&[(0, 1_000), (500, 0)]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::VestingCliffTooShort {
+                min_cliff_seconds: 100,
+                unlocks_at: start + 1,
+            }
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender.clone(),
+            extend_schedule(&[(100, 1_000), (1_001, 0)]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::VestingDurationTooLong {
+                max_duration_seconds: 1_000,
+                fully_vested_at: Some(start + 1_001),
+            }
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            sender.clone(),
+            extend_schedule(&[(100, 1_000), (1_000, 0)]),
+        )
+        .unwrap();
+
+        // delaying the schedule must not take it past the maximum duration
+        let err = execute(deps.as_mut(), mock_env(), sender.clone(), extend.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::VestingDurationTooLong {
+                max_duration_seconds: 1_000,
+                fully_vested_at: Some(start + 1_001),
+            }
+        );
+
+        // unless the vester is exempt
+        let msg = ExecuteMsg::AllowVester {
+            address: "exempt".to_string(),
+            policy_exempt: true,
+        };
+        execute(deps.as_mut(), mock_env(), sender, msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("exempt", &[]), extend).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1_001);
+        let vesting = query_vesting(deps.as_ref(), env, rcpt.to_string()).unwrap();
+        assert_eq!(vesting.locked, Uint128::zero());
+    }
+
+    #[test]
+    fn spending_limit() {
+        let mut deps = mock_dependencies();
+        let minter = "minter";
+        let treasury = "treasury";
+        let rcpt = "addr0002";
+        _do_instantiate(
+            deps.as_mut(),
+            treasury,
+            Uint128::new(10_000),
+            Some(MinterInfo {
+                minter: minter.to_string(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            None,
+        );
+        let set_limit = |window_seconds, max| ExecuteMsg::SetSpendingLimit {
+            limit: Some(SpendingLimit {
+                window_seconds,
+                max_per_sender: Uint128::new(max),
+            }),
+        };
+        let transfer = |amount| ExecuteMsg::Transfer {
+            recipient: rcpt.to_string(),
+            amount: Uint128::new(amount),
+        };
+        let status = |deps: Deps, env: Env| {
+            query_spending_limit_status(deps, env, treasury.to_string()).unwrap()
+        };
+
+        // only the minter can set it, and the window must not be empty
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(treasury, &[]),
+            set_limit(100, 1_000),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let info = mock_info(minter, &[]);
+        let err =
+            execute(deps.as_mut(), mock_env(), info.clone(), set_limit(0, 1_000)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSpendingLimit {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            set_limit(100, 1_000),
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        let sender = mock_info(treasury, &[]);
+        execute(deps.as_mut(), env.clone(), sender.clone(), transfer(600)).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), sender.clone(), transfer(500)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::SpendingLimitExceeded {
+                remaining: Uint128::new(400)
+            }
+        );
+        assert_eq!(
+            status(deps.as_ref(), env.clone()),
+            SpendingLimitStatusResponse {
+                limit: Some(SpendingLimit {
+                    window_seconds: 100,
+                    max_per_sender: Uint128::new(1_000)
+                }),
+                sent: Uint128::new(600),
+                remaining: Some(Uint128::new(400)),
+                window_end: Some(env.block.time.seconds() + 100),
+            }
+        );
+
+        // the window does not move with later transfers
+        env.block.time = env.block.time.plus_seconds(99);
+        execute(deps.as_mut(), env.clone(), sender.clone(), transfer(400)).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), sender.clone(), transfer(1)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::SpendingLimitExceeded {
+                remaining: Uint128::zero()
+            }
+        );
+
+        // a new window starts once it is over
+        env.block.time = env.block.time.plus_seconds(1);
+        assert_eq!(
+            status(deps.as_ref(), env.clone()).remaining,
+            Some(Uint128::new(1_000))
+        );
+        execute(deps.as_mut(), env.clone(), sender.clone(), transfer(1_000)).unwrap();
+
+        // removing the limit allows any transfer again
+        let msg = ExecuteMsg::SetSpendingLimit { limit: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        execute(deps.as_mut(), env.clone(), sender, transfer(5_000)).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), rcpt), Uint128::new(7_000));
+        assert_eq!(
+            status(deps.as_ref(), env),
+            SpendingLimitStatusResponse {
+                limit: None,
+                sent: Uint128::zero(),
+                remaining: None,
+                window_end: None,
+            }
+        );
+    }
+
+    #[test]
+    fn spending_limit_covers_sends_and_allowances() {
+        let mut deps = mock_dependencies();
+        let minter = "minter";
+        let treasury = "treasury";
+        let spender = "spender";
+        let rcpt = "addr0002";
+        let contract = "contract0000";
+        _do_instantiate(
+            deps.as_mut(),
+            treasury,
+            Uint128::new(10_000),
+            Some(MinterInfo {
+                minter: minter.to_string(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            None,
+        );
+        let msg = ExecuteMsg::SetSpendingLimit {
+            limit: Some(SpendingLimit {
+                window_seconds: 100,
+                max_per_sender: Uint128::new(1_000),
+            }),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(minter, &[]), msg).unwrap();
+        let owner = mock_info(treasury, &[]);
+        let send = |amount| ExecuteMsg::Send {
+            contract: contract.to_string(),
+            amount: Uint128::new(amount),
+            msg: Binary::default(),
         };
-        let err = execute(deps.as_mut(), mock_env(), info.clone(), over_vesting).unwrap_err();
-        assert_eq!(err, ContractError::VestsMoreThanSent);
 
-        // send with curve that never hits 0
-        let never_vests = ExecuteMsg::TransferVesting {
-            recipient: addr3.clone(),
-            amount: Uint128::new(10_000),
-            schedule: Curve::saturating_linear((start, 10_000), (end, 1_000)),
+        execute(deps.as_mut(), mock_env(), owner.clone(), send(600)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), send(500)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::SpendingLimitExceeded {
+                remaining: Uint128::new(400)
+            }
+        );
+
+        // an allowance does not get around the limit of the owner
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.to_string(),
+            amount: Uint128::new(5_000),
+            expires: None,
         };
-        let err = execute(deps.as_mut(), mock_env(), info.clone(), never_vests).unwrap_err();
+        execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+        let msg = ExecuteMsg::TransferFrom {
+            owner: treasury.to_string(),
+            recipient: rcpt.to_string(),
+            amount: Uint128::new(300),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(spender, &[]), msg).unwrap();
+        let msg = ExecuteMsg::TransferFrom {
+            owner: treasury.to_string(),
+            recipient: rcpt.to_string(),
+            amount: Uint128::new(200),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(spender, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::SpendingLimitExceeded {
+                remaining: Uint128::new(100)
+            }
+        );
+        let msg = ExecuteMsg::SendFrom {
+            owner: treasury.to_string(),
+            contract: contract.to_string(),
+            amount: Uint128::new(200),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(spender, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::SpendingLimitExceeded {
+                remaining: Uint128::new(100)
+            }
+        );
+
+        assert_eq!(get_balance(deps.as_ref(), contract), Uint128::new(600));
+        assert_eq!(get_balance(deps.as_ref(), rcpt), Uint128::new(300));
+        assert_eq!(get_balance(deps.as_ref(), treasury), Uint128::new(9_100));
+    }
+
+    #[test]
+    fn complexity_tier_overrides_global_limit() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let minter = String::from("minter");
+        let addr2 = String::from("addr0002");
+        let addr3 = String::from("addr0003");
+        let amount1 = Uint128::from(250_000u128);
+
+        let info = mock_info(minter.as_ref(), &[]);
+        _do_instantiate(
+            deps.as_mut(),
+            &minter,
+            amount1,
+            Some(MinterInfo {
+                minter: minter.clone(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            Some(info.clone()),
+        );
+
+        // without a tier, the global limit applies
+        let res = query_complexity_tier(deps.as_ref(), addr2.clone()).unwrap();
+        assert_eq!(
+            res,
+            ComplexityTierResponse {
+                complexity: 10,
+                tier: None
+            }
+        );
+
+        // only minter can set a tier
+        let set_tier = |address: &str, max_complexity| ExecuteMsg::SetComplexityTier {
+            address: address.to_owned(),
+            max_complexity,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr0003", &[]),
+            set_tier(&addr2, 20),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // raise limit for addr2, lower it for addr3
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            set_tier(&addr2, 20),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], ("action", "set_complexity_tier"));
+        assert_eq!(res.attributes[2], ("max_complexity", "20"));
+        execute(deps.as_mut(), mock_env(), info.clone(), set_tier(&addr3, 2)).unwrap();
+        let res = query_complexity_tier(deps.as_ref(), addr2.clone()).unwrap();
+        assert_eq!(
+            res,
+            ComplexityTierResponse {
+                complexity: 20,
+                tier: Some(20)
+            }
+        );
+        // global limit is unaffected
+        assert_eq!(query_max_complexity(deps.as_ref()).unwrap().complexity, 10);
+
+        // a curve above the global limit is accepted for addr2
+        let start = mock_env().block.time.seconds();
+        let end = start + 14;
+        let amount = Uint128::new(10_000);
+        let complex = |recipient: &str| ExecuteMsg::TransferVesting {
+            recipient: recipient.to_owned(),
+            amount,
+            schedule: Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: (start..end)
+                    .map(|x| (x, amount))
+                    .chain(std::iter::once((end, Uint128::zero())))
+                    .collect(),
+            }),
+            memo: None,
+            notify: false,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), complex(&addr2)).unwrap();
+        let res = query_max_complexity(deps.as_ref()).unwrap();
+        assert_eq!(res.largest_curve_size, 15);
+
+        // but rejected for an address without a tier
+        let err =
+            execute(deps.as_mut(), mock_env(), info.clone(), complex("addr0004")).unwrap_err();
+        assert_eq!(err, ContractError::Curve(CurveError::TooComplex));
+
+        // a lowered tier rejects curves allowed by the global limit
+        let medium = ExecuteMsg::TransferVesting {
+            recipient: addr3,
+            amount,
+            schedule: Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: vec![
+                    (start, amount),
+                    (start + 1, Uint128::new(5_000)),
+                    (start + 2, Uint128::zero()),
+                ],
+            }),
+            memo: None,
+            notify: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, medium).unwrap_err();
+        assert_eq!(err, ContractError::Curve(CurveError::TooComplex));
+    }
+
+    #[test]
+    fn extend_vesting() {
+        let mut deps = mock_dependencies();
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let addr3 = String::from("addr0003");
+        let info = mock_info(addr1.as_ref(), &[]);
+        _do_instantiate(
+            deps.as_mut(),
+            &addr1,
+            Uint128::new(10_000),
+            None,
+            Some(info.clone()),
+        );
+        let start = mock_env().block.time.seconds();
+        let transfer =
+            |recipient: &str, amount: u128, schedule: Curve| ExecuteMsg::TransferVesting {
+                recipient: recipient.to_string(),
+                amount: Uint128::new(amount),
+                schedule,
+                memo: None,
+                notify: false,
+            };
+        let extend = |address: &str, extend_by_seconds| ExecuteMsg::ExtendVesting {
+            address: address.to_string(),
+            extend_by_seconds,
+        };
+        let at = |seconds| {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(start + seconds);
+            env
+        };
+
+        // a zero amount transfer does not update the schedule
+        let schedule = Curve::saturating_linear((start, 1_000), (start + 1_000, 0));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            transfer(&addr2, 0, schedule.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ZeroAmountVestingTransfer {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            transfer(&addr2, 1_000, schedule),
+        )
+        .unwrap();
+
+        // only allow list members can extend, only by a positive number of seconds
+        let err = execute(
+            deps.as_mut(),
+            at(500),
+            mock_info(&addr2, &[]),
+            extend(&addr2, 1_000),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), at(500), info.clone(), extend(&addr2, 0)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidVestingExtension {});
+        from_slice::<ExecuteMsg>(
+            br#"{"extend_vesting":{"address":"addr0002","extend_by_seconds":-100}}"#,
+        )
+        .unwrap_err();
+        let err = execute(deps.as_mut(), at(500), info.clone(), extend(&addr3, 1_000)).unwrap_err();
+        assert_eq!(err, ContractError::NoVestingToExtend {});
+
+        // midway, the locked half is released until the extended end of the schedule
+        execute(deps.as_mut(), at(500), info.clone(), extend(&addr2, 1_000)).unwrap();
+        let locked = |deps: Deps, address: &str, seconds| {
+            query_vesting(deps, at(seconds), address.to_string())
+                .unwrap()
+                .locked
+                .u128()
+        };
+        assert_eq!(locked(deps.as_ref(), &addr2, 500), 500);
+        assert_eq!(locked(deps.as_ref(), &addr2, 1_250), 250);
+        assert_eq!(locked(deps.as_ref(), &addr2, 2_000), 0);
+        let schedule = query_vesting(deps.as_ref(), at(500), addr2.clone())
+            .unwrap()
+            .schedule
+            .unwrap();
+        assert_eq!(
+            schedule,
+            Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: vec![
+                    (start + 500, Uint128::new(500)),
+                    (start + 2_000, Uint128::zero()),
+                ]
+            })
+        );
+
+        // steps in the past are dropped, so the schedule does not get more complex
+        let steps = [(0, 1_000), (100, 800), (200, 300), (300, 0)];
+        let schedule = Curve::PiecewiseLinear(PiecewiseLinear {
+            steps: steps
+                .iter()
+                .map(|&(x, y)| (start + x, Uint128::new(y)))
+                .collect(),
+        });
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            transfer(&addr3, 1_000, schedule),
+        )
+        .unwrap();
+        execute(deps.as_mut(), at(150), info.clone(), extend(&addr3, 100)).unwrap();
+        let schedule = query_vesting(deps.as_ref(), at(150), addr3.clone())
+            .unwrap()
+            .schedule
+            .unwrap();
+        assert_eq!(
+            schedule,
+            Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: vec![
+                    (start + 150, Uint128::new(550)),
+                    (start + 300, Uint128::new(300)),
+                    (start + 400, Uint128::zero()),
+                ]
+            })
+        );
+        assert_eq!(locked(deps.as_ref(), &addr3, 250), 384);
+
+        // the extended schedule is bound by the maximum vesting time like new ones
+        let err = execute(
+            deps.as_mut(),
+            at(150),
+            info,
+            extend(&addr3, MAX_VESTING_SECONDS),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::VestingEndsTooLate {
+                latest_end: start + 150 + MAX_VESTING_SECONDS
+            }
+        );
+    }
+
+    #[test]
+    fn extend_vesting_schedule() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let info = mock_info(addr1.as_ref(), &[]);
+        _do_instantiate(
+            deps.as_mut(),
+            &addr1,
+            Uint128::new(10_000),
+            None,
+            Some(info.clone()),
+        );
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(5_000),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let start = mock_env().block.time.seconds();
+        let extend = |steps: &[(u64, u128)]| ExecuteMsg::ExtendVestingSchedule {
+            address: addr2.clone(),
+            additional_steps: steps
+                .iter()
+                .map(|&(x, y)| (start + x, Uint128::new(y)))
+                .collect(),
+        };
+
+        // only allow list members can extend
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&addr2, &[]),
+            extend(&[(0, 2_000), (1000, 0)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // steps must describe a valid vesting schedule
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            extend(&[(0, 0), (1000, 2_000)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Curve(CurveError::MonotonicIncreasing));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            extend(&[(0, 2_000), (1000, 500)]),
+        )
+        .unwrap_err();
         assert_eq!(err, ContractError::NeverFullyVested);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            extend(&[(0, 2_000), (MAX_VESTING_SECONDS + 1, 0)]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::VestingEndsTooLate {
+                latest_end: start + MAX_VESTING_SECONDS
+            }
+        );
+
+        // extend an account without vesting
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            extend(&[(0, 2_000), (1000, 0)]),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("schedule_size", "2"));
+        let vesting = query_vesting(deps.as_ref(), mock_env(), addr2.clone()).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(2_000));
+
+        // extend an existing schedule
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            extend(&[(500, 3_000), (1500, 0)]),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("schedule_size", "4"));
+        let vesting = query_vesting(deps.as_ref(), mock_env(), addr2.clone()).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(5_000));
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let vesting = query_vesting(deps.as_ref(), env, addr2.clone()).unwrap();
+        assert_eq!(vesting.locked, Uint128::new(1_500));
+
+        // cannot lock more than the account holds
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            extend(&[(0, 1), (1000, 0)]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ExtendedVestingExceedsHoldings {
+                locked: Uint128::new(5_001),
+                held: Uint128::new(5_000),
+            }
+        );
+
+        // all of the balance is locked now
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr1,
+            amount: Uint128::new(1),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&addr2, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CantMoveVestingTokens {
+                effectively_locked: Uint128::new(5000)
+            }
+        );
+    }
+
+    #[test]
+    fn simulate_transfer_vesting() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let amount1 = Uint128::from(250_000u128);
 
-        // send with curve that never hits 0
-        let const_never_vests = ExecuteMsg::TransferVesting {
-            recipient: addr3.clone(),
-            amount: Uint128::new(10_000),
-            schedule: Curve::constant(2),
-        };
-        let err = execute(deps.as_mut(), mock_env(), info.clone(), const_never_vests).unwrap_err();
-        assert_eq!(err, ContractError::NeverFullyVested);
+        let info = mock_info(addr1.as_ref(), &coins(amount1.u128(), "AUTO"));
+        _do_instantiate(deps.as_mut(), &addr1, amount1, None, Some(info.clone()));
 
-        // fails with increasing curve
-        let increasing = ExecuteMsg::TransferVesting {
-            recipient: addr3.clone(),
-            amount: Uint128::new(10_000),
-            schedule: Curve::saturating_linear((start, 5_000), (end, 6_000)),
+        let start = mock_env().block.time.seconds();
+        let amount = Uint128::new(10_000);
+        let simulate = |deps: Deps, schedule: Curve| {
+            query_simulate_transfer_vesting(deps, mock_env(), addr2.clone(), amount, schedule)
+                .unwrap()
         };
-        let err = execute(deps.as_mut(), mock_env(), info.clone(), increasing).unwrap_err();
-        assert_eq!(err, ContractError::Curve(CurveError::MonotonicIncreasing));
 
-        // fails with too complex curve
-        let amount = Uint128::new(10_000);
-        let complex = ExecuteMsg::TransferVesting {
-            recipient: addr3.clone(),
+        // valid schedule for an account without vesting
+        let schedule = Curve::saturating_linear((start - 1000, 10_000), (start + 1000, 0));
+        let res = simulate(deps.as_ref(), schedule.clone());
+        assert_eq!(
+            res,
+            SimulateTransferVestingResponse {
+                valid: true,
+                error: None,
+                combined_size: 2,
+                locked_now: Uint128::new(5_000),
+                fully_vested_at: start + 1000,
+            }
+        );
+        // nothing was stored
+        assert_eq!(
+            query_vesting(deps.as_ref(), mock_env(), addr2.clone())
+                .unwrap()
+                .schedule,
+            None
+        );
+
+        let msg = ExecuteMsg::TransferVesting {
+            recipient: addr2.clone(),
             amount,
-            schedule: Curve::PiecewiseLinear(PiecewiseLinear {
-                steps: (start..end)
-                    .map(|x| (x, amount))
-                    .chain(std::iter::once((end, Uint128::new(0)))) // fully vest
-                    .collect(),
-            }),
+            schedule,
+            memo: None,
+            notify: false,
         };
-        let err = execute(deps.as_mut(), mock_env(), info.clone(), complex).unwrap_err();
-        assert_eq!(err, ContractError::Curve(CurveError::TooComplex));
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
-        // works with almost too complex curve
+        // valid combination with the existing schedule
+        let res = simulate(
+            deps.as_ref(),
+            Curve::saturating_linear((start, 8_000), (start + 2000, 0)),
+        );
+        assert_eq!(
+            res,
+            SimulateTransferVestingResponse {
+                valid: true,
+                error: None,
+                combined_size: 4,
+                locked_now: Uint128::new(13_000),
+                fully_vested_at: start + 2000,
+            }
+        );
+
+        // fully vested schedule keeps the existing one
+        let res = simulate(
+            deps.as_ref(),
+            Curve::saturating_linear((start - 2000, 10_000), (start - 1000, 0)),
+        );
+        assert_eq!(
+            res,
+            SimulateTransferVestingResponse {
+                valid: true,
+                error: None,
+                combined_size: 2,
+                locked_now: Uint128::new(5_000),
+                fully_vested_at: start + 1000,
+            }
+        );
+
+        // combined schedule would be too complex
         let max_complexity = MAX_VESTING_COMPLEXITY.load(&deps.storage).unwrap();
-        let end = start + max_complexity as u64 - 1;
-        let almost_too_complex = ExecuteMsg::TransferVesting {
-            recipient: addr3.clone(),
-            amount,
-            schedule: Curve::PiecewiseLinear(PiecewiseLinear {
+        let end = start + max_complexity as u64 - 2;
+        let res = simulate(
+            deps.as_ref(),
+            Curve::PiecewiseLinear(PiecewiseLinear {
                 steps: (start..end)
                     .map(|x| (x, amount))
-                    .chain(std::iter::once((end, Uint128::new(0)))) // fully vest
+                    .chain(std::iter::once((end, Uint128::new(0))))
                     .collect(),
             }),
-        };
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), almost_too_complex).unwrap();
-        assert_eq!(0, res.messages.len());
-
-        // but fails when adding a simple curve if the combined curve becomes too complex
-        let simple = ExecuteMsg::TransferVesting {
-            recipient: addr3,
-            amount,
-            schedule: Curve::saturating_linear((end, amount.u128()), (end + 1, 0)),
-        };
-        let err = execute(deps.as_mut(), mock_env(), info, simple).unwrap_err();
-        assert_eq!(err, ContractError::Curve(CurveError::TooComplex));
+        );
+        assert_eq!(
+            res,
+            SimulateTransferVestingResponse {
+                valid: false,
+                error: Some(ContractError::Curve(CurveError::TooComplex).to_string()),
+                combined_size: 0,
+                locked_now: Uint128::zero(),
+                fully_vested_at: 0,
+            }
+        );
     }
 
     #[test]
@@ -2053,9 +7051,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("marketing".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2109,9 +7109,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2164,9 +7166,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2219,9 +7223,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2274,9 +7280,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2329,9 +7337,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2384,9 +7394,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2443,9 +7455,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2498,9 +7512,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2535,6 +7551,100 @@ mod tests {
             );
         }
 
+        #[test]
+        fn logo_domain_allowlist() {
+            let mut deps = mock_dependencies();
+            let marketing = |logo: &str| InstantiateMarketingInfo {
+                project: None,
+                description: None,
+                marketing: Some("creator".to_owned()),
+                logo: Some(Logo::Url(logo.to_owned())),
+                allowed_logo_domains: vec!["WyndDAO.com ".to_owned()],
+            };
+            let instantiate_msg = |logo: &str| InstantiateMsg {
+                name: "Cash Token".to_string(),
+                symbol: "CASH".to_string(),
+                decimals: 9,
+                initial_balances: vec![],
+                mint: Some(MinterInfo {
+                    minter: "minter".to_owned(),
+                    cap: None,
+                    burns_reduce_cap: false,
+                }),
+                marketing: Some(marketing(logo)),
+                allowed_vesters: None,
+                max_curve_complexity: 10,
+                seeding_open: false,
+            };
+            let info = mock_info("creator", &[]);
+
+            let err = instantiate(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                instantiate_msg("https://evil.io/logo.png"),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::LogoDomainNotAllowed {
+                    host: "evil.io".to_owned()
+                }
+            );
+            instantiate(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                instantiate_msg("https://wynddao.com/logo.png"),
+            )
+            .unwrap();
+
+            let upload = |deps: DepsMut, logo: Logo| {
+                execute(deps, mock_env(), info.clone(), ExecuteMsg::UploadLogo(logo))
+            };
+            let url = |url: &str| Logo::Url(url.to_owned());
+
+            upload(deps.as_mut(), url("https://WYNDDAO.com:443/new.png?v=2")).unwrap();
+            for (logo, host) in [
+                (
+                    "https://wynddao.com.evil.io/logo.png",
+                    "wynddao.com.evil.io",
+                ),
+                ("https://wynddao.com@evil.io/logo.png", "evil.io"),
+                ("https://sub.wynddao.com/logo.png", "sub.wynddao.com"),
+                ("http://wynddao.com/logo.png", "wynddao.com"),
+                ("wynddao.com/logo.png", "wynddao.com/logo.png"),
+            ] {
+                let err = upload(deps.as_mut(), url(logo)).unwrap_err();
+                assert_eq!(
+                    err,
+                    ContractError::LogoDomainNotAllowed {
+                        host: host.to_owned()
+                    }
+                );
+            }
+            assert_eq!(
+                query_marketing_info(deps.as_ref()).unwrap().logo,
+                Some(LogoInfo::Url(
+                    "https://WYNDDAO.com:443/new.png?v=2".to_owned()
+                ))
+            );
+
+            // embedded logos are not checked
+            upload(
+                deps.as_mut(),
+                Logo::Embedded(EmbeddedLogo::Png(PNG_HEADER.into())),
+            )
+            .unwrap();
+
+            // only the minter can change the allowed domains
+            let msg = ExecuteMsg::UpdateLogoDomains { domains: vec![] };
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+            upload(deps.as_mut(), url("http://evil.io/logo.png")).unwrap();
+        }
+
         #[test]
         fn update_logo_png() {
             let mut deps = mock_dependencies();
@@ -2549,9 +7659,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2601,9 +7713,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2654,9 +7768,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2706,9 +7822,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2765,9 +7883,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2817,9 +7937,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2855,6 +7977,144 @@ mod tests {
                 err
             );
         }
+
+        #[test]
+        fn logo_updater() {
+            let mut deps = mock_dependencies();
+            let instantiate_msg = InstantiateMsg {
+                name: "Cash Token".to_string(),
+                symbol: "CASH".to_string(),
+                decimals: 9,
+                initial_balances: vec![],
+                mint: None,
+                marketing: Some(InstantiateMarketingInfo {
+                    project: Some("Project".to_owned()),
+                    description: Some("Description".to_owned()),
+                    marketing: Some("marketing".to_owned()),
+                    logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
+                }),
+                allowed_vesters: None,
+                max_curve_complexity: 10,
+                seeding_open: false,
+            };
+            let marketing = mock_info("marketing", &[]);
+            let updater = mock_info("updater", &[]);
+            instantiate(
+                deps.as_mut(),
+                mock_env(),
+                marketing.clone(),
+                instantiate_msg,
+            )
+            .unwrap();
+
+            let set_updater = |address: Option<&str>| ExecuteMsg::SetLogoUpdater {
+                address: address.map(str::to_owned),
+            };
+            let update = |project: &str, marketing: Option<&str>| ExecuteMsg::UpdateMarketing {
+                project: Some(project.to_owned()),
+                description: Some("Hot key description".to_owned()),
+                marketing: marketing.map(str::to_owned),
+            };
+            let upload = |url: &str| ExecuteMsg::UploadLogo(Logo::Url(url.to_owned()));
+
+            // nothing is allowed before being set
+            let err =
+                execute(deps.as_mut(), mock_env(), updater.clone(), upload("new")).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            // only the marketing address can set it
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                updater.clone(),
+                set_updater(Some("updater")),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                marketing.clone(),
+                set_updater(Some("updater")),
+            )
+            .unwrap();
+            let res: LogoUpdaterResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::LogoUpdater {}).unwrap())
+                    .unwrap();
+            assert_eq!(res.logo_updater, Some(Addr::unchecked("updater")));
+
+            // the updater can change logo, project and description
+            execute(deps.as_mut(), mock_env(), updater.clone(), upload("new")).unwrap();
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                updater.clone(),
+                update("Hot", None),
+            )
+            .unwrap();
+            assert_eq!(
+                query_marketing_info(deps.as_ref()).unwrap(),
+                MarketingInfoResponse {
+                    project: Some("Hot".to_owned()),
+                    description: Some("Hot key description".to_owned()),
+                    marketing: Some(Addr::unchecked("marketing")),
+                    logo: Some(LogoInfo::Url("new".to_owned())),
+                }
+            );
+
+            // but it cannot escalate its privileges
+            for msg in [
+                update("Evil", Some("updater")),
+                update("Evil", Some("")),
+                set_updater(Some("other")),
+                set_updater(None),
+            ] {
+                let err = execute(deps.as_mut(), mock_env(), updater.clone(), msg).unwrap_err();
+                assert_eq!(err, ContractError::Unauthorized {});
+            }
+            let info = query_marketing_info(deps.as_ref()).unwrap();
+            assert_eq!(info.project, Some("Hot".to_owned()));
+            assert_eq!(info.marketing, Some(Addr::unchecked("marketing")));
+
+            // the marketing address can clear it
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                marketing.clone(),
+                set_updater(None),
+            )
+            .unwrap();
+            let err = execute(deps.as_mut(), mock_env(), updater.clone(), upload("x")).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            // a new marketing address does not inherit the updater
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                marketing.clone(),
+                set_updater(Some("updater")),
+            )
+            .unwrap();
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                marketing.clone(),
+                update("Project", Some("new_marketing")),
+            )
+            .unwrap();
+            let err = execute(deps.as_mut(), mock_env(), updater, upload("x")).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            let err = execute(deps.as_mut(), mock_env(), marketing, upload("x")).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("new_marketing", &[]),
+                upload("x"),
+            )
+            .unwrap();
+        }
     }
     mod address_list {
         use super::*;
@@ -2872,9 +8132,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2887,6 +8149,7 @@ mod tests {
                 info,
                 ExecuteMsg::AllowVester {
                     address: "addr1".to_string(),
+                    policy_exempt: false,
                 },
             )
             .unwrap();
@@ -2912,9 +8175,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -2927,6 +8192,7 @@ mod tests {
                 info.clone(),
                 ExecuteMsg::AllowVester {
                     address: "addr1".to_string(),
+                    policy_exempt: false,
                 },
             )
             .unwrap();
@@ -2944,6 +8210,7 @@ mod tests {
                 info,
                 ExecuteMsg::AllowVester {
                     address: "addr1".to_string(),
+                    policy_exempt: false,
                 },
             )
             .unwrap_err();
@@ -2960,6 +8227,7 @@ mod tests {
                 addr2_info,
                 ExecuteMsg::AllowVester {
                     address: "addr2".to_string(),
+                    policy_exempt: false,
                 },
             )
             .unwrap_err();
@@ -2980,9 +8248,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: Some(vec!["airdrop".to_string(), "creator".to_string()]),
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -3020,9 +8290,11 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some("creator".to_owned()),
                     logo: Some(Logo::Url("url".to_owned())),
+                    allowed_logo_domains: vec![],
                 }),
                 allowed_vesters: None,
                 max_curve_complexity: 10,
+                seeding_open: false,
             };
 
             let info = mock_info("creator", &[]);
@@ -3061,10 +8333,124 @@ mod tests {
                 addr2_info,
                 ExecuteMsg::AllowVester {
                     address: "addr2".to_string(),
+                    policy_exempt: false,
                 },
             )
             .unwrap_err();
             assert_eq!(err_unauthorized, ContractError::Unauthorized {});
         }
+
+        #[test]
+        fn address_list_is_normalized() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "creator", Uint128::new(1000));
+            let info = mock_info("creator", &[]);
+
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::AllowVester {
+                    address: "ADDR1".to_string(),
+                    policy_exempt: false,
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                query_allow_list(deps.as_ref()).unwrap().allow_list,
+                vec!["creator".to_string(), "addr1".to_string()]
+            );
+
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::AllowVester {
+                    address: "addr1".to_string(),
+                    policy_exempt: false,
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::AddressAlreadyExist {});
+
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                ExecuteMsg::DenyVester {
+                    address: "Addr1".to_string(),
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                query_allow_list(deps.as_ref()).unwrap().allow_list,
+                vec!["creator".to_string()]
+            );
+        }
+
+        #[test]
+        fn mixed_case_entries_match_before_and_after_migration() {
+            let mut deps = mock_dependencies();
+            do_instantiate_with_minter(
+                deps.as_mut(),
+                "creator",
+                Uint128::new(1000),
+                "minter",
+                None,
+            );
+
+            // entries as they may have been stored before addresses were normalized
+            ALLOWLIST
+                .save(
+                    &mut deps.storage,
+                    &vec![
+                        Addr::unchecked("Creator"),
+                        Addr::unchecked("VESTER"),
+                        Addr::unchecked("creator"),
+                        Addr::unchecked("ab"),
+                    ],
+                )
+                .unwrap();
+            POLICY_EXEMPT
+                .save(&mut deps.storage, &Addr::unchecked("VESTER"), &())
+                .unwrap();
+
+            // the check already matches the normalized sender
+            assert_allowed_vester(deps.as_ref(), &Addr::unchecked("vester")).unwrap();
+            let err = assert_allowed_vester(deps.as_ref(), &Addr::unchecked("other")).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.0.1").unwrap();
+            let msg = MigrateMsg {
+                picewise_linear_curve: Curve::PiecewiseLinear(PiecewiseLinear {
+                    steps: vec![(0, Uint128::new(5000)), (100, Uint128::new(10000))],
+                }),
+            };
+            let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+            // the invalid entry is dropped with an event, the others are rewritten
+            assert_eq!(res.events.len(), 1);
+            assert_eq!(res.events[0].ty, "allowlist_entry_dropped");
+            assert_eq!(res.events[0].attributes[0], attr("address", "ab"));
+            assert_eq!(
+                query_allow_list(deps.as_ref()).unwrap().allow_list,
+                vec!["creator".to_string(), "vester".to_string()]
+            );
+            assert!(POLICY_EXEMPT.has(&deps.storage, &Addr::unchecked("vester")));
+            assert!(!POLICY_EXEMPT.has(&deps.storage, &Addr::unchecked("VESTER")));
+
+            // and still match afterwards
+            assert_allowed_vester(deps.as_ref(), &Addr::unchecked("vester")).unwrap();
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("vester", &[]),
+                ExecuteMsg::AllowVester {
+                    address: "addr2".to_string(),
+                    policy_exempt: false,
+                },
+            )
+            .unwrap();
+        }
     }
 }
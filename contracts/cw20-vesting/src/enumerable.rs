@@ -1,7 +1,10 @@
-use cosmwasm_std::{Deps, Order, StdResult};
+use cosmwasm_std::{Deps, Env, Order, StdResult};
 use cw20::{AllAccountsResponse, AllAllowancesResponse, AllowanceInfo};
 
-use crate::state::{ALLOWANCES, BALANCES};
+use crate::msg::{ReservationInfo, ReservationsResponse, VestingGrantInfo, VestingHistoryResponse};
+use crate::state::{
+    ALLOWANCES, BALANCES, DELEGATION_ALLOWANCES, RESERVED, RESERVED_BY_SPENDER, VESTING_MEMOS,
+};
 use cw_storage_plus::Bound;
 
 // settings for pagination
@@ -33,16 +36,133 @@ pub fn query_all_allowances(
     Ok(AllAllowancesResponse { allowances })
 }
 
+pub fn query_all_delegation_allowances(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllAllowancesResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+
+    let allowances = DELEGATION_ALLOWANCES
+        .prefix(&owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(addr, allow)| AllowanceInfo {
+                spender: addr.into(),
+                allowance: allow.allowance,
+                expires: allow.expires,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(AllAllowancesResponse { allowances })
+}
+
+/// Expired reservations are skipped, as they are released already
+pub fn query_reservations(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ReservationsResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+
+    let reservations = RESERVED
+        .prefix(&owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, r)) if r.expires.is_expired(&env.block)))
+        .take(limit)
+        .map(|item| {
+            item.map(|(spender, r)| ReservationInfo {
+                owner: owner.clone(),
+                spender: spender.into(),
+                amount: r.amount,
+                expires: r.expires,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ReservationsResponse { reservations })
+}
+
+/// Expired reservations are skipped, as they are released already
+pub fn query_reservations_by_spender(
+    deps: Deps,
+    env: Env,
+    spender: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ReservationsResponse> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+
+    let reservations = RESERVED_BY_SPENDER
+        .prefix(&spender_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, r)) if r.expires.is_expired(&env.block)))
+        .take(limit)
+        .map(|item| {
+            item.map(|(owner, r)| ReservationInfo {
+                owner: owner.into(),
+                spender: spender.clone(),
+                amount: r.amount,
+                expires: r.expires,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ReservationsResponse { reservations })
+}
+
+pub fn query_vesting_history(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<VestingHistoryResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let grants = VESTING_MEMOS
+        .prefix(&address)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(seq, grant)| VestingGrantInfo {
+                seq,
+                memo: grant.memo,
+                amount: grant.amount,
+                grantor: grant.grantor.into(),
+                timestamp: grant.timestamp,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(VestingHistoryResponse { grants })
+}
+
 pub fn query_all_accounts(
     deps: Deps,
     start_after: Option<String>,
     limit: Option<u32>,
+    exclude: Option<Vec<String>>,
 ) -> StdResult<AllAccountsResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+    let exclude = exclude
+        .unwrap_or_default()
+        .into_iter()
+        .map(|addr| deps.api.addr_validate(&addr))
+        .collect::<StdResult<Vec<_>>>()?;
 
     let accounts = BALANCES
         .keys(deps.storage, start, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok(addr) if exclude.contains(addr)))
         .take(limit)
         .map(|item| item.map(Into::into))
         .collect::<StdResult<_>>()?;
@@ -71,11 +191,14 @@ mod tests {
                 address: addr.into(),
                 amount,
                 vesting: None,
+                memo: None,
+                relative_vesting: None,
             }],
             mint: None,
             marketing: None,
             allowed_vesters: None,
             max_curve_complexity: 10,
+            seeding_open: false,
         };
         let info = mock_info("creator", &[]);
         let env = mock_env();
@@ -146,6 +269,69 @@ mod tests {
         assert_eq!(&allow.allowance, &allow2);
     }
 
+    #[test]
+    fn query_all_delegation_allowances_works() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let owner = String::from("owner");
+        let spender1 = String::from("earlier");
+        let spender2 = String::from("later");
+
+        let info = mock_info(owner.as_ref(), &[]);
+        let env = mock_env();
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(12340000));
+
+        // transfer allowances are not listed
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender1.clone(),
+            amount: Uint128::new(100),
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let allowances =
+            query_all_delegation_allowances(deps.as_ref(), owner.clone(), None, None).unwrap();
+        assert_eq!(allowances.allowances, vec![]);
+
+        let expires = Expiration::AtHeight(5432);
+        for (spender, expires) in [(&spender1, Some(expires)), (&spender2, None)] {
+            let msg = ExecuteMsg::IncreaseDelegationAllowance {
+                spender: spender.clone(),
+                amount: Uint128::new(7777),
+                expires,
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let allowances =
+            query_all_delegation_allowances(deps.as_ref(), owner.clone(), None, None).unwrap();
+        assert_eq!(allowances.allowances.len(), 2);
+
+        // first one is spender1 (order of CanonicalAddr uncorrelated with String)
+        let allowances =
+            query_all_delegation_allowances(deps.as_ref(), owner.clone(), None, Some(1)).unwrap();
+        assert_eq!(
+            allowances.allowances,
+            vec![AllowanceInfo {
+                spender: spender1.clone(),
+                allowance: Uint128::new(7777),
+                expires,
+            }]
+        );
+
+        // next one is spender2
+        let allowances =
+            query_all_delegation_allowances(deps.as_ref(), owner, Some(spender1), Some(10000))
+                .unwrap();
+        assert_eq!(
+            allowances.allowances,
+            vec![AllowanceInfo {
+                spender: spender2,
+                allowance: Uint128::new(7777),
+                expires: Expiration::Never {},
+            }]
+        );
+    }
+
     #[test]
     fn query_all_accounts_works() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
@@ -194,20 +380,50 @@ mod tests {
         .unwrap();
 
         // make sure we get the proper results
-        let accounts = query_all_accounts(deps.as_ref(), None, None).unwrap();
+        let accounts = query_all_accounts(deps.as_ref(), None, None, None).unwrap();
         assert_eq!(accounts.accounts, expected_order);
 
         // let's do pagination
-        let accounts = query_all_accounts(deps.as_ref(), None, Some(2)).unwrap();
+        let accounts = query_all_accounts(deps.as_ref(), None, Some(2), None).unwrap();
         assert_eq!(accounts.accounts, expected_order[0..2].to_vec());
 
-        let accounts =
-            query_all_accounts(deps.as_ref(), Some(accounts.accounts[1].clone()), Some(1)).unwrap();
+        let accounts = query_all_accounts(
+            deps.as_ref(),
+            Some(accounts.accounts[1].clone()),
+            Some(1),
+            None,
+        )
+        .unwrap();
         assert_eq!(accounts.accounts, expected_order[2..3].to_vec());
 
-        let accounts =
-            query_all_accounts(deps.as_ref(), Some(accounts.accounts[0].clone()), Some(777))
-                .unwrap();
+        let accounts = query_all_accounts(
+            deps.as_ref(),
+            Some(accounts.accounts[0].clone()),
+            Some(777),
+            None,
+        )
+        .unwrap();
         assert_eq!(accounts.accounts, expected_order[3..].to_vec());
+
+        // excluded accounts are skipped, but don't count towards the limit
+        let exclude = Some(vec![acct1.clone()]);
+        let accounts = query_all_accounts(deps.as_ref(), None, Some(2), exclude.clone()).unwrap();
+        assert_eq!(
+            accounts.accounts,
+            vec![expected_order[0].clone(), expected_order[2].clone()]
+        );
+
+        let accounts = query_all_accounts(
+            deps.as_ref(),
+            Some(accounts.accounts[1].clone()),
+            Some(2),
+            exclude.clone(),
+        )
+        .unwrap();
+        assert_eq!(accounts.accounts, expected_order[3..].to_vec());
+
+        // paginating from an excluded account continues after it
+        let accounts = query_all_accounts(deps.as_ref(), Some(acct1), None, exclude).unwrap();
+        assert_eq!(accounts.accounts, expected_order[2..].to_vec());
     }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{OverflowError, StdError};
+use cosmwasm_std::{OverflowError, StdError, Uint128};
 use thiserror::Error;
 use wynd_utils::CurveError;
 
@@ -28,12 +28,24 @@ pub enum ContractError {
     #[error("Invalid zero amount")]
     InvalidZeroAmount {},
 
+    #[error("Memo must be at most {max} characters")]
+    MemoTooLong { max: usize },
+
     #[error("Allowance is expired")]
     Expired {},
 
     #[error("No allowance for this account")]
     NoAllowance {},
 
+    #[error("Permit is expired")]
+    PermitExpired {},
+
+    #[error("Permit signature does not match the owner")]
+    InvalidPermitSignature {},
+
+    #[error("Permit nonce must be {expected}, got {got}")]
+    InvalidPermitNonce { expected: u64, got: u64 },
+
     #[error("Minting cannot exceed the cap")]
     CannotExceedCap {},
 
@@ -46,6 +58,9 @@ pub enum ContractError {
     #[error("Invalid png header")]
     InvalidPngHeader {},
 
+    #[error("Logo url must be https on an allowed domain, got host {host}")]
+    LogoDomainNotAllowed { host: String },
+
     #[error("Duplicate initial balance addresses")]
     DuplicateInitialBalanceAddresses {},
 
@@ -55,8 +70,11 @@ pub enum ContractError {
     #[error("The transfer tries to vest more tokens than it sends")]
     VestsMoreThanSent,
 
-    #[error("The transfer would have moved tokens still locked by a vesting schedule")]
-    CantMoveVestingTokens,
+    #[error("The transfer would have moved tokens still locked by a vesting schedule, {effectively_locked} of the balance are locked after accounting for delegated tokens")]
+    CantMoveVestingTokens { effectively_locked: Uint128 },
+
+    #[error("The transfer would have moved tokens reserved for another address")]
+    CantMoveReservedTokens,
 
     #[error("Can't delegate tokens - sum of balance plus vested is too low")]
     NotEnoughToDelegate,
@@ -90,6 +108,82 @@ pub enum ContractError {
 
     #[error("Migration error - provided curve is not picewise linear!")]
     MigrationIncorrectCurve {},
+
+    #[error("Max vesting complexity can only be raised, current value is {current}")]
+    CannotLowerMaxVestingComplexity { current: u64 },
+
+    #[error("Initial supply {supply} exceeds the current cap {cap_now} (cap will reach at most {cap_max})")]
+    InitialSupplyExceedsCap {
+        supply: Uint128,
+        cap_now: Uint128,
+        cap_max: Uint128,
+    },
+
+    #[error("Minting cap curve must never decrease")]
+    CapCurveMustIncrease {},
+
+    #[error("Cannot reserve more than {available} tokens")]
+    ReserveExceedsAvailable { available: Uint128 },
+
+    #[error("No active reservation found")]
+    NoReservation {},
+
+    #[error("Only {reserved} tokens are reserved")]
+    SeizeExceedsReservation { reserved: Uint128 },
+
+    #[error("Extended vesting schedule locks {locked} tokens, but only {held} are held")]
+    ExtendedVestingExceedsHoldings { locked: Uint128, held: Uint128 },
+
+    #[error("TransferVesting cannot transfer zero tokens, use ExtendVesting to lengthen an existing schedule")]
+    ZeroAmountVestingTransfer {},
+
+    #[error("Vesting can only be extended by a positive number of seconds")]
+    InvalidVestingExtension {},
+
+    #[error("Address has no tokens locked by a vesting schedule")]
+    NoVestingToExtend {},
+
+    #[error("Only one of vesting and relative_vesting can be set")]
+    AmbiguousVestingSchedule {},
+
+    #[error("Vesting schedule must be fully vested by {latest_end}")]
+    VestingEndsTooLate { latest_end: u64 },
+
+    #[error("Only seeding balances is possible until seeding is finished")]
+    SeedingOpen {},
+
+    #[error("Seeding balances is closed")]
+    SeedingClosed {},
+
+    #[error("Batch must contain at least one transfer")]
+    EmptyBatch {},
+
+    #[error("Batch cannot contain more than {max} transfers")]
+    TooManyTransfers { max: usize },
+
+    #[error("Cannot clear more than {max} vesting schedules at once")]
+    TooManyAddresses { max: usize },
+
+    #[error("Spending limit exceeded, only {remaining} tokens can be sent in this window")]
+    SpendingLimitExceeded { remaining: Uint128 },
+
+    #[error("Spending limit window must not be zero")]
+    InvalidSpendingLimit {},
+
+    #[error("Vesting schedule unlocks tokens at {unlocks_at}, before the minimum cliff of {min_cliff_seconds} seconds")]
+    VestingCliffTooShort {
+        min_cliff_seconds: u64,
+        unlocks_at: u64,
+    },
+
+    #[error("Vesting schedule is not fully vested within the maximum duration of {max_duration_seconds} seconds")]
+    VestingDurationTooLong {
+        max_duration_seconds: u64,
+        fully_vested_at: Option<u64>,
+    },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
 }
 
 impl From<OverflowError> for ContractError {
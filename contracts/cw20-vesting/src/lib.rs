@@ -28,11 +28,17 @@ mod error;
 /// custom input output messages
 pub mod msg;
 
+/// allowances authorized by an off-chain signature of the owner
+pub mod permit;
+
+/// escrow of tokens that stay in the owner's balance
+pub mod reservations;
+
 /// state on the blockchain
 pub mod state;
 pub use crate::error::ContractError;
 pub use crate::msg::{ExecuteMsg, InitBalance, InstantiateMsg, MinterInfo, QueryMsg};
-pub use crate::receive_delegate::Cw20ReceiveDelegationMsg;
+pub use crate::receive_delegate::{Cw20ReceiveDelegationMsg, Cw20ReceiveVestingMsg};
 
 #[cfg(test)]
 mod multitest;
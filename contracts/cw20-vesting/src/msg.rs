@@ -4,8 +4,9 @@ use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::state::SpendingLimit;
 use crate::ContractError;
-use wynd_utils::Curve;
+use wynd_utils::{Curve, CurveError, PiecewiseLinear, ScalableCurve};
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct InstantiateMarketingInfo {
@@ -13,6 +14,9 @@ pub struct InstantiateMarketingInfo {
     pub description: Option<String>,
     pub marketing: Option<String>,
     pub logo: Option<Logo>,
+    /// If not empty, logo urls have to use https and one of these hosts exactly
+    #[serde(default)]
+    pub allowed_logo_domains: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
@@ -25,6 +29,10 @@ pub struct InstantiateMsg {
     pub marketing: Option<InstantiateMarketingInfo>,
     pub allowed_vesters: Option<Vec<String>>,
     pub max_curve_complexity: u64,
+    /// Allows the instantiator to add more initial balances with `ExecuteMsg::SeedBalances`
+    /// until `ExecuteMsg::FinishSeeding`. All other executes are rejected until then.
+    #[serde(default)]
+    pub seeding_open: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
@@ -34,18 +42,60 @@ pub struct InitBalance {
     /// Optional vesting schedule
     /// It must be a decreasing curve, ending at 0, and never exceeding amount
     pub vesting: Option<Curve>,
+    /// Optional reason for the vesting grant, recorded in the vesting history.
+    /// At most `MAX_MEMO_LENGTH` characters
+    #[serde(default)]
+    pub memo: Option<String>,
+    /// Optional vesting schedule relative to the instantiation time, instead of `vesting`
+    #[serde(default)]
+    pub relative_vesting: Option<RelativeSchedule>,
+}
+
+/// Vesting of the whole amount, starting at instantiation
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RelativeSchedule {
+    /// Seconds all tokens stay locked
+    pub cliff: u64,
+    /// Seconds over which the tokens are released linearly after the cliff.
+    /// If 0, all tokens are released at once, one second after the cliff.
+    pub duration: u64,
+}
+
+impl RelativeSchedule {
+    /// The schedule as an absolute curve, with `amount` locked from `start`
+    pub fn to_curve(&self, start: Timestamp, amount: Uint128) -> Curve {
+        let unlock_start = start.seconds().saturating_add(self.cliff);
+        if self.duration == 0 {
+            // a curve cannot drop within a single point in time, so this is the closest step
+            return Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: vec![
+                    (unlock_start, amount),
+                    (unlock_start.saturating_add(1), Uint128::zero()),
+                ],
+            });
+        }
+        Curve::saturating_linear(
+            (unlock_start, amount.u128()),
+            (unlock_start.saturating_add(self.duration), 0),
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct MinterInfo {
     pub minter: String,
     /// cap is a hard cap on total supply that can be achieved by minting.
-    /// This can be a monotonically increasing curve based on block time
-    /// (constant value being a special case of this).
+    /// This must be a monotonically increasing curve based on block time
+    /// (constant value being a special case of this). It is evaluated at the current block
+    /// time whenever tokens are minted, and the initial supply must not exceed its value at
+    /// instantiation.
     ///
     /// Note that cap refers to total_supply.
     /// If None, there is unlimited cap.
     pub cap: Option<Curve>,
+    /// If true, burned tokens are subtracted from the cap, so they can never be minted again.
+    #[serde(default)]
+    pub burns_reduce_cap: bool,
 }
 
 impl InstantiateMsg {
@@ -53,6 +103,7 @@ impl InstantiateMsg {
         self.mint.as_ref().and_then(|v| v.cap.as_ref())
     }
 
+    /// Evaluates the cap curve at the given time. Initial balances have to fit under this value.
     pub fn get_cap(&self, block_time: &Timestamp) -> Option<Uint128> {
         self.get_curve().map(|v| v.value(block_time.seconds()))
     }
@@ -69,13 +120,21 @@ impl InstantiateMsg {
             return Err(ContractError::TooManyDecimals);
         }
         if let Some(curve) = self.get_curve() {
-            curve.validate_monotonic_increasing()?;
+            // a decreasing cap could drop below the total supply later, blocking any minting
+            curve
+                .validate_monotonic_increasing()
+                .map_err(|err| match err {
+                    CurveError::NotMonotonic | CurveError::MonotonicDecreasing => {
+                        ContractError::CapCurveMustIncrease {}
+                    }
+                    err => err.into(),
+                })?;
         }
         Ok(())
     }
 }
 
-fn is_valid_name(name: &str) -> bool {
+pub(crate) fn is_valid_name(name: &str) -> bool {
     let bytes = name.as_bytes();
     if bytes.len() < 3 || bytes.len() > 50 {
         return false;
@@ -96,28 +155,76 @@ fn is_valid_symbol(symbol: &str) -> bool {
     true
 }
 
-/// Asserts the vesting schedule decreases to 0 eventually, and is never more than the
-/// amount being sent. If it doesn't match these conditions, returns an error.
+/// Symbols set after instantiation are stricter: 2 to 8 uppercase letters
+pub(crate) fn is_valid_updated_symbol(symbol: &str) -> bool {
+    (2..=8).contains(&symbol.len()) && symbol.bytes().all(|byte| byte.is_ascii_uppercase())
+}
+
+/// Longest time into the future a vesting schedule can reach, about 200 years
+pub const MAX_VESTING_SECONDS: u64 = 200 * 365 * 24 * 60 * 60;
+
+/// Asserts the vesting schedule decreases to 0 eventually, within `MAX_VESTING_SECONDS`.
+/// Every schedule that is created or changed has to pass this before it is stored.
+pub fn assert_valid_schedule(schedule: &Curve, block: &BlockInfo) -> Result<(), ContractError> {
+    schedule.validate_monotonic_decreasing()?;
+    let latest_end = block.time.seconds().saturating_add(MAX_VESTING_SECONDS);
+    if schedule.range().0 != 0 {
+        Err(ContractError::NeverFullyVested)
+    } else if fully_vested_at(schedule) > latest_end {
+        Err(ContractError::VestingEndsTooLate { latest_end })
+    } else {
+        Ok(())
+    }
+}
+
+/// Asserts the vesting schedule is valid (see [`assert_valid_schedule`]) and is never more
+/// than the amount being sent. If it doesn't match these conditions, returns an error.
 pub fn assert_schedule_vests_amount(
     schedule: &Curve,
     amount: Uint128,
+    block: &BlockInfo,
 ) -> Result<(), ContractError> {
-    schedule.validate_monotonic_decreasing()?;
-    let (low, high) = schedule.range();
-    if low != 0 {
-        Err(ContractError::NeverFullyVested)
-    } else if high > amount.u128() {
+    assert_valid_schedule(schedule, block)?;
+    if schedule.range().1 > amount.u128() {
         Err(ContractError::VestsMoreThanSent)
     } else {
         Ok(())
     }
 }
 
+/// Maximum length of a vesting grant memo, in characters
+pub const MAX_MEMO_LENGTH: usize = 256;
+
+/// Asserts the memo of a vesting grant is not longer than `MAX_MEMO_LENGTH`
+pub fn validate_memo(memo: Option<&String>) -> Result<(), ContractError> {
+    match memo {
+        Some(memo) if memo.chars().count() > MAX_MEMO_LENGTH => Err(ContractError::MemoTooLong {
+            max: MAX_MEMO_LENGTH,
+        }),
+        _ => Ok(()),
+    }
+}
+
 /// Returns true if curve is already at 0
 pub fn fully_vested(schedule: &Curve, block: &BlockInfo) -> bool {
     schedule.value(block.time.seconds()).is_zero()
 }
 
+/// Returns the time at which a monotonically decreasing schedule reaches its final value
+pub fn fully_vested_at(schedule: &Curve) -> u64 {
+    match schedule {
+        Curve::Constant { .. } => 0,
+        Curve::SaturatingLinear(s) => s.max_x,
+        Curve::PiecewiseLinear(p) => p.steps.last().map(|(x, _)| *x).unwrap_or_default(),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct BatchTransfer {
+    pub recipient: String,
+    pub amount: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -132,7 +239,48 @@ pub enum ExecuteMsg {
         /// VestingSchedule.
         /// It must be a decreasing curve, ending at 0, and never exceeding amount
         schedule: Curve,
+        /// Optional reason for the grant, recorded in the recipient's vesting history.
+        /// At most `MAX_MEMO_LENGTH` characters
+        #[serde(default)]
+        memo: Option<String>,
+        /// If set and the recipient is a contract, it is sent a `Cw20ReceiveVestingMsg`.
+        /// A recipient that fails to handle it does not block the transfer.
+        #[serde(default)]
+        notify: bool,
     },
+    /// Moves tokens to several accounts at once. Vesting and reservation locks of the sender are
+    /// checked once against the total. If any transfer is invalid, the whole batch fails.
+    TransferBatch { transfers: Vec<BatchTransfer> },
+    /// Like `TransferBatch`, but every recipient gets the given schedule scaled to the amount
+    /// they receive. Only callable by addresses on the vesting allow list.
+    TransferVestingBatch {
+        transfers: Vec<BatchTransfer>,
+        /// Decreasing schedule ending at 0, scaled to each amount. It must never lock more
+        /// than the full amount.
+        schedule: ScalableCurve,
+    },
+    /// Locks more of the address' tokens by adding the given steps to its vesting schedule.
+    /// Only callable by addresses on the vesting allow list. The added steps have to respect
+    /// the vesting policy, unless the sender is exempt.
+    ExtendVestingSchedule {
+        address: String,
+        /// Steps of a decreasing piecewise linear curve, ending at 0 within `MAX_VESTING_SECONDS`.
+        /// The address must hold all tokens locked by the extended schedule.
+        additional_steps: Vec<(u64, Uint128)>,
+    },
+    /// Delays the rest of the address' vesting schedule by `extend_by_seconds`, so that the
+    /// amount locked now is released later. Already released tokens are not locked again.
+    /// The delayed schedule must still end within `MAX_VESTING_SECONDS` and respect the vesting
+    /// policy, unless the sender is exempt.
+    /// Only callable by addresses on the vesting allow list.
+    ExtendVesting {
+        address: String,
+        extend_by_seconds: u64,
+    },
+    /// Removes the stored vesting schedules of the given addresses that are fully vested,
+    /// freeing their storage. Addresses that are still vesting are skipped.
+    /// Callable by anyone, at most `MAX_CLEAR_VESTING` addresses at once.
+    ClearExpiredVesting { addresses: Vec<String> },
     /// Burn is a base message to destroy tokens forever
     Burn { amount: Uint128 },
     /// Send is a base message to transfer tokens to a contract and trigger an action
@@ -180,10 +328,41 @@ pub enum ExecuteMsg {
     Mint { recipient: String, amount: Uint128 },
     /// Only with the "mintable" extension. If minter set and authorized by current
     /// minter, makes the new address the minter.
-    UpdateMinter { minter: String },
+    /// Optionally toggles whether burns reduce the cap, leaving it unchanged if None.
+    UpdateMinter {
+        minter: String,
+        #[serde(default)]
+        burns_reduce_cap: Option<bool>,
+    },
+    /// Only with the "mintable" extension. If authorized by current minter, corrects the token
+    /// name and/or symbol. Setting None/null for any of these will leave it unchanged.
+    /// Decimals cannot be changed, as that would change the meaning of all existing balances.
+    UpdateTokenInfo {
+        name: Option<String>,
+        symbol: Option<String>,
+    },
+    /// Only with the "mintable" extension. If authorized by current minter, raises the maximum
+    /// complexity of vesting curves. Lowering it is rejected, as it could strand existing curves.
+    UpdateMaxVestingComplexity { max: u64 },
+    /// Only with the "mintable" extension. If authorized by current minter, sets a vesting
+    /// complexity limit for the given address, used instead of the global maximum.
+    SetComplexityTier { address: String, max_complexity: u8 },
+    /// Only with the "mintable" extension. If authorized by current minter, limits how much any
+    /// sender can move with `Transfer`, `Send`, `TransferVesting` and their batch variants per
+    /// window. Tokens moved by spenders with `TransferFrom` and `SendFrom` count against the
+    /// limit of their owner. None removes the limit.
+    SetSpendingLimit { limit: Option<SpendingLimit> },
+    /// Only with the "mintable" extension. If authorized by current minter, sets bounds that
+    /// every schedule sent with `TransferVesting` and `TransferVestingBatch` or changed with
+    /// `ExtendVestingSchedule` and `ExtendVesting` has to respect, unless the sender is exempt. None removes the bound.
+    SetVestingPolicy {
+        min_cliff_seconds: Option<u64>,
+        max_duration_seconds: Option<u64>,
+    },
     /// Only with the "marketing" extension. If authorized, updates marketing metadata.
     /// Setting None/null for any of these will leave it unchanged.
-    /// Setting Some("") will clear this field on the contract storage
+    /// Setting Some("") will clear this field on the contract storage.
+    /// The logo updater can change everything except `marketing`.
     UpdateMarketing {
         /// A URL pointing to the project behind this token.
         project: Option<String>,
@@ -192,18 +371,116 @@ pub enum ExecuteMsg {
         /// The address (if any) who can update this data structure
         marketing: Option<String>,
     },
-    /// If set as the "marketing" role on the contract, upload a new URL, SVG, or PNG for the token
+    /// If set as the "marketing" role or the logo updater on the contract, upload a new URL, SVG,
+    /// or PNG for the token
     UploadLogo(Logo),
-    /// If set, it will add an address to a permission list on TransferVesting
-    AllowVester { address: String },
+    /// Only with the "marketing" extension. If set as the "marketing" role, sets an address that
+    /// can upload the logo and update project and description, but not the marketing address.
+    /// None removes it. It is also removed when the marketing address changes.
+    SetLogoUpdater { address: Option<String> },
+    /// Only with the "mintable" extension. If authorized by current minter, sets the hosts logo
+    /// urls are allowed to point to. Embedded logos are not affected. An empty list allows any url.
+    UpdateLogoDomains { domains: Vec<String> },
+    /// If set, it will add an address to a permission list on TransferVesting.
+    /// A `policy_exempt` vester does not have to follow the vesting policy.
+    AllowVester {
+        address: String,
+        #[serde(default)]
+        policy_exempt: bool,
+    },
     /// If set, it will remove an address to a permission list on TransferVesting
     DenyVester { address: String },
     /// Allows minter to update staking address
     UpdateStakingAddress { address: String },
     /// Delegates excess of tokens
     Delegate { amount: Uint128, msg: Binary },
+    /// Allows spender to delegate an additional amount of tokens from the owner's (env.sender)
+    /// account via `DelegateFrom`. This is independent of the transfer allowance.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    IncreaseDelegationAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Lowers the spender's delegation allowance on the owner's (env.sender) account by amount.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    DecreaseDelegationAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Delegates amount tokens from owner to the staking contract
+    /// if `env.sender` has sufficient delegation allowance.
+    DelegateFrom {
+        owner: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Increases the spender's allowance on the owner's account like `IncreaseAllowance`, but
+    /// authorized by a signature of the owner instead of the sender, so anyone can submit it.
+    /// The permit is valid until `expires`, which also becomes the allowance expiration.
+    /// `nonce` must match `PermitNonce` of the owner, see `permit::permit_payload` for
+    /// the signed bytes.
+    Permit {
+        owner: String,
+        spender: String,
+        amount: Uint128,
+        expires: Expiration,
+        nonce: u64,
+        /// 65 bytes: the 64 byte secp256k1 signature (r, s), followed by the recovery id
+        signature: Binary,
+    },
+    /// Reserves `amount` of the sender's liquid tokens for `spender`. Reserved tokens stay in the
+    /// sender's balance, but only the spender can move them until the reservation expires.
+    /// Topping up an active reservation keeps its original expiration.
+    Reserve {
+        spender: String,
+        amount: Uint128,
+        expires: Expiration,
+    },
+    /// Moves up to the reserved amount of `owner`'s tokens to `recipient`.
+    /// Only callable by the spender of an active reservation.
+    SeizeReserved {
+        owner: String,
+        amount: Uint128,
+        recipient: String,
+    },
+    /// Releases the sender's reservation of `owner`'s tokens
+    ReleaseReserved { owner: String },
     /// Undelegates previously delegated tokens
-    Undelegate { recipient: String, amount: Uint128 },
+    Undelegate {
+        recipient: String,
+        amount: Uint128,
+        /// Account whose delegation the tokens are taken from, defaults to `recipient`.
+        /// Used when the staking contract pays out a claim to someone else than the delegator.
+        /// Fails if the delegator's balance and remaining delegation would not cover their
+        /// vesting tokens anymore, so these never become liquid for someone else.
+        #[serde(default)]
+        delegator: Option<String>,
+    },
+    /// Undelegates previously delegated tokens, which vest according to the given schedule.
+    /// Only the staking contract can call this.
+    UndelegateVesting {
+        recipient: String,
+        amount: Uint128,
+        schedule: Curve,
+    },
+    /// Only with "mintable" extension. Repair tool for the minter, which raises the balance of the
+    /// staking contract to the sum of all delegations if it is lower, minting the difference.
+    /// Tokens the staking contract holds on top of delegations (e.g. rewards) are kept.
+    /// The minted difference counts against the cap.
+    ///
+    /// Sums up at most `limit` delegations per call (500 at most and by default). If there are
+    /// more, the call only records its progress and the next one continues from there, so it
+    /// has to be repeated until the response contains `new_staking_balance`.
+    ReconcileDelegationState { limit: Option<u32> },
+    /// Adds initial balances in addition to the ones given on instantiation, for genesis lists
+    /// too large for a single message. Addresses must not have a balance yet.
+    /// Only callable by the instantiator while seeding is open.
+    SeedBalances { accounts: Vec<InitBalance> },
+    /// Closes seeding for good, enabling all other executes.
+    /// Only callable by the instantiator while seeding is open.
+    FinishSeeding {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -212,12 +489,65 @@ pub enum QueryMsg {
     /// Returns the current balance of the given address, 0 if unset.
     /// Return type: BalanceResponse.
     Balance { address: String },
+    /// Returns the balance of the given address at the end of the given block height,
+    /// 0 if unset. Fails for heights before snapshotting was enabled.
+    /// Return type: BalanceResponse.
+    BalanceAt { address: String, height: u64 },
+    /// Returns the total supply at the end of the given block height.
+    /// Fails for heights before snapshotting was enabled.
+    /// Return type: TotalSupplyResponse.
+    TotalSupplyAt { height: u64 },
+    /// Returns the number of tokens in free circulation, which is the number exchanges should display.
+    /// This is the total supply minus the tokens delegated to the staking contract and
+    /// minus the tokens still locked in vesting schedules (not counting those already delegated).
+    /// Return type: CirculatingSupplyResponse.
+    CirculatingSupply {},
+    /// Returns the total and circulating supply (as in `CirculatingSupply`), together with the
+    /// mint cap at the current time.
+    /// Return type: SupplyInfoResponse.
+    SupplyInfo {},
+    /// Returns the nonce the next permit signed by the given owner must use.
+    /// Return type: PermitNonceResponse.
+    PermitNonce { owner: String },
+    /// Returns all active reservations of the given owner's tokens. Supports pagination.
+    /// Return type: ReservationsResponse.
+    Reservations {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all active reservations made for the given spender. Supports pagination.
+    /// Return type: ReservationsResponse.
+    ReservationsBySpender {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     /// Returns the current vesting schedule for the given account.
     /// Return type: VestingResponse.
     Vesting { address: String },
+    /// Returns a summary of the vesting state of up to 50 accounts at the current block time.
+    /// Invalid addresses are reported in their entry instead of failing the query.
+    /// Return type: VestingBatchResponse.
+    VestingBatch { addresses: Vec<String> },
+    /// Returns the earliest time at which the given account can transfer `amount`,
+    /// based on its current balance and vesting schedule. Reserved tokens are not considered.
+    /// Return type: VestingUnlockTimeResponse.
+    VestingUnlockTime { address: String, amount: Uint128 },
+    /// Returns all vesting grants the given account received, oldest first.
+    /// The history is kept even after the schedules are fully vested. Supports pagination.
+    /// Return type: VestingHistoryResponse.
+    VestingHistory {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     /// Returns the amount of delegated tokens for the given account.
     /// Return type: DelegatedResponse.
     Delegated { address: String },
+    /// Returns how many more tokens the given account can delegate.
+    /// Return type: DelegateEligibleResponse.
+    DelegateEligible { address: String },
     /// Returns the allow list who can transfer vesting tokens.
     /// Return type: VestingAllowListResponse.
     VestingAllowList {},
@@ -227,6 +557,13 @@ pub enum QueryMsg {
     /// Returns maximum allowed complexity of vesting curves
     /// Return type: MaxVestingComplexityResponse
     MaxVestingComplexity {},
+    /// Returns the maximum complexity of vesting curves for the given address
+    /// Return type: ComplexityTierResponse
+    ComplexityTier { address: String },
+    /// Returns the spending limit and how much the given address can still send in its
+    /// current window.
+    /// Return type: SpendingLimitStatusResponse
+    SpendingLimitStatus { address: String },
     /// Only with "mintable" extension.
     /// Returns who can mint and the hard cap on maximum tokens after minting.
     /// Return type: MinterResponse.
@@ -243,12 +580,25 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Returns how much spender can delegate from owner account, 0 if unset.
+    /// Return type: AllowanceResponse.
+    DelegationAllowance { owner: String, spender: String },
+    /// Returns all delegation allowances this owner has approved. Supports pagination.
+    /// Return type: AllAllowancesResponse.
+    AllDelegationAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     /// Only with "enumerable" extension
     /// Returns all accounts that have balances. Supports pagination.
+    /// Addresses listed in `exclude` are skipped, without affecting the pagination order.
     /// Return type: AllAccountsResponse.
     AllAccounts {
         start_after: Option<String>,
         limit: Option<u32>,
+        #[serde(default)]
+        exclude: Option<Vec<String>>,
     },
     /// Only with "marketing" extension
     /// Returns more metadata on the contract to display in the client:
@@ -260,9 +610,27 @@ pub enum QueryMsg {
     /// contract.
     /// Return type: DownloadLogoResponse.
     DownloadLogo {},
+    /// Only with "marketing" extension
+    /// Returns the address allowed to update the logo, project and description.
+    /// Return type: LogoUpdaterResponse.
+    LogoUpdater {},
     /// Returns staking address used to delegate tokens.
     /// Return type: StakingAddressResponse.
     StakingAddress {},
+    /// Returns how much the given address (the staking address if unset) can currently move
+    /// out of its balance without running into its vesting schedule or reservations.
+    /// This is the most the staking contract can undelegate at once.
+    /// Return type: UndelegatableAmountResponse.
+    UndelegatableAmount { staking: Option<String> },
+    /// Runs the same schedule validations as `ExecuteMsg::TransferVesting` without executing it,
+    /// and previews the recipient's resulting vesting schedule. Sender's allow list membership and
+    /// balance are not checked.
+    /// Return type: SimulateTransferVestingResponse.
+    SimulateTransferVesting {
+        recipient: String,
+        amount: Uint128,
+        schedule: Curve,
+    },
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
@@ -281,8 +649,16 @@ pub struct MinterResponse {
     /// Note that cap refers to total_supply.
     /// If None, there is unlimited cap.
     pub cap: Option<Curve>,
-    /// This is cap evaluated at the current time
+    /// This is cap evaluated at the current time, minus any reduction from burns
     pub current_cap: Option<Uint128>,
+    /// Whether burns reduce the cap
+    pub burns_reduce_cap: bool,
+    /// Cumulative amount burned while `burns_reduce_cap` was set
+    pub cap_reduction: Uint128,
+    /// How much can still be minted right now: `current_cap` minus total supply,
+    /// or zero if the supply already exceeds it. None if there is unlimited cap.
+    #[serde(default)]
+    pub remaining_mintable: Option<Uint128>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
@@ -292,6 +668,54 @@ pub struct VestingResponse {
     pub schedule: Option<Curve>,
     /// The current amount locked. Always 0 if schedule is None
     pub locked: Uint128,
+    /// Amount currently delegated to the staking contract, which still counts towards the lock
+    pub delegated: Uint128,
+    /// The part of `locked` that has to stay in the liquid balance, i.e. `locked - delegated`
+    pub effectively_locked: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingBatchResponse {
+    /// One entry per requested address, in the order of the request
+    pub vestings: Vec<VestingBatchEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingBatchEntry {
+    pub address: String,
+    /// The current amount locked. Always 0 if there is no schedule
+    pub locked: Uint128,
+    /// Time in seconds at which the schedule is fully vested, None if there is no schedule
+    /// or it never fully vests
+    pub schedule_end: Option<u64>,
+    pub has_schedule: bool,
+    /// Set if the address is invalid, all other fields are empty then
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingUnlockTimeResponse {
+    /// Time in seconds from which on the amount can be transferred, at the earliest the
+    /// current block time. None if the balance is too low to ever transfer it.
+    pub unlock_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct SimulateTransferVestingResponse {
+    /// Whether `ExecuteMsg::TransferVesting` would pass the schedule validation
+    pub valid: bool,
+    /// The validation error, if any
+    pub error: Option<String>,
+    /// Size of the recipient's resulting vesting schedule, 0 if invalid
+    pub combined_size: u64,
+    /// The amount locked for the recipient right now after the transfer, 0 if invalid
+    pub locked_now: Uint128,
+    /// When the recipient's resulting vesting schedule is fully vested, 0 if invalid
+    pub fully_vested_at: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
@@ -300,20 +724,135 @@ pub struct VestingAllowListResponse {
     pub allow_list: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ReservationInfo {
+    pub owner: String,
+    pub spender: String,
+    pub amount: Uint128,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingGrantInfo {
+    /// Position of the grant in the recipient's history, starting at 1
+    pub seq: u64,
+    pub memo: Option<String>,
+    pub amount: Uint128,
+    pub grantor: String,
+    pub timestamp: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingHistoryResponse {
+    pub grants: Vec<VestingGrantInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct PermitNonceResponse {
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ReservationsResponse {
+    pub reservations: Vec<ReservationInfo>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct DelegatedResponse {
     pub delegated: Uint128,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct DelegateEligibleResponse {
+    /// Amount that can still be delegated. Vesting tokens are eligible, reserved ones are not.
+    pub eligible: Uint128,
+    /// Amount currently delegated to the staking contract
+    pub already_delegated: Uint128,
+    /// All tokens held by the account, including the delegated ones
+    pub balance: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct StakingAddressResponse {
     pub address: Option<Addr>,
+    /// Sum of all tokens currently delegated to the staking address
+    pub total_delegated: Uint128,
+    /// Balance of the staking address, zero if it is not set
+    pub staking_balance: Uint128,
+    /// Whether the balance of the staking address covers all delegated tokens
+    pub consistent: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct UndelegatableAmountResponse {
+    pub staking: Addr,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct TotalSupplyResponse {
+    pub total_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct CirculatingSupplyResponse {
+    pub circulating_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct SupplyInfoResponse {
+    pub total_supply: Uint128,
+    pub circulating_supply: Uint128,
+    /// The cap on total supply at the current time, minus any reduction from burns.
+    /// None if there is no minter or no cap.
+    pub mint_cap: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct LogoUpdaterResponse {
+    pub logo_updater: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct SpendingLimitStatusResponse {
+    /// The spending limit, if any is set
+    pub limit: Option<SpendingLimit>,
+    /// Amount sent in the current window
+    pub sent: Uint128,
+    /// Amount that can still be sent in the current window, None if there is no limit
+    pub remaining: Option<Uint128>,
+    /// Time in seconds when the current window ends, None if there is no limit
+    pub window_end: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ComplexityTierResponse {
+    /// The maximum complexity of vesting curves for this address
+    pub complexity: u64,
+    /// The address specific limit, if one is set
+    pub tier: Option<u8>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct MaxVestingComplexityResponse {
+    /// The current maximum complexity of a vesting curve
     pub complexity: u64,
+    /// The size of the most complex vesting curve stored so far
+    pub largest_curve_size: u64,
 }
@@ -2,3 +2,5 @@ mod delegate;
 mod migration;
 mod staking_contract;
 mod suite;
+mod vesting_receiver;
+mod vesting_transfer;
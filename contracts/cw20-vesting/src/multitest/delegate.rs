@@ -1,4 +1,4 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Uint128};
 
 use super::suite::SuiteBuilder;
 
@@ -362,3 +362,49 @@ fn delegate_undelegate_multiple_users() {
     assert_eq!(suite.query_vested(user3).unwrap(), 7_000_000u128);
     assert_eq!(suite.query_delegated(user3).unwrap(), 10_000_000u128);
 }
+
+#[test]
+fn locked_tokens_cannot_be_moved_through_delegation() {
+    let user = "user";
+    // 150_000 tokens, of which 100_000 are locked at START, vesting linearly until END
+    for elapsed in [1_000, 2_500, 5_000, 7_500] {
+        let mut suite = SuiteBuilder::new()
+            .with_initial_balances(vec![(
+                user,
+                150_000,
+                Curve::saturating_linear((START, 100_000), (END, 0)),
+            )])
+            .with_minter("admin", None)
+            .build();
+        let staking_contract = suite.staking_contract();
+        suite
+            .update_staking_address("admin", &staking_contract)
+            .unwrap();
+        suite.update_time(elapsed);
+        let locked = 100_000 * (END - START - elapsed) as u128 / (END - START) as u128;
+        assert_eq!(suite.query_vested(user).unwrap(), locked);
+
+        // delegating the locked tokens first leaves all liquid ones in the wallet
+        suite.delegate(user, 100_000u128).unwrap();
+        suite.transfer(user, "other", 50_000).unwrap();
+        suite.transfer(user, "other", 1).unwrap_err();
+
+        // once undelegated, the lock applies to the returned tokens again
+        suite
+            .undelegate(&staking_contract, user, 100_000u128)
+            .unwrap();
+        assert_eq!(suite.query_balance(user).unwrap(), 100_000);
+        suite.transfer(user, "other", 100_000 - locked).unwrap();
+        let err = suite.transfer(user, "other", 1).unwrap_err();
+        assert_eq!(
+            ContractError::CantMoveVestingTokens {
+                effectively_locked: Uint128::new(locked),
+            },
+            err.downcast().unwrap()
+        );
+
+        // in total, exactly the unlocked tokens were moved
+        assert_eq!(suite.query_balance("other").unwrap(), 150_000 - locked);
+        assert_eq!(suite.query_balance(user).unwrap(), locked);
+    }
+}
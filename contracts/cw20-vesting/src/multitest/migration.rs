@@ -39,6 +39,7 @@ fn migrate_max_cap_curve() {
                 marketing: None,
                 allowed_vesters: None,
                 max_curve_complexity: 500,
+                seeding_open: false,
             },
             &[],
             "vesting",
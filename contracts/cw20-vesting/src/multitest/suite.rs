@@ -7,10 +7,12 @@ use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
 use super::staking_contract::{
     staking_contract, DelegateMsg, EmptyMsg, QueryMsg as StakingQueryMsg,
 };
+use super::vesting_receiver::{vesting_receiver, QueryMsg as ReceiverQueryMsg};
 use crate::msg::{
     DelegatedResponse, ExecuteMsg, InitBalance, InstantiateMarketingInfo, InstantiateMsg,
     MinterInfo, QueryMsg, StakingAddressResponse, VestingResponse,
 };
+use crate::receive_delegate::Cw20ReceiveVestingMsg;
 use wynd_utils::Curve;
 
 pub fn contract_vesting() -> Box<dyn Contract<Empty>> {
@@ -19,7 +21,8 @@ pub fn contract_vesting() -> Box<dyn Contract<Empty>> {
         crate::contract::instantiate,
         crate::contract::query,
     )
-    .with_migrate(crate::contract::migrate);
+    .with_migrate(crate::contract::migrate)
+    .with_reply(crate::contract::reply);
 
     Box::new(contract)
 }
@@ -58,6 +61,8 @@ impl SuiteBuilder {
                 address: address.to_owned(),
                 amount: amount.into(),
                 vesting: vesting.into(),
+                memo: None,
+                relative_vesting: None,
             })
             .collect::<Vec<InitBalance>>();
         self.initial_balances = initial_balances;
@@ -68,6 +73,7 @@ impl SuiteBuilder {
         let mint = MinterInfo {
             minter: minter.to_owned(),
             cap: cap.into(),
+            burns_reduce_cap: false,
         };
         self.mint = Some(mint);
         self
@@ -93,6 +99,7 @@ impl SuiteBuilder {
                     marketing: self.marketing.clone(),
                     allowed_vesters: self.allowed_vesters,
                     max_curve_complexity: 10,
+                    seeding_open: false,
                 },
                 &[],
                 "vesting",
@@ -102,13 +109,26 @@ impl SuiteBuilder {
 
         let staking_id = app.store_code(staking_contract());
         let staking = app
-            .instantiate_contract(staking_id, admin, &EmptyMsg {}, &[], "staking", None)
+            .instantiate_contract(
+                staking_id,
+                admin.clone(),
+                &EmptyMsg {},
+                &[],
+                "staking",
+                None,
+            )
+            .unwrap();
+
+        let receiver_id = app.store_code(vesting_receiver());
+        let vesting_receiver = app
+            .instantiate_contract(receiver_id, admin, &EmptyMsg {}, &[], "receiver", None)
             .unwrap();
 
         Suite {
             app,
             vesting_contract,
             staking_contract: staking,
+            vesting_receiver,
         }
     }
 }
@@ -117,6 +137,7 @@ pub struct Suite {
     app: App,
     vesting_contract: Addr,
     staking_contract: Addr,
+    vesting_receiver: Addr,
 }
 
 impl Suite {
@@ -124,6 +145,10 @@ impl Suite {
         self.staking_contract.to_string()
     }
 
+    pub fn vesting_receiver(&mut self) -> String {
+        self.vesting_receiver.to_string()
+    }
+
     pub fn delegate(&mut self, sender: &str, amount: u128) -> AnyResult<AppResponse> {
         self.app.execute_contract(
             Addr::unchecked(sender),
@@ -148,11 +173,62 @@ impl Suite {
             &ExecuteMsg::Undelegate {
                 recipient: recipient.to_owned(),
                 amount: amount.into(),
+                delegator: None,
+            },
+            &[],
+        )
+    }
+
+    pub fn transfer(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: u128,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.vesting_contract.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: recipient.to_owned(),
+                amount: amount.into(),
             },
             &[],
         )
     }
 
+    pub fn transfer_vesting(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: u128,
+        schedule: Curve,
+        notify: bool,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.vesting_contract.clone(),
+            &ExecuteMsg::TransferVesting {
+                recipient: recipient.to_owned(),
+                amount: amount.into(),
+                schedule,
+                memo: None,
+                notify,
+            },
+            &[],
+        )
+    }
+
+    pub fn update_time(&mut self, seconds: u64) {
+        self.app.update_block(|block| {
+            block.time = block.time.plus_seconds(seconds);
+            block.height += 1;
+        });
+    }
+
+    pub fn block_time(&self) -> u64 {
+        self.app.block_info().time.seconds()
+    }
+
     pub fn update_staking_address(
         &mut self,
         sender: &str,
@@ -210,6 +286,14 @@ impl Suite {
         Ok(delegated.u128())
     }
 
+    /// Returns the last vesting notification received by the mock receiver contract
+    pub fn query_received_vesting(&self) -> StdResult<Option<Cw20ReceiveVestingMsg>> {
+        self.app.wrap().query_wasm_smart(
+            self.vesting_receiver.clone(),
+            &ReceiverQueryMsg::Received {},
+        )
+    }
+
     /// Returns currently assigned address of staking contract.
     /// At first it is not set and returns None.
     /// It can be set via ExecuteMsg::UpdateStakingAddress
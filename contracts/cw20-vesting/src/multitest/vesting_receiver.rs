@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
+};
+use cw_multi_test::{Contract, ContractWrapper};
+use cw_storage_plus::Item;
+
+use crate::receive_delegate::Cw20ReceiveVestingMsg;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    ReceiveVesting(Cw20ReceiveVestingMsg),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns the last notification received
+    Received {},
+}
+
+const RECEIVED: Item<Cw20ReceiveVestingMsg> = Item::new("received");
+
+fn instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> Result<Response, StdError> {
+    Ok(Response::default())
+}
+
+fn execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, StdError> {
+    match msg {
+        ExecuteMsg::ReceiveVesting(received) => RECEIVED.save(deps.storage, &received)?,
+    }
+    Ok(Response::new())
+}
+
+fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Received {} => to_binary(&RECEIVED.may_load(deps.storage)?),
+    }
+}
+
+pub fn vesting_receiver() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(execute, instantiate, query);
+    Box::new(contract)
+}
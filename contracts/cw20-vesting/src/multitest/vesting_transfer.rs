@@ -0,0 +1,61 @@
+use super::suite::SuiteBuilder;
+use crate::receive_delegate::Cw20ReceiveVestingMsg;
+use wynd_utils::Curve;
+
+#[test]
+fn contract_recipient_is_notified() {
+    let admin = "admin";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(admin, 10_000, None)])
+        .build();
+    let receiver = suite.vesting_receiver();
+
+    let now = suite.block_time();
+    let schedule = Curve::saturating_linear((now, 4_000), (now + 1_000, 0));
+
+    // nothing is sent without the flag
+    suite
+        .transfer_vesting(admin, &receiver, 4_000, schedule.clone(), false)
+        .unwrap();
+    assert_eq!(suite.query_received_vesting().unwrap(), None);
+
+    let second = Curve::saturating_linear((now, 2_000), (now + 500, 0));
+    suite
+        .transfer_vesting(admin, &receiver, 2_000, second.clone(), true)
+        .unwrap();
+    // the receiver only learns about the schedule of this transfer
+    assert_eq!(
+        suite.query_received_vesting().unwrap(),
+        Some(Cw20ReceiveVestingMsg {
+            sender: admin.to_owned(),
+            amount: 2_000u128.into(),
+            schedule: second,
+        })
+    );
+    assert_eq!(suite.query_balance(&receiver).unwrap(), 6_000);
+    assert_eq!(suite.query_vested(&receiver).unwrap(), 6_000);
+}
+
+#[test]
+fn failed_notification_does_not_block_transfer() {
+    let admin = "admin";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(admin, 10_000, None)])
+        .build();
+    // the mock staking contract does not handle vesting notifications
+    let staking = suite.staking_contract();
+
+    let now = suite.block_time();
+    let schedule = Curve::saturating_linear((now, 3_000), (now + 1_000, 0));
+    let res = suite
+        .transfer_vesting(admin, &staking, 3_000, schedule, true)
+        .unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "action" && attr.value == "receive_vesting_failed")));
+
+    assert_eq!(suite.query_balance(&staking).unwrap(), 3_000);
+    assert_eq!(suite.query_vested(&staking).unwrap(), 3_000);
+    assert_eq!(suite.query_balance(admin).unwrap(), 7_000);
+}
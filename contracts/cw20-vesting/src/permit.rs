@@ -0,0 +1,346 @@
+use bech32::FromBase32;
+use cosmwasm_std::{attr, Addr, Binary, Deps, DepsMut, Env, Response, StdResult, Uint128};
+use cw_utils::Expiration;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use crate::allowances::increase;
+use crate::error::ContractError;
+use crate::msg::PermitNonceResponse;
+use crate::state::{ALLOWANCES, PERMIT_NONCES};
+
+/// Prefix of every signed permit, so the signature cannot be mistaken for any other message
+pub const PERMIT_DOMAIN: &[u8] = b"wynd-cw20-permit-v1";
+
+/// Builds the bytes the owner signs for a permit. The signature is made over the sha256 hash
+/// of this payload. All integers are big endian, strings are utf-8 prefixed with their length:
+///
+/// | field      | bytes                                                                   |
+/// |------------|-------------------------------------------------------------------------|
+/// | domain     | `PERMIT_DOMAIN`, the 19 ascii bytes `wynd-cw20-permit-v1`               |
+/// | chain id   | u32 length + bytes                                                      |
+/// | contract   | u32 length + bytes of this token contract's address                     |
+/// | owner      | u32 length + bytes                                                      |
+/// | spender    | u32 length + bytes                                                      |
+/// | amount     | u128, 16 bytes                                                          |
+/// | expires    | 1 byte kind (0 never, 1 at height, 2 at time) + u64, 8 bytes: the       |
+/// |            | height, the time in nanoseconds, or 0 for never                         |
+/// | nonce      | u64, 8 bytes                                                            |
+pub fn permit_payload(
+    chain_id: &str,
+    contract: &Addr,
+    owner: &Addr,
+    spender: &Addr,
+    amount: Uint128,
+    expires: &Expiration,
+    nonce: u64,
+) -> Vec<u8> {
+    fn push_str(payload: &mut Vec<u8>, value: &str) {
+        payload.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        payload.extend_from_slice(value.as_bytes());
+    }
+
+    let mut payload = PERMIT_DOMAIN.to_vec();
+    push_str(&mut payload, chain_id);
+    push_str(&mut payload, contract.as_str());
+    push_str(&mut payload, owner.as_str());
+    push_str(&mut payload, spender.as_str());
+    payload.extend_from_slice(&amount.u128().to_be_bytes());
+    let (kind, value) = match expires {
+        Expiration::Never {} => (0u8, 0u64),
+        Expiration::AtHeight(height) => (1, *height),
+        Expiration::AtTime(time) => (2, time.nanos()),
+    };
+    payload.push(kind);
+    payload.extend_from_slice(&value.to_be_bytes());
+    payload.extend_from_slice(&nonce.to_be_bytes());
+    payload
+}
+
+/// Raw address bytes of a compressed secp256k1 public key, as used by cosmos sdk accounts
+fn raw_address(compressed_pubkey: &[u8]) -> Vec<u8> {
+    <Ripemd160 as ripemd::Digest>::digest(Sha256::digest(compressed_pubkey)).to_vec()
+}
+
+/// Checks that the signature over `payload` was made by the key behind the owner's address.
+/// Like `ecrecover`, the public key is recovered from the signature and hashed into the raw
+/// address (`ripemd160(sha256(compressed pubkey))`), which must match the owner's bech32 data.
+fn verify_owner_signature(
+    deps: Deps,
+    owner: &Addr,
+    payload: &[u8],
+    signature: &[u8],
+) -> Result<(), ContractError> {
+    if signature.len() != 65 {
+        return Err(ContractError::InvalidPermitSignature {});
+    }
+    let hash = Sha256::digest(payload);
+    let pubkey = deps
+        .api
+        .secp256k1_recover_pubkey(&hash, &signature[..64], signature[64])
+        .map_err(|_| ContractError::InvalidPermitSignature {})?;
+    // recovered keys are uncompressed (0x04 || x || y), addresses are derived from compressed ones
+    let mut compressed = vec![0x02 | (pubkey[64] & 1)];
+    compressed.extend_from_slice(&pubkey[1..33]);
+
+    let owner_raw = bech32::decode(owner.as_str())
+        .and_then(|(_, data, _)| Vec::<u8>::from_base32(&data))
+        .map_err(|_| ContractError::InvalidPermitSignature {})?;
+    if owner_raw != raw_address(&compressed) {
+        return Err(ContractError::InvalidPermitSignature {});
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_permit(
+    deps: DepsMut,
+    env: Env,
+    owner: String,
+    spender: String,
+    amount: Uint128,
+    expires: Expiration,
+    nonce: u64,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if spender_addr == owner_addr {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::PermitExpired {});
+    }
+
+    let expected = PERMIT_NONCES
+        .may_load(deps.storage, &owner_addr)?
+        .unwrap_or_default();
+    if nonce != expected {
+        return Err(ContractError::InvalidPermitNonce {
+            expected,
+            got: nonce,
+        });
+    }
+
+    let payload = permit_payload(
+        &env.block.chain_id,
+        &env.contract.address,
+        &owner_addr,
+        &spender_addr,
+        amount,
+        &expires,
+        nonce,
+    );
+    verify_owner_signature(deps.as_ref(), &owner_addr, &payload, &signature)?;
+    PERMIT_NONCES.save(deps.storage, &owner_addr, &(nonce + 1))?;
+
+    increase(
+        deps.storage,
+        &ALLOWANCES,
+        (&owner_addr, &spender_addr),
+        amount,
+        Some(expires),
+    )?;
+
+    let res = Response::new().add_attributes(vec![
+        attr("action", "permit"),
+        attr("owner", owner),
+        attr("spender", spender),
+        attr("amount", amount),
+        attr("nonce", nonce.to_string()),
+    ]);
+    Ok(res)
+}
+
+pub fn query_permit_nonce(deps: Deps, owner: String) -> StdResult<PermitNonceResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let nonce = PERMIT_NONCES
+        .may_load(deps.storage, &owner)?
+        .unwrap_or_default();
+    Ok(PermitNonceResponse { nonce })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bech32::{ToBase32, Variant};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use k256::ecdsa::{recoverable, signature::DigestSigner, SigningKey};
+
+    use crate::allowances::query_allowance;
+    use crate::contract::{execute, instantiate};
+    use crate::msg::{ExecuteMsg, InitBalance, InstantiateMsg};
+
+    // fixed key material, so the owner address is stable across runs
+    const OWNER_KEY: [u8; 32] = [
+        0x1f, 0x8b, 0x3a, 0x52, 0x04, 0xc7, 0x6e, 0x91, 0x2d, 0x55, 0xa0, 0x3c, 0x7e, 0x18, 0xb9,
+        0x46, 0xf2, 0x0d, 0x63, 0x8a, 0xc1, 0x37, 0x5b, 0xe4, 0x99, 0x02, 0x7d, 0xa6, 0x4f, 0x10,
+        0xcb, 0x28,
+    ];
+    const SPENDER: &str = "spender";
+
+    fn owner_address(key: &SigningKey) -> String {
+        let pubkey = key.verifying_key().to_bytes();
+        bech32::encode("wasm", raw_address(&pubkey).to_base32(), Variant::Bech32).unwrap()
+    }
+
+    fn do_instantiate(deps: DepsMut, owner: &str) {
+        let msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![InitBalance {
+                address: owner.to_owned(),
+                amount: Uint128::new(10_000),
+                vesting: None,
+                memo: None,
+                relative_vesting: None,
+            }],
+            mint: None,
+            marketing: None,
+            allowed_vesters: None,
+            max_curve_complexity: 10,
+            seeding_open: false,
+        };
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn sign_permit(
+        key: &SigningKey,
+        owner: &str,
+        amount: u128,
+        expires: Expiration,
+        nonce: u64,
+    ) -> Binary {
+        let env = mock_env();
+        let payload = permit_payload(
+            &env.block.chain_id,
+            &env.contract.address,
+            &Addr::unchecked(owner),
+            &Addr::unchecked(SPENDER),
+            Uint128::new(amount),
+            &expires,
+            nonce,
+        );
+        let mut digest = Sha256::new();
+        digest.update(&payload);
+        let signature: recoverable::Signature = key.sign_digest(digest);
+        Binary::from(signature.as_ref())
+    }
+
+    fn permit_msg(
+        owner: &str,
+        amount: u128,
+        expires: Expiration,
+        nonce: u64,
+        signature: Binary,
+    ) -> ExecuteMsg {
+        ExecuteMsg::Permit {
+            owner: owner.to_owned(),
+            spender: SPENDER.to_owned(),
+            amount: Uint128::new(amount),
+            expires,
+            nonce,
+            signature,
+        }
+    }
+
+    #[test]
+    fn permit_increases_allowance() {
+        let mut deps = mock_dependencies();
+        let key = SigningKey::from_bytes(&OWNER_KEY).unwrap();
+        let owner = owner_address(&key);
+        do_instantiate(deps.as_mut(), &owner);
+        assert_eq!(
+            query_permit_nonce(deps.as_ref(), owner.clone()).unwrap(),
+            PermitNonceResponse { nonce: 0 }
+        );
+
+        let expires = Expiration::AtHeight(mock_env().block.height + 100);
+        let signature = sign_permit(&key, &owner, 3_000, expires, 0);
+        // anyone can submit the permit
+        let info = mock_info("relayer", &[]);
+        let msg = permit_msg(&owner, 3_000, expires, 0, signature);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes[0], attr("action", "permit"));
+
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), SPENDER.to_owned()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(3_000));
+        assert_eq!(allowance.expires, expires);
+        assert_eq!(
+            query_permit_nonce(deps.as_ref(), owner.clone()).unwrap(),
+            PermitNonceResponse { nonce: 1 }
+        );
+
+        // the next permit adds to the allowance, like IncreaseAllowance
+        let signature = sign_permit(&key, &owner, 500, Expiration::Never {}, 1);
+        let msg = permit_msg(&owner, 500, Expiration::Never {}, 1, signature);
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+        let allowance = query_allowance(deps.as_ref(), owner, SPENDER.to_owned()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(3_500));
+        assert_eq!(allowance.expires, Expiration::Never {});
+    }
+
+    #[test]
+    fn replayed_permit_rejected() {
+        let mut deps = mock_dependencies();
+        let key = SigningKey::from_bytes(&OWNER_KEY).unwrap();
+        let owner = owner_address(&key);
+        do_instantiate(deps.as_mut(), &owner);
+
+        let signature = sign_permit(&key, &owner, 3_000, Expiration::Never {}, 0);
+        let msg = permit_msg(&owner, 3_000, Expiration::Never {}, 0, signature);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidPermitNonce {
+                expected: 1,
+                got: 0
+            }
+        );
+        let allowance = query_allowance(deps.as_ref(), owner, SPENDER.to_owned()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(3_000));
+    }
+
+    #[test]
+    fn tampered_permit_rejected() {
+        let mut deps = mock_dependencies();
+        let key = SigningKey::from_bytes(&OWNER_KEY).unwrap();
+        let owner = owner_address(&key);
+        do_instantiate(deps.as_mut(), &owner);
+
+        // amount differs from the signed one
+        let signature = sign_permit(&key, &owner, 100, Expiration::Never {}, 0);
+        let msg = permit_msg(&owner, 10_000, Expiration::Never {}, 0, signature);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPermitSignature {});
+
+        // signed by a different key than the owner's
+        let other = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+        let signature = sign_permit(&other, &owner, 100, Expiration::Never {}, 0);
+        let msg = permit_msg(&owner, 100, Expiration::Never {}, 0, signature);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPermitSignature {});
+
+        // expired permits are rejected before the signature is checked
+        let expires = Expiration::AtHeight(mock_env().block.height);
+        let signature = sign_permit(&key, &owner, 100, expires, 0);
+        let msg = permit_msg(&owner, 100, expires, 0, signature);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::PermitExpired {});
+
+        // nothing was approved and the nonce is still unused
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), SPENDER.to_owned()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::zero());
+        assert_eq!(query_permit_nonce(deps.as_ref(), owner).unwrap().nonce, 0);
+    }
+}
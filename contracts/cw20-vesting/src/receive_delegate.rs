@@ -2,6 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{to_binary, Binary, CosmosMsg, StdResult, Uint128, WasmMsg};
+use wynd_utils::Curve;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -33,3 +34,36 @@ impl Cw20ReceiveDelegationMsg {
 pub enum DelegateExecuteMsg {
     ReceiveDelegation(Cw20ReceiveDelegationMsg),
 }
+
+/// Sent to contract recipients of `TransferVesting` that asked to be notified
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct Cw20ReceiveVestingMsg {
+    pub sender: String,
+    pub amount: Uint128,
+    /// The schedule of this transfer, not combined with previous vesting of the recipient
+    pub schedule: Curve,
+}
+
+impl Cw20ReceiveVestingMsg {
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = ReceiveVestingExecuteMsg::ReceiveVesting(self);
+        to_binary(&msg)
+    }
+
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveVestingExecuteMsg {
+    ReceiveVesting(Cw20ReceiveVestingMsg),
+}
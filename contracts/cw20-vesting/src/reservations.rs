@@ -0,0 +1,437 @@
+use cosmwasm_std::{attr, DepsMut, Env, MessageInfo, Response, StdResult, Uint128};
+use cw_utils::Expiration;
+
+use crate::error::ContractError;
+use crate::state::{
+    deduct_coins, remove_reservation, reserved_total, save_reservation, Reservation, BALANCES,
+    DELEGATED, RESERVED, VESTING,
+};
+
+pub fn execute_reserve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: Uint128,
+    expires: Expiration,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if spender_addr == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+
+    // this also releases all expired reservations
+    let reserved = reserved_total(deps.storage, &env.block, &info.sender)?;
+
+    // topping up an active reservation keeps its expiration, so the owner cannot shorten it
+    let reservation = match RESERVED.may_load(deps.storage, (&info.sender, &spender_addr))? {
+        Some(existing) => Reservation {
+            amount: existing.amount + amount,
+            expires: existing.expires,
+        },
+        None => {
+            if expires.is_expired(&env.block) {
+                return Err(ContractError::Expired {});
+            }
+            Reservation { amount, expires }
+        }
+    };
+
+    // reserved tokens must be liquid, in the balance and not locked by vesting
+    let balance = BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let delegated = DELEGATED
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let vesting = VESTING
+        .may_load(deps.storage, &info.sender)?
+        .map(|v| v.value(env.block.time.seconds()))
+        .unwrap_or_default();
+    let available = balance
+        .min((balance + delegated).saturating_sub(vesting))
+        .saturating_sub(reserved);
+    if amount > available {
+        return Err(ContractError::ReserveExceedsAvailable { available });
+    }
+
+    save_reservation(deps.storage, &info.sender, &spender_addr, &reservation)?;
+
+    let res = Response::new().add_attributes(vec![
+        attr("action", "reserve"),
+        attr("owner", info.sender),
+        attr("spender", spender),
+        attr("amount", amount),
+        attr("reserved", reservation.amount),
+    ]);
+    Ok(res)
+}
+
+pub fn execute_seize_reserved(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    amount: Uint128,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let rcpt_addr = deps.api.addr_validate(&recipient)?;
+
+    let mut reservation = match RESERVED.may_load(deps.storage, (&owner_addr, &info.sender))? {
+        Some(reservation) if !reservation.expires.is_expired(&env.block) => reservation,
+        _ => return Err(ContractError::NoReservation {}),
+    };
+    if amount > reservation.amount {
+        return Err(ContractError::SeizeExceedsReservation {
+            reserved: reservation.amount,
+        });
+    }
+
+    // lower the reservation first, so the seized tokens can leave the balance
+    reservation.amount -= amount;
+    if reservation.amount.is_zero() {
+        remove_reservation(deps.storage, &owner_addr, &info.sender);
+    } else {
+        save_reservation(deps.storage, &owner_addr, &info.sender, &reservation)?;
+    }
+
+    deduct_coins(deps.storage, &env, &owner_addr, amount)?;
+    BALANCES.update(
+        deps.storage,
+        &rcpt_addr,
+        env.block.height,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+    )?;
+
+    let res = Response::new().add_attributes(vec![
+        attr("action", "seize_reserved"),
+        attr("owner", owner),
+        attr("spender", info.sender),
+        attr("recipient", recipient),
+        attr("amount", amount),
+    ]);
+    Ok(res)
+}
+
+pub fn execute_release_reserved(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+
+    let reservation = match RESERVED.may_load(deps.storage, (&owner_addr, &info.sender))? {
+        Some(reservation) => reservation,
+        None => return Err(ContractError::NoReservation {}),
+    };
+    remove_reservation(deps.storage, &owner_addr, &info.sender);
+
+    // an expired reservation is released already, nothing is freed by this
+    let released = if reservation.expires.is_expired(&env.block) {
+        Uint128::zero()
+    } else {
+        reservation.amount
+    };
+
+    let res = Response::new().add_attributes(vec![
+        attr("action", "release_reserved"),
+        attr("owner", owner),
+        attr("spender", info.sender),
+        attr("amount", released),
+    ]);
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{Addr, Binary, Deps};
+    use wynd_utils::Curve;
+
+    use crate::contract::{execute, instantiate, query_balance};
+    use crate::enumerable::{query_reservations, query_reservations_by_spender};
+    use crate::msg::{ExecuteMsg, InitBalance, InstantiateMsg, MinterInfo, ReservationInfo};
+
+    const OWNER: &str = "owner";
+    const SPENDER: &str = "spender";
+    const RECIPIENT: &str = "recipient";
+    const MINTER: &str = "minter";
+    const STAKING: &str = "staking";
+
+    fn get_balance(deps: Deps, address: &str) -> Uint128 {
+        query_balance(deps, address.to_owned()).unwrap().balance
+    }
+
+    // instantiates with the given balance of OWNER, locking `vesting` of it for a long time
+    fn do_instantiate(mut deps: DepsMut, amount: u128, vesting: u128) {
+        let start = mock_env().block.time.seconds();
+        let vesting = (vesting > 0)
+            .then(|| Curve::saturating_linear((start + 1000, vesting), (start + 2000, 0)));
+        let msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![InitBalance {
+                address: OWNER.to_owned(),
+                amount: Uint128::new(amount),
+                vesting,
+                memo: None,
+                relative_vesting: None,
+            }],
+            mint: Some(MinterInfo {
+                minter: MINTER.to_owned(),
+                cap: None,
+                burns_reduce_cap: false,
+            }),
+            marketing: None,
+            allowed_vesters: None,
+            max_curve_complexity: 10,
+            seeding_open: false,
+        };
+        instantiate(deps.branch(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::UpdateStakingAddress {
+            address: STAKING.to_owned(),
+        };
+        execute(deps, mock_env(), mock_info(MINTER, &[]), msg).unwrap();
+    }
+
+    fn reserve(
+        deps: DepsMut,
+        amount: u128,
+        expires: Expiration,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Reserve {
+            spender: SPENDER.to_owned(),
+            amount: Uint128::new(amount),
+            expires,
+        };
+        execute(deps, mock_env(), mock_info(OWNER, &[]), msg)
+    }
+
+    fn seize(deps: DepsMut, env: Env, amount: u128) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::SeizeReserved {
+            owner: OWNER.to_owned(),
+            amount: Uint128::new(amount),
+            recipient: RECIPIENT.to_owned(),
+        };
+        execute(deps, env, mock_info(SPENDER, &[]), msg)
+    }
+
+    fn transfer(deps: DepsMut, amount: u128) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Transfer {
+            recipient: RECIPIENT.to_owned(),
+            amount: Uint128::new(amount),
+        };
+        execute(deps, mock_env(), mock_info(OWNER, &[]), msg)
+    }
+
+    fn delegate(deps: DepsMut, amount: u128) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Delegate {
+            amount: Uint128::new(amount),
+            msg: Binary::default(),
+        };
+        execute(deps, mock_env(), mock_info(OWNER, &[]), msg)
+    }
+
+    #[test]
+    fn reserve_and_seize() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1_000, 0);
+
+        // cannot reserve for oneself, zero or more than available
+        let msg = ExecuteMsg::Reserve {
+            spender: OWNER.to_owned(),
+            amount: Uint128::new(100),
+            expires: Expiration::Never {},
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::CannotSetOwnAccount {});
+        let err = reserve(deps.as_mut(), 0, Expiration::Never {}).unwrap_err();
+        assert_eq!(err, ContractError::InvalidZeroAmount {});
+        let err = reserve(deps.as_mut(), 1_001, Expiration::Never {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ReserveExceedsAvailable {
+                available: Uint128::new(1_000)
+            }
+        );
+
+        reserve(deps.as_mut(), 400, Expiration::Never {}).unwrap();
+
+        // tokens stay in the owner's balance, but reserved ones cannot be moved
+        assert_eq!(get_balance(deps.as_ref(), OWNER), Uint128::new(1_000));
+        let err = transfer(deps.as_mut(), 601).unwrap_err();
+        assert_eq!(err, ContractError::CantMoveReservedTokens);
+        transfer(deps.as_mut(), 600).unwrap();
+
+        // only the spender can seize, and only up to the reservation
+        let msg = ExecuteMsg::SeizeReserved {
+            owner: OWNER.to_owned(),
+            amount: Uint128::new(100),
+            recipient: RECIPIENT.to_owned(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(RECIPIENT, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoReservation {});
+        let err = seize(deps.as_mut(), mock_env(), 401).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::SeizeExceedsReservation {
+                reserved: Uint128::new(400)
+            }
+        );
+        let res = seize(deps.as_mut(), mock_env(), 300).unwrap();
+        assert_eq!(res.attributes[0], attr("action", "seize_reserved"));
+        assert_eq!(get_balance(deps.as_ref(), OWNER), Uint128::new(100));
+        assert_eq!(get_balance(deps.as_ref(), RECIPIENT), Uint128::new(900));
+
+        let expected = vec![ReservationInfo {
+            owner: OWNER.to_owned(),
+            spender: SPENDER.to_owned(),
+            amount: Uint128::new(100),
+            expires: Expiration::Never {},
+        }];
+        let res =
+            query_reservations(deps.as_ref(), mock_env(), OWNER.to_owned(), None, None).unwrap();
+        assert_eq!(res.reservations, expected);
+        let res = query_reservations_by_spender(
+            deps.as_ref(),
+            mock_env(),
+            SPENDER.to_owned(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.reservations, expected);
+
+        // releasing frees the rest
+        let msg = ExecuteMsg::ReleaseReserved {
+            owner: OWNER.to_owned(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(SPENDER, &[]), msg).unwrap();
+        assert_eq!(res.attributes[3], attr("amount", "100"));
+        transfer(deps.as_mut(), 100).unwrap();
+        let res =
+            query_reservations(deps.as_ref(), mock_env(), OWNER.to_owned(), None, None).unwrap();
+        assert_eq!(res.reservations, vec![]);
+    }
+
+    #[test]
+    fn expired_reservations_are_released() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1_000, 0);
+
+        let height = mock_env().block.height;
+        let err = reserve(deps.as_mut(), 400, Expiration::AtHeight(height)).unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+        reserve(deps.as_mut(), 400, Expiration::AtHeight(height + 10)).unwrap();
+
+        // topping up keeps the expiration
+        reserve(deps.as_mut(), 100, Expiration::Never {}).unwrap();
+        let res =
+            query_reservations(deps.as_ref(), mock_env(), OWNER.to_owned(), None, None).unwrap();
+        assert_eq!(res.reservations[0].amount, Uint128::new(500));
+        assert_eq!(
+            res.reservations[0].expires,
+            Expiration::AtHeight(height + 10)
+        );
+        let err = transfer(deps.as_mut(), 501).unwrap_err();
+        assert_eq!(err, ContractError::CantMoveReservedTokens);
+
+        let mut env = mock_env();
+        env.block.height += 10;
+        let res =
+            query_reservations(deps.as_ref(), env.clone(), OWNER.to_owned(), None, None).unwrap();
+        assert_eq!(res.reservations, vec![]);
+        let err = seize(deps.as_mut(), env.clone(), 100).unwrap_err();
+        assert_eq!(err, ContractError::NoReservation {});
+
+        // any touch of the owner's balance removes the expired reservation
+        let msg = ExecuteMsg::Transfer {
+            recipient: RECIPIENT.to_owned(),
+            amount: Uint128::new(1_000),
+        };
+        execute(deps.as_mut(), env, mock_info(OWNER, &[]), msg).unwrap();
+        assert!(!RESERVED.has(
+            &deps.storage,
+            (&Addr::unchecked(OWNER), &Addr::unchecked(SPENDER))
+        ));
+    }
+
+    #[test]
+    fn reservations_and_vesting() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1_000, 600);
+
+        // vesting tokens cannot be reserved
+        let err = reserve(deps.as_mut(), 401, Expiration::Never {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ReserveExceedsAvailable {
+                available: Uint128::new(400)
+            }
+        );
+        reserve(deps.as_mut(), 300, Expiration::Never {}).unwrap();
+
+        // vesting and reserved tokens are both locked
+        let err = transfer(deps.as_mut(), 101).unwrap_err();
+        assert_eq!(err, ContractError::CantMoveReservedTokens);
+        transfer(deps.as_mut(), 100).unwrap();
+
+        // seizing does not touch the vesting tokens
+        seize(deps.as_mut(), mock_env(), 300).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), OWNER), Uint128::new(600));
+        let err = transfer(deps.as_mut(), 1).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CantMoveVestingTokens {
+                effectively_locked: Uint128::new(600)
+            }
+        );
+    }
+
+    #[test]
+    fn reservations_and_delegation() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), 1_000, 800);
+
+        // vesting tokens can be delegated
+        delegate(deps.as_mut(), 700).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), OWNER), Uint128::new(300));
+
+        // only what is neither delegated nor covering the vesting can be reserved
+        let err = reserve(deps.as_mut(), 201, Expiration::Never {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ReserveExceedsAvailable {
+                available: Uint128::new(200)
+            }
+        );
+        reserve(deps.as_mut(), 200, Expiration::Never {}).unwrap();
+
+        // reserved tokens cannot be delegated nor transferred
+        let err = delegate(deps.as_mut(), 101).unwrap_err();
+        assert_eq!(err, ContractError::CantMoveReservedTokens);
+        let err = transfer(deps.as_mut(), 1).unwrap_err();
+        assert_eq!(err, ContractError::CantMoveReservedTokens);
+
+        // delegating the remaining unreserved tokens still works
+        delegate(deps.as_mut(), 100).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), OWNER), Uint128::new(200));
+
+        // and the reservation can still be seized in full
+        seize(deps.as_mut(), mock_env(), 200).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), OWNER), Uint128::zero());
+        assert_eq!(get_balance(deps.as_ref(), RECIPIENT), Uint128::new(200));
+        assert_eq!(get_balance(deps.as_ref(), STAKING), Uint128::new(800));
+    }
+}
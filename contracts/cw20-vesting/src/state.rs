@@ -1,12 +1,13 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Env, Storage, Timestamp, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, BlockInfo, Env, Order, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
+use cw_utils::Expiration;
 
 use crate::ContractError;
 use cw20::{AllowanceResponse, Logo, MarketingInfoResponse};
-use wynd_utils::Curve;
+use wynd_utils::{Curve, PiecewiseLinear};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -23,13 +24,36 @@ pub struct MinterData {
     pub minter: Addr,
     /// cap is how many more tokens can be issued by the minter
     pub cap: Option<Curve>,
+    /// if set, every burn permanently lowers the cap by the burned amount
+    #[serde(default)]
+    pub burns_reduce_cap: bool,
+    /// cumulative amount the cap curve has been shifted down by burns
+    #[serde(default)]
+    pub cap_reduction: Uint128,
 }
 
 impl TokenInfo {
+    /// Returns the cap at the given time, shifted down by all burns recorded against it.
     pub fn get_cap(&self, block_time: &Timestamp) -> Option<Uint128> {
-        self.mint
-            .as_ref()
-            .and_then(|v| v.cap.as_ref().map(|v| v.value(block_time.seconds())))
+        self.mint.as_ref().and_then(|v| {
+            v.cap.as_ref().map(|c| {
+                c.value(block_time.seconds())
+                    .saturating_sub(v.cap_reduction)
+            })
+        })
+    }
+
+    /// Reduces total supply by the burned amount and, if configured, lowers the cap by the same.
+    /// Supply drops by the same amount, so a cap that covered the supply before the burn
+    /// still covers it afterwards.
+    pub fn burn(&mut self, amount: Uint128) -> StdResult<()> {
+        self.total_supply = self.total_supply.checked_sub(amount)?;
+        if let Some(mint) = self.mint.as_mut() {
+            if mint.burns_reduce_cap && mint.cap.is_some() {
+                mint.cap_reduction = mint.cap_reduction.checked_add(amount)?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -37,19 +61,434 @@ pub const ALLOWLIST: Item<Vec<Addr>> = Item::new("allowlist");
 pub const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
 pub const MARKETING_INFO: Item<MarketingInfoResponse> = Item::new("marketing_info");
 pub const LOGO: Item<Logo> = Item::new("logo");
-pub const BALANCES: Map<&Addr, Uint128> = Map::new("balance");
+/// Hosts logo urls are allowed to point to. Any url is allowed if empty
+pub const LOGO_DOMAINS: Item<Vec<String>> = Item::new("logo_domains");
+/// Instantiator allowed to seed balances, only set while seeding is open
+pub const SEEDER: Item<Addr> = Item::new("seeder");
+/// Can upload the logo and update project and description, but not the marketing address
+pub const LOGO_UPDATER: Item<Addr> = Item::new("logo_updater");
+/// Balances are snapshotted on every block they change in, so `BalanceAt` can answer historical
+/// queries. This costs every balance change a changelog lookup, plus a changelog write for the
+/// first change of an account within a block. Measured as storage gas with the default Cosmos SDK
+/// gas config (see the `snapshot_gas_of_transfers` test), a transfer costs 9288 gas more if it is
+/// the first change of both accounts in the block (on top of 14779 for everything else),
+/// and 2306 more for later ones.
+/// The primary namespace is the same one the plain `Map` used before, so current balances need no
+/// migration; accounts without a changelog fall back to their current balance.
+pub const BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "balance",
+    "balance__checkpoints",
+    "balance__changelog",
+    Strategy::EveryBlock,
+);
+/// History of the total supply, updated on every mint and burn
+pub const TOTAL_SUPPLY_HISTORY: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_supply",
+    "total_supply__checkpoints",
+    "total_supply__changelog",
+    Strategy::EveryBlock,
+);
+/// First block height for which historical balance and supply queries are available
+pub const SNAPSHOT_START_HEIGHT: Item<u64> = Item::new("snapshot_start_height");
 pub const ALLOWANCES: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("allowance");
+/// allowances to delegate tokens on behalf of the owner, separate from transfer allowances
+pub const DELEGATION_ALLOWANCES: Map<(&Addr, &Addr), AllowanceResponse> =
+    Map::new("delegation_allowance");
+/// next nonce a permit signed by the owner must use, 0 if the owner never used one
+pub const PERMIT_NONCES: Map<&Addr, u64> = Map::new("permit_nonces");
 /// existing vesting schedules for each account
 pub const VESTING: Map<&Addr, Curve> = Map::new("vesting");
+/// number of entries in `VESTING`
+pub const VESTING_COUNT: Item<u64> = Item::new("vesting_count");
 /// the maximum complexity an account's vesting curve is allowed to have
 pub const MAX_VESTING_COMPLEXITY: Item<u64> = Item::new("max_vesting_curve_complexity");
+/// address specific vesting complexity limits, overriding `MAX_VESTING_COMPLEXITY`
+pub const COMPLEXITY_TIERS: Map<&Addr, u8> = Map::new("complexity_tiers");
+/// the size of the most complex vesting curve stored so far
+pub const LARGEST_VESTING_SIZE: Item<u64> = Item::new("largest_vesting_curve_size");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct VestingGrant {
+    pub memo: Option<String>,
+    pub amount: Uint128,
+    pub grantor: Addr,
+    pub timestamp: Timestamp,
+}
+
+/// Append-only log of the vesting grants each account received, keyed by (recipient, seq).
+/// Unlike `VESTING`, this is never pruned.
+pub const VESTING_MEMOS: Map<(&Addr, u64), VestingGrant> = Map::new("vesting_memos");
+
+/// Appends a grant to the vesting history of `recipient`, returning its sequence number
+pub fn record_vesting_grant(
+    storage: &mut dyn Storage,
+    recipient: &Addr,
+    grant: &VestingGrant,
+) -> StdResult<u64> {
+    let last = VESTING_MEMOS
+        .prefix(recipient)
+        .keys(storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .unwrap_or_default();
+    let seq = last + 1;
+    VESTING_MEMOS.save(storage, (recipient, seq), grant)?;
+    Ok(seq)
+}
+
+/// Caps the amount every sender can transfer within a time window
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct SpendingLimit {
+    /// Length of a window in seconds. A sender's window starts with their first transfer
+    /// after the previous one ended.
+    pub window_seconds: u64,
+    /// Maximum amount a single sender can transfer within one window
+    pub max_per_sender: Uint128,
+}
+
+pub const SPENDING_LIMIT: Item<Option<SpendingLimit>> = Item::new("spending_limit");
+/// (window_start, total_sent) of the current window of each sender
+pub const SENT_IN_WINDOW: Map<&Addr, (u64, Uint128)> = Map::new("sent_in_window");
+
+/// Returns the spending limit (if any) and what the sender already sent in the current window
+pub fn spending_status(
+    storage: &dyn Storage,
+    env: &Env,
+    sender: &Addr,
+) -> StdResult<Option<(SpendingLimit, u64, Uint128)>> {
+    let limit = match SPENDING_LIMIT.may_load(storage)?.flatten() {
+        Some(limit) => limit,
+        None => return Ok(None),
+    };
+    let now = env.block.time.seconds();
+    let (window_start, sent) = match SENT_IN_WINDOW.may_load(storage, sender)? {
+        Some((start, sent)) if now < start.saturating_add(limit.window_seconds) => (start, sent),
+        // the last window is over, a new one starts now
+        _ => (now, Uint128::zero()),
+    };
+    Ok(Some((limit, window_start, sent)))
+}
+
+/// Adds `amount` to what the sender sent in the current window, failing if that exceeds the
+/// spending limit. Does nothing if no limit is set.
+pub fn apply_spending_limit(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if let Some((limit, window_start, sent)) = spending_status(storage, env, sender)? {
+        let remaining = limit.max_per_sender.saturating_sub(sent);
+        if amount > remaining {
+            return Err(ContractError::SpendingLimitExceeded { remaining });
+        }
+        SENT_IN_WINDOW.save(storage, sender, &(window_start, sent + amount))?;
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq, JsonSchema, Debug)]
+pub struct VestingPolicy {
+    /// Vesting schedules must not unlock anything for this many seconds after the transfer
+    pub min_cliff_seconds: Option<u64>,
+    /// Vesting schedules must be fully vested at most this many seconds after the transfer
+    pub max_duration_seconds: Option<u64>,
+}
+
+pub const VESTING_POLICY: Item<VestingPolicy> = Item::new("vesting_policy");
+/// Vesters whose vesting transfers are not checked against the vesting policy
+pub const POLICY_EXEMPT: Map<&Addr, ()> = Map::new("policy_exempt");
+
+/// Checks a vesting schedule sent by `sender` against the vesting policy.
+/// Does nothing if no policy is set or the sender is exempt.
+pub fn assert_vesting_policy(
+    storage: &dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    schedule: &Curve,
+) -> Result<(), ContractError> {
+    let policy = VESTING_POLICY.may_load(storage)?.unwrap_or_default();
+    if policy == VestingPolicy::default() || POLICY_EXEMPT.has(storage, sender) {
+        return Ok(());
+    }
+
+    let now = env.block.time.seconds();
+    if let Some(min_cliff_seconds) = policy.min_cliff_seconds {
+        // the first time anything is unlocked compared to now
+        let initial = schedule.value(now);
+        let unlocks_at = match initial.checked_sub(Uint128::new(1)) {
+            Ok(below) => schedule.first_time_at_or_below(below),
+            // nothing is locked at all
+            Err(_) => Some(now),
+        };
+        if let Some(unlocks_at) = unlocks_at {
+            if unlocks_at < now.saturating_add(min_cliff_seconds) {
+                return Err(ContractError::VestingCliffTooShort {
+                    min_cliff_seconds,
+                    unlocks_at,
+                });
+            }
+        }
+    }
+    if let Some(max_duration_seconds) = policy.max_duration_seconds {
+        let fully_vested_at = schedule.first_time_at_or_below(Uint128::zero());
+        if !matches!(fully_vested_at, Some(end) if end <= now.saturating_add(max_duration_seconds))
+        {
+            return Err(ContractError::VestingDurationTooLong {
+                max_duration_seconds,
+                fully_vested_at,
+            });
+        }
+    }
+    Ok(())
+}
+
 /// Address of staking token
 pub const STAKING: Item<Addr> = Item::new("staking");
-/// Map of how much each address has delegated
+/// Map of how much each address has delegated.
+///
+/// Delegating moves tokens out of the wallet balance, and vesting tokens may be delegated first.
+/// So the invariant is that `BALANCES + DELEGATED` of an account always covers its vesting lock,
+/// i.e. the wallet balance alone has to keep `vesting.saturating_sub(delegated)` locked.
+/// Delegated tokens only ever return to the wallet through the staking contract, where the lock
+/// applies to them again, so no order of delegating, transferring and undelegating can move
+/// tokens that are still locked.
 pub const DELEGATED: Map<&Addr, Uint128> = Map::new("delegated");
+/// Sum of all entries in `DELEGATED`
+pub const TOTAL_DELEGATED: Item<Uint128> = Item::new("total_delegated");
+/// Sum of the locks every account has to keep in its own balance, i.e. its vesting lock minus
+/// what it delegated (see `DELEGATED`). Kept up to date with `VESTING` and `DELEGATED`, so the
+/// circulating supply does not have to go through all accounts. Steps before the last update
+/// are dropped, and between steps it can be off by rounding.
+pub const TOTAL_LOCKED: Item<Curve> = Item::new("total_locked");
+
+/// State of a `ReconcileDelegationState` run that did not go through all delegations yet
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ReconcileProgress {
+    /// Last delegator summed up so far
+    pub last: Addr,
+    /// Sum of the delegations up to and including `last`
+    pub delegated: Uint128,
+}
+
+pub const RECONCILE_PROGRESS: Item<ReconcileProgress> = Item::new("reconcile_progress");
 
-/// This reduces the account by the given amount, but it also checks the vesting schedule to
-/// ensure there is enough liquidity to do the transfer.
+/// Stores how much `addr` delegated, keeping `TOTAL_LOCKED` up to date
+pub fn save_delegated(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    delegated: Uint128,
+    now: u64,
+) -> Result<(), ContractError> {
+    let old = DELEGATED.may_load(storage, addr)?.unwrap_or_default();
+    // keep the sum of a running reconciliation in line with accounts it already went through
+    if let Some(mut progress) = RECONCILE_PROGRESS.may_load(storage)? {
+        if addr <= &progress.last {
+            progress.delegated = progress
+                .delegated
+                .checked_sub(old)?
+                .checked_add(delegated)?;
+            RECONCILE_PROGRESS.save(storage, &progress)?;
+        }
+    }
+    if let Some(schedule) = VESTING.may_load(storage, addr)? {
+        let old_lock = balance_lock(Some(&schedule), old);
+        update_total_locked(
+            storage,
+            now,
+            &old_lock,
+            &balance_lock(Some(&schedule), delegated),
+        )?;
+    }
+    DELEGATED.save(storage, addr, &delegated)?;
+    Ok(())
+}
+
+/// The part of an account's vesting lock that it has to keep in its own balance
+pub fn balance_lock(schedule: Option<&Curve>, delegated: Uint128) -> Curve {
+    match schedule {
+        Some(schedule) => schedule.saturating_sub(&Curve::constant(delegated.u128())),
+        None => Curve::constant(0),
+    }
+}
+
+/// Replaces the lock of an account in `TOTAL_LOCKED`
+fn update_total_locked(
+    storage: &mut dyn Storage,
+    now: u64,
+    old: &Curve,
+    new: &Curve,
+) -> Result<(), ContractError> {
+    if old == new {
+        return Ok(());
+    }
+    let total = TOTAL_LOCKED
+        .may_load(storage)?
+        .unwrap_or_else(|| Curve::constant(0));
+    let total = total.saturating_sub(old).combine(new)?;
+    TOTAL_LOCKED.save(storage, &drop_past_steps(total, now))?;
+    Ok(())
+}
+
+/// Replaces all steps of `curve` before `now` by a single step at `now`, so that curves summing
+/// up many schedules do not keep growing
+pub fn drop_past_steps(curve: Curve, now: u64) -> Curve {
+    let value = curve.value(now);
+    let steps = match curve {
+        Curve::PiecewiseLinear(pl) => pl.steps,
+        Curve::SaturatingLinear(ref sl) if sl.min_x < now => PiecewiseLinear::from(sl).steps,
+        curve => return curve,
+    };
+    let steps: Vec<_> = std::iter::once((now, value))
+        .chain(steps.into_iter().filter(|&(x, _)| x > now))
+        .collect();
+    if steps.len() == 1 {
+        Curve::Constant { y: value }
+    } else {
+        Curve::PiecewiseLinear(PiecewiseLinear { steps })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Reservation {
+    pub amount: Uint128,
+    pub expires: Expiration,
+}
+
+/// Tokens an owner reserved for a spender, keyed by (owner, spender).
+/// Reserved tokens stay in the owner's balance, but the owner cannot move them.
+pub const RESERVED: Map<(&Addr, &Addr), Reservation> = Map::new("reserved");
+/// Same as `RESERVED`, keyed by (spender, owner)
+pub const RESERVED_BY_SPENDER: Map<(&Addr, &Addr), Reservation> = Map::new("reserved_by_spender");
+
+/// Stores a reservation in both `RESERVED` and `RESERVED_BY_SPENDER`
+pub fn save_reservation(
+    storage: &mut dyn Storage,
+    owner: &Addr,
+    spender: &Addr,
+    reservation: &Reservation,
+) -> StdResult<()> {
+    RESERVED.save(storage, (owner, spender), reservation)?;
+    RESERVED_BY_SPENDER.save(storage, (spender, owner), reservation)
+}
+
+/// Removes a reservation from both `RESERVED` and `RESERVED_BY_SPENDER`
+pub fn remove_reservation(storage: &mut dyn Storage, owner: &Addr, spender: &Addr) {
+    RESERVED.remove(storage, (owner, spender));
+    RESERVED_BY_SPENDER.remove(storage, (spender, owner));
+}
+
+/// Returns the amount `owner` currently has reserved, releasing all expired reservations
+pub fn reserved_total(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    owner: &Addr,
+) -> StdResult<Uint128> {
+    let reservations = RESERVED
+        .prefix(owner)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut total = Uint128::zero();
+    for (spender, reservation) in reservations {
+        if reservation.expires.is_expired(block) {
+            remove_reservation(storage, owner, &spender);
+        } else {
+            total += reservation.amount;
+        }
+    }
+    Ok(total)
+}
+
+/// Returns the amount `owner` currently has reserved, ignoring expired reservations
+pub fn active_reserved(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    owner: &Addr,
+) -> StdResult<Uint128> {
+    RESERVED
+        .prefix(owner)
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref().map_or(true, |(_, reservation)| {
+                !reservation.expires.is_expired(block)
+            })
+        })
+        .map(|item| item.map(|(_, reservation)| reservation.amount))
+        .sum()
+}
+
+/// Returns how much `addr` can currently deduct with `deduct_coins` without failing.
+pub fn movable_amount(storage: &dyn Storage, block: &BlockInfo, addr: &Addr) -> StdResult<Uint128> {
+    let vesting = VESTING
+        .may_load(storage, addr)?
+        .map(|v| v.value(block.time.seconds()))
+        .unwrap_or_default();
+    let reserved = active_reserved(storage, block, addr)?;
+    let delegated = DELEGATED.may_load(storage, addr)?.unwrap_or_default();
+    let balance = BALANCES.may_load(storage, addr)?.unwrap_or_default();
+
+    // mirrors the checks in `deduct_coins`
+    let unlocked = (balance + delegated).saturating_sub(vesting + reserved);
+    Ok(unlocked.min(balance.saturating_sub(reserved)))
+}
+
+/// Returns the maximum vesting curve complexity for the given account
+pub fn max_vesting_complexity(storage: &dyn Storage, addr: &Addr) -> StdResult<u64> {
+    match COMPLEXITY_TIERS.may_load(storage, addr)? {
+        Some(tier) => Ok(tier.into()),
+        None => MAX_VESTING_COMPLEXITY.load(storage),
+    }
+}
+
+/// Stores the vesting schedule of the given account, keeping track of the largest curve size
+/// and `TOTAL_LOCKED`
+pub fn save_vesting(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    schedule: &Curve,
+    now: u64,
+) -> Result<(), ContractError> {
+    let size = schedule.size() as u64;
+    if size > LARGEST_VESTING_SIZE.may_load(storage)?.unwrap_or_default() {
+        LARGEST_VESTING_SIZE.save(storage, &size)?;
+    }
+    let old = VESTING.may_load(storage, addr)?;
+    if old.is_none() {
+        let count = VESTING_COUNT.may_load(storage)?.unwrap_or_default();
+        VESTING_COUNT.save(storage, &(count + 1))?;
+    }
+    let delegated = DELEGATED.may_load(storage, addr)?.unwrap_or_default();
+    let old_lock = balance_lock(old.as_ref(), delegated);
+    update_total_locked(
+        storage,
+        now,
+        &old_lock,
+        &balance_lock(Some(schedule), delegated),
+    )?;
+    VESTING.save(storage, addr, schedule)?;
+    Ok(())
+}
+
+/// Removes the vesting schedule of the given account, if there is one
+pub fn remove_vesting(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    now: u64,
+) -> Result<(), ContractError> {
+    if let Some(old) = VESTING.may_load(storage, addr)? {
+        let count = VESTING_COUNT.may_load(storage)?.unwrap_or_default();
+        VESTING_COUNT.save(storage, &count.saturating_sub(1))?;
+        let delegated = DELEGATED.may_load(storage, addr)?.unwrap_or_default();
+        let old_lock = balance_lock(Some(&old), delegated);
+        update_total_locked(storage, now, &old_lock, &Curve::constant(0))?;
+        VESTING.remove(storage, addr);
+    }
+    Ok(())
+}
+
+/// This reduces the account by the given amount, but it also checks the vesting schedule and
+/// reservations to ensure there is enough liquidity to do the transfer.
 /// (Always use this to enforce the vesting schedule)
 pub fn deduct_coins(
     storage: &mut dyn Storage,
@@ -66,20 +505,32 @@ pub fn deduct_coins(
     // this occurs when there is a curve defined, but it is now at 0 (eg. fully vested)
     // in this case, we can safely delete it (as it will remain 0 forever)
     if vesting == Uint128::zero() {
-        VESTING.remove(storage, sender);
+        remove_vesting(storage, sender, env.block.time.seconds())?;
     }
 
+    let reserved = reserved_total(storage, &env.block, sender)?;
     let delegated = DELEGATED.may_load(storage, sender)?.unwrap_or_default();
-    BALANCES.update(storage, sender, |balance: Option<Uint128>| {
-        let balance = balance.unwrap_or_default();
-        let remainder = (balance + delegated).checked_sub(amount)?;
+    // see `DELEGATED` for why delegated tokens count towards the locked ones
+    let locked = vesting.saturating_sub(delegated);
+    BALANCES.update(
+        storage,
+        sender,
+        env.block.height,
+        |balance: Option<Uint128>| {
+            let balance = balance.unwrap_or_default().checked_sub(amount)?;
 
-        // enforce vesting (must have at least this much available)
-        if vesting > remainder {
-            return Err(ContractError::CantMoveVestingTokens);
-        }
-        // remainder is only used for comparison with vested amount,
-        // true balance should be updated without delegated
-        Ok(balance.checked_sub(amount)?)
-    })
+            // enforce vesting (must have at least this much available)
+            if locked > balance {
+                return Err(ContractError::CantMoveVestingTokens {
+                    effectively_locked: locked,
+                });
+            }
+            // reserved tokens must stay in the balance, on top of the locked ones,
+            // so they can always be seized
+            if locked + reserved > balance {
+                return Err(ContractError::CantMoveReservedTokens);
+            }
+            Ok(balance)
+        },
+    )
 }
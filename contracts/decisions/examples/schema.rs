@@ -6,6 +6,7 @@ use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use wynd_decisions::msg::{
     DecisionResponse, ExecuteMsg, InstantiateMsg, ListDecisionsResponse, QueryMsg,
 };
+use wynd_decisions::state::Config;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -18,4 +19,5 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(DecisionResponse), &out_dir);
     export_schema(&schema_for!(ListDecisionsResponse), &out_dir);
+    export_schema(&schema_for!(Config), &out_dir);
 }
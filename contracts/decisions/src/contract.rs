@@ -1,18 +1,22 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    ensure_eq, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+    ensure, ensure_eq, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+    Response, StdError, StdResult,
 };
 use cw2::set_contract_version;
+use cw_core_interface::voting::{
+    Query as DaoQuery, TotalPowerAtHeightResponse, VotingPowerAtHeightResponse,
+};
 use cw_storage_plus::Bound;
 use cw_utils::{ensure_from_older_version, nonpayable};
 
 use crate::error::ContractError;
 use crate::msg::{
-    DecisionResponse, ExecuteMsg, InstantiateMsg, ListDecisionsResponse, MigrateMsg, QueryMsg,
-    RecordMsg,
+    DecisionResponse, ExecuteMsg, InstantiateMsg, ListDecisionsResponse, MigrateMsg, PowerSnapshot,
+    QueryMsg, RecordMsg,
 };
-use crate::state::{last_decision, Config, Decision, CONFIG, DECISIONS};
+use crate::state::{decisions, last_decision, Config, Decision, CONFIG, STORED_CONFIG};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:wynd-decisions";
@@ -43,11 +47,20 @@ pub fn instantiate(
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let owner = deps.api.addr_validate(&msg.owner)?;
-    CONFIG.save(deps.storage, &Config { owner })?;
+    let staking_contract = deps.api.addr_validate(&msg.staking_contract)?;
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            owner,
+            staking_contract,
+            quorum: Decimal::zero(),
+        },
+    )?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
-        .add_attribute("owner", msg.owner))
+        .add_attribute("owner", msg.owner)
+        .add_attribute("staking_contract", msg.staking_contract))
 }
 
 /// ## Description
@@ -63,6 +76,10 @@ pub fn instantiate(
 ///
 /// ## Execution Messages
 /// * **ExecuteMsg::Record** Allow to store a decision.
+///
+/// * **ExecuteMsg::UpdateStakingContract** Allow to change the staking contract.
+///
+/// * **ExecuteMsg::UpdateQuorum** Allow to change the quorum needed to record a decision.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -72,6 +89,12 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Record(msg) => record(deps, env, info, msg),
+        ExecuteMsg::UpdateStakingContract { staking_contract } => {
+            update_staking_contract(deps, info, staking_contract)
+        }
+        ExecuteMsg::UpdateQuorum { quorum_pct } => {
+            execute_update_decisions_quorum(deps, info, quorum_pct)
+        }
     }
 }
 
@@ -88,6 +111,16 @@ fn record(
 
     record.validate()?;
 
+    let power_snapshot = query_power_snapshot(deps.as_ref(), &cfg.staking_contract, &info.sender)?;
+    ensure!(
+        power_snapshot.recorder_power >= power_snapshot.total_power * cfg.quorum,
+        ContractError::QuorumNotReached(
+            power_snapshot.recorder_power,
+            power_snapshot.total_power,
+            cfg.quorum
+        )
+    );
+
     // record this in the next available slot
     let id = last_decision(deps.as_ref())? + 1;
     let decision = Decision {
@@ -96,12 +129,88 @@ fn record(
         body: record.body,
         url: record.url,
         hash: record.hash,
+        power_snapshot: Some(power_snapshot.clone()),
+        recorded_height: env.block.height,
     };
-    DECISIONS.save(deps.storage, id, &decision)?;
+    decisions().save(deps.storage, id, &decision)?;
 
     Ok(Response::new()
         .add_attribute("method", "record")
-        .add_attribute("title", record.title))
+        .add_attribute("title", record.title)
+        .add_attribute("total_power", power_snapshot.total_power)
+        .add_attribute("recorder_power", power_snapshot.recorder_power))
+}
+
+/// Capture the current total voting power and the voting power of the recorder
+fn query_power_snapshot(
+    deps: Deps,
+    staking_contract: &Addr,
+    recorder: &Addr,
+) -> Result<PowerSnapshot, ContractError> {
+    let query_failed = |err: StdError| {
+        ContractError::StakingQueryFailed(staking_contract.to_string(), err.to_string())
+    };
+
+    let total: TotalPowerAtHeightResponse = deps
+        .querier
+        .query_wasm_smart(
+            staking_contract,
+            &DaoQuery::TotalPowerAtHeight { height: None },
+        )
+        .map_err(query_failed)?;
+    let recorder: VotingPowerAtHeightResponse = deps
+        .querier
+        .query_wasm_smart(
+            staking_contract,
+            &DaoQuery::VotingPowerAtHeight {
+                address: recorder.to_string(),
+                height: None,
+            },
+        )
+        .map_err(query_failed)?;
+
+    Ok(PowerSnapshot {
+        height: total.height,
+        total_power: total.power,
+        recorder_power: recorder.power,
+    })
+}
+
+/// Change the staking contract if called by owner
+fn update_staking_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_contract: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut cfg = CONFIG.load(deps.storage)?;
+    ensure_eq!(cfg.owner, info.sender, ContractError::Unauthorized);
+
+    cfg.staking_contract = deps.api.addr_validate(&staking_contract)?;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_staking_contract")
+        .add_attribute("staking_contract", staking_contract))
+}
+
+/// Change the quorum if called by owner
+fn execute_update_decisions_quorum(
+    deps: DepsMut,
+    info: MessageInfo,
+    quorum_pct: Decimal,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut cfg = CONFIG.load(deps.storage)?;
+    ensure_eq!(cfg.owner, info.sender, ContractError::Unauthorized);
+    ensure!(quorum_pct <= Decimal::one(), ContractError::InvalidQuorum);
+
+    cfg.quorum = quorum_pct;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_quorum")
+        .add_attribute("quorum", quorum_pct.to_string()))
 }
 
 /// Query enumeration used to get an specific or all decisions
@@ -112,11 +221,24 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::ListDecisions { start_after, limit } => {
             to_binary(&list_decisions(deps, start_after, limit)?)
         }
+        QueryMsg::DecisionsByBlockRange {
+            start_height,
+            end_height,
+            start_after,
+            limit,
+        } => to_binary(&decisions_by_block_range(
+            deps,
+            start_height,
+            end_height,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Config {} => to_binary(&CONFIG.load(deps.storage)?),
     }
 }
 
 fn query_decision(deps: Deps, id: u64) -> StdResult<DecisionResponse> {
-    Ok(DECISIONS.load(deps.storage, id)?.into_response(id))
+    Ok(decisions().load(deps.storage, id)?.into_response(id))
 }
 
 // settings for pagination
@@ -131,7 +253,7 @@ fn list_decisions(
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
 
-    let decisions = DECISIONS
+    let decisions = decisions()
         .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
         .map(|item| {
@@ -142,10 +264,62 @@ fn list_decisions(
     Ok(ListDecisionsResponse { decisions })
 }
 
+fn decisions_by_block_range(
+    deps: Deps,
+    start_height: u64,
+    end_height: u64,
+    start_after: Option<(u64, u64)>,
+    limit: Option<u32>,
+) -> StdResult<ListDecisionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // several decisions can share a height, so the cursor includes the id
+    let min = match start_after {
+        Some(cursor) if cursor.0 >= start_height => Bound::exclusive(cursor),
+        _ => Bound::inclusive((start_height, 0)),
+    };
+    let max = Bound::inclusive((end_height, u64::MAX));
+
+    let decisions = decisions()
+        .idx
+        .recorded_height
+        .range(deps.storage, Some(min), Some(max), Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (id, dec) = item?;
+            Ok(dec.into_response(id))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ListDecisionsResponse { decisions })
+}
+
 /// Entry point for migration
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let stored = STORED_CONFIG.load(deps.storage)?;
+    let staking_contract = match msg.staking_contract {
+        Some(staking_contract) => deps.api.addr_validate(&staking_contract)?,
+        None => stored
+            .staking_contract
+            .ok_or(ContractError::MissingStakingContract)?,
+    };
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            owner: stored.owner,
+            staking_contract,
+            quorum: stored.quorum,
+        },
+    )?;
+
+    // index decisions recorded before the height index existed, at their stored height
+    let stored = decisions()
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, decision) in stored {
+        decisions().replace(deps.storage, id, Some(&decision), None)?;
+    }
     Ok(Response::new())
 }
 
@@ -153,18 +327,67 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
 mod tests {
     use super::*;
 
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::Timestamp;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        from_slice, ContractResult, OwnedDeps, SystemError, SystemResult, Timestamp, Uint128,
+        WasmQuery,
+    };
+
+    const STAKING: &str = "staking";
+
+    /// Mocks a staking contract with the given total power where every address has `power`
+    fn mock_staking(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        total: u128,
+        power: u128,
+    ) {
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == STAKING => {
+                let res = match from_slice(msg).unwrap() {
+                    DaoQuery::TotalPowerAtHeight { height } => {
+                        to_binary(&TotalPowerAtHeightResponse {
+                            power: Uint128::new(total),
+                            height: height.unwrap_or(12_345),
+                        })
+                    }
+                    DaoQuery::VotingPowerAtHeight { height, .. } => {
+                        to_binary(&VotingPowerAtHeightResponse {
+                            power: Uint128::new(power),
+                            height: height.unwrap_or(12_345),
+                        })
+                    }
+                    _ => unimplemented!(),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::NoSuchContract {
+                addr: "unknown".to_string(),
+            }),
+        });
+    }
+
+    fn sample_record() -> RecordMsg {
+        RecordMsg {
+            title: "My awesome decision".to_string(),
+            body: "Let's all go to the beach and enjoy the sun!".to_string(),
+            url: None,
+            hash: None,
+        }
+    }
 
     #[test]
     fn happy_path() {
         let mut deps = mock_dependencies();
+        mock_staking(&mut deps, 1_000, 250);
         let owner = "the-man";
 
         // init
         let info = mock_info("someone", &[]);
         let msg = InstantiateMsg {
             owner: owner.to_string(),
+            staking_contract: STAKING.to_string(),
         };
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -195,6 +418,11 @@ mod tests {
         execute(deps.as_mut(), env, mock_info(owner, &[]), msg).unwrap();
 
         // what do we expect?
+        let power_snapshot = Some(PowerSnapshot {
+            height: 12_345,
+            total_power: Uint128::new(1_000),
+            recorder_power: Uint128::new(250),
+        });
         let expected1 = DecisionResponse {
             id: 1,
             created: time1,
@@ -202,6 +430,8 @@ mod tests {
             body: record.body,
             url: record.url,
             hash: record.hash,
+            power_snapshot: power_snapshot.clone(),
+            recorded_height: 12_345,
         };
         let expected2 = DecisionResponse {
             id: 2,
@@ -210,6 +440,8 @@ mod tests {
             body: record2.body,
             url: record2.url,
             hash: record2.hash,
+            power_snapshot,
+            recorded_height: 12_345,
         };
 
         let dec1 = query_decision(deps.as_ref(), 1).unwrap();
@@ -220,4 +452,318 @@ mod tests {
         let all = list_decisions(deps.as_ref(), None, None).unwrap();
         assert_eq!(all.decisions, vec![expected1, expected2]);
     }
+
+    #[test]
+    fn record_fails_if_staking_query_fails() {
+        let mut deps = mock_dependencies();
+        let owner = "the-man";
+
+        let msg = InstantiateMsg {
+            owner: owner.to_string(),
+            staking_contract: "not-staking".to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap();
+        mock_staking(&mut deps, 1_000, 250);
+
+        let msg = ExecuteMsg::Record(sample_record());
+        let err = execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap_err();
+        assert!(
+            matches!(err, ContractError::StakingQueryFailed(ref addr, _) if addr == "not-staking"),
+            "unexpected error: {}",
+            err
+        );
+        assert_eq!(last_decision(deps.as_ref()).unwrap(), 0);
+    }
+
+    #[test]
+    fn update_staking_contract() {
+        let mut deps = mock_dependencies();
+        let owner = "the-man";
+
+        let msg = InstantiateMsg {
+            owner: owner.to_string(),
+            staking_contract: "old-staking".to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap();
+        mock_staking(&mut deps, 1_000, 250);
+
+        // only the owner can change it
+        let msg = ExecuteMsg::UpdateStakingContract {
+            staking_contract: STAKING.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+
+        let raw = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        let cfg: Config = from_slice(&raw).unwrap();
+        assert_eq!(cfg.staking_contract.as_str(), STAKING);
+
+        // records now use the new staking contract
+        let msg = ExecuteMsg::Record(sample_record());
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+        let snapshot = query_decision(deps.as_ref(), 1)
+            .unwrap()
+            .power_snapshot
+            .unwrap();
+        assert_eq!(snapshot.total_power, Uint128::new(1_000));
+    }
+
+    #[test]
+    fn update_quorum() {
+        let mut deps = mock_dependencies();
+        mock_staking(&mut deps, 1_000, 250);
+        let owner = "the-man";
+
+        let msg = InstantiateMsg {
+            owner: owner.to_string(),
+            staking_contract: STAKING.to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap();
+
+        // only the owner can change it
+        let msg = ExecuteMsg::UpdateQuorum {
+            quorum_pct: Decimal::percent(30),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+
+        // cannot be more than the total power
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &[]),
+            ExecuteMsg::UpdateQuorum {
+                quorum_pct: Decimal::percent(101),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidQuorum));
+
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+        let raw = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        let cfg: Config = from_slice(&raw).unwrap();
+        assert_eq!(cfg.quorum, Decimal::percent(30));
+
+        // owner holds 25% of the power
+        let msg = ExecuteMsg::Record(sample_record());
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::QuorumNotReached(recorder, total, quorum)
+                if recorder.u128() == 250 && total.u128() == 1_000 && quorum == Decimal::percent(30)
+        ));
+        assert_eq!(last_decision(deps.as_ref()).unwrap(), 0);
+
+        // lowering the quorum allows recording again
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &[]),
+            ExecuteMsg::UpdateQuorum {
+                quorum_pct: Decimal::percent(25),
+            },
+        )
+        .unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+        assert_eq!(last_decision(deps.as_ref()).unwrap(), 1);
+    }
+
+    #[test]
+    fn decisions_by_block_range_pagination() {
+        let mut deps = mock_dependencies();
+        mock_staking(&mut deps, 1_000, 250);
+        let owner = "the-man";
+
+        let msg = InstantiateMsg {
+            owner: owner.to_string(),
+            staking_contract: STAKING.to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap();
+
+        // two decisions at height 20, one at each of 10, 30 and 40
+        for height in [10, 20, 20, 30, 40] {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::Record(sample_record());
+            execute(deps.as_mut(), env, mock_info(owner, &[]), msg).unwrap();
+        }
+
+        let range = |start_height, end_height, limit| {
+            decisions_by_block_range(deps.as_ref(), start_height, end_height, None, limit)
+                .unwrap()
+                .decisions
+                .into_iter()
+                .map(|d| (d.id, d.recorded_height))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(range(0, 100, None).len(), 5);
+        assert_eq!(range(20, 30, None), vec![(2, 20), (3, 20), (4, 30)]);
+        assert_eq!(range(11, 19, None), vec![]);
+        assert_eq!(range(40, 40, None), vec![(5, 40)]);
+
+        // paginate with a limit
+        assert_eq!(range(0, 100, Some(2)), vec![(1, 10), (2, 20)]);
+        assert_eq!(range(30, 100, Some(2)), vec![(4, 30), (5, 40)]);
+
+        // continue after the last decision of a page, even within the same height
+        let after = |start_after| {
+            decisions_by_block_range(deps.as_ref(), 0, 100, start_after, Some(2))
+                .unwrap()
+                .decisions
+                .into_iter()
+                .map(|d| (d.id, d.recorded_height))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(after(Some((20, 2))), vec![(3, 20), (4, 30)]);
+        assert_eq!(after(Some((40, 5))), vec![]);
+        // a cursor below the range starts at its beginning
+        assert_eq!(
+            decisions_by_block_range(deps.as_ref(), 20, 100, Some((10, 1)), Some(1))
+                .unwrap()
+                .decisions[0]
+                .id,
+            2
+        );
+
+        // raw query works as well
+        let raw = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DecisionsByBlockRange {
+                start_height: 15,
+                end_height: 25,
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+        let res: ListDecisionsResponse = from_slice(&raw).unwrap();
+        assert_eq!(res.decisions.len(), 1);
+        assert_eq!(res.decisions[0].id, 2);
+    }
+
+    #[test]
+    fn migration_indexes_legacy_decisions() {
+        let mut deps = mock_dependencies();
+        let owner = "the-man";
+        let msg = InstantiateMsg {
+            owner: owner.to_string(),
+            staking_contract: STAKING.to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.0.1").unwrap();
+
+        // decisions stored without the height index
+        let legacy: cw_storage_plus::Map<u64, Decision> = cw_storage_plus::Map::new("decisions");
+        for id in 1..=2 {
+            let decision = Decision {
+                created: 1234,
+                title: format!("Legacy decision {}", id),
+                body: "Stored before heights were tracked".to_string(),
+                url: None,
+                hash: None,
+                power_snapshot: None,
+                recorded_height: 0,
+            };
+            legacy.save(&mut deps.storage, id, &decision).unwrap();
+        }
+        let range = |deps: Deps| {
+            decisions_by_block_range(deps, 0, 0, None, None)
+                .unwrap()
+                .decisions
+                .into_iter()
+                .map(|d| d.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(range(deps.as_ref()), Vec::<u64>::new());
+
+        let msg = MigrateMsg {
+            staking_contract: None,
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(range(deps.as_ref()), vec![1, 2]);
+        assert_eq!(
+            list_decisions(deps.as_ref(), None, None)
+                .unwrap()
+                .decisions
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn migration_keeps_config() {
+        let mut deps = mock_dependencies();
+        let owner = Addr::unchecked("the-man");
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.0.1").unwrap();
+
+        // config from before voting power snapshots needs a staking contract
+        deps.as_mut()
+            .storage
+            .set(b"config", br#"{"owner":"the-man"}"#);
+        let msg = MigrateMsg {
+            staking_contract: None,
+        };
+        let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::MissingStakingContract));
+
+        let msg = MigrateMsg {
+            staking_contract: Some(STAKING.to_string()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        let expected = Config {
+            owner: owner.clone(),
+            staking_contract: Addr::unchecked(STAKING),
+            quorum: Decimal::zero(),
+        };
+        assert_eq!(CONFIG.load(&deps.storage).unwrap(), expected);
+
+        // later migrations keep the staking contract and quorum
+        let expected = Config {
+            quorum: Decimal::percent(30),
+            ..expected
+        };
+        CONFIG.save(&mut deps.storage, &expected).unwrap();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let msg = MigrateMsg {
+            staking_contract: None,
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(CONFIG.load(&deps.storage).unwrap(), expected);
+
+        // unless a new staking contract is given
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let msg = MigrateMsg {
+            staking_contract: Some("new-staking".to_string()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(
+            CONFIG.load(&deps.storage).unwrap(),
+            Config {
+                owner,
+                staking_contract: Addr::unchecked("new-staking"),
+                quorum: Decimal::percent(30),
+            }
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Decimal, StdError, Uint128};
 use cw_utils::PaymentError;
 use thiserror::Error;
 
@@ -20,4 +20,20 @@ pub enum ContractError {
     /// length handler error for RecordMessage
     #[error("{0} must be between {1} and {2} characters")]
     InvalidLength(&'static str, u64, u64),
+
+    /// Voting power could not be read from the staking contract
+    #[error("Failed to query voting power from staking contract {0}: {1}")]
+    StakingQueryFailed(String, String),
+
+    /// Quorum is a share of the total voting power and cannot exceed it
+    #[error("Quorum must be at most 100%")]
+    InvalidQuorum,
+
+    /// The owner holds too little voting power to record a decision
+    #[error("Recorder holds {0} of {1} voting power, quorum of {2} not reached")]
+    QuorumNotReached(Uint128, Uint128, Decimal),
+
+    /// Versions without voting power snapshots have no staking contract to keep
+    #[error("Migrating from a version without voting power snapshots requires a staking contract")]
+    MissingStakingContract,
 }
@@ -23,3 +23,6 @@ pub mod msg;
 
 /// state on the blockchain
 pub mod state;
+
+#[cfg(test)]
+mod multitest;
@@ -1,4 +1,5 @@
 use crate::error::ContractError;
+use cosmwasm_std::{Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -7,6 +8,8 @@ use serde::{Deserialize, Serialize};
 pub struct InstantiateMsg {
     /// The address who can add decisions to the log
     pub owner: String,
+    /// The wynd-stake contract whose voting power is captured with every decision
+    pub staking_contract: String,
 }
 
 /// Execute message enumeration
@@ -15,6 +18,17 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     /// Store a Decision
     Record(RecordMsg),
+    /// Change the staking contract used for voting power snapshots. Only callable by the owner
+    UpdateStakingContract {
+        /// The new wynd-stake contract address
+        staking_contract: String,
+    },
+    /// Change the share of the total voting power the owner needs to record a decision.
+    /// Only callable by the owner
+    UpdateQuorum {
+        /// The new quorum, at most 100%
+        quorum_pct: Decimal,
+    },
 }
 
 /// Represents a Decision track
@@ -89,6 +103,33 @@ pub enum QueryMsg {
         /// Represents how many rows will return the [`DecisionResponse`]
         limit: Option<u32>,
     },
+    /// Query decisions recorded between two block heights (both inclusive),
+    /// in ascending height order. Decisions recorded before heights were tracked
+    /// are at height zero.
+    DecisionsByBlockRange {
+        /// First block height to include
+        start_height: u64,
+        /// Last block height to include
+        end_height: u64,
+        /// `(recorded_height, id)` of the last decision of the previous page, to continue
+        /// after it. If None, it will start from `start_height`
+        start_after: Option<(u64, u64)>,
+        /// Represents how many rows will return the [`DecisionResponse`]
+        limit: Option<u32>,
+    },
+    /// Returns the current [`Config`](crate::state::Config)
+    Config {},
+}
+
+/// Voting power of the staking contract at the block a decision was recorded
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, JsonSchema, Debug)]
+pub struct PowerSnapshot {
+    /// Block height at which the voting power was captured
+    pub height: u64,
+    /// Total voting power of all stakers
+    pub total_power: Uint128,
+    /// Voting power of the address that recorded the decision
+    pub recorder_power: Uint128,
 }
 
 /// Decision Response that may contain the public IPFS link or private hash for the document
@@ -107,6 +148,12 @@ pub struct DecisionResponse {
     /// Optional document hash. Intended when this refers to a privately shared document
     /// in order to assert which version was approved.
     pub hash: Option<String>,
+    /// Voting power at the time of the decision. Not available for decisions
+    /// recorded before snapshots were introduced
+    pub power_snapshot: Option<PowerSnapshot>,
+    /// Block height at which the decision was recorded. Zero for decisions recorded
+    /// before heights were tracked
+    pub recorded_height: u64,
 }
 
 /// Decision Response list wrapper
@@ -118,4 +165,9 @@ pub struct ListDecisionsResponse {
 
 /// Message that is passed during migration
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// Staking contract to use for voting power snapshots. Required when migrating
+    /// from a version without snapshots, replaces the configured one otherwise
+    #[serde(default)]
+    pub staking_contract: Option<String>,
+}
@@ -0,0 +1,89 @@
+use cosmwasm_std::Uint128;
+
+use super::suite::SuiteBuilder;
+use crate::error::ContractError;
+use crate::msg::PowerSnapshot;
+
+#[test]
+fn record_captures_current_voting_power() {
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(&[("owner", 1_000), ("member", 4_000)])
+        .build();
+
+    suite.delegate("owner", 500).unwrap();
+    suite.delegate("member", 3_000).unwrap();
+    suite.next_block();
+
+    suite.record("owner", "First decision").unwrap();
+    let height = suite.app.block_info().height;
+    let decision = suite.query_decision(1).unwrap();
+    assert_eq!(
+        decision.power_snapshot,
+        Some(PowerSnapshot {
+            height,
+            total_power: Uint128::new(3_500),
+            recorder_power: Uint128::new(500),
+        })
+    );
+
+    // stake changes in the same block are reflected in the snapshot
+    suite.next_block();
+    suite.unbond("member", 1_000).unwrap();
+    suite.delegate("owner", 500).unwrap();
+    suite.record("owner", "Second decision").unwrap();
+    let decision = suite.query_decision(2).unwrap();
+    assert_eq!(
+        decision.power_snapshot,
+        Some(PowerSnapshot {
+            height: height + 1,
+            total_power: Uint128::new(3_000),
+            recorder_power: Uint128::new(1_000),
+        })
+    );
+
+    // the first snapshot is not affected
+    let decision = suite.query_decision(1).unwrap();
+    assert_eq!(
+        decision.power_snapshot.unwrap().total_power,
+        Uint128::new(3_500)
+    );
+}
+
+#[test]
+fn record_fails_with_invalid_staking_contract() {
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(&[("owner", 1_000)])
+        .build();
+
+    // only the owner can update the staking contract
+    let err = suite
+        .update_staking_contract("someone", "not-a-contract")
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Unauthorized.to_string(),
+        err.root_cause().to_string()
+    );
+
+    suite
+        .update_staking_contract("owner", "not-a-contract")
+        .unwrap();
+    assert_eq!(
+        suite.query_config().unwrap().staking_contract.as_str(),
+        "not-a-contract"
+    );
+
+    let err = suite.record("owner", "Lost decision").unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .starts_with("Failed to query voting power from staking contract not-a-contract"));
+    suite.query_decision(1).unwrap_err();
+
+    // pointing back to the staking contract makes recording work again
+    let stake_contract = suite.stake_contract();
+    suite
+        .update_staking_contract("owner", &stake_contract)
+        .unwrap();
+    suite.record("owner", "Found decision").unwrap();
+    assert!(suite.query_decision(1).unwrap().power_snapshot.is_some());
+}
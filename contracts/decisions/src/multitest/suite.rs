@@ -0,0 +1,254 @@
+use anyhow::Result as AnyResult;
+
+use cosmwasm_std::{to_binary, Addr, Decimal, Empty, Uint128};
+use cw20_vesting::{
+    ExecuteMsg as VestingExecuteMsg, InitBalance, InstantiateMsg as VestingInstantiateMsg,
+    MinterInfo,
+};
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use wynd_stake::msg::{
+    ExecuteMsg as StakeExecuteMsg, InstantiateMsg as StakeInstantiateMsg, ReceiveDelegationMsg,
+    StakeConfig,
+};
+
+use crate::msg::{DecisionResponse, ExecuteMsg, InstantiateMsg, QueryMsg, RecordMsg};
+use crate::state::Config;
+
+pub const UNBONDING_PERIOD: u64 = 1_000;
+
+fn contract_decisions() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+
+    Box::new(contract)
+}
+
+fn contract_stake() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        wynd_stake::contract::execute,
+        wynd_stake::contract::instantiate,
+        wynd_stake::contract::query,
+    )
+    .with_reply(wynd_stake::contract::reply);
+
+    Box::new(contract)
+}
+
+fn contract_vesting() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        cw20_vesting::contract::execute,
+        cw20_vesting::contract::instantiate,
+        cw20_vesting::contract::query,
+    );
+
+    Box::new(contract)
+}
+
+#[derive(Debug, Default)]
+pub struct SuiteBuilder {
+    initial_balances: Vec<InitBalance>,
+}
+
+impl SuiteBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_initial_balances(mut self, balances: &[(&str, u128)]) -> Self {
+        self.initial_balances = balances
+            .iter()
+            .map(|&(address, amount)| InitBalance {
+                address: address.to_owned(),
+                amount: amount.into(),
+                vesting: None,
+                memo: None,
+                relative_vesting: None,
+            })
+            .collect();
+        self
+    }
+
+    #[track_caller]
+    pub fn build(self) -> Suite {
+        let mut app = App::default();
+        let admin = Addr::unchecked("admin");
+        let owner = Addr::unchecked("owner");
+
+        let vesting_id = app.store_code(contract_vesting());
+        let vesting_contract = app
+            .instantiate_contract(
+                vesting_id,
+                admin.clone(),
+                &VestingInstantiateMsg {
+                    name: "vesting".to_owned(),
+                    symbol: "VEST".to_owned(),
+                    decimals: 9,
+                    initial_balances: self.initial_balances,
+                    mint: Some(MinterInfo {
+                        minter: "minter".to_owned(),
+                        cap: None,
+                        burns_reduce_cap: false,
+                    }),
+                    marketing: None,
+                    allowed_vesters: None,
+                    max_curve_complexity: 10,
+                    seeding_open: false,
+                },
+                &[],
+                "vesting",
+                None,
+            )
+            .unwrap();
+
+        let stake_id = app.store_code(contract_stake());
+        let stake_contract = app
+            .instantiate_contract(
+                stake_id,
+                admin.clone(),
+                &StakeInstantiateMsg {
+                    cw20_contract: vesting_contract.to_string(),
+                    tokens_per_power: Uint128::new(1),
+                    min_bond: Uint128::new(1),
+                    stake_config: vec![StakeConfig {
+                        unbonding_period: UNBONDING_PERIOD,
+                        voting_multiplier: Decimal::one(),
+                        reward_multiplier: Decimal::one(),
+                        exit_vesting: None,
+                    }],
+                    admin: None,
+                    max_hooks: None,
+                    distribution_fee: None,
+                    vesting_voting_discount: None,
+                },
+                &[],
+                "stake",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("minter"),
+            vesting_contract.clone(),
+            &VestingExecuteMsg::UpdateStakingAddress {
+                address: stake_contract.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let decisions_id = app.store_code(contract_decisions());
+        let decisions_contract = app
+            .instantiate_contract(
+                decisions_id,
+                admin,
+                &InstantiateMsg {
+                    owner: owner.to_string(),
+                    staking_contract: stake_contract.to_string(),
+                },
+                &[],
+                "decisions",
+                None,
+            )
+            .unwrap();
+
+        Suite {
+            app,
+            decisions_contract,
+            stake_contract,
+            vesting_contract,
+        }
+    }
+}
+
+pub struct Suite {
+    pub app: App,
+    decisions_contract: Addr,
+    stake_contract: Addr,
+    vesting_contract: Addr,
+}
+
+impl Suite {
+    pub fn stake_contract(&self) -> String {
+        self.stake_contract.to_string()
+    }
+
+    pub fn next_block(&mut self) {
+        self.app.update_block(|block| {
+            block.height += 1;
+            block.time = block.time.plus_seconds(5);
+        });
+    }
+
+    pub fn delegate(&mut self, sender: &str, amount: u128) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.vesting_contract.clone(),
+            &VestingExecuteMsg::Delegate {
+                amount: amount.into(),
+                msg: to_binary(&ReceiveDelegationMsg::Delegate {
+                    unbonding_period: UNBONDING_PERIOD,
+                })?,
+            },
+            &[],
+        )
+    }
+
+    pub fn unbond(&mut self, sender: &str, amount: u128) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &StakeExecuteMsg::Unbond {
+                tokens: amount.into(),
+                unbonding_period: UNBONDING_PERIOD,
+                claim_recipient: None,
+            },
+            &[],
+        )
+    }
+
+    pub fn record(&mut self, sender: &str, title: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.decisions_contract.clone(),
+            &ExecuteMsg::Record(RecordMsg {
+                title: title.to_owned(),
+                body: "The community agreed to this after long discussions".to_owned(),
+                url: None,
+                hash: None,
+            }),
+            &[],
+        )
+    }
+
+    pub fn update_staking_contract(
+        &mut self,
+        sender: &str,
+        staking_contract: &str,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.decisions_contract.clone(),
+            &ExecuteMsg::UpdateStakingContract {
+                staking_contract: staking_contract.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn query_decision(&self, id: u64) -> AnyResult<DecisionResponse> {
+        Ok(self
+            .app
+            .wrap()
+            .query_wasm_smart(&self.decisions_contract, &QueryMsg::Decision { id })?)
+    }
+
+    pub fn query_config(&self) -> AnyResult<Config> {
+        Ok(self
+            .app
+            .wrap()
+            .query_wasm_smart(&self.decisions_contract, &QueryMsg::Config {})?)
+    }
+}
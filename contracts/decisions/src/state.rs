@@ -1,21 +1,66 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::msg::DecisionResponse;
-use cosmwasm_std::{Addr, Deps, Order, StdResult};
-use cw_storage_plus::{Item, Map};
+use crate::msg::{DecisionResponse, PowerSnapshot};
+use cosmwasm_std::{Addr, Decimal, Deps, Order, StdResult};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
 
 /// Configuration Item
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Secondary indexes of the decisions
+pub struct DecisionIndexes<'a> {
+    /// Decision ids by the block height they were recorded at
+    pub recorded_height: MultiIndex<'a, u64, Decision, u64>,
+}
+
+impl<'a> IndexList<Decision> for DecisionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Decision>> + '_> {
+        Box::new(std::iter::once(
+            &self.recorded_height as &dyn Index<Decision>,
+        ))
+    }
+}
+
 /// Desicion Map <Decision ID, Decision>
-pub const DECISIONS: Map<u64, Decision> = Map::new("decisions");
+pub fn decisions<'a>() -> IndexedMap<'a, u64, Decision, DecisionIndexes<'a>> {
+    let indexes = DecisionIndexes {
+        recorded_height: MultiIndex::new(
+            |_, decision| decision.recorded_height,
+            "decisions",
+            "decisions__recorded_height",
+        ),
+    };
+    IndexedMap::new("decisions", indexes)
+}
+
+/// Configuration as stored by any version, only read when migrating
+pub const STORED_CONFIG: Item<StoredConfig> = Item::new("config");
 
 /// Configuration
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 pub struct Config {
     /// contract owner, wynd foundation
     pub owner: Addr,
+    /// wynd-stake contract queried for voting power when recording a decision
+    pub staking_contract: Addr,
+    /// Share of the total voting power the owner needs to record a decision
+    #[serde(default)]
+    pub quorum: Decimal,
+}
+
+/// Configuration as stored by any version. Versions before voting power snapshots were
+/// introduced had no staking contract and quorum
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct StoredConfig {
+    /// contract owner, wynd foundation
+    pub owner: Addr,
+    /// wynd-stake contract queried for voting power, missing before snapshots
+    #[serde(default)]
+    pub staking_contract: Option<Addr>,
+    /// Share of the total voting power the owner needs to record a decision
+    #[serde(default)]
+    pub quorum: Decimal,
 }
 
 /// Decision
@@ -32,6 +77,13 @@ pub struct Decision {
     /// Optional document hash. Intended when this refers to a privately shared document
     /// in order to assert which version was approved.
     pub hash: Option<String>,
+    /// Voting power at the time of the decision
+    #[serde(default)]
+    pub power_snapshot: Option<PowerSnapshot>,
+    /// Block height at which the decision was recorded. Zero for decisions recorded
+    /// before heights were tracked
+    #[serde(default)]
+    pub recorded_height: u64,
 }
 
 impl Decision {
@@ -49,13 +101,15 @@ impl Decision {
             body: self.body,
             url: self.url,
             hash: self.hash,
+            power_snapshot: self.power_snapshot,
+            recorded_height: self.recorded_height,
         }
     }
 }
 
 /// Returns the last recorded decision id (auto-incremented count)
 pub fn last_decision(deps: Deps) -> StdResult<u64> {
-    DECISIONS
+    decisions()
         .keys(deps.storage, None, None, Order::Descending)
         .next()
         .unwrap_or(Ok(0))
@@ -3,15 +3,20 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
-use cw4::{AdminResponse, HooksResponse};
+use cw4::{AdminResponse, MemberListResponse};
 use cw_core_interface::voting::{
     InfoResponse, TotalPowerAtHeightResponse, VotingPowerAtHeightResponse,
 };
 use wynd_stake::msg::{
-    AllStakedResponse, BondingInfoResponse, ClaimsResponse, DelegatedResponse,
-    DistributedRewardsResponse, DistributionDataResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
-    ReceiveDelegationMsg, RewardsResponse, StakedResponse, TokenContractResponse,
-    TotalRewardsResponse, TotalStakedResponse, UndistributedRewardsResponse,
+    AdminMultisigResponse, AdminStatusResponse, AllDelegationsResponse, AllLabelsResponse,
+    AllStakedResponse, BondingInfoResponse, ClaimsFrozenResponse, ClaimsResponse, ConfigResponse,
+    DelegatedResponse, DelegationReportResponse, DistributedRewardsResponse,
+    DistributionDataResponse, DistributionLogResponse, DistributionWeightResponse, ExecuteMsg,
+    FirstStakedResponse, HooksResponse, InstantiateMsg, LabelResponse, LoyaltyBonusResponse,
+    MigrationProgressResponse, PayoutAddressResponse, PowerExclusionsResponse, QueryMsg,
+    RawKeyLayoutResponse, ReceiveDelegationMsg, ReceiveMsg, RewardsBreakdownResponse,
+    RewardsResponse, ScheduledRebondsResponse, StakedResponse, StakingLockResponse,
+    TokenContractResponse, TotalRewardsResponse, TotalStakedResponse, UndistributedRewardsResponse,
     WithdrawAdjustmentDataResponse, WithdrawableRewardsResponse,
 };
 
@@ -25,6 +30,7 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ReceiveDelegationMsg), &out_dir);
+    export_schema(&schema_for!(ReceiveMsg), &out_dir);
 
     export_schema(&schema_for!(AdminResponse), &out_dir);
     export_schema(&schema_for!(HooksResponse), &out_dir);
@@ -33,6 +39,24 @@ fn main() {
     export_schema(&schema_for!(AllStakedResponse), &out_dir);
     export_schema(&schema_for!(TotalStakedResponse), &out_dir);
     export_schema(&schema_for!(BondingInfoResponse), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(StakingLockResponse), &out_dir);
+    export_schema(&schema_for!(PowerExclusionsResponse), &out_dir);
+    export_schema(&schema_for!(MemberListResponse), &out_dir);
+    export_schema(&schema_for!(LoyaltyBonusResponse), &out_dir);
+    export_schema(&schema_for!(RewardsBreakdownResponse), &out_dir);
+    export_schema(&schema_for!(ClaimsFrozenResponse), &out_dir);
+    export_schema(&schema_for!(ScheduledRebondsResponse), &out_dir);
+    export_schema(&schema_for!(DistributionLogResponse), &out_dir);
+    export_schema(&schema_for!(LabelResponse), &out_dir);
+    export_schema(&schema_for!(AdminStatusResponse), &out_dir);
+    export_schema(&schema_for!(AllLabelsResponse), &out_dir);
+    export_schema(&schema_for!(DelegationReportResponse), &out_dir);
+    export_schema(&schema_for!(AllDelegationsResponse), &out_dir);
+    export_schema(&schema_for!(AdminMultisigResponse), &out_dir);
+    export_schema(&schema_for!(MigrationProgressResponse), &out_dir);
+    export_schema(&schema_for!(FirstStakedResponse), &out_dir);
+    export_schema(&schema_for!(RawKeyLayoutResponse), &out_dir);
 
     export_schema(&schema_for!(InfoResponse), &out_dir);
     export_schema(&schema_for!(TotalPowerAtHeightResponse), &out_dir);
@@ -43,6 +67,8 @@ fn main() {
 
     export_schema(&schema_for!(WithdrawableRewardsResponse), &out_dir);
     export_schema(&schema_for!(DelegatedResponse), &out_dir);
+    export_schema(&schema_for!(PayoutAddressResponse), &out_dir);
+    export_schema(&schema_for!(DistributionWeightResponse), &out_dir);
     export_schema_with_title(
         &schema_for!(UndistributedRewardsResponse),
         &out_dir,
@@ -1,33 +1,69 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_slice, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
+    from_slice, to_binary, Addr, Binary, BlockInfo, Decimal, Decimal256, Deps, DepsMut, Env, Event,
+    MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg, SubMsgResult,
+    Timestamp, Uint128, Uint256, WasmMsg,
 };
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::BTreeMap;
 
 use crate::distribution::{
-    apply_points_correction, execute_delegate_withdrawal, execute_distribute_rewards,
-    execute_withdraw_rewards, query_delegated, query_distributed_rewards,
-    query_undistributed_rewards, query_withdraw_adjustment_data, query_withdrawable_rewards,
+    apply_points_correction, execute_batch_distribute_rewards, execute_continue_reward_migration,
+    execute_delegate_withdrawal, execute_distribute_rewards, execute_migrate_reward_token,
+    execute_receive, execute_set_distribution_weight_override, execute_set_payout_address,
+    execute_start_reward_stream, execute_update_reward_decay, execute_withdraw_rewards,
+    execute_withdraw_rewards_batch, query_can_distribute, query_delegated, query_delegation_report,
+    query_distributed_rewards, query_distribution_log, query_distribution_weight,
+    query_payout_address, query_undistributed_rewards, query_withdraw_adjustment_data,
+    query_withdrawable_rewards, record_interaction, stream_rewards,
 };
 use cw2::{get_contract_version, set_contract_version};
+use cw20_vesting::msg::{UndelegatableAmountResponse, VestingResponse};
 use cw20_vesting::{Cw20ReceiveDelegationMsg, ExecuteMsg as VestingExecuteMsg};
+use cw4::{Member, MemberListResponse};
+use cw_controllers::HookError;
 use cw_core_interface::voting::{
     InfoResponse, TotalPowerAtHeightResponse, VotingPowerAtHeightResponse,
 };
+use cw_storage_plus::{Bound, KeyDeserialize, PrefixBound};
 use cw_utils::{ensure_from_older_version, maybe_addr, Expiration};
+use wynd_utils::ScalableCurve;
 
 use crate::error::ContractError;
 use crate::hook::{MemberChangedHookMsg, MemberDiff};
 use crate::msg::{
-    AllStakedResponse, BondingInfoResponse, BondingPeriodInfo, ExecuteMsg, InstantiateMsg,
-    MigrateMsg, QueryMsg, ReceiveDelegationMsg, RewardsResponse, StakedResponse,
-    TotalRewardsResponse, TotalStakedResponse, TotalUnbondingResponse,
+    AddressLabel, AdminAction, AdminMultisigResponse, AdminStatusResponse, AllDelegationsResponse,
+    AllLabelsResponse, AllStakedResponse, BondingInfoResponse, BondingPeriodInfo,
+    ClaimsFrozenResponse, ConfigResponse, DelegationInfo, DistributionFeeInfo, ExecuteMsg,
+    FirstStakedResponse, GovernanceContractsResponse, HookConfigResponse, HooksResponse,
+    InstantiateMsg, LabelResponse, LoyaltyBonusResponse, MatureClaim, MatureClaimsResponse,
+    MigrateMsg, MigrationProgressResponse, OptimizeFor, PeriodStaked, PowerExclusionsResponse,
+    QueryMsg, RawKeyLayoutResponse, ReceiveDelegationMsg, RecomputeStatusResponse,
+    RewardsBreakdownEntry, RewardsBreakdownResponse, RewardsResponse, ScheduledRebond,
+    ScheduledRebondsResponse, SlashDestination, StakeConfig, StakedResponse, StakingLockResponse,
+    TotalRewardsResponse, TotalStakedResponse, TotalUnbondingResponse, UnbondRequest,
 };
 use crate::state::{
-    Config, Distribution, TokenInfo, ADMIN, CLAIMS, CONFIG, DISTRIBUTION, HOOKS, MEMBERS, REWARDS,
-    STAKE, STAKE_CONFIG, TOTAL_REWARDS, TOTAL_STAKED, TOTAL_VOTES,
+    cw4_weight, index_claim, loyalty_epoch, loyalty_epochs, migrate_member_power,
+    release_voting_discounted, save_member_power, save_total_votes, save_voting_discounted,
+    staking_locked_until, take_claim_delegators, take_exit_vesting, total_voting_discounted,
+    unindex_claim, voting_discounted, AdminProposal, BondingInfo, Config, Distribution,
+    DistributionFee, ExitVesting, HookConfig, MigrationProgress, RecomputeProgress,
+    StakeMultipliers, TokenInfo, ADMIN, ADMIN_MULTISIG, ADMIN_PROPOSALS, ADMIN_RENOUNCED, CLAIMS,
+    CLAIMS_BY_ADDR, CLAIMS_BY_MATURITY, CLAIMS_FROZEN, CLAIM_DELEGATORS, CONFIG, CW4_MEMBERS,
+    CW4_TOTAL, DEFAULT_MAX_HOOKS, DISTRIBUTION, DISTRIBUTION_WEIGHT, EXIT_VESTING, FIRST_STAKED,
+    GOVERNANCE_CONTRACTS, HOOKS, HOOK_REPLY_IDS, LABELS, LAST_ADMIN_PROPOSAL_ID,
+    LAST_HOOK_REPLY_ID, LAST_VOTE_HEIGHT, LEGACY_DISTRIBUTION, LEGACY_HOOKS, LEGACY_MEMBERS,
+    LEGACY_TOTAL_VOTES, LOYALTY_EPOCHS, MAX_DISTRIBUTION_FEE_BPS, MAX_LABEL_LENGTH,
+    MAX_SCHEDULED_REBONDS, MAX_STAKE_MULTIPLIER, MEMBERS, MEMBERS_PRIMARY, MIGRATION_PROGRESS,
+    MIN_APPROVALS, PENDING_RECOMPUTE, POWER_EXCLUDED, REWARDS, REWARD_MIGRATION, REWARD_STREAM,
+    SCHEDULED_REBONDS, STAKE, STAKE_CONFIG, STAKING_LOCKED, STORAGE_VERSION,
+    STORAGE_VERSION_MARKER, TOTAL_REWARDS, TOTAL_STAKED, TOTAL_VOTES, TOTAL_VOTES_PRIMARY,
+    VOTES_CAST, VOTING_DISCOUNTED,
 };
+use crate::state_keys::storage_layout;
 
 // version info for migration info
 const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_CRATE_NAME"));
@@ -49,11 +85,15 @@ pub fn instantiate(
     // min_bond is at least 1, so 0 stake -> non-membership
     let min_bond = std::cmp::max(msg.min_bond, Uint128::new(1));
 
-    TOTAL_VOTES.save(deps.storage, &Uint128::zero(), env.block.height)?;
+    save_total_votes(deps.storage, Uint128::zero(), env.block.height)?;
     TOTAL_STAKED.save(deps.storage, &TokenInfo::default())?;
 
+    let stake_config = validate_stake_config(msg.stake_config)?;
+    if matches!(msg.vesting_voting_discount, Some(discount) if discount > Decimal::one()) {
+        return Err(ContractError::InvalidVotingDiscount {});
+    }
     let mut unbonding_periods = vec![];
-    for stake_config in msg.stake_config {
+    for stake_config in stake_config {
         unbonding_periods.push(stake_config.unbonding_period);
         STAKE_CONFIG.save(
             deps.storage,
@@ -67,14 +107,72 @@ pub fn instantiate(
         tokens_per_power: msg.tokens_per_power,
         min_bond,
         unbonding_periods,
+        reward_token: None,
+        max_hooks: msg.max_hooks.unwrap_or(DEFAULT_MAX_HOOKS),
+        distribution_fee: validate_distribution_fee(deps.as_ref(), msg.distribution_fee)?,
+        reward_decay: None,
+        loyalty_bonus_pct: 0,
+        max_loyalty_bonus_pct: 0,
+        slash_destination: SlashDestination::default(),
+        vesting_voting_discount: msg.vesting_voting_discount,
     };
     CONFIG.save(deps.storage, &config)?;
 
     DISTRIBUTION.save(deps.storage, &Distribution::default())?;
+    STORAGE_VERSION_MARKER.save(deps.storage, &STORAGE_VERSION)?;
 
     Ok(Response::default())
 }
 
+/// Validates the given unbonding periods and returns them sorted by period, ascending
+fn validate_stake_config(
+    mut stake_config: Vec<StakeConfig>,
+) -> Result<Vec<StakeConfig>, ContractError> {
+    if stake_config.is_empty() {
+        return Err(ContractError::NoUnbondingPeriods {});
+    }
+    stake_config.sort_by_key(|config| config.unbonding_period);
+
+    let max = Decimal::from_ratio(MAX_STAKE_MULTIPLIER, 1u128);
+    for (i, config) in stake_config.iter().enumerate() {
+        let unbonding_period = config.unbonding_period;
+        if unbonding_period == 0 {
+            return Err(ContractError::ZeroUnbondingPeriod {});
+        }
+        if i > 0 && stake_config[i - 1].unbonding_period == unbonding_period {
+            return Err(ContractError::DuplicateUnbondingPeriod { unbonding_period });
+        }
+        let multipliers = [config.voting_multiplier, config.reward_multiplier];
+        if multipliers.iter().any(Decimal::is_zero) {
+            return Err(ContractError::ZeroMultiplier { unbonding_period });
+        }
+        if multipliers.iter().any(|m| *m > max) {
+            return Err(ContractError::MultiplierTooHigh {
+                unbonding_period,
+                max,
+            });
+        }
+        if let Some(exit_vesting) = &config.exit_vesting {
+            validate_exit_vesting(exit_vesting, unbonding_period)?;
+        }
+    }
+    Ok(stake_config)
+}
+
+/// Ensures the exit vesting curve releases all claimed tokens eventually
+fn validate_exit_vesting(
+    exit_vesting: &ScalableCurve,
+    unbonding_period: u64,
+) -> Result<(), ContractError> {
+    let amount = Uint128::new(1_000_000_000);
+    let curve = exit_vesting.clone().scale(amount);
+    let (low, high) = curve.range();
+    if curve.validate_monotonic_decreasing().is_err() || low != 0 || high > amount.u128() {
+        return Err(ContractError::InvalidExitVesting { unbonding_period });
+    }
+    Ok(())
+}
+
 // And declare a custom Error variant for the ones where you will want to make use of it
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
@@ -84,732 +182,3443 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     let api = deps.api;
+    // stakers' data is incomplete until a staged migration is finished
+    if MIGRATION_PROGRESS.may_load(deps.storage)?.is_some()
+        && !matches!(msg, ExecuteMsg::ContinueMigration { .. })
+    {
+        return Err(ContractError::MigrationInProgress {});
+    }
+    // unclaimed rewards are only partly converted until a reward token migration is finished
+    if REWARD_MIGRATION.may_load(deps.storage)?.is_some()
+        && !matches!(msg, ExecuteMsg::ContinueRewardMigration { .. })
+    {
+        return Err(ContractError::RewardMigrationInProgress {});
+    }
+    // release streamed rewards before anything can change the reward power
+    stream_rewards(deps.storage, &env)?;
     match msg {
         ExecuteMsg::UpdateAdmin { admin } => {
+            assert_not_renounced(deps.storage)?;
             Ok(ADMIN.execute_update_admin(deps, info, maybe_addr(api, admin)?)?)
         }
-        ExecuteMsg::AddHook { addr } => {
-            Ok(HOOKS.execute_add_hook(&ADMIN, deps, info, api.addr_validate(&addr)?)?)
-        }
-        ExecuteMsg::RemoveHook { addr } => {
-            Ok(HOOKS.execute_remove_hook(&ADMIN, deps, info, api.addr_validate(&addr)?)?)
-        }
+        ExecuteMsg::RenounceAdmin {} => execute_renounce_admin(deps, env, info),
+        ExecuteMsg::AddHook { addr, gas_limit } => execute_add_hook(deps, info, addr, gas_limit),
+        ExecuteMsg::RemoveHook { addr } => execute_remove_hook(deps, info, addr),
         ExecuteMsg::Rebond {
             tokens,
             bond_from,
             bond_to,
-        } => execute_rebond(deps, env, info, tokens, bond_from, bond_to),
+            claim_first,
+        } => execute_rebond(deps, env, info, tokens, bond_from, bond_to, claim_first),
+        ExecuteMsg::RebondToOptimal {
+            bond_from,
+            amount,
+            optimize_for,
+        } => execute_rebond_to_optimal(deps, env, info, amount, bond_from, optimize_for),
+        ExecuteMsg::ScheduleRebond {
+            tokens,
+            bond_from,
+            bond_to,
+            execute_after,
+        } => execute_schedule_rebond(
+            deps,
+            info,
+            ScheduledRebond {
+                tokens,
+                bond_from,
+                bond_to,
+                execute_after,
+            },
+        ),
+        ExecuteMsg::ExecuteScheduledRebonds { address } => {
+            execute_scheduled_rebonds(deps, env, info, address)
+        }
         ExecuteMsg::Unbond {
             tokens: amount,
             unbonding_period,
-        } => execute_unbond(deps, env, info, amount, unbonding_period),
+            claim_recipient,
+        } => execute_unbond(deps, env, info, amount, unbonding_period, claim_recipient),
+        ExecuteMsg::BatchUnbond { unbonds } => execute_batch_unbond(deps, env, info, unbonds),
         ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::ClaimSpecific { expires_at } => {
+            execute_claim_specific(deps, env, info, expires_at)
+        }
+        ExecuteMsg::CancelClaim {
+            amount,
+            unbonding_period,
+        } => execute_cancel_claim(deps, env, info, amount, unbonding_period),
         ExecuteMsg::ReceiveDelegation(msg) => execute_receive_delegation(deps, env, info, msg),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
         ExecuteMsg::DistributeRewards { sender } => {
             execute_distribute_rewards(deps, env, info, sender)
         }
-        ExecuteMsg::WithdrawRewards { owner, receiver } => {
-            execute_withdraw_rewards(deps, info, owner, receiver)
+        ExecuteMsg::BatchDistributeRewards { amounts } => {
+            execute_batch_distribute_rewards(deps, env, info, amounts)
         }
+        ExecuteMsg::WithdrawRewards {
+            owner,
+            receiver,
+            receiver_msg,
+        } => execute_withdraw_rewards(deps, env, info, owner, receiver, receiver_msg),
+        ExecuteMsg::WithdrawRewardsBatch {
+            owners,
+            receiver_mode,
+        } => execute_withdraw_rewards_batch(deps, env, info, owners, receiver_mode),
         ExecuteMsg::DelegateWithdrawal { delegated } => {
             execute_delegate_withdrawal(deps, info, delegated)
         }
+        ExecuteMsg::SetPayoutAddress { payout } => execute_set_payout_address(deps, info, payout),
+        ExecuteMsg::SetDistributionWeight { address, weight } => {
+            execute_set_distribution_weight_override(deps, info, address, weight)
+        }
+        ExecuteMsg::MigrateRewardToken {
+            new_token,
+            exchange_rate,
+        } => execute_migrate_reward_token(deps, env, info, new_token, exchange_rate),
+        ExecuteMsg::StartRewardStream {
+            total,
+            duration_blocks,
+        } => execute_start_reward_stream(deps, env, info, total, duration_blocks),
+        ExecuteMsg::UpdatePowerConfig {
+            tokens_per_power,
+            min_bond,
+        } => execute_update_power_config(deps, info, tokens_per_power, min_bond),
+        ExecuteMsg::UpdateDistributionFee { distribution_fee } => {
+            execute_update_distribution_fee(deps, info, distribution_fee)
+        }
+        ExecuteMsg::UpdateRewardDecay { reward_decay } => {
+            execute_update_reward_decay(deps, env, info, reward_decay)
+        }
+        ExecuteMsg::UpdateLoyaltyBonus { bonus_pct, max_pct } => {
+            execute_update_loyalty_bonus(deps, info, bonus_pct, max_pct)
+        }
+        ExecuteMsg::RecomputeMembers { start_after, limit } => {
+            execute_recompute_members(deps, env, start_after, limit)
+        }
+        ExecuteMsg::ContinueMigration { limit } => execute_continue_migration(deps, env, limit),
+        ExecuteMsg::ContinueRewardMigration { start_after, limit } => {
+            execute_continue_reward_migration(deps, env, start_after, limit)
+        }
+        ExecuteMsg::RegisterGovernanceContract { address } => {
+            execute_register_governance_contract(deps, info, address)
+        }
+        ExecuteMsg::UnregisterGovernanceContract { address } => {
+            execute_unregister_governance_contract(deps, info, address)
+        }
+        ExecuteMsg::RecordVote { voter } => execute_record_vote(deps, env, info, voter),
+        ExecuteMsg::LockNewStaking { expires_at_height } => {
+            execute_lock_staking(deps, info, expires_at_height)
+        }
+        ExecuteMsg::ExcludeFromPower { address } => execute_exclude_from_power(deps, info, address),
+        ExecuteMsg::IncludeInPower { address } => execute_include_in_power(deps, info, address),
+        ExecuteMsg::FreezeClaims { address } => execute_freeze_claims(deps, info, address, true),
+        ExecuteMsg::UnfreezeClaims { address } => execute_freeze_claims(deps, info, address, false),
+        ExecuteMsg::SetLabel { address, label } => {
+            execute_set_label(deps, info, address, Some(label))
+        }
+        ExecuteMsg::RemoveLabel { address } => execute_set_label(deps, info, address, None),
+        ExecuteMsg::RecomputeVotingPower { address } => {
+            execute_recompute_voting_power(deps, env, info, address)
+        }
+        ExecuteMsg::RefreshVotingDiscount { address } => {
+            execute_refresh_voting_discount(deps, env, info, address)
+        }
+        ExecuteMsg::SlashMember {
+            address,
+            portion,
+            unbonding_period,
+        } => execute_slash_member(deps, env, info, address, portion, unbonding_period),
+        ExecuteMsg::UpdateSlashDestination { destination } => {
+            execute_update_slash_destination(deps, info, destination)
+        }
+        ExecuteMsg::SetAdminMultisig {
+            addresses,
+            min_approvals,
+        } => execute_set_admin_multisig(deps, env, info, addresses, min_approvals),
+        ExecuteMsg::ProposeAdminAction { action, expires_at } => {
+            execute_propose_admin_action(deps, env, info, action, expires_at)
+        }
+        ExecuteMsg::ApproveAdminAction { proposal_id } => {
+            execute_approve_admin_action(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::ExecuteAdminAction { proposal_id } => {
+            execute_execute_admin_action(deps, env, info, proposal_id)
+        }
     }
 }
 
-pub fn execute_rebond(
-    deps: DepsMut,
+/// Ensures the admin was not renounced
+fn assert_not_renounced(storage: &dyn Storage) -> Result<(), ContractError> {
+    if ADMIN_RENOUNCED.may_load(storage)?.is_some() {
+        return Err(ContractError::AdminRenounced {});
+    }
+    Ok(())
+}
+
+/// Ensures `sender` is the admin, failing with `AdminRenounced` once there can be none anymore
+pub(crate) fn assert_admin(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    assert_not_renounced(deps.storage)?;
+    ADMIN.assert_admin(deps, sender)?;
+    Ok(())
+}
+
+pub fn execute_renounce_admin(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    amount: Uint128,
-    bond_from: u64,
-    bond_to: u64,
 ) -> Result<Response, ContractError> {
-    let cfg = CONFIG.load(deps.storage)?;
+    assert_admin(deps.as_ref(), &info.sender)?;
+    ADMIN.set(deps.branch(), None)?;
+    ADMIN_RENOUNCED.save(deps.storage, &env.block.time)?;
 
-    // Raise if no amount was provided
-    if amount == Uint128::zero() {
-        return Err(ContractError::NoRebondAmount {});
-    }
-    // Short out with an error if trying to rebond to itself
-    if bond_from == bond_to {
-        return Err(ContractError::SameUnbondingRebond {});
-    }
+    Ok(Response::new()
+        .add_attribute("action", "renounce_admin")
+        .add_attribute("sender", info.sender))
+}
 
-    // Validate both bond_from and bond_to are valid and get their relevant voting multiplier
-    // Validate bond_from is a valid time period
-    let bond_from_staking_multipliers =
-        STAKE_CONFIG.update::<_, ContractError>(deps.storage, bond_from, |multipliers| {
-            let mut multipliers =
-                multipliers.ok_or(ContractError::NoUnbondingPeriodFound(bond_from))?;
-            multipliers.staked = multipliers.staked.checked_sub(amount)?;
-            Ok(multipliers)
-        })?;
+pub fn execute_register_governance_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
 
-    // Validate bond_to is a valid time period for Unbonding
-    let bond_to_staking_multipliers =
-        STAKE_CONFIG.update::<_, ContractError>(deps.storage, bond_to, |multipliers| {
-            let mut multipliers =
-                multipliers.ok_or(ContractError::NoUnbondingPeriodFound(bond_to))?;
-            multipliers.staked += amount;
-            Ok(multipliers)
-        })?;
+    let mut contracts = GOVERNANCE_CONTRACTS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if contracts.contains(&addr) {
+        return Err(ContractError::GovernanceContractAlreadyRegistered { address });
+    }
+    contracts.push(addr);
+    GOVERNANCE_CONTRACTS.save(deps.storage, &contracts)?;
 
-    // update the sender's stake
-    let mut old_votes_from = Uint128::zero();
-    let mut old_votes_to = Uint128::zero();
+    Ok(Response::new()
+        .add_attribute("action", "register_governance_contract")
+        .add_attribute("address", address)
+        .add_attribute("sender", info.sender))
+}
 
-    let mut old_rewards_from = Uint128::zero();
-    let mut old_rewards_to = Uint128::zero();
+pub fn execute_unregister_governance_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
 
-    // Reduce the bond_from
-    let bond_from_stake_change = STAKE.update(
-        deps.storage,
-        (&info.sender, bond_from),
-        |bonding_info| -> StdResult<_> {
-            let mut bonding_info = bonding_info.unwrap_or_default();
-            // Release the stake, also accounting for locked tokens, raising if there is not enough tokens
-            bonding_info.release_stake(&env, amount)?;
-            let stake = bonding_info.total_stake();
-            let votes = calc_power(&cfg, stake, bond_from_staking_multipliers.voting);
-            let rewards = calc_power(&cfg, stake, bond_from_staking_multipliers.reward);
+    let mut contracts = GOVERNANCE_CONTRACTS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let pos = contracts
+        .iter()
+        .position(|contract| contract == &addr)
+        .ok_or_else(|| ContractError::GovernanceContractNotRegistered {
+            address: address.clone(),
+        })?;
+    contracts.remove(pos);
+    GOVERNANCE_CONTRACTS.save(deps.storage, &contracts)?;
 
-            old_votes_from = bonding_info.votes;
-            old_rewards_from = bonding_info.rewards;
-            bonding_info.votes = votes;
-            bonding_info.rewards = rewards;
-            Ok(bonding_info)
-        },
-    )?;
+    Ok(Response::new()
+        .add_attribute("action", "unregister_governance_contract")
+        .add_attribute("address", address)
+        .add_attribute("sender", info.sender))
+}
 
-    // Increase the bond_to
-    let bond_to_stake_change = STAKE.update(
-        deps.storage,
-        (&info.sender, bond_to),
-        |bonding_info| -> StdResult<_> {
-            let mut bonding_info = bonding_info.unwrap_or_default();
-            if bond_from > bond_to {
-                bonding_info
-                    .add_locked_tokens(env.block.time.plus_seconds(bond_from - bond_to), amount);
-            } else {
-                bonding_info.add_unlocked_tokens(amount);
-            };
-            let stake = bonding_info.total_stake();
-            let voting_power = calc_power(&cfg, stake, bond_to_staking_multipliers.voting);
-            let rewards = calc_power(&cfg, stake, bond_to_staking_multipliers.reward);
+pub fn execute_record_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    voter: String,
+) -> Result<Response, ContractError> {
+    let contracts = GOVERNANCE_CONTRACTS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if !contracts.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
 
-            old_votes_to = bonding_info.votes;
-            old_rewards_to = bonding_info.rewards;
-            bonding_info.votes = voting_power;
-            bonding_info.rewards = rewards;
-            Ok(bonding_info)
-        },
-    )?;
-    let bond_update_messages = update_membership(
-        deps.storage,
-        info.sender.clone(),
-        &[old_votes_to, old_votes_from],
-        &[bond_to_stake_change.votes, bond_from_stake_change.votes],
-        env.block.height,
-    )?;
-    update_rewards(
-        deps.storage,
-        info.sender,
-        &[old_rewards_to, old_rewards_from],
-        &[bond_to_stake_change.rewards, bond_from_stake_change.rewards],
-    )?;
+    let voter = deps.api.addr_validate(&voter)?;
+    let votes_cast = VOTES_CAST
+        .may_load(deps.storage, &voter)?
+        .unwrap_or_default()
+        + 1;
+    VOTES_CAST.save(deps.storage, &voter, &votes_cast)?;
+    LAST_VOTE_HEIGHT.save(deps.storage, &voter, &env.block.height)?;
 
     Ok(Response::new()
-        .add_submessages(bond_update_messages)
-        .add_attribute("action", "rebond")
-        .add_attribute("amount", amount)
-        .add_attribute("bond_from", bond_from.to_string())
-        .add_attribute("bond_to", bond_to.to_string()))
+        .add_attribute("action", "record_vote")
+        .add_attribute("voter", voter)
+        .add_attribute("votes_cast", votes_cast.to_string())
+        .add_attribute("sender", info.sender))
 }
 
-pub fn execute_bond(
+pub fn execute_exclude_from_power(
     deps: DepsMut,
-    env: Env,
-    sender_cw20_contract: Addr,
-    amount: Uint128,
-    unbonding_period: u64,
-    sender: Addr,
+    info: MessageInfo,
+    address: String,
 ) -> Result<Response, ContractError> {
-    let cfg = CONFIG.load(deps.storage)?;
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
 
-    // ensure that cw20 token contract's addresses matches
-    if cfg.cw20_contract != sender_cw20_contract {
-        return Err(ContractError::Cw20AddressesNotMatch {
-            got: sender_cw20_contract.into(),
-            expected: cfg.cw20_contract.into(),
-        });
+    if POWER_EXCLUDED.has(deps.storage, &addr) {
+        return Err(ContractError::AlreadyExcludedFromPower { address });
     }
+    POWER_EXCLUDED.save(deps.storage, &addr, &())?;
 
-    // load staking_multipliers to calculate votes and rewards
-    let staking_multipliers =
-        STAKE_CONFIG.update::<_, ContractError>(deps.storage, unbonding_period, |multipliers| {
-            let mut multipliers =
-                multipliers.ok_or(ContractError::NoUnbondingPeriodFound(unbonding_period))?;
-            multipliers.staked += amount;
-            Ok(multipliers)
-        })?;
+    Ok(Response::new()
+        .add_attribute("action", "exclude_from_power")
+        .add_attribute("address", address)
+        .add_attribute("sender", info.sender))
+}
 
-    // update the sender's stake
-    let mut old_votes = Uint128::zero();
-    let mut old_rewards = Uint128::zero();
-    let new_stake = STAKE.update(
-        deps.storage,
-        (&sender, unbonding_period),
-        |bonding_info| -> StdResult<_> {
-            let mut bonding_info = bonding_info.unwrap_or_default();
-            // Release the stake, also accounting for locked tokens, raising if there is not enough tokens
+pub fn execute_include_in_power(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
 
-            bonding_info.add_unlocked_tokens(amount);
-            let new_stake = bonding_info.total_stake();
-            let voting_power = calc_power(&cfg, new_stake, staking_multipliers.voting);
-            let rewards = calc_power(&cfg, new_stake, staking_multipliers.reward);
-            old_votes = bonding_info.votes;
-            old_rewards = bonding_info.rewards;
-            bonding_info.votes = voting_power;
-            bonding_info.rewards = rewards;
-            Ok(bonding_info)
-        },
-    )?;
+    if !POWER_EXCLUDED.has(deps.storage, &addr) {
+        return Err(ContractError::NotExcludedFromPower { address });
+    }
+    POWER_EXCLUDED.remove(deps.storage, &addr);
 
-    let messages = update_membership(
-        deps.storage,
-        sender.clone(),
-        &[old_votes],
-        &[new_stake.votes],
-        env.block.height,
-    )?;
-    update_rewards(
-        deps.storage,
-        sender.clone(),
-        &[old_rewards],
-        &[new_stake.rewards],
-    )?;
+    Ok(Response::new()
+        .add_attribute("action", "include_in_power")
+        .add_attribute("address", address)
+        .add_attribute("sender", info.sender))
+}
 
-    TOTAL_STAKED.update::<_, StdError>(deps.storage, |token_info| {
-        Ok(TokenInfo {
-            staked: token_info.staked + amount,
-            unbonding: token_info.unbonding,
-        })
-    })?;
+pub fn execute_freeze_claims(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+
+    let action = if frozen {
+        CLAIMS_FROZEN.save(deps.storage, &addr, &())?;
+        "freeze_claims"
+    } else {
+        CLAIMS_FROZEN.remove(deps.storage, &addr);
+        "unfreeze_claims"
+    };
 
+    // an event on its own, so that the changes can be audited independently of the message
+    let event = Event::new(action)
+        .add_attribute("address", &address)
+        .add_attribute("sender", &info.sender);
     Ok(Response::new()
-        .add_submessages(messages)
-        .add_attribute("action", "bond")
-        .add_attribute("amount", amount)
-        .add_attribute("sender", sender))
+        .add_event(event)
+        .add_attribute("action", action)
+        .add_attribute("address", address)
+        .add_attribute("sender", info.sender))
 }
 
-pub fn execute_receive_delegation(
+pub fn execute_set_label(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    wrapper: Cw20ReceiveDelegationMsg,
+    address: String,
+    label: Option<String>,
 ) -> Result<Response, ContractError> {
-    // info.sender is the address of the cw20 contract (that re-sent this message).
-    // wrapper.sender is the address of the user that requested the cw20 contract to send this.
-    // This cannot be fully trusted (the cw20 contract can fake it), so only use it for actions
-    // in the address's favor (like paying/bonding tokens, not withdrawls)
-    let msg: ReceiveDelegationMsg = from_slice(&wrapper.msg)?;
-    let api = deps.api;
-    match msg {
-        ReceiveDelegationMsg::Delegate { unbonding_period } => execute_bond(
-            deps,
-            env,
-            info.sender,
-            wrapper.amount,
-            unbonding_period,
-            api.addr_validate(&wrapper.sender)?,
-        ),
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+
+    let mut res = Response::new();
+    match label {
+        Some(label) => {
+            if label.is_empty() || label.chars().count() > MAX_LABEL_LENGTH {
+                return Err(ContractError::InvalidLabel {
+                    max: MAX_LABEL_LENGTH,
+                });
+            }
+            LABELS.save(deps.storage, &addr, &label)?;
+            res = res
+                .add_attribute("action", "set_label")
+                .add_attribute("label", label);
+        }
+        None => {
+            LABELS.remove(deps.storage, &addr);
+            res = res.add_attribute("action", "remove_label");
+        }
     }
+    Ok(res
+        .add_attribute("address", address)
+        .add_attribute("sender", info.sender))
 }
 
-pub fn execute_unbond(
+fn assert_claims_not_frozen(storage: &dyn Storage, addr: &Addr) -> Result<(), ContractError> {
+    if CLAIMS_FROZEN.has(storage, addr) {
+        return Err(ContractError::ClaimsFrozen {});
+    }
+    Ok(())
+}
+
+pub fn execute_lock_staking(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    amount: Uint128,
-    unbonding_period: u64,
+    expires_at_height: u64,
 ) -> Result<Response, ContractError> {
-    let cfg = CONFIG.load(deps.storage)?;
+    assert_admin(deps.as_ref(), &info.sender)?;
 
-    // load voting and reward multiplier to calculate votes and rewards
-    // also update the amount staked here
-    let staking_multipliers =
-        STAKE_CONFIG.update::<_, ContractError>(deps.storage, unbonding_period, |multipliers| {
-            let mut multipliers =
-                multipliers.ok_or(ContractError::NoUnbondingPeriodFound(unbonding_period))?;
-            multipliers.staked = multipliers.staked.checked_sub(amount)?;
-            Ok(multipliers)
-        })?;
+    STAKING_LOCKED.save(deps.storage, &Some(expires_at_height))?;
 
-    // reduce the sender's stake - aborting if insufficient
-    let mut old_votes = Uint128::zero();
-    let mut old_rewards = Uint128::zero();
-    let new_stake = STAKE.update(
-        deps.storage,
-        (&info.sender, unbonding_period),
-        |bonding_info| -> StdResult<_> {
-            let mut bonding_info = bonding_info.unwrap_or_default();
+    Ok(Response::new()
+        .add_attribute("action", "lock_new_staking")
+        .add_attribute("expires_at_height", expires_at_height.to_string())
+        .add_attribute("sender", info.sender))
+}
 
-            bonding_info.release_stake(&env, amount)?;
-            let new_stake = bonding_info.total_stake();
-            let voting_power = calc_power(&cfg, new_stake, staking_multipliers.voting);
-            let rewards = calc_power(&cfg, new_stake, staking_multipliers.reward);
-            old_votes = bonding_info.votes;
-            old_rewards = bonding_info.rewards;
+pub fn execute_set_admin_multisig(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addresses: Vec<String>,
+    min_approvals: u32,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let mut members = addresses
+        .iter()
+        .map(|addr| deps.api.addr_validate(addr))
+        .collect::<StdResult<Vec<_>>>()?;
+    members.sort();
+    members.dedup();
+    if members.len() != addresses.len()
+        || min_approvals == 0
+        || min_approvals as usize > members.len()
+    {
+        return Err(ContractError::InvalidAdminMultisig {});
+    }
+    ADMIN_MULTISIG.save(deps.storage, &members)?;
+    MIN_APPROVALS.save(deps.storage, &min_approvals)?;
+    // the multisig acts as admin through this contract
+    ADMIN.set(deps.branch(), Some(env.contract.address))?;
 
-            bonding_info.votes = voting_power;
-            bonding_info.rewards = rewards;
-            Ok(bonding_info)
-        },
-    )?;
+    Ok(Response::new()
+        .add_attribute("action", "set_admin_multisig")
+        .add_attribute("members", members.len().to_string())
+        .add_attribute("min_approvals", min_approvals.to_string())
+        .add_attribute("sender", info.sender))
+}
 
-    // provide them a claim
-    CLAIMS.create_claim(
-        deps.storage,
-        &info.sender,
-        amount,
-        Expiration::AtTime(env.block.time.plus_seconds(unbonding_period)),
-    )?;
+/// Ensures `sender` is a member of the admin multisig
+fn assert_multisig_member(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    let members = ADMIN_MULTISIG
+        .may_load(storage)?
+        .ok_or(ContractError::NoAdminMultisig {})?;
+    if !members.contains(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
 
-    let messages = update_membership(
-        deps.storage,
-        info.sender.clone(),
-        &[old_votes],
-        &[new_stake.votes],
-        env.block.height,
-    )?;
-    update_rewards(
-        deps.storage,
-        info.sender.clone(),
-        &[old_rewards],
-        &[new_stake.rewards],
-    )?;
+/// Loads a proposal that can still be approved and executed
+fn load_open_proposal(
+    storage: &dyn Storage,
+    env: &Env,
+    proposal_id: u64,
+) -> Result<AdminProposal, ContractError> {
+    let proposal = ADMIN_PROPOSALS
+        .may_load(storage, proposal_id)?
+        .ok_or(ContractError::AdminProposalNotFound { proposal_id })?;
+    if proposal.executed {
+        return Err(ContractError::AdminProposalExecuted { proposal_id });
+    }
+    if proposal.expires_at <= env.block.time.seconds() {
+        return Err(ContractError::AdminProposalExpired { proposal_id });
+    }
+    Ok(proposal)
+}
 
-    TOTAL_STAKED.update::<_, StdError>(deps.storage, |token_info| {
-        Ok(TokenInfo {
-            staked: token_info.staked.saturating_sub(amount),
-            unbonding: token_info.unbonding + amount,
-        })
-    })?;
+pub fn execute_propose_admin_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: AdminAction,
+    expires_at: u64,
+) -> Result<Response, ContractError> {
+    assert_multisig_member(deps.storage, &info.sender)?;
+    if expires_at <= env.block.time.seconds() {
+        return Err(ContractError::InvalidProposalExpiration {});
+    }
+
+    let proposal_id = LAST_ADMIN_PROPOSAL_ID
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    LAST_ADMIN_PROPOSAL_ID.save(deps.storage, &proposal_id)?;
+    let proposal = AdminProposal {
+        action,
+        proposer: info.sender.clone(),
+        expires_at,
+        approvals: vec![info.sender.clone()],
+        executed: false,
+    };
+    ADMIN_PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
 
     Ok(Response::new()
-        .add_submessages(messages)
-        .add_attribute("action", "unbond")
-        .add_attribute("amount", amount)
+        .add_attribute("action", "propose_admin_action")
+        .add_attribute("proposal_id", proposal_id.to_string())
         .add_attribute("sender", info.sender))
 }
 
-fn update_membership(
-    storage: &mut dyn Storage,
-    sender: Addr,
-    old_votes: &[Uint128],
-    new_votes: &[Uint128],
-    height: u64,
-) -> StdResult<Vec<SubMsg>> {
-    let old_voting_power: Uint128 = old_votes.iter().sum();
-    let new_voting_power: Uint128 = new_votes.iter().sum();
+pub fn execute_approve_admin_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    assert_multisig_member(deps.storage, &info.sender)?;
+    let mut proposal = load_open_proposal(deps.storage, &env, proposal_id)?;
+    if proposal.approvals.contains(&info.sender) {
+        return Err(ContractError::AlreadyApproved { proposal_id });
+    }
+    proposal.approvals.push(info.sender.clone());
+    ADMIN_PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
 
-    // short-circuit if no change
-    if new_voting_power == old_voting_power {
-        return Ok(vec![]);
+    Ok(Response::new()
+        .add_attribute("action", "approve_admin_action")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("approvals", proposal.approvals.len().to_string())
+        .add_attribute("sender", info.sender))
+}
+
+pub fn execute_execute_admin_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    assert_multisig_member(deps.storage, &info.sender)?;
+    let mut proposal = load_open_proposal(deps.storage, &env, proposal_id)?;
+    if !proposal.approvals.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
     }
 
-    // otherwise, record change of power
-    let old_total_power = MEMBERS.may_load(storage, &sender)?;
-    let new_total_power = old_total_power.unwrap_or_default() + new_voting_power - old_voting_power;
+    // approvals of addresses removed from the multisig in the meantime don't count
+    let members = ADMIN_MULTISIG.load(deps.storage)?;
+    let approvals = proposal
+        .approvals
+        .iter()
+        .filter(|addr| members.contains(addr))
+        .count() as u32;
+    let required = MIN_APPROVALS.load(deps.storage)?;
+    if approvals < required {
+        return Err(ContractError::NotEnoughApprovals {
+            approvals,
+            required,
+        });
+    }
+    proposal.executed = true;
+    ADMIN_PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
 
-    let new_hook = if new_total_power.is_zero() {
-        MEMBERS.remove(storage, &sender, height)?;
-        None
-    } else {
-        MEMBERS.save(storage, &sender, &new_total_power, height)?;
-        Some(new_total_power)
+    // the contract itself is the admin while the multisig is set
+    let admin_info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
     };
+    let res = execute(deps, env, admin_info, proposal.action.into())?;
+    Ok(res
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("executor", info.sender))
+}
 
-    // update total
-    TOTAL_VOTES.update(storage, height, |total| -> StdResult<_> {
-        Ok(total.unwrap_or_default() + new_voting_power - old_voting_power)
-    })?;
-
-    // alert the hooks
-    let diff = MemberDiff::new(sender, old_total_power, new_hook);
-    HOOKS.prepare_hooks(storage, |h| {
-        MemberChangedHookMsg::one(diff.clone())
-            .into_cosmos_msg(h)
-            .map(SubMsg::new)
+fn validate_distribution_fee(
+    deps: Deps,
+    fee: Option<DistributionFeeInfo>,
+) -> Result<Option<DistributionFee>, ContractError> {
+    fee.map(|fee| {
+        if fee.bps > MAX_DISTRIBUTION_FEE_BPS {
+            return Err(ContractError::DistributionFeeTooHigh {
+                max: MAX_DISTRIBUTION_FEE_BPS,
+            });
+        }
+        Ok(DistributionFee {
+            bps: fee.bps,
+            recipient: deps.api.addr_validate(&fee.recipient)?,
+        })
     })
+    .transpose()
 }
 
-fn update_rewards(
-    storage: &mut dyn Storage,
-    sender: Addr,
-    old_rewards: &[Uint128],
-    new_rewards: &[Uint128],
-) -> StdResult<()> {
-    let old_reward_power: Uint128 = old_rewards.iter().sum();
-    let new_reward_power: Uint128 = new_rewards.iter().sum();
-
-    // short-circuit if no change
-    if old_reward_power == new_reward_power {
-        return Ok(());
+pub fn execute_update_distribution_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    distribution_fee: Option<DistributionFeeInfo>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    cfg.distribution_fee = validate_distribution_fee(deps.as_ref(), distribution_fee)?;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "update_distribution_fee")
+        .add_attribute("sender", info.sender);
+    if let Some(fee) = cfg.distribution_fee {
+        resp = resp
+            .add_attribute("bps", fee.bps.to_string())
+            .add_attribute("recipient", fee.recipient);
     }
+    Ok(resp)
+}
 
-    let old_total_power = REWARDS.may_load(storage, &sender)?.unwrap_or_default();
-    // otherwise, record change of power
-    if new_reward_power.is_zero() && old_total_power == old_reward_power {
-        REWARDS.remove(storage, &sender);
-    } else {
-        let new_total_power = old_total_power + new_reward_power - old_reward_power;
-        REWARDS.save(storage, &sender, &new_total_power)?;
-    }
+pub fn execute_update_power_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    tokens_per_power: Option<Uint128>,
+    min_bond: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
 
-    // update total
-    let old_total = TOTAL_REWARDS.may_load(storage)?.unwrap_or_default();
-    TOTAL_REWARDS.save(storage, &(old_total + new_reward_power - old_reward_power))?;
+    let mut cfg = CONFIG.load(deps.storage)?;
+    if let Some(tokens_per_power) = tokens_per_power {
+        if tokens_per_power.is_zero() {
+            return Err(ContractError::ZeroTokensPerPower {});
+        }
+        cfg.tokens_per_power = tokens_per_power;
+    }
+    if let Some(min_bond) = min_bond {
+        // min_bond is at least 1, so 0 stake -> non-membership
+        cfg.min_bond = std::cmp::max(min_bond, Uint128::new(1));
+    }
+    CONFIG.save(deps.storage, &cfg)?;
 
-    // update their share of the distribution
-    let ppw = DISTRIBUTION.load(storage)?.shares_per_point.u128();
-    let diff = new_reward_power.u128() as i128 - old_reward_power.u128() as i128;
-    apply_points_correction(storage, &sender, ppw, diff)?;
+    // everyone has to be recomputed under the new config, even if a previous recompute is not
+    // finished yet
+    PENDING_RECOMPUTE.save(deps.storage, &RecomputeProgress::default())?;
 
-    Ok(())
+    Ok(Response::new()
+        .add_attribute("action", "update_power_config")
+        .add_attribute("sender", info.sender)
+        .add_attribute("tokens_per_power", cfg.tokens_per_power)
+        .add_attribute("min_bond", cfg.min_bond))
 }
 
-fn calc_power(cfg: &Config, stake: Uint128, multiplier: Decimal) -> Uint128 {
-    if stake < cfg.min_bond {
-        Uint128::zero()
-    } else {
-        stake * multiplier / cfg.tokens_per_power
-    }
+pub fn execute_update_loyalty_bonus(
+    deps: DepsMut,
+    info: MessageInfo,
+    bonus_pct: u8,
+    max_pct: u8,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    cfg.loyalty_bonus_pct = bonus_pct;
+    cfg.max_loyalty_bonus_pct = max_pct;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    // voting powers of everyone have to include the new bonus
+    PENDING_RECOMPUTE.save(deps.storage, &RecomputeProgress::default())?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_loyalty_bonus")
+        .add_attribute("sender", info.sender)
+        .add_attribute("bonus_pct", bonus_pct.to_string())
+        .add_attribute("max_pct", max_pct.to_string()))
 }
 
-pub fn execute_claim(
+const DEFAULT_RECOMPUTE_LIMIT: u32 = 10;
+const MAX_RECOMPUTE_LIMIT: u32 = 30;
+
+pub fn execute_recompute_members(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
 ) -> Result<Response, ContractError> {
-    let release = CLAIMS.claim_tokens(deps.storage, &info.sender, &env.block, None)?;
-    if release.is_zero() {
-        return Err(ContractError::NothingToClaim {});
+    let mut progress = PENDING_RECOMPUTE
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoRecomputePending {})?;
+    if let Some(start_after) = start_after {
+        if progress.last_processed.as_ref().map(Addr::as_str) != Some(start_after.as_str()) {
+            return Err(ContractError::InvalidRecomputeStart {
+                expected: progress.last_processed.map(Addr::into_string),
+            });
+        }
     }
+    let limit = limit
+        .unwrap_or(DEFAULT_RECOMPUTE_LIMIT)
+        .min(MAX_RECOMPUTE_LIMIT) as usize;
 
-    let config = CONFIG.load(deps.storage)?;
-    let amount_str = coin_to_string(release, config.cw20_contract.as_str());
-    let undelegate = VestingExecuteMsg::Undelegate {
-        recipient: info.sender.to_string(),
-        amount: release,
-    };
-    let undelegate_msg = SubMsg::new(WasmMsg::Execute {
-        contract_addr: config.cw20_contract.to_string(),
-        msg: to_binary(&undelegate)?,
-        funds: vec![],
-    });
+    let (mut addresses, finished) =
+        next_stakers(deps.storage, progress.last_processed.as_ref(), limit)?;
 
-    TOTAL_STAKED.update::<_, StdError>(deps.storage, |token_info| {
-        Ok(TokenInfo {
-            staked: token_info.staked,
-            unbonding: token_info.unbonding.saturating_sub(release),
-        })
-    })?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut messages = vec![];
+    for addr in &addresses {
+        messages.extend(recompute_member(deps.storage, &cfg, addr, &env.block)?);
+    }
 
-    Ok(Response::new()
-        .add_submessage(undelegate_msg)
-        .add_attribute("action", "claim")
-        .add_attribute("tokens", amount_str)
-        .add_attribute("sender", info.sender))
-}
+    progress.processed += addresses.len() as u64;
+    if let Some(last) = addresses.pop() {
+        progress.last_processed = Some(last);
+    }
+    if finished {
+        PENDING_RECOMPUTE.remove(deps.storage);
+    } else {
+        PENDING_RECOMPUTE.save(deps.storage, &progress)?;
+    }
 
-#[inline]
-fn coin_to_string(amount: Uint128, address: &str) -> String {
-    format!("{} {}", amount, address)
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("action", "recompute_members")
+        .add_attribute("processed", progress.processed.to_string())
+        .add_attribute("finished", finished.to_string()))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Claims { address } => {
-            to_binary(&CLAIMS.query_claims(deps, &deps.api.addr_validate(&address)?)?)
-        }
-        QueryMsg::Staked {
-            address,
-            unbonding_period,
-        } => to_binary(&query_staked(deps, &env, address, unbonding_period)?),
-        QueryMsg::BondingInfo {} => to_binary(&query_bonding_info(deps)?),
-        QueryMsg::AllStaked { address } => to_binary(&query_all_staked(deps, env, address)?),
-        QueryMsg::TotalStaked {} => to_binary(&query_total_staked(deps)?),
-        QueryMsg::TotalUnbonding {} => to_binary(&query_total_unbonding(deps)?),
-        QueryMsg::Admin {} => to_binary(&ADMIN.query_admin(deps)?),
-        QueryMsg::Hooks {} => to_binary(&HOOKS.query_hooks(deps)?),
-        QueryMsg::VotingPowerAtHeight { address, height } => {
-            to_binary(&query_voting_power(deps, env, address, height)?)
-        }
-        QueryMsg::TotalPowerAtHeight { height } => {
-            to_binary(&query_total_power(deps, env, height)?)
-        }
-        QueryMsg::Info {} => to_binary(&query_info(deps)?),
-        QueryMsg::TokenContract {} => to_binary(&query_token_contract(deps)?),
-        QueryMsg::TotalRewards {} => to_binary(&query_total_rewards(deps)?),
-        QueryMsg::Rewards { address } => to_binary(&query_rewards(deps, address)?),
-        QueryMsg::WithdrawableRewards { owner } => {
-            to_binary(&query_withdrawable_rewards(deps, owner)?)
-        }
-        QueryMsg::DistributedRewards {} => to_binary(&query_distributed_rewards(deps)?),
-        QueryMsg::UndistributedRewards {} => to_binary(&query_undistributed_rewards(deps, env)?),
-        QueryMsg::Delegated { owner } => to_binary(&query_delegated(deps, owner)?),
-        QueryMsg::DistributionData {} => to_binary(&DISTRIBUTION.may_load(deps.storage)?),
-        QueryMsg::WithdrawAdjustmentData { addr } => {
-            to_binary(&query_withdraw_adjustment_data(deps, addr)?)
+/// Returns up to `limit` addresses with stakes, ordered by address and starting after the given
+/// one, and whether these were the last ones
+fn next_stakers(
+    storage: &dyn Storage,
+    start_after: Option<&Addr>,
+    limit: usize,
+) -> StdResult<(Vec<Addr>, bool)> {
+    // find the next addresses, one more than needed to know if there are any left
+    let mut addresses: Vec<Addr> = vec![];
+    for stake in STAKE.prefix_range(
+        storage,
+        start_after.map(PrefixBound::exclusive),
+        None,
+        Order::Ascending,
+    ) {
+        let ((addr, _), _) = stake?;
+        if addresses.last() != Some(&addr) {
+            if addresses.len() == limit + 1 {
+                break;
+            }
+            addresses.push(addr);
         }
     }
+    let finished = addresses.len() <= limit;
+    addresses.truncate(limit);
+    Ok((addresses, finished))
 }
 
-fn query_voting_power(
-    deps: Deps,
+const DEFAULT_MIGRATION_LIMIT: u32 = 30;
+const MAX_MIGRATION_LIMIT: u32 = 100;
+
+pub fn execute_continue_migration(
+    deps: DepsMut,
     env: Env,
-    addr: String,
-    height: Option<u64>,
-) -> StdResult<VotingPowerAtHeightResponse> {
-    let addr = deps.api.addr_validate(&addr)?;
-    let power = match height {
-        Some(h) => MEMBERS.may_load_at_height(deps.storage, &addr, h),
-        None => MEMBERS.may_load(deps.storage, &addr),
-    }?;
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let mut progress = MIGRATION_PROGRESS
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoMigrationInProgress {})?;
+    let limit = limit
+        .unwrap_or(DEFAULT_MIGRATION_LIMIT)
+        .min(MAX_MIGRATION_LIMIT) as usize;
+
+    let (mut addresses, finished) =
+        next_stakers(deps.storage, progress.last_processed.as_ref(), limit)?;
+    for addr in &addresses {
+        backfill_staker(deps.storage, &env, addr)?;
+    }
 
-    let power = power.unwrap_or_default();
-    let height = height.unwrap_or(env.block.height);
+    progress.processed += addresses.len() as u64;
+    if let Some(last) = addresses.pop() {
+        progress.last_processed = Some(last);
+    }
+    if finished {
+        MIGRATION_PROGRESS.remove(deps.storage);
+        STORAGE_VERSION_MARKER.save(deps.storage, &progress.target_version)?;
+    } else {
+        MIGRATION_PROGRESS.save(deps.storage, &progress)?;
+    }
 
-    Ok(VotingPowerAtHeightResponse { power, height })
+    Ok(Response::new()
+        .add_attribute("action", "continue_migration")
+        .add_attribute("processed", progress.processed.to_string())
+        .add_attribute("finished", finished.to_string()))
 }
 
-fn query_total_power(
-    deps: Deps,
-    env: Env,
-    height: Option<u64>,
-) -> StdResult<TotalPowerAtHeightResponse> {
-    let power = match height {
-        Some(h) => TOTAL_VOTES.may_load_at_height(deps.storage, h),
-        None => TOTAL_VOTES.may_load(deps.storage),
-    }?
-    .unwrap_or_default();
-
-    let height = height.unwrap_or(env.block.height);
-
-    Ok(TotalPowerAtHeightResponse { power, height })
+/// Fills in the data added by the current storage layout for a staker from before it existed.
+/// The exact time of their first stake is unknown, so the time of the migration is used.
+/// Their voting power is moved off the cw4 member key, which keeps the cw4 `u64` weight.
+fn backfill_staker(storage: &mut dyn Storage, env: &Env, addr: &Addr) -> StdResult<()> {
+    if !FIRST_STAKED.has(storage, addr) {
+        FIRST_STAKED.save(storage, addr, &env.block.time)?;
+    }
+    migrate_member_power(storage, addr)
 }
 
-fn query_rewards(deps: Deps, addr: String) -> StdResult<RewardsResponse> {
-    let addr = deps.api.addr_validate(&addr)?;
-    Ok(RewardsResponse {
-        rewards: REWARDS.may_load(deps.storage, &addr)?.unwrap_or_default(),
-    })
-}
+/// Recalculates votes and rewards of all stakes of `addr` under the given config
+fn recompute_member(
+    storage: &mut dyn Storage,
+    cfg: &Config,
+    addr: &Addr,
+    block: &BlockInfo,
+) -> StdResult<Vec<SubMsg>> {
+    let loyalty = cfg.loyalty_multiplier(loyalty_epochs(storage, addr, block.time)?);
+    let stakes = STAKE
+        .prefix(addr)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut old_votes = vec![];
+    let mut new_votes = vec![];
+    let mut old_rewards = vec![];
+    let mut new_rewards = vec![];
+    for (unbonding_period, mut bonding_info) in stakes {
+        let multipliers = STAKE_CONFIG.load(storage, unbonding_period)?;
+        let stake = bonding_info.total_stake();
+        old_votes.push(bonding_info.votes);
+        old_rewards.push(bonding_info.rewards);
+        let discounted = voting_discounted(storage, addr, unbonding_period)?;
+        bonding_info.votes = calc_votes(cfg, stake, discounted, multipliers.voting * loyalty);
+        bonding_info.rewards = calc_power(cfg, stake, multipliers.reward);
+        new_votes.push(bonding_info.votes);
+        new_rewards.push(bonding_info.rewards);
+        STAKE.save(storage, (addr, unbonding_period), &bonding_info)?;
+    }
 
-fn query_total_rewards(deps: Deps) -> StdResult<TotalRewardsResponse> {
-    Ok(TotalRewardsResponse {
-        rewards: TOTAL_REWARDS.may_load(deps.storage)?.unwrap_or_default(),
-    })
+    let messages = update_membership(storage, addr.clone(), &old_votes, &new_votes, block.height)?;
+    update_rewards(storage, addr.clone(), &old_rewards, &new_rewards)?;
+    Ok(messages)
 }
 
-fn query_bonding_info(deps: Deps) -> StdResult<BondingInfoResponse> {
-    let config = CONFIG.load(deps.storage)?;
+/// Recomputes the voting power of `address` from scratch, in case it got out of sync with
+/// its stakes. Unlike `recompute_member`, this does not rely on the stored votes being correct
+/// and does not alert the hooks.
+pub fn execute_recompute_voting_power(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let loyalty = cfg.loyalty_multiplier(loyalty_epochs(deps.storage, &addr, env.block.time)?);
+
+    let stakes = STAKE
+        .prefix(&addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut new_power = Decimal256::zero();
+    for (unbonding_period, mut bonding_info) in stakes {
+        let multipliers = STAKE_CONFIG.load(deps.storage, unbonding_period)?;
+        bonding_info.votes = calc_votes(
+            &cfg,
+            bonding_info.total_stake(),
+            voting_discounted(deps.storage, &addr, unbonding_period)?,
+            multipliers.voting * loyalty,
+        );
+        new_power += bonding_info.votes;
+        STAKE.save(deps.storage, (&addr, unbonding_period), &bonding_info)?;
+    }
+    let new_power = truncate_power(new_power)?;
+
+    let old_power = MEMBERS.may_load(deps.storage, &addr)?.unwrap_or_default();
+    if new_power != old_power {
+        let height = env.block.height;
+        save_member_power(deps.storage, &addr, new_power, height)?;
+        let total = TOTAL_VOTES.may_load(deps.storage)?.unwrap_or_default();
+        save_total_votes(
+            deps.storage,
+            (total + new_power).checked_sub(old_power)?,
+            height,
+        )?;
+    }
 
-    let bonding = config
-        .unbonding_periods
-        .into_iter()
-        .filter_map(|up| match STAKE_CONFIG.may_load(deps.storage, up) {
-            Ok(Some(multipliers)) => Some(Ok(BondingPeriodInfo {
-                voting_multiplier: multipliers.voting,
-                reward_multiplier: multipliers.reward,
-                unbonding_period: up,
-                total_staked: multipliers.staked,
-            })),
-            Ok(None) => None,
-            Err(e) => Some(Err(e)),
-        })
-        .collect::<StdResult<Vec<BondingPeriodInfo>>>()?;
-    Ok(BondingInfoResponse { bonding })
+    Ok(Response::new()
+        .add_attribute("action", "recompute_voting_power")
+        .add_attribute("address", address)
+        .add_attribute("old_power", old_power)
+        .add_attribute("new_power", new_power)
+        .add_attribute("sender", info.sender))
 }
 
-fn query_info(deps: Deps) -> StdResult<InfoResponse> {
-    let info = get_contract_version(deps.storage)?;
-    Ok(cw_core_interface::voting::InfoResponse { info })
+pub fn execute_refresh_voting_discount(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.vesting_voting_discount.is_none() {
+        return Err(ContractError::VotingDiscountDisabled {});
+    }
+    let addr = deps.api.addr_validate(&address)?;
+    let locked = query_vesting_locked(deps.as_ref(), &cfg, &addr)?;
+
+    let discounted = VOTING_DISCOUNTED
+        .prefix(&addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let total: Uint128 = discounted.iter().map(|(_, discounted)| *discounted).sum();
+    let mut messages = vec![];
+    if locked < total {
+        for (unbonding_period, discounted) in discounted {
+            let discounted = discounted.multiply_ratio(locked, total);
+            save_voting_discounted(deps.storage, &addr, unbonding_period, discounted)?;
+        }
+        messages = recompute_member(deps.storage, &cfg, &addr, &env.block)?;
+    }
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("action", "refresh_voting_discount")
+        .add_attribute("address", addr)
+        .add_attribute("locked", locked)
+        .add_attribute("discounted", total.min(locked))
+        .add_attribute("sender", info.sender))
 }
 
-fn query_token_contract(deps: Deps) -> StdResult<Addr> {
+pub fn execute_slash_member(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    portion: Decimal,
+    unbonding_period: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    if portion.is_zero() || portion > Decimal::one() {
+        return Err(ContractError::InvalidSlashPortion {});
+    }
+    let addr = deps.api.addr_validate(&address)?;
     let cfg = CONFIG.load(deps.storage)?;
-    Ok(cfg.cw20_contract)
-}
+    // slashed tokens only become rewards if these are paid in the staked token
+    if cfg.slash_destination == SlashDestination::RewardPool
+        && *cfg.reward_token() != cfg.cw20_contract
+    {
+        return Err(ContractError::SlashToRewardPoolUnavailable {});
+    }
+    let periods = match unbonding_period {
+        Some(period) if !cfg.unbonding_periods.contains(&period) => {
+            return Err(ContractError::NoUnbondingPeriodFound(period));
+        }
+        Some(period) => vec![period],
+        None => cfg.unbonding_periods.clone(),
+    };
+    let loyalty = cfg.loyalty_multiplier(loyalty_epochs(deps.storage, &addr, env.block.time)?);
+
+    let mut old_votes = vec![];
+    let mut new_votes = vec![];
+    let mut old_rewards = vec![];
+    let mut new_rewards = vec![];
+    let mut slashed = Uint128::zero();
+    let mut events = vec![];
+    for period in periods {
+        let mut stake = match STAKE.may_load(deps.storage, (&addr, period))? {
+            Some(stake) => stake,
+            None => continue,
+        };
+        // tokens locked by a rebond to a shorter period are left untouched
+        let amount = stake.total_unlocked(&env) * portion;
+        if amount.is_zero() {
+            continue;
+        }
 
-pub fn query_staked(
-    deps: Deps,
-    env: &Env,
-    addr: String,
-    unbonding_period: u64,
-) -> StdResult<StakedResponse> {
-    let addr = deps.api.addr_validate(&addr)?;
-    // sanity check if such unbonding period exists
-    STAKE_CONFIG
-        .load(deps.storage, unbonding_period)
-        .map_err(|_| {
-            StdError::generic_err(format!("No unbonding period found: {}", unbonding_period))
-        })?;
-    let stake = STAKE
-        .may_load(deps.storage, (&addr, unbonding_period))?
-        .unwrap_or_default();
-    let cw20_contract = CONFIG.load(deps.storage)?.cw20_contract.to_string();
-    Ok(StakedResponse {
-        stake: stake.total_stake(),
-        total_locked: stake.total_locked(env),
-        unbonding_period,
-        cw20_contract,
-    })
-}
+        let multipliers =
+            STAKE_CONFIG.update::<_, ContractError>(deps.storage, period, |multipliers| {
+                let mut multipliers =
+                    multipliers.ok_or(ContractError::NoUnbondingPeriodFound(period))?;
+                multipliers.staked = multipliers.staked.checked_sub(amount)?;
+                Ok(multipliers)
+            })?;
+        old_votes.push(stake.votes);
+        old_rewards.push(stake.rewards);
+        release_voting_discounted(deps.storage, &addr, period, stake.total_stake(), amount)?;
+        stake.release_stake(&env, amount)?;
+        let total = stake.total_stake();
+        let discounted = voting_discounted(deps.storage, &addr, period)?;
+        stake.votes = calc_votes(&cfg, total, discounted, multipliers.voting * loyalty);
+        stake.rewards = calc_power(&cfg, total, multipliers.reward);
+        STAKE.save(deps.storage, (&addr, period), &stake)?;
+        new_votes.push(stake.votes);
+        new_rewards.push(stake.rewards);
+
+        slashed += amount;
+        events.push(
+            Event::new("slash")
+                .add_attribute("address", &addr)
+                .add_attribute("unbonding_period", period.to_string())
+                .add_attribute("amount", amount)
+                .add_attribute("remaining", total),
+        );
+    }
+    if slashed.is_zero() {
+        return Err(ContractError::NothingToSlash { address });
+    }
 
-pub fn query_all_staked(deps: Deps, env: Env, addr: String) -> StdResult<AllStakedResponse> {
-    let addr = deps.api.addr_validate(&addr)?;
-    let config = CONFIG.load(deps.storage)?;
-    let cw20_contract = config.cw20_contract.to_string();
+    let messages = update_membership(
+        deps.storage,
+        addr.clone(),
+        &old_votes,
+        &new_votes,
+        env.block.height,
+    )?;
+    update_rewards(deps.storage, addr.clone(), &old_rewards, &new_rewards)?;
+    TOTAL_STAKED.update::<_, StdError>(deps.storage, |token_info| {
+        Ok(TokenInfo {
+            staked: token_info.staked.saturating_sub(slashed),
+            unbonding: token_info.unbonding,
+        })
+    })?;
 
-    let stakes = config
-        .unbonding_periods
+    // the slashed tokens are no longer delegated by the member, but owned by this contract,
+    // where they count as undistributed rewards unless they are burned
+    let mut token_msgs = vec![VestingExecuteMsg::Undelegate {
+        recipient: env.contract.address.to_string(),
+        amount: slashed,
+        delegator: Some(addr.to_string()),
+    }];
+    if cfg.slash_destination == SlashDestination::Burn {
+        token_msgs.push(VestingExecuteMsg::Burn { amount: slashed });
+    }
+    let token_msgs = token_msgs
         .into_iter()
-        .filter_map(|up| match STAKE.may_load(deps.storage, (&addr, up)) {
-            Ok(Some(stake)) => Some(Ok(StakedResponse {
-                stake: stake.total_stake(),
-                total_locked: stake.total_locked(&env),
-                unbonding_period: up,
-                cw20_contract: cw20_contract.clone(),
-            })),
-            Ok(None) => None,
-            Err(e) => Some(Err(e)),
+        .map(|msg| {
+            Ok(WasmMsg::Execute {
+                contract_addr: cfg.cw20_contract.to_string(),
+                msg: to_binary(&msg)?,
+                funds: vec![],
+            })
         })
-        .collect::<StdResult<Vec<StakedResponse>>>()?;
+        .collect::<StdResult<Vec<_>>>()?;
 
-    Ok(AllStakedResponse { stakes })
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_messages(token_msgs)
+        .add_events(events)
+        .add_attribute("action", "slash_member")
+        .add_attribute("address", addr)
+        .add_attribute("portion", portion.to_string())
+        .add_attribute("amount", slashed)
+        .add_attribute("destination", cfg.slash_destination.as_str())
+        .add_attribute("sender", info.sender))
 }
 
-pub fn query_total_staked(deps: Deps) -> StdResult<TotalStakedResponse> {
-    Ok(TotalStakedResponse {
-        total_staked: TOTAL_STAKED.load(deps.storage).unwrap_or_default().staked,
-    })
-}
+pub fn execute_update_slash_destination(
+    deps: DepsMut,
+    info: MessageInfo,
+    destination: SlashDestination,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
 
-pub fn query_total_unbonding(deps: Deps) -> StdResult<TotalUnbondingResponse> {
-    Ok(TotalUnbondingResponse {
-        total_unbonding: TOTAL_STAKED
-            .load(deps.storage)
-            .unwrap_or_default()
-            .unbonding,
-    })
-}
+    let mut cfg = CONFIG.load(deps.storage)?;
+    cfg.slash_destination = destination;
+    CONFIG.save(deps.storage, &cfg)?;
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(Response::new())
+    Ok(Response::new()
+        .add_attribute("action", "update_slash_destination")
+        .add_attribute("destination", destination.as_str())
+        .add_attribute("sender", info.sender))
 }
 
-#[cfg(test)]
-mod tests {
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{from_slice, CosmosMsg, Decimal, Order, Storage};
-    use cw2::ContractVersion;
-    use cw4::{member_key, TOTAL_KEY};
-    use cw_controllers::{AdminError, Claim, HookError};
-    use cw_utils::Duration;
-    use test_case::test_case;
-
-    use crate::error::ContractError;
-    use crate::msg::{StakeConfig, WithdrawAdjustmentDataResponse};
-    use crate::state::{Distribution, WithdrawAdjustment};
-
-    use super::*;
-
-    const INIT_ADMIN: &str = "admin";
-    const USER1: &str = "user1";
-    const USER2: &str = "user2";
-    const USER3: &str = "user3";
-    const TOKENS_PER_POWER: Uint128 = Uint128::new(1_000);
-    const MIN_BOND: Uint128 = Uint128::new(5_000);
-    const UNBONDING_BLOCKS: u64 = 100;
-    const UNBONDING_PERIOD: u64 = UNBONDING_BLOCKS / 5;
-    const UNBONDING_PERIOD_2: u64 = 2 * UNBONDING_PERIOD;
-    const CW20_ADDRESS: &str = "wasm1234567890";
-
-    #[test]
-    fn check_crate_name() {
-        assert_eq!(CONTRACT_NAME, "crates.io:wynd_stake");
-    }
+pub fn execute_rebond(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    bond_from: u64,
+    bond_to: u64,
+    claim_first: bool,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
 
-    fn default_instantiate(deps: DepsMut, env: Env) {
-        cw20_instantiate(
-            deps,
-            env,
-            TOKENS_PER_POWER,
-            MIN_BOND,
-            vec![StakeConfig {
-                unbonding_period: UNBONDING_PERIOD,
-                voting_multiplier: Decimal::one(),
-                reward_multiplier: Decimal::one(),
-            }],
-        )
+    // Raise if no amount was provided
+    if amount == Uint128::zero() {
+        return Err(ContractError::NoRebondAmount {});
     }
-
-    fn cw20_instantiate(
-        deps: DepsMut,
-        env: Env,
-        tokens_per_power: Uint128,
-        min_bond: Uint128,
-        stake_config: Vec<StakeConfig>,
-    ) {
-        let msg = InstantiateMsg {
-            cw20_contract: CW20_ADDRESS.to_owned(),
-            tokens_per_power,
-            min_bond,
-            stake_config,
-            admin: Some(INIT_ADMIN.into()),
-        };
-        let info = mock_info("creator", &[]);
-        instantiate(deps, env, info, msg).unwrap();
+    // Short out with an error if trying to rebond to itself
+    if bond_from == bond_to {
+        return Err(ContractError::SameUnbondingRebond {});
     }
 
-    fn bond_cw20_with_period(
-        mut deps: DepsMut,
-        user1: u128,
-        user2: u128,
-        user3: u128,
-        unbonding_period: u64,
-        time_delta: u64,
-    ) {
-        let mut env = mock_env();
-        env.block.time = env.block.time.plus_seconds(time_delta);
-
-        for (addr, stake) in &[(USER1, user1), (USER2, user2), (USER3, user3)] {
-            if *stake != 0 {
-                let msg = ExecuteMsg::ReceiveDelegation(Cw20ReceiveDelegationMsg {
-                    sender: addr.to_string(),
-                    amount: Uint128::new(*stake),
-                    msg: to_binary(&ReceiveDelegationMsg::Delegate { unbonding_period }).unwrap(),
-                });
-                let info = mock_info(CW20_ADDRESS, &[]);
-                execute(deps.branch(), env.clone(), info, msg).unwrap();
-            }
+    // claimed tokens are not bonded anymore, so they can never be part of the rebond
+    let mut claim_messages = vec![];
+    let mut claimed = Uint128::zero();
+    if claim_first {
+        assert_claims_not_frozen(deps.storage, &info.sender)?;
+        let (release, exit_vesting, delegators) =
+            take_matured_claims(deps.storage, &env, &info.sender)?;
+        if !release.is_zero() {
+            let claim_resp = release_claimed(
+                deps.branch(),
+                &env,
+                info.sender.clone(),
+                release,
+                exit_vesting,
+                delegators,
+                "claim",
+            )?;
+            claim_messages = claim_resp.messages;
+            claimed = release;
         }
     }
+    record_interaction(deps.storage, &env, &info.sender)?;
+    let loyalty =
+        cfg.loyalty_multiplier(loyalty_epochs(deps.storage, &info.sender, env.block.time)?);
 
-    fn bond_cw20(deps: DepsMut, user1: u128, user2: u128, user3: u128, time_delta: u64) {
-        bond_cw20_with_period(deps, user1, user2, user3, UNBONDING_PERIOD, time_delta);
-    }
-
-    fn rebond_with_period(
-        mut deps: DepsMut,
-        user1: u128,
+    // Validate both bond_from and bond_to are valid and get their relevant voting multiplier
+    // Validate bond_from is a valid time period
+    let bond_from_staking_multipliers =
+        STAKE_CONFIG.update::<_, ContractError>(deps.storage, bond_from, |multipliers| {
+            let mut multipliers =
+                multipliers.ok_or(ContractError::NoUnbondingPeriodFound(bond_from))?;
+            multipliers.staked = multipliers.staked.checked_sub(amount)?;
+            Ok(multipliers)
+        })?;
+
+    // Validate bond_to is a valid time period for Unbonding
+    let bond_to_staking_multipliers =
+        STAKE_CONFIG.update::<_, ContractError>(deps.storage, bond_to, |multipliers| {
+            let mut multipliers =
+                multipliers.ok_or(ContractError::NoUnbondingPeriodFound(bond_to))?;
+            multipliers.staked += amount;
+            Ok(multipliers)
+        })?;
+
+    // the vesting part of the moved tokens moves along with them
+    let stake_from = STAKE
+        .may_load(deps.storage, (&info.sender, bond_from))?
+        .unwrap_or_default()
+        .total_stake();
+    let moved_discounted =
+        release_voting_discounted(deps.storage, &info.sender, bond_from, stake_from, amount)?;
+    let discounted_from = voting_discounted(deps.storage, &info.sender, bond_from)?;
+    let discounted_to = voting_discounted(deps.storage, &info.sender, bond_to)? + moved_discounted;
+    save_voting_discounted(deps.storage, &info.sender, bond_to, discounted_to)?;
+
+    // update the sender's stake
+    let mut old_votes_from = Decimal256::zero();
+    let mut old_votes_to = Decimal256::zero();
+
+    let mut old_rewards_from = Decimal256::zero();
+    let mut old_rewards_to = Decimal256::zero();
+
+    // Reduce the bond_from
+    let bond_from_stake_change = STAKE.update(
+        deps.storage,
+        (&info.sender, bond_from),
+        |bonding_info| -> StdResult<_> {
+            let mut bonding_info = bonding_info.unwrap_or_default();
+            // Release the stake, also accounting for locked tokens, raising if there is not enough tokens
+            bonding_info.release_stake(&env, amount)?;
+            let stake = bonding_info.total_stake();
+            let votes = calc_votes(
+                &cfg,
+                stake,
+                discounted_from,
+                bond_from_staking_multipliers.voting * loyalty,
+            );
+            let rewards = calc_power(&cfg, stake, bond_from_staking_multipliers.reward);
+
+            old_votes_from = bonding_info.votes;
+            old_rewards_from = bonding_info.rewards;
+            bonding_info.votes = votes;
+            bonding_info.rewards = rewards;
+            Ok(bonding_info)
+        },
+    )?;
+
+    // Increase the bond_to
+    let bond_to_stake_change = STAKE.update(
+        deps.storage,
+        (&info.sender, bond_to),
+        |bonding_info| -> StdResult<_> {
+            let mut bonding_info = bonding_info.unwrap_or_default();
+            if bond_from > bond_to {
+                bonding_info
+                    .add_locked_tokens(env.block.time.plus_seconds(bond_from - bond_to), amount);
+            } else {
+                bonding_info.add_unlocked_tokens(amount);
+            };
+            let stake = bonding_info.total_stake();
+            let voting_power = calc_votes(
+                &cfg,
+                stake,
+                discounted_to,
+                bond_to_staking_multipliers.voting * loyalty,
+            );
+            let rewards = calc_power(&cfg, stake, bond_to_staking_multipliers.reward);
+
+            old_votes_to = bonding_info.votes;
+            old_rewards_to = bonding_info.rewards;
+            bonding_info.votes = voting_power;
+            bonding_info.rewards = rewards;
+            Ok(bonding_info)
+        },
+    )?;
+    let bond_update_messages = update_membership(
+        deps.storage,
+        info.sender.clone(),
+        &[old_votes_to, old_votes_from],
+        &[bond_to_stake_change.votes, bond_from_stake_change.votes],
+        env.block.height,
+    )?;
+    update_rewards(
+        deps.storage,
+        info.sender,
+        &[old_rewards_to, old_rewards_from],
+        &[bond_to_stake_change.rewards, bond_from_stake_change.rewards],
+    )?;
+
+    let mut resp = Response::new()
+        .add_submessages(claim_messages)
+        .add_submessages(bond_update_messages)
+        .add_attribute("action", "rebond")
+        .add_attribute("amount", amount)
+        .add_attribute("bond_from", bond_from.to_string())
+        .add_attribute("bond_to", bond_to.to_string());
+    if claim_first {
+        resp = resp.add_attribute("claimed", claimed);
+    }
+    Ok(resp)
+}
+
+pub fn execute_rebond_to_optimal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    bond_from: u64,
+    optimize_for: OptimizeFor,
+) -> Result<Response, ContractError> {
+    let multiplier = |multipliers: &StakeMultipliers| match optimize_for {
+        OptimizeFor::Voting => multipliers.voting,
+        OptimizeFor::Rewards => multipliers.reward,
+    };
+
+    let periods = STAKE_CONFIG
+        .range(deps.storage, None, None, Order::Descending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let from_multiplier = periods
+        .iter()
+        .find(|(period, _)| *period == bond_from)
+        .map(|(_, multipliers)| multiplier(multipliers))
+        .ok_or(ContractError::NoUnbondingPeriodFound(bond_from))?;
+    // periods are in descending order, so on ties this picks the shortest one
+    let (bond_to, to_multiplier) = periods
+        .iter()
+        .map(|(period, multipliers)| (*period, multiplier(multipliers)))
+        .max_by_key(|(_, multiplier)| *multiplier)
+        .ok_or(ContractError::NoUnbondingPeriodFound(bond_from))?;
+    if from_multiplier >= to_multiplier {
+        return Err(ContractError::AlreadyOptimal {});
+    }
+
+    execute_rebond(deps, env, info, amount, bond_from, bond_to, false)
+}
+
+pub fn execute_schedule_rebond(
+    deps: DepsMut,
+    info: MessageInfo,
+    rebond: ScheduledRebond,
+) -> Result<Response, ContractError> {
+    // the stake might still be locked, it is only checked on execution
+    if rebond.tokens.is_zero() {
+        return Err(ContractError::NoRebondAmount {});
+    }
+    if rebond.bond_from == rebond.bond_to {
+        return Err(ContractError::SameUnbondingRebond {});
+    }
+    for period in [rebond.bond_from, rebond.bond_to] {
+        if !STAKE_CONFIG.has(deps.storage, period) {
+            return Err(ContractError::NoUnbondingPeriodFound(period));
+        }
+    }
+
+    let mut pending = SCHEDULED_REBONDS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if pending.len() >= MAX_SCHEDULED_REBONDS {
+        return Err(ContractError::TooManyScheduledRebonds {
+            max: MAX_SCHEDULED_REBONDS as u32,
+        });
+    }
+    pending.push(rebond.clone());
+    SCHEDULED_REBONDS.save(deps.storage, &info.sender, &pending)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "schedule_rebond")
+        .add_attribute("tokens", rebond.tokens)
+        .add_attribute("bond_from", rebond.bond_from.to_string())
+        .add_attribute("bond_to", rebond.bond_to.to_string())
+        .add_attribute("execute_after", rebond.execute_after.to_string())
+        .add_attribute("sender", info.sender))
+}
+
+pub fn execute_scheduled_rebonds(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let addr = deps.api.addr_validate(&address)?;
+
+    let (due, pending): (Vec<_>, Vec<_>) = SCHEDULED_REBONDS
+        .may_load(deps.storage, &addr)?
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|rebond| rebond.execute_after <= env.block.time);
+    if due.is_empty() {
+        return Err(ContractError::NoScheduledRebondsDue {});
+    }
+    if pending.is_empty() {
+        SCHEDULED_REBONDS.remove(deps.storage, &addr);
+    } else {
+        SCHEDULED_REBONDS.save(deps.storage, &addr, &pending)?;
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "execute_scheduled_rebonds")
+        .add_attribute("address", &address)
+        .add_attribute("sender", info.sender);
+    let owner = MessageInfo {
+        sender: addr.clone(),
+        funds: vec![],
+    };
+    for rebond in due {
+        // a failing rebond would revert all of them, so only the ones which succeed are executed
+        match check_rebond(deps.as_ref(), &env, &addr, &rebond) {
+            Ok(()) => {
+                let rebond_res = execute_rebond(
+                    deps.branch(),
+                    env.clone(),
+                    owner.clone(),
+                    rebond.tokens,
+                    rebond.bond_from,
+                    rebond.bond_to,
+                    false,
+                )?;
+                res = res.add_submessages(rebond_res.messages).add_event(
+                    Event::new("scheduled_rebond").add_attributes(rebond_res.attributes),
+                );
+            }
+            Err(err) => {
+                res = res.add_event(
+                    Event::new("scheduled_rebond_dropped")
+                        .add_attribute("tokens", rebond.tokens)
+                        .add_attribute("bond_from", rebond.bond_from.to_string())
+                        .add_attribute("bond_to", rebond.bond_to.to_string())
+                        .add_attribute("error", err.to_string()),
+                );
+            }
+        }
+    }
+    Ok(res)
+}
+
+/// Checks that `execute_rebond` would currently succeed for this rebond, without changing any state
+fn check_rebond(
+    deps: Deps,
+    env: &Env,
+    addr: &Addr,
+    rebond: &ScheduledRebond,
+) -> Result<(), ContractError> {
+    if rebond.bond_from == rebond.bond_to {
+        return Err(ContractError::SameUnbondingRebond {});
+    }
+    for period in [rebond.bond_from, rebond.bond_to] {
+        if !STAKE_CONFIG.has(deps.storage, period) {
+            return Err(ContractError::NoUnbondingPeriodFound(period));
+        }
+    }
+    // only the stake which is not locked can be rebonded
+    let unlocked = STAKE
+        .may_load(deps.storage, (addr, rebond.bond_from))?
+        .map(|bonding_info| bonding_info.total_stake() - bonding_info.total_locked(env))
+        .unwrap_or_default();
+    unlocked.checked_sub(rebond.tokens)?;
+    Ok(())
+}
+
+pub fn execute_bond(
+    mut deps: DepsMut,
+    env: Env,
+    sender_cw20_contract: Addr,
+    amount: Uint128,
+    unbonding_period: u64,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    if let Some(unlocks_at) = staking_locked_until(deps.storage, env.block.height)? {
+        return Err(ContractError::StakingLocked { unlocks_at });
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // ensure that cw20 token contract's addresses matches
+    if cfg.cw20_contract != sender_cw20_contract {
+        return Err(ContractError::Cw20AddressesNotMatch {
+            got: sender_cw20_contract.into(),
+            expected: cfg.cw20_contract.into(),
+        });
+    }
+    record_interaction(deps.storage, &env, &sender)?;
+    discount_vesting_bond(deps.branch(), &cfg, &sender, amount, unbonding_period)?;
+    let messages = bond_stake(deps.storage, &env, &cfg, &sender, amount, unbonding_period)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("action", "bond")
+        .add_attribute("amount", amount)
+        .add_attribute("sender", sender))
+}
+
+/// Discounts the votes of the part of `amount` that is still vesting, if enabled.
+/// Must be called before the tokens are added to the stake of `sender`.
+fn discount_vesting_bond(
+    deps: DepsMut,
+    cfg: &Config,
+    sender: &Addr,
+    amount: Uint128,
+    unbonding_period: u64,
+) -> Result<(), ContractError> {
+    if cfg.vesting_voting_discount.is_none() {
+        return Ok(());
+    }
+    // delegations use up vesting tokens first, so the newly bonded tokens are vesting
+    // as far as the lock is not covered by stake discounted before
+    let locked = query_vesting_locked(deps.as_ref(), cfg, sender)?;
+    let vesting = amount.min(locked.saturating_sub(total_voting_discounted(deps.storage, sender)?));
+    let discounted = voting_discounted(deps.storage, sender, unbonding_period)?;
+    save_voting_discounted(deps.storage, sender, unbonding_period, discounted + vesting)?;
+    Ok(())
+}
+
+/// Adds `amount` to the stake of `sender` in the given unbonding period, updating their voting
+/// power, rewards and the total stake. The tokens must already be delegated to this contract.
+fn bond_stake(
+    storage: &mut dyn Storage,
+    env: &Env,
+    cfg: &Config,
+    sender: &Addr,
+    amount: Uint128,
+    unbonding_period: u64,
+) -> Result<Vec<SubMsg>, ContractError> {
+    // a loyalty streak starts with the first stake
+    let staked = STAKE
+        .prefix(sender)
+        .range(storage, None, None, Order::Ascending)
+        .map(|stake| stake.map(|(_, bonding_info)| bonding_info.total_stake()))
+        .sum::<StdResult<Uint128>>()?;
+    if staked.is_zero() {
+        LOYALTY_EPOCHS.save(storage, sender, &loyalty_epoch(env.block.time))?;
+    }
+    if !FIRST_STAKED.has(storage, sender) {
+        FIRST_STAKED.save(storage, sender, &env.block.time)?;
+    }
+    let loyalty = cfg.loyalty_multiplier(loyalty_epochs(storage, sender, env.block.time)?);
+
+    // load staking_multipliers to calculate votes and rewards
+    let staking_multipliers =
+        STAKE_CONFIG.update::<_, ContractError>(storage, unbonding_period, |multipliers| {
+            let mut multipliers =
+                multipliers.ok_or(ContractError::NoUnbondingPeriodFound(unbonding_period))?;
+            multipliers.staked += amount;
+            Ok(multipliers)
+        })?;
+
+    // update the sender's stake
+    let discounted = voting_discounted(storage, sender, unbonding_period)?;
+    let mut old_votes = Decimal256::zero();
+    let mut old_rewards = Decimal256::zero();
+    let new_stake = STAKE.update(
+        storage,
+        (sender, unbonding_period),
+        |bonding_info| -> StdResult<_> {
+            let mut bonding_info = bonding_info.unwrap_or_default();
+            bonding_info.add_unlocked_tokens(amount);
+            let new_stake = bonding_info.total_stake();
+            let voting_power = calc_votes(
+                cfg,
+                new_stake,
+                discounted,
+                staking_multipliers.voting * loyalty,
+            );
+            let rewards = calc_power(cfg, new_stake, staking_multipliers.reward);
+            old_votes = bonding_info.votes;
+            old_rewards = bonding_info.rewards;
+            bonding_info.votes = voting_power;
+            bonding_info.rewards = rewards;
+            Ok(bonding_info)
+        },
+    )?;
+
+    let messages = update_membership(
+        storage,
+        sender.clone(),
+        &[old_votes],
+        &[new_stake.votes],
+        env.block.height,
+    )?;
+    update_rewards(
+        storage,
+        sender.clone(),
+        &[old_rewards],
+        &[new_stake.rewards],
+    )?;
+
+    TOTAL_STAKED.update::<_, StdError>(storage, |token_info| {
+        Ok(TokenInfo {
+            staked: token_info.staked + amount,
+            unbonding: token_info.unbonding,
+        })
+    })?;
+
+    Ok(messages)
+}
+
+pub fn execute_receive_delegation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveDelegationMsg,
+) -> Result<Response, ContractError> {
+    // info.sender is the address of the cw20 contract (that re-sent this message).
+    // wrapper.sender is the address of the user that requested the cw20 contract to send this.
+    // This cannot be fully trusted (the cw20 contract can fake it), so only use it for actions
+    // in the address's favor (like paying/bonding tokens, not withdrawls)
+    let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.cw20_contract {
+        return Err(ContractError::UnexpectedDelegationToken {
+            token: info.sender.into_string(),
+            expected: cfg.cw20_contract.into_string(),
+        });
+    }
+
+    let msg = parse_delegation_msg(&wrapper.msg)?;
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    let event = Event::new("receive_delegation")
+        .add_attribute("token", &info.sender)
+        .add_attribute("sender", &sender)
+        .add_attribute("amount", wrapper.amount);
+    let res = match msg {
+        ReceiveDelegationMsg::Delegate { unbonding_period } => execute_bond(
+            deps,
+            env,
+            info.sender,
+            wrapper.amount,
+            unbonding_period,
+            sender,
+        )?,
+    };
+    Ok(res.add_event(event))
+}
+
+/// Parses the payload of a delegation. Messages of a kind this contract does not know (e.g. sent
+/// by a newer token contract) fail with `UnsupportedDelegationMsg` instead of a parse error.
+fn parse_delegation_msg(msg: &Binary) -> Result<ReceiveDelegationMsg, ContractError> {
+    match from_slice(msg)? {
+        DelegationPayload::Known(msg) => Ok(msg),
+        DelegationPayload::Other(fields) => {
+            let kinds: Vec<_> = fields.into_keys().collect();
+            match kinds.as_slice() {
+                [kind] if !ReceiveDelegationMsg::KINDS.contains(&kind.as_str()) => {
+                    Err(ContractError::UnsupportedDelegationMsg { kind: kind.clone() })
+                }
+                // a known kind with invalid fields, parsed again for the reason
+                _ => Ok(from_slice(msg)?),
+            }
+        }
+    }
+}
+
+/// Payload of a delegation, which may be of a kind this contract does not know
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DelegationPayload {
+    Known(ReceiveDelegationMsg),
+    /// Any other object, keyed by the kind of the message
+    Other(BTreeMap<String, IgnoredAny>),
+}
+
+pub fn execute_unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    unbonding_period: u64,
+    claim_recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    // the stake is always taken from the sender, only the claim can go to someone else
+    let claim_recipient = match claim_recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
+    let cfg = CONFIG.load(deps.storage)?;
+    record_interaction(deps.storage, &env, &info.sender)?;
+    let lost_bonus = reset_loyalty(deps.storage, &cfg, &env, &info.sender)?;
+
+    let (old_stake, new_stake) = unbond_stake(
+        deps.storage,
+        &env,
+        &cfg,
+        &info.sender,
+        &claim_recipient,
+        amount,
+        unbonding_period,
+    )?;
+
+    let mut messages = update_membership(
+        deps.storage,
+        info.sender.clone(),
+        &[old_stake.votes],
+        &[new_stake.votes],
+        env.block.height,
+    )?;
+    update_rewards(
+        deps.storage,
+        info.sender.clone(),
+        &[old_stake.rewards],
+        &[new_stake.rewards],
+    )?;
+    if lost_bonus {
+        messages.extend(recompute_member(
+            deps.storage,
+            &cfg,
+            &info.sender,
+            &env.block,
+        )?);
+    }
+
+    let claim_event = claim_created_event(
+        &claim_recipient,
+        amount,
+        env.block.time.plus_seconds(unbonding_period),
+    );
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_event(claim_event)
+        .add_attribute("action", "unbond")
+        .add_attribute("amount", amount)
+        .add_attribute("sender", info.sender)
+        .add_attribute("claim_recipient", claim_recipient))
+}
+
+pub fn execute_batch_unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    unbonds: Vec<UnbondRequest>,
+) -> Result<Response, ContractError> {
+    if unbonds.is_empty() {
+        return Err(ContractError::NoUnbonds {});
+    }
+    let cfg = CONFIG.load(deps.storage)?;
+    record_interaction(deps.storage, &env, &info.sender)?;
+    let lost_bonus = reset_loyalty(deps.storage, &cfg, &env, &info.sender)?;
+
+    let mut old_votes = vec![];
+    let mut new_votes = vec![];
+    let mut old_rewards = vec![];
+    let mut new_rewards = vec![];
+    let mut total = Uint128::zero();
+    let mut claim_events = vec![];
+    for unbond in unbonds {
+        let (old_stake, new_stake) = unbond_stake(
+            deps.storage,
+            &env,
+            &cfg,
+            &info.sender,
+            &info.sender,
+            unbond.amount,
+            unbond.unbonding_period,
+        )?;
+        old_votes.push(old_stake.votes);
+        new_votes.push(new_stake.votes);
+        old_rewards.push(old_stake.rewards);
+        new_rewards.push(new_stake.rewards);
+        total += unbond.amount;
+        claim_events.push(claim_created_event(
+            &info.sender,
+            unbond.amount,
+            env.block.time.plus_seconds(unbond.unbonding_period),
+        ));
+    }
+
+    // power is updated once for all unbonds
+    let mut messages = update_membership(
+        deps.storage,
+        info.sender.clone(),
+        &old_votes,
+        &new_votes,
+        env.block.height,
+    )?;
+    update_rewards(
+        deps.storage,
+        info.sender.clone(),
+        &old_rewards,
+        &new_rewards,
+    )?;
+    if lost_bonus {
+        messages.extend(recompute_member(
+            deps.storage,
+            &cfg,
+            &info.sender,
+            &env.block,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_events(claim_events)
+        .add_attribute("action", "batch_unbond")
+        .add_attribute("amount", total)
+        .add_attribute("sender", info.sender))
+}
+
+/// Event for indexers to schedule notifications about the claim maturing
+fn claim_created_event(owner: &Addr, amount: Uint128, matures_at: Timestamp) -> Event {
+    Event::new("claim_created")
+        .add_attribute("owner", owner)
+        .add_attribute("amount", amount)
+        .add_attribute("matures_at", matures_at.to_string())
+}
+
+/// Starts a new loyalty streak for `addr`, as they are reducing their stake.
+/// Returns whether they lose a bonus by this, in which case the votes of all their stakes have
+/// to be recomputed.
+fn reset_loyalty(
+    storage: &mut dyn Storage,
+    cfg: &Config,
+    env: &Env,
+    addr: &Addr,
+) -> StdResult<bool> {
+    let epochs = loyalty_epochs(storage, addr, env.block.time)?;
+    LOYALTY_EPOCHS.save(storage, addr, &loyalty_epoch(env.block.time))?;
+    Ok(cfg.loyalty_bonus_pct(epochs) > 0)
+}
+
+/// Reduces the stake of `sender` in the given unbonding period - aborting if insufficient - and
+/// provides `claim_recipient` a claim. Returns the votes and rewards of the stake before and after, the
+/// membership and rewards are not updated yet.
+fn unbond_stake(
+    storage: &mut dyn Storage,
+    env: &Env,
+    cfg: &Config,
+    sender: &Addr,
+    claim_recipient: &Addr,
+    amount: Uint128,
+    unbonding_period: u64,
+) -> Result<(BondingInfo, BondingInfo), ContractError> {
+    // load voting and reward multiplier to calculate votes and rewards
+    // also update the amount staked here
+    let staking_multipliers =
+        STAKE_CONFIG.update::<_, ContractError>(storage, unbonding_period, |multipliers| {
+            let mut multipliers =
+                multipliers.ok_or(ContractError::NoUnbondingPeriodFound(unbonding_period))?;
+            // exit vesting would make the recipient's undelegation depend on two accounts
+            if claim_recipient != sender && multipliers.exit_vesting.is_some() {
+                return Err(ContractError::ExitVestingClaimRecipient(unbonding_period));
+            }
+            multipliers.staked = multipliers.staked.checked_sub(amount)?;
+            Ok(multipliers)
+        })?;
+
+    // reduce the sender's stake - aborting if insufficient
+    let old_stake = STAKE
+        .may_load(storage, (sender, unbonding_period))?
+        .unwrap_or_default();
+    let mut new_stake = old_stake.clone();
+    release_voting_discounted(
+        storage,
+        sender,
+        unbonding_period,
+        old_stake.total_stake(),
+        amount,
+    )?;
+    new_stake.release_stake(env, amount)?;
+    let stake = new_stake.total_stake();
+    let discounted = voting_discounted(storage, sender, unbonding_period)?;
+    new_stake.votes = calc_votes(cfg, stake, discounted, staking_multipliers.voting);
+    new_stake.rewards = calc_power(cfg, stake, staking_multipliers.reward);
+    STAKE.save(storage, (sender, unbonding_period), &new_stake)?;
+
+    // provide the recipient a claim
+    let matures_at = env.block.time.plus_seconds(unbonding_period);
+    CLAIMS.create_claim(
+        storage,
+        claim_recipient,
+        amount,
+        Expiration::AtTime(matures_at),
+    )?;
+    index_claim(storage, claim_recipient, matures_at, amount)?;
+    if claim_recipient != sender {
+        CLAIM_DELEGATORS.update(
+            storage,
+            (claim_recipient, matures_at.nanos()),
+            |parts| -> StdResult<_> {
+                let mut parts = parts.unwrap_or_default();
+                parts.push((sender.clone(), amount));
+                Ok(parts)
+            },
+        )?;
+    }
+    if let Some(schedule) = staking_multipliers.exit_vesting {
+        EXIT_VESTING.update(
+            storage,
+            (claim_recipient, matures_at.nanos()),
+            |parts| -> StdResult<_> {
+                let mut parts = parts.unwrap_or_default();
+                parts.push(ExitVesting { amount, schedule });
+                Ok(parts)
+            },
+        )?;
+    }
+
+    TOTAL_STAKED.update::<_, StdError>(storage, |token_info| {
+        Ok(TokenInfo {
+            staked: token_info.staked.saturating_sub(amount),
+            unbonding: token_info.unbonding + amount,
+        })
+    })?;
+
+    Ok((old_stake, new_stake))
+}
+
+/// Updates the voting power of `sender` after some of its stakes changed from `old_votes` to
+/// `new_votes`. `STAKE` must already be up to date when this is called.
+fn update_membership(
+    storage: &mut dyn Storage,
+    sender: Addr,
+    old_votes: &[Decimal256],
+    new_votes: &[Decimal256],
+    height: u64,
+) -> StdResult<Vec<SubMsg>> {
+    let old_voting_power: Decimal256 = old_votes.iter().sum();
+    let new_voting_power: Decimal256 = new_votes.iter().sum();
+
+    // short-circuit if no change
+    if new_voting_power == old_voting_power {
+        return Ok(vec![]);
+    }
+
+    // otherwise, record change of power, which is truncated only once for the sum of all stakes
+    let old_total_power = MEMBERS.may_load(storage, &sender)?;
+    let new_total_power = total_votes(storage, &sender)?;
+    if new_total_power == old_total_power.unwrap_or_default() {
+        return Ok(vec![]);
+    }
+
+    save_member_power(storage, &sender, new_total_power, height)?;
+    let new_hook = Some(new_total_power).filter(|power| !power.is_zero());
+
+    // update total
+    let total = TOTAL_VOTES.may_load(storage)?.unwrap_or_default();
+    save_total_votes(
+        storage,
+        (total + new_total_power).checked_sub(old_total_power.unwrap_or_default())?,
+        height,
+    )?;
+
+    // alert the hooks
+    let diff = MemberDiff::new(sender, old_total_power, new_hook);
+    HOOKS
+        .range(storage, None, None, Order::Ascending)
+        .map(|hook| {
+            let (addr, hook) = hook?;
+            let msg = MemberChangedHookMsg::one(diff.clone()).into_cosmos_msg(addr)?;
+            // gas limited hooks cannot block membership changes, so their failures are only recorded
+            Ok(match hook.gas_limit {
+                Some(gas_limit) => {
+                    SubMsg::reply_on_error(msg, hook.reply_id).with_gas_limit(gas_limit)
+                }
+                None => SubMsg::new(msg),
+            })
+        })
+        .collect()
+}
+
+pub fn execute_add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+    gas_limit: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_not_renounced(deps.storage)?;
+    ADMIN
+        .assert_admin(deps.as_ref(), &info.sender)
+        .map_err(HookError::from)?;
+    let addr = deps.api.addr_validate(&addr)?;
+    if HOOKS.has(deps.storage, &addr) {
+        return Err(HookError::HookAlreadyRegistered {}.into());
+    }
+
+    let max_hooks = CONFIG.load(deps.storage)?.max_hooks;
+    let registered = HOOKS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count();
+    if registered >= max_hooks as usize {
+        return Err(ContractError::TooManyHooks { max: max_hooks });
+    }
+
+    let reply_id = LAST_HOOK_REPLY_ID
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    LAST_HOOK_REPLY_ID.save(deps.storage, &reply_id)?;
+    HOOK_REPLY_IDS.save(deps.storage, reply_id, &addr)?;
+    HOOKS.save(
+        deps.storage,
+        &addr,
+        &HookConfig {
+            gas_limit,
+            reply_id,
+            failures: 0,
+        },
+    )?;
+
+    let gas_limit = gas_limit.map_or_else(|| "none".to_owned(), |limit| limit.to_string());
+    Ok(Response::new()
+        .add_attribute("action", "add_hook")
+        .add_attribute("hook", addr)
+        .add_attribute("gas_limit", gas_limit)
+        .add_attribute("sender", info.sender))
+}
+
+pub fn execute_remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response, ContractError> {
+    assert_not_renounced(deps.storage)?;
+    ADMIN
+        .assert_admin(deps.as_ref(), &info.sender)
+        .map_err(HookError::from)?;
+    let addr = deps.api.addr_validate(&addr)?;
+    let hook = HOOKS
+        .may_load(deps.storage, &addr)?
+        .ok_or(HookError::HookNotRegistered {})?;
+    HOOKS.remove(deps.storage, &addr);
+    HOOK_REPLY_IDS.remove(deps.storage, hook.reply_id);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("hook", addr)
+        .add_attribute("sender", info.sender))
+}
+
+fn update_rewards(
+    storage: &mut dyn Storage,
+    sender: Addr,
+    old_rewards: &[Decimal256],
+    new_rewards: &[Decimal256],
+) -> StdResult<()> {
+    let old_reward_power: Decimal256 = old_rewards.iter().sum();
+    let new_reward_power: Decimal256 = new_rewards.iter().sum();
+
+    // short-circuit if no change
+    if old_reward_power == new_reward_power {
+        return Ok(());
+    }
+
+    recompute_rewards(storage, &sender)
+}
+
+/// Sets the reward power of `addr` to the sum of the reward power of all its stakes, scaled by
+/// its distribution weight and truncated. `STAKE` must already be up to date when this is called.
+pub(crate) fn recompute_rewards(storage: &mut dyn Storage, addr: &Addr) -> StdResult<()> {
+    let unweighted_power = STAKE
+        .prefix(addr)
+        .range(storage, None, None, Order::Ascending)
+        .map(|stake| stake.map(|(_, bonding_info)| bonding_info.rewards))
+        .sum::<StdResult<Decimal256>>()?;
+    let weight = DISTRIBUTION_WEIGHT
+        .may_load(storage, addr)?
+        .unwrap_or_else(Decimal::one);
+    let new_reward_power = truncate_power(unweighted_power * decimal256(weight))?;
+
+    let old_reward_power = REWARDS.may_load(storage, addr)?.unwrap_or_default();
+    if old_reward_power == new_reward_power {
+        return Ok(());
+    }
+
+    // otherwise, record change of power
+    if new_reward_power.is_zero() {
+        REWARDS.remove(storage, addr);
+    } else {
+        REWARDS.save(storage, addr, &new_reward_power)?;
+    }
+
+    // update total
+    let old_total = TOTAL_REWARDS.may_load(storage)?.unwrap_or_default();
+    TOTAL_REWARDS.save(storage, &(old_total + new_reward_power - old_reward_power))?;
+
+    // update their share of the distribution
+    let ppw = DISTRIBUTION.load(storage)?.shares_per_point.u128();
+    let diff = new_reward_power.u128() as i128 - old_reward_power.u128() as i128;
+    apply_points_correction(storage, addr, ppw, diff)?;
+
+    Ok(())
+}
+
+/// Calculates the power of a single stake. The result is not truncated, so that small stakes
+/// still count once they are summed up with the other stakes of their owner.
+/// It is computed in 256 bits, as a `Decimal` cannot hold stakes above about 3.4e20 tokens.
+fn calc_power(cfg: &Config, stake: Uint128, multiplier: Decimal) -> Decimal256 {
+    if stake < cfg.min_bond {
+        Decimal256::zero()
+    } else {
+        Decimal256::from_ratio(stake, 1u128) * decimal256(multiplier)
+            / Uint256::from(cfg.tokens_per_power)
+    }
+}
+
+/// Calculates the voting power of a single stake, of which `discounted` tokens are still vesting
+/// and count only partially if a vesting voting discount is configured
+fn calc_votes(
+    cfg: &Config,
+    stake: Uint128,
+    discounted: Uint128,
+    multiplier: Decimal,
+) -> Decimal256 {
+    match cfg.vesting_voting_discount {
+        Some(discount) if stake >= cfg.min_bond => {
+            let discounted = discounted.min(stake) * discount;
+            Decimal256::from_ratio(stake - discounted, 1u128) * decimal256(multiplier)
+                / Uint256::from(cfg.tokens_per_power)
+        }
+        _ => calc_power(cfg, stake, multiplier),
+    }
+}
+
+/// Rounds a (summed up) power down to the integer stored in `MEMBERS` and `REWARDS`,
+/// failing if it does not fit
+fn truncate_power(power: Decimal256) -> StdResult<Uint128> {
+    Ok((Uint256::from(1u8) * power).try_into()?)
+}
+
+/// Converts a multiplier or weight for use with the 256 bit powers
+fn decimal256(value: Decimal) -> Decimal256 {
+    // both types have 18 decimal places
+    Decimal256::new(value.atomics().into())
+}
+
+/// Sum of the voting power of all stakes of `addr`, truncated
+fn total_votes(storage: &dyn Storage, addr: &Addr) -> StdResult<Uint128> {
+    let votes = STAKE
+        .prefix(addr)
+        .range(storage, None, None, Order::Ascending)
+        .map(|stake| stake.map(|(_, bonding_info)| bonding_info.votes))
+        .sum::<StdResult<Decimal256>>()?;
+    truncate_power(votes)
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_claims_not_frozen(deps.storage, &info.sender)?;
+    let (release, exit_vesting, delegators) =
+        take_matured_claims(deps.storage, &env, &info.sender)?;
+    if release.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    release_claimed(
+        deps,
+        &env,
+        info.sender,
+        release,
+        exit_vesting,
+        delegators,
+        "claim",
+    )
+}
+
+/// Released amount of claims, with the parts of it with exit vesting or other delegators
+type MaturedClaims = (Uint128, Vec<ExitVesting>, Vec<(Addr, Uint128)>);
+
+/// Removes all matured claims of `addr`
+fn take_matured_claims(
+    storage: &mut dyn Storage,
+    env: &Env,
+    addr: &Addr,
+) -> StdResult<MaturedClaims> {
+    // remove the claims about to be released from the maturity index
+    let claims = CLAIMS_BY_ADDR.may_load(storage, addr)?.unwrap_or_default();
+    let mut exit_vesting = vec![];
+    let mut delegators = vec![];
+    for claim in claims {
+        if let Expiration::AtTime(matures_at) = claim.release_at {
+            if claim.release_at.is_expired(&env.block) {
+                unindex_claim(storage, addr, matures_at, claim.amount)?;
+                exit_vesting.extend(take_exit_vesting(storage, addr, matures_at, claim.amount)?);
+                delegators.extend(take_claim_delegators(
+                    storage,
+                    addr,
+                    matures_at,
+                    claim.amount,
+                )?);
+            }
+        }
+    }
+
+    let release = CLAIMS.claim_tokens(storage, addr, &env.block, None)?;
+    Ok((release, exit_vesting, delegators))
+}
+
+pub fn execute_claim_specific(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    expires_at: u64,
+) -> Result<Response, ContractError> {
+    assert_claims_not_frozen(deps.storage, &info.sender)?;
+    let mut claims = CLAIMS_BY_ADDR
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    // claims are created with nanosecond precision, so only the seconds are compared
+    let (pos, unlocks_at) = claims
+        .iter()
+        .enumerate()
+        .find_map(|(pos, claim)| match claim.release_at {
+            Expiration::AtTime(time) if time.seconds() == expires_at => Some((pos, time)),
+            _ => None,
+        })
+        .ok_or(ContractError::ClaimNotFound {})?;
+    if !claims[pos].release_at.is_expired(&env.block) {
+        return Err(ContractError::ClaimNotYetMatured { unlocks_at });
+    }
+
+    let claim = claims.remove(pos);
+    unindex_claim(deps.storage, &info.sender, unlocks_at, claim.amount)?;
+    let exit_vesting = take_exit_vesting(deps.storage, &info.sender, unlocks_at, claim.amount)?;
+    let delegators = take_claim_delegators(deps.storage, &info.sender, unlocks_at, claim.amount)?;
+    if claims.is_empty() {
+        CLAIMS_BY_ADDR.remove(deps.storage, &info.sender);
+    } else {
+        CLAIMS_BY_ADDR.save(deps.storage, &info.sender, &claims)?;
+    }
+
+    release_claimed(
+        deps,
+        &env,
+        info.sender,
+        claim.amount,
+        exit_vesting,
+        delegators,
+        "claim_specific",
+    )
+}
+
+/// Cancels pending claims of the sender and bonds the tokens again.
+///
+/// `cw-controllers` `Claims` can only release matured claims, so the pending ones are edited
+/// through `CLAIMS_BY_ADDR`, the raw storage of `CLAIMS`, keeping the maturity index in sync.
+/// Claims with parts unbonded by other delegators are skipped, as these tokens are delegated by
+/// them in the token contract and cannot become stake of the sender.
+pub fn execute_cancel_claim(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    unbonding_period: u64,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::NoCancelAmount {});
+    }
+    if let Some(unlocks_at) = staking_locked_until(deps.storage, env.block.height)? {
+        return Err(ContractError::StakingLocked { unlocks_at });
+    }
+    assert_claims_not_frozen(deps.storage, &info.sender)?;
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let claims = CLAIMS_BY_ADDR
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let mut remaining = Vec::with_capacity(claims.len());
+    let mut to_cancel = amount;
+    for mut claim in claims {
+        if let Expiration::AtTime(matures_at) = claim.release_at {
+            let cancelable = !claim.release_at.is_expired(&env.block)
+                && !CLAIM_DELEGATORS.has(deps.storage, (&info.sender, matures_at.nanos()));
+            if cancelable && !to_cancel.is_zero() {
+                let canceled = claim.amount.min(to_cancel);
+                unindex_claim(deps.storage, &info.sender, matures_at, canceled)?;
+                // the exit vesting of the canceled part is applied again on the next unbond
+                take_exit_vesting(deps.storage, &info.sender, matures_at, canceled)?;
+                claim.amount -= canceled;
+                to_cancel -= canceled;
+            }
+        }
+        if !claim.amount.is_zero() {
+            remaining.push(claim);
+        }
+    }
+    if !to_cancel.is_zero() {
+        return Err(ContractError::InsufficientPendingClaims {
+            pending: amount - to_cancel,
+            requested: amount,
+        });
+    }
+    if remaining.is_empty() {
+        CLAIMS_BY_ADDR.remove(deps.storage, &info.sender);
+    } else {
+        CLAIMS_BY_ADDR.save(deps.storage, &info.sender, &remaining)?;
+    }
+
+    record_interaction(deps.storage, &env, &info.sender)?;
+    TOTAL_STAKED.update::<_, StdError>(deps.storage, |token_info| {
+        Ok(TokenInfo {
+            staked: token_info.staked,
+            unbonding: token_info.unbonding.saturating_sub(amount),
+        })
+    })?;
+    // the canceled tokens lost their discount on unbond, so it is applied again
+    discount_vesting_bond(deps.branch(), &cfg, &info.sender, amount, unbonding_period)?;
+    let messages = bond_stake(
+        deps.storage,
+        &env,
+        &cfg,
+        &info.sender,
+        amount,
+        unbonding_period,
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("action", "cancel_claim")
+        .add_attribute("amount", amount)
+        .add_attribute("unbonding_period", unbonding_period.to_string())
+        .add_attribute("sender", info.sender))
+}
+
+/// Undelegates the claimed tokens back to the sender.
+/// The `exit_vesting` parts of them are undelegated into a vesting schedule starting now.
+/// The `delegators` parts of them were unbonded by someone else for the sender and are taken
+/// from the delegations of these accounts.
+fn release_claimed(
+    deps: DepsMut,
+    env: &Env,
+    sender: Addr,
+    release: Uint128,
+    exit_vesting: Vec<ExitVesting>,
+    delegators: Vec<(Addr, Uint128)>,
+    action: &str,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_token_undelegatable(deps.as_ref(), env, &config, release)?;
+    let amount_str = coin_to_string(release, config.cw20_contract.as_str());
+
+    // merge parts with the same schedule, to keep the recipient's vesting curve simple
+    let mut vesting_parts: Vec<ExitVesting> = vec![];
+    for part in exit_vesting {
+        match vesting_parts
+            .iter_mut()
+            .find(|existing| existing.schedule == part.schedule)
+        {
+            Some(existing) => existing.amount += part.amount,
+            None => vesting_parts.push(part),
+        }
+    }
+
+    // merge parts of the same delegator, to send one undelegation each
+    let mut delegator_parts: Vec<(Addr, Uint128)> = vec![];
+    for (delegator, amount) in delegators {
+        match delegator_parts
+            .iter_mut()
+            .find(|(existing, _)| *existing == delegator)
+        {
+            Some((_, existing)) => *existing += amount,
+            None => delegator_parts.push((delegator, amount)),
+        }
+    }
+
+    let liquid = vesting_parts
+        .iter()
+        .map(|part| part.amount)
+        .chain(delegator_parts.iter().map(|(_, amount)| *amount))
+        .fold(release, |liquid, amount| liquid.saturating_sub(amount));
+    let mut undelegate_msgs = vec![];
+    if !liquid.is_zero() {
+        undelegate_msgs.push(VestingExecuteMsg::Undelegate {
+            recipient: sender.to_string(),
+            amount: liquid,
+            delegator: None,
+        });
+    }
+    for (delegator, amount) in delegator_parts {
+        undelegate_msgs.push(VestingExecuteMsg::Undelegate {
+            recipient: sender.to_string(),
+            amount,
+            delegator: Some(delegator.into_string()),
+        });
+    }
+    for part in vesting_parts {
+        undelegate_msgs.push(VestingExecuteMsg::UndelegateVesting {
+            recipient: sender.to_string(),
+            amount: part.amount,
+            schedule: part
+                .schedule
+                .scale(part.amount)
+                .shift_right(env.block.time.seconds()),
+        });
+    }
+    let undelegate_msgs = undelegate_msgs
+        .into_iter()
+        .map(|msg| {
+            Ok(SubMsg::new(WasmMsg::Execute {
+                contract_addr: config.cw20_contract.to_string(),
+                msg: to_binary(&msg)?,
+                funds: vec![],
+            }))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    TOTAL_STAKED.update::<_, StdError>(deps.storage, |token_info| {
+        Ok(TokenInfo {
+            staked: token_info.staked,
+            unbonding: token_info.unbonding.saturating_sub(release),
+        })
+    })?;
+
+    Ok(Response::new()
+        .add_submessages(undelegate_msgs)
+        .add_attribute("action", action)
+        .add_attribute("tokens", amount_str)
+        .add_attribute("sender", sender))
+}
+
+/// Tokens of `addr` currently locked by its vesting schedule on the token contract
+fn query_vesting_locked(deps: Deps, config: &Config, addr: &Addr) -> StdResult<Uint128> {
+    let query = cw20_vesting::QueryMsg::Vesting {
+        address: addr.to_string(),
+    };
+    let vesting: VestingResponse = deps
+        .querier
+        .query_wasm_smart(&config.cw20_contract, &query)?;
+    Ok(vesting.locked)
+}
+
+/// Fails early if the token contract would reject undelegating `requested` tokens, instead of
+/// reverting with the raw error of the undelegation submessage.
+/// Token contracts not supporting the query are not checked.
+fn assert_token_undelegatable(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    requested: Uint128,
+) -> Result<(), ContractError> {
+    let query = cw20_vesting::QueryMsg::UndelegatableAmount {
+        staking: Some(env.contract.address.to_string()),
+    };
+    let res: StdResult<UndelegatableAmountResponse> =
+        deps.querier.query_wasm_smart(&config.cw20_contract, &query);
+    match res {
+        Ok(UndelegatableAmountResponse { amount, .. }) if amount < requested => {
+            Err(ContractError::TokenSideBlocked {
+                available: amount,
+                requested,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[inline]
+fn coin_to_string(amount: Uint128, address: &str) -> String {
+    format!("{} {}", amount, address)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Claims { address } => {
+            to_binary(&CLAIMS.query_claims(deps, &deps.api.addr_validate(&address)?)?)
+        }
+        QueryMsg::Staked {
+            address,
+            unbonding_period,
+        } => to_binary(&query_staked(deps, &env, address, unbonding_period)?),
+        QueryMsg::BondingInfo {} => to_binary(&query_bonding_info(deps)?),
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::AllStaked {
+            address,
+            start_after,
+            limit,
+            include_empty,
+        } => to_binary(&query_all_staked(
+            deps,
+            env,
+            address,
+            start_after,
+            limit,
+            include_empty.unwrap_or(true),
+        )?),
+        QueryMsg::TotalStaked {} => to_binary(&query_total_staked(deps)?),
+        QueryMsg::TotalUnbonding {} => to_binary(&query_total_unbonding(deps)?),
+        QueryMsg::DelegationReport {} => to_binary(&query_delegation_report(deps, env)?),
+        QueryMsg::AllDelegations { start_after, limit } => {
+            to_binary(&query_all_delegations(deps, start_after, limit)?)
+        }
+        QueryMsg::Admin {} => to_binary(&ADMIN.query_admin(deps)?),
+        QueryMsg::AdminStatus {} => to_binary(&query_admin_status(deps)?),
+        QueryMsg::Hooks {} => to_binary(&query_hooks(deps)?),
+        QueryMsg::VotingPowerAtHeight { address, height } => {
+            to_binary(&query_voting_power(deps, env, address, height)?)
+        }
+        QueryMsg::TotalPowerAtHeight { height } => {
+            to_binary(&query_total_power(deps, env, height)?)
+        }
+        QueryMsg::Info {} => to_binary(&query_info(deps)?),
+        QueryMsg::TokenContract {} => to_binary(&query_token_contract(deps)?),
+        QueryMsg::TotalRewards {} => to_binary(&query_total_rewards(deps)?),
+        QueryMsg::Rewards { address } => to_binary(&query_rewards(deps, address)?),
+        QueryMsg::WithdrawableRewards { owner } => {
+            to_binary(&query_withdrawable_rewards(deps, env, owner)?)
+        }
+        QueryMsg::DistributedRewards {} => to_binary(&query_distributed_rewards(deps, env)?),
+        QueryMsg::UndistributedRewards {} => to_binary(&query_undistributed_rewards(deps, env)?),
+        QueryMsg::CanDistribute {} => to_binary(&query_can_distribute(deps)?),
+        QueryMsg::Delegated { owner } => to_binary(&query_delegated(deps, owner)?),
+        QueryMsg::PayoutAddress { owner } => to_binary(&query_payout_address(deps, owner)?),
+        QueryMsg::DistributionData {} => to_binary(&DISTRIBUTION.may_load(deps.storage)?),
+        QueryMsg::RewardStream {} => to_binary(&REWARD_STREAM.may_load(deps.storage)?),
+        QueryMsg::WithdrawAdjustmentData { addr } => {
+            to_binary(&query_withdraw_adjustment_data(deps, addr)?)
+        }
+        QueryMsg::DistributionWeight { address } => {
+            to_binary(&query_distribution_weight(deps, address)?)
+        }
+        QueryMsg::RecomputeStatus {} => to_binary(&query_recompute_status(deps)?),
+        QueryMsg::MigrationProgress {} => to_binary(&query_migration_progress(deps)?),
+        QueryMsg::FirstStaked { address } => to_binary(&query_first_staked(deps, address)?),
+        QueryMsg::MatureClaims { start_after, limit } => {
+            to_binary(&query_mature_claims(deps, env, start_after, limit)?)
+        }
+        QueryMsg::GovernanceContracts {} => to_binary(&GovernanceContractsResponse {
+            contracts: GOVERNANCE_CONTRACTS
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        }),
+        QueryMsg::AdminMultisig {} => to_binary(&AdminMultisigResponse {
+            addresses: ADMIN_MULTISIG.may_load(deps.storage)?.unwrap_or_default(),
+            min_approvals: MIN_APPROVALS.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::AdminProposal { proposal_id } => {
+            to_binary(&ADMIN_PROPOSALS.load(deps.storage, proposal_id)?)
+        }
+        QueryMsg::IsStakingLocked {} => to_binary(&query_staking_lock(deps, env)?),
+        QueryMsg::PowerExclusions { start_after, limit } => {
+            to_binary(&query_power_exclusions(deps, start_after, limit)?)
+        }
+        QueryMsg::ListMembersCw4 { start_after, limit } => {
+            to_binary(&query_list_members_cw4(deps, start_after, limit)?)
+        }
+        QueryMsg::LoyaltyBonus { address } => to_binary(&query_loyalty_bonus(deps, env, address)?),
+        QueryMsg::DistributionLog {
+            start_after,
+            limit,
+            descending,
+        } => to_binary(&query_distribution_log(
+            deps,
+            start_after,
+            limit,
+            descending,
+        )?),
+        QueryMsg::Label { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&LabelResponse {
+                label: LABELS.may_load(deps.storage, &address)?,
+            })
+        }
+        QueryMsg::AllLabels { start_after, limit } => {
+            to_binary(&query_all_labels(deps, start_after, limit)?)
+        }
+        QueryMsg::ScheduledRebonds { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            to_binary(&ScheduledRebondsResponse {
+                rebonds: SCHEDULED_REBONDS
+                    .may_load(deps.storage, &addr)?
+                    .unwrap_or_default(),
+            })
+        }
+        QueryMsg::ClaimsFrozen { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            to_binary(&ClaimsFrozenResponse {
+                frozen: CLAIMS_FROZEN.has(deps.storage, &addr),
+            })
+        }
+        QueryMsg::RewardsBreakdown { address } => {
+            to_binary(&query_rewards_breakdown(deps, address)?)
+        }
+        QueryMsg::RawKeyLayout {} => to_binary(&RawKeyLayoutResponse {
+            layout: storage_layout(),
+        }),
+    }
+}
+
+pub fn query_loyalty_bonus(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<LoyaltyBonusResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let epochs = loyalty_epochs(deps.storage, &addr, env.block.time)?;
+    Ok(LoyaltyBonusResponse {
+        epochs,
+        bonus_pct: cfg.loyalty_bonus_pct(epochs),
+    })
+}
+
+pub fn query_staking_lock(deps: Deps, env: Env) -> StdResult<StakingLockResponse> {
+    let unlocks_at = staking_locked_until(deps.storage, env.block.height)?;
+    Ok(StakingLockResponse {
+        locked: unlocks_at.is_some(),
+        unlocks_at,
+    })
+}
+
+const DEFAULT_CLAIMS_LIMIT: u32 = 10;
+const MAX_CLAIMS_LIMIT: u32 = 30;
+
+fn query_mature_claims(
+    deps: Deps,
+    env: Env,
+    start_after: Option<(Timestamp, String)>,
+    limit: Option<u32>,
+) -> StdResult<MatureClaimsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_CLAIMS_LIMIT).min(MAX_CLAIMS_LIMIT) as usize;
+    let start_after = start_after
+        .map(|(matured_at, addr)| -> StdResult<_> {
+            Ok((matured_at.nanos(), deps.api.addr_validate(&addr)?))
+        })
+        .transpose()?;
+    let min = start_after
+        .as_ref()
+        .map(|(nanos, addr)| Bound::exclusive((*nanos, addr)));
+
+    // the index is ordered by maturity, so stop at the first claim that is not matured yet
+    let now = env.block.time.nanos();
+    let claims = CLAIMS_BY_MATURITY
+        .range(deps.storage, min, None, Order::Ascending)
+        .take_while(|item| !matches!(item, Ok(((matures_at, _), _)) if *matures_at > now))
+        .take(limit)
+        .map(|item| {
+            let ((matured_at, address), amount) = item?;
+            Ok(MatureClaim {
+                address,
+                amount,
+                matured_at: Timestamp::from_nanos(matured_at),
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(MatureClaimsResponse { claims })
+}
+
+fn query_recompute_status(deps: Deps) -> StdResult<RecomputeStatusResponse> {
+    let progress = PENDING_RECOMPUTE.may_load(deps.storage)?;
+    Ok(RecomputeStatusResponse {
+        pending: progress.is_some(),
+        last_processed: progress
+            .as_ref()
+            .and_then(|progress| progress.last_processed.clone()),
+        processed: progress
+            .map(|progress| progress.processed)
+            .unwrap_or_default(),
+    })
+}
+
+fn query_migration_progress(deps: Deps) -> StdResult<MigrationProgressResponse> {
+    let progress = MIGRATION_PROGRESS.may_load(deps.storage)?;
+    Ok(MigrationProgressResponse {
+        in_progress: progress.is_some(),
+        storage_version: STORAGE_VERSION_MARKER
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        target_version: progress.as_ref().map(|progress| progress.target_version),
+        last_processed: progress
+            .as_ref()
+            .and_then(|progress| progress.last_processed.clone()),
+        processed: progress
+            .map(|progress| progress.processed)
+            .unwrap_or_default(),
+    })
+}
+
+fn query_first_staked(deps: Deps, address: String) -> StdResult<FirstStakedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(FirstStakedResponse {
+        first_staked: FIRST_STAKED.may_load(deps.storage, &address)?,
+    })
+}
+
+fn query_voting_power(
+    deps: Deps,
+    env: Env,
+    addr: String,
+    height: Option<u64>,
+) -> StdResult<VotingPowerAtHeightResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    let power = if POWER_EXCLUDED.has(deps.storage, &addr) {
+        Uint128::zero()
+    } else {
+        member_power(deps, &addr, height)?
+    };
+    let height = height.unwrap_or(env.block.height);
+
+    Ok(VotingPowerAtHeightResponse { power, height })
+}
+
+fn query_total_power(
+    deps: Deps,
+    env: Env,
+    height: Option<u64>,
+) -> StdResult<TotalPowerAtHeightResponse> {
+    let power = match height {
+        Some(h) => TOTAL_VOTES.may_load_at_height(deps.storage, h),
+        None => TOTAL_VOTES.may_load(deps.storage),
+    }?
+    .unwrap_or_default();
+
+    // the exclusion list is not snapshotted, so the current list applies to all heights
+    let excluded = POWER_EXCLUDED
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|addr| member_power(deps, &addr?, height))
+        .sum::<StdResult<Uint128>>()?;
+    let power = power.saturating_sub(excluded);
+
+    let height = height.unwrap_or(env.block.height);
+
+    Ok(TotalPowerAtHeightResponse { power, height })
+}
+
+/// Raw voting power of the member at the given height, ignoring exclusions
+fn member_power(deps: Deps, addr: &Addr, height: Option<u64>) -> StdResult<Uint128> {
+    // until the staged migration reaches a member, their power is still under the cw4 key,
+    // where the snapshot does not look for it
+    if MIGRATION_PROGRESS.may_load(deps.storage)?.is_some()
+        && !MEMBERS_PRIMARY.has(deps.storage, addr)
+    {
+        let change = match height {
+            Some(h) => MEMBERS
+                .changelog()
+                .prefix(addr)
+                .range(
+                    deps.storage,
+                    Some(Bound::inclusive(h)),
+                    None,
+                    Order::Ascending,
+                )
+                .next()
+                .transpose()?,
+            None => None,
+        };
+        let power = match change {
+            Some((_, change)) => change.old,
+            None => LEGACY_MEMBERS.may_load(deps.storage, addr)?,
+        };
+        return Ok(power.unwrap_or_default());
+    }
+
+    let power = match height {
+        Some(h) => MEMBERS.may_load_at_height(deps.storage, addr, h),
+        None => MEMBERS.may_load(deps.storage, addr),
+    }?;
+    Ok(power.unwrap_or_default())
+}
+
+const DEFAULT_MEMBERS_LIMIT: u32 = 10;
+const MAX_MEMBERS_LIMIT: u32 = 30;
+
+fn query_list_members_cw4(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<MemberListResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_MEMBERS_LIMIT)
+        .min(MAX_MEMBERS_LIMIT) as usize;
+    let start_after = maybe_addr(deps.api, start_after)?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    // the cw4 keys list all members, even while the staged migration is moving their powers.
+    // Their values are not read, as they are `Uint128` until then.
+    let members = CW4_MEMBERS
+        .keys_raw(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|key| {
+            let addr = Addr::from_vec(key)?;
+            let weight = if POWER_EXCLUDED.has(deps.storage, &addr) {
+                0
+            } else {
+                cw4_weight(member_power(deps, &addr, None)?)
+            };
+            Ok(Member {
+                addr: addr.into(),
+                weight,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(MemberListResponse { members })
+}
+
+const DEFAULT_LABELS_LIMIT: u32 = 10;
+const MAX_LABELS_LIMIT: u32 = 30;
+
+fn query_all_labels(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllLabelsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LABELS_LIMIT).min(MAX_LABELS_LIMIT) as usize;
+    let start_after = maybe_addr(deps.api, start_after)?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let labels = LABELS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, label) = item?;
+            Ok(AddressLabel { address, label })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(AllLabelsResponse { labels })
+}
+
+const DEFAULT_EXCLUSIONS_LIMIT: u32 = 10;
+const MAX_EXCLUSIONS_LIMIT: u32 = 30;
+
+fn query_power_exclusions(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PowerExclusionsResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_EXCLUSIONS_LIMIT)
+        .min(MAX_EXCLUSIONS_LIMIT) as usize;
+    let start_after = maybe_addr(deps.api, start_after)?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let addresses = POWER_EXCLUDED
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    Ok(PowerExclusionsResponse { addresses })
+}
+
+fn query_rewards(deps: Deps, addr: String) -> StdResult<RewardsResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    Ok(RewardsResponse {
+        rewards: REWARDS.may_load(deps.storage, &addr)?.unwrap_or_default(),
+    })
+}
+
+fn query_rewards_breakdown(deps: Deps, addr: String) -> StdResult<RewardsBreakdownResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let periods = STAKE
+        .prefix(&addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|stake| {
+            let (unbonding_period, bonding_info) = stake?;
+            let stake = bonding_info.total_stake();
+            let reward_multiplier = STAKE_CONFIG.load(deps.storage, unbonding_period)?.reward;
+            Ok(RewardsBreakdownEntry {
+                unbonding_period,
+                stake,
+                reward_multiplier,
+                reward_power: calc_power(&cfg, stake, reward_multiplier),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let weight = DISTRIBUTION_WEIGHT
+        .may_load(deps.storage, &addr)?
+        .unwrap_or_else(Decimal::one);
+    let total = truncate_power(
+        periods.iter().map(|p| p.reward_power).sum::<Decimal256>() * decimal256(weight),
+    )?;
+    let cached = REWARDS.may_load(deps.storage, &addr)?.unwrap_or_default();
+
+    Ok(RewardsBreakdownResponse {
+        periods,
+        weight,
+        total,
+        cached,
+        stale: total != cached,
+    })
+}
+
+fn query_total_rewards(deps: Deps) -> StdResult<TotalRewardsResponse> {
+    Ok(TotalRewardsResponse {
+        rewards: TOTAL_REWARDS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn query_bonding_info(deps: Deps) -> StdResult<BondingInfoResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let bonding = config
+        .unbonding_periods
+        .into_iter()
+        .filter_map(|up| match STAKE_CONFIG.may_load(deps.storage, up) {
+            Ok(Some(multipliers)) => Some(Ok(BondingPeriodInfo {
+                voting_multiplier: multipliers.voting,
+                reward_multiplier: multipliers.reward,
+                unbonding_period: up,
+                total_staked: multipliers.staked,
+                exit_vesting: multipliers.exit_vesting,
+            })),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .collect::<StdResult<Vec<BondingPeriodInfo>>>()?;
+    Ok(BondingInfoResponse { bonding })
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let totals = TOTAL_STAKED.load(deps.storage).unwrap_or_default();
+    Ok(ConfigResponse {
+        cw20_contract: config.cw20_contract,
+        admin: ADMIN.get(deps)?,
+        tokens_per_power: config.tokens_per_power,
+        min_bond: config.min_bond,
+        bonding: query_bonding_info(deps)?.bonding,
+        total_staked: totals.staked,
+        total_unbonding: totals.unbonding,
+        slash_destination: config.slash_destination,
+        vesting_voting_discount: config.vesting_voting_discount,
+        version: get_contract_version(deps.storage)?,
+    })
+}
+
+fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+    let info = get_contract_version(deps.storage)?;
+    Ok(cw_core_interface::voting::InfoResponse { info })
+}
+
+fn query_token_contract(deps: Deps) -> StdResult<Addr> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(cfg.cw20_contract)
+}
+
+pub fn query_staked(
+    deps: Deps,
+    env: &Env,
+    addr: String,
+    unbonding_period: u64,
+) -> StdResult<StakedResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    // sanity check if such unbonding period exists
+    STAKE_CONFIG
+        .load(deps.storage, unbonding_period)
+        .map_err(|_| {
+            StdError::generic_err(format!("No unbonding period found: {}", unbonding_period))
+        })?;
+    let stake = STAKE
+        .may_load(deps.storage, (&addr, unbonding_period))?
+        .unwrap_or_default();
+    let cw20_contract = CONFIG.load(deps.storage)?.cw20_contract.to_string();
+    Ok(StakedResponse {
+        stake: stake.total_stake(),
+        total_locked: stake.total_locked(env),
+        unbonding_period,
+        cw20_contract,
+        earliest_unlock: stake.earliest_unlock(env),
+    })
+}
+
+pub fn query_all_staked(
+    deps: Deps,
+    env: Env,
+    addr: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    include_empty: bool,
+) -> StdResult<AllStakedResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    let config = CONFIG.load(deps.storage)?;
+    let cw20_contract = config.cw20_contract.to_string();
+    let limit = limit.map_or(usize::MAX, |l| l as usize);
+
+    let stakes = config
+        .unbonding_periods
+        .into_iter()
+        .filter(|up| !matches!(start_after, Some(start) if *up <= start))
+        .filter_map(|up| match STAKE.may_load(deps.storage, (&addr, up)) {
+            Ok(Some(stake)) if include_empty || !stake.total_stake().is_zero() => {
+                Some(Ok(StakedResponse {
+                    stake: stake.total_stake(),
+                    total_locked: stake.total_locked(&env),
+                    unbonding_period: up,
+                    cw20_contract: cw20_contract.clone(),
+                    earliest_unlock: stake.earliest_unlock(&env),
+                }))
+            }
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .take(limit)
+        .collect::<StdResult<Vec<StakedResponse>>>()?;
+
+    Ok(AllStakedResponse { stakes })
+}
+
+pub fn query_total_staked(deps: Deps) -> StdResult<TotalStakedResponse> {
+    let total_staked = TOTAL_STAKED.load(deps.storage).unwrap_or_default().staked;
+    let by_period = STAKE_CONFIG
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (unbonding_period, multipliers) = item?;
+            Ok(PeriodStaked {
+                unbonding_period,
+                staked: multipliers.staked,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let sum = by_period.iter().try_fold(Uint128::zero(), |sum, period| {
+        sum.checked_add(period.staked)
+    });
+
+    Ok(TotalStakedResponse {
+        total_staked,
+        consistent: sum == Ok(total_staked),
+        by_period,
+    })
+}
+
+pub fn query_total_unbonding(deps: Deps) -> StdResult<TotalUnbondingResponse> {
+    Ok(TotalUnbondingResponse {
+        total_unbonding: TOTAL_STAKED
+            .load(deps.storage)
+            .unwrap_or_default()
+            .unbonding,
+    })
+}
+
+const DEFAULT_DELEGATIONS_LIMIT: u32 = 10;
+const MAX_DELEGATIONS_LIMIT: u32 = 30;
+
+fn query_all_delegations(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllDelegationsResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_DELEGATIONS_LIMIT)
+        .min(MAX_DELEGATIONS_LIMIT) as usize;
+    let start_after = maybe_addr(deps.api, start_after)?;
+
+    let mut delegations: Vec<DelegationInfo> = vec![];
+    // fully unbonded stakers are skipped, but count towards the limit, so a query never goes
+    // through more than `limit` stakers
+    let mut scanned = 0;
+    let mut last_scanned: Option<Addr> = None;
+    let mut finished = true;
+    for stake in STAKE.prefix_range(
+        deps.storage,
+        start_after.as_ref().map(PrefixBound::exclusive),
+        None,
+        Order::Ascending,
+    ) {
+        let ((address, _), bonding_info) = stake?;
+        if last_scanned.as_ref() != Some(&address) {
+            if scanned == limit {
+                finished = false;
+                break;
+            }
+            scanned += 1;
+            last_scanned = Some(address.clone());
+        }
+        let stake = bonding_info.total_stake();
+        match delegations.last_mut() {
+            Some(last) if last.address == address => last.stake += stake,
+            _ if stake.is_zero() => {}
+            _ => delegations.push(DelegationInfo {
+                label: LABELS.may_load(deps.storage, &address)?,
+                address,
+                stake,
+            }),
+        }
+    }
+
+    Ok(AllDelegationsResponse {
+        delegations,
+        next_start_after: if finished { None } else { last_scanned },
+    })
+}
+
+pub fn query_admin_status(deps: Deps) -> StdResult<AdminStatusResponse> {
+    let renounced_at = ADMIN_RENOUNCED.may_load(deps.storage)?;
+    Ok(AdminStatusResponse {
+        admin: ADMIN.get(deps)?,
+        renounced: renounced_at.is_some(),
+        renounced_at,
+    })
+}
+
+pub fn query_hooks(deps: Deps) -> StdResult<HooksResponse> {
+    let max_hooks = CONFIG.load(deps.storage)?.max_hooks;
+    let hook_configs = HOOKS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|hook| {
+            let (addr, hook) = hook?;
+            Ok(HookConfigResponse {
+                addr: addr.into(),
+                gas_limit: hook.gas_limit,
+                failures: hook.failures,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(HooksResponse {
+        hooks: hook_configs.iter().map(|hook| hook.addr.clone()).collect(),
+        max_hooks,
+        hook_configs,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let addr = HOOK_REPLY_IDS
+        .may_load(deps.storage, msg.id)?
+        .ok_or(ContractError::UnknownReplyId { id: msg.id })?;
+    match msg.result {
+        SubMsgResult::Err(err) => {
+            HOOKS.update(deps.storage, &addr, |hook| -> StdResult<_> {
+                let mut hook = hook.ok_or_else(|| StdError::not_found("HookConfig"))?;
+                hook.failures += 1;
+                Ok(hook)
+            })?;
+            Ok(Response::new()
+                .add_attribute("action", "hook_failed")
+                .add_attribute("hook", addr)
+                .add_attribute("error", err))
+        }
+        SubMsgResult::Ok(_) => Ok(Response::new()),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // move hooks registered by older versions to the new storage, without a gas limit
+    if let Some(legacy_hooks) = LEGACY_HOOKS.may_load(deps.storage)? {
+        let mut reply_id = LAST_HOOK_REPLY_ID
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        for addr in legacy_hooks {
+            reply_id += 1;
+            HOOK_REPLY_IDS.save(deps.storage, reply_id, &addr)?;
+            HOOKS.save(
+                deps.storage,
+                &addr,
+                &HookConfig {
+                    gas_limit: None,
+                    reply_id,
+                    failures: 0,
+                },
+            )?;
+        }
+        LAST_HOOK_REPLY_ID.save(deps.storage, &reply_id)?;
+        LEGACY_HOOKS.remove(deps.storage);
+    }
+
+    // older versions stored the distribution leftover as a 64-bit number
+    if let Ok(legacy) = LEGACY_DISTRIBUTION.load(deps.storage) {
+        DISTRIBUTION.save(deps.storage, &legacy.into())?;
+    }
+
+    // older versions stored the total power under the cw4 key, which holds the `u64` weight now.
+    // Powers of members are moved by the staged migration below.
+    if let Ok(Some(total)) = LEGACY_TOTAL_VOTES.may_load(deps.storage) {
+        TOTAL_VOTES_PRIMARY.save(deps.storage, &total)?;
+        CW4_TOTAL.save(deps.storage, &cw4_weight(total))?;
+    }
+
+    // older versions stored truncated powers per stake, which are still readable as decimals,
+    // but need a recompute to become exact
+    if PENDING_RECOMPUTE.may_load(deps.storage)?.is_none() {
+        PENDING_RECOMPUTE.save(deps.storage, &RecomputeProgress::default())?;
+    }
+
+    // back-filling data for all stakers could exceed the gas limit of a single migration, so it
+    // is done in stages by `ContinueMigration`, blocking everything else until it is finished
+    let storage_version = STORAGE_VERSION_MARKER
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if storage_version < STORAGE_VERSION {
+        MIGRATION_PROGRESS.save(
+            deps.storage,
+            &MigrationProgress {
+                target_version: STORAGE_VERSION,
+                ..MigrationProgress::default()
+            },
+        )?;
+    }
+
+    Ok(Response::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{attr, from_slice, CosmosMsg, Decimal, Storage};
+    use cw2::ContractVersion;
+    use cw4::{member_key, Member, MemberListResponse, TOTAL_KEY};
+    use cw_controllers::{AdminError, Claim, HookError};
+    use cw_utils::Duration;
+    use test_case::test_case;
+
+    use cw_storage_plus::{Map, PrimaryKey};
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    use crate::error::ContractError;
+    use crate::msg::{KeyEncoding, StakeConfig, StorageLayout, WithdrawAdjustmentDataResponse};
+    use crate::state::{
+        Distribution, WithdrawAdjustment, DISTRIBUTION_LOG, LOYALTY_EPOCH, NEXT_DISTRIBUTION_SEQ,
+        PAYOUT_ADDRESS, REWARD_ACTIVITY, SHARES_PER_POINT_HISTORY, SHARES_RESET_AT,
+        WITHDRAW_ADJUSTMENT,
+    };
+    use crate::state_keys;
+
+    use super::*;
+
+    const INIT_ADMIN: &str = "admin";
+    const USER1: &str = "user1";
+    const USER2: &str = "user2";
+    const USER3: &str = "user3";
+    const TOKENS_PER_POWER: Uint128 = Uint128::new(1_000);
+    const MIN_BOND: Uint128 = Uint128::new(5_000);
+    const UNBONDING_BLOCKS: u64 = 100;
+    const UNBONDING_PERIOD: u64 = UNBONDING_BLOCKS / 5;
+    const UNBONDING_PERIOD_2: u64 = 2 * UNBONDING_PERIOD;
+    const CW20_ADDRESS: &str = "wasm1234567890";
+
+    #[test]
+    fn check_crate_name() {
+        assert_eq!(CONTRACT_NAME, "crates.io:wynd_stake");
+    }
+
+    #[test]
+    fn invalid_exit_vesting_rejected() {
+        let mut deps = mock_dependencies();
+        let exit_vesting = [
+            // never fully vested
+            ScalableCurve::linear((0, 100), (1000, 10)),
+            // increasing
+            ScalableCurve::linear((0, 0), (1000, 100)),
+            // vests more than claimed
+            ScalableCurve::linear((0, 150), (1000, 0)),
+        ];
+        for exit_vesting in exit_vesting {
+            let msg = InstantiateMsg {
+                cw20_contract: CW20_ADDRESS.to_owned(),
+                tokens_per_power: TOKENS_PER_POWER,
+                min_bond: MIN_BOND,
+                stake_config: vec![StakeConfig {
+                    unbonding_period: UNBONDING_PERIOD,
+                    voting_multiplier: Decimal::one(),
+                    reward_multiplier: Decimal::one(),
+                    exit_vesting: Some(exit_vesting),
+                }],
+                admin: None,
+                max_hooks: None,
+                distribution_fee: None,
+                vesting_voting_discount: None,
+            };
+            let err =
+                instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidExitVesting {
+                    unbonding_period: UNBONDING_PERIOD
+                }
+            );
+        }
+    }
+
+    fn stake_config(unbonding_period: u64, voting: u64, reward: u64) -> StakeConfig {
+        StakeConfig {
+            unbonding_period,
+            voting_multiplier: Decimal::percent(voting),
+            reward_multiplier: Decimal::percent(reward),
+            exit_vesting: None,
+        }
+    }
+
+    #[test_case(vec![] => ContractError::NoUnbondingPeriods {}; "empty")]
+    #[test_case(vec![stake_config(0, 100, 100)] => ContractError::ZeroUnbondingPeriod {}; "zero period")]
+    #[test_case(vec![stake_config(20, 100, 100), stake_config(10, 100, 100), stake_config(20, 200, 200)]
+        => ContractError::DuplicateUnbondingPeriod { unbonding_period: 20 }; "duplicate period")]
+    #[test_case(vec![stake_config(10, 0, 100)] => ContractError::ZeroMultiplier { unbonding_period: 10 }; "zero voting multiplier")]
+    #[test_case(vec![stake_config(10, 100, 0)] => ContractError::ZeroMultiplier { unbonding_period: 10 }; "zero reward multiplier")]
+    #[test_case(vec![stake_config(10, 1001, 100)]
+        => ContractError::MultiplierTooHigh { unbonding_period: 10, max: Decimal::percent(1000) }; "voting multiplier too high")]
+    #[test_case(vec![stake_config(10, 100, 1001)]
+        => ContractError::MultiplierTooHigh { unbonding_period: 10, max: Decimal::percent(1000) }; "reward multiplier too high")]
+    fn invalid_stake_config_rejected(stake_config: Vec<StakeConfig>) -> ContractError {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            cw20_contract: CW20_ADDRESS.to_owned(),
+            tokens_per_power: TOKENS_PER_POWER,
+            min_bond: MIN_BOND,
+            stake_config,
+            admin: None,
+            max_hooks: None,
+            distribution_fee: None,
+            vesting_voting_discount: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err()
+    }
+
+    #[test]
+    fn stake_config_sorted_on_instantiate() {
+        let mut deps = mock_dependencies();
+        cw20_instantiate(
+            deps.as_mut(),
+            mock_env(),
+            TOKENS_PER_POWER,
+            MIN_BOND,
+            vec![
+                stake_config(30, 300, 300),
+                stake_config(10, 100, 100),
+                stake_config(20, 1000, 1000),
+            ],
+        );
+        let cfg = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(cfg.unbonding_periods, vec![10, 20, 30]);
+        let multiplier = STAKE_CONFIG.load(&deps.storage, 20).unwrap().voting;
+        assert_eq!(multiplier, Decimal::percent(1000));
+    }
+
+    #[test]
+    fn rebond_to_optimal() {
+        let mut deps = mock_dependencies();
+        cw20_instantiate(
+            deps.as_mut(),
+            mock_env(),
+            TOKENS_PER_POWER,
+            MIN_BOND,
+            vec![
+                stake_config(10, 100, 300),
+                stake_config(20, 200, 200),
+                stake_config(30, 200, 100),
+            ],
+        );
+        bond_cw20_with_period(deps.as_mut(), 4_000, 0, 0, 10, 1);
+        bond_cw20_with_period(deps.as_mut(), 8_000, 0, 0, 30, 1);
+
+        let rebond = |deps: DepsMut, bond_from: u64, optimize_for: OptimizeFor| {
+            let msg = ExecuteMsg::RebondToOptimal {
+                bond_from,
+                amount: Uint128::new(4_000),
+                optimize_for,
+            };
+            execute(deps, mock_env(), mock_info(USER1, &[]), msg)
+        };
+        let stake = |deps: Deps, period: u64| {
+            query_staked(deps, &mock_env(), USER1.to_owned(), period)
+                .unwrap()
+                .stake
+                .u128()
+        };
+
+        // shortest of the periods sharing the highest voting multiplier
+        let res = rebond(deps.as_mut(), 10, OptimizeFor::Voting).unwrap();
+        assert_eq!(res.attributes[3], attr("bond_to", "20"));
+        assert_eq!(stake(deps.as_ref(), 10), 0);
+        assert_eq!(stake(deps.as_ref(), 20), 4_000);
+
+        // highest reward multiplier
+        let res = rebond(deps.as_mut(), 30, OptimizeFor::Rewards).unwrap();
+        assert_eq!(res.attributes[3], attr("bond_to", "10"));
+        assert_eq!(stake(deps.as_ref(), 10), 4_000);
+        assert_eq!(stake(deps.as_ref(), 30), 4_000);
+
+        for (bond_from, optimize_for) in [(30, OptimizeFor::Voting), (20, OptimizeFor::Voting)] {
+            let err = rebond(deps.as_mut(), bond_from, optimize_for).unwrap_err();
+            assert_eq!(err, ContractError::AlreadyOptimal {});
+        }
+        let err = rebond(deps.as_mut(), 40, OptimizeFor::Voting).unwrap_err();
+        assert_eq!(err, ContractError::NoUnbondingPeriodFound(40));
+    }
+
+    #[test]
+    fn all_delegations_sum_periods() {
+        let mut deps = mock_dependencies();
+        cw20_instantiate(
+            deps.as_mut(),
+            mock_env(),
+            TOKENS_PER_POWER,
+            MIN_BOND,
+            vec![
+                stake_config(UNBONDING_PERIOD, 100, 100),
+                stake_config(UNBONDING_PERIOD_2, 200, 200),
+            ],
+        );
+        bond_cw20_with_period(deps.as_mut(), 1_000, 2_000, 3_000, UNBONDING_PERIOD, 1);
+        bond_cw20_with_period(deps.as_mut(), 500, 0, 700, UNBONDING_PERIOD_2, 2);
+        // user2 fully unbonds and is not listed anymore
+        unbond(deps.as_mut(), 0, 2_000, 0, 3);
+
+        let delegations = |start_after: Option<&str>, limit: u32| {
+            let resp =
+                query_all_delegations(deps.as_ref(), start_after.map(str::to_owned), Some(limit))
+                    .unwrap();
+            let delegations = resp
+                .delegations
+                .into_iter()
+                .map(|d| (d.address.into_string(), d.stake.u128()))
+                .collect::<Vec<_>>();
+            (delegations, resp.next_start_after.map(Addr::into_string))
+        };
+        assert_eq!(
+            delegations(None, 1),
+            (vec![(USER1.to_owned(), 1_500)], Some(USER1.to_owned()))
+        );
+        assert_eq!(
+            delegations(Some(USER1), 10),
+            (vec![(USER3.to_owned(), 3_700)], None)
+        );
+        assert_eq!(delegations(Some(USER3), 10), (vec![], None));
+        // the unbonded staker counts towards the limit
+        assert_eq!(
+            delegations(Some(USER1), 1),
+            (vec![], Some(USER2.to_owned()))
+        );
+        assert_eq!(
+            delegations(Some(USER2), 1),
+            (vec![(USER3.to_owned(), 3_700)], None)
+        );
+    }
+
+    #[test]
+    fn total_staked_by_period() {
+        let mut deps = mock_dependencies();
+        cw20_instantiate(
+            deps.as_mut(),
+            mock_env(),
+            TOKENS_PER_POWER,
+            MIN_BOND,
+            vec![
+                stake_config(UNBONDING_PERIOD, 100, 100),
+                stake_config(UNBONDING_PERIOD_2, 200, 200),
+            ],
+        );
+        bond_cw20_with_period(deps.as_mut(), 1_000, 2_000, 3_000, UNBONDING_PERIOD, 1);
+        bond_cw20_with_period(deps.as_mut(), 500, 0, 700, UNBONDING_PERIOD_2, 2);
+        unbond(deps.as_mut(), 0, 2_000, 0, 3);
+        rebond_with_period(
+            deps.as_mut(),
+            100,
+            0,
+            0,
+            UNBONDING_PERIOD,
+            UNBONDING_PERIOD_2,
+            4,
+        );
+
+        let total = query_total_staked(deps.as_ref()).unwrap();
+        assert_eq!(
+            total,
+            TotalStakedResponse {
+                total_staked: Uint128::new(5_200),
+                by_period: vec![
+                    PeriodStaked {
+                        unbonding_period: UNBONDING_PERIOD,
+                        staked: Uint128::new(3_900),
+                    },
+                    PeriodStaked {
+                        unbonding_period: UNBONDING_PERIOD_2,
+                        staked: Uint128::new(1_300),
+                    },
+                ],
+                consistent: true,
+            }
+        );
+
+        // a drift between the per period and overall totals is flagged
+        STAKE_CONFIG
+            .update(deps.as_mut().storage, UNBONDING_PERIOD_2, |config| {
+                let mut config = config.unwrap();
+                config.staked += Uint128::new(1);
+                StdResult::Ok(config)
+            })
+            .unwrap();
+        let total = query_total_staked(deps.as_ref()).unwrap();
+        assert_eq!(total.total_staked, Uint128::new(5_200));
+        assert!(!total.consistent);
+    }
+
+    fn default_instantiate(deps: DepsMut, env: Env) {
+        cw20_instantiate(
+            deps,
+            env,
+            TOKENS_PER_POWER,
+            MIN_BOND,
+            vec![StakeConfig {
+                unbonding_period: UNBONDING_PERIOD,
+                voting_multiplier: Decimal::one(),
+                reward_multiplier: Decimal::one(),
+                exit_vesting: None,
+            }],
+        )
+    }
+
+    fn cw20_instantiate(
+        deps: DepsMut,
+        env: Env,
+        tokens_per_power: Uint128,
+        min_bond: Uint128,
+        stake_config: Vec<StakeConfig>,
+    ) {
+        let msg = InstantiateMsg {
+            cw20_contract: CW20_ADDRESS.to_owned(),
+            tokens_per_power,
+            min_bond,
+            stake_config,
+            admin: Some(INIT_ADMIN.into()),
+            max_hooks: None,
+            distribution_fee: None,
+            vesting_voting_discount: None,
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, env, info, msg).unwrap();
+    }
+
+    fn bond_cw20_with_period(
+        mut deps: DepsMut,
+        user1: u128,
+        user2: u128,
+        user3: u128,
+        unbonding_period: u64,
+        time_delta: u64,
+    ) {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(time_delta);
+
+        for (addr, stake) in &[(USER1, user1), (USER2, user2), (USER3, user3)] {
+            if *stake != 0 {
+                let msg = ExecuteMsg::ReceiveDelegation(Cw20ReceiveDelegationMsg {
+                    sender: addr.to_string(),
+                    amount: Uint128::new(*stake),
+                    msg: to_binary(&ReceiveDelegationMsg::Delegate { unbonding_period }).unwrap(),
+                });
+                let info = mock_info(CW20_ADDRESS, &[]);
+                execute(deps.branch(), env.clone(), info, msg).unwrap();
+            }
+        }
+    }
+
+    fn bond_cw20(deps: DepsMut, user1: u128, user2: u128, user3: u128, time_delta: u64) {
+        bond_cw20_with_period(deps, user1, user2, user3, UNBONDING_PERIOD, time_delta);
+    }
+
+    fn rebond_with_period(
+        mut deps: DepsMut,
+        user1: u128,
         user2: u128,
         user3: u128,
         bond_from: u64,
@@ -817,585 +3626,2033 @@ mod tests {
         time_delta: u64,
     ) {
         let mut env = mock_env();
-        env.block.time = env.block.time.plus_seconds(time_delta);
+        env.block.time = env.block.time.plus_seconds(time_delta);
+
+        for (addr, stake) in &[(USER1, user1), (USER2, user2), (USER3, user3)] {
+            if *stake != 0 {
+                let msg = ExecuteMsg::Rebond {
+                    bond_from,
+                    bond_to,
+                    tokens: Uint128::new(*stake),
+                    claim_first: false,
+                };
+                let info = mock_info(addr, &[]);
+                execute(deps.branch(), env.clone(), info, msg).unwrap();
+            }
+        }
+    }
+
+    fn unbond_with_period(
+        mut deps: DepsMut,
+        user1: u128,
+        user2: u128,
+        user3: u128,
+        time_delta: u64,
+        unbonding_period: u64,
+    ) {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(time_delta);
+
+        for (addr, stake) in &[(USER1, user1), (USER2, user2), (USER3, user3)] {
+            if *stake != 0 {
+                let msg = ExecuteMsg::Unbond {
+                    tokens: Uint128::new(*stake),
+                    unbonding_period,
+                    claim_recipient: None,
+                };
+                let info = mock_info(addr, &[]);
+                execute(deps.branch(), env.clone(), info, msg).unwrap();
+            }
+        }
+    }
+
+    fn unbond(deps: DepsMut, user1: u128, user2: u128, user3: u128, time_delta: u64) {
+        unbond_with_period(deps, user1, user2, user3, time_delta, UNBONDING_PERIOD);
+    }
+
+    #[test]
+    fn proper_instantiation() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
+
+        // it worked, let's query the state
+        let res = ADMIN.query_admin(deps.as_ref()).unwrap();
+        assert_eq!(Some(INIT_ADMIN.into()), res.admin);
+
+        let res = query_total_power(deps.as_ref(), env, None).unwrap();
+        assert_eq!(Uint128::zero(), res.power);
+
+        // make sure distribution logic is set up properly
+        let raw = query(deps.as_ref(), mock_env(), QueryMsg::DistributionData {}).unwrap();
+        let res: Distribution = from_slice(&raw).unwrap();
+        assert_eq!(
+            res,
+            Distribution {
+                shares_per_point: Uint128::zero(),
+                shares_leftover: Uint128::zero(),
+                distributed_total: Uint128::zero(),
+                withdrawable_total: Uint128::zero(),
+                fees_total: Uint128::zero(),
+            }
+        );
+
+        let raw = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::WithdrawAdjustmentData {
+                addr: USER1.to_owned(),
+            },
+        )
+        .unwrap();
+        let res: WithdrawAdjustmentDataResponse = from_slice(&raw).unwrap();
+        assert_eq!(
+            res,
+            WithdrawAdjustment {
+                shares_correction: 0,
+                withdrawn_rewards: Uint128::zero(),
+                delegated: Addr::unchecked(USER1),
+            }
+        );
+    }
+
+    fn get_member(deps: Deps, env: Env, address: String, height: Option<u64>) -> u128 {
+        let raw = query(deps, env, QueryMsg::VotingPowerAtHeight { address, height }).unwrap();
+        let res: VotingPowerAtHeightResponse = from_slice(&raw).unwrap();
+        res.power.u128()
+    }
+
+    // this tests the member queries
+    fn assert_users(
+        deps: Deps,
+        env: Env,
+        user1_power: Option<u128>,
+        user2_power: Option<u128>,
+        user3_power: Option<u128>,
+        height: Option<u64>,
+    ) {
+        let member1 = get_member(deps, env.clone(), USER1.into(), height);
+        assert_eq!(member1, user1_power.unwrap_or_default());
+
+        let member2 = get_member(deps, env.clone(), USER2.into(), height);
+        assert_eq!(member2, user2_power.unwrap_or_default());
+
+        let member3 = get_member(deps, env.clone(), USER3.into(), height);
+        assert_eq!(member3, user3_power.unwrap_or_default());
+
+        // compute expected metrics
+        let powers = vec![user1_power, user2_power, user3_power];
+        let sum: u128 = powers.iter().map(|x| x.unwrap_or_default()).sum();
+        let count = powers.iter().filter(|x| x.is_some()).count();
+
+        let total = query_total_power(deps, env, height).unwrap();
+        assert_eq!(Uint128::new(sum), total.power);
+
+        // this is only valid if we are not doing a historical query
+        if height.is_none() {
+            let members = MEMBERS
+                .range(deps.storage, None, None, Order::Ascending)
+                .count();
+            assert_eq!(count, members);
+        }
+    }
+
+    fn assert_stake_in_period(
+        deps: Deps,
+        env: &Env,
+        user1_stake: u128,
+        user2_stake: u128,
+        user3_stake: u128,
+        unbonding_period: u64,
+    ) {
+        let stake1 = query_staked(deps, env, USER1.into(), unbonding_period).unwrap();
+        assert_eq!(stake1.stake.u128(), user1_stake);
+
+        let stake2 = query_staked(deps, env, USER2.into(), unbonding_period).unwrap();
+        assert_eq!(stake2.stake.u128(), user2_stake);
+
+        let stake3 = query_staked(deps, env, USER3.into(), unbonding_period).unwrap();
+        assert_eq!(stake3.stake.u128(), user3_stake);
+    }
+
+    // this tests the member queries
+    fn assert_stake(
+        deps: Deps,
+        env: &Env,
+        user1_stake: u128,
+        user2_stake: u128,
+        user3_stake: u128,
+    ) {
+        assert_stake_in_period(
+            deps,
+            env,
+            user1_stake,
+            user2_stake,
+            user3_stake,
+            UNBONDING_PERIOD,
+        );
+    }
+
+    fn assert_cw20_undelegate(res: cosmwasm_std::Response, recipient: &str, amount: u128) {
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) => {
+                assert_eq!(contract_addr.as_str(), CW20_ADDRESS);
+                assert_eq!(funds.len(), 0);
+                let parsed: VestingExecuteMsg = from_slice(msg).unwrap();
+                assert_eq!(
+                    parsed,
+                    VestingExecuteMsg::Undelegate {
+                        recipient: recipient.into(),
+                        amount: Uint128::new(amount),
+                        delegator: None,
+                    }
+                );
+            }
+            _ => panic!("Must initiate undelegate!"),
+        }
+    }
+
+    #[test]
+    fn cw20_token_bond() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        cw20_instantiate(
+            deps.as_mut(),
+            env.clone(),
+            TOKENS_PER_POWER,
+            MIN_BOND,
+            vec![StakeConfig {
+                unbonding_period: UNBONDING_PERIOD,
+                voting_multiplier: Decimal::one(),
+                reward_multiplier: Decimal::one(),
+                exit_vesting: None,
+            }],
+        );
+
+        let initial_height = env.block.height;
+
+        // Assert original powers
+        assert_users(deps.as_ref(), env.clone(), None, None, None, None);
+
+        // ensure it rounds down, and respects cut-off
+        bond_cw20(deps.as_mut(), 12_000, 7_500, 4_000, 1);
+
+        // Assert updated powers
+        assert_stake(deps.as_ref(), &env, 12_000, 7_500, 4_000);
+        assert_users(deps.as_ref(), env.clone(), Some(12), Some(7), None, None);
+
+        // assert users at initial height
+        assert_users(deps.as_ref(), env, None, None, None, Some(initial_height));
+    }
+
+    #[test]
+    fn receive_delegation_validation() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
+
+        let delegation = |msg: &[u8]| {
+            ExecuteMsg::ReceiveDelegation(Cw20ReceiveDelegationMsg {
+                sender: USER1.to_owned(),
+                amount: Uint128::new(5_000),
+                msg: Binary::from(msg),
+            })
+        };
+        let delegate = format!(
+            r#"{{"delegate":{{"unbonding_period":{}}}}}"#,
+            UNBONDING_PERIOD
+        );
+
+        // the token is checked before anything else
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("other_token", &[]),
+            delegation(b"malformed"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnexpectedDelegationToken {
+                token: "other_token".to_owned(),
+                expected: CW20_ADDRESS.to_owned(),
+            }
+        );
+
+        let info = mock_info(CW20_ADDRESS, &[]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            delegation(b"malformed"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::ParseErr { .. })));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            delegation(br#"{"delegate":{"unbonding_period":"soon"}}"#),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::ParseErr { .. })));
+
+        // every kind of message is known as such
+        let msg = to_binary(&ReceiveDelegationMsg::Delegate {
+            unbonding_period: UNBONDING_PERIOD,
+        })
+        .unwrap();
+        let payload: DelegationPayload = from_slice(&msg).unwrap();
+        assert!(matches!(payload, DelegationPayload::Known(_)));
+        let kind = String::from_utf8(msg.to_vec()).unwrap();
+        assert!(ReceiveDelegationMsg::KINDS
+            .iter()
+            .any(|known| kind.starts_with(&format!(r#"{{"{}":"#, known))));
+
+        // a message this contract does not know yet
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            delegation(br#"{"delegate_wrapped":{"unbonding_period":100,"asset":"x"}}"#),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnsupportedDelegationMsg {
+                kind: "delegate_wrapped".to_owned()
+            }
+        );
+
+        let res = execute(deps.as_mut(), env, info, delegation(delegate.as_bytes())).unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("receive_delegation")
+                .add_attribute("token", CW20_ADDRESS)
+                .add_attribute("sender", USER1)
+                .add_attribute("amount", "5000")]
+        );
+    }
+
+    #[test]
+    fn cw20_token_claim() {
+        let unbonding_period: u64 = 20;
+
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let unbonding = Duration::Time(unbonding_period);
+        cw20_instantiate(
+            deps.as_mut(),
+            env.clone(),
+            TOKENS_PER_POWER,
+            MIN_BOND,
+            vec![StakeConfig {
+                unbonding_period,
+                voting_multiplier: Decimal::one(),
+                reward_multiplier: Decimal::one(),
+                exit_vesting: None,
+            }],
+        );
+
+        // bond some tokens
+        bond_cw20(deps.as_mut(), 20_000, 13_500, 500, 5);
+
+        // unbond part
+        unbond(deps.as_mut(), 7_900, 4_600, 0, unbonding_period);
+
+        // Assert updated powers
+        assert_stake(deps.as_ref(), &env, 12_100, 8_900, 500);
+        assert_users(deps.as_ref(), env.clone(), Some(12), Some(8), None, None);
+
+        // with proper claims
+        env.block.time = env.block.time.plus_seconds(unbonding_period);
+        let expires = unbonding.after(&env.block);
+        assert_eq!(
+            get_claims(deps.as_ref(), &Addr::unchecked(USER1)),
+            vec![Claim::new(7_900, expires)]
+        );
+
+        // wait til they expire and get payout
+        env.block.time = env.block.time.plus_seconds(unbonding_period);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info(USER1, &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_cw20_undelegate(res, USER1, 7_900)
+    }
+
+    #[test]
+    fn raw_queries_work() {
+        // add will over-write and remove have no effect
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+        // Set values as (11, 6, None)
+        bond_cw20(deps.as_mut(), 11_000, 6_000, 0, 1);
+
+        // get total from raw key
+        let total_raw = deps.storage.get(TOTAL_KEY.as_bytes()).unwrap();
+        let total: u64 = from_slice(&total_raw).unwrap();
+        assert_eq!(17, total);
+
+        // get member votes from raw key
+        let member2_raw = deps.storage.get(&member_key(USER2)).unwrap();
+        let member2: u64 = from_slice(&member2_raw).unwrap();
+        assert_eq!(6, member2);
+
+        // and execute misses
+        let member3_raw = deps.storage.get(&member_key(USER3));
+        assert_eq!(None, member3_raw);
+
+        // the documented keys match the cw4 ones
+        assert_eq!(state_keys::cw4_member_key(USER2), member_key(USER2));
+        assert_eq!(state_keys::cw4_total_key(), TOTAL_KEY.as_bytes());
+
+        // the exact powers are kept under their own keys
+        let power_raw = deps.storage.get(&state_keys::member_key(USER2)).unwrap();
+        let power: Uint128 = from_slice(&power_raw).unwrap();
+        assert_eq!(6, power.u128());
+        let total_raw = deps.storage.get(&state_keys::total_votes_key()).unwrap();
+        let total: Uint128 = from_slice(&total_raw).unwrap();
+        assert_eq!(17, total.u128());
+
+        // get stakes from raw keys
+        let user1 = Addr::unchecked(USER1);
+        let stake_raw = deps
+            .storage
+            .get(&state_keys::stake_key(USER1, UNBONDING_PERIOD))
+            .unwrap();
+        let stake: BondingInfo = from_slice(&stake_raw).unwrap();
+        assert_eq!(
+            stake,
+            STAKE
+                .load(&deps.storage, (&user1, UNBONDING_PERIOD))
+                .unwrap()
+        );
+        assert_eq!(
+            None,
+            deps.storage
+                .get(&state_keys::stake_key(USER1, UNBONDING_PERIOD_2))
+        );
+
+        // get reward power and stake config from raw keys
+        let rewards_raw = deps.storage.get(&state_keys::rewards_key(USER1)).unwrap();
+        let rewards: Uint128 = from_slice(&rewards_raw).unwrap();
+        assert_eq!(rewards, REWARDS.load(&deps.storage, &user1).unwrap());
+        let multipliers_raw = deps
+            .storage
+            .get(&state_keys::stake_config_key(UNBONDING_PERIOD))
+            .unwrap();
+        let multipliers: StakeMultipliers = from_slice(&multipliers_raw).unwrap();
+        assert_eq!(
+            multipliers,
+            STAKE_CONFIG.load(&deps.storage, UNBONDING_PERIOD).unwrap()
+        );
+
+        // get claims from raw keys
+        unbond(deps.as_mut(), 4_000, 0, 0, 0);
+        let claims_raw = deps.storage.get(&state_keys::claims_key(USER1)).unwrap();
+        let claims: Vec<Claim> = from_slice(&claims_raw).unwrap();
+        assert_eq!(
+            claims,
+            CLAIMS.query_claims(deps.as_ref(), &user1).unwrap().claims
+        );
+        let maturity = match claims[0].release_at {
+            Expiration::AtTime(time) => time.nanos(),
+            _ => panic!("claims mature at a time"),
+        };
+        let amount_raw = deps
+            .storage
+            .get(&state_keys::claims_by_maturity_key(maturity, USER1))
+            .unwrap();
+        let amount: Uint128 = from_slice(&amount_raw).unwrap();
+        assert_eq!(amount.u128(), 4_000);
+    }
+
+    /// Raw key of the entry `name` in `state_keys::LAYOUT`, for the given address and number
+    fn layout_key(name: &str, addr: &Addr, n: u64) -> Vec<u8> {
+        let &(_, namespace, encoding) = state_keys::LAYOUT
+            .iter()
+            .find(|(entry, ..)| *entry == name)
+            .unwrap();
+        let (addr, n) = (addr.as_bytes(), n.to_be_bytes());
+        match encoding {
+            KeyEncoding::Item => state_keys::item_key(namespace),
+            KeyEncoding::Addr => state_keys::map_key(namespace, addr),
+            KeyEncoding::U64 => state_keys::map_key(namespace, &n),
+            KeyEncoding::AddrU64 => state_keys::composite_key(namespace, addr, &n),
+            KeyEncoding::U64Addr => state_keys::composite_key(namespace, &n, addr),
+        }
+    }
+
+    /// Writes a raw value under the documented key and checks that `map` finds it
+    fn assert_map_layout<'a, K, T>(
+        storage: &mut dyn Storage,
+        name: &str,
+        raw_key: Vec<u8>,
+        map: &Map<'a, K, T>,
+        key: K,
+    ) where
+        K: PrimaryKey<'a> + Clone,
+        T: Serialize + DeserializeOwned,
+    {
+        assert!(!map.has(storage, key.clone()), "{} already set", name);
+        storage.set(&raw_key, b"{}");
+        assert!(map.has(storage, key), "{} does not match its layout", name);
+    }
+
+    #[test]
+    fn raw_keys_match_layout() {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+        bond_cw20(deps.as_mut(), 11_000, 6_000, 0, 1);
+
+        let addr = Addr::unchecked(USER3);
+        let n = 1234;
+        let mut checked = vec![];
+
+        macro_rules! check_map {
+            ($name:literal, $map:expr, $key:expr) => {
+                let raw_key = layout_key($name, &addr, n);
+                assert_map_layout(&mut deps.storage, $name, raw_key, &$map, $key);
+                checked.push($name);
+            };
+        }
+        check_map!("ADMIN_PROPOSALS", ADMIN_PROPOSALS, n);
+        check_map!("CLAIMS", CLAIMS_BY_ADDR, &addr);
+        check_map!("CLAIMS_BY_MATURITY", CLAIMS_BY_MATURITY, (n, &addr));
+        check_map!("CLAIMS_FROZEN", CLAIMS_FROZEN, &addr);
+        check_map!("CLAIM_DELEGATORS", CLAIM_DELEGATORS, (&addr, n));
+        check_map!("CW4_MEMBERS", CW4_MEMBERS, &addr);
+        check_map!("DISTRIBUTION_LOG", DISTRIBUTION_LOG, n);
+        check_map!("DISTRIBUTION_WEIGHT", DISTRIBUTION_WEIGHT, &addr);
+        check_map!("EXIT_VESTING", EXIT_VESTING, (&addr, n));
+        check_map!("FIRST_STAKED", FIRST_STAKED, &addr);
+        check_map!("HOOKS", HOOKS, &addr);
+        check_map!("HOOK_REPLY_IDS", HOOK_REPLY_IDS, n);
+        check_map!("LABELS", LABELS, &addr);
+        check_map!("LAST_VOTE_HEIGHT", LAST_VOTE_HEIGHT, &addr);
+        check_map!("LOYALTY_EPOCHS", LOYALTY_EPOCHS, &addr);
+        check_map!("PAYOUT_ADDRESS", PAYOUT_ADDRESS, &addr);
+        check_map!("POWER_EXCLUDED", POWER_EXCLUDED, &addr);
+        check_map!("REWARDS", REWARDS, &addr);
+        check_map!("REWARD_ACTIVITY", REWARD_ACTIVITY, &addr);
+        check_map!("SCHEDULED_REBONDS", SCHEDULED_REBONDS, &addr);
+        check_map!("SHARES_PER_POINT_HISTORY", SHARES_PER_POINT_HISTORY, n);
+        check_map!("STAKE", STAKE, (&addr, n));
+        check_map!("STAKE_CONFIG", STAKE_CONFIG, n);
+        check_map!("VOTES_CAST", VOTES_CAST, &addr);
+        check_map!("VOTING_DISCOUNTED", VOTING_DISCOUNTED, (&addr, n));
+        check_map!("WITHDRAW_ADJUSTMENT", WITHDRAW_ADJUSTMENT, &addr);
+
+        // snapshotted members
+        deps.storage
+            .set(&layout_key("MEMBERS", &addr, n), b"\"42\"");
+        assert_eq!(
+            MEMBERS.may_load(&deps.storage, &addr).unwrap(),
+            Some(Uint128::new(42))
+        );
+        checked.push("MEMBERS");
+
+        // items are stored under their namespace
+        macro_rules! check_item {
+            ($name:literal, $item:expr) => {
+                assert_eq!(layout_key($name, &addr, n), $item.as_slice(), $name);
+                checked.push($name);
+            };
+        }
+        check_item!("ADMIN_MULTISIG", ADMIN_MULTISIG);
+        check_item!("ADMIN_RENOUNCED", ADMIN_RENOUNCED);
+        check_item!("CONFIG", CONFIG);
+        check_item!("CW4_TOTAL", CW4_TOTAL);
+        check_item!("DISTRIBUTION", DISTRIBUTION);
+        check_item!("GOVERNANCE_CONTRACTS", GOVERNANCE_CONTRACTS);
+        check_item!("LAST_ADMIN_PROPOSAL_ID", LAST_ADMIN_PROPOSAL_ID);
+        check_item!("LAST_HOOK_REPLY_ID", LAST_HOOK_REPLY_ID);
+        check_item!("LEGACY_HOOKS", LEGACY_HOOKS);
+        check_item!("MIGRATION_PROGRESS", MIGRATION_PROGRESS);
+        check_item!("MIN_APPROVALS", MIN_APPROVALS);
+        check_item!("NEXT_DISTRIBUTION_SEQ", NEXT_DISTRIBUTION_SEQ);
+        check_item!("PENDING_RECOMPUTE", PENDING_RECOMPUTE);
+        check_item!("REWARD_MIGRATION", REWARD_MIGRATION);
+        check_item!("REWARD_STREAM", REWARD_STREAM);
+        check_item!("SHARES_RESET_AT", SHARES_RESET_AT);
+        check_item!("STAKING_LOCKED", STAKING_LOCKED);
+        check_item!("STORAGE_VERSION_MARKER", STORAGE_VERSION_MARKER);
+        check_item!("TOTAL_REWARDS", TOTAL_REWARDS);
+        check_item!("TOTAL_STAKED", TOTAL_STAKED);
+
+        // controllers and snapshotted items are read back
+        let admin_raw = deps.storage.get(&layout_key("ADMIN", &addr, n)).unwrap();
+        let admin: Option<Addr> = from_slice(&admin_raw).unwrap();
+        assert_eq!(admin, ADMIN.get(deps.as_ref()).unwrap());
+        checked.push("ADMIN");
+        let total_raw = deps
+            .storage
+            .get(&layout_key("TOTAL_VOTES", &addr, n))
+            .unwrap();
+        let total: Uint128 = from_slice(&total_raw).unwrap();
+        assert_eq!(total, TOTAL_VOTES.load(&deps.storage).unwrap());
+        checked.push("TOTAL_VOTES");
+
+        // every documented entry was checked
+        let mut documented: Vec<_> = state_keys::LAYOUT.iter().map(|(name, ..)| *name).collect();
+        documented.sort_unstable();
+        checked.sort_unstable();
+        assert_eq!(checked, documented);
+
+        // and the layout is exposed as a query
+        let res: RawKeyLayoutResponse =
+            from_slice(&query(deps.as_ref(), mock_env(), QueryMsg::RawKeyLayout {}).unwrap())
+                .unwrap();
+        assert_eq!(res.layout.len(), state_keys::LAYOUT.len());
+        assert!(res.layout.contains(&StorageLayout {
+            name: "STAKE".to_owned(),
+            namespace: "stake".to_owned(),
+            key: KeyEncoding::AddrU64,
+        }));
+    }
+
+    fn list_members_cw4(deps: Deps, start_after: Option<&str>, limit: u32) -> Vec<Member> {
+        let msg = QueryMsg::ListMembersCw4 {
+            start_after: start_after.map(str::to_owned),
+            limit: Some(limit),
+        };
+        let res: MemberListResponse = from_slice(&query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.members
+    }
+
+    #[test]
+    fn list_members_cw4_matches_raw_keys() {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+        bond_cw20(deps.as_mut(), 11_000, 6_000, 0, 1);
+
+        // every listed weight is the value behind the cw4 member key
+        let members = list_members_cw4(deps.as_ref(), None, 10);
+        assert_eq!(
+            members,
+            [
+                Member {
+                    addr: USER1.to_owned(),
+                    weight: 11
+                },
+                Member {
+                    addr: USER2.to_owned(),
+                    weight: 6
+                },
+            ]
+        );
+        for member in &members {
+            let raw = deps.storage.get(&member_key(&member.addr)).unwrap();
+            let weight: u64 = from_slice(&raw).unwrap();
+            assert_eq!(weight, member.weight);
+        }
+        let total_raw = deps.storage.get(TOTAL_KEY.as_bytes()).unwrap();
+        let total: u64 = from_slice(&total_raw).unwrap();
+        let weights: u64 = members.iter().map(|m| m.weight).sum();
+        assert_eq!(total, weights);
+
+        // pagination
+        let page = list_members_cw4(deps.as_ref(), None, 1);
+        assert_eq!(page, members[..1]);
+        let page = list_members_cw4(deps.as_ref(), Some(USER1), 1);
+        assert_eq!(page, members[1..]);
+        assert_eq!(list_members_cw4(deps.as_ref(), Some(USER2), 1), []);
+
+        // excluded members are listed without weight
+        let msg = ExecuteMsg::ExcludeFromPower {
+            address: USER2.to_owned(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(INIT_ADMIN, &[]), msg).unwrap();
+        let page = list_members_cw4(deps.as_ref(), Some(USER1), 1);
+        assert_eq!(
+            page,
+            [Member {
+                addr: USER2.to_owned(),
+                weight: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn list_members_cw4_saturates_weight() {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+        let height = mock_env().block.height;
+        let powers = [
+            (USER1, u64::MAX as u128),
+            (USER2, u64::MAX as u128 + 1),
+            (USER3, u128::MAX),
+        ];
+        for (user, power) in powers {
+            save_member_power(
+                &mut deps.storage,
+                &Addr::unchecked(user),
+                Uint128::new(power),
+                height,
+            )
+            .unwrap();
+        }
+
+        let weights: Vec<_> = list_members_cw4(deps.as_ref(), None, 10)
+            .into_iter()
+            .map(|m| m.weight)
+            .collect();
+        assert_eq!(weights, [u64::MAX; 3]);
+        // the raw weights saturate the same way, while the exact powers are kept
+        let raw = deps.storage.get(&member_key(USER3)).unwrap();
+        assert_eq!(from_slice::<u64>(&raw).unwrap(), u64::MAX);
+        assert_eq!(
+            MEMBERS
+                .load(&deps.storage, &Addr::unchecked(USER3))
+                .unwrap(),
+            Uint128::MAX
+        );
+        assert_eq!(cw4_weight(Uint128::new(17)), 17);
+    }
+
+    #[test]
+    fn loyalty_bonus_applies_to_voting_power() {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+        bond_cw20(deps.as_mut(), 100_000, 0, 0, 0);
+        let at_epoch = |epochs: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(epochs * LOYALTY_EPOCH);
+            env
+        };
+        let loyalty_bonus = |deps: Deps, env: Env| -> LoyaltyBonusResponse {
+            let msg = QueryMsg::LoyaltyBonus {
+                address: USER1.to_owned(),
+            };
+            from_slice(&query(deps, env, msg).unwrap()).unwrap()
+        };
+
+        // only the admin can set the bonus
+        let msg = ExecuteMsg::UpdateLoyaltyBonus {
+            bonus_pct: 10,
+            max_pct: 25,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(USER1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
+        execute(deps.as_mut(), mock_env(), mock_info(INIT_ADMIN, &[]), msg).unwrap();
+
+        assert_eq!(
+            loyalty_bonus(deps.as_ref(), at_epoch(2)),
+            LoyaltyBonusResponse {
+                epochs: 2,
+                bonus_pct: 20
+            }
+        );
+        // capped at max_pct
+        assert_eq!(
+            loyalty_bonus(deps.as_ref(), at_epoch(5)),
+            LoyaltyBonusResponse {
+                epochs: 5,
+                bonus_pct: 25
+            }
+        );
+
+        // voting power picks up the bonus when recomputed, rewards are not affected
+        let msg = ExecuteMsg::RecomputeMembers {
+            start_after: None,
+            limit: None,
+        };
+        execute(deps.as_mut(), at_epoch(5), mock_info(USER2, &[]), msg).unwrap();
+        assert_eq!(
+            get_member(deps.as_ref(), at_epoch(5), USER1.into(), None),
+            125
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), USER1.into())
+                .unwrap()
+                .rewards
+                .u128(),
+            100
+        );
+
+        // bonding more keeps the streak
+        bond_cw20(deps.as_mut(), 20_000, 0, 0, 5 * LOYALTY_EPOCH);
+        assert_eq!(
+            get_member(deps.as_ref(), at_epoch(5), USER1.into(), None),
+            150
+        );
+
+        // unbonding ends the streak and removes the bonus
+        unbond(deps.as_mut(), 10_000, 0, 0, 5 * LOYALTY_EPOCH);
+        assert_eq!(
+            get_member(deps.as_ref(), at_epoch(5), USER1.into(), None),
+            110
+        );
+        assert_eq!(
+            loyalty_bonus(deps.as_ref(), at_epoch(5)),
+            LoyaltyBonusResponse {
+                epochs: 0,
+                bonus_pct: 0
+            }
+        );
+    }
+
+    fn get_claims(deps: Deps, addr: &Addr) -> Vec<Claim> {
+        CLAIMS.query_claims(deps, addr).unwrap().claims
+    }
+
+    #[test]
+    fn claim_specific() {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+
+        // two claims for USER1, unbonded at different times
+        bond_cw20(deps.as_mut(), 12_000, 0, 0, 5);
+        unbond(deps.as_mut(), 4_500, 0, 0, 10);
+        unbond(deps.as_mut(), 2_000, 0, 0, 22);
+        let first_unlock = mock_env().block.time.plus_seconds(10 + UNBONDING_PERIOD);
+        let first_expiry = first_unlock.seconds();
+        let second_expiry = first_expiry + 12;
+
+        let claim = |deps: DepsMut, time: u64, expires_at: u64| {
+            // claims unlock with sub-second precision, so `time` is still before `time + 1`
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(time + 1);
+            execute(
+                deps,
+                env,
+                mock_info(USER1, &[]),
+                ExecuteMsg::ClaimSpecific { expires_at },
+            )
+        };
+
+        // no claim with that expiry
+        let err = claim(deps.as_mut(), second_expiry, first_expiry + 1).unwrap_err();
+        assert_eq!(err, ContractError::ClaimNotFound {});
+
+        // claim not matured yet
+        let err = claim(deps.as_mut(), first_expiry - 1, first_expiry).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ClaimNotYetMatured {
+                unlocks_at: first_unlock
+            }
+        );
+
+        // only the second claim is released, even though both are matured
+        let res = claim(deps.as_mut(), second_expiry, second_expiry).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_cw20_undelegate(res, USER1, 2_000);
+        assert_eq!(
+            get_claims(deps.as_ref(), &Addr::unchecked(USER1)),
+            vec![Claim::new(4_500, Expiration::AtTime(first_unlock))]
+        );
+        let err = claim(deps.as_mut(), second_expiry, second_expiry).unwrap_err();
+        assert_eq!(err, ContractError::ClaimNotFound {});
+
+        let res = claim(deps.as_mut(), second_expiry, first_expiry).unwrap();
+        assert_cw20_undelegate(res, USER1, 4_500);
+        assert_eq!(get_claims(deps.as_ref(), &Addr::unchecked(USER1)), vec![]);
+        assert_eq!(
+            query_total_unbonding(deps.as_ref())
+                .unwrap()
+                .total_unbonding
+                .u128(),
+            0
+        );
+    }
+
+    #[test]
+    fn mature_claims() {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+
+        // claims created at different times
+        bond_cw20(deps.as_mut(), 12_000, 7_500, 4_000, 5);
+        unbond(deps.as_mut(), 4_500, 0, 0, 10);
+        unbond(deps.as_mut(), 0, 2_600, 0, 22);
+        unbond(deps.as_mut(), 0, 0, 1_500, 30);
+        let matures_at = |time_delta| {
+            mock_env()
+                .block
+                .time
+                .plus_seconds(time_delta + UNBONDING_PERIOD)
+        };
+
+        // unbonding emits the exact maturity
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(40);
+        let msg = ExecuteMsg::Unbond {
+            tokens: Uint128::new(1_000),
+            unbonding_period: UNBONDING_PERIOD,
+            claim_recipient: None,
+        };
+        let res = execute(deps.as_mut(), env, mock_info(USER1, &[]), msg).unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("claim_created")
+                .add_attribute("owner", USER1)
+                .add_attribute("amount", "1000")
+                .add_attribute("matures_at", matures_at(40).to_string())]
+        );
+
+        let query_claims = |deps: Deps, time: Timestamp, start_after, limit| {
+            let mut env = mock_env();
+            env.block.time = time;
+            query_mature_claims(deps, env, start_after, limit)
+                .unwrap()
+                .claims
+        };
+        let claim = |addr: &str, amount: u128, time_delta| MatureClaim {
+            address: Addr::unchecked(addr),
+            amount: Uint128::new(amount),
+            matured_at: matures_at(time_delta),
+        };
+
+        // nothing matured yet
+        assert_eq!(
+            query_claims(deps.as_ref(), matures_at(0), None, None),
+            vec![]
+        );
+
+        // only the first two are matured
+        let now = matures_at(22);
+        assert_eq!(
+            query_claims(deps.as_ref(), now, None, None),
+            vec![claim(USER1, 4_500, 10), claim(USER2, 2_600, 22)]
+        );
+        // paginated
+        let page = query_claims(deps.as_ref(), now, None, Some(1));
+        assert_eq!(page, vec![claim(USER1, 4_500, 10)]);
+        let start_after = Some((page[0].matured_at, page[0].address.to_string()));
+        assert_eq!(
+            query_claims(deps.as_ref(), now, start_after, None),
+            vec![claim(USER2, 2_600, 22)]
+        );
+
+        // claimed ones are removed
+        let mut env = mock_env();
+        env.block.time = now;
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info(USER1, &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        assert_eq!(
+            query_claims(deps.as_ref(), matures_at(40), None, None),
+            vec![
+                claim(USER2, 2_600, 22),
+                claim(USER3, 1_500, 30),
+                claim(USER1, 1_000, 40)
+            ]
+        );
+    }
+
+    #[test]
+    fn unbond_claim_workflow() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
+
+        // create some data
+        bond_cw20(deps.as_mut(), 12_000, 7_500, 4_000, 5);
+        unbond(deps.as_mut(), 4_500, 2_600, 0, 10);
+        env.block.time = env.block.time.plus_seconds(10);
+
+        // check the claims for each user
+        let expires = Duration::Time(UNBONDING_PERIOD).after(&env.block);
+        assert_eq!(
+            get_claims(deps.as_ref(), &Addr::unchecked(USER1)),
+            vec![Claim::new(4_500, expires)]
+        );
+        assert_eq!(
+            get_claims(deps.as_ref(), &Addr::unchecked(USER2)),
+            vec![Claim::new(2_600, expires)]
+        );
+        assert_eq!(get_claims(deps.as_ref(), &Addr::unchecked(USER3)), vec![]);
+
+        // do another unbond later on
+        let mut env2 = mock_env();
+        env2.block.time = env2.block.time.plus_seconds(22);
+        unbond(deps.as_mut(), 0, 1_345, 1_500, 22);
+
+        // with updated claims
+        let expires2 = Duration::Time(UNBONDING_PERIOD).after(&env2.block);
+        assert_eq!(
+            get_claims(deps.as_ref(), &Addr::unchecked(USER1)),
+            vec![Claim::new(4_500, expires)]
+        );
+        assert_eq!(
+            get_claims(deps.as_ref(), &Addr::unchecked(USER2)),
+            vec![Claim::new(2_600, expires), Claim::new(1_345, expires2)]
+        );
+        assert_eq!(
+            get_claims(deps.as_ref(), &Addr::unchecked(USER3)),
+            vec![Claim::new(1_500, expires2)]
+        );
+
+        // nothing can be withdrawn yet
+        let err = execute(
+            deps.as_mut(),
+            env2,
+            mock_info(USER1, &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        // now mature first section, withdraw that
+        let mut env3 = mock_env();
+        env3.block.time = env3.block.time.plus_seconds(UNBONDING_PERIOD + 10);
+        // first one can now release
+        let res = execute(
+            deps.as_mut(),
+            env3.clone(),
+            mock_info(USER1, &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        assert_cw20_undelegate(res, USER1, 4_500);
+
+        // second releases partially
+        let res = execute(
+            deps.as_mut(),
+            env3.clone(),
+            mock_info(USER2, &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        assert_cw20_undelegate(res, USER2, 2_600);
 
-        for (addr, stake) in &[(USER1, user1), (USER2, user2), (USER3, user3)] {
-            if *stake != 0 {
-                let msg = ExecuteMsg::Rebond {
-                    bond_from,
-                    bond_to,
-                    tokens: Uint128::new(*stake),
-                };
-                let info = mock_info(addr, &[]);
-                execute(deps.branch(), env.clone(), info, msg).unwrap();
-            }
-        }
-    }
+        // but the third one cannot release
+        let err = execute(
+            deps.as_mut(),
+            env3,
+            mock_info(USER3, &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
 
-    fn unbond_with_period(
-        mut deps: DepsMut,
-        user1: u128,
-        user2: u128,
-        user3: u128,
-        time_delta: u64,
-        unbonding_period: u64,
-    ) {
-        let mut env = mock_env();
-        env.block.time = env.block.time.plus_seconds(time_delta);
+        // claims updated properly
+        assert_eq!(get_claims(deps.as_ref(), &Addr::unchecked(USER1)), vec![]);
+        assert_eq!(
+            get_claims(deps.as_ref(), &Addr::unchecked(USER2)),
+            vec![Claim::new(1_345, expires2)]
+        );
+        assert_eq!(
+            get_claims(deps.as_ref(), &Addr::unchecked(USER3)),
+            vec![Claim::new(1_500, expires2)]
+        );
 
-        for (addr, stake) in &[(USER1, user1), (USER2, user2), (USER3, user3)] {
-            if *stake != 0 {
-                let msg = ExecuteMsg::Unbond {
-                    tokens: Uint128::new(*stake),
-                    unbonding_period,
-                };
-                let info = mock_info(addr, &[]);
-                execute(deps.branch(), env.clone(), info, msg).unwrap();
-            }
-        }
+        // add another few claims for 2
+        unbond(deps.as_mut(), 0, 600, 0, 6 + UNBONDING_PERIOD);
+        unbond(deps.as_mut(), 0, 1_005, 0, 10 + UNBONDING_PERIOD);
+
+        // ensure second can claim all tokens at once
+        let mut env4 = mock_env();
+        env4.block.time = env4.block.time.plus_seconds(UNBONDING_PERIOD * 2 + 12);
+        let res = execute(
+            deps.as_mut(),
+            env4,
+            mock_info(USER2, &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        assert_cw20_undelegate(res, USER2, 2_950); // 1_345 + 600 + 1_005
+        assert_eq!(get_claims(deps.as_ref(), &Addr::unchecked(USER2)), vec![]);
     }
 
-    fn unbond(deps: DepsMut, user1: u128, user2: u128, user3: u128, time_delta: u64) {
-        unbond_with_period(deps, user1, user2, user3, time_delta, UNBONDING_PERIOD);
+    fn rewards(deps: Deps, user: &str) -> u128 {
+        query_rewards(deps, user.to_string())
+            .unwrap()
+            .rewards
+            .u128()
     }
 
     #[test]
-    fn proper_instantiation() {
+    fn rewards_saved() {
         let mut deps = mock_dependencies();
         let env = mock_env();
-        default_instantiate(deps.as_mut(), env.clone());
+        cw20_instantiate(
+            deps.as_mut(),
+            env,
+            TOKENS_PER_POWER,
+            MIN_BOND,
+            vec![StakeConfig {
+                unbonding_period: UNBONDING_PERIOD,
+                voting_multiplier: Decimal::one(),
+                reward_multiplier: Decimal::percent(1),
+                exit_vesting: None,
+            }],
+        );
 
-        // it worked, let's query the state
-        let res = ADMIN.query_admin(deps.as_ref()).unwrap();
-        assert_eq!(Some(INIT_ADMIN.into()), res.admin);
+        // assert original rewards
+        assert_eq!(0, rewards(deps.as_ref(), USER1));
+        assert_eq!(0, rewards(deps.as_ref(), USER2));
+        assert_eq!(0, rewards(deps.as_ref(), USER3));
 
-        let res = query_total_power(deps.as_ref(), env, None).unwrap();
-        assert_eq!(Uint128::zero(), res.power);
+        // ensure it rounds down, and respects cut-off
+        bond_cw20(deps.as_mut(), 1_200_000, 770_000, 4_000_000, 1);
 
-        // make sure distribution logic is set up properly
-        let raw = query(deps.as_ref(), mock_env(), QueryMsg::DistributionData {}).unwrap();
-        let res: Distribution = from_slice(&raw).unwrap();
+        // assert updated rewards
         assert_eq!(
-            res,
-            Distribution {
-                shares_per_point: Uint128::zero(),
-                shares_leftover: 0,
-                distributed_total: Uint128::zero(),
-                withdrawable_total: Uint128::zero(),
-            }
+            12,
+            rewards(deps.as_ref(), USER1),
+            "1_200_000 * 1% / 1_000 = 12"
         );
-
-        let raw = query(
-            deps.as_ref(),
-            mock_env(),
-            QueryMsg::WithdrawAdjustmentData {
-                addr: USER1.to_owned(),
-            },
-        )
-        .unwrap();
-        let res: WithdrawAdjustmentDataResponse = from_slice(&raw).unwrap();
+        assert_eq!(7, rewards(deps.as_ref(), USER2), "770_000 * 1% / 1_000 = 7");
         assert_eq!(
-            res,
-            WithdrawAdjustment {
-                shares_correction: 0,
-                withdrawn_rewards: Uint128::zero(),
-                delegated: Addr::unchecked(USER1),
-            }
+            40,
+            rewards(deps.as_ref(), USER3),
+            "4_000_000 * 1% / 1_000 = 4"
         );
-    }
-
-    fn get_member(deps: Deps, env: Env, address: String, height: Option<u64>) -> u128 {
-        let raw = query(deps, env, QueryMsg::VotingPowerAtHeight { address, height }).unwrap();
-        let res: VotingPowerAtHeightResponse = from_slice(&raw).unwrap();
-        res.power.u128()
-    }
-
-    // this tests the member queries
-    fn assert_users(
-        deps: Deps,
-        env: Env,
-        user1_power: Option<u128>,
-        user2_power: Option<u128>,
-        user3_power: Option<u128>,
-        height: Option<u64>,
-    ) {
-        let member1 = get_member(deps, env.clone(), USER1.into(), height);
-        assert_eq!(member1, user1_power.unwrap_or_default());
 
-        let member2 = get_member(deps, env.clone(), USER2.into(), height);
-        assert_eq!(member2, user2_power.unwrap_or_default());
+        // unbond some tokens
+        unbond(deps.as_mut(), 100_000, 99_600, 3_600_000, UNBONDING_PERIOD);
 
-        let member3 = get_member(deps, env.clone(), USER3.into(), height);
-        assert_eq!(member3, user3_power.unwrap_or_default());
+        assert_eq!(
+            11,
+            rewards(deps.as_ref(), USER1),
+            "1_100_000 * 1% / 1_000 = 11"
+        );
+        assert_eq!(6, rewards(deps.as_ref(), USER2), "600_955 * 1% / 1_000 = 6");
+        // USER3 has 400_000 left, this is above min_bound. But the rewards (4_000) would have been less
+        assert_eq!(
+            4,
+            rewards(deps.as_ref(), USER3),
+            "min_bound applied to stake (400_000), before reward multiplier (4_000)"
+        );
+    }
 
-        // compute expected metrics
-        let powers = vec![user1_power, user2_power, user3_power];
-        let sum: u128 = powers.iter().map(|x| x.unwrap_or_default()).sum();
-        let count = powers.iter().filter(|x| x.is_some()).count();
+    #[test]
+    fn rewards_rebonding() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        cw20_instantiate(
+            deps.as_mut(),
+            env.clone(),
+            TOKENS_PER_POWER,
+            Uint128::new(1000),
+            vec![
+                StakeConfig {
+                    unbonding_period: UNBONDING_PERIOD,
+                    voting_multiplier: Decimal::one(),
+                    reward_multiplier: Decimal::percent(1),
+                    exit_vesting: None,
+                },
+                StakeConfig {
+                    unbonding_period: UNBONDING_PERIOD_2,
+                    voting_multiplier: Decimal::from_ratio(Uint128::new(2), Uint128::one()),
+                    reward_multiplier: Decimal::percent(10),
+                    exit_vesting: None,
+                },
+            ],
+        );
 
-        let total = query_total_power(deps, env, height).unwrap();
-        assert_eq!(Uint128::new(sum), total.power);
+        // assert original rewards
+        assert_eq!(0, rewards(deps.as_ref(), USER1));
+        assert_eq!(0, rewards(deps.as_ref(), USER2));
+        assert_eq!(0, rewards(deps.as_ref(), USER3));
 
-        // this is only valid if we are not doing a historical query
-        if height.is_none() {
-            let members = MEMBERS
-                .range(deps.storage, None, None, Order::Ascending)
-                .count();
-            assert_eq!(count, members);
-        }
-    }
+        // bond some tokens for first period
+        bond_cw20(deps.as_mut(), 1_000_000, 180_000, 10_000, 1);
 
-    fn assert_stake_in_period(
-        deps: Deps,
-        env: &Env,
-        user1_stake: u128,
-        user2_stake: u128,
-        user3_stake: u128,
-        unbonding_period: u64,
-    ) {
-        let stake1 = query_staked(deps, env, USER1.into(), unbonding_period).unwrap();
-        assert_eq!(stake1.stake.u128(), user1_stake);
+        // assert updated rewards
+        assert_eq!(
+            10,
+            rewards(deps.as_ref(), USER1),
+            "1_000_000 * 1% / 1_000 = 10"
+        );
+        assert_eq!(1, rewards(deps.as_ref(), USER2), "180_000 * 1% / 1_000 = 1");
+        assert_eq!(
+            0,
+            rewards(deps.as_ref(), USER3),
+            "10_000 * 1% = 100 < min_bond"
+        );
 
-        let stake2 = query_staked(deps, env, USER2.into(), unbonding_period).unwrap();
-        assert_eq!(stake2.stake.u128(), user2_stake);
+        // bond some more tokens for second period
+        bond_cw20_with_period(
+            deps.as_mut(),
+            1_000_000,
+            100_000,
+            9_000,
+            UNBONDING_PERIOD_2,
+            2,
+        );
 
-        let stake3 = query_staked(deps, env, USER3.into(), unbonding_period).unwrap();
-        assert_eq!(stake3.stake.u128(), user3_stake);
-    }
+        // assert updated rewards
+        assert_eq!(
+            110,
+            rewards(deps.as_ref(), USER1),
+            "10 + 1_000_000 * 10% / 1_000 = 110"
+        );
+        assert_eq!(
+            11,
+            rewards(deps.as_ref(), USER2),
+            "1 + 100_000 * 10% / 1_000 = 11"
+        );
+        assert_eq!(
+            1,
+            rewards(deps.as_ref(), USER3),
+            "10_000 * 1% / 1_000 + 9_000 * 10% / 1_000 = 0.1 + 0.9 = 1"
+        );
 
-    // this tests the member queries
-    fn assert_stake(
-        deps: Deps,
-        env: &Env,
-        user1_stake: u128,
-        user2_stake: u128,
-        user3_stake: u128,
-    ) {
-        assert_stake_in_period(
-            deps,
-            env,
-            user1_stake,
-            user2_stake,
-            user3_stake,
+        // rebond tokens
+        rebond_with_period(
+            deps.as_mut(),
+            100_000,
+            180_000,
+            10_000,
             UNBONDING_PERIOD,
+            UNBONDING_PERIOD_2,
+            3,
         );
-    }
 
-    fn assert_cw20_undelegate(res: cosmwasm_std::Response, recipient: &str, amount: u128) {
-        match &res.messages[0].msg {
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr,
-                msg,
-                funds,
-            }) => {
-                assert_eq!(contract_addr.as_str(), CW20_ADDRESS);
-                assert_eq!(funds.len(), 0);
-                let parsed: VestingExecuteMsg = from_slice(msg).unwrap();
-                assert_eq!(
-                    parsed,
-                    VestingExecuteMsg::Undelegate {
-                        recipient: recipient.into(),
-                        amount: Uint128::new(amount)
-                    }
-                );
-            }
-            _ => panic!("Must initiate undelegate!"),
-        }
+        // assert stake
+        assert_stake(deps.as_ref(), &env, 900_000, 0, 0);
+        assert_stake_in_period(
+            deps.as_ref(),
+            &env,
+            1_100_000,
+            280_000,
+            19_000,
+            UNBONDING_PERIOD_2,
+        );
+        // assert updated rewards
+        assert_eq!(
+            119,
+            rewards(deps.as_ref(), USER1),
+            "900_000 * 1% / 1_000 + 1_100_000 * 10% / 1_000 = 9 + 110 = 119"
+        );
+        assert_eq!(
+            28,
+            rewards(deps.as_ref(), USER2),
+            "0 + 280_000 * 10% / 1_000 = 28"
+        );
+        assert_eq!(
+            1,
+            rewards(deps.as_ref(), USER3),
+            "0 + 19_000 * 10% / 1_000 = 1"
+        );
     }
 
     #[test]
-    fn cw20_token_bond() {
+    fn all_staked_pagination_and_empty_periods() {
         let mut deps = mock_dependencies();
         let env = mock_env();
+        let stake_config = [1000, 2000, 3000]
+            .into_iter()
+            .map(|unbonding_period| StakeConfig {
+                unbonding_period,
+                voting_multiplier: Decimal::one(),
+                reward_multiplier: Decimal::one(),
+                exit_vesting: None,
+            })
+            .collect();
         cw20_instantiate(
             deps.as_mut(),
             env.clone(),
             TOKENS_PER_POWER,
             MIN_BOND,
-            vec![StakeConfig {
-                unbonding_period: UNBONDING_PERIOD,
-                voting_multiplier: Decimal::one(),
-                reward_multiplier: Decimal::one(),
-            }],
+            stake_config,
+        );
+        bond_cw20_with_period(deps.as_mut(), 5_000, 0, 0, 1000, 0);
+        bond_cw20_with_period(deps.as_mut(), 6_000, 0, 0, 2000, 0);
+        bond_cw20_with_period(deps.as_mut(), 7_000, 0, 0, 3000, 0);
+        // leaves an empty bucket behind
+        rebond_with_period(deps.as_mut(), 6_000, 0, 0, 2000, 3000, 0);
+
+        // request from an existing client still gets the exact same response
+        let msg = from_slice(br#"{"all_staked":{"address":"user1"}}"#).unwrap();
+        let raw = query(deps.as_ref(), env.clone(), msg).unwrap();
+        assert_eq!(
+            raw.as_slice(),
+            &br#"{"stakes":[{"stake":"5000","total_locked":"0","unbonding_period":1000,"cw20_contract":"wasm1234567890"},{"stake":"0","total_locked":"0","unbonding_period":2000,"cw20_contract":"wasm1234567890"},{"stake":"13000","total_locked":"0","unbonding_period":3000,"cw20_contract":"wasm1234567890"}]}"#[..]
         );
 
-        let initial_height = env.block.height;
+        let periods = |start_after, limit, include_empty| {
+            query_all_staked(
+                deps.as_ref(),
+                env.clone(),
+                USER1.to_owned(),
+                start_after,
+                limit,
+                include_empty,
+            )
+            .unwrap()
+            .stakes
+            .into_iter()
+            .map(|s| s.unbonding_period)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(periods(None, None, true), vec![1000, 2000, 3000]);
+        assert_eq!(periods(None, None, false), vec![1000, 3000]);
+        assert_eq!(periods(None, Some(2), true), vec![1000, 2000]);
+        assert_eq!(periods(None, Some(2), false), vec![1000, 3000]);
+        assert_eq!(periods(Some(1000), Some(1), true), vec![2000]);
+        assert_eq!(periods(Some(1000), Some(1), false), vec![3000]);
+        assert_eq!(periods(Some(3000), None, true), Vec::<u64>::new());
+
+        // rebonding down locks tokens until the difference in periods passed
+        rebond_with_period(deps.as_mut(), 1_000, 0, 0, 3000, 1000, 0);
+        let stakes = query_all_staked(
+            deps.as_ref(),
+            env.clone(),
+            USER1.to_owned(),
+            None,
+            None,
+            false,
+        )
+        .unwrap()
+        .stakes;
+        assert_eq!(stakes[0].total_locked, Uint128::new(1_000));
+        assert_eq!(
+            stakes[0].earliest_unlock,
+            Some(env.block.time.plus_seconds(2000))
+        );
+        assert_eq!(stakes[1].earliest_unlock, None);
 
-        // Assert original powers
-        assert_users(deps.as_ref(), env.clone(), None, None, None, None);
+        let mut env = env;
+        env.block.time = env.block.time.plus_seconds(2000);
+        let stakes = query_all_staked(deps.as_ref(), env, USER1.to_owned(), None, None, false)
+            .unwrap()
+            .stakes;
+        assert_eq!(stakes[0].total_locked, Uint128::zero());
+        assert_eq!(stakes[0].earliest_unlock, None);
+    }
 
-        // ensure it rounds down, and respects cut-off
-        bond_cw20(deps.as_mut(), 12_000, 7_500, 4_000, 1);
+    #[test]
+    fn distribution_weight_scales_rewards() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
 
-        // Assert updated powers
-        assert_stake(deps.as_ref(), &env, 12_000, 7_500, 4_000);
-        assert_users(deps.as_ref(), env.clone(), Some(12), Some(7), None, None);
+        bond_cw20(deps.as_mut(), 10_000, 10_000, 0, 1);
+        assert_eq!(10, rewards(deps.as_ref(), USER1));
+        assert_eq!(10, rewards(deps.as_ref(), USER2));
 
-        // assert users at initial height
-        assert_users(deps.as_ref(), env, None, None, None, Some(initial_height));
-    }
+        let weight_of = |deps: Deps, user: &str| {
+            query_distribution_weight(deps, user.to_owned())
+                .unwrap()
+                .weight
+        };
+        assert_eq!(weight_of(deps.as_ref(), USER1), Decimal::one());
 
-    #[test]
-    fn cw20_token_claim() {
-        let unbonding_period: u64 = 20;
+        let set_weight = |weight: Decimal| ExecuteMsg::SetDistributionWeight {
+            address: USER1.to_owned(),
+            weight,
+        };
 
-        let mut deps = mock_dependencies();
-        let mut env = mock_env();
-        let unbonding = Duration::Time(unbonding_period);
-        cw20_instantiate(
+        // only admin can set it
+        let err = execute(
             deps.as_mut(),
             env.clone(),
-            TOKENS_PER_POWER,
-            MIN_BOND,
-            vec![StakeConfig {
-                unbonding_period,
-                voting_multiplier: Decimal::one(),
-                reward_multiplier: Decimal::one(),
-            }],
-        );
-
-        // bond some tokens
-        bond_cw20(deps.as_mut(), 20_000, 13_500, 500, 5);
+            mock_info(USER1, &[]),
+            set_weight(Decimal::percent(250)),
+        )
+        .unwrap_err();
+        assert_eq!(err, AdminError::NotAdmin {}.into());
 
-        // unbond part
-        unbond(deps.as_mut(), 7_900, 4_600, 0, unbonding_period);
+        // weight is capped
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(INIT_ADMIN, &[]),
+            set_weight(Decimal::percent(1001)),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidDistributionWeight {
+                max: Decimal::percent(1000)
+            }
+        );
 
-        // Assert updated powers
-        assert_stake(deps.as_ref(), &env, 12_100, 8_900, 500);
-        assert_users(deps.as_ref(), env.clone(), Some(12), Some(8), None, None);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(INIT_ADMIN, &[]),
+            set_weight(Decimal::percent(250)),
+        )
+        .unwrap();
+        assert_eq!(weight_of(deps.as_ref(), USER1), Decimal::percent(250));
+        assert_eq!(25, rewards(deps.as_ref(), USER1));
+        assert_eq!(10, rewards(deps.as_ref(), USER2));
+        assert_eq!(
+            query_total_rewards(deps.as_ref()).unwrap().rewards.u128(),
+            35
+        );
 
-        // with proper claims
-        env.block.time = env.block.time.plus_seconds(unbonding_period);
-        let expires = unbonding.after(&env.block);
+        // weight is applied to further stake changes
+        bond_cw20(deps.as_mut(), 5_000, 0, 0, 2);
+        assert_eq!(37, rewards(deps.as_ref(), USER1), "15 * 2.5 rounded down");
         assert_eq!(
-            get_claims(deps.as_ref(), &Addr::unchecked(USER1)),
-            vec![Claim::new(7_900, expires)]
+            query_total_rewards(deps.as_ref()).unwrap().rewards.u128(),
+            47
         );
 
-        // wait til they expire and get payout
-        env.block.time = env.block.time.plus_seconds(unbonding_period);
-        let res = execute(
+        // zero weight removes reward power, stake and votes are untouched
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(INIT_ADMIN, &[]),
+            set_weight(Decimal::zero()),
+        )
+        .unwrap();
+        assert_eq!(0, rewards(deps.as_ref(), USER1));
+        assert_stake(deps.as_ref(), &env, 15_000, 10_000, 0);
+        assert_users(deps.as_ref(), env.clone(), Some(15), Some(10), None, None);
+
+        // resetting to 1.0 restores the default
+        execute(
             deps.as_mut(),
             env,
-            mock_info(USER1, &[]),
-            ExecuteMsg::Claim {},
+            mock_info(INIT_ADMIN, &[]),
+            set_weight(Decimal::one()),
         )
         .unwrap();
-        assert_eq!(res.messages.len(), 1);
-        assert_cw20_undelegate(res, USER1, 7_900)
+        assert_eq!(weight_of(deps.as_ref(), USER1), Decimal::one());
+        assert_eq!(15, rewards(deps.as_ref(), USER1));
+        assert_eq!(
+            query_total_rewards(deps.as_ref()).unwrap().rewards.u128(),
+            25
+        );
     }
 
     #[test]
-    fn raw_queries_work() {
-        // add will over-write and remove have no effect
+    fn distribution_rounding_does_not_accumulate() {
         let mut deps = mock_dependencies();
-        default_instantiate(deps.as_mut(), mock_env());
-        // Set values as (11, 6, None)
-        bond_cw20(deps.as_mut(), 11_000, 6_000, 0, 1);
-
-        // get total from raw key
-        let total_raw = deps.storage.get(TOTAL_KEY.as_bytes()).unwrap();
-        let total: Uint128 = from_slice(&total_raw).unwrap();
-        assert_eq!(17, total.u128());
-
-        // get member votes from raw key
-        let member2_raw = deps.storage.get(&member_key(USER2)).unwrap();
-        let member2: Uint128 = from_slice(&member2_raw).unwrap();
-        assert_eq!(6, member2.u128());
+        let env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
+        let members = [USER1, USER2, USER3];
+        bond_cw20(deps.as_mut(), 7_000, 11_000, 13_000, 0);
+        let mut staked = [7_000u128, 11_000, 13_000];
+
+        // simple deterministic pseudo-random numbers
+        let mut seed = 42u64;
+        let mut random = |max: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % max
+        };
 
-        // and execute misses
-        let member3_raw = deps.storage.get(&member_key(USER3));
-        assert_eq!(None, member3_raw);
-    }
+        for _ in 0..1000 {
+            let member = random(3) as usize;
+            match random(4) {
+                // small distribution, mostly not divisible by the total reward power
+                0 | 1 => {
+                    let amount = random(50) as u128 + 1;
+                    let total = TOTAL_REWARDS.load(&deps.storage).unwrap().u128();
+                    let mut distribution = DISTRIBUTION.load(&deps.storage).unwrap();
+                    distribution.distribute(amount, total);
+                    DISTRIBUTION.save(&mut deps.storage, &distribution).unwrap();
+                }
+                2 => {
+                    let amount = random(5_000) as u128 + 1;
+                    let msg = ExecuteMsg::ReceiveDelegation(Cw20ReceiveDelegationMsg {
+                        sender: members[member].to_owned(),
+                        amount: Uint128::new(amount),
+                        msg: to_binary(&ReceiveDelegationMsg::Delegate {
+                            unbonding_period: UNBONDING_PERIOD,
+                        })
+                        .unwrap(),
+                    });
+                    execute(
+                        deps.as_mut(),
+                        env.clone(),
+                        mock_info(CW20_ADDRESS, &[]),
+                        msg,
+                    )
+                    .unwrap();
+                    staked[member] += amount;
+                }
+                _ => {
+                    // keep every member above min_bond, so the total reward power never drops to zero
+                    let amount = (random(5_000) as u128 + 1).min(staked[member] - MIN_BOND.u128());
+                    if amount > 0 {
+                        let msg = ExecuteMsg::Unbond {
+                            tokens: Uint128::new(amount),
+                            unbonding_period: UNBONDING_PERIOD,
+                            claim_recipient: None,
+                        };
+                        execute(
+                            deps.as_mut(),
+                            env.clone(),
+                            mock_info(members[member], &[]),
+                            msg,
+                        )
+                        .unwrap();
+                        staked[member] -= amount;
+                    }
+                }
+            }
+            if random(10) == 0 {
+                let msg = ExecuteMsg::WithdrawRewards {
+                    owner: None,
+                    receiver: None,
+                    receiver_msg: None,
+                };
+                execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(members[member], &[]),
+                    msg,
+                )
+                .unwrap();
+            }
+        }
 
-    fn get_claims(deps: Deps, addr: &Addr) -> Vec<Claim> {
-        CLAIMS.query_claims(deps, addr).unwrap().claims
+        let distribution = DISTRIBUTION.load(&deps.storage).unwrap();
+        let credited: u128 = members
+            .iter()
+            .map(|member| {
+                let addr = Addr::unchecked(*member);
+                let adjustment = WITHDRAW_ADJUSTMENT.load(&deps.storage, &addr).unwrap();
+                let withdrawable = crate::distribution::withdrawable_rewards(
+                    &deps.storage,
+                    &addr,
+                    &distribution,
+                    &adjustment,
+                )
+                .unwrap();
+                (withdrawable + adjustment.withdrawn_rewards).u128()
+            })
+            .sum();
+        let distributed = distribution.distributed_total.u128();
+        assert!(distributed >= credited);
+        assert!(
+            distributed - credited <= members.len() as u128,
+            "{} distributed, but only {} credited",
+            distributed,
+            credited
+        );
     }
 
     #[test]
-    fn unbond_claim_workflow() {
+    fn update_power_config_and_recompute() {
         let mut deps = mock_dependencies();
-        let mut env = mock_env();
-        default_instantiate(deps.as_mut(), env.clone());
-
-        // create some data
-        bond_cw20(deps.as_mut(), 12_000, 7_500, 4_000, 5);
-        unbond(deps.as_mut(), 4_500, 2_600, 0, 10);
-        env.block.time = env.block.time.plus_seconds(10);
-
-        // check the claims for each user
-        let expires = Duration::Time(UNBONDING_PERIOD).after(&env.block);
-        assert_eq!(
-            get_claims(deps.as_ref(), &Addr::unchecked(USER1)),
-            vec![Claim::new(4_500, expires)]
-        );
-        assert_eq!(
-            get_claims(deps.as_ref(), &Addr::unchecked(USER2)),
-            vec![Claim::new(2_600, expires)]
-        );
-        assert_eq!(get_claims(deps.as_ref(), &Addr::unchecked(USER3)), vec![]);
-
-        // do another unbond later on
-        let mut env2 = mock_env();
-        env2.block.time = env2.block.time.plus_seconds(22);
-        unbond(deps.as_mut(), 0, 1_345, 1_500, 22);
+        default_instantiate(deps.as_mut(), mock_env());
+        bond_cw20(deps.as_mut(), 12_000, 7_500, 4_000, 1);
+        assert_users(deps.as_ref(), mock_env(), Some(12), Some(7), None, None);
 
-        // with updated claims
-        let expires2 = Duration::Time(UNBONDING_PERIOD).after(&env2.block);
-        assert_eq!(
-            get_claims(deps.as_ref(), &Addr::unchecked(USER1)),
-            vec![Claim::new(4_500, expires)]
-        );
-        assert_eq!(
-            get_claims(deps.as_ref(), &Addr::unchecked(USER2)),
-            vec![Claim::new(2_600, expires), Claim::new(1_345, expires2)]
-        );
-        assert_eq!(
-            get_claims(deps.as_ref(), &Addr::unchecked(USER3)),
-            vec![Claim::new(1_500, expires2)]
-        );
+        let mut env = mock_env();
+        env.block.height += 1;
+        let admin_info = mock_info(INIT_ADMIN, &[]);
 
-        // nothing can be withdrawn yet
+        // nothing to recompute yet
+        let recompute = |start_after: Option<&str>| ExecuteMsg::RecomputeMembers {
+            start_after: start_after.map(str::to_owned),
+            limit: Some(2),
+        };
         let err = execute(
             deps.as_mut(),
-            env2,
+            env.clone(),
             mock_info(USER1, &[]),
-            ExecuteMsg::Claim {},
+            recompute(None),
         )
         .unwrap_err();
-        assert_eq!(err, ContractError::NothingToClaim {});
+        assert_eq!(err, ContractError::NoRecomputePending {});
 
-        // now mature first section, withdraw that
-        let mut env3 = mock_env();
-        env3.block.time = env3.block.time.plus_seconds(UNBONDING_PERIOD + 10);
-        // first one can now release
-        let res = execute(
+        // only admin can update
+        let msg = ExecuteMsg::UpdatePowerConfig {
+            tokens_per_power: Some(Uint128::new(500)),
+            min_bond: Some(Uint128::new(3_000)),
+        };
+        let err = execute(
             deps.as_mut(),
-            env3.clone(),
+            env.clone(),
             mock_info(USER1, &[]),
-            ExecuteMsg::Claim {},
+            msg.clone(),
         )
-        .unwrap();
-        assert_cw20_undelegate(res, USER1, 4_500);
+        .unwrap_err();
+        assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
 
-        // second releases partially
-        let res = execute(
+        let err = execute(
             deps.as_mut(),
-            env3.clone(),
-            mock_info(USER2, &[]),
-            ExecuteMsg::Claim {},
+            env.clone(),
+            admin_info.clone(),
+            ExecuteMsg::UpdatePowerConfig {
+                tokens_per_power: Some(Uint128::zero()),
+                min_bond: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ZeroTokensPerPower {});
+
+        execute(deps.as_mut(), env.clone(), admin_info, msg).unwrap();
+        let config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(config.tokens_per_power, Uint128::new(500));
+        assert_eq!(config.min_bond, Uint128::new(3_000));
+        // powers are not changed until recomputed
+        assert_users(deps.as_ref(), env.clone(), Some(12), Some(7), None, None);
+        assert!(query_recompute_status(deps.as_ref()).unwrap().pending);
+
+        // first page
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(USER3, &[]),
+            recompute(None),
         )
         .unwrap();
-        assert_cw20_undelegate(res, USER2, 2_600);
+        assert_users(deps.as_ref(), env.clone(), Some(24), Some(15), None, None);
+        assert_eq!(
+            query_recompute_status(deps.as_ref()).unwrap(),
+            RecomputeStatusResponse {
+                pending: true,
+                last_processed: Some(Addr::unchecked(USER2)),
+                processed: 2,
+            }
+        );
 
-        // but the third one cannot release
+        // has to continue where the previous page stopped
         let err = execute(
             deps.as_mut(),
-            env3,
+            env.clone(),
             mock_info(USER3, &[]),
-            ExecuteMsg::Claim {},
+            recompute(Some(USER1)),
         )
         .unwrap_err();
-        assert_eq!(err, ContractError::NothingToClaim {});
-
-        // claims updated properly
-        assert_eq!(get_claims(deps.as_ref(), &Addr::unchecked(USER1)), vec![]);
-        assert_eq!(
-            get_claims(deps.as_ref(), &Addr::unchecked(USER2)),
-            vec![Claim::new(1_345, expires2)]
-        );
         assert_eq!(
-            get_claims(deps.as_ref(), &Addr::unchecked(USER3)),
-            vec![Claim::new(1_500, expires2)]
+            err,
+            ContractError::InvalidRecomputeStart {
+                expected: Some(USER2.to_owned())
+            }
         );
 
-        // add another few claims for 2
-        unbond(deps.as_mut(), 0, 600, 0, 6 + UNBONDING_PERIOD);
-        unbond(deps.as_mut(), 0, 1_005, 0, 10 + UNBONDING_PERIOD);
-
-        // ensure second can claim all tokens at once
-        let mut env4 = mock_env();
-        env4.block.time = env4.block.time.plus_seconds(UNBONDING_PERIOD * 2 + 12);
-        let res = execute(
+        // second page, USER3 is above the new min_bond
+        execute(
             deps.as_mut(),
-            env4,
-            mock_info(USER2, &[]),
-            ExecuteMsg::Claim {},
+            env.clone(),
+            mock_info(USER3, &[]),
+            recompute(Some(USER2)),
         )
         .unwrap();
-        assert_cw20_undelegate(res, USER2, 2_950); // 1_345 + 600 + 1_005
-        assert_eq!(get_claims(deps.as_ref(), &Addr::unchecked(USER2)), vec![]);
-    }
+        assert_users(
+            deps.as_ref(),
+            env.clone(),
+            Some(24),
+            Some(15),
+            Some(8),
+            None,
+        );
+        assert_eq!(rewards(deps.as_ref(), USER1), 24);
+        assert_eq!(rewards(deps.as_ref(), USER3), 8);
+        assert_eq!(
+            query_total_rewards(deps.as_ref()).unwrap().rewards,
+            Uint128::new(47)
+        );
+        assert!(!query_recompute_status(deps.as_ref()).unwrap().pending);
 
-    fn rewards(deps: Deps, user: &str) -> u128 {
-        query_rewards(deps, user.to_string())
-            .unwrap()
-            .rewards
-            .u128()
+        // powers from before the change are still available
+        assert_users(
+            deps.as_ref(),
+            env.clone(),
+            Some(12),
+            Some(7),
+            None,
+            Some(env.block.height),
+        );
+
+        let err = execute(deps.as_mut(), env, mock_info(USER3, &[]), recompute(None)).unwrap_err();
+        assert_eq!(err, ContractError::NoRecomputePending {});
     }
 
     #[test]
-    fn rewards_saved() {
+    fn recompute_voting_power_repairs_members() {
         let mut deps = mock_dependencies();
-        let env = mock_env();
-        cw20_instantiate(
-            deps.as_mut(),
-            env,
-            TOKENS_PER_POWER,
-            MIN_BOND,
-            vec![StakeConfig {
-                unbonding_period: UNBONDING_PERIOD,
-                voting_multiplier: Decimal::one(),
-                reward_multiplier: Decimal::percent(1),
-            }],
-        );
-
-        // assert original rewards
-        assert_eq!(0, rewards(deps.as_ref(), USER1));
-        assert_eq!(0, rewards(deps.as_ref(), USER2));
-        assert_eq!(0, rewards(deps.as_ref(), USER3));
+        default_instantiate(deps.as_mut(), mock_env());
+        bond_cw20(deps.as_mut(), 12_000, 7_500, 4_000, 1);
+        assert_users(deps.as_ref(), mock_env(), Some(12), Some(7), None, None);
 
-        // ensure it rounds down, and respects cut-off
-        bond_cw20(deps.as_mut(), 1_200_000, 770_000, 4_000_000, 1);
+        // corrupt the powers of USER1 and USER3 as a buggy migration might
+        let mut env = mock_env();
+        env.block.height += 1;
+        let height = env.block.height;
+        let user1 = Addr::unchecked(USER1);
+        let user3 = Addr::unchecked(USER3);
+        MEMBERS
+            .save(&mut deps.storage, &user1, &Uint128::new(3), height)
+            .unwrap();
+        MEMBERS
+            .save(&mut deps.storage, &user3, &Uint128::new(5), height)
+            .unwrap();
+        TOTAL_VOTES
+            .save(&mut deps.storage, &Uint128::new(15), height)
+            .unwrap();
+        env.block.height += 1;
+
+        // only admin can repair
+        let msg = ExecuteMsg::RecomputeVotingPower {
+            address: USER1.to_owned(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(USER1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
 
-        // assert updated rewards
-        assert_eq!(
-            12,
-            rewards(deps.as_ref(), USER1),
-            "1_200_000 * 1% / 1_000 = 12"
-        );
-        assert_eq!(7, rewards(deps.as_ref(), USER2), "770_000 * 1% / 1_000 = 7");
-        assert_eq!(
-            40,
-            rewards(deps.as_ref(), USER3),
-            "4_000_000 * 1% / 1_000 = 4"
-        );
+        let admin_info = mock_info(INIT_ADMIN, &[]);
+        let res = execute(deps.as_mut(), env.clone(), admin_info.clone(), msg).unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res.attributes.contains(&attr("old_power", "3")));
+        assert!(res.attributes.contains(&attr("new_power", "12")));
+
+        // USER3 is below min_bond and should not have any power
+        let msg = ExecuteMsg::RecomputeVotingPower {
+            address: USER3.to_owned(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), admin_info.clone(), msg.clone()).unwrap();
+        assert!(res.attributes.contains(&attr("old_power", "5")));
+        assert!(res.attributes.contains(&attr("new_power", "0")));
 
-        // unbond some tokens
-        unbond(deps.as_mut(), 100_000, 99_600, 3_600_000, UNBONDING_PERIOD);
+        assert_users(deps.as_ref(), env.clone(), Some(12), Some(7), None, None);
+        assert_eq!(TOTAL_VOTES.load(&deps.storage).unwrap().u128(), 19);
 
-        assert_eq!(
-            11,
-            rewards(deps.as_ref(), USER1),
-            "1_100_000 * 1% / 1_000 = 11"
-        );
-        assert_eq!(6, rewards(deps.as_ref(), USER2), "600_955 * 1% / 1_000 = 6");
-        // USER3 has 400_000 left, this is above min_bound. But the rewards (4_000) would have been less
-        assert_eq!(
-            4,
-            rewards(deps.as_ref(), USER3),
-            "min_bound applied to stake (400_000), before reward multiplier (4_000)"
-        );
+        // nothing changes for a consistent member
+        let res = execute(deps.as_mut(), env, admin_info, msg).unwrap();
+        assert!(res.attributes.contains(&attr("old_power", "0")));
+        assert!(res.attributes.contains(&attr("new_power", "0")));
+        assert_eq!(TOTAL_VOTES.load(&deps.storage).unwrap().u128(), 19);
     }
 
     #[test]
-    fn rewards_rebonding() {
+    fn admin_multisig_workflow() {
         let mut deps = mock_dependencies();
-        let env = mock_env();
-        cw20_instantiate(
+        let mut env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
+        let members = ["member1", "member2", "member3"];
+        let set_multisig = |addresses: &[&str], min_approvals: u32| ExecuteMsg::SetAdminMultisig {
+            addresses: addresses.iter().map(|a| a.to_string()).collect(),
+            min_approvals,
+        };
+
+        // only admin can set the multisig, and it has to be valid
+        let err = execute(
             deps.as_mut(),
             env.clone(),
-            TOKENS_PER_POWER,
-            Uint128::new(1000),
-            vec![
-                StakeConfig {
-                    unbonding_period: UNBONDING_PERIOD,
-                    voting_multiplier: Decimal::one(),
-                    reward_multiplier: Decimal::percent(1),
-                },
-                StakeConfig {
-                    unbonding_period: UNBONDING_PERIOD_2,
-                    voting_multiplier: Decimal::from_ratio(Uint128::new(2), Uint128::one()),
-                    reward_multiplier: Decimal::percent(10),
-                },
-            ],
-        );
+            mock_info(USER1, &[]),
+            set_multisig(&members, 2),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
+        for (addresses, min_approvals) in [
+            (&members[..], 0),
+            (&members[..], 4),
+            (&[members[0], members[0]][..], 1),
+        ] {
+            let msg = set_multisig(addresses, min_approvals);
+            let err =
+                execute(deps.as_mut(), env.clone(), mock_info(INIT_ADMIN, &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidAdminMultisig {});
+        }
+        let msg = set_multisig(&members, 2);
+        execute(deps.as_mut(), env.clone(), mock_info(INIT_ADMIN, &[]), msg).unwrap();
 
-        // assert original rewards
-        assert_eq!(0, rewards(deps.as_ref(), USER1));
-        assert_eq!(0, rewards(deps.as_ref(), USER2));
-        assert_eq!(0, rewards(deps.as_ref(), USER3));
+        // the old admin lost its rights
+        let add_hook = AdminAction::AddHook {
+            addr: "hook".to_owned(),
+            gas_limit: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(INIT_ADMIN, &[]),
+            add_hook.clone().into(),
+        )
+        .unwrap_err();
+        assert_eq!(err, HookError::Admin(AdminError::NotAdmin {}).into());
 
-        // bond some tokens for first period
-        bond_cw20(deps.as_mut(), 1_000_000, 180_000, 10_000, 1);
+        // only members can propose
+        let expires_at = env.block.time.seconds() + 100;
+        let propose = ExecuteMsg::ProposeAdminAction {
+            action: add_hook,
+            expires_at,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(USER1, &[]),
+            propose.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(members[0], &[]),
+            propose.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1], attr("proposal_id", "1"));
 
-        // assert updated rewards
+        let approve = ExecuteMsg::ApproveAdminAction { proposal_id: 1 };
+        let execute_action = ExecuteMsg::ExecuteAdminAction { proposal_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(members[0], &[]),
+            execute_action.clone(),
+        )
+        .unwrap_err();
         assert_eq!(
-            10,
-            rewards(deps.as_ref(), USER1),
-            "1_000_000 * 1% / 1_000 = 10"
+            err,
+            ContractError::NotEnoughApprovals {
+                approvals: 1,
+                required: 2
+            }
         );
-        assert_eq!(1, rewards(deps.as_ref(), USER2), "180_000 * 1% / 1_000 = 1");
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(members[0], &[]),
+            approve.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyApproved { proposal_id: 1 });
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(members[1], &[]),
+            approve,
+        )
+        .unwrap();
+
+        // only approvers can execute
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(members[2], &[]),
+            execute_action.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(members[1], &[]),
+            execute_action.clone(),
+        )
+        .unwrap();
+        assert_eq!(query_hooks(deps.as_ref()).unwrap().hooks, vec!["hook"]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(members[1], &[]),
+            execute_action,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AdminProposalExecuted { proposal_id: 1 });
+
+        // proposals cannot be approved after expiring
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(members[0], &[]),
+            propose,
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(members[1], &[]),
+            ExecuteMsg::ApproveAdminAction { proposal_id: 2 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AdminProposalExpired { proposal_id: 2 });
+
+        let raw = query(deps.as_ref(), env, QueryMsg::AdminMultisig {}).unwrap();
+        let res: AdminMultisigResponse = from_slice(&raw).unwrap();
+        assert_eq!(res.addresses, members.map(Addr::unchecked).to_vec());
+        assert_eq!(res.min_approvals, 2);
+    }
+
+    #[test]
+    fn governance_contracts_record_votes() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
+        let admin_info = mock_info(INIT_ADMIN, &[]);
+        let gov = "governance";
+
+        // only admin can register
+        let register = ExecuteMsg::RegisterGovernanceContract {
+            address: gov.to_owned(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(USER1, &[]),
+            register.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            register.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), env.clone(), admin_info.clone(), register).unwrap_err();
         assert_eq!(
-            0,
-            rewards(deps.as_ref(), USER3),
-            "10_000 * 1% = 100 < min_bond"
+            err,
+            ContractError::GovernanceContractAlreadyRegistered {
+                address: gov.to_owned()
+            }
         );
+        let raw = query(deps.as_ref(), env.clone(), QueryMsg::GovernanceContracts {}).unwrap();
+        let res: GovernanceContractsResponse = from_slice(&raw).unwrap();
+        assert_eq!(res.contracts, vec![Addr::unchecked(gov)]);
 
-        // bond some more tokens for second period
-        bond_cw20_with_period(
+        // only registered contracts can record votes
+        let record = ExecuteMsg::RecordVote {
+            voter: USER1.to_owned(),
+        };
+        let err = execute(
             deps.as_mut(),
-            1_000_000,
-            100_000,
-            9_000,
-            UNBONDING_PERIOD_2,
-            2,
-        );
+            env.clone(),
+            mock_info(USER2, &[]),
+            record.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(gov, &[]),
+            record.clone(),
+        )
+        .unwrap();
+        env.block.height += 10;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(gov, &[]),
+            record.clone(),
+        )
+        .unwrap();
 
-        // assert updated rewards
-        assert_eq!(
-            110,
-            rewards(deps.as_ref(), USER1),
-            "10 + 1_000_000 * 10% / 1_000 = 110"
-        );
+        let user1 = Addr::unchecked(USER1);
+        assert_eq!(VOTES_CAST.load(&deps.storage, &user1).unwrap(), 2);
         assert_eq!(
-            11,
-            rewards(deps.as_ref(), USER2),
-            "1 + 100_000 * 10% / 1_000 = 11"
+            LAST_VOTE_HEIGHT.load(&deps.storage, &user1).unwrap(),
+            env.block.height
         );
+
+        // unregistered contracts cannot record anymore
+        let unregister = ExecuteMsg::UnregisterGovernanceContract {
+            address: gov.to_owned(),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            unregister.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), env.clone(), admin_info, unregister).unwrap_err();
         assert_eq!(
-            0,
-            rewards(deps.as_ref(), USER3),
-            "0 + 9_000 * 10% = 900 < min_bond"
+            err,
+            ContractError::GovernanceContractNotRegistered {
+                address: gov.to_owned()
+            }
         );
+        let err = execute(deps.as_mut(), env, mock_info(gov, &[]), record).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
 
-        // rebond tokens
-        rebond_with_period(
+    #[test]
+    fn lock_new_staking() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
+        bond_cw20(deps.as_mut(), 12_000, 0, 0, 0);
+
+        let lock = ExecuteMsg::LockNewStaking {
+            expires_at_height: env.block.height + 100,
+        };
+        let err = execute(
             deps.as_mut(),
-            100_000,
-            180_000,
-            10_000,
-            UNBONDING_PERIOD,
-            UNBONDING_PERIOD_2,
-            3,
-        );
+            env.clone(),
+            mock_info(USER1, &[]),
+            lock.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
+        execute(deps.as_mut(), env.clone(), mock_info(INIT_ADMIN, &[]), lock).unwrap();
 
-        // assert stake
-        assert_stake(deps.as_ref(), &env, 900_000, 0, 0);
-        assert_stake_in_period(
-            deps.as_ref(),
-            &env,
-            1_100_000,
-            280_000,
-            19_000,
-            UNBONDING_PERIOD_2,
-        );
-        // assert updated rewards
+        let res = query_staking_lock(deps.as_ref(), env.clone()).unwrap();
         assert_eq!(
-            119,
-            rewards(deps.as_ref(), USER1),
-            "900_000 * 1% / 1_000 + 1_100_000 * 10% / 1_000 = 9 + 110 = 119"
-        );
-        assert_eq!(
-            28,
-            rewards(deps.as_ref(), USER2),
-            "0 + 280_000 * 10% / 1_000 = 28"
+            res,
+            StakingLockResponse {
+                locked: true,
+                unlocks_at: Some(env.block.height + 100)
+            }
         );
+
+        // bonding is locked
+        let bond = ExecuteMsg::ReceiveDelegation(Cw20ReceiveDelegationMsg {
+            sender: USER2.to_string(),
+            amount: Uint128::new(5_000),
+            msg: to_binary(&ReceiveDelegationMsg::Delegate {
+                unbonding_period: UNBONDING_PERIOD,
+            })
+            .unwrap(),
+        });
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CW20_ADDRESS, &[]),
+            bond.clone(),
+        )
+        .unwrap_err();
         assert_eq!(
-            1,
-            rewards(deps.as_ref(), USER3),
-            "0 + 19_000 * 10% / 1_000 = 1"
+            err,
+            ContractError::StakingLocked {
+                unlocks_at: env.block.height + 100
+            }
         );
+
+        // unbonding still works
+        let unbond = ExecuteMsg::Unbond {
+            tokens: Uint128::new(2_000),
+            unbonding_period: UNBONDING_PERIOD,
+            claim_recipient: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), unbond).unwrap();
+
+        // lock expires by itself
+        env.block.height += 100;
+        let res = query_staking_lock(deps.as_ref(), env.clone()).unwrap();
+        assert!(!res.locked);
+        assert_eq!(res.unlocks_at, None);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CW20_ADDRESS, &[]),
+            bond,
+        )
+        .unwrap();
+        assert_stake(deps.as_ref(), &env, 10_000, 5_000, 0);
     }
 
     #[test]
@@ -1405,7 +5662,7 @@ mod tests {
         let env = mock_env();
         default_instantiate(deps.as_mut(), env.clone());
 
-        let hooks = HOOKS.query_hooks(deps.as_ref()).unwrap();
+        let hooks = query_hooks(deps.as_ref()).unwrap();
         assert!(hooks.hooks.is_empty());
 
         let contract1 = String::from("hook1");
@@ -1413,6 +5670,7 @@ mod tests {
 
         let add_msg = ExecuteMsg::AddHook {
             addr: contract1.clone(),
+            gas_limit: None,
         };
 
         // non-admin cannot add hook
@@ -1435,7 +5693,7 @@ mod tests {
             add_msg.clone(),
         )
         .unwrap();
-        let hooks = HOOKS.query_hooks(deps.as_ref()).unwrap();
+        let hooks = query_hooks(deps.as_ref()).unwrap();
         assert_eq!(hooks.hooks, vec![contract1.clone()]);
 
         // cannot remove a non-registered contract
@@ -1448,9 +5706,10 @@ mod tests {
         // add second contract
         let add_msg2 = ExecuteMsg::AddHook {
             addr: contract2.clone(),
+            gas_limit: None,
         };
         let _ = execute(deps.as_mut(), env.clone(), admin_info.clone(), add_msg2).unwrap();
-        let hooks = HOOKS.query_hooks(deps.as_ref()).unwrap();
+        let hooks = query_hooks(deps.as_ref()).unwrap();
         assert_eq!(hooks.hooks, vec![contract1.clone(), contract2.clone()]);
 
         // cannot re-add an existing contract
@@ -1464,7 +5723,7 @@ mod tests {
 
         // remove the original
         let _ = execute(deps.as_mut(), env, admin_info, remove_msg).unwrap();
-        let hooks = HOOKS.query_hooks(deps.as_ref()).unwrap();
+        let hooks = query_hooks(deps.as_ref()).unwrap();
         assert_eq!(hooks.hooks, vec![contract2]);
     }
 
@@ -1474,7 +5733,7 @@ mod tests {
         let env = mock_env();
         default_instantiate(deps.as_mut(), env.clone());
 
-        let hooks = HOOKS.query_hooks(deps.as_ref()).unwrap();
+        let hooks = query_hooks(deps.as_ref()).unwrap();
         assert!(hooks.hooks.is_empty());
 
         let contract1 = String::from("hook1");
@@ -1484,9 +5743,11 @@ mod tests {
         let admin_info = mock_info(INIT_ADMIN, &[]);
         let add_msg = ExecuteMsg::AddHook {
             addr: contract1.clone(),
+            gas_limit: None,
         };
         let add_msg2 = ExecuteMsg::AddHook {
             addr: contract2.clone(),
+            gas_limit: None,
         };
         for msg in vec![add_msg, add_msg2] {
             let _ = execute(deps.as_mut(), env.clone(), admin_info.clone(), msg).unwrap();
@@ -1523,6 +5784,7 @@ mod tests {
         let msg = ExecuteMsg::Unbond {
             tokens: Uint128::new(7_300),
             unbonding_period: UNBONDING_PERIOD,
+            claim_recipient: None,
         };
         let info = mock_info(USER1, &[]);
         let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
@@ -1537,6 +5799,120 @@ mod tests {
         assert_eq!(res.messages, vec![msg1, msg2]);
     }
 
+    #[test]
+    fn gas_limited_hooks() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
+
+        // register a gas limited hook
+        let admin_info = mock_info(INIT_ADMIN, &[]);
+        let hook = String::from("hook1");
+        let add_msg = ExecuteMsg::AddHook {
+            addr: hook.clone(),
+            gas_limit: Some(100_000),
+        };
+        let res = execute(deps.as_mut(), env.clone(), admin_info.clone(), add_msg).unwrap();
+        assert_eq!(res.attributes[2], ("gas_limit", "100000"));
+
+        // bonding dispatches it with the gas limit, not failing on error
+        let info = mock_info(CW20_ADDRESS, &[]);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ReceiveDelegation(Cw20ReceiveDelegationMsg {
+                sender: USER1.to_string(),
+                amount: Uint128::new(13_800),
+                msg: to_binary(&ReceiveDelegationMsg::Delegate {
+                    unbonding_period: UNBONDING_PERIOD,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+        let diff = MemberDiff::new(USER1, None, Some(13u128.into()));
+        let hook_msg = MemberChangedHookMsg::one(diff);
+        let msg = SubMsg::reply_on_error(hook_msg.into_cosmos_msg(hook.clone()).unwrap(), 1)
+            .with_gas_limit(100_000);
+        assert_eq!(res.messages, vec![msg]);
+
+        // a failing call is recorded
+        let res = reply(
+            deps.as_mut(),
+            env.clone(),
+            Reply {
+                id: 1,
+                result: SubMsgResult::Err("out of gas".to_owned()),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1], ("hook", hook.as_str()));
+        assert_eq!(res.attributes[2], ("error", "out of gas"));
+        let hooks = query_hooks(deps.as_ref()).unwrap();
+        assert_eq!(
+            hooks.hook_configs,
+            vec![HookConfigResponse {
+                addr: hook,
+                gas_limit: Some(100_000),
+                failures: 1,
+            }]
+        );
+
+        // unknown replies are rejected
+        let err = reply(
+            deps.as_mut(),
+            env,
+            Reply {
+                id: 2,
+                result: SubMsgResult::Err("out of gas".to_owned()),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::UnknownReplyId { id: 2 });
+    }
+
+    #[test]
+    fn max_hooks() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        default_instantiate(deps.as_mut(), env.clone());
+        assert_eq!(
+            query_hooks(deps.as_ref()).unwrap().max_hooks,
+            DEFAULT_MAX_HOOKS
+        );
+
+        let admin_info = mock_info(INIT_ADMIN, &[]);
+        let add_hook = |deps: DepsMut, i: u32| {
+            let msg = ExecuteMsg::AddHook {
+                addr: format!("hook{}", i),
+                gas_limit: None,
+            };
+            execute(deps, env.clone(), admin_info.clone(), msg)
+        };
+        for i in 0..DEFAULT_MAX_HOOKS {
+            add_hook(deps.as_mut(), i).unwrap();
+        }
+        let err = add_hook(deps.as_mut(), DEFAULT_MAX_HOOKS).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TooManyHooks {
+                max: DEFAULT_MAX_HOOKS
+            }
+        );
+
+        // removing one makes room again
+        let remove_msg = ExecuteMsg::RemoveHook {
+            addr: "hook0".to_owned(),
+        };
+        execute(deps.as_mut(), env.clone(), admin_info.clone(), remove_msg).unwrap();
+        add_hook(deps.as_mut(), DEFAULT_MAX_HOOKS).unwrap();
+        assert_eq!(
+            query_hooks(deps.as_ref()).unwrap().hooks.len(),
+            DEFAULT_MAX_HOOKS as usize
+        );
+    }
+
     #[test]
     fn ensure_bonding_edge_cases() {
         // use min_bond 0, tokens_per_power 500
@@ -1551,6 +5927,7 @@ mod tests {
                 unbonding_period: UNBONDING_PERIOD,
                 voting_multiplier: Decimal::one(),
                 reward_multiplier: Decimal::one(),
+                exit_vesting: None,
             }],
         );
 
@@ -1575,14 +5952,60 @@ mod tests {
             tokens_per_power: Uint128::new(tpower),
             min_bond: Uint128::new(min_bound),
             unbonding_periods: vec![0u64],
+            reward_token: None,
+            max_hooks: DEFAULT_MAX_HOOKS,
+            distribution_fee: None,
+            reward_decay: None,
+            loyalty_bonus_pct: 0,
+            max_loyalty_bonus_pct: 0,
+            slash_destination: SlashDestination::default(),
+            vesting_voting_discount: None,
+        };
+        truncate_power(calc_power(&cfg, Uint128::new(stake), Decimal::percent(50)))
+            .unwrap()
+            .u128()
+    }
+
+    #[test]
+    fn test_power_of_large_stakes() {
+        let cfg = Config {
+            cw20_contract: Addr::unchecked("cw20_contract"),
+            tokens_per_power: Uint128::new(1),
+            min_bond: Uint128::new(1),
+            unbonding_periods: vec![0u64],
+            reward_token: None,
+            max_hooks: DEFAULT_MAX_HOOKS,
+            distribution_fee: None,
+            reward_decay: None,
+            loyalty_bonus_pct: 0,
+            max_loyalty_bonus_pct: 0,
+            slash_destination: SlashDestination::default(),
+            vesting_voting_discount: Some(Decimal::percent(50)),
         };
-        calc_power(&cfg, Uint128::new(stake), Decimal::percent(50)).u128()
+        // far above the ~3.4e20 a `Decimal` can hold
+        let stake = Uint128::new(10u128.pow(30));
+        let power = calc_power(&cfg, stake, Decimal::percent(150));
+        assert_eq!(
+            truncate_power(power).unwrap(),
+            Uint128::new(15 * 10u128.pow(29))
+        );
+        let votes = calc_votes(&cfg, stake, stake, Decimal::one());
+        assert_eq!(
+            truncate_power(votes).unwrap(),
+            Uint128::new(5 * 10u128.pow(29))
+        );
+
+        // a sum that does not fit into the stored power is an error rather than a panic
+        let max = calc_power(&cfg, Uint128::MAX, Decimal::one());
+        assert_eq!(truncate_power(max).unwrap(), Uint128::MAX);
+        assert!(truncate_power(max + max).is_err());
     }
 
     #[test_case(1000 ,1, 1, 5 => 1000; "should success")]
+    #[test_case(10u128.pow(30), 1, 1, 5 => 10u128.pow(30); "stake above the decimal range")]
     #[test_case(1000 ,0, 1, 5 => panics "attempt to divide by zero")]
     #[test_case(2 ,2, 1, 10 => 1; "when tokens_per_power equals stake should success")]
-    #[test_case(2 ,2, 1, 0 => 1; "when unbonding_period equals zero should success")]
+    #[test_case(2 ,2, 1, 0 => panics "ZeroUnbondingPeriod"; "when unbonding_period equals zero should fail")]
     fn test_update_membership_on_execute_bond(
         new_stake: u128,
         tokens_per_power: u128,
@@ -1601,6 +6024,7 @@ mod tests {
                 unbonding_period,
                 voting_multiplier: Decimal::one(),
                 reward_multiplier: Decimal::one(),
+                exit_vesting: None,
             }],
         );
 
@@ -1611,6 +6035,7 @@ mod tests {
         let admin_info = mock_info(INIT_ADMIN, &[]);
         let hook_msg = ExecuteMsg::AddHook {
             addr: contract1.clone(),
+            gas_limit: None,
         };
         let _ = execute(deps.as_mut(), env.clone(), admin_info, hook_msg).unwrap();
 
@@ -1624,7 +6049,8 @@ mod tests {
         assert_eq!(initial_power.power, Uint128::zero());
 
         // and the expected result
-        let new = calc_power(&cfg, Uint128::new(new_stake), Decimal::one());
+        let new =
+            truncate_power(calc_power(&cfg, Uint128::new(new_stake), Decimal::one())).unwrap();
         let diff = MemberDiff::new(USER1, None, Some(new));
         let hook_msg = MemberChangedHookMsg::one(diff);
         let msg = SubMsg::new(hook_msg.into_cosmos_msg(contract1).unwrap());
@@ -1685,6 +6111,7 @@ mod tests {
                     voting_multiplier: Decimal::one(),
                     reward_multiplier: Decimal::one(),
                     total_staked: Uint128::zero(),
+                    exit_vesting: None,
                 })
             }
         );
@@ -1719,6 +6146,196 @@ mod tests {
         migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
     }
 
+    #[test]
+    fn migrate_legacy_hooks() {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let legacy = vec![Addr::unchecked("hook2"), Addr::unchecked("hook1")];
+        LEGACY_HOOKS.save(deps.as_mut().storage, &legacy).unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let hooks = query_hooks(deps.as_ref()).unwrap();
+        assert_eq!(hooks.hooks, vec!["hook1".to_owned(), "hook2".to_owned()]);
+        assert!(hooks.hook_configs.iter().all(|h| h.gas_limit.is_none()));
+        assert!(LEGACY_HOOKS.may_load(&deps.storage).unwrap().is_none());
+        assert_eq!(HOOK_REPLY_IDS.load(&deps.storage, 1).unwrap(), "hook2");
+    }
+
+    #[test]
+    fn staged_migration_backfills_first_staked() {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+        bond_cw20(deps.as_mut(), 12_000, 7_500, 6_000, 1);
+        assert_eq!(
+            query_first_staked(deps.as_ref(), USER1.to_owned())
+                .unwrap()
+                .first_staked,
+            Some(mock_env().block.time.plus_seconds(1))
+        );
+
+        // layout of older versions, before first stakes were tracked
+        STORAGE_VERSION_MARKER.remove(deps.as_mut().storage);
+        for user in [USER1, USER2, USER3] {
+            FIRST_STAKED.remove(deps.as_mut().storage, &Addr::unchecked(user));
+        }
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let progress = query_migration_progress(deps.as_ref()).unwrap();
+        assert!(progress.in_progress);
+        assert_eq!(progress.storage_version, 0);
+        assert_eq!(progress.target_version, Some(STORAGE_VERSION));
+
+        // everything else is blocked until the migration is done
+        let msg = ExecuteMsg::RecomputeMembers {
+            start_after: None,
+            limit: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(USER1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::MigrationInProgress {});
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let msg = ExecuteMsg::ContinueMigration { limit: Some(2) };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let progress = query_migration_progress(deps.as_ref()).unwrap();
+        assert!(progress.in_progress);
+        assert_eq!(progress.processed, 2);
+        assert_eq!(progress.last_processed, Some(Addr::unchecked(USER2)));
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            query_migration_progress(deps.as_ref()).unwrap(),
+            MigrationProgressResponse {
+                in_progress: false,
+                storage_version: STORAGE_VERSION,
+                target_version: None,
+                last_processed: None,
+                processed: 0,
+            }
+        );
+        for user in [USER1, USER2, USER3] {
+            assert_eq!(
+                query_first_staked(deps.as_ref(), user.to_owned())
+                    .unwrap()
+                    .first_staked,
+                Some(env.block.time)
+            );
+        }
+
+        let err = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoMigrationInProgress {});
+
+        // migrating again does not need to back-fill anything
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(!query_migration_progress(deps.as_ref()).unwrap().in_progress);
+    }
+
+    #[test]
+    fn staged_migration_moves_powers_from_cw4_keys() {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut(), mock_env());
+        bond_cw20(deps.as_mut(), 11_000, 6_000, 0, 1);
+        let mut env = mock_env();
+        env.block.height += 10;
+        let msg = ExecuteMsg::ReceiveDelegation(Cw20ReceiveDelegationMsg {
+            sender: USER2.to_owned(),
+            amount: Uint128::new(2_000),
+            msg: to_binary(&ReceiveDelegationMsg::Delegate {
+                unbonding_period: UNBONDING_PERIOD,
+            })
+            .unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CW20_ADDRESS, &[]),
+            msg,
+        )
+        .unwrap();
+
+        // layout of older versions, with the powers under the cw4 keys
+        for user in [USER1, USER2] {
+            let addr = Addr::unchecked(user);
+            let power = MEMBERS_PRIMARY.load(&deps.storage, &addr).unwrap();
+            MEMBERS_PRIMARY.remove(deps.as_mut().storage, &addr);
+            LEGACY_MEMBERS
+                .save(deps.as_mut().storage, &addr, &power)
+                .unwrap();
+        }
+        let total = TOTAL_VOTES_PRIMARY.load(&deps.storage).unwrap();
+        TOTAL_VOTES_PRIMARY.remove(deps.as_mut().storage);
+        LEGACY_TOTAL_VOTES
+            .save(deps.as_mut().storage, &total)
+            .unwrap();
+        STORAGE_VERSION_MARKER
+            .save(deps.as_mut().storage, &1)
+            .unwrap();
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+
+        // the total is moved right away
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        let total_raw = deps.storage.get(TOTAL_KEY.as_bytes()).unwrap();
+        assert_eq!(from_slice::<u64>(&total_raw).unwrap(), 19);
+        assert_eq!(
+            query_total_power(deps.as_ref(), env.clone(), None)
+                .unwrap()
+                .power
+                .u128(),
+            19
+        );
+
+        // powers not moved yet are still readable, at any height
+        let power = |deps: Deps, user: &str, height| {
+            query_voting_power(deps, mock_env(), user.to_owned(), height)
+                .unwrap()
+                .power
+                .u128()
+        };
+        let check_powers = |deps: Deps| {
+            assert_eq!(power(deps, USER1, None), 11);
+            assert_eq!(power(deps, USER2, None), 8);
+            assert_eq!(power(deps, USER2, Some(env.block.height - 5)), 6);
+            assert_eq!(power(deps, USER2, Some(env.block.height - 20)), 0);
+            let weights: Vec<_> = list_members_cw4(deps, None, 10)
+                .into_iter()
+                .map(|m| m.weight)
+                .collect();
+            assert_eq!(weights, [11, 8]);
+        };
+        check_powers(deps.as_ref());
+
+        let msg = ExecuteMsg::ContinueMigration { limit: Some(1) };
+        execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        let raw = deps.storage.get(&member_key(USER1)).unwrap();
+        assert_eq!(from_slice::<u64>(&raw).unwrap(), 11);
+        let raw = deps.storage.get(&member_key(USER2)).unwrap();
+        assert!(from_slice::<u64>(&raw).is_err());
+        check_powers(deps.as_ref());
+
+        let msg = ExecuteMsg::ContinueMigration { limit: Some(1) };
+        execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        assert!(!query_migration_progress(deps.as_ref()).unwrap().in_progress);
+        let raw = deps.storage.get(&member_key(USER2)).unwrap();
+        assert_eq!(from_slice::<u64>(&raw).unwrap(), 8);
+        check_powers(deps.as_ref());
+    }
+
     #[test]
     fn migrate_newer_version() {
         let mut deps = mock_dependencies();
@@ -85,6 +85,8 @@ pub fn instantiate_with_wynd_stake(
             address: acct.address,
             amount: acct.amount,
             vesting: None,
+            memo: None,
+            relative_vesting: None,
         })
         .collect();
 
@@ -98,9 +100,11 @@ pub fn instantiate_with_wynd_stake(
         mint: Some(cw20_vesting::msg::MinterInfo {
             minter: CREATOR_ADDR.to_string(),
             cap: None,
+            burns_reduce_cap: false,
         }),
         allowed_vesters: None,
         max_curve_complexity: 10,
+        seeding_open: false,
     };
     let cw20_addr = app
         .instantiate_contract(
@@ -133,9 +137,13 @@ pub fn instantiate_with_wynd_stake(
                         unbonding_period,
                         voting_multiplier: Decimal::one(),
                         reward_multiplier: Decimal::one(),
+                        exit_vesting: None,
                     },
                 ],
                 admin: None,
+                max_hooks: None,
+                distribution_fee: None,
+                vesting_voting_discount: None,
             })
             .unwrap(),
             admin: cw_core::msg::Admin::CoreContract {},
@@ -187,6 +195,8 @@ pub fn instantiate_with_wynd_stake(
         address,
         amount,
         vesting: _,
+        memo: _,
+        relative_vesting: _,
     } in initial_balances
     {
         app.execute_contract(
@@ -1,16 +1,25 @@
 use cosmwasm_std::{
-    to_binary, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128,
+    from_slice, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+    OverflowError, OverflowOperation, Response, StdResult, Storage, Timestamp, Uint128, Uint256,
     WasmMsg,
 };
+use cw20::Cw20ReceiveMsg;
+use cw_storage_plus::Bound;
 
+use crate::contract::{assert_admin, recompute_rewards};
 use crate::error::ContractError;
 use crate::msg::{
-    DelegatedResponse, DistributedRewardsResponse, UndistributedRewardsResponse,
-    WithdrawAdjustmentDataResponse, WithdrawableRewardsResponse,
+    CanDistributeResponse, DelegatedResponse, DelegationReportResponse, DistributedRewardsResponse,
+    DistributionLogEntry, DistributionLogResponse, DistributionWeightResponse,
+    PayoutAddressResponse, ReceiveMsg, ReceiverMode, RewardDecayInfo, RewardSource,
+    UndistributedRewardsResponse, WithdrawAdjustmentDataResponse, WithdrawableRewardsResponse,
 };
 use crate::state::{
-    Distribution, WithdrawAdjustment, CONFIG, DISTRIBUTION, REWARDS, SHARES_SHIFT, TOTAL_REWARDS,
-    TOTAL_STAKED, WITHDRAW_ADJUSTMENT,
+    Config, Distribution, RewardActivity, RewardDecay, RewardMigration, RewardStream,
+    WithdrawAdjustment, CONFIG, DISTRIBUTION, DISTRIBUTION_LOG, DISTRIBUTION_LOG_SIZE,
+    DISTRIBUTION_WEIGHT, MAX_DISTRIBUTION_WEIGHT, NEXT_DISTRIBUTION_SEQ, PAYOUT_ADDRESS, REWARDS,
+    REWARD_ACTIVITY, REWARD_MIGRATION, REWARD_STREAM, SHARES_PER_POINT_HISTORY, SHARES_RESET_AT,
+    SHARES_SHIFT, TOTAL_REWARDS, TOTAL_STAKED, WITHDRAW_ADJUSTMENT,
 };
 
 pub fn execute_distribute_rewards(
@@ -19,7 +28,10 @@ pub fn execute_distribute_rewards(
     info: MessageInfo,
     sender: Option<String>,
 ) -> Result<Response, ContractError> {
-    let total = TOTAL_REWARDS.load(deps.storage)?.u128();
+    let total = TOTAL_REWARDS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .u128();
 
     // There are no shares in play - noone to distribute to
     if total == 0 {
@@ -31,53 +43,344 @@ pub fn execute_distribute_rewards(
         .transpose()?
         .unwrap_or(info.sender);
 
-    let mut distribution = DISTRIBUTION.load(deps.storage)?;
-    let withdrawable: u128 = distribution.withdrawable_total.into();
-
-    // Query current cw20 reward balance, we assume we pay out rewards in
-    // the same token that is used to stake.
-    let balance = undistributed_rewards(deps.as_ref(), env.contract.address)?.u128();
-
     // Calculate how much we have received since the last time Distributed was called.
     // This is the amount we will distribute to all members.
-    let amount = balance - withdrawable;
+    let amount = available_rewards(deps.as_ref(), &env)?;
     if amount == 0 {
         return Ok(Response::new());
     }
 
-    let leftover: u128 = distribution.shares_leftover.into();
-    let points = (amount << SHARES_SHIFT) + leftover;
-    let points_per_share = points / total;
-    distribution.shares_leftover = (points % total) as u64;
+    let resp = Response::new()
+        .add_attribute("action", "distribute_rewards")
+        .add_attribute("sender", sender.as_str());
+    distribute_with_fee(deps.storage, &env, &sender, resp, amount, total)
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // the sent tokens are part of this contract's balance already, so they are picked up like
+    // any other undistributed rewards
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != *config.reward_token() {
+        return Err(ContractError::UnexpectedRewardToken {
+            token: info.sender.into_string(),
+            expected: config.reward_token().to_string(),
+        });
+    }
 
-    // Everything goes back to 128-bits/16-bytes
-    // Full amount is added here to total withdrawable, as it should not be considered on its own
-    // on future distributions - even if because of calculation offsets it is not fully
-    // distributed, the error is handled by leftover.
-    distribution.shares_per_point += Uint128::new(points_per_share);
-    distribution.distributed_total += Uint128::new(amount);
-    distribution.withdrawable_total += Uint128::new(amount);
+    let msg: ReceiveMsg = from_slice(&wrapper.msg)?;
+    match msg {
+        ReceiveMsg::Fund {} => execute_distribute_rewards(deps, env, info, Some(wrapper.sender)),
+    }
+}
 
-    DISTRIBUTION.save(deps.storage, &distribution)?;
+pub fn execute_batch_distribute_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amounts: Vec<RewardSource>,
+) -> Result<Response, ContractError> {
+    if amounts.is_empty() {
+        return Err(ContractError::NoRewardSources {});
+    }
+    let total = TOTAL_REWARDS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .u128();
+    if total == 0 {
+        return Err(ContractError::NoMembersToDistributeTo {});
+    }
 
+    let mut senders = vec![];
+    let mut requested = Uint128::zero();
+    for source in &amounts {
+        let sender = match &source.sender {
+            Some(sender) => deps.api.addr_validate(sender)?,
+            None => info.sender.clone(),
+        };
+        senders.push(sender.into_string());
+        requested += source.amount;
+    }
+
+    let available = available_rewards(deps.as_ref(), &env)?;
+    if requested.u128() > available {
+        return Err(ContractError::InsufficientUndistributedRewards {
+            requested,
+            available: Uint128::new(available),
+        });
+    }
+    if requested.is_zero() {
+        return Ok(Response::new());
+    }
+
+    let source_amounts: Vec<_> = amounts
+        .iter()
+        .map(|source| source.amount.to_string())
+        .collect();
     let resp = Response::new()
-        .add_attribute("action", "distribute_rewards")
-        .add_attribute("sender", sender.as_str())
-        .add_attribute("amount", &amount.to_string());
+        .add_attribute("action", "batch_distribute_rewards")
+        .add_attribute("senders", senders.join(","))
+        .add_attribute("source_amounts", source_amounts.join(","));
+    distribute_with_fee(
+        deps.storage,
+        &env,
+        &info.sender,
+        resp,
+        requested.u128(),
+        total,
+    )
+}
+
+/// Rewards received since the last distribution, which are not reserved for a reward stream
+fn available_rewards(deps: Deps, env: &Env) -> Result<u128, ContractError> {
+    let withdrawable = DISTRIBUTION.load(deps.storage)?.withdrawable_total.u128();
+
+    // Query current cw20 reward balance, we assume we pay out rewards in
+    // the same token that is used to stake.
+    let balance = undistributed_rewards(deps, env.contract.address.clone())?.u128();
+
+    // Tokens reserved for an active reward stream are released by the stream only
+    let streamed = REWARD_STREAM
+        .may_load(deps.storage)?
+        .map(|stream| stream.remaining().u128())
+        .unwrap_or_default();
+
+    Ok(balance - withdrawable - streamed)
+}
+
+/// Distributes `amount` between stakers holding `total` reward power, after taking the
+/// distribution fee. Adds the distributed amount and the fee to `resp`, and records the
+/// distribution in the log.
+fn distribute_with_fee(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    resp: Response,
+    amount: u128,
+    total: u128,
+) -> Result<Response, ContractError> {
+    // the fee leaves the contract right away, only the rest is split between stakers
+    let config = CONFIG.load(storage)?;
+    let fee = config
+        .distribution_fee
+        .as_ref()
+        .map(|fee| (fee.fee(amount), &fee.recipient))
+        .filter(|(fee, _)| *fee > 0);
+    let fee_amount = fee.map(|(fee, _)| fee).unwrap_or_default();
+    let amount = amount - fee_amount;
+
+    let mut distribution = DISTRIBUTION.load(storage)?;
+    distribution.distribute(amount, total);
+    distribution.fees_total += Uint128::new(fee_amount);
+    DISTRIBUTION.save(storage, &distribution)?;
+    record_shares_per_point(storage, &config, env, &distribution)?;
+
+    let seq = NEXT_DISTRIBUTION_SEQ.may_load(storage)?.unwrap_or_default();
+    let entry = DistributionLogEntry {
+        seq,
+        amount: Uint128::new(amount),
+        sender: sender.clone(),
+        time: env.block.time,
+        height: env.block.height,
+        total_reward_power: Uint128::new(total),
+        distributed_total: distribution.distributed_total,
+    };
+    DISTRIBUTION_LOG.save(storage, seq, &entry)?;
+    if seq >= DISTRIBUTION_LOG_SIZE {
+        DISTRIBUTION_LOG.remove(storage, seq - DISTRIBUTION_LOG_SIZE);
+    }
+    NEXT_DISTRIBUTION_SEQ.save(storage, &(seq + 1))?;
+
+    let mut resp = resp.add_attribute("amount", &amount.to_string());
+
+    if let Some((fee, recipient)) = fee {
+        let msg = WasmMsg::Execute {
+            contract_addr: config.reward_token().to_string(),
+            msg: to_binary(&cw20_vesting::ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: Uint128::new(fee),
+            })?,
+            funds: vec![],
+        };
+        resp = resp
+            .add_attribute("fee", fee.to_string())
+            .add_attribute("fee_recipient", recipient.as_str())
+            .add_message(msg);
+    }
+
+    Ok(resp)
+}
+
+/// Releases rewards of the active stream up to the current block.
+/// Has to be called before any change of reward power, so the released rewards are split
+/// according to the reward power they were accrued with.
+pub fn stream_rewards(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let mut stream = match REWARD_STREAM.may_load(storage)? {
+        Some(stream) => stream,
+        None => return Ok(()),
+    };
+    let mut distribution = DISTRIBUTION.load(storage)?;
+    let total = TOTAL_REWARDS.may_load(storage)?.unwrap_or_default();
+    if !apply_stream(&mut distribution, &mut stream, total, env.block.height) {
+        return Ok(());
+    }
+
+    DISTRIBUTION.save(storage, &distribution)?;
+    let config = CONFIG.load(storage)?;
+    record_shares_per_point(storage, &config, env, &distribution)?;
+    if stream.is_finished() {
+        REWARD_STREAM.remove(storage);
+    } else {
+        REWARD_STREAM.save(storage, &stream)?;
+    }
+    Ok(())
+}
+
+/// Records the current `shares_per_point` in `SHARES_PER_POINT_HISTORY` if reward decay is
+/// enabled, so rewards earned while being inactive can be told apart later.
+fn record_shares_per_point(
+    storage: &mut dyn Storage,
+    config: &Config,
+    env: &Env,
+    distribution: &Distribution,
+) -> StdResult<()> {
+    if config.reward_decay.is_some() {
+        SHARES_PER_POINT_HISTORY.save(
+            storage,
+            env.block.time.seconds(),
+            &distribution.shares_per_point,
+        )?;
+    }
+    Ok(())
+}
+
+/// `shares_per_point` right before the given time in seconds
+fn shares_per_point_before(storage: &dyn Storage, seconds: u64) -> StdResult<Uint128> {
+    // nothing from before a migration is left in the current `shares_per_point`
+    if matches!(SHARES_RESET_AT.may_load(storage)?, Some(reset_at) if reset_at >= seconds) {
+        return Ok(Uint128::zero());
+    }
+    let last = SHARES_PER_POINT_HISTORY
+        .range(
+            storage,
+            None,
+            Some(Bound::exclusive(seconds)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?;
+    Ok(last.map(|(_, shares)| shares).unwrap_or_default())
+}
+
+/// Distributes rewards released by the stream up to `height`.
+/// If there is no reward power, released rewards are not reserved for the stream anymore, and
+/// are distributed by the next `ExecuteMsg::DistributeRewards` instead.
+/// Returns false if nothing was released.
+fn apply_stream(
+    distribution: &mut Distribution,
+    stream: &mut RewardStream,
+    total: Uint128,
+    height: u64,
+) -> bool {
+    let pending = stream.pending(height);
+    if pending.is_zero() {
+        return false;
+    }
+    if !total.is_zero() {
+        distribution.distribute(pending.u128(), total.u128());
+    }
+    stream.distributed_so_far += pending;
+    true
+}
+
+/// Loads distribution data and the reward stream as they will be after releasing the rewards
+/// streamed up to the current block.
+fn load_streamed_distribution(
+    deps: Deps,
+    env: &Env,
+) -> StdResult<(Distribution, Option<RewardStream>)> {
+    let mut distribution = DISTRIBUTION.load(deps.storage)?;
+    let mut stream = REWARD_STREAM.may_load(deps.storage)?;
+    if let Some(stream) = stream.as_mut() {
+        let total = TOTAL_REWARDS.may_load(deps.storage)?.unwrap_or_default();
+        apply_stream(&mut distribution, stream, total, env.block.height);
+    }
+    Ok((distribution, stream))
+}
+
+pub fn execute_start_reward_stream(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    total: Uint128,
+    duration_blocks: u64,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    if total.is_zero() {
+        return Err(ContractError::NoFunds {});
+    }
+    if duration_blocks == 0 {
+        return Err(ContractError::InvalidStreamDuration {});
+    }
+    // finished streams are removed when releasing the rewards
+    if REWARD_STREAM.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::RewardStreamActive {});
+    }
+
+    // rewards have to be transferred to the contract first
+    let distribution = DISTRIBUTION.load(deps.storage)?;
+    let available = undistributed_rewards(deps.as_ref(), env.contract.address)?
+        - distribution.withdrawable_total;
+    if available < total {
+        return Err(ContractError::InsufficientStreamFunds {
+            required: total,
+            available,
+        });
+    }
+
+    let stream = RewardStream {
+        total,
+        start_height: env.block.height,
+        end_height: env.block.height + duration_blocks,
+        distributed_so_far: Uint128::zero(),
+    };
+    REWARD_STREAM.save(deps.storage, &stream)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "start_reward_stream")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("total", total)
+        .add_attribute("start_height", stream.start_height.to_string())
+        .add_attribute("end_height", stream.end_height.to_string());
 
     Ok(resp)
 }
 
 /// Query current cw20 reward balance.
-/// We assume we pay out rewards in the same token that is used to stake.
 fn undistributed_rewards(deps: Deps, contract_address: Addr) -> StdResult<Uint128> {
-    // Query current cw20 reward balance, we assume we pay out rewards in
-    // the same token that is used to stake.
-    let cw20 = CONFIG.load(deps.storage)?.cw20_contract;
+    let config = CONFIG.load(deps.storage)?;
+    reward_balance(deps, &config, config.reward_token(), contract_address)
+}
+
+/// Query balance of `token` held by this contract which can be used for rewards.
+fn reward_balance(
+    deps: Deps,
+    config: &Config,
+    token: &Addr,
+    contract_address: Addr,
+) -> StdResult<Uint128> {
     let query = cw20_vesting::QueryMsg::Balance {
         address: contract_address.into_string(),
     };
-    let cw20::BalanceResponse { balance } = deps.querier.query_wasm_smart(cw20, &query)?;
+    let cw20::BalanceResponse { balance } = deps.querier.query_wasm_smart(token, &query)?;
+    if token != &config.cw20_contract {
+        return Ok(balance);
+    }
     // we don't distribute the staked tokens (including currently unbonding ones)
     let staked = TOTAL_STAKED.load(deps.storage)?.total();
     Ok(balance - staked)
@@ -85,59 +388,341 @@ fn undistributed_rewards(deps: Deps, contract_address: Addr) -> StdResult<Uint12
 
 pub fn execute_withdraw_rewards(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     owner: Option<String>,
     receiver: Option<String>,
+    receiver_msg: Option<Binary>,
 ) -> Result<Response, ContractError> {
     let owner = owner.map_or_else(
         || Ok(info.sender.clone()),
         |owner| deps.api.addr_validate(&owner),
     )?;
 
-    let mut distribution = DISTRIBUTION.load(deps.storage)?;
-    let mut adjustment = WITHDRAW_ADJUSTMENT.load(deps.storage, &owner)?;
-
+    let adjustment = WITHDRAW_ADJUSTMENT.load(deps.storage, &owner)?;
     if ![&owner, &adjustment.delegated].contains(&&info.sender) {
         return Err(ContractError::Unauthorized {});
     }
 
-    let reward = withdrawable_rewards(deps.as_ref(), &owner, &distribution, &adjustment)?;
     let receiver = receiver
         .map(|receiver| deps.api.addr_validate(&receiver))
-        .transpose()?
-        .unwrap_or_else(|| info.sender.clone());
+        .transpose()?;
+    let receiver = match (PAYOUT_ADDRESS.may_load(deps.storage, &owner)?, receiver) {
+        (Some(payout), Some(receiver)) if payout != receiver => {
+            return Err(ContractError::ConflictingReceiver {
+                owner: owner.into_string(),
+                payout: payout.into_string(),
+                receiver: receiver.into_string(),
+            });
+        }
+        (Some(payout), _) => payout,
+        (None, receiver) => receiver.unwrap_or_else(|| info.sender.clone()),
+    };
+
+    let (reward, forfeited) = take_withdrawable_rewards(deps.storage, &env, &owner)?;
 
     if reward.is_zero() {
         // Just do nothing
         return Ok(Response::new());
     }
 
-    adjustment.withdrawn_rewards += reward;
-    WITHDRAW_ADJUSTMENT.save(deps.storage, &owner, &adjustment)?;
-    distribution.withdrawable_total -= reward;
-    DISTRIBUTION.save(deps.storage, &distribution)?;
-
-    // send via cw20
-    let msg = WasmMsg::Execute {
-        contract_addr: CONFIG.load(deps.storage)?.cw20_contract.into_string(),
-        msg: to_binary(&cw20_vesting::ExecuteMsg::Transfer {
+    // send via cw20, calling the receiver if it expects a message
+    let token_msg = match receiver_msg {
+        Some(msg) => cw20_vesting::ExecuteMsg::Send {
+            contract: receiver.to_string(),
+            amount: reward,
+            msg,
+        },
+        None => cw20_vesting::ExecuteMsg::Transfer {
             recipient: receiver.to_string(),
             amount: reward,
-        })?,
+        },
+    };
+    let msg = WasmMsg::Execute {
+        contract_addr: CONFIG.load(deps.storage)?.reward_token().to_string(),
+        msg: to_binary(&token_msg)?,
         funds: vec![],
     };
 
-    let resp = Response::new()
+    let mut resp = Response::new()
         .add_attribute("action", "withdraw_rewards")
         .add_attribute("sender", info.sender.as_str())
         .add_attribute("owner", owner.as_str())
         .add_attribute("receiver", receiver.as_str())
         .add_attribute("reward", reward)
         .add_message(msg);
+    if !forfeited.is_zero() {
+        resp = resp.add_attribute("forfeited", forfeited);
+    }
 
     Ok(resp)
 }
 
+/// Marks all rewards withdrawable by `owner` as withdrawn. The caller has to transfer them.
+/// Returns the withdrawn rewards and the rewards forfeited due to inactivity.
+fn take_withdrawable_rewards(
+    storage: &mut dyn Storage,
+    env: &Env,
+    owner: &Addr,
+) -> StdResult<(Uint128, Uint128)> {
+    let forfeited = record_interaction(storage, env, owner)?;
+    let mut distribution = DISTRIBUTION.load(storage)?;
+    let mut adjustment = WITHDRAW_ADJUSTMENT.load(storage, owner)?;
+
+    let reward = withdrawable_rewards(storage, owner, &distribution, &adjustment)?;
+    if reward.is_zero() {
+        return Ok((reward, forfeited));
+    }
+
+    adjustment.withdrawn_rewards += reward;
+    WITHDRAW_ADJUSTMENT.save(storage, owner, &adjustment)?;
+    distribution.withdrawable_total -= reward;
+    DISTRIBUTION.save(storage, &distribution)?;
+    if let Some(mut activity) = REWARD_ACTIVITY.may_load(storage, owner)? {
+        activity.settled_rewards = activity.settled_rewards.saturating_sub(reward);
+        REWARD_ACTIVITY.save(storage, owner, &activity)?;
+    }
+    Ok((reward, forfeited))
+}
+
+/// Maximum number of owners a single `WithdrawRewardsBatch` can withdraw for
+const MAX_WITHDRAW_BATCH: usize = 30;
+
+pub fn execute_withdraw_rewards_batch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owners: Vec<String>,
+    receiver_mode: ReceiverMode,
+) -> Result<Response, ContractError> {
+    if owners.is_empty() {
+        return Err(ContractError::EmptyWithdrawBatch {});
+    }
+    if owners.len() > MAX_WITHDRAW_BATCH {
+        return Err(ContractError::TooManyWithdrawOwners {
+            max: MAX_WITHDRAW_BATCH as u32,
+        });
+    }
+
+    // owners that cannot be withdrawn for are skipped, with the reason reported
+    let mut skipped = vec![];
+    let mut eligible = vec![];
+    for owner in owners {
+        let owner = match deps.api.addr_validate(&owner) {
+            Ok(owner) => owner,
+            Err(_) => {
+                skipped.push(format!("{}:invalid_address", owner));
+                continue;
+            }
+        };
+        let adjustment = match WITHDRAW_ADJUSTMENT.may_load(deps.storage, &owner)? {
+            Some(adjustment) => adjustment,
+            None => {
+                skipped.push(format!("{}:no_rewards", owner));
+                continue;
+            }
+        };
+        eligible.push((owner, adjustment.delegated));
+    }
+
+    let single_receiver = match receiver_mode {
+        ReceiverMode::OwnerWallets => None,
+        ReceiverMode::SingleReceiver(receiver) => {
+            // aggregating rewards of others is only allowed for their withdrawal delegate
+            if let Some((owner, _)) = eligible
+                .iter()
+                .find(|(_, delegated)| *delegated != info.sender)
+            {
+                return Err(ContractError::NotWithdrawalDelegate {
+                    owner: owner.to_string(),
+                });
+            }
+            Some(deps.api.addr_validate(&receiver)?)
+        }
+    };
+
+    let token = CONFIG.load(deps.storage)?.reward_token().clone();
+    let mut resp = Response::new()
+        .add_attribute("action", "withdraw_rewards_batch")
+        .add_attribute("sender", info.sender.as_str());
+    let mut total = Uint128::zero();
+    for (owner, delegated) in eligible {
+        if ![&owner, &delegated].contains(&&info.sender) {
+            skipped.push(format!("{}:unauthorized", owner));
+            continue;
+        }
+        let payout = PAYOUT_ADDRESS.may_load(deps.storage, &owner)?;
+        let receiver = match (payout, &single_receiver) {
+            (Some(payout), Some(receiver)) if payout != *receiver => {
+                skipped.push(format!("{}:payout_address", owner));
+                continue;
+            }
+            (Some(payout), _) => payout,
+            (None, Some(receiver)) => receiver.clone(),
+            (None, None) => owner.clone(),
+        };
+
+        let (reward, forfeited) = take_withdrawable_rewards(deps.storage, &env, &owner)?;
+        if !forfeited.is_zero() {
+            resp = resp.add_attribute("forfeited", format!("{}:{}", owner, forfeited));
+        }
+        if reward.is_zero() {
+            skipped.push(format!("{}:no_rewards", owner));
+            continue;
+        }
+        total += reward;
+        resp = resp.add_attribute("withdrawn", format!("{}:{}", owner, reward));
+        if single_receiver.is_none() {
+            resp = resp.add_message(reward_transfer(&token, &receiver, reward)?);
+        }
+    }
+
+    if let Some(receiver) = single_receiver {
+        if !total.is_zero() {
+            resp = resp.add_message(reward_transfer(&token, &receiver, total)?);
+        }
+        resp = resp.add_attribute("receiver", receiver);
+    }
+    for skipped in skipped {
+        resp = resp.add_attribute("skipped", skipped);
+    }
+    Ok(resp.add_attribute("reward", total))
+}
+
+fn reward_transfer(token: &Addr, recipient: &Addr, amount: Uint128) -> StdResult<WasmMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&cw20_vesting::ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    })
+}
+
+pub fn execute_update_reward_decay(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reward_decay: Option<RewardDecayInfo>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    // changing the parameters does not reset the inactivity of stakers
+    let enabled_at = cfg
+        .reward_decay
+        .map_or(env.block.time, |decay| decay.enabled_at);
+    cfg.reward_decay = reward_decay
+        .map(|decay| {
+            if decay.inactivity_period == 0 || decay.decay > Decimal::one() {
+                return Err(ContractError::InvalidRewardDecay {});
+            }
+            Ok(RewardDecay {
+                inactivity_period: decay.inactivity_period,
+                decay: decay.decay,
+                enabled_at,
+            })
+        })
+        .transpose()?;
+    CONFIG.save(deps.storage, &cfg)?;
+    // rewards of stakers becoming inactive are measured from here
+    let distribution = DISTRIBUTION.load(deps.storage)?;
+    record_shares_per_point(deps.storage, &cfg, &env, &distribution)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "update_reward_decay")
+        .add_attribute("sender", info.sender);
+    if let Some(decay) = cfg.reward_decay {
+        resp = resp
+            .add_attribute("inactivity_period", decay.inactivity_period.to_string())
+            .add_attribute("decay", decay.decay.to_string());
+    }
+    Ok(resp)
+}
+
+/// Records that `owner` bonded, unbonded, rebonded or withdrew rewards. If they were inactive
+/// for too long, the decayed part of the rewards they earned since becoming inactive is
+/// forfeited. Forfeited rewards are not withdrawable anymore, so they are distributed to all
+/// stakers by the next `ExecuteMsg::DistributeRewards`.
+/// Returns the forfeited rewards.
+pub fn record_interaction(
+    storage: &mut dyn Storage,
+    env: &Env,
+    owner: &Addr,
+) -> StdResult<Uint128> {
+    let decay = match CONFIG.load(storage)?.reward_decay {
+        Some(decay) => decay,
+        None => return Ok(Uint128::zero()),
+    };
+
+    let mut withdrawable = Uint128::zero();
+    let mut forfeited = Uint128::zero();
+    if let Some(mut adjustment) = WITHDRAW_ADJUSTMENT.may_load(storage, owner)? {
+        let mut distribution = DISTRIBUTION.load(storage)?;
+        withdrawable = withdrawable_rewards(storage, owner, &distribution, &adjustment)?;
+        forfeited = forfeited_rewards(
+            storage,
+            &decay,
+            &distribution,
+            env.block.time,
+            owner,
+            withdrawable,
+        )?;
+        if !forfeited.is_zero() {
+            // forfeited rewards are accounted like withdrawn ones, so they stay reconciled
+            // with the points of the owner
+            adjustment.withdrawn_rewards += forfeited;
+            WITHDRAW_ADJUSTMENT.save(storage, owner, &adjustment)?;
+            distribution.withdrawable_total -= forfeited;
+            DISTRIBUTION.save(storage, &distribution)?;
+        }
+    }
+
+    REWARD_ACTIVITY.save(
+        storage,
+        owner,
+        &RewardActivity {
+            last_interaction: env.block.time,
+            settled_rewards: withdrawable - forfeited,
+        },
+    )?;
+    Ok(forfeited)
+}
+
+/// Part of the `withdrawable` rewards of `owner` that is forfeited at `now` due to inactivity.
+/// Only rewards earned after the owner became inactive decay, rewards that were already
+/// withdrawable at the last interaction never do.
+fn forfeited_rewards(
+    storage: &dyn Storage,
+    decay: &RewardDecay,
+    distribution: &Distribution,
+    now: Timestamp,
+    owner: &Addr,
+    withdrawable: Uint128,
+) -> StdResult<Uint128> {
+    let activity = REWARD_ACTIVITY
+        .may_load(storage, owner)?
+        .unwrap_or(RewardActivity {
+            last_interaction: decay.enabled_at,
+            settled_rewards: Uint128::zero(),
+        });
+    let inactive_from = decay.inactive_from(activity.last_interaction);
+    if now.seconds() < inactive_from {
+        return Ok(Uint128::zero());
+    }
+
+    // earned with the current points, as points only change on interactions
+    let points = Uint256::from(REWARDS.may_load(storage, owner)?.unwrap_or_default());
+    let shares = distribution
+        .shares_per_point
+        .saturating_sub(shares_per_point_before(storage, inactive_from)?);
+    let earned_inactive =
+        Uint128::try_from((points * Uint256::from(shares)) >> SHARES_SHIFT as u32)?;
+    let unsettled = withdrawable.saturating_sub(activity.settled_rewards);
+    Ok(earned_inactive.min(unsettled) * decay.decay)
+}
+
 pub fn execute_delegate_withdrawal(
     deps: DepsMut,
     info: MessageInfo,
@@ -167,14 +752,272 @@ pub fn execute_delegate_withdrawal(
     Ok(resp)
 }
 
+pub fn execute_set_payout_address(
+    deps: DepsMut,
+    info: MessageInfo,
+    payout: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut resp = Response::new()
+        .add_attribute("action", "set_payout_address")
+        .add_attribute("sender", info.sender.as_str());
+
+    match payout {
+        Some(payout) => {
+            let payout = deps.api.addr_validate(&payout)?;
+            PAYOUT_ADDRESS.save(deps.storage, &info.sender, &payout)?;
+            resp = resp.add_attribute("payout", payout);
+        }
+        None => PAYOUT_ADDRESS.remove(deps.storage, &info.sender),
+    }
+
+    Ok(resp)
+}
+
+pub fn execute_set_distribution_weight_override(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    weight: Decimal,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let max = Decimal::from_ratio(MAX_DISTRIBUTION_WEIGHT, 1u128);
+    if weight > max {
+        return Err(ContractError::InvalidDistributionWeight { max });
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    if weight == Decimal::one() {
+        DISTRIBUTION_WEIGHT.remove(deps.storage, &address);
+    } else {
+        DISTRIBUTION_WEIGHT.save(deps.storage, &address, &weight)?;
+    }
+    // rewards distributed so far stay untouched, only the future share changes
+    recompute_rewards(deps.storage, &address)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "set_distribution_weight")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("address", address.as_str())
+        .add_attribute("weight", weight.to_string());
+
+    Ok(resp)
+}
+
+pub fn execute_migrate_reward_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_token: String,
+    exchange_rate: Decimal,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    // the stream reserve is held in the old token
+    if REWARD_STREAM.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::RewardStreamActive {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let new_token = deps.api.addr_validate(&new_token)?;
+    let old_token = config.reward_token().clone();
+    if new_token == old_token {
+        return Err(ContractError::SameRewardToken {});
+    }
+
+    let old_balance = reward_balance(
+        deps.as_ref(),
+        &config,
+        &old_token,
+        env.contract.address.clone(),
+    )?;
+    if old_balance.is_zero() {
+        return Err(ContractError::NoRewardsToMigrate {});
+    }
+
+    // checked upfront, so the migration does not get stuck on the last page. Converting every
+    // account separately only rounds down, so this covers the sum of all of them.
+    let required = DISTRIBUTION.load(deps.storage)?.withdrawable_total * exchange_rate;
+    let available = reward_balance(
+        deps.as_ref(),
+        &config,
+        &new_token,
+        env.contract.address.clone(),
+    )?;
+    if available < required {
+        return Err(ContractError::InsufficientRewardTokens {
+            required,
+            available,
+        });
+    }
+
+    let migration = RewardMigration {
+        new_token,
+        exchange_rate,
+        admin: info.sender.clone(),
+        last_processed: None,
+        unclaimed: Uint128::zero(),
+        migrated: Uint128::zero(),
+    };
+    let resp = migrate_rewards(
+        deps,
+        env,
+        migration,
+        DEFAULT_REWARD_MIGRATION_LIMIT as usize,
+    )?
+    .add_attribute("sender", info.sender.as_str())
+    .add_attribute("old_token", old_token.as_str());
+
+    Ok(resp)
+}
+
+const DEFAULT_REWARD_MIGRATION_LIMIT: u32 = 30;
+const MAX_REWARD_MIGRATION_LIMIT: u32 = 100;
+
+pub fn execute_continue_reward_migration(
+    deps: DepsMut,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let migration = REWARD_MIGRATION
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoRewardMigrationInProgress {})?;
+    if let Some(start_after) = start_after {
+        if migration.last_processed.as_ref().map(Addr::as_str) != Some(start_after.as_str()) {
+            return Err(ContractError::InvalidRewardMigrationStart {
+                expected: migration.last_processed.map(Addr::into_string),
+            });
+        }
+    }
+    let limit = limit
+        .unwrap_or(DEFAULT_REWARD_MIGRATION_LIMIT)
+        .min(MAX_REWARD_MIGRATION_LIMIT) as usize;
+
+    migrate_rewards(deps, env, migration, limit)
+}
+
+/// Converts the unclaimed rewards of up to `limit` accounts after `migration.last_processed`,
+/// and switches to the new token once all accounts are converted
+fn migrate_rewards(
+    deps: DepsMut,
+    env: Env,
+    mut migration: RewardMigration,
+    limit: usize,
+) -> Result<Response, ContractError> {
+    // The points per share are reset to zero once finished, so the whole converted amount is
+    // stored as a correction.
+    let mut distribution = DISTRIBUTION.load(deps.storage)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    let exchange_rate = migration.exchange_rate;
+    let start = migration.last_processed.as_ref().map(Bound::exclusive);
+    // one more than needed, to know if there are any left
+    let adjustments = WITHDRAW_ADJUSTMENT
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let finished = adjustments.len() <= limit;
+    for (addr, mut adjustment) in adjustments.into_iter().take(limit) {
+        let unclaimed = withdrawable_rewards(deps.storage, &addr, &distribution, &adjustment)?;
+        // decay cannot be told apart anymore after the reset, so it is applied before converting
+        let forfeited = match &config.reward_decay {
+            Some(decay) => forfeited_rewards(
+                deps.storage,
+                decay,
+                &distribution,
+                env.block.time,
+                &addr,
+                unclaimed,
+            )?,
+            None => Uint128::zero(),
+        };
+        let migrated = (unclaimed - forfeited) * exchange_rate;
+        if let Some(mut activity) = REWARD_ACTIVITY.may_load(deps.storage, &addr)? {
+            activity.settled_rewards = activity.settled_rewards * exchange_rate;
+            REWARD_ACTIVITY.save(deps.storage, &addr, &activity)?;
+        }
+        // `checked_shl` only checks the shift amount, so shifting is done as a multiplication
+        adjustment.shares_correction = migrated
+            .u128()
+            .checked_mul(1 << SHARES_SHIFT)
+            .and_then(|shares| i128::try_from(shares).ok())
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Shl, migrated, SHARES_SHIFT))?;
+        adjustment.withdrawn_rewards = Uint128::zero();
+        WITHDRAW_ADJUSTMENT.save(deps.storage, &addr, &adjustment)?;
+        migration.unclaimed += unclaimed;
+        migration.migrated += migrated;
+        migration.last_processed = Some(addr);
+    }
+
+    let resp = Response::new()
+        .add_attribute("action", "migrate_reward_token")
+        .add_attribute("new_token", migration.new_token.as_str())
+        .add_attribute("exchange_rate", exchange_rate.to_string())
+        .add_attribute("finished", finished.to_string());
+    if !finished {
+        REWARD_MIGRATION.save(deps.storage, &migration)?;
+        return Ok(resp);
+    }
+    REWARD_MIGRATION.remove(deps.storage);
+
+    let old_token = config.reward_token().clone();
+    let available = reward_balance(
+        deps.as_ref(),
+        &config,
+        &migration.new_token,
+        env.contract.address.clone(),
+    )?;
+    if available < migration.migrated {
+        return Err(ContractError::InsufficientRewardTokens {
+            required: migration.migrated,
+            available,
+        });
+    }
+    let old_balance = reward_balance(
+        deps.as_ref(),
+        &config,
+        &old_token,
+        env.contract.address.clone(),
+    )?;
+
+    distribution.shares_per_point = Uint128::zero();
+    distribution.shares_leftover = Uint128::zero();
+    distribution.distributed_total = distribution.distributed_total * exchange_rate;
+    distribution.withdrawable_total = migration.migrated;
+    DISTRIBUTION.save(deps.storage, &distribution)?;
+    SHARES_RESET_AT.save(deps.storage, &env.block.time.seconds())?;
+    record_shares_per_point(deps.storage, &config, &env, &distribution)?;
+
+    config.reward_token = Some(migration.new_token);
+    CONFIG.save(deps.storage, &config)?;
+
+    // old tokens are not owed to anyone anymore
+    let msg = WasmMsg::Execute {
+        contract_addr: old_token.to_string(),
+        msg: to_binary(&cw20_vesting::ExecuteMsg::Transfer {
+            recipient: migration.admin.to_string(),
+            amount: old_balance,
+        })?,
+        funds: vec![],
+    };
+
+    let resp = resp
+        .add_attribute("unclaimed", migration.unclaimed)
+        .add_attribute("migrated", migration.migrated)
+        .add_attribute("returned", old_balance)
+        .add_message(msg);
+
+    Ok(resp)
+}
+
 pub fn query_withdrawable_rewards(
     deps: Deps,
+    env: Env,
     owner: String,
 ) -> StdResult<WithdrawableRewardsResponse> {
     // Not checking address, as if it is invalid it is guaranteed not to appear in maps, so
     // `withdrawable_rewards` would return error itself.
     let owner = Addr::unchecked(&owner);
-    let distribution = DISTRIBUTION.load(deps.storage)?;
+    let (distribution, _) = load_streamed_distribution(deps, &env)?;
     let adjustment = if let Some(adj) = WITHDRAW_ADJUSTMENT.may_load(deps.storage, &owner)? {
         adj
     } else {
@@ -183,7 +1026,17 @@ pub fn query_withdrawable_rewards(
         });
     };
 
-    let rewards = withdrawable_rewards(deps, &owner, &distribution, &adjustment)?;
+    let mut rewards = withdrawable_rewards(deps.storage, &owner, &distribution, &adjustment)?;
+    if let Some(decay) = CONFIG.load(deps.storage)?.reward_decay {
+        rewards -= forfeited_rewards(
+            deps.storage,
+            &decay,
+            &distribution,
+            env.block.time,
+            &owner,
+            rewards,
+        )?;
+    }
     Ok(WithdrawableRewardsResponse { rewards })
 }
 
@@ -191,19 +1044,84 @@ pub fn query_undistributed_rewards(
     deps: Deps,
     env: Env,
 ) -> StdResult<UndistributedRewardsResponse> {
-    let distribution = DISTRIBUTION.load(deps.storage)?;
+    let (distribution, stream) = load_streamed_distribution(deps, &env)?;
     let balance = undistributed_rewards(deps, env.contract.address)?;
+    let streamed = stream.map(|stream| stream.remaining()).unwrap_or_default();
 
     Ok(UndistributedRewardsResponse {
-        rewards: (balance - distribution.withdrawable_total),
+        rewards: (balance - distribution.withdrawable_total - streamed),
     })
 }
 
-pub fn query_distributed_rewards(deps: Deps) -> StdResult<DistributedRewardsResponse> {
-    let distribution = DISTRIBUTION.load(deps.storage)?;
+pub fn query_distributed_rewards(deps: Deps, env: Env) -> StdResult<DistributedRewardsResponse> {
+    let (distribution, _) = load_streamed_distribution(deps, &env)?;
     Ok(DistributedRewardsResponse {
         distributed: distribution.distributed_total,
         withdrawable: distribution.withdrawable_total,
+        fees: distribution.fees_total,
+    })
+}
+
+const DEFAULT_DISTRIBUTION_LOG_LIMIT: u32 = 10;
+const MAX_DISTRIBUTION_LOG_LIMIT: u32 = 100;
+
+pub fn query_distribution_log(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    descending: bool,
+) -> StdResult<DistributionLogResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_DISTRIBUTION_LOG_LIMIT)
+        .min(MAX_DISTRIBUTION_LOG_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let (min, max, order) = if descending {
+        (None, start, Order::Descending)
+    } else {
+        (start, None, Order::Ascending)
+    };
+
+    let entries = DISTRIBUTION_LOG
+        .range(deps.storage, min, max, order)
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect::<StdResult<_>>()?;
+
+    Ok(DistributionLogResponse { entries })
+}
+
+pub fn query_delegation_report(deps: Deps, env: Env) -> StdResult<DelegationReportResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let query = cw20_vesting::QueryMsg::Balance {
+        address: env.contract.address.to_string(),
+    };
+    let cw20::BalanceResponse { balance } = deps
+        .querier
+        .query_wasm_smart(&config.cw20_contract, &query)?;
+
+    let totals = TOTAL_STAKED.may_load(deps.storage)?.unwrap_or_default();
+    let (distribution, stream) = load_streamed_distribution(deps, &env)?;
+    let mut accounted = totals.total();
+    // rewards owed to stakers are only paid from this balance if they are in the staked token
+    if config.reward_token() == &config.cw20_contract {
+        accounted += distribution.withdrawable_total;
+        accounted += stream.map(|stream| stream.remaining()).unwrap_or_default();
+    }
+
+    Ok(DelegationReportResponse {
+        token_balance_of_staking: balance,
+        total_staked: totals.staked,
+        total_unbonding: totals.unbonding,
+        withdrawable_rewards_total: distribution.withdrawable_total,
+        surplus: balance.saturating_sub(accounted),
+    })
+}
+
+pub fn query_can_distribute(deps: Deps) -> StdResult<CanDistributeResponse> {
+    let total_reward_power = TOTAL_REWARDS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(CanDistributeResponse {
+        can_distribute: !total_reward_power.is_zero(),
+        total_reward_power,
     })
 }
 
@@ -217,6 +1135,23 @@ pub fn query_delegated(deps: Deps, owner: String) -> StdResult<DelegatedResponse
     Ok(DelegatedResponse { delegated })
 }
 
+pub fn query_payout_address(deps: Deps, owner: String) -> StdResult<PayoutAddressResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let payout = PAYOUT_ADDRESS.may_load(deps.storage, &owner)?;
+    Ok(PayoutAddressResponse { payout })
+}
+
+pub fn query_distribution_weight(
+    deps: Deps,
+    address: String,
+) -> StdResult<DistributionWeightResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let weight = DISTRIBUTION_WEIGHT
+        .may_load(deps.storage, &address)?
+        .unwrap_or_else(Decimal::one);
+    Ok(DistributionWeightResponse { weight })
+}
+
 pub fn query_withdraw_adjustment_data(
     deps: Deps,
     owner: String,
@@ -260,23 +1195,25 @@ pub fn apply_points_correction(
 
 /// This is customized for the use case of the contract
 /// Since it is cw20, we just return the number, not the denom
+/// Does not take the reward decay into account, see `record_interaction`.
 pub fn withdrawable_rewards(
-    deps: Deps,
+    storage: &dyn Storage,
     owner: &Addr,
     distribution: &Distribution,
     adjustment: &WithdrawAdjustment,
 ) -> StdResult<Uint128> {
-    let ppw = distribution.shares_per_point.u128();
-    let points = REWARDS
-        .may_load(deps.storage, owner)?
-        .unwrap_or_default()
-        .u128();
+    let ppw = Uint256::from(distribution.shares_per_point);
+    let points = Uint256::from(REWARDS.may_load(storage, owner)?.unwrap_or_default());
     let correction = adjustment.shares_correction;
-    let withdrawn = adjustment.withdrawn_rewards.u128();
-    let points = (ppw * points) as i128;
-    let points = points + correction;
-    let amount = points as u128 >> SHARES_SHIFT;
-    let amount = amount - withdrawn;
+    let withdrawn = adjustment.withdrawn_rewards;
+    let points = ppw * points;
+    // the correction never takes more than the points were worth
+    let points = if correction < 0 {
+        points - Uint256::from(correction.unsigned_abs())
+    } else {
+        points + Uint256::from(correction as u128)
+    };
+    let amount = Uint128::try_from(points >> SHARES_SHIFT as u32)?;
 
-    Ok(amount.into())
+    Ok(amount - withdrawn)
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{OverflowError, StdError};
+use cosmwasm_std::{Decimal, OverflowError, StdError, Timestamp, Uint128};
 use thiserror::Error;
 
 use cw_controllers::{AdminError, HookError};
@@ -20,17 +20,59 @@ pub enum ContractError {
     #[error("Cannot rebond to the same unbonding period")]
     SameUnbondingRebond {},
 
+    #[error("Tokens are already bonded to the unbonding period with the highest multiplier")]
+    AlreadyOptimal {},
+
     #[error("Rebond amount is invalid")]
     NoRebondAmount {},
 
+    #[error("No unbonds provided")]
+    NoUnbonds {},
+
     #[error("No claims that can be released currently")]
     NothingToClaim {},
 
+    #[error("The token contract can only release {available} of the {requested} claimed tokens currently")]
+    TokenSideBlocked {
+        available: Uint128,
+        requested: Uint128,
+    },
+
+    #[error("Claims of this address are frozen")]
+    ClaimsFrozen {},
+
+    #[error("Label must be between 1 and {max} characters long")]
+    InvalidLabel { max: usize },
+
+    #[error("No claim found with the given expiry")]
+    ClaimNotFound {},
+
+    #[error("Claim is not matured yet, it unlocks at {unlocks_at}")]
+    ClaimNotYetMatured { unlocks_at: Timestamp },
+
+    #[error("Amount of claims to cancel must be positive")]
+    NoCancelAmount {},
+
+    #[error("Only {pending} of the {requested} tokens are in pending claims that can be canceled")]
+    InsufficientPendingClaims {
+        pending: Uint128,
+        requested: Uint128,
+    },
+
     #[error(
         "Sender's CW20 token contract address {got} does not match one from config {expected}"
     )]
     Cw20AddressesNotMatch { got: String, expected: String },
 
+    #[error("Only delegations of token {expected} can be staked, but {token} sent one")]
+    UnexpectedDelegationToken { token: String, expected: String },
+
+    #[error("Only rewards in token {expected} can be received, but {token} sent some")]
+    UnexpectedRewardToken { token: String, expected: String },
+
+    #[error("Delegation message `{kind}` is not supported by this staking contract")]
+    UnsupportedDelegationMsg { kind: String },
+
     #[error("No funds sent")]
     NoFunds {},
 
@@ -42,6 +84,182 @@ pub enum ContractError {
 
     #[error("No members to distribute tokens to")]
     NoMembersToDistributeTo {},
+
+    #[error("No reward sources to distribute")]
+    NoRewardSources {},
+
+    #[error("Cannot distribute {requested} rewards, only {available} are undistributed")]
+    InsufficientUndistributedRewards {
+        requested: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Distribution weight must be between 0 and {max}")]
+    InvalidDistributionWeight { max: Decimal },
+
+    #[error("No rewards left in the current reward token to migrate")]
+    NoRewardsToMigrate {},
+
+    #[error("Rewards are already paid out in this token")]
+    SameRewardToken {},
+
+    #[error("Not enough new reward tokens to cover unclaimed rewards: required {required}, available {available}")]
+    InsufficientRewardTokens {
+        required: Uint128,
+        available: Uint128,
+    },
+
+    #[error(
+        "Not enough undistributed rewards to stream: required {required}, available {available}"
+    )]
+    InsufficientStreamFunds {
+        required: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Reward stream duration must be at least one block")]
+    InvalidStreamDuration {},
+
+    #[error("There is already an active reward stream")]
+    RewardStreamActive {},
+
+    #[error("Tokens per power must be greater than zero")]
+    ZeroTokensPerPower {},
+
+    #[error("No members are waiting for their powers to be recomputed")]
+    NoRecomputePending {},
+
+    #[error("Recomputing has to continue after {expected:?}")]
+    InvalidRecomputeStart { expected: Option<String> },
+
+    #[error("A migration is in progress, it has to be completed with ContinueMigration first")]
+    MigrationInProgress {},
+
+    #[error("No migration is in progress")]
+    NoMigrationInProgress {},
+
+    #[error("A reward token migration is in progress, it has to be completed with ContinueRewardMigration first")]
+    RewardMigrationInProgress {},
+
+    #[error("No reward token migration is in progress")]
+    NoRewardMigrationInProgress {},
+
+    #[error("Reward token migration has to continue after {expected:?}")]
+    InvalidRewardMigrationStart { expected: Option<String> },
+
+    #[error("Governance contract {address} is already registered")]
+    GovernanceContractAlreadyRegistered { address: String },
+
+    #[error("Governance contract {address} is not registered")]
+    GovernanceContractNotRegistered { address: String },
+
+    #[error("Cannot register more than {max} hooks")]
+    TooManyHooks { max: u32 },
+
+    #[error("Cannot schedule more than {max} rebonds")]
+    TooManyScheduledRebonds { max: u32 },
+
+    #[error("No scheduled rebonds can be executed yet")]
+    NoScheduledRebondsDue {},
+
+    #[error("Voting power of {address} is already excluded")]
+    AlreadyExcludedFromPower { address: String },
+
+    #[error("Voting power of {address} is not excluded")]
+    NotExcludedFromPower { address: String },
+
+    #[error("Bonding new stake is locked until height {unlocks_at}")]
+    StakingLocked { unlocks_at: u64 },
+
+    #[error("Distribution fee cannot exceed {max} bps")]
+    DistributionFeeTooHigh { max: u64 },
+
+    #[error("Reward decay must be at most 100% and needs an inactivity period")]
+    InvalidRewardDecay {},
+
+    #[error("Exit vesting of unbonding period {unbonding_period} must decrease to zero")]
+    InvalidExitVesting { unbonding_period: u64 },
+
+    #[error("At least one unbonding period has to be configured")]
+    NoUnbondingPeriods {},
+
+    #[error("Unbonding period must be greater than zero")]
+    ZeroUnbondingPeriod {},
+
+    #[error("Unbonding period {unbonding_period} is configured more than once")]
+    DuplicateUnbondingPeriod { unbonding_period: u64 },
+
+    #[error("Multipliers of unbonding period {unbonding_period} must be greater than zero")]
+    ZeroMultiplier { unbonding_period: u64 },
+
+    #[error("Multipliers of unbonding period {unbonding_period} cannot exceed {max}")]
+    MultiplierTooHigh { unbonding_period: u64, max: Decimal },
+
+    #[error("The admin of this contract was renounced")]
+    AdminRenounced {},
+
+    #[error("Admin multisig needs unique members and between 1 and all of them as approvals")]
+    InvalidAdminMultisig {},
+
+    #[error("No admin multisig is set")]
+    NoAdminMultisig {},
+
+    #[error("Admin proposal has to expire in the future")]
+    InvalidProposalExpiration {},
+
+    #[error("Admin proposal {proposal_id} not found")]
+    AdminProposalNotFound { proposal_id: u64 },
+
+    #[error("Admin proposal {proposal_id} expired")]
+    AdminProposalExpired { proposal_id: u64 },
+
+    #[error("Admin proposal {proposal_id} was already executed")]
+    AdminProposalExecuted { proposal_id: u64 },
+
+    #[error("Admin proposal {proposal_id} is already approved by the sender")]
+    AlreadyApproved { proposal_id: u64 },
+
+    #[error("Admin proposal needs {required} approvals, but only has {approvals}")]
+    NotEnoughApprovals { approvals: u32, required: u32 },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Rewards of {owner} are always paid to {payout}, not to {receiver}")]
+    ConflictingReceiver {
+        owner: String,
+        payout: String,
+        receiver: String,
+    },
+
+    #[error("Claims of unbonding period {0} vest on exit and can only go to the sender")]
+    ExitVestingClaimRecipient(u64),
+
+    #[error("Slashed portion must be greater than 0 and at most 1")]
+    InvalidSlashPortion {},
+
+    #[error("{address} has no unlocked stake to slash")]
+    NothingToSlash { address: String },
+
+    #[error(
+        "Slashed tokens can only go to the reward pool if rewards are paid in the staked token"
+    )]
+    SlashToRewardPoolUnavailable {},
+
+    #[error("No owners given to withdraw rewards for")]
+    EmptyWithdrawBatch {},
+
+    #[error("Cannot withdraw rewards for more than {max} owners at once")]
+    TooManyWithdrawOwners { max: u32 },
+
+    #[error("Vesting voting discount must be at most 1")]
+    InvalidVotingDiscount {},
+
+    #[error("No vesting voting discount is configured")]
+    VotingDiscountDisabled {},
+
+    #[error("Sender is not the withdrawal delegate of {owner}")]
+    NotWithdrawalDelegate { owner: String },
 }
 
 impl From<OverflowError> for ContractError {
@@ -28,6 +28,8 @@ pub mod msg;
 pub mod hook;
 /// state on the blockchain
 pub mod state;
+/// raw storage keys of the state, for clients reading it directly
+pub mod state_keys;
 
 #[cfg(test)]
 mod multitest;
@@ -1,10 +1,13 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Decimal, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Decimal256, Timestamp, Uint128};
+use cw2::ContractVersion;
+use cw20::Cw20ReceiveMsg;
 use cw20_vesting::Cw20ReceiveDelegationMsg;
 pub use cw_controllers::ClaimsResponse;
 use cw_core_macros::{token_query, voting_query};
+use wynd_utils::ScalableCurve;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct InstantiateMsg {
@@ -16,9 +19,75 @@ pub struct InstantiateMsg {
 
     // admin can only add/remove hooks, not change other parameters
     pub admin: Option<String>,
+    /// Maximum number of hooks that can be registered, defaults to 5
+    #[serde(default)]
+    pub max_hooks: Option<u32>,
+    /// Part of every reward distribution sent to a recipient instead of the stakers
+    #[serde(default)]
+    pub distribution_fee: Option<DistributionFeeInfo>,
+    /// If set, the votes of staked tokens that are still vesting on the token contract are
+    /// reduced by this portion (at most 1). Rewards are not affected.
+    #[serde(default)]
+    pub vesting_voting_discount: Option<Decimal>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RewardDecayInfo {
+    /// Seconds without bonding, unbonding, rebonding or withdrawing rewards after which a
+    /// staker is considered inactive
+    pub inactivity_period: u64,
+    /// Part of the rewards earned after becoming inactive that a staker forfeits.
+    /// Forfeited rewards are distributed to all stakers again.
+    pub decay: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct DistributionFeeInfo {
+    /// Fee in basis points, at most 2000 (20%)
+    pub bps: u64,
+    pub recipient: String,
+}
+
+/// Where tokens removed from a member's stake by `SlashMember` go
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SlashDestination {
+    /// The tokens are burned on the token contract
+    #[default]
+    Burn,
+    /// The tokens stay with this contract as undistributed rewards, which are given to all
+    /// stakers with the next distribution
+    RewardPool,
+}
+
+impl SlashDestination {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlashDestination::Burn => "burn",
+            SlashDestination::RewardPool => "reward_pool",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RewardSource {
+    /// Original source of the rewards, informational. The message sender by default.
+    pub sender: Option<String>,
+    pub amount: Uint128,
+}
+
+/// Where rewards withdrawn with `ExecuteMsg::WithdrawRewardsBatch` are sent
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiverMode {
+    /// Rewards of every owner are sent to the owner, or their payout address if set
+    OwnerWallets,
+    /// Rewards of all owners are summed up and sent to this address in a single transfer.
+    /// Only allowed if the sender is the withdrawal delegate of all owners.
+    SingleReceiver(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     /// Rebond will update an amount of bonded tokens from one bond period to the other
@@ -27,7 +96,33 @@ pub enum ExecuteMsg {
         // these must be valid time periods
         bond_from: u64,
         bond_to: u64,
+        /// If set, all matured claims of the sender are released first, like `Claim` does.
+        /// The claimed tokens leave the contract, they are never rebonded.
+        /// Does not fail if there is nothing to claim.
+        #[serde(default)]
+        claim_first: bool,
+    },
+    /// Rebonds tokens from one bond period to the one with the highest voting or reward
+    /// multiplier. If several periods share the highest multiplier, the shortest one is used.
+    RebondToOptimal {
+        bond_from: u64,
+        amount: Uint128,
+        optimize_for: OptimizeFor,
+    },
+    /// Queues a rebond of the sender's tokens, to be executed by anyone with
+    /// `ExecuteScheduledRebonds` once `execute_after` has passed, e.g. when tokens locked by an
+    /// earlier rebond to a shorter period are released. At most `MAX_SCHEDULED_REBONDS` rebonds
+    /// can be pending per address
+    ScheduleRebond {
+        tokens: Uint128,
+        bond_from: u64,
+        bond_to: u64,
+        execute_after: Timestamp,
     },
+    /// Executes all scheduled rebonds of `address` whose time has come, like `Rebond` would.
+    /// Rebonds that would fail are dropped and reported in a `scheduled_rebond_dropped` event.
+    /// Can be called by anyone
+    ExecuteScheduledRebonds { address: String },
     /// Unbond will start the unbonding process for the given number of tokens.
     /// The sender immediately loses power from these tokens, and can claim them
     /// back to his wallet after `unbonding_period`
@@ -36,20 +131,49 @@ pub enum ExecuteMsg {
         /// As each unbonding period in delegation corresponds to particular voting
         /// multiplier, unbonding_period needs to be passed in unbond as well
         unbonding_period: u64,
+        /// If set, the claim is created for this address instead of the sender, so it can claim
+        /// the tokens after `unbonding_period`. The sender still loses the stake and powers.
+        /// Claiming fails while the sender's remaining tokens would not cover their vesting ones.
+        #[serde(default)]
+        claim_recipient: Option<String>,
     },
+    /// Unbonds tokens from several unbonding periods at once. Either all unbonds succeed
+    /// or none of them is applied.
+    BatchUnbond { unbonds: Vec<UnbondRequest> },
     /// Claim is used to claim your native tokens that you previously "unbonded"
     /// after the contract-defined waiting period (eg. 1 week)
     Claim {},
+    /// Claims only the single claim releasing at the given timestamp (in seconds),
+    /// if it is already matured.
+    ClaimSpecific { expires_at: u64 },
+    /// Cancels `amount` of the sender's claims that have not matured yet, oldest first, and
+    /// stakes the tokens again in the given unbonding period. Matured claims can only be claimed,
+    /// claims created by someone else unbonding for the sender cannot be canceled.
+    CancelClaim {
+        amount: Uint128,
+        unbonding_period: u64,
+    },
 
     /// Change the admin
     UpdateAdmin { admin: Option<String> },
-    /// Add a new hook to be informed of all membership changes. Must be called by Admin
-    AddHook { addr: String },
+    /// Removes the admin permanently. Afterwards, all admin messages fail, including
+    /// `UpdateAdmin`. Must be called by Admin.
+    RenounceAdmin {},
+    /// Add a new hook to be informed of all membership changes. Must be called by Admin.
+    /// If `gas_limit` is set, every call to the hook is limited to that much gas and
+    /// failures of the hook are recorded instead of failing the membership change.
+    AddHook {
+        addr: String,
+        #[serde(default)]
+        gas_limit: Option<u64>,
+    },
     /// Remove a hook. Must be called by Admin
     RemoveHook { addr: String },
 
     /// This accepts a properly-encoded ReceiveMsg from a cw20 contract
     ReceiveDelegation(Cw20ReceiveDelegationMsg),
+    /// Accepts tokens sent with cw20 `Send` by the reward token contract, with a `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
 
     /// Distributes rewards sent with this message, and all rewards transferred since last call of this
     /// to members, proportionally to their points. Rewards are not immediately send to members, but
@@ -59,6 +183,10 @@ pub enum ExecuteMsg {
         /// propagated event.
         sender: Option<String>,
     },
+    /// Distributes rewards deposited by several sources at once, updating the distribution only
+    /// once. The summed `amounts` have to be available as undistributed rewards, anything on top
+    /// is left for a later distribution.
+    BatchDistributeRewards { amounts: Vec<RewardSource> },
     /// Withdraws rewards which were previously distributed and assigned to sender.
     WithdrawRewards {
         /// Account from which assigned rewards would be withdrawn; `sender` by default. `sender` has
@@ -66,7 +194,22 @@ pub enum ExecuteMsg {
         /// call `DelegateWithdrawal { delegated: sender }` before)
         owner: Option<String>,
         /// Address where to transfer funds. If not present, funds would be sent to `sender`.
+        /// If `owner` has a payout address set, funds are always sent there and any other
+        /// receiver is rejected.
         receiver: Option<String>,
+        /// If set, the rewards are sent to the receiver with a cw20 `Send` instead of a
+        /// `Transfer`, so the receiver has to be a contract handling `Cw20ReceiveMsg` with
+        /// this `msg`. The tokens are sent from the rewards held by this contract, no
+        /// undelegation is involved.
+        #[serde(default)]
+        receiver_msg: Option<Binary>,
+    },
+    /// Withdraws rewards of up to 30 owners at once. `sender` has to be the owner or their
+    /// withdrawal delegate, otherwise the owner is skipped. Skipped owners are reported in the
+    /// `skipped` attributes and don't fail the batch.
+    WithdrawRewardsBatch {
+        owners: Vec<String>,
+        receiver_mode: ReceiverMode,
     },
     /// Sets given address as allowed for senders funds withdrawal. Funds still can be withdrawn by
     /// sender himself, but this additional account is allowed to perform it as well. There can be only
@@ -76,6 +219,315 @@ pub enum ExecuteMsg {
         /// to own address.
         delegated: String,
     },
+    /// Sets a fixed address receiving all rewards withdrawn for the sender, no matter who
+    /// triggers the withdrawal. `None` removes it, so rewards go to the given receiver again.
+    SetPayoutAddress { payout: Option<String> },
+    /// Overrides the reward power weight of the given address (1.0 by default, at most 10.0).
+    /// Must be called by Admin
+    SetDistributionWeight { address: String, weight: Decimal },
+    /// Switches rewards to be paid out in `new_token`. All unclaimed rewards are converted at
+    /// `exchange_rate` (new tokens per old token), except for the part forfeited due to reward
+    /// decay, so the new tokens covering them have to be transferred to this contract beforehand. Remaining old reward tokens are sent back to the
+    /// admin. Must be called by Admin
+    ///
+    /// Converts the rewards of the first accounts only. If there are more, the migration is
+    /// finished by `ContinueRewardMigration`, and all other messages are rejected until then.
+    MigrateRewardToken {
+        new_token: String,
+        exchange_rate: Decimal,
+    },
+    /// Releases `total` rewards linearly over the next `duration_blocks` blocks, instead of
+    /// distributing them at once. The rewards have to be transferred to this contract beforehand.
+    /// Only one stream can be active at a time. Must be called by Admin
+    StartRewardStream {
+        total: Uint128,
+        duration_blocks: u64,
+    },
+    /// Changes how voting and reward powers are calculated from the stake. Setting None/null
+    /// for any of these will leave it unchanged. Powers of existing members are updated by
+    /// `RecomputeMembers` afterwards. Must be called by Admin
+    UpdatePowerConfig {
+        tokens_per_power: Option<Uint128>,
+        min_bond: Option<Uint128>,
+    },
+    /// Sets the fee taken from every reward distribution, None removes it.
+    /// Must be called by Admin
+    UpdateDistributionFee {
+        distribution_fee: Option<DistributionFeeInfo>,
+    },
+    /// Sets how rewards of inactive stakers decay, None disables it. Must be called by Admin
+    UpdateRewardDecay {
+        reward_decay: Option<RewardDecayInfo>,
+    },
+    /// Sets the extra voting power in percent stakers get for every loyalty epoch they keep their
+    /// stake, capped at `max_pct`. Powers of existing members are updated by `RecomputeMembers`
+    /// afterwards. Must be called by Admin
+    UpdateLoyaltyBonus { bonus_pct: u8, max_pct: u8 },
+    /// Recomputes the powers of up to `limit` members under the current power config, continuing
+    /// where the previous call stopped. Can be called by anyone while a recompute is pending.
+    RecomputeMembers {
+        /// Has to match where the previous call stopped if set, to avoid racing calls
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Back-fills the data of up to `limit` stakers for a staged migration, continuing where the
+    /// previous call stopped. Can be called by anyone while a migration is in progress, which
+    /// blocks all other messages.
+    ContinueMigration { limit: Option<u32> },
+    /// Converts the unclaimed rewards of up to `limit` more accounts for the reward token
+    /// migration started by `MigrateRewardToken`, continuing where the previous call stopped.
+    /// Can be called by anyone while the migration is in progress.
+    ContinueRewardMigration {
+        /// Has to match where the previous call stopped if set, to avoid racing calls
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Allows the given governance contract to record votes. Must be called by Admin
+    RegisterGovernanceContract { address: String },
+    /// Removes a governance contract registered before. Must be called by Admin
+    UnregisterGovernanceContract { address: String },
+    /// Records that `voter` cast a vote. Must be called by a registered governance contract
+    RecordVote { voter: String },
+    /// Prevents any new bonds until the given block height is reached. Unbonding, rebonding and
+    /// claiming are not affected. A height in the past lifts an active lock.
+    /// Must be called by Admin
+    LockNewStaking { expires_at_height: u64 },
+    /// Excludes the voting power of the given address from `VotingPowerAtHeight` and
+    /// `TotalPowerAtHeight`. Rewards and hooks still use the real power. Must be called by Admin
+    ExcludeFromPower { address: String },
+    /// Counts the voting power of a previously excluded address again. Must be called by Admin
+    IncludeInPower { address: String },
+    /// Prevents the given address from releasing any of its claims, e.g. after its key was
+    /// compromised. Must be called by Admin
+    FreezeClaims { address: String },
+    /// Allows the given address to release its claims again. Must be called by Admin
+    UnfreezeClaims { address: String },
+    /// Tags the given address with a label of at most `MAX_LABEL_LENGTH` characters, replacing
+    /// any previous one. Labels are only informational. Must be called by Admin
+    SetLabel { address: String, label: String },
+    /// Removes the label of the given address. Must be called by Admin
+    RemoveLabel { address: String },
+    /// Repairs the voting power of the given address by recomputing it from its stakes under
+    /// the current multipliers. No hooks are called. Must be called by Admin
+    RecomputeVotingPower { address: String },
+    /// Reduces the discounted vesting stake of `address` to what is still locked on the token
+    /// contract and recomputes its votes. Vesting only ever unlocks tokens, so the discount
+    /// never grows here. Can be called by anyone
+    RefreshVotingDiscount { address: String },
+    /// Removes `portion` of the unlocked stake of `address` as a penalty, from the given
+    /// unbonding period or from all of them proportionally. Pending claims and tokens locked by
+    /// rebonds to shorter periods are never slashed. The slashed tokens go to the configured
+    /// `SlashDestination`. Must be called by Admin
+    SlashMember {
+        address: String,
+        portion: Decimal,
+        unbonding_period: Option<u64>,
+    },
+    /// Sets where tokens removed by `SlashMember` go. Must be called by Admin
+    UpdateSlashDestination { destination: SlashDestination },
+    /// Hands the admin rights over to a group of addresses. Afterwards admin actions have to be
+    /// proposed and approved by at least `min_approvals` of them. Must be called by Admin
+    SetAdminMultisig {
+        addresses: Vec<String>,
+        min_approvals: u32,
+    },
+    /// Proposes an admin action, approved by the proposer already.
+    /// Must be called by a member of the admin multisig
+    ProposeAdminAction {
+        action: AdminAction,
+        /// Time in seconds after which the proposal cannot be approved or executed anymore
+        expires_at: u64,
+    },
+    /// Approves a proposed admin action. Must be called by a member of the admin multisig
+    ApproveAdminAction { proposal_id: u64 },
+    /// Executes an admin action approved by enough members of the admin multisig.
+    /// Must be called by one of the approvers
+    ExecuteAdminAction { proposal_id: u64 },
+}
+
+/// Admin actions that can be proposed to the admin multisig.
+/// Each of them executes the `ExecuteMsg` of the same name.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    UpdateAdmin {
+        admin: Option<String>,
+    },
+    RenounceAdmin {},
+    AddHook {
+        addr: String,
+        #[serde(default)]
+        gas_limit: Option<u64>,
+    },
+    RemoveHook {
+        addr: String,
+    },
+    SetDistributionWeight {
+        address: String,
+        weight: Decimal,
+    },
+    MigrateRewardToken {
+        new_token: String,
+        exchange_rate: Decimal,
+    },
+    StartRewardStream {
+        total: Uint128,
+        duration_blocks: u64,
+    },
+    UpdatePowerConfig {
+        tokens_per_power: Option<Uint128>,
+        min_bond: Option<Uint128>,
+    },
+    UpdateDistributionFee {
+        distribution_fee: Option<DistributionFeeInfo>,
+    },
+    UpdateRewardDecay {
+        reward_decay: Option<RewardDecayInfo>,
+    },
+    UpdateLoyaltyBonus {
+        bonus_pct: u8,
+        max_pct: u8,
+    },
+    RegisterGovernanceContract {
+        address: String,
+    },
+    UnregisterGovernanceContract {
+        address: String,
+    },
+    LockNewStaking {
+        expires_at_height: u64,
+    },
+    ExcludeFromPower {
+        address: String,
+    },
+    IncludeInPower {
+        address: String,
+    },
+    FreezeClaims {
+        address: String,
+    },
+    UnfreezeClaims {
+        address: String,
+    },
+    SetLabel {
+        address: String,
+        label: String,
+    },
+    RemoveLabel {
+        address: String,
+    },
+    RecomputeVotingPower {
+        address: String,
+    },
+    SlashMember {
+        address: String,
+        portion: Decimal,
+        unbonding_period: Option<u64>,
+    },
+    UpdateSlashDestination {
+        destination: SlashDestination,
+    },
+    SetAdminMultisig {
+        addresses: Vec<String>,
+        min_approvals: u32,
+    },
+}
+
+impl From<AdminAction> for ExecuteMsg {
+    fn from(action: AdminAction) -> Self {
+        match action {
+            AdminAction::UpdateAdmin { admin } => ExecuteMsg::UpdateAdmin { admin },
+            AdminAction::RenounceAdmin {} => ExecuteMsg::RenounceAdmin {},
+            AdminAction::AddHook { addr, gas_limit } => ExecuteMsg::AddHook { addr, gas_limit },
+            AdminAction::RemoveHook { addr } => ExecuteMsg::RemoveHook { addr },
+            AdminAction::SetDistributionWeight { address, weight } => {
+                ExecuteMsg::SetDistributionWeight { address, weight }
+            }
+            AdminAction::MigrateRewardToken {
+                new_token,
+                exchange_rate,
+            } => ExecuteMsg::MigrateRewardToken {
+                new_token,
+                exchange_rate,
+            },
+            AdminAction::StartRewardStream {
+                total,
+                duration_blocks,
+            } => ExecuteMsg::StartRewardStream {
+                total,
+                duration_blocks,
+            },
+            AdminAction::UpdatePowerConfig {
+                tokens_per_power,
+                min_bond,
+            } => ExecuteMsg::UpdatePowerConfig {
+                tokens_per_power,
+                min_bond,
+            },
+            AdminAction::UpdateDistributionFee { distribution_fee } => {
+                ExecuteMsg::UpdateDistributionFee { distribution_fee }
+            }
+            AdminAction::UpdateRewardDecay { reward_decay } => {
+                ExecuteMsg::UpdateRewardDecay { reward_decay }
+            }
+            AdminAction::UpdateLoyaltyBonus { bonus_pct, max_pct } => {
+                ExecuteMsg::UpdateLoyaltyBonus { bonus_pct, max_pct }
+            }
+            AdminAction::RegisterGovernanceContract { address } => {
+                ExecuteMsg::RegisterGovernanceContract { address }
+            }
+            AdminAction::UnregisterGovernanceContract { address } => {
+                ExecuteMsg::UnregisterGovernanceContract { address }
+            }
+            AdminAction::LockNewStaking { expires_at_height } => {
+                ExecuteMsg::LockNewStaking { expires_at_height }
+            }
+            AdminAction::ExcludeFromPower { address } => ExecuteMsg::ExcludeFromPower { address },
+            AdminAction::IncludeInPower { address } => ExecuteMsg::IncludeInPower { address },
+            AdminAction::FreezeClaims { address } => ExecuteMsg::FreezeClaims { address },
+            AdminAction::UnfreezeClaims { address } => ExecuteMsg::UnfreezeClaims { address },
+            AdminAction::SetLabel { address, label } => ExecuteMsg::SetLabel { address, label },
+            AdminAction::RemoveLabel { address } => ExecuteMsg::RemoveLabel { address },
+            AdminAction::RecomputeVotingPower { address } => {
+                ExecuteMsg::RecomputeVotingPower { address }
+            }
+            AdminAction::SlashMember {
+                address,
+                portion,
+                unbonding_period,
+            } => ExecuteMsg::SlashMember {
+                address,
+                portion,
+                unbonding_period,
+            },
+            AdminAction::UpdateSlashDestination { destination } => {
+                ExecuteMsg::UpdateSlashDestination { destination }
+            }
+            AdminAction::SetAdminMultisig {
+                addresses,
+                min_approvals,
+            } => ExecuteMsg::SetAdminMultisig {
+                addresses,
+                min_approvals,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct UnbondRequest {
+    pub amount: Uint128,
+    pub unbonding_period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OptimizeFor {
+    /// Pick the unbonding period with the highest voting multiplier
+    Voting,
+    /// Pick the unbonding period with the highest reward multiplier
+    Rewards,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -87,6 +539,21 @@ pub enum ReceiveDelegationMsg {
     },
 }
 
+impl ReceiveDelegationMsg {
+    /// Keys naming the kinds of delegation messages in JSON, one for every variant
+    pub const KINDS: &'static [&'static str] = &["delegate"];
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Distributes the sent tokens to stakers, together with all other undistributed rewards,
+    /// the same way as `ExecuteMsg::DistributeRewards`.
+    /// Fails with `NoMembersToDistributeTo` while nobody is staking, which reverts the whole
+    /// `Send`, so the tokens stay with the sender.
+    Fund {},
+}
+
 #[voting_query]
 #[token_query]
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
@@ -105,11 +572,27 @@ pub enum QueryMsg {
     /// Show the number of tokens currently staked by this address for all unbonding periods
     AllStaked {
         address: String,
+        /// Unbonding period (in seconds) after which to start listing
+        start_after: Option<u64>,
+        /// Maximum number of entries to return, all of them if not set
+        limit: Option<u32>,
+        /// Whether to list unbonding periods the address has no stake in anymore, true by default
+        include_empty: Option<bool>,
     },
     /// Show the number of all, not unbonded tokens delegated by all users for all unbonding periods
     TotalStaked {},
     /// Show the number of all tokens being unbonded for all unbonding periods
     TotalUnbonding {},
+    /// Compares the staked token balance of this contract with the amounts it accounts for.
+    /// Returns `DelegationReportResponse`
+    DelegationReport {},
+    /// Lists all stakers with their stake summed over all unbonding periods and their label,
+    /// if any. Goes through at most `limit` stakers and returns `AllDelegationsResponse`,
+    /// which tells where to continue
+    AllDelegations {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     /// Show the total number of outstanding rewards
     TotalRewards {},
     /// Show the outstanding rewards for this address
@@ -118,9 +601,15 @@ pub enum QueryMsg {
     },
     /// Return AdminResponse
     Admin {},
+    /// Shows whether the admin was renounced. Returns AdminStatusResponse.
+    AdminStatus {},
     /// Shows all registered hooks. Returns HooksResponse.
     Hooks {},
     BondingInfo {},
+    /// Everything needed to render a staking page in one query: token contract, admin,
+    /// power config, all unbonding periods with their totals and the contract version.
+    /// Returns `ConfigResponse`
+    Config {},
 
     /// Return how many rewards are assigned for withdrawal from the given address. Returns
     /// `RewardsResponse`.
@@ -133,16 +622,117 @@ pub enum QueryMsg {
     /// Return how many funds were sent to this contract since last `ExecuteMsg::DistributeFunds`,
     /// and await for distribution. Returns `RewardsResponse`.
     UndistributedRewards {},
+    /// Return whether `ExecuteMsg::DistributeRewards` would currently be accepted, which requires
+    /// some reward power to be staked. Returns `CanDistributeResponse`.
+    CanDistribute {},
     /// Return address allowed for withdrawal of the funds assigned to owner. Returns `DelegateResponse`
     Delegated {
         owner: String,
     },
+    /// Return the address all rewards of the owner are paid to, if set. Returns `PayoutAddressResponse`
+    PayoutAddress {
+        owner: String,
+    },
     /// Returns rewards distribution data
     DistributionData {},
+    /// Returns the active reward stream, if any. Returns `RewardStreamResponse`
+    RewardStream {},
     /// Returns withdraw adjustment data
     WithdrawAdjustmentData {
         addr: String,
     },
+    /// Return the reward power weight of the given address. Returns `DistributionWeightResponse`
+    DistributionWeight {
+        address: String,
+    },
+    /// Return the progress of recomputing member powers after `ExecuteMsg::UpdatePowerConfig`.
+    /// Returns `RecomputeStatusResponse`
+    RecomputeStatus {},
+    /// Return the progress of the staged migration started by the last `migrate`.
+    /// Returns `MigrationProgressResponse`
+    MigrationProgress {},
+    /// Return when the given address staked for the first time. Returns `FirstStakedResponse`
+    FirstStaked {
+        address: String,
+    },
+    /// Return the claims of all addresses that are already matured, ordered by the time they
+    /// matured. Returns `MatureClaimsResponse`
+    MatureClaims {
+        /// Maturity time and address of the claim after which to start listing
+        start_after: Option<(Timestamp, String)>,
+        limit: Option<u32>,
+    },
+    /// Return the governance contracts allowed to record votes.
+    /// Returns `GovernanceContractsResponse`
+    GovernanceContracts {},
+    /// Returns the members of the admin multisig, empty if there is none.
+    /// Returns `AdminMultisigResponse`
+    AdminMultisig {},
+    /// Returns a proposed admin action.
+    /// Returns `AdminProposal`
+    AdminProposal {
+        proposal_id: u64,
+    },
+    /// Returns whether new bonds are currently locked.
+    /// Returns `StakingLockResponse`
+    IsStakingLocked {},
+    /// Lists the addresses excluded from voting power.
+    /// Returns `PowerExclusionsResponse`
+    PowerExclusions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Lists the members with their current voting power in the shape of a cw4 group,
+    /// so cw4 tooling can use this contract as a group.
+    /// As cw4 weights are `u64`, powers above `u64::MAX` are reported as `u64::MAX`.
+    /// Excluded addresses are listed with a weight of 0.
+    /// The raw `cw4::member_key` and `cw4::TOTAL_KEY` entries hold the same `u64` weights for
+    /// raw cw4 readers, but do not take exclusions into account.
+    /// Returns `cw4::MemberListResponse`
+    ListMembersCw4 {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the loyalty epochs of the given address and the bonus it gives on voting power.
+    /// Returns `LoyaltyBonusResponse`
+    LoyaltyBonus {
+        address: String,
+    },
+    /// Shows how much each unbonding period contributes to the reward power of the given address,
+    /// recomputed from its stakes and the current config. Returns `RewardsBreakdownResponse`
+    RewardsBreakdown {
+        address: String,
+    },
+    /// Lists the pending scheduled rebonds of the given address.
+    /// Returns `ScheduledRebondsResponse`
+    ScheduledRebonds {
+        address: String,
+    },
+    /// Returns whether the claims of the given address are frozen. Returns `ClaimsFrozenResponse`
+    ClaimsFrozen {
+        address: String,
+    },
+    /// Lists the latest distributions by sequence number. Only the latest
+    /// `DISTRIBUTION_LOG_SIZE` entries are kept. Returns `DistributionLogResponse`
+    DistributionLog {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        /// List from the latest distribution backwards, defaults to false
+        #[serde(default)]
+        descending: bool,
+    },
+    /// Returns the label of the given address, if any. Returns `LabelResponse`
+    Label {
+        address: String,
+    },
+    /// Lists all labelled addresses. Returns `AllLabelsResponse`
+    AllLabels {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the namespaces and key encodings of the contract state, for clients reading
+    /// it directly. See `state_keys` for the details. Returns `RawKeyLayoutResponse`
+    RawKeyLayout {},
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
@@ -153,6 +743,10 @@ pub struct StakeConfig {
     pub unbonding_period: u64,      // seconds
     pub voting_multiplier: Decimal, // stake * voting_ratio = voting_power
     pub reward_multiplier: Decimal, // stake * reward_ratio = reward_power
+    /// If set, claimed tokens unbonded from this period vest according to this curve,
+    /// scaled to the claimed amount. Its x values are seconds relative to the claim.
+    #[serde(default)]
+    pub exit_vesting: Option<ScalableCurve>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -162,6 +756,10 @@ pub struct StakedResponse {
     pub total_locked: Uint128,
     pub unbonding_period: u64,
     pub cw20_contract: String,
+    /// Time the earliest locked tokens (from rebonding to a shorter unbonding period) are
+    /// released at. Not set if no tokens are currently locked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub earliest_unlock: Option<Timestamp>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -174,6 +772,19 @@ pub struct AllStakedResponse {
 #[serde(rename_all = "snake_case")]
 pub struct TotalStakedResponse {
     pub total_staked: Uint128,
+    /// Tokens staked to each unbonding period, as tracked by the period's stake config
+    #[serde(default)]
+    pub by_period: Vec<PeriodStaked>,
+    /// Whether the sum of `by_period` equals `total_staked`. False means the two storage
+    /// paths drifted apart.
+    #[serde(default)]
+    pub consistent: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PeriodStaked {
+    pub unbonding_period: u64,
+    pub staked: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -198,6 +809,8 @@ pub struct BondingPeriodInfo {
     pub voting_multiplier: Decimal,
     pub reward_multiplier: Decimal,
     pub total_staked: Uint128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_vesting: Option<ScalableCurve>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -206,6 +819,24 @@ pub struct BondingInfoResponse {
     pub bonding: Vec<BondingPeriodInfo>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigResponse {
+    /// address of cw20 contract token to stake
+    pub cw20_contract: Addr,
+    pub admin: Option<Addr>,
+    pub tokens_per_power: Uint128,
+    pub min_bond: Uint128,
+    /// All unbonding periods with their multipliers and staked totals, same as `BondingInfo`
+    pub bonding: Vec<BondingPeriodInfo>,
+    pub total_staked: Uint128,
+    pub total_unbonding: Uint128,
+    pub slash_destination: SlashDestination,
+    pub vesting_voting_discount: Option<Decimal>,
+    /// Contract name and version as stored by cw2
+    pub version: ContractVersion,
+}
+
 // just for the proper json outputs
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct TokenContractResponse(Addr);
@@ -221,14 +852,281 @@ pub struct DelegatedResponse {
     pub delegated: Addr,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct PayoutAddressResponse {
+    pub payout: Option<Addr>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct DistributedRewardsResponse {
     /// Total number of tokens sent to the contract over all time.
     pub distributed: Uint128,
     /// Total number of tokens available to be withdrawn.
     pub withdrawable: Uint128,
+    /// Total number of tokens sent to the distribution fee recipient, not part of `distributed`.
+    #[serde(default)]
+    pub fees: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct CanDistributeResponse {
+    /// False if there is no reward power staked, so there is noone to distribute to.
+    pub can_distribute: bool,
+    /// Total reward power of all members.
+    pub total_reward_power: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct DistributionWeightResponse {
+    pub weight: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RecomputeStatusResponse {
+    /// True until all members were recomputed under the current power config
+    pub pending: bool,
+    /// Last address recomputed by the pending recompute
+    pub last_processed: Option<Addr>,
+    /// Number of addresses recomputed by the pending recompute
+    pub processed: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct MigrationProgressResponse {
+    /// True until `ContinueMigration` back-filled the data of all stakers
+    pub in_progress: bool,
+    /// Storage layout version the data of this contract is complete for
+    pub storage_version: u32,
+    /// Storage layout version the migration in progress back-fills data for
+    pub target_version: Option<u32>,
+    /// Last address processed by the migration in progress
+    pub last_processed: Option<Addr>,
+    /// Number of addresses processed by the migration in progress
+    pub processed: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct FirstStakedResponse {
+    /// None if the address never staked
+    pub first_staked: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct HooksResponse {
+    /// Addresses of all registered hooks
+    pub hooks: Vec<String>,
+    /// Maximum number of hooks that can be registered
+    pub max_hooks: u32,
+    /// Configuration of each hook in `hooks`
+    pub hook_configs: Vec<HookConfigResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct HookConfigResponse {
+    pub addr: String,
+    /// Gas limit for each call of the hook, `None` if unlimited
+    pub gas_limit: Option<u64>,
+    /// Number of calls of the hook that failed
+    pub failures: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct GovernanceContractsResponse {
+    pub contracts: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct AdminMultisigResponse {
+    pub addresses: Vec<Addr>,
+    pub min_approvals: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct PowerExclusionsResponse {
+    pub addresses: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct DelegationReportResponse {
+    /// Balance of the staked token held by this contract, as reported by the token contract
+    pub token_balance_of_staking: Uint128,
+    pub total_staked: Uint128,
+    pub total_unbonding: Uint128,
+    /// Rewards distributed, but not yet withdrawn by the stakers
+    pub withdrawable_rewards_total: Uint128,
+    /// Tokens held beyond stake, unbonding claims and rewards owed to stakers, e.g. tokens sent
+    /// directly to this contract or rewards not distributed yet
+    pub surplus: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct DelegationInfo {
+    pub address: Addr,
+    /// Stake summed over all unbonding periods
+    pub stake: Uint128,
+    /// Label set by the admin, if any
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct AllDelegationsResponse {
+    pub delegations: Vec<DelegationInfo>,
+    /// Where to continue listing if there may be more stakers. Fully unbonded stakers are not
+    /// listed, but count towards the limit, so this can be set even if fewer delegations than
+    /// the limit were returned.
+    #[serde(default)]
+    pub next_start_after: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct StakingLockResponse {
+    pub locked: bool,
+    /// Height at which the lock expires, if it is active
+    pub unlocks_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct LoyaltyBonusResponse {
+    /// Full loyalty epochs staked without reducing stake
+    pub epochs: u64,
+    /// Extra voting power in percent, the bonus currently applied may be lower until the
+    /// member is recomputed
+    pub bonus_pct: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ScheduledRebond {
+    pub tokens: Uint128,
+    pub bond_from: u64,
+    pub bond_to: u64,
+    pub execute_after: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ScheduledRebondsResponse {
+    /// Pending rebonds, in the order they were scheduled
+    pub rebonds: Vec<ScheduledRebond>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct DistributionLogEntry {
+    /// Sequence number of the distribution, starting at 0
+    pub seq: u64,
+    /// Amount distributed to the stakers, after the distribution fee
+    pub amount: Uint128,
+    /// Address the distribution was attributed to
+    pub sender: Addr,
+    pub time: Timestamp,
+    pub height: u64,
+    /// Total reward power the amount was split between
+    pub total_reward_power: Uint128,
+    /// Total amount distributed by this contract, including this entry. If it differs from
+    /// the previous entry's total by more than `amount`, entries were pruned or rewards were
+    /// distributed without an entry, e.g. by a reward stream.
+    pub distributed_total: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct DistributionLogResponse {
+    pub entries: Vec<DistributionLogEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct AdminStatusResponse {
+    pub admin: Option<Addr>,
+    pub renounced: bool,
+    /// Time the admin was renounced at, if it was
+    pub renounced_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct LabelResponse {
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct AddressLabel {
+    pub address: Addr,
+    pub label: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct AllLabelsResponse {
+    pub labels: Vec<AddressLabel>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ClaimsFrozenResponse {
+    pub frozen: bool,
+}
+
+/// How the keys below a namespace are encoded
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyEncoding {
+    /// The value is stored directly under the namespace
+    Item,
+    /// Length prefixed namespace, followed by the address
+    Addr,
+    /// Length prefixed namespace, followed by a big endian `u64`
+    U64,
+    /// Length prefixed namespace and address, followed by a big endian `u64`
+    AddrU64,
+    /// Length prefixed namespace and big endian `u64`, followed by the address
+    U64Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct StorageLayout {
+    /// Name of the constant in the contract's `state` module
+    pub name: String,
+    pub namespace: String,
+    pub key: KeyEncoding,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RawKeyLayoutResponse {
+    pub layout: Vec<StorageLayout>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RewardsBreakdownEntry {
+    pub unbonding_period: u64,
+    pub stake: Uint128,
+    pub reward_multiplier: Decimal,
+    /// Unweighted reward power of this stake, not truncated
+    pub reward_power: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RewardsBreakdownResponse {
+    pub periods: Vec<RewardsBreakdownEntry>,
+    /// Distribution weight the summed up reward power is scaled by
+    pub weight: Decimal,
+    /// Sum of the recomputed reward powers, weighted and truncated
+    pub total: Uint128,
+    /// Reward power currently stored for the address, as returned by `QueryMsg::Rewards`
+    pub cached: Uint128,
+    /// True if `total` and `cached` disagree, e.g. because the address was not recomputed
+    /// after a config change yet
+    pub stale: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct MatureClaimsResponse {
+    pub claims: Vec<MatureClaim>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct MatureClaim {
+    pub address: Addr,
+    /// Sum of all claims of `address` maturing at `matured_at`
+    pub amount: Uint128,
+    pub matured_at: Timestamp,
 }
 
 pub type UndistributedRewardsResponse = WithdrawableRewardsResponse;
 pub type DistributionDataResponse = crate::state::Distribution;
+pub type RewardStreamResponse = Option<crate::state::RewardStream>;
 pub type WithdrawAdjustmentDataResponse = crate::state::WithdrawAdjustment;
@@ -1,4 +1,22 @@
+mod cancel_claim;
+mod claim_blocked;
+mod claim_recipient;
+mod claims_frozen;
+mod config;
 mod delegate;
+mod delegation_report;
 mod distribution;
+mod exit_vesting;
+mod hooks;
+mod labels;
+mod migration;
+mod power_config;
+mod power_exclusion;
+mod renounce_admin;
+mod reward_decay;
+mod scheduled_rebond;
+mod slash;
 mod staking_rewards;
 mod suite;
+mod voting_discount;
+mod withdraw_batch;
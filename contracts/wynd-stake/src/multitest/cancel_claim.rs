@@ -0,0 +1,106 @@
+use cosmwasm_std::{Decimal, Uint128};
+use cw_controllers::Claim;
+use cw_utils::Expiration;
+
+use super::suite::{SuiteBuilder, SEVEN_DAYS};
+use crate::ContractError;
+
+const FOURTEEN_DAYS: u64 = 2 * SEVEN_DAYS;
+
+#[test]
+fn partial_cancel_across_two_claims() {
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_min_bond(1_000)
+        .with_initial_balances(vec![(user, 10_000, None)])
+        .build();
+
+    suite.delegate(user, 10_000, None).unwrap();
+    suite.unbond(user, 2_000, None).unwrap();
+    suite.update_time(100);
+    suite.unbond(user, 3_000, None).unwrap();
+    let second_matures = suite.block_time().plus_seconds(SEVEN_DAYS);
+    assert_eq!(suite.query_total_unbonding().unwrap(), 5_000);
+
+    // the older claim is canceled completely, the newer one partially
+    suite.cancel_claim(user, 4_000, None).unwrap();
+    assert_eq!(
+        suite.query_claims(user).unwrap(),
+        vec![Claim {
+            amount: Uint128::new(1_000),
+            release_at: Expiration::AtTime(second_matures),
+        }]
+    );
+    assert_eq!(suite.query_staked(user, None).unwrap(), 9_000);
+    assert_eq!(suite.query_total_staked().unwrap(), 9_000);
+    assert_eq!(suite.query_total_unbonding().unwrap(), 1_000);
+
+    let err = suite.cancel_claim(user, 2_000, None).unwrap_err();
+    assert_eq!(
+        ContractError::InsufficientPendingClaims {
+            pending: Uint128::new(1_000),
+            requested: Uint128::new(2_000),
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn matured_claims_cannot_be_canceled() {
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_min_bond(1_000)
+        .with_initial_balances(vec![(user, 10_000, None)])
+        .build();
+
+    suite.delegate(user, 10_000, None).unwrap();
+    suite.unbond(user, 2_000, None).unwrap();
+    suite.update_time(SEVEN_DAYS);
+    suite.unbond(user, 1_000, None).unwrap();
+
+    // only the pending claim counts
+    let err = suite.cancel_claim(user, 2_000, None).unwrap_err();
+    assert_eq!(
+        ContractError::InsufficientPendingClaims {
+            pending: Uint128::new(1_000),
+            requested: Uint128::new(2_000),
+        },
+        err.downcast().unwrap()
+    );
+
+    suite.cancel_claim(user, 1_000, None).unwrap();
+    assert_eq!(suite.query_staked(user, None).unwrap(), 8_000);
+
+    // the matured claim is still there to be claimed
+    suite.claim(user).unwrap();
+    assert_eq!(suite.query_claims(user).unwrap(), vec![]);
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 2_000);
+    assert_eq!(suite.query_total_unbonding().unwrap(), 0);
+}
+
+#[test]
+fn canceling_restores_voting_power() {
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_min_bond(1_000)
+        .with_stake_config_voting(vec![
+            (SEVEN_DAYS, Decimal::one()),
+            (FOURTEEN_DAYS, Decimal::percent(200)),
+        ])
+        .with_initial_balances(vec![(user, 10_000, None)])
+        .build();
+
+    suite.delegate(user, 10_000, None).unwrap();
+    suite.unbond(user, 10_000, None).unwrap();
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 0);
+    assert_eq!(suite.query_total_power(None).unwrap(), 0);
+
+    // the tokens can be staked again in another period
+    suite.update_time(100);
+    suite.cancel_claim(user, 10_000, FOURTEEN_DAYS).unwrap();
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 20);
+    assert_eq!(suite.query_total_power(None).unwrap(), 20);
+    assert_eq!(suite.query_staked(user, SEVEN_DAYS).unwrap(), 0);
+    assert_eq!(suite.query_staked(user, FOURTEEN_DAYS).unwrap(), 10_000);
+    assert_eq!(suite.query_claims(user).unwrap(), vec![]);
+}
@@ -0,0 +1,61 @@
+use cosmwasm_std::Uint128;
+use wynd_utils::Curve;
+
+use super::suite::{SuiteBuilder, SEVEN_DAYS};
+use crate::ContractError;
+
+const YEAR: u64 = 365 * 24 * 60 * 60;
+
+#[test]
+fn claim_fails_early_if_token_side_is_blocked() {
+    let user = "user";
+    let vester = "admin";
+    let distributor = "distributor";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![
+            (user, 10_000, None),
+            (vester, 4_000, None),
+            (distributor, 6_000, None),
+        ])
+        .build();
+
+    suite.delegate(user, 10_000, SEVEN_DAYS).unwrap();
+
+    // vesting tokens sent to the stake contract are picked up as rewards, but remain locked there
+    let now = suite.block_time().seconds();
+    let schedule = Curve::saturating_linear((now + YEAR, 4_000), (now + 2 * YEAR, 0));
+    suite
+        .transfer_vesting(vester, &suite.stake_contract(), 4_000u128, schedule)
+        .unwrap();
+    suite.distribute_funds(distributor, None, 2_000).unwrap();
+    suite.unbond(user, 10_000, SEVEN_DAYS).unwrap();
+
+    // withdrawing them leaves less than the claimed amount movable on the token
+    suite.withdraw_funds(user, None, "rewards").unwrap();
+    let available = suite.query_undelegatable_amount().unwrap();
+    assert_eq!(
+        available,
+        suite.query_balance_staking_contract().unwrap() - 4_000
+    );
+    assert!(available < 10_000);
+
+    suite.update_time(SEVEN_DAYS);
+    let err = suite.claim(user).unwrap_err();
+    assert_eq!(
+        ContractError::TokenSideBlocked {
+            available: Uint128::new(available),
+            requested: Uint128::new(10_000),
+        },
+        err.downcast().unwrap()
+    );
+    assert_eq!(suite.query_claims(user).unwrap()[0].amount.u128(), 10_000);
+
+    // once the stake contract holds enough unlocked tokens again, the claim goes through
+    suite
+        .transfer(distributor, &suite.stake_contract(), 4_000u128)
+        .unwrap();
+    assert!(suite.query_undelegatable_amount().unwrap() >= 10_000);
+    suite.claim(user).unwrap();
+    assert_eq!(suite.query_claims(user).unwrap(), []);
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 10_000);
+}
@@ -0,0 +1,141 @@
+use cosmwasm_std::{Decimal, Uint128};
+use cw20_vesting::ContractError as VestingContractError;
+use wynd_utils::{Curve, ScalableCurve};
+
+use super::suite::{SuiteBuilder, SEVEN_DAYS};
+use crate::ContractError;
+
+const FOURTEEN_DAYS: u64 = 2 * SEVEN_DAYS;
+const START: u64 = 1671797419; // way after env's timestamp at start to keep tokens vesting
+const END: u64 = START + 10_000;
+
+#[test]
+fn treasury_unbonds_to_distributor() {
+    let treasury = "treasury";
+    let distributor = "distributor";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(treasury, 10_000, None)])
+        .with_stake_config_voting(vec![(SEVEN_DAYS, Decimal::one())])
+        .build();
+
+    suite.delegate(treasury, 10_000, SEVEN_DAYS).unwrap();
+    suite.advance_blocks(1);
+    assert_eq!(suite.query_voting_power(treasury, None).unwrap(), 10);
+
+    // the treasury loses stake and power right away
+    suite
+        .unbond_to(treasury, 4_000, SEVEN_DAYS, distributor)
+        .unwrap();
+    suite.advance_blocks(1);
+    assert_eq!(suite.query_staked(treasury, SEVEN_DAYS).unwrap(), 6_000);
+    assert_eq!(suite.query_voting_power(treasury, None).unwrap(), 6);
+    assert_eq!(suite.query_voting_power(distributor, None).unwrap(), 0);
+
+    // the claim is listed for the distributor
+    assert_eq!(suite.query_claims(treasury).unwrap(), []);
+    let claims = suite.query_claims(distributor).unwrap();
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0].amount, Uint128::new(4_000));
+
+    // the treasury cannot claim it, the distributor can
+    suite.update_time(SEVEN_DAYS);
+    suite.claim(treasury).unwrap_err();
+    suite.claim(distributor).unwrap();
+    assert_eq!(suite.query_claims(distributor).unwrap(), []);
+    assert_eq!(
+        suite.query_balance_vesting_contract(distributor).unwrap(),
+        4_000
+    );
+    assert_eq!(suite.query_balance_vesting_contract(treasury).unwrap(), 0);
+
+    // the undelegated tokens are taken from the treasury's delegation
+    assert_eq!(
+        suite.query_delegated_vesting_contract(treasury).unwrap(),
+        6_000
+    );
+    assert_eq!(
+        suite.query_delegated_vesting_contract(distributor).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn claim_recipient_not_allowed_with_exit_vesting() {
+    let treasury = "treasury";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(treasury, 10_000, None)])
+        .with_stake_config(vec![
+            (SEVEN_DAYS, Decimal::one(), Decimal::one()),
+            (FOURTEEN_DAYS, Decimal::one(), Decimal::one()),
+        ])
+        .with_exit_vesting(
+            FOURTEEN_DAYS,
+            ScalableCurve::linear((0, 100), (SEVEN_DAYS, 0)),
+        )
+        .build();
+
+    suite.delegate(treasury, 5_000, FOURTEEN_DAYS).unwrap();
+    let err = suite
+        .unbond_to(treasury, 5_000, FOURTEEN_DAYS, "distributor")
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ExitVestingClaimRecipient(FOURTEEN_DAYS),
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn vesting_tokens_are_not_claimed_by_someone_else() {
+    let treasury = "treasury";
+    let distributor = "distributor";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(
+            treasury,
+            10_000,
+            Curve::saturating_linear((START, 6_000), (END, 0)),
+        )])
+        .with_stake_config_voting(vec![(SEVEN_DAYS, Decimal::one())])
+        .build();
+    suite.delegate(treasury, 10_000, SEVEN_DAYS).unwrap();
+
+    // the liquid part can go to the distributor, the rest still covers the vesting tokens
+    suite
+        .unbond_to(treasury, 4_000, SEVEN_DAYS, distributor)
+        .unwrap();
+    suite.update_time(SEVEN_DAYS);
+    suite.claim(distributor).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(distributor).unwrap(),
+        4_000
+    );
+
+    // vesting tokens would become liquid for the distributor
+    suite
+        .unbond_to(treasury, 2_000, SEVEN_DAYS, distributor)
+        .unwrap();
+    suite.update_time(SEVEN_DAYS);
+    let err = suite.claim(distributor).unwrap_err();
+    assert_eq!(
+        VestingContractError::CantMoveVestingTokens {
+            effectively_locked: Uint128::new(2_000)
+        },
+        err.downcast().unwrap()
+    );
+    assert_eq!(
+        suite.query_balance_vesting_contract(distributor).unwrap(),
+        4_000
+    );
+    assert_eq!(
+        suite.query_delegated_vesting_contract(treasury).unwrap(),
+        6_000
+    );
+
+    // the treasury can still unbond them to itself, where they stay locked
+    suite.unbond(treasury, 4_000, SEVEN_DAYS).unwrap();
+    suite.update_time(SEVEN_DAYS);
+    suite.claim(treasury).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(treasury).unwrap(),
+        4_000
+    );
+}
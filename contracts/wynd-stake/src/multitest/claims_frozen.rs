@@ -0,0 +1,41 @@
+use cosmwasm_std::Uint128;
+
+use super::suite::{SuiteBuilder, SEVEN_DAYS};
+use crate::ContractError;
+
+#[test]
+fn frozen_claims_cannot_be_released() {
+    let user = "user";
+    let admin = "admin";
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_initial_balances(vec![(user, 10_000, None)])
+        .build();
+
+    suite.delegate(user, 10_000, SEVEN_DAYS).unwrap();
+    suite.unbond(user, 4_000, SEVEN_DAYS).unwrap();
+    let claims = suite.query_claims(user).unwrap();
+
+    // only the admin can freeze claims
+    suite.freeze_claims(user, user).unwrap_err();
+    let res = suite.freeze_claims(admin, user).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "wasm-freeze_claims"));
+    assert!(suite.query_claims_frozen(user).unwrap());
+
+    // the matured claim cannot be released, but is kept as it is
+    suite.update_time(SEVEN_DAYS);
+    let err = suite.claim(user).unwrap_err();
+    assert_eq!(ContractError::ClaimsFrozen {}, err.downcast().unwrap());
+    assert_eq!(suite.query_claims(user).unwrap(), claims);
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 0);
+
+    suite.unfreeze_claims(user, user).unwrap_err();
+    let res = suite.unfreeze_claims(admin, user).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "wasm-unfreeze_claims"));
+    assert!(!suite.query_claims_frozen(user).unwrap());
+
+    suite.claim(user).unwrap();
+    assert_eq!(suite.query_claims(user).unwrap(), []);
+    assert_eq!(claims[0].amount, Uint128::new(4_000));
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 4_000);
+}
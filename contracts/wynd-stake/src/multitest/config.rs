@@ -0,0 +1,60 @@
+use cosmwasm_std::{Decimal, Uint128};
+
+use super::suite::SuiteBuilder;
+
+#[test]
+fn config_matches_individual_queries() {
+    let admin = "admin";
+    let members = ["member1", "member2"];
+    let short_period = 1000u64;
+    let long_period = 4000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_min_bond(500)
+        .with_stake_config(vec![
+            (short_period, Decimal::one(), Decimal::one()),
+            (long_period, Decimal::percent(200), Decimal::percent(150)),
+        ])
+        .with_initial_balances(vec![
+            (members[0], 3_000u128, None),
+            (members[1], 2_000u128, None),
+        ])
+        .build();
+
+    suite.delegate(members[0], 3_000u128, short_period).unwrap();
+    suite.delegate(members[1], 2_000u128, long_period).unwrap();
+    suite.unbond(members[0], 1_000u128, short_period).unwrap();
+
+    let config = suite.query_config().unwrap();
+
+    assert_eq!(config.cw20_contract, suite.query_token_contract().unwrap());
+    assert_eq!(
+        config.admin.as_ref().map(|a| a.to_string()),
+        suite.query_admin().unwrap()
+    );
+    assert_eq!(config.admin.unwrap().as_str(), admin);
+    assert_eq!(config.tokens_per_power, Uint128::new(1000));
+    assert_eq!(config.min_bond, Uint128::new(500));
+    assert_eq!(config.bonding, suite.query_staked_periods().unwrap());
+    assert_eq!(
+        config
+            .bonding
+            .iter()
+            .map(|b| (b.unbonding_period, b.total_staked.u128()))
+            .collect::<Vec<_>>(),
+        vec![(short_period, 2_000), (long_period, 2_000)]
+    );
+    assert_eq!(
+        config.total_staked.u128(),
+        suite.query_total_staked().unwrap()
+    );
+    assert_eq!(config.total_staked, Uint128::new(4_000));
+    assert_eq!(
+        config.total_unbonding.u128(),
+        suite.query_total_unbonding().unwrap()
+    );
+    assert_eq!(config.total_unbonding, Uint128::new(1_000));
+    assert_eq!(config.version, suite.query_info().unwrap().info);
+    assert_eq!(config.version.version, env!("CARGO_PKG_VERSION"));
+}
@@ -72,7 +72,9 @@ fn delegate_and_unbond_tokens_still_vested() {
     // user still cannot transfer any token, because it's all vested
     let err = suite.transfer(user, "random_user", 1u128).unwrap_err();
     assert_eq!(
-        VestingContractError::CantMoveVestingTokens,
+        VestingContractError::CantMoveVestingTokens {
+            effectively_locked: Uint128::new(100_000)
+        },
         err.downcast().unwrap()
     );
 }
@@ -139,7 +141,9 @@ fn mixed_vested_liquid_delegate_and_transfer_remaining() {
     suite.transfer(user, "random_user", 10_000u128).unwrap(); // works
     let err = suite.transfer(user, "random_user", 1u128).unwrap_err(); // this is vested amount
     assert_eq!(
-        VestingContractError::CantMoveVestingTokens,
+        VestingContractError::CantMoveVestingTokens {
+            effectively_locked: Uint128::new(10_000)
+        },
         err.downcast().unwrap()
     );
 }
@@ -0,0 +1,144 @@
+use cosmwasm_std::{Addr, Decimal, Uint128};
+
+use super::suite::SuiteBuilder;
+use crate::msg::{DelegationInfo, DelegationReportResponse, PeriodStaked};
+
+#[test]
+fn report_accounts_for_stake_unbonding_and_rewards() {
+    let members = ["member1", "member2", "member3"];
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![
+            (members[0], 3_000u128, None),
+            (members[1], 1_000u128, None),
+            (members[2], 1_400u128, None),
+        ])
+        .build();
+
+    suite
+        .delegate(members[0], 3_000u128, unbonding_period)
+        .unwrap();
+    suite
+        .delegate(members[1], 1_000u128, unbonding_period)
+        .unwrap();
+    suite
+        .unbond(members[0], 1_000u128, unbonding_period)
+        .unwrap();
+    // rewards are paid in the staked token, so they are held by the staking contract as well
+    suite.distribute_funds(members[2], None, 400).unwrap();
+
+    assert_eq!(
+        suite.query_delegation_report().unwrap(),
+        DelegationReportResponse {
+            token_balance_of_staking: Uint128::new(4_400),
+            total_staked: Uint128::new(3_000),
+            total_unbonding: Uint128::new(1_000),
+            withdrawable_rewards_total: Uint128::new(400),
+            surplus: Uint128::zero(),
+        }
+    );
+
+    assert_eq!(
+        suite.query_all_delegations(None, None).unwrap(),
+        vec![
+            DelegationInfo {
+                address: Addr::unchecked(members[0]),
+                stake: Uint128::new(2_000),
+                label: None,
+            },
+            DelegationInfo {
+                address: Addr::unchecked(members[1]),
+                stake: Uint128::new(1_000),
+                label: None,
+            },
+        ]
+    );
+    assert_eq!(
+        suite.query_all_delegations(Some(members[0]), 1u32).unwrap(),
+        vec![DelegationInfo {
+            address: Addr::unchecked(members[1]),
+            stake: Uint128::new(1_000),
+            label: None,
+        }]
+    );
+}
+
+#[test]
+fn tokens_sent_directly_show_up_as_surplus() {
+    let member = "member";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![(member, 5_000u128, None)])
+        .build();
+
+    suite.delegate(member, 3_000u128, unbonding_period).unwrap();
+    assert_eq!(
+        suite.query_delegation_report().unwrap().surplus,
+        Uint128::zero()
+    );
+
+    // tokens sent without delegating are not accounted for
+    let staking = suite.stake_contract();
+    suite.transfer(member, &staking, 1_500u128).unwrap();
+
+    let report = suite.query_delegation_report().unwrap();
+    assert_eq!(report.token_balance_of_staking, Uint128::new(4_500));
+    assert_eq!(report.total_staked, Uint128::new(3_000));
+    assert_eq!(report.surplus, Uint128::new(1_500));
+
+    // withdrawing the stake keeps the drift visible
+    suite.unbond(member, 3_000u128, unbonding_period).unwrap();
+    suite.update_time(unbonding_period);
+    suite.claim(member).unwrap();
+    let report = suite.query_delegation_report().unwrap();
+    assert_eq!(report.token_balance_of_staking, Uint128::new(1_500));
+    assert_eq!(report.total_unbonding, Uint128::zero());
+    assert_eq!(report.surplus, Uint128::new(1_500));
+}
+
+#[test]
+fn total_staked_is_broken_down_by_period() {
+    let members = ["member1", "member2"];
+    let short = 1000u64;
+    let long = 2000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(short, Decimal::one()), (long, Decimal::percent(200))])
+        .with_initial_balances(vec![
+            (members[0], 3_000u128, None),
+            (members[1], 2_000u128, None),
+        ])
+        .build();
+
+    suite.delegate(members[0], 3_000u128, short).unwrap();
+    suite.delegate(members[1], 2_000u128, long).unwrap();
+    suite.unbond(members[0], 1_000u128, short).unwrap();
+    suite.rebond(members[0], 500u128, short, long).unwrap();
+
+    let total = suite.query_total_staked_by_period().unwrap();
+    assert_eq!(total.total_staked, Uint128::new(4_000));
+    assert_eq!(
+        total.by_period,
+        [
+            PeriodStaked {
+                unbonding_period: short,
+                staked: Uint128::new(1_500),
+            },
+            PeriodStaked {
+                unbonding_period: long,
+                staked: Uint128::new(2_500),
+            },
+        ]
+    );
+    assert!(total.consistent);
+
+    suite.unbond(members[1], 2_000u128, long).unwrap();
+    let total = suite.query_total_staked_by_period().unwrap();
+    assert_eq!(total.total_staked, Uint128::new(2_000));
+    assert_eq!(total.by_period[1].staked, Uint128::new(500));
+    assert!(total.consistent);
+}
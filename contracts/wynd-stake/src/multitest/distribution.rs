@@ -1,6 +1,10 @@
-use cosmwasm_std::Decimal;
+use cosmwasm_std::{to_binary, Addr, Decimal, Event, StdError, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_controllers::AdminError;
 
 use super::suite::SuiteBuilder;
+use crate::msg::DistributionLogEntry;
+use crate::state::DISTRIBUTION_LOG_SIZE;
 use crate::ContractError;
 
 #[test]
@@ -619,6 +623,43 @@ fn redirecting_withdrawn_funds() {
     );
 }
 
+#[test]
+fn withdrawn_funds_sent_with_message() {
+    let member = "member";
+    let funder = "funder";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![(member, 1_000u128, None), (funder, 100u128, None)])
+        .build();
+    let receiver = suite.instantiate_receiver();
+
+    suite.delegate(member, 1_000, unbonding_period).unwrap();
+    suite.distribute_funds(funder, None, 100).unwrap();
+
+    let payload = to_binary(&"zap").unwrap();
+    suite
+        .withdraw_funds_with_msg(member, &receiver, payload.clone())
+        .unwrap();
+
+    // the receiver got the rewards along with the hook, sent by the staking contract
+    assert_eq!(
+        suite.query_received(&receiver).unwrap(),
+        [Cw20ReceiveMsg {
+            sender: suite.stake_contract().to_string(),
+            amount: Uint128::new(100),
+            msg: payload,
+        }]
+    );
+    assert_eq!(
+        suite.query_balance_vesting_contract(&receiver).unwrap(),
+        100
+    );
+    assert_eq!(suite.withdrawable_rewards(member).unwrap(), 0);
+    assert_eq!(suite.query_balance_staking_contract().unwrap(), 1_000);
+}
+
 #[test]
 fn cannot_withdraw_others_funds() {
     let members = vec![
@@ -748,6 +789,81 @@ fn funds_withdrawal_delegation() {
     );
 }
 
+#[test]
+fn payout_address() {
+    let members = ["member1", "member2", "member3"];
+    let cold_wallet = "cold_wallet";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_min_bond(1000)
+        .with_initial_balances(vec![
+            (members[0], 4_000u128, None),
+            (members[1], 6_000u128, None),
+            (members[2], 300u128, None),
+        ])
+        .build();
+    suite
+        .delegate(members[0], 4_000u128, unbonding_period)
+        .unwrap();
+    suite
+        .delegate(members[1], 6_000u128, unbonding_period)
+        .unwrap();
+
+    assert_eq!(suite.payout_address(members[1]).unwrap(), None);
+    suite
+        .set_payout_address(members[1], Some(cold_wallet))
+        .unwrap();
+    assert_eq!(
+        suite.payout_address(members[1]).unwrap(),
+        Some(Addr::unchecked(cold_wallet))
+    );
+
+    // rewards withdrawn by the withdrawal delegate still go to the payout address
+    suite.distribute_funds(members[2], None, 100).unwrap();
+    suite.delegate_withdrawal(members[1], members[0]).unwrap();
+    suite.withdraw_funds(members[0], members[1], None).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(cold_wallet).unwrap(),
+        60
+    );
+    assert_eq!(suite.query_balance_vesting_contract(members[0]).unwrap(), 0);
+
+    // any other receiver is rejected, the payout address itself is fine
+    suite.distribute_funds(members[2], None, 100).unwrap();
+    let err = suite
+        .withdraw_funds(members[1], None, members[2])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ConflictingReceiver {
+            owner: members[1].to_owned(),
+            payout: cold_wallet.to_owned(),
+            receiver: members[2].to_owned(),
+        },
+        err.downcast().unwrap()
+    );
+    suite.withdraw_funds(members[1], None, cold_wallet).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(cold_wallet).unwrap(),
+        120
+    );
+
+    // clearing it restores sending rewards to the receiver
+    suite.set_payout_address(members[1], None).unwrap();
+    assert_eq!(suite.payout_address(members[1]).unwrap(), None);
+    suite.distribute_funds(members[2], None, 100).unwrap();
+    suite.withdraw_funds(members[1], None, None).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(members[1]).unwrap(),
+        60
+    );
+    assert_eq!(
+        suite.query_balance_vesting_contract(cold_wallet).unwrap(),
+        120
+    );
+}
+
 #[test]
 fn querying_unknown_address() {
     let suite = SuiteBuilder::new().build();
@@ -849,3 +965,684 @@ fn rebond_works() {
         "member1 should have received 300 * 2 / 3 = 200"
     );
 }
+
+#[test]
+fn migrate_reward_token() {
+    let members = vec![
+        "member1".to_owned(),
+        "member2".to_owned(),
+        "member3".to_owned(),
+    ];
+    let unbonding_period = 1000u64;
+    let admin = "admin";
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![
+            (&members[0], 5_000u128, None),
+            (&members[1], 15_000u128, None),
+            (&members[2], 400u128, None),
+        ])
+        .build();
+
+    suite
+        .delegate(&members[0], 5_000u128, unbonding_period)
+        .unwrap();
+    suite
+        .delegate(&members[1], 15_000u128, unbonding_period)
+        .unwrap();
+
+    let new_token = suite.instantiate_token(vec![(admin, 2_000u128)]);
+
+    // nothing to migrate before anything was distributed
+    let err = suite
+        .migrate_reward_token(admin, &new_token, Decimal::percent(200))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NoRewardsToMigrate {},
+        err.downcast().unwrap()
+    );
+
+    suite.distribute_funds(&members[2], None, 400).unwrap();
+    assert_eq!(suite.withdrawable_rewards(&members[0]).unwrap(), 100);
+    assert_eq!(suite.withdrawable_rewards(&members[1]).unwrap(), 300);
+
+    // only admin can migrate
+    let err = suite
+        .migrate_reward_token(&members[0], &new_token, Decimal::percent(200))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Admin(AdminError::NotAdmin {}),
+        err.downcast().unwrap()
+    );
+
+    // new tokens have to be transferred first
+    let err = suite
+        .migrate_reward_token(admin, &new_token, Decimal::percent(200))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InsufficientRewardTokens {
+            required: Uint128::new(800),
+            available: Uint128::zero(),
+        },
+        err.downcast().unwrap()
+    );
+
+    let stake_contract = suite.stake_contract();
+    suite
+        .transfer_token(&new_token, admin, &stake_contract, 800u128)
+        .unwrap();
+    suite
+        .migrate_reward_token(admin, &new_token, Decimal::percent(200))
+        .unwrap();
+
+    // old rewards are sent back to admin, stake is untouched
+    assert_eq!(suite.query_balance_vesting_contract(admin).unwrap(), 400);
+    assert_eq!(suite.query_balance_staking_contract().unwrap(), 20_000);
+
+    // unclaimed rewards are converted
+    assert_eq!(suite.withdrawable_rewards(&members[0]).unwrap(), 200);
+    assert_eq!(suite.withdrawable_rewards(&members[1]).unwrap(), 600);
+    assert_eq!(suite.withdrawable_funds().unwrap(), 800);
+    assert_eq!(suite.undistributed_funds().unwrap(), 0);
+
+    suite.withdraw_funds(&members[0], None, None).unwrap();
+    assert_eq!(
+        suite.query_token_balance(&new_token, &members[0]).unwrap(),
+        200
+    );
+    assert_eq!(suite.withdrawable_rewards(&members[0]).unwrap(), 0);
+
+    // further rewards are distributed in the new token
+    suite
+        .distribute_token_funds(&new_token, admin, 400)
+        .unwrap();
+    assert_eq!(suite.withdrawable_rewards(&members[0]).unwrap(), 100);
+    assert_eq!(suite.withdrawable_rewards(&members[1]).unwrap(), 900);
+
+    suite.withdraw_funds(&members[1], None, None).unwrap();
+    assert_eq!(
+        suite.query_token_balance(&new_token, &members[1]).unwrap(),
+        900
+    );
+    assert_eq!(
+        suite.query_balance_vesting_contract(&members[1]).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn migrate_reward_token_in_pages() {
+    let members: Vec<String> = (0..35).map(|i| format!("member{:02}", i)).collect();
+    let unbonding_period = 1000u64;
+    let admin = "admin";
+    let funder = "funder";
+
+    let mut balances: Vec<_> = members
+        .iter()
+        .map(|m| (m.as_str(), 1_000u128, None))
+        .collect();
+    balances.push((funder, 3_500u128, None));
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(balances)
+        .build();
+    for member in &members {
+        suite.delegate(member, 1_000u128, unbonding_period).unwrap();
+    }
+    suite.distribute_funds(funder, None, 3_500).unwrap();
+    assert_eq!(suite.withdrawable_rewards(&members[0]).unwrap(), 100);
+
+    let new_token = suite.instantiate_token(vec![(admin, 7_000u128)]);
+    let stake_contract = suite.stake_contract();
+    suite
+        .transfer_token(&new_token, admin, &stake_contract, 7_000u128)
+        .unwrap();
+
+    // only the first 30 accounts are converted at once
+    let resp = suite
+        .migrate_reward_token(admin, &new_token, Decimal::percent(200))
+        .unwrap();
+    let wasm = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "finished" && attr.value == "false"));
+
+    // everything else waits for the migration to finish
+    let err = suite.withdraw_funds(&members[0], None, None).unwrap_err();
+    assert_eq!(
+        ContractError::RewardMigrationInProgress {},
+        err.downcast().unwrap()
+    );
+    let err = suite
+        .continue_reward_migration(funder, members[0].as_str(), None)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidRewardMigrationStart {
+            expected: Some(members[29].clone()),
+        },
+        err.downcast().unwrap()
+    );
+
+    let resp = suite
+        .continue_reward_migration(funder, members[29].as_str(), None)
+        .unwrap();
+    let wasm = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "finished" && attr.value == "true"));
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "migrated" && attr.value == "1000"));
+    let err = suite
+        .continue_reward_migration(funder, None, None)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NoRewardMigrationInProgress {},
+        err.downcast().unwrap()
+    );
+
+    // all unclaimed rewards are converted, the old ones are returned to the admin
+    assert_eq!(suite.query_balance_vesting_contract(admin).unwrap(), 3_500);
+    assert_eq!(suite.withdrawable_rewards(&members[0]).unwrap(), 200);
+    assert_eq!(suite.withdrawable_rewards(&members[34]).unwrap(), 200);
+    assert_eq!(suite.withdrawable_funds().unwrap(), 7_000);
+
+    suite.withdraw_funds(&members[34], None, None).unwrap();
+    assert_eq!(
+        suite.query_token_balance(&new_token, &members[34]).unwrap(),
+        200
+    );
+}
+
+#[test]
+fn migrate_reward_token_overflow() {
+    let member = "member";
+    let funder = "funder";
+    let admin = "admin";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![
+            (member, 1_000u128, None),
+            (funder, 1_000_000_000u128, None),
+        ])
+        .build();
+    suite.delegate(member, 1_000u128, unbonding_period).unwrap();
+    suite.distribute_funds(funder, None, 1_000_000_000).unwrap();
+
+    // the converted rewards do not fit into a correction anymore
+    let exchange_rate = Decimal::from_ratio(1u128 << 68, 1u128);
+    let required = 1_000_000_000u128 << 68;
+    let new_token = suite.instantiate_token(vec![(admin, required)]);
+    let stake_contract = suite.stake_contract();
+    suite
+        .transfer_token(&new_token, admin, &stake_contract, required)
+        .unwrap();
+    let err = suite
+        .migrate_reward_token(admin, &new_token, exchange_rate)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast().unwrap(),
+        ContractError::Std(StdError::Overflow { .. })
+    ));
+}
+
+#[test]
+fn distribution_rejected_without_stakers() {
+    let member = "member";
+    let funder = "funder";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![(member, 1_000u128, None), (funder, 500u128, None)])
+        .build();
+
+    let status = suite.can_distribute().unwrap();
+    assert!(!status.can_distribute);
+    assert_eq!(status.total_reward_power, Uint128::zero());
+
+    // rewards transferred while noone is staking are not absorbed into the distribution
+    let err = suite.distribute_funds(funder, None, 400).unwrap_err();
+    assert_eq!(
+        ContractError::NoMembersToDistributeTo {},
+        err.downcast().unwrap()
+    );
+    assert_eq!(suite.distributed_funds().unwrap(), 0);
+    assert_eq!(suite.withdrawable_funds().unwrap(), 0);
+    assert_eq!(suite.undistributed_funds().unwrap(), 400);
+
+    // once someone stakes, the pending rewards are distributed with the next call
+    suite.delegate(member, 1_000u128, unbonding_period).unwrap();
+    let status = suite.can_distribute().unwrap();
+    assert!(status.can_distribute);
+    assert!(!status.total_reward_power.is_zero());
+
+    suite.distribute_funds(funder, None, 100).unwrap();
+    assert_eq!(suite.distributed_funds().unwrap(), 500);
+    assert_eq!(suite.withdrawable_rewards(member).unwrap(), 500);
+}
+
+#[test]
+fn reward_stream() {
+    let members = vec!["member1", "member2"];
+    let admin = "admin";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![
+            (members[0], 1_000u128, None),
+            (members[1], 3_000u128, None),
+            (admin, 2_000u128, None),
+        ])
+        .build();
+
+    suite
+        .delegate(members[0], 1_000u128, unbonding_period)
+        .unwrap();
+
+    // only admin can start a stream
+    let err = suite
+        .start_reward_stream(members[0], 1_000, 10)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Admin(AdminError::NotAdmin {}),
+        err.downcast().unwrap()
+    );
+
+    // rewards have to be transferred first
+    let err = suite.start_reward_stream(admin, 1_000, 10).unwrap_err();
+    assert_eq!(
+        ContractError::InsufficientStreamFunds {
+            required: Uint128::new(1_000),
+            available: Uint128::zero(),
+        },
+        err.downcast().unwrap()
+    );
+
+    let stake_contract = suite.stake_contract();
+    suite.transfer(admin, &stake_contract, 1_000u128).unwrap();
+    let err = suite.start_reward_stream(admin, 1_000, 0).unwrap_err();
+    assert_eq!(
+        ContractError::InvalidStreamDuration {},
+        err.downcast().unwrap()
+    );
+    suite.start_reward_stream(admin, 1_000, 10).unwrap();
+    assert_eq!(suite.undistributed_funds().unwrap(), 0);
+
+    let err = suite.start_reward_stream(admin, 1_000, 10).unwrap_err();
+    assert_eq!(
+        ContractError::RewardStreamActive {},
+        err.downcast().unwrap()
+    );
+
+    // streamed rewards are not distributed at once, only newly transferred ones
+    suite.distribute_funds(admin, None, 100).unwrap();
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 100);
+
+    // after 4 of 10 blocks, 400 tokens were released to the only staker
+    suite.advance_blocks(4);
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 500);
+    assert_eq!(suite.distributed_funds().unwrap(), 500);
+
+    // another member joins with triple the stake, rewards released so far are unaffected
+    suite
+        .delegate(members[1], 3_000u128, unbonding_period)
+        .unwrap();
+    suite.advance_blocks(4);
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 600);
+    assert_eq!(suite.withdrawable_rewards(members[1]).unwrap(), 300);
+
+    suite.withdraw_funds(members[0], None, None).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(members[0]).unwrap(),
+        600
+    );
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 0);
+
+    // nothing more is released after the stream ends
+    suite.advance_blocks(10);
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 50);
+    assert_eq!(suite.withdrawable_rewards(members[1]).unwrap(), 450);
+    assert_eq!(suite.distributed_funds().unwrap(), 1_100);
+    assert_eq!(suite.undistributed_funds().unwrap(), 0);
+
+    suite.withdraw_funds(members[1], None, None).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(members[1]).unwrap(),
+        450
+    );
+
+    // finished stream allows starting a new one
+    suite.transfer(admin, &stake_contract, 400u128).unwrap();
+    suite.start_reward_stream(admin, 400, 4).unwrap();
+}
+
+#[test]
+fn distribution_fee_sent_to_recipient() {
+    let members = ["member1", "member2", "member3"];
+    let community_pool = "community_pool";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_distribution_fee(1000, community_pool)
+        .with_initial_balances(vec![
+            (members[0], 10_000u128, None),
+            (members[1], 30_000u128, None),
+            (members[2], 1_000u128, None),
+        ])
+        .build();
+
+    suite
+        .delegate(members[0], 10_000, unbonding_period)
+        .unwrap();
+    suite
+        .delegate(members[1], 30_000, unbonding_period)
+        .unwrap();
+
+    // 10% of the distribution goes to the community pool
+    let resp = suite.distribute_funds(members[2], None, 1_000).unwrap();
+    let wasm = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "fee" && attr.value == "100"));
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "amount" && attr.value == "900"));
+
+    assert_eq!(
+        suite
+            .query_balance_vesting_contract(community_pool)
+            .unwrap(),
+        100
+    );
+    assert_eq!(suite.distributed_funds().unwrap(), 900);
+    assert_eq!(suite.undistributed_funds().unwrap(), 0);
+    assert_eq!(suite.withdrawable_funds().unwrap(), 900);
+
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 225);
+    assert_eq!(suite.withdrawable_rewards(members[1]).unwrap(), 675);
+    suite.withdraw_funds(members[0], None, None).unwrap();
+    suite.withdraw_funds(members[1], None, None).unwrap();
+
+    // stakers and the fee recipient got exactly the distributed amount
+    let withdrawn = suite.query_balance_vesting_contract(members[0]).unwrap()
+        + suite.query_balance_vesting_contract(members[1]).unwrap();
+    assert_eq!(
+        withdrawn
+            + suite
+                .query_balance_vesting_contract(community_pool)
+                .unwrap(),
+        1_000
+    );
+}
+
+#[test]
+fn update_distribution_fee() {
+    let members = ["member1", "member2"];
+    let admin = "admin";
+    let community_pool = "community_pool";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![
+            (members[0], 10_000u128, None),
+            (members[1], 2_000u128, None),
+        ])
+        .build();
+    suite
+        .delegate(members[0], 10_000, unbonding_period)
+        .unwrap();
+
+    // only admin can set the fee, and it is capped
+    let err = suite
+        .update_distribution_fee(members[0], Some((500, community_pool)))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Admin(AdminError::NotAdmin {}),
+        err.downcast().unwrap()
+    );
+    let err = suite
+        .update_distribution_fee(admin, Some((2001, community_pool)))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::DistributionFeeTooHigh { max: 2000 },
+        err.downcast().unwrap()
+    );
+
+    suite
+        .update_distribution_fee(admin, Some((2000, community_pool)))
+        .unwrap();
+    suite.distribute_funds(members[1], None, 1_000).unwrap();
+    assert_eq!(
+        suite
+            .query_balance_vesting_contract(community_pool)
+            .unwrap(),
+        200
+    );
+    assert_eq!(suite.distributed_funds().unwrap(), 800);
+
+    // without fee everything goes to the stakers again
+    suite.update_distribution_fee(admin, None).unwrap();
+    suite.distribute_funds(members[1], None, 1_000).unwrap();
+    assert_eq!(
+        suite
+            .query_balance_vesting_contract(community_pool)
+            .unwrap(),
+        200
+    );
+    assert_eq!(suite.distributed_funds().unwrap(), 1_800);
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 1_800);
+}
+
+#[test]
+fn batch_distribution_of_several_sources() {
+    let members = ["member1", "member2"];
+    let sources = ["source1", "source2"];
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![
+            (members[0], 1_000u128, None),
+            (members[1], 3_000u128, None),
+            (sources[0], 300u128, None),
+            (sources[1], 500u128, None),
+        ])
+        .build();
+
+    suite.delegate(members[0], 1_000, unbonding_period).unwrap();
+    suite.delegate(members[1], 3_000, unbonding_period).unwrap();
+
+    let stake_contract = suite.stake_contract();
+    suite
+        .transfer(sources[0], &stake_contract, 300u128)
+        .unwrap();
+    suite
+        .transfer(sources[1], &stake_contract, 500u128)
+        .unwrap();
+
+    // cannot distribute more than was received
+    let err = suite
+        .batch_distribute_funds(
+            members[0],
+            &[(Some(sources[0]), 300), (Some(sources[1]), 600)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InsufficientUndistributedRewards {
+            requested: Uint128::new(900),
+            available: Uint128::new(800),
+        },
+        err.downcast().unwrap()
+    );
+    let err = suite.batch_distribute_funds(members[0], &[]).unwrap_err();
+    assert_eq!(ContractError::NoRewardSources {}, err.downcast().unwrap());
+
+    // all sources are distributed at once, the rest stays undistributed
+    let resp = suite
+        .batch_distribute_funds(
+            members[0],
+            &[(Some(sources[0]), 300), (Some(sources[1]), 400)],
+        )
+        .unwrap();
+    resp.assert_event(
+        &Event::new("wasm")
+            .add_attribute("action", "batch_distribute_rewards")
+            .add_attribute("senders", "source1,source2")
+            .add_attribute("source_amounts", "300,400")
+            .add_attribute("amount", "700"),
+    );
+    assert_eq!(suite.distributed_funds().unwrap(), 700);
+    assert_eq!(suite.undistributed_funds().unwrap(), 100);
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 175);
+    assert_eq!(suite.withdrawable_rewards(members[1]).unwrap(), 525);
+}
+
+#[test]
+fn distribution_log() {
+    let member = "member";
+    let funder = "funder";
+    let other = "other";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![(member, 1_000u128, None), (funder, 2_000u128, None)])
+        .build();
+    suite.delegate(member, 1_000u128, unbonding_period).unwrap();
+    let total_reward_power = suite.can_distribute().unwrap().total_reward_power;
+
+    suite.distribute_funds(funder, None, 100).unwrap();
+    suite.distribute_funds(funder, other, 200).unwrap();
+    suite.distribute_funds(funder, None, 300).unwrap();
+
+    let entries = suite.query_distribution_log(None, None, false).unwrap();
+    let summary: Vec<_> = entries
+        .iter()
+        .map(|e| {
+            (
+                e.seq,
+                e.amount.u128(),
+                e.sender.as_str(),
+                e.distributed_total.u128(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (0, 100, funder, 100),
+            (1, 200, other, 300),
+            (2, 300, funder, 600)
+        ]
+    );
+    assert_eq!(entries[0].time, suite.block_time());
+    assert_eq!(entries[0].total_reward_power, total_reward_power);
+
+    // pagination in both directions
+    let seqs = |entries: Vec<DistributionLogEntry>| -> Vec<u64> {
+        entries.into_iter().map(|e| e.seq).collect()
+    };
+    let page = suite.query_distribution_log(0, 1, false).unwrap();
+    assert_eq!(seqs(page), [1]);
+    let page = suite.query_distribution_log(None, 2, true).unwrap();
+    assert_eq!(seqs(page), [2, 1]);
+    let page = suite.query_distribution_log(1, None, true).unwrap();
+    assert_eq!(seqs(page), [0]);
+
+    // once the log is full, the oldest entries are pruned
+    for _ in 3..=DISTRIBUTION_LOG_SIZE {
+        suite.distribute_funds(funder, None, 1).unwrap();
+    }
+    let first = suite.query_distribution_log(None, 1, false).unwrap();
+    assert_eq!(first[0].seq, 1);
+    // the previous total shows that entries are missing before the first one
+    assert_eq!(
+        first[0].distributed_total - first[0].amount,
+        Uint128::new(100)
+    );
+    let last = suite.query_distribution_log(None, 1, true).unwrap();
+    assert_eq!(last[0].seq, DISTRIBUTION_LOG_SIZE);
+    assert_eq!(last[0].distributed_total.u128(), 600 + 998);
+}
+
+#[test]
+fn rewards_funded_with_send_hook() {
+    let member = "member";
+    let partner = "partner";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(member, 10_000, None), (partner, 5_000, None)])
+        .build();
+    suite.delegate(member, 10_000, None).unwrap();
+
+    // the partner never delegates, the tokens are sent right to the stake contract
+    let token = suite.vesting_contract();
+    let res = suite.fund_rewards(&token, partner, 3_000).unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|e| e.attributes.iter().any(|a| a.value == "distribute_rewards")));
+    assert_eq!(suite.distributed_funds().unwrap(), 3_000);
+    assert_eq!(suite.withdrawable_rewards(member).unwrap(), 3_000);
+    assert_eq!(
+        suite.query_balance_vesting_contract(partner).unwrap(),
+        2_000
+    );
+    let log = suite.query_distribution_log(None, None, false).unwrap();
+    assert_eq!(log[0].sender.as_str(), partner);
+
+    suite.withdraw_funds(member, None, None).unwrap();
+    assert_eq!(suite.query_balance_vesting_contract(member).unwrap(), 3_000);
+
+    // tokens of any other contract are rejected
+    let rogue = suite.instantiate_token(vec![(partner, 5_000)]);
+    let err = suite.fund_rewards(&rogue, partner, 1_000).unwrap_err();
+    assert_eq!(
+        ContractError::UnexpectedRewardToken {
+            token: rogue.clone(),
+            expected: token,
+        },
+        err.downcast().unwrap()
+    );
+    assert_eq!(suite.query_token_balance(&rogue, partner).unwrap(), 5_000);
+    assert_eq!(suite.distributed_funds().unwrap(), 3_000);
+}
+
+#[test]
+fn rewards_funded_without_stakers_are_bounced() {
+    let partner = "partner";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(partner, 5_000, None)])
+        .build();
+
+    // nobody is staking, so the whole send is reverted and the tokens stay with the partner
+    let token = suite.vesting_contract();
+    let err = suite.fund_rewards(&token, partner, 3_000).unwrap_err();
+    assert_eq!(
+        ContractError::NoMembersToDistributeTo {},
+        err.downcast().unwrap()
+    );
+    assert_eq!(
+        suite.query_balance_vesting_contract(partner).unwrap(),
+        5_000
+    );
+    assert_eq!(suite.query_balance_staking_contract().unwrap(), 0);
+    assert_eq!(suite.undistributed_funds().unwrap(), 0);
+    assert_eq!(suite.distributed_funds().unwrap(), 0);
+}
@@ -0,0 +1,95 @@
+use cosmwasm_std::Decimal;
+use wynd_utils::ScalableCurve;
+
+use super::suite::{SuiteBuilder, SEVEN_DAYS};
+
+const FOURTEEN_DAYS: u64 = 2 * SEVEN_DAYS;
+const THIRTY_DAYS: u64 = 30 * 24 * 60 * 60;
+
+#[test]
+fn claimed_tokens_vest_after_exit() {
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(user, 10_000, None)])
+        .with_stake_config(vec![
+            (SEVEN_DAYS, Decimal::one(), Decimal::one()),
+            (FOURTEEN_DAYS, Decimal::percent(200), Decimal::percent(200)),
+        ])
+        .with_exit_vesting(
+            FOURTEEN_DAYS,
+            ScalableCurve::linear((0, 100), (THIRTY_DAYS, 0)),
+        )
+        .build();
+
+    let exit_vesting = suite.query_staked_periods().unwrap()[1]
+        .exit_vesting
+        .clone();
+    assert_eq!(
+        exit_vesting,
+        Some(ScalableCurve::linear((0, 100), (THIRTY_DAYS, 0)))
+    );
+
+    suite.delegate(user, 2_000, SEVEN_DAYS).unwrap();
+    suite.delegate(user, 4_000, FOURTEEN_DAYS).unwrap();
+    suite.unbond(user, 2_000, SEVEN_DAYS).unwrap();
+    suite.unbond(user, 4_000, FOURTEEN_DAYS).unwrap();
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 4_000);
+
+    // the short claim is released liquid
+    suite.update_time(SEVEN_DAYS);
+    suite.claim(user).unwrap();
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 6_000);
+    assert_eq!(suite.query_vesting_locked(user).unwrap(), 0);
+
+    // the long claim arrives locked
+    suite.update_time(SEVEN_DAYS);
+    suite.claim(user).unwrap();
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 10_000);
+    assert_eq!(suite.query_vesting_locked(user).unwrap(), 4_000);
+    assert_eq!(suite.query_balance_staking_contract().unwrap(), 0);
+
+    // locked tokens cannot be moved
+    suite.transfer(user, "other", 6_001u128).unwrap_err();
+    suite.transfer(user, "other", 6_000u128).unwrap();
+
+    // and unlock linearly according to the scaled curve
+    suite.update_time(THIRTY_DAYS / 4);
+    assert_eq!(suite.query_vesting_locked(user).unwrap(), 3_000);
+    suite.update_time(THIRTY_DAYS / 4);
+    assert_eq!(suite.query_vesting_locked(user).unwrap(), 2_000);
+    suite.transfer(user, "other", 2_000u128).unwrap();
+
+    suite.update_time(THIRTY_DAYS / 2);
+    assert_eq!(suite.query_vesting_locked(user).unwrap(), 0);
+    suite.transfer(user, "other", 2_000u128).unwrap();
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 0);
+}
+
+#[test]
+fn claims_of_same_period_are_combined() {
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(user, 10_000, None)])
+        .with_exit_vesting(
+            SEVEN_DAYS,
+            ScalableCurve::linear((0, 100), (THIRTY_DAYS, 0)),
+        )
+        .build();
+
+    suite.delegate(user, 6_000, None).unwrap();
+    suite.unbond(user, 1_000, None).unwrap();
+    suite.update_time(1);
+    suite.unbond(user, 3_000, None).unwrap();
+
+    // both claims are released in one vesting schedule
+    suite.update_time(SEVEN_DAYS);
+    suite.claim(user).unwrap();
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 8_000);
+    assert_eq!(suite.query_vesting_locked(user).unwrap(), 4_000);
+
+    // the rest is unaffected
+    assert_eq!(suite.query_staked(user, None).unwrap(), 2_000);
+
+    suite.update_time(THIRTY_DAYS / 2);
+    assert_eq!(suite.query_vesting_locked(user).unwrap(), 2_000);
+}
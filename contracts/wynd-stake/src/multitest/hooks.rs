@@ -0,0 +1,75 @@
+use cosmwasm_std::Decimal;
+
+use super::suite::SuiteBuilder;
+use crate::error::ContractError;
+use crate::state::DEFAULT_MAX_HOOKS;
+
+#[test]
+fn gas_limited_hook_failure_does_not_block_bonding() {
+    let admin = "admin";
+    let member = "member";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![(member, 10_000u128, None)])
+        .build();
+
+    let hook = suite.instantiate_failing_hook();
+    suite.add_hook(admin, &hook, 100_000).unwrap();
+
+    let hooks = suite.query_hooks().unwrap();
+    assert_eq!(hooks.hooks, vec![hook.clone()]);
+    assert_eq!(hooks.max_hooks, DEFAULT_MAX_HOOKS);
+    assert_eq!(hooks.hook_configs[0].gas_limit, Some(100_000));
+
+    // bonding succeeds even though the hook fails, the failure is recorded
+    suite.delegate(member, 10_000, unbonding_period).unwrap();
+    assert_eq!(
+        suite.query_staked(member, unbonding_period).unwrap(),
+        10_000
+    );
+    let hooks = suite.query_hooks().unwrap();
+    assert_eq!(hooks.hook_configs[0].failures, 1);
+}
+
+#[test]
+fn hook_without_gas_limit_failure_blocks_bonding() {
+    let admin = "admin";
+    let member = "member";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![(member, 10_000u128, None)])
+        .build();
+
+    let hook = suite.instantiate_failing_hook();
+    suite.add_hook(admin, &hook, None).unwrap();
+
+    suite
+        .delegate(member, 10_000, unbonding_period)
+        .unwrap_err();
+    assert_eq!(suite.query_staked(member, unbonding_period).unwrap(), 0);
+}
+
+#[test]
+fn max_hooks_enforced() {
+    let admin = "admin";
+    let mut suite = SuiteBuilder::new().with_admin(admin).build();
+
+    for _ in 0..DEFAULT_MAX_HOOKS {
+        let hook = suite.instantiate_failing_hook();
+        suite.add_hook(admin, &hook, 100_000).unwrap();
+    }
+    let hook = suite.instantiate_failing_hook();
+    let err = suite.add_hook(admin, &hook, 100_000).unwrap_err();
+    assert_eq!(
+        ContractError::TooManyHooks {
+            max: DEFAULT_MAX_HOOKS
+        },
+        err.downcast().unwrap()
+    );
+}
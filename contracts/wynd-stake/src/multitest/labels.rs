@@ -0,0 +1,98 @@
+use cosmwasm_std::{Addr, Uint128};
+use cw_controllers::AdminError;
+
+use super::suite::{SuiteBuilder, SEVEN_DAYS};
+use crate::msg::DelegationInfo;
+use crate::ContractError;
+
+#[test]
+fn admin_labels_addresses() {
+    let admin = "admin";
+    let treasury = "treasury";
+    let market_maker = "market_maker";
+    let mut suite = SuiteBuilder::new().with_admin(admin).build();
+
+    // only the admin can set labels
+    let err = suite.set_label(treasury, treasury, "Treasury").unwrap_err();
+    assert_eq!(
+        ContractError::Admin(AdminError::NotAdmin {}),
+        err.downcast().unwrap()
+    );
+    assert_eq!(suite.query_label(treasury).unwrap(), None);
+
+    // labels have to be short and not empty
+    let err = suite.set_label(admin, treasury, "").unwrap_err();
+    assert_eq!(
+        ContractError::InvalidLabel { max: 64 },
+        err.downcast().unwrap()
+    );
+    let err = suite
+        .set_label(admin, treasury, &"x".repeat(65))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidLabel { max: 64 },
+        err.downcast().unwrap()
+    );
+    suite.set_label(admin, treasury, &"€".repeat(64)).unwrap();
+
+    // setting a label again overwrites it
+    suite.set_label(admin, treasury, "Treasury").unwrap();
+    suite
+        .set_label(admin, market_maker, "Market maker")
+        .unwrap();
+    assert_eq!(
+        suite.query_label(treasury).unwrap(),
+        Some("Treasury".to_owned())
+    );
+    assert_eq!(
+        suite.query_all_labels(None, None).unwrap(),
+        [
+            (market_maker.to_owned(), "Market maker".to_owned()),
+            (treasury.to_owned(), "Treasury".to_owned()),
+        ]
+    );
+    assert_eq!(
+        suite.query_all_labels(Some(market_maker), 1).unwrap(),
+        [(treasury.to_owned(), "Treasury".to_owned())]
+    );
+
+    // removing it
+    suite.remove_label(treasury, treasury).unwrap_err();
+    suite.remove_label(admin, treasury).unwrap();
+    assert_eq!(suite.query_label(treasury).unwrap(), None);
+    assert_eq!(
+        suite.query_all_labels(None, None).unwrap(),
+        [(market_maker.to_owned(), "Market maker".to_owned())]
+    );
+}
+
+#[test]
+fn labels_are_listed_with_delegations() {
+    let admin = "admin";
+    let team = "team";
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_initial_balances(vec![(team, 2_000, None), (user, 1_000, None)])
+        .build();
+
+    suite.delegate(team, 2_000, SEVEN_DAYS).unwrap();
+    suite.delegate(user, 1_000, SEVEN_DAYS).unwrap();
+    suite.set_label(admin, team, "Team multisig").unwrap();
+
+    assert_eq!(
+        suite.query_all_delegations(None, None).unwrap(),
+        [
+            DelegationInfo {
+                address: Addr::unchecked(team),
+                stake: Uint128::new(2_000),
+                label: Some("Team multisig".to_owned()),
+            },
+            DelegationInfo {
+                address: Addr::unchecked(user),
+                stake: Uint128::new(1_000),
+                label: None,
+            },
+        ]
+    );
+}
@@ -0,0 +1,112 @@
+use cosmwasm_std::Addr;
+
+use super::suite::SuiteBuilder;
+use crate::msg::MigrationProgressResponse;
+use crate::state::STORAGE_VERSION;
+use crate::ContractError;
+
+#[test]
+fn staged_migration_backfills_stakers_in_chunks() {
+    let members = ["member1", "member2", "member3", "member4", "member5"];
+
+    let mut suite = SuiteBuilder::new()
+        .with_legacy_layout()
+        .with_initial_balances(
+            members
+                .iter()
+                .map(|member| (*member, 10_000u128, None))
+                .collect(),
+        )
+        .build();
+
+    for member in members {
+        suite.delegate(member, 6_000u128, None).unwrap();
+    }
+    // the old layout has neither first stakes nor a storage version
+    assert_eq!(suite.query_first_staked(members[0]).unwrap(), None);
+    assert_eq!(suite.query_migration_progress().unwrap().storage_version, 0);
+
+    suite.migrate_stake().unwrap();
+    assert_eq!(
+        suite.query_migration_progress().unwrap(),
+        MigrationProgressResponse {
+            in_progress: true,
+            storage_version: 0,
+            target_version: Some(STORAGE_VERSION),
+            last_processed: None,
+            processed: 0,
+        }
+    );
+
+    // nothing else can be done until the migration is finished
+    let err = suite.recompute_members(members[0], None, None).unwrap_err();
+    assert_eq!(
+        ContractError::MigrationInProgress {},
+        err.downcast().unwrap()
+    );
+    suite.delegate(members[0], 1_000u128, None).unwrap_err();
+
+    suite.update_time(100);
+    let migrated_at = suite.block_time();
+
+    // five stakers in chunks of two
+    suite.continue_migration("anyone", 2).unwrap();
+    let progress = suite.query_migration_progress().unwrap();
+    assert!(progress.in_progress);
+    assert_eq!(progress.processed, 2);
+    assert_eq!(progress.last_processed, Some(Addr::unchecked(members[1])));
+
+    suite.continue_migration("anyone", 2).unwrap();
+    let progress = suite.query_migration_progress().unwrap();
+    assert!(progress.in_progress);
+    assert_eq!(progress.processed, 4);
+    assert_eq!(progress.last_processed, Some(Addr::unchecked(members[3])));
+
+    suite.continue_migration("anyone", 2).unwrap();
+    assert_eq!(
+        suite.query_migration_progress().unwrap(),
+        MigrationProgressResponse {
+            in_progress: false,
+            storage_version: STORAGE_VERSION,
+            target_version: None,
+            last_processed: None,
+            processed: 0,
+        }
+    );
+
+    let err = suite.continue_migration("anyone", 2).unwrap_err();
+    assert_eq!(
+        ContractError::NoMigrationInProgress {},
+        err.downcast().unwrap()
+    );
+
+    // stakers from before got the time of the migration, new ones the time they stake
+    for member in members {
+        assert_eq!(suite.query_first_staked(member).unwrap(), Some(migrated_at));
+    }
+    suite.update_time(100);
+    suite.delegate(members[0], 1_000u128, None).unwrap();
+    assert_eq!(
+        suite.query_first_staked(members[0]).unwrap(),
+        Some(migrated_at)
+    );
+    assert_eq!(suite.query_first_staked("newcomer").unwrap(), None);
+}
+
+#[test]
+fn new_contracts_need_no_migration() {
+    let member = "member";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(member, 10_000u128, None)])
+        .build();
+
+    let progress = suite.query_migration_progress().unwrap();
+    assert!(!progress.in_progress);
+    assert_eq!(progress.storage_version, STORAGE_VERSION);
+
+    suite.delegate(member, 6_000u128, None).unwrap();
+    assert_eq!(
+        suite.query_first_staked(member).unwrap(),
+        Some(suite.block_time())
+    );
+}
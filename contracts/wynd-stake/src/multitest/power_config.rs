@@ -0,0 +1,149 @@
+use cosmwasm_std::{Addr, Decimal, Decimal256, Uint128};
+
+use super::suite::SuiteBuilder;
+use crate::msg::RewardsBreakdownEntry;
+
+#[test]
+fn update_power_config_and_recompute_in_pages() {
+    let members = ["member1", "member2", "member3"];
+    let admin = "admin";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![
+            (members[0], 10_000u128, None),
+            (members[1], 20_000u128, None),
+            (members[2], 30_000u128, None),
+        ])
+        .build();
+
+    for (member, stake) in members.iter().zip([10_000u128, 20_000, 30_000]) {
+        suite.delegate(member, stake, unbonding_period).unwrap();
+    }
+    suite.advance_blocks(1);
+    let height_before = suite.block_height();
+    assert_eq!(suite.query_total_power(None).unwrap(), 60);
+
+    // halve tokens per power, doubling everyone's power
+    suite.update_power_config(admin, 500, None).unwrap();
+    assert!(suite.query_recompute_status().unwrap().pending);
+
+    suite.recompute_members("anyone", None, 2).unwrap();
+    let status = suite.query_recompute_status().unwrap();
+    assert!(status.pending);
+    assert_eq!(status.processed, 2);
+    assert_eq!(status.last_processed, Some(Addr::unchecked(members[1])));
+    assert_eq!(suite.query_voting_power(members[0], None).unwrap(), 20);
+    assert_eq!(suite.query_voting_power(members[1], None).unwrap(), 40);
+    assert_eq!(suite.query_voting_power(members[2], None).unwrap(), 30);
+    assert_eq!(suite.query_total_power(None).unwrap(), 90);
+
+    suite
+        .recompute_members("anyone", Some(members[1]), 2)
+        .unwrap();
+    assert!(!suite.query_recompute_status().unwrap().pending);
+    assert_eq!(suite.query_voting_power(members[2], None).unwrap(), 60);
+    assert_eq!(suite.query_total_power(None).unwrap(), 120);
+    assert_eq!(suite.query_rewards(members[2]).unwrap(), 60);
+    assert_eq!(suite.query_total_rewards().unwrap(), 120);
+
+    // historical queries at the pre-change height still return the old powers
+    assert_eq!(
+        suite.query_voting_power(members[0], height_before).unwrap(),
+        10
+    );
+    assert_eq!(
+        suite.query_voting_power(members[2], height_before).unwrap(),
+        30
+    );
+    assert_eq!(suite.query_total_power(height_before).unwrap(), 60);
+}
+
+#[test]
+fn unsorted_stake_config_is_sorted() {
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![
+            (8000, Decimal::percent(300)),
+            (1000, Decimal::one()),
+            (4000, Decimal::percent(200)),
+        ])
+        .with_initial_balances(vec![(user, 10_000u128, None)])
+        .build();
+
+    let periods = suite.query_staked_periods().unwrap();
+    let summary: Vec<_> = periods
+        .iter()
+        .map(|info| (info.unbonding_period, info.voting_multiplier))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (1000, Decimal::one()),
+            (4000, Decimal::percent(200)),
+            (8000, Decimal::percent(300)),
+        ]
+    );
+
+    // staking works as usual
+    suite.delegate(user, 5_000u128, 4000).unwrap();
+    suite.advance_blocks(1);
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 10);
+}
+
+#[test]
+fn rewards_breakdown_detects_stale_rewards() {
+    let user = "user";
+    let admin = "admin";
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_min_bond(1_000)
+        .with_stake_config(vec![
+            (1000, Decimal::one(), Decimal::one()),
+            (2000, Decimal::percent(200), Decimal::percent(50)),
+        ])
+        .with_initial_balances(vec![(user, 15_000, None)])
+        .build();
+
+    suite.delegate(user, 10_000, 1000).unwrap();
+    suite.delegate(user, 5_000, 2000).unwrap();
+
+    let breakdown = suite.query_rewards_breakdown(user).unwrap();
+    assert_eq!(
+        breakdown.periods,
+        [
+            RewardsBreakdownEntry {
+                unbonding_period: 1000,
+                stake: Uint128::new(10_000),
+                reward_multiplier: Decimal::one(),
+                reward_power: Decimal256::from_atomics(10u128, 0).unwrap(),
+            },
+            RewardsBreakdownEntry {
+                unbonding_period: 2000,
+                stake: Uint128::new(5_000),
+                reward_multiplier: Decimal::percent(50),
+                reward_power: Decimal256::percent(250),
+            },
+        ]
+    );
+    // only the sum is truncated
+    assert_eq!(breakdown.total, Uint128::new(12));
+    assert_eq!(breakdown.cached, Uint128::new(12));
+    assert!(!breakdown.stale);
+
+    // halving tokens per power doubles the reward power, but only once recomputed
+    suite.update_power_config(admin, 500, None).unwrap();
+    let breakdown = suite.query_rewards_breakdown(user).unwrap();
+    assert_eq!(breakdown.periods[1].reward_power, Decimal256::percent(500));
+    assert_eq!(breakdown.total, Uint128::new(25));
+    assert_eq!(breakdown.cached, Uint128::new(12));
+    assert!(breakdown.stale);
+
+    suite.recompute_members("anyone", None, None).unwrap();
+    let breakdown = suite.query_rewards_breakdown(user).unwrap();
+    assert_eq!(breakdown.cached, Uint128::new(25));
+    assert!(!breakdown.stale);
+    assert_eq!(suite.query_rewards(user).unwrap(), 25);
+}
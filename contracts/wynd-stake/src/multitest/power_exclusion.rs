@@ -0,0 +1,102 @@
+use cosmwasm_std::{Addr, Decimal};
+
+use super::suite::SuiteBuilder;
+use crate::ContractError;
+
+#[test]
+fn excluded_treasury_does_not_count_towards_quorum() {
+    let members = ["member1", "member2"];
+    let treasury = "treasury";
+    let admin = "admin";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![
+            (members[0], 10_000u128, None),
+            (members[1], 20_000u128, None),
+            (treasury, 70_000u128, None),
+        ])
+        .build();
+
+    // only admin can exclude
+    let err = suite.exclude_from_power(members[0], treasury).unwrap_err();
+    assert_eq!(
+        ContractError::Admin(cw_controllers::AdminError::NotAdmin {}),
+        err.downcast().unwrap()
+    );
+    suite.exclude_from_power(admin, treasury).unwrap();
+    let err = suite.exclude_from_power(admin, treasury).unwrap_err();
+    assert_eq!(
+        ContractError::AlreadyExcludedFromPower {
+            address: treasury.to_owned()
+        },
+        err.downcast().unwrap()
+    );
+    assert_eq!(
+        suite.query_power_exclusions(None, None).unwrap(),
+        vec![Addr::unchecked(treasury)]
+    );
+
+    suite
+        .delegate(members[0], 10_000, unbonding_period)
+        .unwrap();
+    suite
+        .delegate(members[1], 20_000, unbonding_period)
+        .unwrap();
+    suite.delegate(treasury, 70_000, unbonding_period).unwrap();
+    suite.advance_blocks(1);
+
+    // total power only reflects the community, the treasury votes with zero power
+    assert_eq!(suite.query_total_power(None).unwrap(), 30);
+    assert_eq!(suite.query_voting_power(treasury, None).unwrap(), 0);
+    assert_eq!(suite.query_voting_power(members[1], None).unwrap(), 20);
+
+    // stake and rewards are untouched
+    assert_eq!(
+        suite.query_staked(treasury, unbonding_period).unwrap(),
+        70_000
+    );
+    assert_eq!(suite.query_rewards(treasury).unwrap(), 70);
+    assert_eq!(suite.query_total_rewards().unwrap(), 100);
+
+    // historical queries subtract the excluded power at that height as well
+    let height = suite.block_height();
+    suite.unbond(treasury, 20_000, unbonding_period).unwrap();
+    suite.advance_blocks(1);
+    assert_eq!(suite.query_total_power(height).unwrap(), 30);
+    assert_eq!(suite.query_total_power(None).unwrap(), 30);
+
+    // removing the exclusion restores the power
+    suite.include_in_power(admin, treasury).unwrap();
+    let err = suite.include_in_power(admin, treasury).unwrap_err();
+    assert_eq!(
+        ContractError::NotExcludedFromPower {
+            address: treasury.to_owned()
+        },
+        err.downcast().unwrap()
+    );
+    assert!(suite.query_power_exclusions(None, None).unwrap().is_empty());
+    assert_eq!(suite.query_voting_power(treasury, None).unwrap(), 50);
+    assert_eq!(suite.query_total_power(None).unwrap(), 80);
+    assert_eq!(suite.query_total_power(height).unwrap(), 100);
+}
+
+#[test]
+fn list_exclusions_paginated() {
+    let admin = "admin";
+    let mut suite = SuiteBuilder::new().with_admin(admin).build();
+
+    for addr in ["excluded1", "excluded2", "excluded3"] {
+        suite.exclude_from_power(admin, addr).unwrap();
+    }
+
+    let page = suite.query_power_exclusions(None, 2).unwrap();
+    assert_eq!(
+        page,
+        vec![Addr::unchecked("excluded1"), Addr::unchecked("excluded2")]
+    );
+    let page = suite.query_power_exclusions(Some("excluded2"), 2).unwrap();
+    assert_eq!(page, vec![Addr::unchecked("excluded3")]);
+}
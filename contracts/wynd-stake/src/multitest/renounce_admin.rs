@@ -0,0 +1,58 @@
+use cw_controllers::AdminError;
+
+use super::suite::SuiteBuilder;
+use crate::ContractError;
+
+#[test]
+fn renounced_admin_cannot_be_used_anymore() {
+    let admin = "admin";
+    let mut suite = SuiteBuilder::new().with_admin(admin).build();
+
+    let status = suite.query_admin_status().unwrap();
+    assert_eq!(status.admin.unwrap().as_str(), admin);
+    assert!(!status.renounced);
+    assert_eq!(status.renounced_at, None);
+
+    // only the admin can renounce
+    let err = suite.renounce_admin("someone").unwrap_err();
+    assert_eq!(
+        ContractError::Admin(AdminError::NotAdmin {}),
+        err.downcast().unwrap()
+    );
+
+    let res = suite.renounce_admin(admin).unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "renounce_admin")));
+    let status = suite.query_admin_status().unwrap();
+    assert_eq!(status.admin, None);
+    assert!(status.renounced);
+    assert_eq!(status.renounced_at, Some(suite.block_time()));
+
+    let results = [
+        suite.add_hook(admin, "hook", None),
+        suite.remove_hook(admin, "hook"),
+        suite.update_power_config(admin, 10u128, None),
+        suite.update_distribution_fee(admin, Some((100, "fees"))),
+        suite.freeze_claims(admin, "member"),
+        suite.set_label(admin, "member", "label"),
+        suite.exclude_from_power(admin, "member"),
+        suite.renounce_admin(admin),
+    ];
+    for res in results {
+        assert_eq!(
+            ContractError::AdminRenounced {},
+            res.unwrap_err().downcast().unwrap()
+        );
+    }
+
+    // the admin cannot be resurrected, not even by the former admin
+    for sender in [admin, "someone"] {
+        let err = suite.update_admin(sender, Some(sender)).unwrap_err();
+        assert_eq!(ContractError::AdminRenounced {}, err.downcast().unwrap());
+    }
+    let status = suite.query_admin_status().unwrap();
+    assert_eq!(status.admin, None);
+    assert!(status.renounced);
+}
@@ -0,0 +1,198 @@
+use cosmwasm_std::Decimal;
+use cw_controllers::AdminError;
+
+use super::suite::SuiteBuilder;
+use crate::ContractError;
+
+#[test]
+fn inactive_stakers_forfeit_decayed_rewards() {
+    let members = ["member1", "member2", "member3"];
+    let admin = "admin";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![
+            (members[0], 10_000, None),
+            (members[1], 10_000, None),
+            (members[2], 2_000, None),
+        ])
+        .build();
+
+    suite
+        .delegate(members[0], 10_000, unbonding_period)
+        .unwrap();
+    suite
+        .delegate(members[1], 10_000, unbonding_period)
+        .unwrap();
+
+    suite
+        .update_reward_decay(admin, Some((1000, Decimal::percent(50))))
+        .unwrap();
+
+    // nobody is inactive yet, so the rewards are split evenly
+    suite.distribute_funds(members[2], None, 1000).unwrap();
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 500);
+    assert_eq!(suite.withdrawable_rewards(members[1]).unwrap(), 500);
+
+    suite.withdraw_funds(members[0], None, None).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(members[0]).unwrap(),
+        500
+    );
+
+    // member2 did not interact for a whole inactivity period, but nothing was earned since
+    suite.update_time(1000);
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 0);
+    assert_eq!(suite.withdrawable_rewards(members[1]).unwrap(), 500);
+
+    // member1 is inactive as well, only the rewards earned from now on decay
+    suite.distribute_funds(members[2], None, 1000).unwrap();
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 250);
+    assert_eq!(suite.withdrawable_rewards(members[1]).unwrap(), 750);
+    assert_eq!(suite.distributed_funds().unwrap(), 2000);
+    assert_eq!(suite.withdrawable_funds().unwrap(), 1500);
+    assert_eq!(suite.undistributed_funds().unwrap(), 0);
+
+    let resp = suite.withdraw_funds(members[1], None, None).unwrap();
+    assert!(resp.events.iter().any(|ev| ev
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "forfeited" && attr.value == "250")));
+    suite.withdraw_funds(members[0], None, None).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(members[0]).unwrap(),
+        750
+    );
+    assert_eq!(
+        suite.query_balance_vesting_contract(members[1]).unwrap(),
+        750
+    );
+
+    // forfeited rewards are distributed again, both stakers just interacted
+    assert_eq!(suite.withdrawable_funds().unwrap(), 0);
+    assert_eq!(suite.undistributed_funds().unwrap(), 500);
+    suite.distribute_rewards(members[2]).unwrap();
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 250);
+    assert_eq!(suite.withdrawable_rewards(members[1]).unwrap(), 250);
+
+    suite.withdraw_funds(members[0], None, None).unwrap();
+    suite.withdraw_funds(members[1], None, None).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(members[0]).unwrap()
+            + suite.query_balance_vesting_contract(members[1]).unwrap(),
+        2000
+    );
+    assert_eq!(suite.query_balance_staking_contract().unwrap(), 20_000);
+}
+
+#[test]
+fn only_rewards_earned_while_inactive_decay() {
+    let members = ["member1", "member2"];
+    let admin = "admin";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![(members[0], 10_000, None), (members[1], 4_000, None)])
+        .build();
+
+    suite
+        .delegate(members[0], 10_000, unbonding_period)
+        .unwrap();
+    suite
+        .update_reward_decay(admin, Some((1000, Decimal::percent(50))))
+        .unwrap();
+
+    // earned before member1 becomes inactive
+    suite.distribute_funds(members[1], None, 1000).unwrap();
+    suite.update_time(500);
+    suite.distribute_funds(members[1], None, 1000).unwrap();
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 2000);
+
+    // earned after
+    suite.update_time(500);
+    suite.distribute_funds(members[1], None, 1000).unwrap();
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 2500);
+    suite.update_time(500);
+    suite.distribute_funds(members[1], None, 1000).unwrap();
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 3000);
+
+    let resp = suite.withdraw_funds(members[0], None, None).unwrap();
+    assert!(resp.events.iter().any(|ev| ev
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "forfeited" && attr.value == "1000")));
+    assert_eq!(
+        suite.query_balance_vesting_contract(members[0]).unwrap(),
+        3000
+    );
+    assert_eq!(suite.undistributed_funds().unwrap(), 1000);
+}
+
+#[test]
+fn disabled_decay_keeps_rewards() {
+    let members = ["member1", "member2"];
+    let admin = "admin";
+    let unbonding_period = 1000u64;
+
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_stake_config_voting(vec![(unbonding_period, Decimal::one())])
+        .with_initial_balances(vec![(members[0], 10_000, None), (members[1], 1_000, None)])
+        .build();
+
+    suite
+        .delegate(members[0], 10_000, unbonding_period)
+        .unwrap();
+    suite
+        .update_reward_decay(admin, Some((1000, Decimal::percent(50))))
+        .unwrap();
+    suite.update_time(1000);
+    suite.distribute_funds(members[1], None, 1000).unwrap();
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 500);
+
+    suite.update_reward_decay(admin, None).unwrap();
+    assert_eq!(suite.withdrawable_rewards(members[0]).unwrap(), 1000);
+    suite.withdraw_funds(members[0], None, None).unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(members[0]).unwrap(),
+        1000
+    );
+}
+
+#[test]
+fn update_reward_decay_validation() {
+    let admin = "admin";
+    let mut suite = SuiteBuilder::new().with_admin(admin).build();
+
+    let err = suite
+        .update_reward_decay("random", Some((1000, Decimal::percent(50))))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Admin(AdminError::NotAdmin {}),
+        err.downcast().unwrap()
+    );
+
+    let err = suite
+        .update_reward_decay(admin, Some((0, Decimal::percent(50))))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidRewardDecay {},
+        err.downcast().unwrap()
+    );
+
+    let err = suite
+        .update_reward_decay(admin, Some((1000, Decimal::percent(101))))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidRewardDecay {},
+        err.downcast().unwrap()
+    );
+
+    suite
+        .update_reward_decay(admin, Some((1000, Decimal::one())))
+        .unwrap();
+}
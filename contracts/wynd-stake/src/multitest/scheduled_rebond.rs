@@ -0,0 +1,121 @@
+use cosmwasm_std::{Decimal, Uint128};
+
+use super::suite::{SuiteBuilder, SEVEN_DAYS};
+use crate::msg::ScheduledRebond;
+use crate::ContractError;
+
+const FOURTEEN_DAYS: u64 = 2 * SEVEN_DAYS;
+
+#[test]
+fn scheduled_rebond_is_executed_once_unlocked() {
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![
+            (SEVEN_DAYS, Decimal::one()),
+            (FOURTEEN_DAYS, Decimal::percent(200)),
+        ])
+        .with_initial_balances(vec![(user, 10_000, None)])
+        .build();
+
+    suite.delegate(user, 10_000, FOURTEEN_DAYS).unwrap();
+    // rebonding down locks the tokens for the difference of the periods
+    suite
+        .rebond(user, 5_000, FOURTEEN_DAYS, SEVEN_DAYS)
+        .unwrap();
+    suite
+        .rebond(user, 5_000, SEVEN_DAYS, FOURTEEN_DAYS)
+        .unwrap_err();
+
+    // so moving them back up is scheduled for when they are unlocked
+    let execute_after = suite.block_time().plus_seconds(SEVEN_DAYS);
+    suite
+        .schedule_rebond(user, 5_000, SEVEN_DAYS, FOURTEEN_DAYS, execute_after)
+        .unwrap();
+    assert_eq!(
+        suite.query_scheduled_rebonds(user).unwrap(),
+        [ScheduledRebond {
+            tokens: Uint128::new(5_000),
+            bond_from: SEVEN_DAYS,
+            bond_to: FOURTEEN_DAYS,
+            execute_after,
+        }]
+    );
+
+    let err = suite.execute_scheduled_rebonds("keeper", user).unwrap_err();
+    assert_eq!(
+        ContractError::NoScheduledRebondsDue {},
+        err.downcast().unwrap()
+    );
+
+    suite.update_time(SEVEN_DAYS);
+    let res = suite.execute_scheduled_rebonds("keeper", user).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "wasm-scheduled_rebond"));
+    assert_eq!(suite.query_staked(user, SEVEN_DAYS).unwrap(), 0);
+    assert_eq!(suite.query_staked(user, FOURTEEN_DAYS).unwrap(), 10_000);
+    assert_eq!(suite.query_scheduled_rebonds(user).unwrap(), []);
+}
+
+#[test]
+fn invalid_scheduled_rebond_is_dropped() {
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![
+            (SEVEN_DAYS, Decimal::one()),
+            (FOURTEEN_DAYS, Decimal::percent(200)),
+        ])
+        .with_initial_balances(vec![(user, 10_000, None)])
+        .build();
+
+    suite.delegate(user, 10_000, FOURTEEN_DAYS).unwrap();
+    let now = suite.block_time();
+    suite
+        .schedule_rebond(user, 20_000, FOURTEEN_DAYS, SEVEN_DAYS, now)
+        .unwrap();
+    suite
+        .schedule_rebond(user, 4_000, FOURTEEN_DAYS, SEVEN_DAYS, now)
+        .unwrap();
+
+    // the rebond of more than is staked does not prevent the other one
+    let res = suite.execute_scheduled_rebonds("keeper", user).unwrap();
+    let dropped = res
+        .events
+        .iter()
+        .filter(|e| e.ty == "wasm-scheduled_rebond_dropped")
+        .count();
+    assert_eq!(dropped, 1);
+    assert_eq!(suite.query_staked(user, FOURTEEN_DAYS).unwrap(), 6_000);
+    assert_eq!(suite.query_staked(user, SEVEN_DAYS).unwrap(), 4_000);
+    assert_eq!(suite.query_scheduled_rebonds(user).unwrap(), []);
+}
+
+#[test]
+fn scheduled_rebonds_are_limited() {
+    let user = "user";
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![
+            (SEVEN_DAYS, Decimal::one()),
+            (FOURTEEN_DAYS, Decimal::percent(200)),
+        ])
+        .build();
+
+    let later = suite.block_time().plus_seconds(SEVEN_DAYS);
+    for _ in 0..5 {
+        suite
+            .schedule_rebond(user, 1_000, FOURTEEN_DAYS, SEVEN_DAYS, later)
+            .unwrap();
+    }
+    let err = suite
+        .schedule_rebond(user, 1_000, FOURTEEN_DAYS, SEVEN_DAYS, later)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::TooManyScheduledRebonds { max: 5 },
+        err.downcast().unwrap()
+    );
+    let err = suite
+        .schedule_rebond(user, 1_000, FOURTEEN_DAYS, 1, later)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NoUnbondingPeriodFound(1),
+        err.downcast().unwrap()
+    );
+}
@@ -0,0 +1,191 @@
+use cosmwasm_std::{Decimal, Uint128};
+use cw_controllers::AdminError;
+
+use super::suite::{SuiteBuilder, SEVEN_DAYS};
+use crate::msg::SlashDestination;
+use crate::ContractError;
+
+const FOURTEEN_DAYS: u64 = 2 * SEVEN_DAYS;
+const TWENTY_ONE_DAYS: u64 = 3 * SEVEN_DAYS;
+
+#[test]
+fn proportional_slash_across_three_periods() {
+    let admin = "admin";
+    let member = "member";
+    let other = "other";
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_min_bond(1_000)
+        .with_stake_config_voting(vec![
+            (SEVEN_DAYS, Decimal::one()),
+            (FOURTEEN_DAYS, Decimal::percent(200)),
+            (TWENTY_ONE_DAYS, Decimal::percent(300)),
+        ])
+        .with_initial_balances(vec![(member, 30_000, None), (other, 10_000, None)])
+        .build();
+
+    suite.delegate(member, 10_000, SEVEN_DAYS).unwrap();
+    suite.delegate(member, 10_000, FOURTEEN_DAYS).unwrap();
+    suite.delegate(member, 10_000, TWENTY_ONE_DAYS).unwrap();
+    suite.delegate(other, 10_000, SEVEN_DAYS).unwrap();
+    // a pending claim and a tranche locked by rebonding down are both out of reach
+    suite.unbond(member, 2_000, SEVEN_DAYS).unwrap();
+    suite
+        .rebond(member, 4_000, TWENTY_ONE_DAYS, SEVEN_DAYS)
+        .unwrap();
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 50);
+    assert_eq!(suite.query_total_staked().unwrap(), 38_000);
+
+    let res = suite
+        .slash_member(admin, member, Decimal::percent(50), None)
+        .unwrap();
+    let slashes: Vec<_> = res
+        .events
+        .iter()
+        .filter(|event| event.ty == "wasm-slash")
+        .map(|event| {
+            let attr = |key: &str| {
+                event
+                    .attributes
+                    .iter()
+                    .find(|attr| attr.key == key)
+                    .unwrap()
+                    .value
+                    .clone()
+            };
+            (attr("unbonding_period"), attr("amount"))
+        })
+        .collect();
+    assert_eq!(
+        slashes,
+        [
+            (SEVEN_DAYS.to_string(), "4000".to_owned()),
+            (FOURTEEN_DAYS.to_string(), "5000".to_owned()),
+            (TWENTY_ONE_DAYS.to_string(), "3000".to_owned()),
+        ]
+    );
+
+    // half of the unlocked stake is gone, the locked tranche is still there
+    assert_eq!(suite.query_staked(member, SEVEN_DAYS).unwrap(), 8_000);
+    assert_eq!(suite.query_staked(member, FOURTEEN_DAYS).unwrap(), 5_000);
+    assert_eq!(suite.query_staked(member, TWENTY_ONE_DAYS).unwrap(), 3_000);
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 27);
+    assert_eq!(suite.query_voting_power(other, None).unwrap(), 10);
+    assert_eq!(suite.query_total_power(None).unwrap(), 37);
+    assert_eq!(suite.query_total_staked().unwrap(), 26_000);
+    let periods = suite.query_total_staked_by_period().unwrap();
+    assert_eq!(periods.total_staked, Uint128::new(26_000));
+
+    // the claim is untouched
+    assert_eq!(suite.query_claims(member).unwrap().len(), 1);
+    assert_eq!(suite.query_total_unbonding().unwrap(), 2_000);
+
+    // and the slashed tokens are burned
+    assert_eq!(suite.query_token_supply().unwrap(), 28_000);
+    assert_eq!(
+        suite.query_delegated_vesting_contract(member).unwrap(),
+        18_000
+    );
+    assert_eq!(suite.query_balance_staking_contract().unwrap(), 28_000);
+    assert_eq!(suite.undistributed_funds().unwrap(), 0);
+}
+
+#[test]
+fn slash_single_period_into_reward_pool() {
+    let admin = "admin";
+    let member = "member";
+    let other = "other";
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_min_bond(1_000)
+        .with_stake_config_voting(vec![
+            (SEVEN_DAYS, Decimal::one()),
+            (FOURTEEN_DAYS, Decimal::percent(200)),
+        ])
+        .with_initial_balances(vec![(member, 15_000, None), (other, 10_000, None)])
+        .build();
+
+    suite.delegate(member, 10_000, SEVEN_DAYS).unwrap();
+    suite.delegate(member, 5_000, FOURTEEN_DAYS).unwrap();
+    suite.delegate(other, 10_000, SEVEN_DAYS).unwrap();
+
+    suite
+        .update_slash_destination(admin, SlashDestination::RewardPool)
+        .unwrap();
+    assert_eq!(
+        suite.query_config().unwrap().slash_destination,
+        SlashDestination::RewardPool
+    );
+
+    suite
+        .slash_member(admin, member, Decimal::percent(20), SEVEN_DAYS)
+        .unwrap();
+    assert_eq!(suite.query_staked(member, SEVEN_DAYS).unwrap(), 8_000);
+    assert_eq!(suite.query_staked(member, FOURTEEN_DAYS).unwrap(), 5_000);
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 18);
+    assert_eq!(suite.query_total_staked().unwrap(), 23_000);
+
+    // nothing is burned, the tokens stay with the stake contract as rewards
+    assert_eq!(suite.query_token_supply().unwrap(), 25_000);
+    assert_eq!(suite.query_balance_staking_contract().unwrap(), 25_000);
+    assert_eq!(
+        suite.query_delegated_vesting_contract(member).unwrap(),
+        13_000
+    );
+    assert_eq!(suite.undistributed_funds().unwrap(), 2_000);
+
+    suite.distribute_rewards("anyone").unwrap();
+    assert_eq!(suite.distributed_funds().unwrap(), 2_000);
+    assert_eq!(suite.undistributed_funds().unwrap(), 0);
+}
+
+#[test]
+fn invalid_slashes() {
+    let admin = "admin";
+    let member = "member";
+    let mut suite = SuiteBuilder::new()
+        .with_admin(admin)
+        .with_min_bond(1_000)
+        .with_initial_balances(vec![(member, 10_000, None)])
+        .build();
+    suite.delegate(member, 10_000, None).unwrap();
+
+    let err = suite
+        .slash_member(member, member, Decimal::percent(50), None)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Admin(AdminError::NotAdmin {}),
+        err.downcast().unwrap()
+    );
+
+    for portion in [Decimal::zero(), Decimal::percent(101)] {
+        let err = suite
+            .slash_member(admin, member, portion, None)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidSlashPortion {},
+            err.downcast().unwrap()
+        );
+    }
+
+    let err = suite
+        .slash_member(admin, member, Decimal::percent(50), 1234)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NoUnbondingPeriodFound(1234),
+        err.downcast().unwrap()
+    );
+
+    // only pending claims are left, which are never slashed
+    suite.unbond(member, 10_000, None).unwrap();
+    let err = suite
+        .slash_member(admin, member, Decimal::percent(50), None)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NothingToSlash {
+            address: member.to_owned()
+        },
+        err.downcast().unwrap()
+    );
+    assert_eq!(suite.query_total_unbonding().unwrap(), 10_000);
+}
@@ -6,7 +6,7 @@ use crate::error::ContractError;
 use crate::msg::{AllStakedResponse, StakedResponse};
 use crate::multitest::suite::SEVEN_DAYS;
 
-use super::suite::SuiteBuilder;
+use super::suite::{Suite, SuiteBuilder};
 use test_case::test_case;
 
 #[test]
@@ -235,18 +235,21 @@ fn one_user_multiple_unbonding_periods() {
                     total_locked: Uint128::zero(),
                     unbonding_period: 1000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
                 StakedResponse {
                     stake: Uint128::new(10_000),
                     total_locked: Uint128::zero(),
                     unbonding_period: 4000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
                 StakedResponse {
                     stake: Uint128::new(10_000),
                     total_locked: Uint128::zero(),
                     unbonding_period: 8000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
             ]
         }
@@ -265,6 +268,74 @@ fn one_user_multiple_unbonding_periods() {
     assert_eq!(periods[2].total_staked.u128(), 10_000);
 }
 
+#[test]
+fn batch_unbond_multiple_periods() {
+    let user = "user";
+    let unbonding_period1 = 1000u64;
+    let unbonding_period2 = 4000u64;
+    let unbonding_period3 = 8000u64;
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![
+            (unbonding_period1, Decimal::one()),
+            (unbonding_period2, Decimal::percent(200)),
+            (unbonding_period3, Decimal::percent(300)),
+        ])
+        .with_initial_balances(vec![(user, 60_000, None)])
+        .build();
+
+    suite.delegate(user, 20_000u128, unbonding_period1).unwrap();
+    suite.delegate(user, 20_000u128, unbonding_period2).unwrap();
+    suite.delegate(user, 20_000u128, unbonding_period3).unwrap();
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 120u128);
+    assert_eq!(suite.query_rewards(user).unwrap(), 60u128);
+
+    // all unbonds fail if any of them fails
+    let err = suite
+        .batch_unbond(
+            user,
+            vec![
+                (10_000, unbonding_period1),
+                (30_000, unbonding_period2),
+                (10_000, unbonding_period3),
+            ],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Std(StdError::Overflow { .. })
+    ));
+    assert_eq!(suite.query_staked(user, unbonding_period1).unwrap(), 20_000);
+    assert_eq!(suite.query_claims(user).unwrap().len(), 0);
+
+    suite
+        .batch_unbond(
+            user,
+            vec![
+                (10_000, unbonding_period1),
+                (10_000, unbonding_period2),
+                (10_000, unbonding_period3),
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(suite.query_staked(user, unbonding_period1).unwrap(), 10_000);
+    assert_eq!(suite.query_staked(user, unbonding_period2).unwrap(), 10_000);
+    assert_eq!(suite.query_staked(user, unbonding_period3).unwrap(), 10_000);
+    assert_eq!(suite.query_total_staked().unwrap(), 30_000);
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 60u128);
+    assert_eq!(suite.query_total_power(None).unwrap(), 60u128);
+    assert_eq!(suite.query_rewards(user).unwrap(), 30u128);
+    assert_eq!(suite.query_claims(user).unwrap().len(), 3);
+
+    // every unbond releases its tokens after its own unbonding period
+    suite.update_time(unbonding_period2);
+    suite.claim(user).unwrap();
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 20_000);
+    suite.update_time(unbonding_period3 - unbonding_period2);
+    suite.claim(user).unwrap();
+    assert_eq!(suite.query_balance_vesting_contract(user).unwrap(), 30_000);
+}
+
 #[test]
 fn one_user_multiple_periods_rebond_then_bond() {
     let user = "user";
@@ -332,18 +403,21 @@ fn one_user_multiple_periods_rebond_then_bond() {
                     total_locked: Uint128::zero(),
                     unbonding_period: 1000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
                 StakedResponse {
                     stake: Uint128::new(50_000),
                     total_locked: Uint128::zero(),
                     unbonding_period: 4000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
                 StakedResponse {
                     stake: Uint128::new(10_000),
                     total_locked: Uint128::zero(),
                     unbonding_period: 8000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
             ]
         }
@@ -498,18 +572,21 @@ fn rebond_then_rebond_again() {
                     total_locked: Uint128::zero(),
                     unbonding_period: 1000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
                 StakedResponse {
                     stake: Uint128::new(20_000),
                     total_locked: Uint128::zero(),
                     unbonding_period: 4000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
                 StakedResponse {
                     stake: Uint128::new(20_000),
                     total_locked: Uint128::zero(),
                     unbonding_period: 8000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
             ]
         }
@@ -637,11 +714,49 @@ fn multiple_users_multiple_unbonding_periods() {
     );
     assert_eq!(suite.query_voting_power(user1, None).unwrap(), 8u128); // 0.8 * 10_000
 
-    assert_eq!(suite.query_total_power(None).unwrap(), 39u128); // 8 + 18 + 13
+    // user3 has 0.6 * 16_000 + 0.8 * 6_000 = 9.6 + 4.8 = 14.4, truncated only once
+    assert_eq!(suite.query_total_power(None).unwrap(), 40u128); // 8 + 18 + 14
 
     assert_eq!(suite.query_rewards(user1).unwrap(), 6u128); // same as before
 
-    assert_eq!(suite.query_total_rewards().unwrap(), 27u128); // same as before
+    // user3 has 0.4 * 16_000 + 0.6 * 6_000 = 6.4 + 3.6 = 10
+    assert_eq!(suite.query_total_rewards().unwrap(), 28u128); // 6 + 12 + 10
+}
+
+#[test]
+fn powers_are_summed_before_truncation() {
+    let user = "user";
+    let unbonding_period1 = 1000u64;
+    let unbonding_period2 = 4000u64;
+    let unbonding_period3 = 8000u64;
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![
+            (unbonding_period1, Decimal::percent(25)),
+            (unbonding_period2, Decimal::percent(50)),
+            (unbonding_period3, Decimal::percent(75)),
+        ])
+        .with_min_bond(1_000)
+        .with_initial_balances(vec![(user, 100_000, None)])
+        .build();
+
+    // no single stake is worth a full point of power
+    suite.delegate(user, 2_000u128, unbonding_period1).unwrap();
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 0u128); // 0.25 * 2_000 = 0.5
+    suite.delegate(user, 1_000u128, unbonding_period2).unwrap();
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 1u128); // 0.5 + 0.5 * 1_000 = 1
+    suite.delegate(user, 3_999u128, unbonding_period3).unwrap();
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 3u128); // 1 + 0.75 * 3_999 = 3.99925
+    assert_eq!(suite.query_total_power(None).unwrap(), 3u128);
+    assert_eq!(suite.query_rewards(user).unwrap(), 6u128); // 2 + 1 + 3.999
+
+    // moving stake around does not lose power either
+    suite
+        .rebond(user, 1_000u128, unbonding_period1, unbonding_period2)
+        .unwrap();
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 4u128); // 0.25 + 1 + 2.99925
+    suite.unbond(user, 1_999u128, unbonding_period3).unwrap();
+    assert_eq!(suite.query_voting_power(user, None).unwrap(), 2u128); // 0.25 + 1 + 1.5
+    assert_eq!(suite.query_total_power(None).unwrap(), 2u128);
 }
 
 #[test]
@@ -681,6 +796,9 @@ fn one_user_rebond_decrease() {
     suite
         .rebond(user, 10_000u128, unbonding_period3, unbonding_period1)
         .unwrap();
+    let lock_release = suite
+        .block_time()
+        .plus_seconds(unbonding_period3 - unbonding_period1);
 
     assert_eq!(
         suite.query_staked(user, unbonding_period1).unwrap(),
@@ -737,18 +855,21 @@ fn one_user_rebond_decrease() {
                     total_locked: Uint128::new(10_000),
                     unbonding_period: 1000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: Some(lock_release),
                 },
                 StakedResponse {
                     stake: Uint128::new(30_000),
                     total_locked: Uint128::zero(),
                     unbonding_period: 4000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
                 StakedResponse {
                     stake: Uint128::zero(),
                     total_locked: Uint128::zero(),
                     unbonding_period: 8000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
             ]
         }
@@ -775,18 +896,21 @@ fn one_user_rebond_decrease() {
                     total_locked: Uint128::zero(),
                     unbonding_period: 1000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
                 StakedResponse {
                     stake: Uint128::new(30_000),
                     total_locked: Uint128::zero(),
                     unbonding_period: 4000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
                 StakedResponse {
                     stake: Uint128::new(5_000),
                     total_locked: Uint128::zero(),
                     unbonding_period: 8000,
                     cw20_contract: suite.vesting_contract(),
+                    earliest_unlock: None,
                 },
             ]
         }
@@ -1042,3 +1166,66 @@ fn delegate_unbond_under_min_bond() {
     );
     assert_eq!(suite.query_voting_power(user, None).unwrap(), 5u128); // only points from second unbonding_period counts now
 }
+
+#[test]
+fn claim_and_rebond_matches_separate_calls() {
+    let user = "user";
+    let unbonding_period1 = 1000u64;
+    let unbonding_period2 = 4000u64;
+    let setup = || {
+        let mut suite = SuiteBuilder::new()
+            .with_stake_config_voting(vec![
+                (unbonding_period1, Decimal::percent(50)),
+                (unbonding_period2, Decimal::one()),
+            ])
+            .with_initial_balances(vec![(user, 100_000, None)])
+            .build();
+        suite.delegate(user, 30_000u128, unbonding_period1).unwrap();
+        suite.delegate(user, 20_000u128, unbonding_period2).unwrap();
+        suite.unbond(user, 10_000u128, unbonding_period1).unwrap();
+        suite.update_time(unbonding_period1);
+        suite
+    };
+    let state = |suite: &Suite| {
+        (
+            suite.query_balance_vesting_contract(user).unwrap(),
+            suite.query_staked(user, unbonding_period1).unwrap(),
+            suite.query_staked(user, unbonding_period2).unwrap(),
+            suite.query_claims(user).unwrap(),
+            suite.query_voting_power(user, None).unwrap(),
+            suite.query_total_staked().unwrap(),
+        )
+    };
+
+    let mut separate = setup();
+    separate.claim(user).unwrap();
+    separate
+        .rebond(user, 5_000u128, unbonding_period1, unbonding_period2)
+        .unwrap();
+
+    let mut combined = setup();
+    combined
+        .claim_and_rebond(user, 5_000u128, unbonding_period1, unbonding_period2)
+        .unwrap();
+
+    let combined_state = state(&combined);
+    assert_eq!(combined_state, state(&separate));
+    // the claimed tokens left the contract and are not part of any stake
+    assert_eq!(combined_state.0, 60_000);
+    assert_eq!(combined_state.1, 15_000);
+    assert_eq!(combined_state.2, 25_000);
+    assert_eq!(combined_state.3, []);
+
+    // with nothing to claim, it only rebonds
+    combined
+        .claim_and_rebond(user, 5_000u128, unbonding_period1, unbonding_period2)
+        .unwrap();
+    assert_eq!(
+        combined.query_staked(user, unbonding_period2).unwrap(),
+        30_000
+    );
+    assert_eq!(
+        combined.query_balance_vesting_contract(user).unwrap(),
+        60_000
+    );
+}
@@ -1,22 +1,39 @@
 use anyhow::Result as AnyResult;
 
-use cosmwasm_std::{to_binary, Addr, Decimal, Empty, StdResult, Uint128};
-use cw20::BalanceResponse;
-use cw_controllers::{Claim, ClaimsResponse};
-use cw_core_interface::voting::VotingPowerAtHeightResponse;
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response,
+    StdError, StdResult, Storage, Timestamp, Uint128,
+};
+use cw20::{BalanceResponse, Cw20ReceiveMsg, TokenInfoResponse};
+use cw_controllers::{AdminResponse, Claim, ClaimsResponse};
+use cw_core_interface::voting::{InfoResponse, VotingPowerAtHeightResponse};
 use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use serde::Deserialize;
 
 use crate::msg::{
-    AllStakedResponse, BondingInfoResponse, BondingPeriodInfo, DelegatedResponse,
-    DistributedRewardsResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveDelegationMsg,
-    RewardsResponse, StakeConfig, StakedResponse, TotalRewardsResponse, TotalStakedResponse,
-    UndistributedRewardsResponse, WithdrawableRewardsResponse,
+    AdminStatusResponse, AllDelegationsResponse, AllLabelsResponse, AllStakedResponse,
+    BondingInfoResponse, BondingPeriodInfo, CanDistributeResponse, ClaimsFrozenResponse,
+    ConfigResponse, DelegatedResponse, DelegationInfo, DelegationReportResponse,
+    DistributedRewardsResponse, DistributionFeeInfo, DistributionLogEntry, DistributionLogResponse,
+    ExecuteMsg, FirstStakedResponse, HooksResponse, InstantiateMsg, LabelResponse, MigrateMsg,
+    MigrationProgressResponse, PayoutAddressResponse, PowerExclusionsResponse, QueryMsg,
+    ReceiveDelegationMsg, ReceiveMsg, ReceiverMode, RecomputeStatusResponse, RewardDecayInfo,
+    RewardSource, RewardsBreakdownResponse, RewardsResponse, ScheduledRebond,
+    ScheduledRebondsResponse, SlashDestination, StakeConfig, StakedResponse, TotalRewardsResponse,
+    TotalStakedResponse, TotalUnbondingResponse, UnbondRequest, UndistributedRewardsResponse,
+    WithdrawableRewardsResponse,
+};
+use crate::state::{FIRST_STAKED, STORAGE_VERSION_MARKER};
+use crate::ContractError;
+use cw20_vesting::msg::{
+    DelegatedResponse as VestingDelegatedResponse, UndelegatableAmountResponse, VestingResponse,
 };
 use cw20_vesting::{
     ExecuteMsg as VestingExecuteMsg, InitBalance, InstantiateMsg as VestingInstantiateMsg,
     MinterInfo, QueryMsg as VestingQueryMsg,
 };
-use wynd_utils::Curve;
+use wynd_utils::{Curve, ScalableCurve};
 
 pub const SEVEN_DAYS: u64 = 604800;
 
@@ -25,6 +42,93 @@ fn contract_stake() -> Box<dyn Contract<Empty>> {
         crate::contract::execute,
         crate::contract::instantiate,
         crate::contract::query,
+    )
+    .with_reply(crate::contract::reply)
+    .with_migrate(crate::contract::migrate);
+
+    Box::new(contract)
+}
+
+// removes the data that versions before staged migrations did not have
+fn strip_to_legacy_layout(storage: &mut dyn Storage) -> StdResult<()> {
+    STORAGE_VERSION_MARKER.remove(storage);
+    let first_staked = FIRST_STAKED
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for addr in first_staked {
+        FIRST_STAKED.remove(storage, &addr);
+    }
+    Ok(())
+}
+
+// stake contract keeping the storage layout of versions before staged migrations
+fn contract_stake_legacy() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        |mut deps: DepsMut,
+         env: Env,
+         info: MessageInfo,
+         msg: ExecuteMsg|
+         -> Result<Response, ContractError> {
+            let res = crate::contract::execute(deps.branch(), env, info, msg)?;
+            strip_to_legacy_layout(deps.storage)?;
+            Ok(res)
+        },
+        |mut deps: DepsMut,
+         env: Env,
+         info: MessageInfo,
+         msg: InstantiateMsg|
+         -> Result<Response, ContractError> {
+            let res = crate::contract::instantiate(deps.branch(), env, info, msg)?;
+            cw2::set_contract_version(deps.storage, "crates.io:wynd_stake", "1.6.0")?;
+            strip_to_legacy_layout(deps.storage)?;
+            Ok(res)
+        },
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply);
+
+    Box::new(contract)
+}
+
+// hook that always fails, like one running out of gas would
+fn contract_failing_hook() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
+            Err(StdError::generic_err("out of gas"))
+        },
+        |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |_: Deps, _: Env, _: Empty| -> StdResult<Binary> { to_binary(&Empty {}) },
+    );
+
+    Box::new(contract)
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum ReceiverExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+}
+
+const RECEIVED: Item<Vec<Cw20ReceiveMsg>> = Item::new("received");
+
+// contract recording every cw20 `Send` it receives, like a zapper would process them
+fn contract_receiver() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new_with_empty(
+        |deps: DepsMut, _: Env, _: MessageInfo, msg: ReceiverExecuteMsg| -> StdResult<Response> {
+            let ReceiverExecuteMsg::Receive(msg) = msg;
+            let mut received = RECEIVED.may_load(deps.storage)?.unwrap_or_default();
+            received.push(msg);
+            RECEIVED.save(deps.storage, &received)?;
+            Ok(Response::new())
+        },
+        |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |deps: Deps, _: Env, _: Empty| -> StdResult<Binary> {
+            to_binary(&RECEIVED.may_load(deps.storage)?.unwrap_or_default())
+        },
     );
 
     Box::new(contract)
@@ -48,6 +152,9 @@ pub struct SuiteBuilder {
     pub stake_config: Vec<StakeConfig>,
     pub admin: Option<String>,
     pub initial_balances: Vec<InitBalance>,
+    pub distribution_fee: Option<DistributionFeeInfo>,
+    pub vesting_voting_discount: Option<Decimal>,
+    pub legacy_layout: bool,
 }
 
 impl SuiteBuilder {
@@ -60,12 +167,23 @@ impl SuiteBuilder {
                 unbonding_period: SEVEN_DAYS,
                 voting_multiplier: Decimal::one(),
                 reward_multiplier: Decimal::one(),
+                exit_vesting: None,
             }],
             admin: None,
             initial_balances: vec![],
+            distribution_fee: None,
+            vesting_voting_discount: None,
+            legacy_layout: false,
         }
     }
 
+    /// Instantiates the stake contract with the storage layout from before staged migrations.
+    /// Use `Suite::migrate_stake` to migrate it to the current version.
+    pub fn with_legacy_layout(mut self) -> Self {
+        self.legacy_layout = true;
+        self
+    }
+
     pub fn with_initial_balances(
         mut self,
         balances: Vec<(&str, u128, impl Into<Option<Curve>>)>,
@@ -76,12 +194,32 @@ impl SuiteBuilder {
                 address: address.to_owned(),
                 amount: amount.into(),
                 vesting: vesting.into(),
+                memo: None,
+                relative_vesting: None,
             })
             .collect::<Vec<InitBalance>>();
         self.initial_balances = initial_balances;
         self
     }
 
+    pub fn with_admin(mut self, admin: &str) -> Self {
+        self.admin = Some(admin.to_owned());
+        self
+    }
+
+    pub fn with_distribution_fee(mut self, bps: u64, recipient: &str) -> Self {
+        self.distribution_fee = Some(DistributionFeeInfo {
+            bps,
+            recipient: recipient.to_owned(),
+        });
+        self
+    }
+
+    pub fn with_vesting_voting_discount(mut self, discount: Decimal) -> Self {
+        self.vesting_voting_discount = Some(discount);
+        self
+    }
+
     pub fn with_min_bond(mut self, min_bond: u128) -> Self {
         self.min_bond = min_bond.into();
         self
@@ -104,6 +242,7 @@ impl SuiteBuilder {
                     unbonding_period,
                     voting_multiplier,
                     reward_multiplier,
+                    exit_vesting: None,
                 },
             )
             .collect::<Vec<StakeConfig>>();
@@ -111,6 +250,16 @@ impl SuiteBuilder {
         self
     }
 
+    /// Sets the exit vesting schedule of the given, already configured unbonding period
+    pub fn with_exit_vesting(mut self, unbonding_period: u64, exit_vesting: ScalableCurve) -> Self {
+        self.stake_config
+            .iter_mut()
+            .find(|config| config.unbonding_period == unbonding_period)
+            .expect("unbonding period not configured")
+            .exit_vesting = Some(exit_vesting);
+        self
+    }
+
     #[track_caller]
     pub fn build(self) -> Suite {
         let mut app: App = App::default();
@@ -130,10 +279,12 @@ impl SuiteBuilder {
                     mint: Some(MinterInfo {
                         minter: "minter".to_owned(),
                         cap: None,
+                        burns_reduce_cap: false,
                     }),
                     marketing: None,
                     allowed_vesters: None,
                     max_curve_complexity: 10,
+                    seeding_open: false,
                 },
                 &[],
                 "vesting",
@@ -141,21 +292,28 @@ impl SuiteBuilder {
             )
             .unwrap();
 
-        let stake_id = app.store_code(contract_stake());
+        let stake_id = if self.legacy_layout {
+            app.store_code(contract_stake_legacy())
+        } else {
+            app.store_code(contract_stake())
+        };
         let stake_contract = app
             .instantiate_contract(
                 stake_id,
-                admin,
+                admin.clone(),
                 &InstantiateMsg {
                     cw20_contract: vesting_contract.to_string(),
                     tokens_per_power: self.tokens_per_power,
                     min_bond: self.min_bond,
                     stake_config: self.stake_config,
                     admin: self.admin,
+                    max_hooks: None,
+                    distribution_fee: self.distribution_fee,
+                    vesting_voting_discount: self.vesting_voting_discount,
                 },
                 &[],
                 "stake",
-                None,
+                Some(admin.to_string()),
             )
             .unwrap();
 
@@ -174,6 +332,7 @@ impl SuiteBuilder {
             app,
             stake_contract,
             vesting_contract,
+            vesting_id,
         }
     }
 }
@@ -182,6 +341,7 @@ pub struct Suite {
     app: App,
     stake_contract: Addr,
     vesting_contract: Addr,
+    vesting_id: u64,
 }
 
 impl Suite {
@@ -193,6 +353,85 @@ impl Suite {
         self.vesting_contract.to_string()
     }
 
+    // instantiates another token contract, i.e. to be used as a new reward token
+    pub fn instantiate_token(&mut self, initial_balances: Vec<(&str, u128)>) -> String {
+        let initial_balances = initial_balances
+            .into_iter()
+            .map(|(address, amount)| InitBalance {
+                address: address.to_owned(),
+                amount: amount.into(),
+                vesting: None,
+                memo: None,
+                relative_vesting: None,
+            })
+            .collect();
+        self.app
+            .instantiate_contract(
+                self.vesting_id,
+                Addr::unchecked("admin"),
+                &VestingInstantiateMsg {
+                    name: "reward".to_owned(),
+                    symbol: "REWARD".to_owned(),
+                    decimals: 9,
+                    initial_balances,
+                    mint: None,
+                    marketing: None,
+                    allowed_vesters: None,
+                    max_curve_complexity: 10,
+                    seeding_open: false,
+                },
+                &[],
+                "reward",
+                None,
+            )
+            .unwrap()
+            .to_string()
+    }
+
+    pub fn block_time(&self) -> Timestamp {
+        self.app.block_info().time
+    }
+
+    pub fn block_height(&self) -> u64 {
+        self.app.block_info().height
+    }
+
+    // instantiates a hook contract failing on every call
+    pub fn instantiate_failing_hook(&mut self) -> String {
+        let hook_id = self.app.store_code(contract_failing_hook());
+        self.app
+            .instantiate_contract(
+                hook_id,
+                Addr::unchecked("admin"),
+                &Empty {},
+                &[],
+                "hook",
+                None,
+            )
+            .unwrap()
+            .to_string()
+    }
+
+    // instantiates a contract recording the cw20 messages it receives
+    pub fn instantiate_receiver(&mut self) -> String {
+        let receiver_id = self.app.store_code(contract_receiver());
+        self.app
+            .instantiate_contract(
+                receiver_id,
+                Addr::unchecked("admin"),
+                &Empty {},
+                &[],
+                "receiver",
+                None,
+            )
+            .unwrap()
+            .to_string()
+    }
+
+    pub fn query_received(&self, receiver: &str) -> StdResult<Vec<Cw20ReceiveMsg>> {
+        self.app.wrap().query_wasm_smart(receiver, &Empty {})
+    }
+
     // update block's time to simulate passage of time
     pub fn update_time(&mut self, time_update: u64) {
         let mut block = self.app.block_info();
@@ -200,6 +439,12 @@ impl Suite {
         self.app.set_block(block);
     }
 
+    pub fn advance_blocks(&mut self, blocks: u64) {
+        let mut block = self.app.block_info();
+        block.height += blocks;
+        self.app.set_block(block);
+    }
+
     fn unbonding_period_or_default(&self, unbonding_period: impl Into<Option<u64>>) -> u64 {
         // Use default SEVEN_DAYS unbonding period if none provided
         if let Some(up) = unbonding_period.into() {
@@ -244,6 +489,64 @@ impl Suite {
                 tokens: amount.into(),
                 bond_from: self.unbonding_period_or_default(bond_from),
                 bond_to: self.unbonding_period_or_default(bond_to),
+                claim_first: false,
+            },
+            &[],
+        )
+    }
+
+    /// Rebonds after releasing all matured claims of `sender`
+    pub fn claim_and_rebond(
+        &mut self,
+        sender: &str,
+        amount: u128,
+        bond_from: u64,
+        bond_to: u64,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &ExecuteMsg::Rebond {
+                tokens: amount.into(),
+                bond_from,
+                bond_to,
+                claim_first: true,
+            },
+            &[],
+        )
+    }
+
+    pub fn schedule_rebond(
+        &mut self,
+        sender: &str,
+        amount: u128,
+        bond_from: u64,
+        bond_to: u64,
+        execute_after: Timestamp,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &ExecuteMsg::ScheduleRebond {
+                tokens: amount.into(),
+                bond_from,
+                bond_to,
+                execute_after,
+            },
+            &[],
+        )
+    }
+
+    pub fn execute_scheduled_rebonds(
+        &mut self,
+        executor: &str,
+        address: &str,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::ExecuteScheduledRebonds {
+                address: address.to_owned(),
             },
             &[],
         )
@@ -261,6 +564,96 @@ impl Suite {
             &ExecuteMsg::Unbond {
                 tokens: amount.into(),
                 unbonding_period: self.unbonding_period_or_default(unbonding_period),
+                claim_recipient: None,
+            },
+            &[],
+        )
+    }
+
+    // unbonds the sender's stake, creating the claim for `claim_recipient`
+    pub fn unbond_to(
+        &mut self,
+        sender: &str,
+        amount: u128,
+        unbonding_period: impl Into<Option<u64>>,
+        claim_recipient: &str,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &ExecuteMsg::Unbond {
+                tokens: amount.into(),
+                unbonding_period: self.unbonding_period_or_default(unbonding_period),
+                claim_recipient: Some(claim_recipient.to_owned()),
+            },
+            &[],
+        )
+    }
+
+    pub fn add_hook(
+        &mut self,
+        sender: &str,
+        addr: &str,
+        gas_limit: impl Into<Option<u64>>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &ExecuteMsg::AddHook {
+                addr: addr.to_owned(),
+                gas_limit: gas_limit.into(),
+            },
+            &[],
+        )
+    }
+
+    pub fn remove_hook(&mut self, sender: &str, addr: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &ExecuteMsg::RemoveHook {
+                addr: addr.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn update_admin(&mut self, sender: &str, admin: Option<&str>) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &ExecuteMsg::UpdateAdmin {
+                admin: admin.map(str::to_owned),
+            },
+            &[],
+        )
+    }
+
+    pub fn renounce_admin(&mut self, sender: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &ExecuteMsg::RenounceAdmin {},
+            &[],
+        )
+    }
+
+    pub fn batch_unbond(
+        &mut self,
+        sender: &str,
+        unbonds: Vec<(u128, u64)>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &ExecuteMsg::BatchUnbond {
+                unbonds: unbonds
+                    .into_iter()
+                    .map(|(amount, unbonding_period)| UnbondRequest {
+                        amount: amount.into(),
+                        unbonding_period,
+                    })
+                    .collect(),
             },
             &[],
         )
@@ -275,6 +668,23 @@ impl Suite {
         )
     }
 
+    pub fn cancel_claim(
+        &mut self,
+        sender: &str,
+        amount: u128,
+        unbonding_period: impl Into<Option<u64>>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.stake_contract.clone(),
+            &ExecuteMsg::CancelClaim {
+                amount: amount.into(),
+                unbonding_period: self.unbonding_period_or_default(unbonding_period),
+            },
+            &[],
+        )
+    }
+
     // call to vesting contract
     pub fn transfer(
         &mut self,
@@ -293,6 +703,28 @@ impl Suite {
         )
     }
 
+    // call to vesting contract
+    pub fn transfer_vesting(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: impl Into<Uint128>,
+        schedule: Curve,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.vesting_contract.clone(),
+            &VestingExecuteMsg::TransferVesting {
+                recipient: recipient.into(),
+                amount: amount.into(),
+                schedule,
+                memo: None,
+                notify: false,
+            },
+            &[],
+        )
+    }
+
     pub fn distribute_funds<'s>(
         &mut self,
         executor: &str,
@@ -310,51 +742,453 @@ impl Suite {
         )
     }
 
-    pub fn withdraw_funds<'s>(
+    // distributes rewards of several sources, which have to be transferred before
+    pub fn batch_distribute_funds(
         &mut self,
         executor: &str,
-        owner: impl Into<Option<&'s str>>,
-        receiver: impl Into<Option<&'s str>>,
+        sources: &[(Option<&str>, u128)],
     ) -> AnyResult<AppResponse> {
         self.app.execute_contract(
             Addr::unchecked(executor),
             self.stake_contract.clone(),
-            &ExecuteMsg::WithdrawRewards {
-                owner: owner.into().map(str::to_owned),
-                receiver: receiver.into().map(str::to_owned),
+            &ExecuteMsg::BatchDistributeRewards {
+                amounts: sources
+                    .iter()
+                    .map(|(sender, amount)| RewardSource {
+                        sender: sender.map(str::to_owned),
+                        amount: Uint128::new(*amount),
+                    })
+                    .collect(),
             },
             &[],
         )
     }
 
-    #[allow(dead_code)]
-    pub fn delegate_withdrawal(
+    // sends `funds` of given token to the stake contract with a cw20 hook to distribute them
+    pub fn fund_rewards(
         &mut self,
+        token: &str,
         executor: &str,
-        delegated: &str,
+        funds: u128,
     ) -> AnyResult<AppResponse> {
         self.app.execute_contract(
             Addr::unchecked(executor),
-            self.stake_contract.clone(),
-            &ExecuteMsg::DelegateWithdrawal {
-                delegated: delegated.to_owned(),
+            Addr::unchecked(token),
+            &VestingExecuteMsg::Send {
+                contract: self.stake_contract.to_string(),
+                amount: funds.into(),
+                msg: to_binary(&ReceiveMsg::Fund {})?,
             },
             &[],
         )
     }
 
-    pub fn withdrawable_rewards(&self, owner: &str) -> StdResult<u128> {
-        let resp: WithdrawableRewardsResponse = self.app.wrap().query_wasm_smart(
+    // transfers `funds` of given token to the stake contract and distributes them
+    pub fn distribute_token_funds(
+        &mut self,
+        token: &str,
+        executor: &str,
+        funds: u128,
+    ) -> AnyResult<AppResponse> {
+        self.transfer_token(token, executor, self.stake_contract.clone().as_str(), funds)?;
+        self.app.execute_contract(
+            Addr::unchecked(executor),
             self.stake_contract.clone(),
-            &QueryMsg::WithdrawableRewards {
-                owner: owner.to_owned(),
+            &ExecuteMsg::DistributeRewards { sender: None },
+            &[],
+        )
+    }
+
+    pub fn transfer_token(
+        &mut self,
+        token: &str,
+        sender: &str,
+        recipient: &str,
+        amount: impl Into<Uint128>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            Addr::unchecked(token),
+            &VestingExecuteMsg::Transfer {
+                recipient: recipient.into(),
+                amount: amount.into(),
             },
-        )?;
-        Ok(resp.rewards.u128())
+            &[],
+        )
     }
 
-    pub fn distributed_funds(&self) -> StdResult<u128> {
-        let resp: DistributedRewardsResponse = self.app.wrap().query_wasm_smart(
+    pub fn migrate_reward_token(
+        &mut self,
+        executor: &str,
+        new_token: &str,
+        exchange_rate: Decimal,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::MigrateRewardToken {
+                new_token: new_token.to_owned(),
+                exchange_rate,
+            },
+            &[],
+        )
+    }
+
+    pub fn continue_reward_migration<'s>(
+        &mut self,
+        executor: &str,
+        start_after: impl Into<Option<&'s str>>,
+        limit: impl Into<Option<u32>>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::ContinueRewardMigration {
+                start_after: start_after.into().map(str::to_owned),
+                limit: limit.into(),
+            },
+            &[],
+        )
+    }
+
+    pub fn start_reward_stream(
+        &mut self,
+        executor: &str,
+        total: u128,
+        duration_blocks: u64,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::StartRewardStream {
+                total: total.into(),
+                duration_blocks,
+            },
+            &[],
+        )
+    }
+
+    pub fn update_power_config(
+        &mut self,
+        executor: &str,
+        tokens_per_power: impl Into<Option<u128>>,
+        min_bond: impl Into<Option<u128>>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::UpdatePowerConfig {
+                tokens_per_power: tokens_per_power.into().map(Uint128::new),
+                min_bond: min_bond.into().map(Uint128::new),
+            },
+            &[],
+        )
+    }
+
+    pub fn exclude_from_power(&mut self, executor: &str, address: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::ExcludeFromPower {
+                address: address.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn freeze_claims(&mut self, executor: &str, address: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::FreezeClaims {
+                address: address.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn set_label(
+        &mut self,
+        executor: &str,
+        address: &str,
+        label: &str,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::SetLabel {
+                address: address.to_owned(),
+                label: label.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn remove_label(&mut self, executor: &str, address: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::RemoveLabel {
+                address: address.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn unfreeze_claims(&mut self, executor: &str, address: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::UnfreezeClaims {
+                address: address.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn include_in_power(&mut self, executor: &str, address: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::IncludeInPower {
+                address: address.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn update_distribution_fee(
+        &mut self,
+        executor: &str,
+        fee: Option<(u64, &str)>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::UpdateDistributionFee {
+                distribution_fee: fee.map(|(bps, recipient)| DistributionFeeInfo {
+                    bps,
+                    recipient: recipient.to_owned(),
+                }),
+            },
+            &[],
+        )
+    }
+
+    pub fn update_reward_decay(
+        &mut self,
+        executor: &str,
+        reward_decay: Option<(u64, Decimal)>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::UpdateRewardDecay {
+                reward_decay: reward_decay.map(|(inactivity_period, decay)| RewardDecayInfo {
+                    inactivity_period,
+                    decay,
+                }),
+            },
+            &[],
+        )
+    }
+
+    pub fn refresh_voting_discount(
+        &mut self,
+        executor: &str,
+        address: &str,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::RefreshVotingDiscount {
+                address: address.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn slash_member(
+        &mut self,
+        executor: &str,
+        address: &str,
+        portion: Decimal,
+        unbonding_period: impl Into<Option<u64>>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::SlashMember {
+                address: address.to_owned(),
+                portion,
+                unbonding_period: unbonding_period.into(),
+            },
+            &[],
+        )
+    }
+
+    pub fn update_slash_destination(
+        &mut self,
+        executor: &str,
+        destination: SlashDestination,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::UpdateSlashDestination { destination },
+            &[],
+        )
+    }
+
+    // distributes whatever is undistributed in the stake contract, without sending new funds
+    pub fn distribute_rewards(&mut self, executor: &str) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::DistributeRewards { sender: None },
+            &[],
+        )
+    }
+
+    pub fn recompute_members(
+        &mut self,
+        executor: &str,
+        start_after: Option<&str>,
+        limit: impl Into<Option<u32>>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::RecomputeMembers {
+                start_after: start_after.map(str::to_owned),
+                limit: limit.into(),
+            },
+            &[],
+        )
+    }
+
+    /// Migrates the stake contract to the current code
+    pub fn migrate_stake(&mut self) -> AnyResult<AppResponse> {
+        let stake_id = self.app.store_code(contract_stake());
+        self.app.migrate_contract(
+            Addr::unchecked("admin"),
+            self.stake_contract.clone(),
+            &MigrateMsg {},
+            stake_id,
+        )
+    }
+
+    pub fn continue_migration(
+        &mut self,
+        executor: &str,
+        limit: impl Into<Option<u32>>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::ContinueMigration {
+                limit: limit.into(),
+            },
+            &[],
+        )
+    }
+
+    pub fn withdraw_funds<'s>(
+        &mut self,
+        executor: &str,
+        owner: impl Into<Option<&'s str>>,
+        receiver: impl Into<Option<&'s str>>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::WithdrawRewards {
+                owner: owner.into().map(str::to_owned),
+                receiver: receiver.into().map(str::to_owned),
+                receiver_msg: None,
+            },
+            &[],
+        )
+    }
+
+    pub fn withdraw_funds_with_msg(
+        &mut self,
+        executor: &str,
+        receiver: &str,
+        receiver_msg: Binary,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::WithdrawRewards {
+                owner: None,
+                receiver: Some(receiver.to_owned()),
+                receiver_msg: Some(receiver_msg),
+            },
+            &[],
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn withdraw_rewards_batch(
+        &mut self,
+        executor: &str,
+        owners: &[&str],
+        receiver_mode: ReceiverMode,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::WithdrawRewardsBatch {
+                owners: owners.iter().map(|owner| (*owner).to_owned()).collect(),
+                receiver_mode,
+            },
+            &[],
+        )
+    }
+
+    pub fn delegate_withdrawal(
+        &mut self,
+        executor: &str,
+        delegated: &str,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::DelegateWithdrawal {
+                delegated: delegated.to_owned(),
+            },
+            &[],
+        )
+    }
+
+    pub fn set_payout_address(
+        &mut self,
+        executor: &str,
+        payout: Option<&str>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(executor),
+            self.stake_contract.clone(),
+            &ExecuteMsg::SetPayoutAddress {
+                payout: payout.map(str::to_owned),
+            },
+            &[],
+        )
+    }
+
+    pub fn withdrawable_rewards(&self, owner: &str) -> StdResult<u128> {
+        let resp: WithdrawableRewardsResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::WithdrawableRewards {
+                owner: owner.to_owned(),
+            },
+        )?;
+        Ok(resp.rewards.u128())
+    }
+
+    pub fn distributed_funds(&self) -> StdResult<u128> {
+        let resp: DistributedRewardsResponse = self.app.wrap().query_wasm_smart(
             self.stake_contract.clone(),
             &QueryMsg::DistributedRewards {},
         )?;
@@ -377,6 +1211,22 @@ impl Suite {
         Ok(resp.rewards.u128())
     }
 
+    pub fn can_distribute(&self) -> StdResult<CanDistributeResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::CanDistribute {})
+    }
+
+    pub fn payout_address(&self, owner: &str) -> StdResult<Option<Addr>> {
+        let resp: PayoutAddressResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::PayoutAddress {
+                owner: owner.to_owned(),
+            },
+        )?;
+        Ok(resp.payout)
+    }
+
     #[allow(dead_code)]
     pub fn delegated(&self, owner: &str) -> StdResult<Addr> {
         let resp: DelegatedResponse = self.app.wrap().query_wasm_smart(
@@ -399,6 +1249,48 @@ impl Suite {
         Ok(balance.balance.u128())
     }
 
+    // returns the amount of address' tokens delegated on vesting contract
+    pub fn query_delegated_vesting_contract(&self, address: &str) -> StdResult<u128> {
+        let delegated: VestingDelegatedResponse = self.app.wrap().query_wasm_smart(
+            self.vesting_contract.clone(),
+            &VestingQueryMsg::Delegated {
+                address: address.to_owned(),
+            },
+        )?;
+        Ok(delegated.delegated.u128())
+    }
+
+    // returns the amount of address' tokens still locked by vesting
+    pub fn query_vesting_locked(&self, address: &str) -> StdResult<u128> {
+        let vesting: VestingResponse = self.app.wrap().query_wasm_smart(
+            self.vesting_contract.clone(),
+            &VestingQueryMsg::Vesting {
+                address: address.to_owned(),
+            },
+        )?;
+        Ok(vesting.locked.u128())
+    }
+
+    // returns the total supply of the vesting contract
+    pub fn query_token_supply(&self) -> StdResult<u128> {
+        let info: TokenInfoResponse = self.app.wrap().query_wasm_smart(
+            self.vesting_contract.clone(),
+            &VestingQueryMsg::TokenInfo {},
+        )?;
+        Ok(info.total_supply.u128())
+    }
+
+    // returns address' balance on given token contract
+    pub fn query_token_balance(&self, token: &str, address: &str) -> StdResult<u128> {
+        let balance: BalanceResponse = self.app.wrap().query_wasm_smart(
+            token,
+            &VestingQueryMsg::Balance {
+                address: address.to_owned(),
+            },
+        )?;
+        Ok(balance.balance.u128())
+    }
+
     // returns address' balance on vesting contract
     pub fn query_balance_staking_contract(&self) -> StdResult<u128> {
         let balance: BalanceResponse = self.app.wrap().query_wasm_smart(
@@ -410,6 +1302,15 @@ impl Suite {
         Ok(balance.balance.u128())
     }
 
+    // returns how much the stake contract can currently undelegate on vesting contract
+    pub fn query_undelegatable_amount(&self) -> StdResult<u128> {
+        let res: UndelegatableAmountResponse = self.app.wrap().query_wasm_smart(
+            self.vesting_contract.clone(),
+            &VestingQueryMsg::UndelegatableAmount { staking: None },
+        )?;
+        Ok(res.amount.u128())
+    }
+
     pub fn query_staked(
         &self,
         address: &str,
@@ -438,6 +1339,9 @@ impl Suite {
             self.stake_contract.clone(),
             &QueryMsg::AllStaked {
                 address: address.to_owned(),
+                start_after: None,
+                limit: None,
+                include_empty: None,
             },
         )?;
         Ok(all_staked)
@@ -451,6 +1355,12 @@ impl Suite {
         Ok(total_staked.total_staked.u128())
     }
 
+    pub fn query_total_staked_by_period(&self) -> StdResult<TotalStakedResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::TotalStaked {})
+    }
+
     pub fn query_claims(&self, address: &str) -> StdResult<Vec<Claim>> {
         let claims: ClaimsResponse = self.app.wrap().query_wasm_smart(
             self.stake_contract.clone(),
@@ -461,6 +1371,43 @@ impl Suite {
         Ok(claims.claims)
     }
 
+    pub fn query_scheduled_rebonds(&self, address: &str) -> StdResult<Vec<ScheduledRebond>> {
+        let resp: ScheduledRebondsResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::ScheduledRebonds {
+                address: address.to_owned(),
+            },
+        )?;
+        Ok(resp.rebonds)
+    }
+
+    pub fn query_distribution_log(
+        &self,
+        start_after: impl Into<Option<u64>>,
+        limit: impl Into<Option<u32>>,
+        descending: bool,
+    ) -> StdResult<Vec<DistributionLogEntry>> {
+        let resp: DistributionLogResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::DistributionLog {
+                start_after: start_after.into(),
+                limit: limit.into(),
+                descending,
+            },
+        )?;
+        Ok(resp.entries)
+    }
+
+    pub fn query_claims_frozen(&self, address: &str) -> StdResult<bool> {
+        let resp: ClaimsFrozenResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::ClaimsFrozen {
+                address: address.to_owned(),
+            },
+        )?;
+        Ok(resp.frozen)
+    }
+
     pub fn query_voting_power(
         &self,
         address: &str,
@@ -497,6 +1444,148 @@ impl Suite {
         Ok(rewards.rewards.u128())
     }
 
+    pub fn query_rewards_breakdown(&self, address: &str) -> StdResult<RewardsBreakdownResponse> {
+        self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::RewardsBreakdown {
+                address: address.to_owned(),
+            },
+        )
+    }
+
+    pub fn query_power_exclusions(
+        &self,
+        start_after: Option<&str>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<Vec<Addr>> {
+        let resp: PowerExclusionsResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::PowerExclusions {
+                start_after: start_after.map(str::to_owned),
+                limit: limit.into(),
+            },
+        )?;
+        Ok(resp.addresses)
+    }
+
+    pub fn query_delegation_report(&self) -> StdResult<DelegationReportResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::DelegationReport {})
+    }
+
+    pub fn query_all_delegations(
+        &self,
+        start_after: Option<&str>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<Vec<DelegationInfo>> {
+        let resp: AllDelegationsResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::AllDelegations {
+                start_after: start_after.map(str::to_owned),
+                limit: limit.into(),
+            },
+        )?;
+        Ok(resp.delegations)
+    }
+
+    pub fn query_label(&self, address: &str) -> StdResult<Option<String>> {
+        let resp: LabelResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::Label {
+                address: address.to_owned(),
+            },
+        )?;
+        Ok(resp.label)
+    }
+
+    pub fn query_all_labels(
+        &self,
+        start_after: Option<&str>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<Vec<(String, String)>> {
+        let resp: AllLabelsResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::AllLabels {
+                start_after: start_after.map(str::to_owned),
+                limit: limit.into(),
+            },
+        )?;
+        Ok(resp
+            .labels
+            .into_iter()
+            .map(|label| (label.address.into_string(), label.label))
+            .collect())
+    }
+
+    pub fn query_recompute_status(&self) -> StdResult<RecomputeStatusResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::RecomputeStatus {})
+    }
+
+    pub fn query_admin_status(&self) -> StdResult<AdminStatusResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::AdminStatus {})
+    }
+
+    pub fn query_migration_progress(&self) -> StdResult<MigrationProgressResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::MigrationProgress {})
+    }
+
+    pub fn query_first_staked(&self, address: &str) -> StdResult<Option<Timestamp>> {
+        let resp: FirstStakedResponse = self.app.wrap().query_wasm_smart(
+            self.stake_contract.clone(),
+            &QueryMsg::FirstStaked {
+                address: address.to_owned(),
+            },
+        )?;
+        Ok(resp.first_staked)
+    }
+
+    pub fn query_config(&self) -> StdResult<ConfigResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::Config {})
+    }
+
+    pub fn query_admin(&self) -> StdResult<Option<String>> {
+        let resp: AdminResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::Admin {})?;
+        Ok(resp.admin)
+    }
+
+    pub fn query_token_contract(&self) -> StdResult<Addr> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::TokenContract {})
+    }
+
+    pub fn query_total_unbonding(&self) -> StdResult<u128> {
+        let resp: TotalUnbondingResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::TotalUnbonding {})?;
+        Ok(resp.total_unbonding.u128())
+    }
+
+    pub fn query_info(&self) -> StdResult<InfoResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::Info {})
+    }
+
+    pub fn query_hooks(&self) -> StdResult<HooksResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.stake_contract.clone(), &QueryMsg::Hooks {})
+    }
+
     pub fn query_total_rewards(&self) -> StdResult<u128> {
         let rewards: TotalRewardsResponse = self
             .app
@@ -0,0 +1,123 @@
+use cosmwasm_std::Decimal;
+use wynd_utils::Curve;
+
+use super::suite::{SuiteBuilder, SEVEN_DAYS};
+use crate::ContractError;
+
+const YEAR: u64 = 365 * 24 * 60 * 60;
+
+#[test]
+fn vesting_stake_votes_are_discounted_until_refreshed() {
+    let member = "member";
+    let other = "other";
+    let vester = "admin";
+    let mut suite = SuiteBuilder::new()
+        .with_min_bond(1_000)
+        .with_vesting_voting_discount(Decimal::percent(50))
+        .with_initial_balances(vec![
+            (member, 2_000, None),
+            (other, 10_000, None),
+            (vester, 8_000, None),
+            ("distributor", 2_000, None),
+        ])
+        .build();
+
+    // locked for a year, then released linearly over another year
+    let now = suite.block_time().seconds();
+    let schedule = Curve::saturating_linear((now + YEAR, 8_000), (now + 2 * YEAR, 0));
+    suite
+        .transfer_vesting(vester, member, 8_000u128, schedule)
+        .unwrap();
+
+    // 8_000 of the bonded tokens are vesting, their votes count half
+    suite.delegate(member, 10_000, SEVEN_DAYS).unwrap();
+    suite.delegate(other, 10_000, SEVEN_DAYS).unwrap();
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 6);
+    assert_eq!(suite.query_voting_power(other, None).unwrap(), 10);
+    assert_eq!(suite.query_total_power(None).unwrap(), 16);
+
+    // rewards are not discounted
+    suite.distribute_funds("distributor", None, 2_000).unwrap();
+    assert_eq!(suite.withdrawable_rewards(member).unwrap(), 1_000);
+    assert_eq!(suite.withdrawable_rewards(other).unwrap(), 1_000);
+
+    // unbonding releases the discounted part proportionally
+    suite.unbond(member, 5_000, SEVEN_DAYS).unwrap();
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 3);
+
+    // nothing unlocked yet
+    suite.refresh_voting_discount("anyone", member).unwrap();
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 3);
+
+    // a quarter of the vesting tokens is still locked
+    suite.update_time(YEAR + YEAR / 4 * 3);
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 3);
+    let resp = suite.refresh_voting_discount("anyone", member).unwrap();
+    let attr = |key: &str| {
+        resp.events
+            .iter()
+            .filter(|event| event.ty == "wasm")
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == key)
+            .map(|attr| attr.value.clone())
+    };
+    assert_eq!(attr("locked").as_deref(), Some("2000"));
+    assert_eq!(attr("discounted").as_deref(), Some("2000"));
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 4);
+    assert_eq!(suite.query_total_power(None).unwrap(), 14);
+
+    // fully vested, full votes
+    suite.update_time(YEAR / 4);
+    suite.refresh_voting_discount("anyone", member).unwrap();
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 5);
+    assert_eq!(suite.query_total_power(None).unwrap(), 15);
+
+    // later bonds of liquid tokens are not discounted
+    suite.update_time(SEVEN_DAYS);
+    suite.claim(member).unwrap();
+    suite.delegate(member, 5_000, SEVEN_DAYS).unwrap();
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 10);
+}
+
+#[test]
+fn refresh_requires_discount() {
+    let member = "member";
+    let mut suite = SuiteBuilder::new()
+        .with_initial_balances(vec![(member, 10_000, None)])
+        .build();
+    suite.delegate(member, 10_000, SEVEN_DAYS).unwrap();
+
+    let err = suite.refresh_voting_discount("anyone", member).unwrap_err();
+    assert_eq!(
+        ContractError::VotingDiscountDisabled {},
+        err.downcast().unwrap()
+    );
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 10);
+}
+
+#[test]
+fn canceled_claims_stay_discounted() {
+    let member = "member";
+    let vester = "admin";
+    let mut suite = SuiteBuilder::new()
+        .with_min_bond(1_000)
+        .with_vesting_voting_discount(Decimal::percent(50))
+        .with_initial_balances(vec![(member, 2_000, None), (vester, 8_000, None)])
+        .build();
+
+    let now = suite.block_time().seconds();
+    let schedule = Curve::saturating_linear((now + YEAR, 8_000), (now + 2 * YEAR, 0));
+    suite
+        .transfer_vesting(vester, member, 8_000u128, schedule)
+        .unwrap();
+    suite.delegate(member, 10_000, SEVEN_DAYS).unwrap();
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 6);
+
+    suite.unbond(member, 5_000, SEVEN_DAYS).unwrap();
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 3);
+
+    // the re-bonded tokens are still vesting
+    suite.cancel_claim(member, 5_000, SEVEN_DAYS).unwrap();
+    assert_eq!(suite.query_voting_power(member, None).unwrap(), 6);
+    assert_eq!(suite.query_total_power(None).unwrap(), 6);
+}
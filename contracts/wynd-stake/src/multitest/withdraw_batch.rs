@@ -0,0 +1,198 @@
+use cosmwasm_std::Decimal;
+use cw_multi_test::AppResponse;
+
+use super::suite::{Suite, SuiteBuilder};
+use crate::msg::ReceiverMode;
+use crate::ContractError;
+
+const UNBONDING_PERIOD: u64 = 1000;
+const MEMBERS: [&str; 4] = ["member1", "member2", "member3", "member4"];
+const BOT: &str = "bot";
+
+/// Members stake 1000, 2000, 3000 and 4000 tokens and get 1000 tokens of rewards distributed
+fn setup() -> Suite {
+    let mut suite = SuiteBuilder::new()
+        .with_stake_config_voting(vec![(UNBONDING_PERIOD, Decimal::one())])
+        .with_min_bond(1_000)
+        .with_initial_balances(vec![
+            (MEMBERS[0], 1_000u128, None),
+            (MEMBERS[1], 2_000u128, None),
+            (MEMBERS[2], 3_000u128, None),
+            (MEMBERS[3], 4_000u128, None),
+            ("distributor", 1_000u128, None),
+        ])
+        .build();
+
+    for (member, bond) in MEMBERS.iter().zip([1_000u128, 2_000, 3_000, 4_000]) {
+        suite.delegate(member, bond, UNBONDING_PERIOD).unwrap();
+    }
+    suite.distribute_funds("distributor", None, 1_000).unwrap();
+    suite
+}
+
+fn attributes(resp: &AppResponse, key: &str) -> Vec<String> {
+    resp.events
+        .iter()
+        .filter(|event| event.ty == "wasm")
+        .flat_map(|event| &event.attributes)
+        .filter(|attr| attr.key == key)
+        .map(|attr| attr.value.clone())
+        .collect()
+}
+
+#[test]
+fn batch_withdrawal_to_owner_wallets() {
+    let mut suite = setup();
+    for member in &MEMBERS[..3] {
+        suite.delegate_withdrawal(member, BOT).unwrap();
+    }
+    suite
+        .set_payout_address(MEMBERS[2], Some("cold_wallet"))
+        .unwrap();
+
+    let resp = suite
+        .withdraw_rewards_batch(BOT, &MEMBERS[..3], ReceiverMode::OwnerWallets)
+        .unwrap();
+    assert_eq!(
+        attributes(&resp, "withdrawn"),
+        ["member1:100", "member2:200", "member3:300"]
+    );
+    assert!(attributes(&resp, "skipped").is_empty());
+    assert_eq!(attributes(&resp, "reward"), ["600"]);
+
+    // every owner got their own rewards, the payout address is respected
+    assert_eq!(
+        suite.query_balance_vesting_contract(MEMBERS[0]).unwrap(),
+        100
+    );
+    assert_eq!(
+        suite.query_balance_vesting_contract(MEMBERS[1]).unwrap(),
+        200
+    );
+    assert_eq!(suite.query_balance_vesting_contract(MEMBERS[2]).unwrap(), 0);
+    assert_eq!(
+        suite.query_balance_vesting_contract("cold_wallet").unwrap(),
+        300
+    );
+    assert_eq!(suite.query_balance_vesting_contract(BOT).unwrap(), 0);
+    for member in &MEMBERS[..3] {
+        assert_eq!(suite.withdrawable_rewards(member).unwrap(), 0);
+    }
+    assert_eq!(suite.withdrawable_rewards(MEMBERS[3]).unwrap(), 400);
+}
+
+#[test]
+fn batch_withdrawal_to_single_receiver() {
+    let mut suite = setup();
+    for member in &MEMBERS[..3] {
+        suite.delegate_withdrawal(member, BOT).unwrap();
+    }
+
+    // the bot is not the delegate of member4
+    let err = suite
+        .withdraw_rewards_batch(
+            BOT,
+            &MEMBERS,
+            ReceiverMode::SingleReceiver("treasury".to_owned()),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NotWithdrawalDelegate {
+            owner: MEMBERS[3].to_owned()
+        },
+        err.downcast().unwrap()
+    );
+
+    suite.delegate_withdrawal(MEMBERS[3], BOT).unwrap();
+    let resp = suite
+        .withdraw_rewards_batch(
+            BOT,
+            &MEMBERS,
+            ReceiverMode::SingleReceiver("treasury".to_owned()),
+        )
+        .unwrap();
+    assert_eq!(attributes(&resp, "reward"), ["1000"]);
+    assert_eq!(attributes(&resp, "receiver"), ["treasury"]);
+
+    // all rewards arrive in one transfer
+    let transfers = resp
+        .events
+        .iter()
+        .filter(|event| {
+            event.ty == "wasm"
+                && event
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.key == "action" && attr.value == "transfer")
+        })
+        .count();
+    assert_eq!(transfers, 1);
+    assert_eq!(
+        suite.query_balance_vesting_contract("treasury").unwrap(),
+        1_000
+    );
+    for member in MEMBERS {
+        assert_eq!(suite.query_balance_vesting_contract(member).unwrap(), 0);
+        assert_eq!(suite.withdrawable_rewards(member).unwrap(), 0);
+    }
+}
+
+#[test]
+fn mixed_permission_batch() {
+    let mut suite = setup();
+    suite.delegate_withdrawal(MEMBERS[0], BOT).unwrap();
+    suite.delegate_withdrawal(MEMBERS[2], BOT).unwrap();
+
+    let resp = suite
+        .withdraw_rewards_batch(
+            BOT,
+            &[MEMBERS[0], MEMBERS[1], MEMBERS[2], "stranger", MEMBERS[0]],
+            ReceiverMode::OwnerWallets,
+        )
+        .unwrap();
+    assert_eq!(
+        attributes(&resp, "withdrawn"),
+        ["member1:100", "member3:300"]
+    );
+    assert_eq!(
+        attributes(&resp, "skipped"),
+        [
+            "stranger:no_rewards",
+            "member2:unauthorized",
+            "member1:no_rewards"
+        ]
+    );
+    assert_eq!(
+        suite.query_balance_vesting_contract(MEMBERS[0]).unwrap(),
+        100
+    );
+    assert_eq!(
+        suite.query_balance_vesting_contract(MEMBERS[2]).unwrap(),
+        300
+    );
+    assert_eq!(suite.withdrawable_rewards(MEMBERS[1]).unwrap(), 200);
+
+    // owners can always withdraw for themselves
+    suite
+        .withdraw_rewards_batch(MEMBERS[1], &[MEMBERS[1]], ReceiverMode::OwnerWallets)
+        .unwrap();
+    assert_eq!(
+        suite.query_balance_vesting_contract(MEMBERS[1]).unwrap(),
+        200
+    );
+
+    let err = suite
+        .withdraw_rewards_batch(BOT, &[], ReceiverMode::OwnerWallets)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::EmptyWithdrawBatch {},
+        err.downcast().unwrap()
+    );
+    let err = suite
+        .withdraw_rewards_batch(BOT, &[MEMBERS[0]; 31], ReceiverMode::OwnerWallets)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::TooManyWithdrawOwners { max: 30 },
+        err.downcast().unwrap()
+    );
+}
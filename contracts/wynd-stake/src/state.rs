@@ -1,13 +1,59 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Decimal, Env, OverflowError, Timestamp, Uint128};
-use cw_controllers::{Admin, Claims, Hooks};
+use cosmwasm_std::{
+    Addr, Decimal, Decimal256, Env, Order, OverflowError, StdResult, Storage, Timestamp, Uint128,
+    Uint256,
+};
+use cw_controllers::{Admin, Claim, Claims};
 use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
 
-use crate::msg::StakeConfig;
+use wynd_utils::ScalableCurve;
+
+use crate::msg::{
+    AdminAction, DistributionLogEntry, ScheduledRebond, SlashDestination, StakeConfig,
+};
 
 pub const CLAIMS: Claims = Claims::new("claims");
+/// Raw access to the claims managed by `CLAIMS`, used to release a single claim
+pub const CLAIMS_BY_ADDR: Map<&Addr, Vec<Claim>> = Map::new("claims");
+/// Amounts of all claims, indexed by the time they mature (in nanoseconds) and their owner.
+/// Kept in sync with `CLAIMS` to find matured claims across all addresses.
+pub const CLAIMS_BY_MATURITY: Map<(u64, &Addr), Uint128> = Map::new("claims_by_maturity");
+
+/// Adds a newly created claim to `CLAIMS_BY_MATURITY`
+pub fn index_claim(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    matures_at: Timestamp,
+    amount: Uint128,
+) -> StdResult<()> {
+    CLAIMS_BY_MATURITY.update(storage, (matures_at.nanos(), addr), |old| -> StdResult<_> {
+        Ok(old.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+/// Removes a released claim from `CLAIMS_BY_MATURITY`.
+/// Claims created before the index existed are not in it, so missing entries are ignored.
+pub fn unindex_claim(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    matures_at: Timestamp,
+    amount: Uint128,
+) -> StdResult<()> {
+    let key = (matures_at.nanos(), addr);
+    let remaining = CLAIMS_BY_MATURITY
+        .may_load(storage, key)?
+        .unwrap_or_default()
+        .saturating_sub(amount);
+    if remaining.is_zero() {
+        CLAIMS_BY_MATURITY.remove(storage, key);
+    } else {
+        CLAIMS_BY_MATURITY.save(storage, key, &remaining)?;
+    }
+    Ok(())
+}
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct Config {
@@ -17,14 +63,142 @@ pub struct Config {
     pub min_bond: Uint128,
     /// configured unbonding periods in seconds
     pub unbonding_periods: Vec<UnbondingPeriod>,
+    /// address of cw20 contract rewards are paid out in, `None` means the staked token
+    #[serde(default)]
+    pub reward_token: Option<Addr>,
+    /// maximum number of hooks that can be registered
+    #[serde(default = "default_max_hooks")]
+    pub max_hooks: u32,
+    /// part of every reward distribution sent to a fixed recipient before stakers get the rest
+    #[serde(default)]
+    pub distribution_fee: Option<DistributionFee>,
+    /// reduces rewards of stakers that did not interact with the contract for a while
+    #[serde(default)]
+    pub reward_decay: Option<RewardDecay>,
+    /// extra voting power in percent per full epoch staked without reducing stake
+    #[serde(default)]
+    pub loyalty_bonus_pct: u8,
+    /// upper bound of the loyalty bonus in percent
+    #[serde(default)]
+    pub max_loyalty_bonus_pct: u8,
+    /// where tokens removed by slashing go
+    #[serde(default)]
+    pub slash_destination: SlashDestination,
+    /// portion by which votes of still vesting stake are reduced
+    #[serde(default)]
+    pub vesting_voting_discount: Option<Decimal>,
+}
+
+/// Length of an epoch for the loyalty bonus, in seconds
+pub const LOYALTY_EPOCH: u64 = 7 * 24 * 60 * 60;
+
+/// Loyalty epoch in which the staker started their current streak, i.e. last bonded from zero
+/// or unbonded
+pub const LOYALTY_EPOCHS: Map<&Addr, u64> = Map::new("loyalty_epochs");
+
+/// Index of the loyalty epoch `time` falls in
+pub fn loyalty_epoch(time: Timestamp) -> u64 {
+    time.seconds() / LOYALTY_EPOCH
+}
+
+/// Number of full loyalty epochs `addr` has been staking without reducing their stake at `now`
+pub fn loyalty_epochs(storage: &dyn Storage, addr: &Addr, now: Timestamp) -> StdResult<u64> {
+    Ok(LOYALTY_EPOCHS
+        .may_load(storage, addr)?
+        .map(|start| loyalty_epoch(now).saturating_sub(start))
+        .unwrap_or_default())
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RewardDecay {
+    /// seconds without bonding, unbonding, rebonding or withdrawing after which a staker is
+    /// considered inactive
+    pub inactivity_period: u64,
+    /// part of the rewards earned while being inactive that a staker forfeits
+    pub decay: Decimal,
+    /// stakers that did not interact since the decay was enabled count as active from here
+    pub enabled_at: Timestamp,
+}
+
+impl RewardDecay {
+    /// Time in seconds from which a staker who last interacted at `last_interaction` is inactive
+    pub fn inactive_from(&self, last_interaction: Timestamp) -> u64 {
+        let since = last_interaction.max(self.enabled_at);
+        since.seconds().saturating_add(self.inactivity_period)
+    }
+}
+
+/// Last interaction of a staker, only tracked while reward decay is enabled
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RewardActivity {
+    pub last_interaction: Timestamp,
+    /// withdrawable rewards right after the last interaction, these never decay
+    pub settled_rewards: Uint128,
+}
+
+pub const REWARD_ACTIVITY: Map<&Addr, RewardActivity> = Map::new("reward_activity");
+/// `Distribution::shares_per_point` after the last change at or before the given time in
+/// seconds, recorded while reward decay is enabled. It tells how much of the rewards of
+/// a staker were earned after they became inactive.
+pub const SHARES_PER_POINT_HISTORY: Map<u64, Uint128> = Map::new("shares_per_point_history");
+/// Time in seconds a reward token migration last reset `Distribution::shares_per_point`,
+/// older entries of `SHARES_PER_POINT_HISTORY` do not apply anymore
+pub const SHARES_RESET_AT: Item<u64> = Item::new("shares_reset_at");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct DistributionFee {
+    /// fee in basis points of every distributed amount
+    pub bps: u64,
+    pub recipient: Addr,
+}
+
+/// Distribution fees can take at most 20% of a distribution
+pub const MAX_DISTRIBUTION_FEE_BPS: u64 = 2000;
+
+impl DistributionFee {
+    /// Part of `amount` that goes to the fee recipient
+    pub fn fee(&self, amount: u128) -> u128 {
+        Uint128::new(amount)
+            .multiply_ratio(self.bps, 10_000u64)
+            .u128()
+    }
+}
+
+pub const DEFAULT_MAX_HOOKS: u32 = 5;
+
+fn default_max_hooks() -> u32 {
+    DEFAULT_MAX_HOOKS
+}
+
+impl Config {
+    /// Returns the cw20 contract rewards are currently paid out in
+    pub fn reward_token(&self) -> &Addr {
+        self.reward_token.as_ref().unwrap_or(&self.cw20_contract)
+    }
+
+    /// Loyalty bonus in percent of a staker that kept their stake for `epochs` loyalty epochs
+    pub fn loyalty_bonus_pct(&self, epochs: u64) -> u64 {
+        epochs
+            .saturating_mul(self.loyalty_bonus_pct as u64)
+            .min(self.max_loyalty_bonus_pct as u64)
+    }
+
+    /// Voting multiplier of a staker that kept their stake for `epochs` loyalty epochs
+    pub fn loyalty_multiplier(&self, epochs: u64) -> Decimal {
+        Decimal::percent(100 + self.loyalty_bonus_pct(epochs))
+    }
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct BondingInfo {
     /// the amount of staked tokens which are not locked
     stake: Uint128,
-    pub votes: Uint128,
-    pub rewards: Uint128,
+    /// Voting power of this stake, kept fractional so that it can be summed up without losses.
+    /// Only the sum over all of a member's stakes is truncated, when it is written to `MEMBERS`.
+    pub votes: Decimal256,
+    /// Unweighted reward power of this stake. Like `votes`, only the (weighted) sum is truncated,
+    /// when it is written to `REWARDS`.
+    pub rewards: Decimal256,
     /// Vec of locked_tokens sorted by expiry timestamp
     locked_tokens: Vec<(Timestamp, Uint128)>,
 }
@@ -88,6 +262,15 @@ impl BondingInfo {
         locked_stake
     }
 
+    /// Return the earliest time any of the still locked tokens get released
+    pub fn earliest_unlock(&self, env: &Env) -> Option<Timestamp> {
+        // locked_tokens are sorted by expiry
+        self.locked_tokens
+            .iter()
+            .map(|(t, _)| *t)
+            .find(|t| t > &env.block.time)
+    }
+
     /// Return all locked tokens at a given block time that is all
     /// locked_tokens with a Timestamp > the block time passed in env as a param
     pub fn total_unlocked(&self, env: &Env) -> Uint128 {
@@ -111,28 +294,208 @@ impl BondingInfo {
     }
 }
 
+/// Progress of recomputing the powers of all members after the power config was changed
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug, Default)]
+pub struct RecomputeProgress {
+    /// Last address whose powers were recomputed, `None` if none yet
+    pub last_processed: Option<Addr>,
+    /// Number of addresses recomputed so far
+    pub processed: u64,
+}
+
+/// Version of the storage layout. Bumped whenever a migration has to back-fill data for existing
+/// stakers, which is done in stages by `ExecuteMsg::ContinueMigration`.
+pub const STORAGE_VERSION: u32 = 2;
+
+/// Storage layout version the data of this contract is complete for.
+/// Missing on contracts instantiated before staged migrations were introduced.
+pub const STORAGE_VERSION_MARKER: Item<u32> = Item::new("storage_version");
+
+/// Progress of a staged migration. All other messages are rejected while it is set.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug, Default)]
+pub struct MigrationProgress {
+    /// Storage layout version the data is back-filled for
+    pub target_version: u32,
+    /// Last address whose data was back-filled, `None` if none yet
+    pub last_processed: Option<Addr>,
+    /// Number of addresses processed so far
+    pub processed: u64,
+}
+
+pub const MIGRATION_PROGRESS: Item<MigrationProgress> = Item::new("migration_progress");
+
+/// Time each address staked for the first time. For stakers from before this was tracked, it is
+/// the time their data was back-filled by the staged migration.
+pub const FIRST_STAKED: Map<&Addr, Timestamp> = Map::new("first_staked");
+
+/// Configuration of a registered membership hook
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct HookConfig {
+    /// Gas limit for each call of this hook. If set, a failing call does not fail the
+    /// membership change, but is only recorded in `failures`.
+    pub gas_limit: Option<u64>,
+    /// Id of the replies to calls of this hook
+    pub reply_id: u64,
+    /// Number of calls of this hook that failed
+    pub failures: u64,
+}
+
 pub const ADMIN: Admin = Admin::new("admin");
-pub const HOOKS: Hooks = Hooks::new("cw4-hooks");
+/// Time the admin was renounced at. Once set, there is no admin anymore
+pub const ADMIN_RENOUNCED: Item<Timestamp> = Item::new("admin_renounced");
+/// Hooks as they were stored by `cw_controllers::Hooks`, only read when migrating
+pub const LEGACY_HOOKS: Item<Vec<Addr>> = Item::new("cw4-hooks");
+pub const HOOKS: Map<&Addr, HookConfig> = Map::new("hooks");
+/// Reverse lookup of the hook a reply belongs to
+pub const HOOK_REPLY_IDS: Map<u64, Addr> = Map::new("hook_reply_ids");
+/// Last reply id assigned to a hook
+pub const LAST_HOOK_REPLY_ID: Item<u64> = Item::new("last_hook_reply_id");
 pub const CONFIG: Item<Config> = Item::new("config");
+/// Set while the powers of some members are not recomputed under the current config yet
+pub const PENDING_RECOMPUTE: Item<RecomputeProgress> = Item::new("pending_recompute");
 
+/// Voting power of every member. Checkpoints and changelog are kept under the cw4 group keys,
+/// where older versions stored the powers as well, so the history stays readable. The cw4 member
+/// key itself holds the power as a cw4 `u64` weight (`CW4_MEMBERS`).
+/// Only written by `save_member_power`, which keeps both in sync.
 pub const MEMBERS: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
-    cw4::MEMBERS_KEY,
+    "members_power",
     cw4::MEMBERS_CHECKPOINTS,
     cw4::MEMBERS_CHANGELOG,
     Strategy::EveryBlock,
 );
+/// Current entries of `MEMBERS`, used to move powers without recording a change
+pub const MEMBERS_PRIMARY: Map<&Addr, Uint128> = Map::new("members_power");
+/// Powers as stored by older versions under the cw4 member key, only read when migrating
+pub const LEGACY_MEMBERS: Map<&Addr, Uint128> = Map::new(cw4::MEMBERS_KEY);
+/// `MEMBERS` as cw4 `u64` weights (see `cw4::member_key`), larger powers saturate.
+/// Like `MEMBERS`, the weights ignore `POWER_EXCLUDED`.
+pub const CW4_MEMBERS: Map<&Addr, u64> = Map::new(cw4::MEMBERS_KEY);
 /// Contains the total rewards per user
 pub const REWARDS: Map<&Addr, Uint128> = Map::new("rewards");
 
+/// Total voting power. Like `MEMBERS`, the history is kept under the cw4 group keys and the
+/// cw4 total key holds the `u64` weight (`CW4_TOTAL`).
+/// Only written by `save_total_votes`, which keeps both in sync.
 pub const TOTAL_VOTES: SnapshotItem<Uint128> = SnapshotItem::new(
-    "total",
-    "total__checkpoints",
-    "total__changelog",
+    "total_power",
+    cw4::TOTAL_KEY_CHECKPOINTS,
+    cw4::TOTAL_KEY_CHANGELOG,
     Strategy::EveryBlock,
 );
+/// Current value of `TOTAL_VOTES`, used to move it without recording a change
+pub const TOTAL_VOTES_PRIMARY: Item<Uint128> = Item::new("total_power");
+/// Total power as stored by older versions under the cw4 total key, only read when migrating
+pub const LEGACY_TOTAL_VOTES: Item<Uint128> = Item::new(cw4::TOTAL_KEY);
+/// `TOTAL_VOTES` as cw4 `u64` weight (see `cw4::TOTAL_KEY`), larger powers saturate
+pub const CW4_TOTAL: Item<u64> = Item::new(cw4::TOTAL_KEY);
+
+/// cw4 weights are `u64`, so larger powers saturate
+pub fn cw4_weight(power: Uint128) -> u64 {
+    u64::try_from(power.u128()).unwrap_or(u64::MAX)
+}
+
+/// Sets the voting power of `addr` in `MEMBERS` and `CW4_MEMBERS`, removing it if zero
+pub fn save_member_power(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    power: Uint128,
+    height: u64,
+) -> StdResult<()> {
+    if power.is_zero() {
+        MEMBERS.remove(storage, addr, height)?;
+        CW4_MEMBERS.remove(storage, addr);
+    } else {
+        MEMBERS.save(storage, addr, &power, height)?;
+        CW4_MEMBERS.save(storage, addr, &cw4_weight(power))?;
+    }
+    Ok(())
+}
+
+/// Sets the total voting power in `TOTAL_VOTES` and `CW4_TOTAL`
+pub fn save_total_votes(storage: &mut dyn Storage, total: Uint128, height: u64) -> StdResult<()> {
+    TOTAL_VOTES.save(storage, &total, height)?;
+    CW4_TOTAL.save(storage, &cw4_weight(total))
+}
+
+/// Moves the power of `addr` stored by older versions under the cw4 member key to `MEMBERS`,
+/// leaving its `u64` weight behind. Does nothing if it was moved already.
+pub fn migrate_member_power(storage: &mut dyn Storage, addr: &Addr) -> StdResult<()> {
+    if MEMBERS_PRIMARY.has(storage, addr) {
+        return Ok(());
+    }
+    // a moved weight does not parse as `Uint128` anymore
+    if let Ok(Some(power)) = LEGACY_MEMBERS.may_load(storage, addr) {
+        MEMBERS_PRIMARY.save(storage, addr, &power)?;
+        CW4_MEMBERS.save(storage, addr, &cw4_weight(power))?;
+    }
+    Ok(())
+}
 /// Contains the sum of all rewards
 pub const TOTAL_REWARDS: Item<Uint128> = Item::new("total_rewards");
 
+/// Highest weight the admin can assign to a single address
+pub const MAX_DISTRIBUTION_WEIGHT: u128 = 10;
+
+/// Highest voting or reward multiplier an unbonding period can be configured with
+pub const MAX_STAKE_MULTIPLIER: u128 = 10;
+
+/// Addresses whose voting power is excluded from governance, e.g. the treasury.
+/// Their stake, rewards and hooks are unaffected.
+pub const POWER_EXCLUDED: Map<&Addr, ()> = Map::new("power_excluded");
+
+/// Maximum number of pending scheduled rebonds per address
+pub const MAX_SCHEDULED_REBONDS: usize = 5;
+
+/// Rebonds queued by an address with `ExecuteMsg::ScheduleRebond`, in the order they were scheduled
+pub const SCHEDULED_REBONDS: Map<&Addr, Vec<ScheduledRebond>> = Map::new("scheduled_rebonds");
+
+/// Addresses whose claims cannot be released, e.g. after their key was compromised.
+/// Their claims keep their amounts and maturities.
+pub const CLAIMS_FROZEN: Map<&Addr, ()> = Map::new("claims_frozen");
+
+/// Maximum length of an address label, in characters
+pub const MAX_LABEL_LENGTH: usize = 64;
+/// Labels the admin gave to known addresses, for bookkeeping only
+pub const LABELS: Map<&Addr, String> = Map::new("labels");
+
+/// Height until which no new stake can be bonded, `None` if bonding was never locked
+pub const STAKING_LOCKED: Item<Option<u64>> = Item::new("staking_locked");
+
+/// Returns the height at which the staking lock expires, if it is still active
+pub fn staking_locked_until(storage: &dyn Storage, height: u64) -> StdResult<Option<u64>> {
+    let locked = STAKING_LOCKED.may_load(storage)?.flatten();
+    Ok(locked.filter(|&expires_at| height < expires_at))
+}
+
+/// Governance contracts allowed to record votes
+pub const GOVERNANCE_CONTRACTS: Item<Vec<Addr>> = Item::new("governance_contracts");
+
+/// Members of the admin multisig. While set, the contract itself is the admin and admin actions
+/// are executed through approved proposals.
+pub const ADMIN_MULTISIG: Item<Vec<Addr>> = Item::new("admin_multisig");
+/// Number of members that have to approve an admin action before it can be executed
+pub const MIN_APPROVALS: Item<u32> = Item::new("min_approvals");
+pub const ADMIN_PROPOSALS: Map<u64, AdminProposal> = Map::new("admin_proposals");
+pub const LAST_ADMIN_PROPOSAL_ID: Item<u64> = Item::new("last_admin_proposal_id");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct AdminProposal {
+    pub action: AdminAction,
+    pub proposer: Addr,
+    /// Time in seconds after which the proposal cannot be approved or executed anymore
+    pub expires_at: u64,
+    pub approvals: Vec<Addr>,
+    pub executed: bool,
+}
+/// Number of votes cast by each address, as recorded by the governance contracts
+pub const VOTES_CAST: Map<&Addr, u64> = Map::new("votes_cast");
+/// Height of the last vote cast by each address
+pub const LAST_VOTE_HEIGHT: Map<&Addr, u64> = Map::new("last_vote_height");
+/// Multiplier applied to the reward power of an address on top of the unbonding period
+/// multipliers. Addresses without an entry have a weight of 1.0.
+pub const DISTRIBUTION_WEIGHT: Map<&Addr, Decimal> = Map::new("distribution_weight");
+
 #[derive(Default, Serialize, Deserialize)]
 pub struct TokenInfo {
     // how many tokens are fully bonded
@@ -151,6 +514,62 @@ pub const TOTAL_STAKED: Item<TokenInfo> = Item::new("total_staked");
 
 pub const STAKE: Map<(&Addr, UnbondingPeriod), BondingInfo> = Map::new("stake");
 
+/// Part of each stake that was still vesting when bonded, so its votes are reduced by the
+/// `vesting_voting_discount`. Reduced proportionally when the stake shrinks.
+pub const VOTING_DISCOUNTED: Map<(&Addr, UnbondingPeriod), Uint128> = Map::new("voting_discounted");
+
+/// Stake of `addr` in the given unbonding period counted as vesting
+pub fn voting_discounted(
+    storage: &dyn Storage,
+    addr: &Addr,
+    unbonding_period: UnbondingPeriod,
+) -> StdResult<Uint128> {
+    Ok(VOTING_DISCOUNTED
+        .may_load(storage, (addr, unbonding_period))?
+        .unwrap_or_default())
+}
+
+/// Reduces the discounted stake of `addr` in proportion to `released` tokens leaving a stake of
+/// `stake` tokens. Returns the discounted tokens released with them.
+pub fn release_voting_discounted(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    unbonding_period: UnbondingPeriod,
+    stake: Uint128,
+    released: Uint128,
+) -> StdResult<Uint128> {
+    let discounted = voting_discounted(storage, addr, unbonding_period)?;
+    if discounted.is_zero() || stake.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    let remaining = discounted.multiply_ratio(stake.saturating_sub(released), stake);
+    save_voting_discounted(storage, addr, unbonding_period, remaining)?;
+    Ok(discounted - remaining)
+}
+
+/// Stake of `addr` counted as vesting, over all unbonding periods
+pub fn total_voting_discounted(storage: &dyn Storage, addr: &Addr) -> StdResult<Uint128> {
+    VOTING_DISCOUNTED
+        .prefix(addr)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, discounted)| discounted))
+        .sum()
+}
+
+pub fn save_voting_discounted(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    unbonding_period: UnbondingPeriod,
+    discounted: Uint128,
+) -> StdResult<()> {
+    if discounted.is_zero() {
+        VOTING_DISCOUNTED.remove(storage, (addr, unbonding_period));
+        Ok(())
+    } else {
+        VOTING_DISCOUNTED.save(storage, (addr, unbonding_period), &discounted)
+    }
+}
+
 /// Unbonding period in seconds
 type UnbondingPeriod = u64;
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
@@ -161,6 +580,9 @@ pub struct StakeMultipliers {
     pub reward: Decimal,
     /// Total staked - not a multiplier, but a total amount of tokens staked to this UnbondingPeriod
     pub staked: Uint128,
+    /// Vesting schedule for tokens claimed after unbonding from this UnbondingPeriod
+    #[serde(default)]
+    pub exit_vesting: Option<ScalableCurve>,
 }
 
 impl From<StakeConfig> for StakeMultipliers {
@@ -169,8 +591,96 @@ impl From<StakeConfig> for StakeMultipliers {
             voting: sc.voting_multiplier,
             reward: sc.reward_multiplier,
             staked: Uint128::zero(),
+            exit_vesting: sc.exit_vesting,
+        }
+    }
+}
+
+/// Part of a claim that is released into a vesting schedule instead of liquid tokens
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ExitVesting {
+    pub amount: Uint128,
+    /// x values are seconds relative to the time of the claim
+    pub schedule: ScalableCurve,
+}
+
+/// Exit vesting parts of claims, indexed by owner and the time the claims mature (in nanoseconds)
+pub const EXIT_VESTING: Map<(&Addr, u64), Vec<ExitVesting>> = Map::new("exit_vesting");
+
+/// Removes up to `amount` of exit vesting from the claims of `addr` maturing at the given time
+pub fn take_exit_vesting(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    matures_at: Timestamp,
+    amount: Uint128,
+) -> StdResult<Vec<ExitVesting>> {
+    let key = (addr, matures_at.nanos());
+    let mut remaining = match EXIT_VESTING.may_load(storage, key)? {
+        Some(remaining) => remaining,
+        None => return Ok(vec![]),
+    };
+
+    let mut taken = vec![];
+    let mut to_take = amount;
+    while !to_take.is_zero() && !remaining.is_empty() {
+        let part = &mut remaining[0];
+        let amount = part.amount.min(to_take);
+        taken.push(ExitVesting {
+            amount,
+            schedule: part.schedule.clone(),
+        });
+        to_take -= amount;
+        part.amount -= amount;
+        if part.amount.is_zero() {
+            remaining.remove(0);
         }
     }
+
+    if remaining.is_empty() {
+        EXIT_VESTING.remove(storage, key);
+    } else {
+        EXIT_VESTING.save(storage, key, &remaining)?;
+    }
+    Ok(taken)
+}
+
+/// Delegators of claims that were created for another recipient, together with the amounts.
+/// Indexed by recipient and the time the claims mature (in nanoseconds)
+pub const CLAIM_DELEGATORS: Map<(&Addr, u64), Vec<(Addr, Uint128)>> = Map::new("claim_delegators");
+
+/// Removes up to `amount` of third party delegations from the claims of `addr` maturing at the
+/// given time
+pub fn take_claim_delegators(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    matures_at: Timestamp,
+    amount: Uint128,
+) -> StdResult<Vec<(Addr, Uint128)>> {
+    let key = (addr, matures_at.nanos());
+    let mut remaining = match CLAIM_DELEGATORS.may_load(storage, key)? {
+        Some(remaining) => remaining,
+        None => return Ok(vec![]),
+    };
+
+    let mut taken = vec![];
+    let mut to_take = amount;
+    while !to_take.is_zero() && !remaining.is_empty() {
+        let (delegator, part) = &mut remaining[0];
+        let amount = (*part).min(to_take);
+        taken.push((delegator.clone(), amount));
+        to_take -= amount;
+        *part -= amount;
+        if part.is_zero() {
+            remaining.remove(0);
+        }
+    }
+
+    if remaining.is_empty() {
+        CLAIM_DELEGATORS.remove(storage, key);
+    } else {
+        CLAIM_DELEGATORS.save(storage, key, &remaining)?;
+    }
+    Ok(taken)
 }
 pub const STAKE_CONFIG: Map<UnbondingPeriod, StakeMultipliers> = Map::new("stake_config");
 
@@ -181,21 +691,101 @@ pub const STAKE_CONFIG: Map<UnbondingPeriod, StakeMultipliers> = Map::new("stake
 /// This value is not actually the scaling itself, but how much bits value should be shifted
 /// (for way more efficient division).
 ///
-/// 32, to have those 32 bits, but it reduces how much tokens may be handled by this contract
-/// (it is now 96-bit integer instead of 128). In original ERC2222 it is handled by 256-bit
-/// calculations, but I256 is missing and it is required for this.
+/// 32, to have those 32 bits. Distributions and withdrawals are calculated with 256-bit
+/// intermediates like in the original ERC2222, only the stored values are 128-bit.
 pub const SHARES_SHIFT: u8 = 32;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug, Default)]
 pub struct Distribution {
     /// How many shares is single point worth
     pub shares_per_point: Uint128,
-    /// Shares which were not fully distributed on previous distributions, and should be redistributed
-    pub shares_leftover: u64,
+    /// Shares which were not fully distributed on previous distributions, and should be redistributed.
+    /// Always lower than the total reward power at the time of the last distribution.
+    pub shares_leftover: Uint128,
     /// Total rewards distributed by this contract.
     pub distributed_total: Uint128,
     /// Total rewards not yet withdrawn.
     pub withdrawable_total: Uint128,
+    /// Total distribution fees sent out, not part of `distributed_total`.
+    #[serde(default, skip_serializing_if = "Uint128::is_zero")]
+    pub fees_total: Uint128,
+}
+
+impl Distribution {
+    /// Distributes `amount` of rewards between `total_points` of reward power.
+    ///
+    /// The remainder of the division is carried over to the next distribution, so that the rewards
+    /// credited to all members only ever differ from `distributed_total` by the rounding of each
+    /// member's withdrawable amount (less than one token per member), no matter how many
+    /// distributions there were.
+    pub fn distribute(&mut self, amount: u128, total_points: u128) {
+        let total_points = Uint256::from(total_points);
+        let points =
+            (Uint256::from(amount) << SHARES_SHIFT as u32) + Uint256::from(self.shares_leftover);
+        let points_per_share = points / total_points;
+        // the remainder is lower than `total_points`, so it always fits
+        self.shares_leftover = Uint128::try_from(points % total_points).unwrap();
+
+        // Everything goes back to 128-bits/16-bytes
+        // Full amount is added here to total withdrawable, as it should not be considered on its own
+        // on future distributions - even if because of calculation offsets it is not fully
+        // distributed, the error is handled by leftover.
+        self.shares_per_point += Uint128::try_from(points_per_share).unwrap();
+        self.distributed_total += Uint128::new(amount);
+        self.withdrawable_total += Uint128::new(amount);
+    }
+}
+
+/// Progress of a reward token migration. All other messages are rejected while it is set,
+/// as the unclaimed rewards of only some accounts are converted yet.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RewardMigration {
+    pub new_token: Addr,
+    /// New tokens per old token
+    pub exchange_rate: Decimal,
+    /// Receiver of the old reward tokens left once the migration is finished
+    pub admin: Addr,
+    /// Last address whose rewards were converted, `None` if none yet
+    pub last_processed: Option<Addr>,
+    /// Unclaimed rewards converted so far, in the old token
+    pub unclaimed: Uint128,
+    /// Unclaimed rewards converted so far, in the new token
+    pub migrated: Uint128,
+}
+
+pub const REWARD_MIGRATION: Item<RewardMigration> = Item::new("reward_migration");
+
+/// Rewards released linearly block by block on top of regular distributions
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RewardStream {
+    /// Total amount of rewards released over the whole stream
+    pub total: Uint128,
+    pub start_height: u64,
+    pub end_height: u64,
+    /// Amount of rewards already released to stakers
+    pub distributed_so_far: Uint128,
+}
+
+impl RewardStream {
+    /// Rewards released up to the given height which were not distributed yet
+    pub fn pending(&self, height: u64) -> Uint128 {
+        let elapsed = height
+            .min(self.end_height)
+            .saturating_sub(self.start_height);
+        let released = self
+            .total
+            .multiply_ratio(elapsed, self.end_height - self.start_height);
+        released - self.distributed_so_far
+    }
+
+    /// Rewards reserved for the stream which were not released yet
+    pub fn remaining(&self) -> Uint128 {
+        self.total - self.distributed_so_far
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.distributed_so_far == self.total
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
@@ -210,8 +800,42 @@ pub struct WithdrawAdjustment {
 
 /// Rewards distribution data
 pub const DISTRIBUTION: Item<Distribution> = Item::new("distribution");
+/// Distribution data as stored by older versions, with a 64-bit leftover. Only read when migrating.
+pub const LEGACY_DISTRIBUTION: Item<LegacyDistribution> = Item::new("distribution");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct LegacyDistribution {
+    pub shares_per_point: Uint128,
+    pub shares_leftover: u64,
+    pub distributed_total: Uint128,
+    pub withdrawable_total: Uint128,
+    #[serde(default)]
+    pub fees_total: Uint128,
+}
+
+impl From<LegacyDistribution> for Distribution {
+    fn from(legacy: LegacyDistribution) -> Self {
+        Distribution {
+            shares_per_point: legacy.shares_per_point,
+            shares_leftover: legacy.shares_leftover.into(),
+            distributed_total: legacy.distributed_total,
+            withdrawable_total: legacy.withdrawable_total,
+            fees_total: legacy.fees_total,
+        }
+    }
+}
+/// Number of distributions kept in `DISTRIBUTION_LOG`, older ones are pruned
+pub const DISTRIBUTION_LOG_SIZE: u64 = 1000;
+/// Latest distributions by sequence number
+pub const DISTRIBUTION_LOG: Map<u64, DistributionLogEntry> = Map::new("distribution_log");
+/// Sequence number of the next distribution
+pub const NEXT_DISTRIBUTION_SEQ: Item<u64> = Item::new("next_distribution_seq");
+/// Currently active reward stream, if any
+pub const REWARD_STREAM: Item<RewardStream> = Item::new("reward_stream");
 /// Information how to exactly adjust rewards while withdrawal
 pub const WITHDRAW_ADJUSTMENT: Map<&Addr, WithdrawAdjustment> = Map::new("withdraw_adjustment");
+/// Address that receives all rewards withdrawn for the owner, no matter who withdraws them
+pub const PAYOUT_ADDRESS: Map<&Addr, Addr> = Map::new("payout_address");
 
 #[cfg(test)]
 mod tests {
@@ -303,4 +927,48 @@ mod tests {
         assert_eq!(info.total_stake(), Uint128::new(1500));
         assert_eq!(info.total_locked(&env), Uint128::new(1000u128));
     }
+
+    #[test]
+    fn test_reward_stream_pending() {
+        let mut stream = RewardStream {
+            total: Uint128::new(1000),
+            start_height: 100,
+            end_height: 110,
+            distributed_so_far: Uint128::zero(),
+        };
+
+        assert_eq!(stream.pending(100), Uint128::zero());
+        assert_eq!(stream.pending(103), Uint128::new(300));
+
+        stream.distributed_so_far += stream.pending(103);
+        assert_eq!(stream.pending(103), Uint128::zero());
+        assert_eq!(stream.pending(105), Uint128::new(200));
+        assert_eq!(stream.remaining(), Uint128::new(700));
+        assert!(!stream.is_finished());
+
+        // nothing more is released after the end of the stream
+        assert_eq!(stream.pending(200), Uint128::new(700));
+        stream.distributed_so_far += stream.pending(200);
+        assert!(stream.is_finished());
+        assert_eq!(stream.pending(300), Uint128::zero());
+    }
+
+    #[test]
+    fn test_reward_decay_inactive_from() {
+        let decay = RewardDecay {
+            inactivity_period: 100,
+            decay: Decimal::percent(30),
+            enabled_at: Timestamp::from_seconds(1000),
+        };
+
+        // interactions before enabling the decay count from `enabled_at`
+        assert_eq!(decay.inactive_from(Timestamp::from_seconds(0)), 1100);
+        assert_eq!(decay.inactive_from(Timestamp::from_seconds(1050)), 1150);
+
+        let decay = RewardDecay {
+            inactivity_period: u64::MAX,
+            ..decay
+        };
+        assert_eq!(decay.inactive_from(Timestamp::from_seconds(1050)), u64::MAX);
+    }
 }
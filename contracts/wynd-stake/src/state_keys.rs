@@ -0,0 +1,183 @@
+//! Raw storage keys of the contract state, for clients that read it directly
+//! (e.g. from a state sync export) instead of using smart queries.
+//!
+//! The layout is part of the contract interface and must not change without a migration:
+//! - `Item`s are stored directly under their namespace.
+//! - `Map` keys are the namespace, prefixed with its length, followed by the key.
+//!   In composite keys, every element but the last is prefixed with its length as well.
+//! - Length prefixes are 2 bytes big endian, `u64`s are stored as 8 bytes big endian
+//!   and addresses as their (bech32) string bytes.
+//!
+//! Values are JSON encoded. Snapshotted maps and items (`MEMBERS` and `TOTAL_VOTES`) additionally
+//! keep checkpoints and changelogs under the `cw4` namespaces, which are not covered here.
+
+use crate::msg::{KeyEncoding, StorageLayout};
+
+/// Namespaces and key encodings of all maps and items in [`crate::state`]
+#[rustfmt::skip]
+pub const LAYOUT: &[(&str, &str, KeyEncoding)] = &[
+    ("ADMIN", "admin", KeyEncoding::Item),
+    ("ADMIN_MULTISIG", "admin_multisig", KeyEncoding::Item),
+    ("ADMIN_PROPOSALS", "admin_proposals", KeyEncoding::U64),
+    ("ADMIN_RENOUNCED", "admin_renounced", KeyEncoding::Item),
+    ("CLAIMS", "claims", KeyEncoding::Addr),
+    ("CLAIMS_BY_MATURITY", "claims_by_maturity", KeyEncoding::U64Addr),
+    ("CLAIMS_FROZEN", "claims_frozen", KeyEncoding::Addr),
+    ("CLAIM_DELEGATORS", "claim_delegators", KeyEncoding::AddrU64),
+    ("CONFIG", "config", KeyEncoding::Item),
+    ("CW4_MEMBERS", cw4::MEMBERS_KEY, KeyEncoding::Addr),
+    ("CW4_TOTAL", cw4::TOTAL_KEY, KeyEncoding::Item),
+    ("DISTRIBUTION", "distribution", KeyEncoding::Item),
+    ("DISTRIBUTION_LOG", "distribution_log", KeyEncoding::U64),
+    ("DISTRIBUTION_WEIGHT", "distribution_weight", KeyEncoding::Addr),
+    ("EXIT_VESTING", "exit_vesting", KeyEncoding::AddrU64),
+    ("FIRST_STAKED", "first_staked", KeyEncoding::Addr),
+    ("GOVERNANCE_CONTRACTS", "governance_contracts", KeyEncoding::Item),
+    ("HOOKS", "hooks", KeyEncoding::Addr),
+    ("HOOK_REPLY_IDS", "hook_reply_ids", KeyEncoding::U64),
+    ("LABELS", "labels", KeyEncoding::Addr),
+    ("LAST_ADMIN_PROPOSAL_ID", "last_admin_proposal_id", KeyEncoding::Item),
+    ("LAST_HOOK_REPLY_ID", "last_hook_reply_id", KeyEncoding::Item),
+    ("LAST_VOTE_HEIGHT", "last_vote_height", KeyEncoding::Addr),
+    ("LEGACY_HOOKS", "cw4-hooks", KeyEncoding::Item),
+    ("LOYALTY_EPOCHS", "loyalty_epochs", KeyEncoding::Addr),
+    ("MEMBERS", "members_power", KeyEncoding::Addr),
+    ("MIGRATION_PROGRESS", "migration_progress", KeyEncoding::Item),
+    ("MIN_APPROVALS", "min_approvals", KeyEncoding::Item),
+    ("NEXT_DISTRIBUTION_SEQ", "next_distribution_seq", KeyEncoding::Item),
+    ("PAYOUT_ADDRESS", "payout_address", KeyEncoding::Addr),
+    ("PENDING_RECOMPUTE", "pending_recompute", KeyEncoding::Item),
+    ("POWER_EXCLUDED", "power_excluded", KeyEncoding::Addr),
+    ("REWARDS", "rewards", KeyEncoding::Addr),
+    ("REWARD_ACTIVITY", "reward_activity", KeyEncoding::Addr),
+    ("REWARD_MIGRATION", "reward_migration", KeyEncoding::Item),
+    ("REWARD_STREAM", "reward_stream", KeyEncoding::Item),
+    ("SCHEDULED_REBONDS", "scheduled_rebonds", KeyEncoding::Addr),
+    ("SHARES_PER_POINT_HISTORY", "shares_per_point_history", KeyEncoding::U64),
+    ("SHARES_RESET_AT", "shares_reset_at", KeyEncoding::Item),
+    ("STAKE", "stake", KeyEncoding::AddrU64),
+    ("STAKE_CONFIG", "stake_config", KeyEncoding::U64),
+    ("STAKING_LOCKED", "staking_locked", KeyEncoding::Item),
+    ("STORAGE_VERSION_MARKER", "storage_version", KeyEncoding::Item),
+    ("TOTAL_REWARDS", "total_rewards", KeyEncoding::Item),
+    ("TOTAL_STAKED", "total_staked", KeyEncoding::Item),
+    ("TOTAL_VOTES", "total_power", KeyEncoding::Item),
+    ("VOTES_CAST", "votes_cast", KeyEncoding::Addr),
+    ("VOTING_DISCOUNTED", "voting_discounted", KeyEncoding::AddrU64),
+    ("WITHDRAW_ADJUSTMENT", "withdraw_adjustment", KeyEncoding::Addr),
+];
+
+/// Returns [`LAYOUT`] in the shape of the `RawKeyLayout` query response
+pub fn storage_layout() -> Vec<StorageLayout> {
+    LAYOUT
+        .iter()
+        .map(|&(name, namespace, key)| StorageLayout {
+            name: name.to_owned(),
+            namespace: namespace.to_owned(),
+            key,
+        })
+        .collect()
+}
+
+fn length_prefixed(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() + 2);
+    out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    out.extend_from_slice(bytes);
+    out
+}
+
+/// Key of an `Item`
+pub fn item_key(namespace: &str) -> Vec<u8> {
+    namespace.as_bytes().to_vec()
+}
+
+/// Key of a map entry with a single element key
+pub fn map_key(namespace: &str, key: &[u8]) -> Vec<u8> {
+    let mut out = length_prefixed(namespace.as_bytes());
+    out.extend_from_slice(key);
+    out
+}
+
+/// Key of a map entry with a `(prefix, key)` composite key
+pub fn composite_key(namespace: &str, prefix: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut out = length_prefixed(namespace.as_bytes());
+    out.extend(length_prefixed(prefix));
+    out.extend_from_slice(key);
+    out
+}
+
+/// Key of the bonding info of `addr` in the given unbonding period (`STAKE`)
+pub fn stake_key(addr: &str, unbonding_period: u64) -> Vec<u8> {
+    composite_key("stake", addr.as_bytes(), &unbonding_period.to_be_bytes())
+}
+
+/// Key of the discounted stake of `addr` in the given unbonding period (`VOTING_DISCOUNTED`)
+pub fn voting_discounted_key(addr: &str, unbonding_period: u64) -> Vec<u8> {
+    composite_key(
+        "voting_discounted",
+        addr.as_bytes(),
+        &unbonding_period.to_be_bytes(),
+    )
+}
+
+/// Key of the voting power of `addr` (`MEMBERS`)
+pub fn member_key(addr: &str) -> Vec<u8> {
+    map_key("members_power", addr.as_bytes())
+}
+
+/// Key of the total voting power (`TOTAL_VOTES`)
+pub fn total_votes_key() -> Vec<u8> {
+    item_key("total_power")
+}
+
+/// Key of the cw4 `u64` weight of `addr` (`CW4_MEMBERS`), equal to `cw4::member_key`
+pub fn cw4_member_key(addr: &str) -> Vec<u8> {
+    map_key(cw4::MEMBERS_KEY, addr.as_bytes())
+}
+
+/// Key of the cw4 `u64` total weight (`CW4_TOTAL`), equal to `cw4::TOTAL_KEY`
+pub fn cw4_total_key() -> Vec<u8> {
+    item_key(cw4::TOTAL_KEY)
+}
+
+/// Key of the pending claims of `addr` (`CLAIMS`)
+pub fn claims_key(addr: &str) -> Vec<u8> {
+    map_key("claims", addr.as_bytes())
+}
+
+/// Key of the amount of the claim of `addr` maturing at the given time in nanoseconds
+/// (`CLAIMS_BY_MATURITY`)
+pub fn claims_by_maturity_key(maturity: u64, addr: &str) -> Vec<u8> {
+    composite_key(
+        "claims_by_maturity",
+        &maturity.to_be_bytes(),
+        addr.as_bytes(),
+    )
+}
+
+/// Key of the reward power of `addr` (`REWARDS`)
+pub fn rewards_key(addr: &str) -> Vec<u8> {
+    map_key("rewards", addr.as_bytes())
+}
+
+/// Key of the withdraw adjustment of `addr` (`WITHDRAW_ADJUSTMENT`)
+pub fn withdraw_adjustment_key(addr: &str) -> Vec<u8> {
+    map_key("withdraw_adjustment", addr.as_bytes())
+}
+
+/// Key of the multipliers of the given unbonding period (`STAKE_CONFIG`)
+pub fn stake_config_key(unbonding_period: u64) -> Vec<u8> {
+    map_key("stake_config", &unbonding_period.to_be_bytes())
+}
+
+/// Key of the exit vesting parts of the claims of `addr` maturing at the given time
+/// in nanoseconds (`EXIT_VESTING`)
+pub fn exit_vesting_key(addr: &str, maturity: u64) -> Vec<u8> {
+    composite_key("exit_vesting", addr.as_bytes(), &maturity.to_be_bytes())
+}
+
+/// Key of the delegators of the claims of `addr` maturing at the given time
+/// in nanoseconds (`CLAIM_DELEGATORS`)
+pub fn claim_delegators_key(addr: &str, maturity: u64) -> Vec<u8> {
+    composite_key("claim_delegators", addr.as_bytes(), &maturity.to_be_bytes())
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use thiserror::Error;
 
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Uint128, Uint256};
 
 /// Handle Contract Errors
 #[derive(Error, Debug, Eq, PartialEq)]
@@ -33,6 +33,40 @@ pub enum CurveError {
     /// Prevents vesting curves from becoming too complex, rendering the account useless.
     #[error("Curve is too complex")]
     TooComplex,
+
+    /// There is no step at the given x
+    #[error("No step found at the given point")]
+    StepNotFound {},
+
+    /// Compression tolerance is larger than 1% of the curve's maximum value
+    #[error("Tolerance must not exceed 1% of the curve's maximum value")]
+    ToleranceTooLarge {},
+
+    /// Combining curves results in values that do not fit into a Uint128
+    #[error("Curve value overflows")]
+    Overflow {},
+
+    /// Curve takes a value outside of the allowed range
+    #[error("Curve value {value} is outside of the allowed range [{min}, {max}]")]
+    OutOfBounds {
+        /// offending value
+        value: Uint128,
+        /// lower bound of the allowed range
+        min: Uint128,
+        /// upper bound of the allowed range
+        max: Uint128,
+    },
+}
+
+/// returns an error if either extreme of a curve lies outside of [min, max]
+fn check_bounds((low, high): (u128, u128), min: Uint128, max: Uint128) -> Result<(), CurveError> {
+    [low, high]
+        .into_iter()
+        .map(Uint128::new)
+        .find(|value| *value < min || *value > max)
+        .map_or(Ok(()), |value| {
+            Err(CurveError::OutOfBounds { value, min, max })
+        })
 }
 
 /// Curve types
@@ -125,6 +159,16 @@ impl Curve {
         }
     }
 
+    /// returns an error if the curve ever takes a value outside of [min_y, max_y]
+    pub fn validate_bounded(&self, min_y: Uint128, max_y: Uint128) -> Result<(), CurveError> {
+        match self {
+            Curve::Constant { .. } | Curve::SaturatingLinear(_) => {
+                check_bounds(self.range(), min_y, max_y)
+            }
+            Curve::PiecewiseLinear(p) => p.validate_bounded(min_y, max_y),
+        }
+    }
+
     /// return (min, max) that can ever be returned from value. These could potentially be u128::MIN and u128::MAX
     pub fn range(&self) -> (u128, u128) {
         match self {
@@ -134,46 +178,202 @@ impl Curve {
         }
     }
 
-    /// combines a constant with a curve (shifting the curve up)
-    fn combine_const(&self, const_y: Uint128) -> Curve {
+    /// return (min, max) that value returns for any x in [from, to]
+    pub fn value_range(&self, from: u64, to: u64) -> (Uint128, Uint128) {
+        let (from, to) = (from.min(to), from.max(to));
+        match self {
+            Curve::Constant { y } => (*y, *y),
+            Curve::SaturatingLinear(sat) => sat.value_range(from, to),
+            Curve::PiecewiseLinear(p) => p.value_range(from, to),
+        }
+    }
+
+    /// return the max value that value returns for any x in [from, to]
+    pub fn max_in_range(&self, from: u64, to: u64) -> Uint128 {
+        self.value_range(from, to).1
+    }
+
+    /// return the min value that value returns for any x in [from, to]
+    pub fn min_in_range(&self, from: u64, to: u64) -> Uint128 {
+        self.value_range(from, to).0
+    }
+
+    /// return true if value returns 0 for every x in [from, to]
+    pub fn is_zero_at_range(&self, from: u64, to: u64) -> bool {
+        self.max_in_range(from, to).is_zero()
+    }
+
+    /// return the first x at which value is less than or equal to `target`,
+    /// None if the curve never gets that low
+    pub fn first_time_at_or_below(&self, target: Uint128) -> Option<u64> {
+        first_time_where(&self.steps(), |y| y <= target, target)
+    }
+
+    /// return the first x at which value is greater than or equal to `target`,
+    /// None if the curve never gets that high
+    pub fn first_time_at_or_above(&self, target: Uint128) -> Option<u64> {
+        first_time_where(&self.steps(), |y| y >= target, target)
+    }
+
+    /// the points the curve interpolates between, as in PiecewiseLinear
+    fn steps(&self) -> Vec<(u64, Uint128)> {
         match self {
-            Curve::Constant { y } => Curve::Constant { y: const_y + y },
+            Curve::Constant { y } => vec![(0, *y)],
+            Curve::SaturatingLinear(sl) => PiecewiseLinear::from(sl).steps,
+            Curve::PiecewiseLinear(pl) => pl.steps.clone(),
+        }
+    }
+
+    /// return the time between the first and the last point of the curve, None for constants
+    pub fn total_vest_duration(&self) -> Option<u64> {
+        match self {
+            Curve::Constant { .. } => None,
+            Curve::SaturatingLinear(sat) => Some(sat.max_x.saturating_sub(sat.min_x)),
+            Curve::PiecewiseLinear(p) => p.total_vest_duration(),
+        }
+    }
+
+    /// return the first x at which the value begins to change, None if it never changes
+    pub fn vest_start_time(&self) -> Option<u64> {
+        match self {
+            Curve::Constant { .. } => None,
+            Curve::SaturatingLinear(sat) => (sat.min_y != sat.max_y).then_some(sat.min_x),
+            Curve::PiecewiseLinear(p) => p.vest_start_time(),
+        }
+    }
+
+    /// returns the same curve, moved `offset` to the right on the x axis
+    pub fn shift_right(&self, offset: u64) -> Curve {
+        match self {
+            Curve::Constant { y } => Curve::Constant { y: *y },
+            Curve::SaturatingLinear(sl) => Curve::SaturatingLinear(SaturatingLinear {
+                min_x: sl.min_x.saturating_add(offset),
+                min_y: sl.min_y,
+                max_x: sl.max_x.saturating_add(offset),
+                max_y: sl.max_y,
+            }),
+            Curve::PiecewiseLinear(pl) => Curve::PiecewiseLinear(PiecewiseLinear {
+                steps: pl
+                    .steps
+                    .iter()
+                    .map(|&(x, y)| (x.saturating_add(offset), y))
+                    .collect(),
+            }),
+        }
+    }
+
+    /// combines a constant with a curve (shifting the curve up)
+    fn combine_const(&self, const_y: Uint128) -> Result<Curve, CurveError> {
+        let add = |y: Uint128| y.checked_add(const_y).map_err(|_| CurveError::Overflow {});
+        Ok(match self {
+            Curve::Constant { y } => Curve::Constant { y: add(*y)? },
             Curve::SaturatingLinear(sl) => Curve::SaturatingLinear(SaturatingLinear {
                 min_x: sl.min_x,
-                min_y: sl.min_y + const_y,
+                min_y: add(sl.min_y)?,
                 max_x: sl.max_x,
-                max_y: sl.max_y + const_y,
+                max_y: add(sl.max_y)?,
             }),
             Curve::PiecewiseLinear(pl) => Curve::PiecewiseLinear(PiecewiseLinear {
-                steps: pl.steps.iter().map(|&(x, y)| (x, const_y + y)).collect(),
+                steps: pl
+                    .steps
+                    .iter()
+                    .map(|&(x, y)| Ok((x, add(y)?)))
+                    .collect::<Result<_, CurveError>>()?,
             }),
+        })
+    }
+
+    /// lossy compression of a monotonic decreasing curve down to at most `max_steps` steps.
+    /// The result never has a lower value than this curve and still ends at the same value.
+    pub fn simplify_conservative(&self, max_steps: usize) -> Result<Curve, CurveError> {
+        match self {
+            _ if self.size() <= max_steps => Ok(self.clone()),
+            Curve::PiecewiseLinear(p) => {
+                Ok(Curve::PiecewiseLinear(p.simplify_conservative(max_steps)?))
+            }
+            _ => Err(CurveError::TooComplex),
         }
     }
 
-    /// returns a new curve that is the result of adding the given curve to this one
-    pub fn combine(&self, other: &Curve) -> Curve {
-        match (self, other) {
+    /// returns a new curve that is the result of adding the given curve to this one.
+    /// Fails with `CurveError::Overflow` if the sum does not fit into a Uint128 anywhere.
+    pub fn combine(&self, other: &Curve) -> Result<Curve, CurveError> {
+        Ok(match (self, other) {
             // special handling for constant cases:
             (Curve::Constant { y }, curve) | (curve, Curve::Constant { y }) => {
-                curve.combine_const(*y)
+                curve.combine_const(*y)?
             }
             // cases that can be converted to piecewise linear:
             (Curve::SaturatingLinear(sl1), Curve::SaturatingLinear(sl2)) => {
                 // convert to piecewise linear, then combine those
                 Curve::PiecewiseLinear(
-                    PiecewiseLinear::from(sl1).combine(&PiecewiseLinear::from(sl2)),
+                    PiecewiseLinear::from(sl1).combine(&PiecewiseLinear::from(sl2))?,
                 )
             }
             (Curve::SaturatingLinear(sl), Curve::PiecewiseLinear(pl))
             | (Curve::PiecewiseLinear(pl), Curve::SaturatingLinear(sl)) => {
                 // convert sl to piecewise linear, then combine
-                Curve::PiecewiseLinear(PiecewiseLinear::from(sl).combine(pl))
+                Curve::PiecewiseLinear(PiecewiseLinear::from(sl).combine(pl)?)
             }
             (Curve::PiecewiseLinear(pl1), Curve::PiecewiseLinear(pl2)) => {
-                Curve::PiecewiseLinear(pl1.combine(pl2))
+                Curve::PiecewiseLinear(pl1.combine(pl2)?)
             }
+        })
+    }
+
+    /// returns a curve that has the lower value of the two curves everywhere.
+    /// Where the curves cross between two steps, a step is added at the crossing, rounded down.
+    /// So the result has at most `size(self) + size(other) + crossings` steps, and between such a
+    /// step and the next one it can be slightly below the actual minimum, but never above it.
+    pub fn min(&self, other: &Curve) -> Curve {
+        self.pointwise(other, Uint128::min)
+    }
+
+    /// returns a curve that has the higher value of the two curves everywhere.
+    /// Like `min`, but the result can be slightly above the actual maximum after a crossing.
+    pub fn max(&self, other: &Curve) -> Curve {
+        self.pointwise(other, Uint128::max)
+    }
+
+    /// returns a curve that is the difference of the two curves everywhere, floored at 0.
+    /// Like `min`, a step is added where the curves cross, rounded down.
+    pub fn saturating_sub(&self, other: &Curve) -> Curve {
+        self.pointwise(other, Uint128::saturating_sub)
+    }
+
+    /// the x-coordinates at which the slope of the curve can change, empty for constants
+    fn breakpoints(&self) -> Vec<u64> {
+        match self {
+            Curve::Constant { .. } => vec![],
+            _ => self.steps().into_iter().map(|(x, _)| x).collect(),
         }
     }
+
+    /// picks the value of one of the curves at all steps of both and where they cross
+    fn pointwise(&self, other: &Curve, pick: fn(Uint128, Uint128) -> Uint128) -> Curve {
+        if let (Curve::Constant { y: a }, Curve::Constant { y: b }) = (self, other) {
+            return Curve::Constant { y: pick(*a, *b) };
+        }
+
+        let mut x = self.breakpoints();
+        x.extend(other.breakpoints());
+        x.sort_unstable();
+        x.dedup();
+
+        // both curves are linear between these points
+        let values: Vec<_> = x
+            .into_iter()
+            .map(|x| (x, self.value(x), other.value(x)))
+            .collect();
+        let mut steps = Vec::with_capacity(values.len());
+        for (i, &(x, a, b)) in values.iter().enumerate() {
+            steps.push((x, pick(a, b)));
+            if let Some(crossing) = values.get(i + 1).and_then(|&end| crossing((x, a, b), end)) {
+                steps.push((crossing, pick(self.value(crossing), other.value(crossing))));
+            }
+        }
+        Curve::PiecewiseLinear(PiecewiseLinear { steps })
+    }
 }
 
 /// Saturating Linear
@@ -243,17 +443,87 @@ impl SaturatingLinear {
             (self.max_y.u128(), self.min_y.u128())
         }
     }
+
+    /// return (min, max) that value returns for any x in [from, to].
+    /// As the curve is linear between its saturation points, the extremes are on the window bounds.
+    pub fn value_range(&self, from: u64, to: u64) -> (Uint128, Uint128) {
+        let (start, end) = (self.value(from), self.value(to));
+        (start.min(end), start.max(end))
+    }
 }
 
 // this requires min_x < x < max_x to have been previously validated
 fn interpolate((min_x, min_y): (u64, Uint128), (max_x, max_y): (u64, Uint128), x: u64) -> Uint128 {
+    // the product of the differences can exceed 128 bits, but the moved distance is never larger
+    // than the difference of the y values
+    let moved = |diff: Uint128| {
+        let moved = Uint256::from(diff) * Uint256::from(x - min_x) / Uint256::from(max_x - min_x);
+        Uint128::try_from(moved).unwrap()
+    };
     if max_y > min_y {
-        min_y + (max_y - min_y) * Uint128::from(x - min_x) / Uint128::from(max_x - min_x)
+        min_y + moved(max_y - min_y)
     } else {
-        min_y - (min_y - max_y) * Uint128::from(x - min_x) / Uint128::from(max_x - min_x)
+        min_y - moved(min_y - max_y)
     }
 }
 
+/// Returns the x at which two linear segments cross strictly between `start` and `end`, rounded
+/// down. Both are given as (x, value of the first segment, value of the second segment).
+/// None if they do not cross there, or if the crossing rounds down to `start`.
+fn crossing(
+    (start_x, start_a, start_b): (u64, Uint128, Uint128),
+    (end_x, end_a, end_b): (u64, Uint128, Uint128),
+) -> Option<u64> {
+    let (start_diff, end_diff) = match (start_a.cmp(&start_b), end_a.cmp(&end_b)) {
+        (Ordering::Less, Ordering::Greater) => (start_b - start_a, end_a - end_b),
+        (Ordering::Greater, Ordering::Less) => (start_a - start_b, end_b - end_a),
+        _ => return None,
+    };
+    // the distance between the segments shrinks linearly from start_diff to 0 at the crossing
+    let start_diff = Uint256::from(start_diff);
+    let dx = start_diff * Uint256::from(end_x - start_x) / (start_diff + Uint256::from(end_diff));
+    // both differences are positive, so dx < end_x - start_x
+    let dx = Uint128::try_from(dx).unwrap().u128() as u64;
+    (dx > 0).then_some(start_x + dx)
+}
+
+/// Finds the first x for which `reached(value(x))` holds, where `reached` is either `y <= target`
+/// or `y >= target`. Every segment starting at a value that has not reached the target is solved
+/// in closed form, using the same rounding as `interpolate`.
+fn first_time_where(
+    steps: &[(u64, Uint128)],
+    reached: impl Fn(Uint128) -> bool,
+    target: Uint128,
+) -> Option<u64> {
+    let &(_, first) = steps.first()?;
+    // everything before the first step has its value
+    if reached(first) {
+        return Some(0);
+    }
+    for w in steps.windows(2) {
+        let ((start_x, start_y), (end_x, end_y)) = (w[0], w[1]);
+        if !reached(end_y) {
+            // the value between the steps lies between the two of them, so the target is missed
+            continue;
+        }
+        // start_y has not reached the target, so we need to move `missing` towards end_y.
+        // interpolate moves floor(diff * dx / width), which is at least `missing` from
+        // dx = ceil(missing * width / diff) on
+        let (missing, diff) = if end_y < start_y {
+            (start_y - target, start_y - end_y)
+        } else {
+            (target - start_y, end_y - start_y)
+        };
+        let width = Uint256::from(end_x - start_x);
+        let diff = Uint256::from(diff);
+        let dx = (Uint256::from(missing) * width + diff - Uint256::from(1u8)) / diff;
+        // missing <= diff, so dx <= width
+        let dx = Uint128::try_from(dx).ok()?.u128() as u64;
+        return Some(start_x + dx);
+    }
+    None
+}
+
 /// This is a generalization of SaturatingLinear, steps must be arranged with increasing time [`u64`].
 /// Any point before first step gets the first value, after last step the last value.
 /// Otherwise, it is a linear interpolation between the two closest points.
@@ -359,6 +629,19 @@ impl PiecewiseLinear {
         shape
     }
 
+    /// returns an error if any of the steps lies outside of [min_y, max_y].
+    /// Values between the steps are interpolated, so they are always within the range as well.
+    pub fn validate_bounded(&self, min_y: Uint128, max_y: Uint128) -> Result<(), CurveError> {
+        match self.steps.iter().find(|(_, y)| *y < min_y || *y > max_y) {
+            Some((_, value)) => Err(CurveError::OutOfBounds {
+                value: *value,
+                min: min_y,
+                max: max_y,
+            }),
+            None => Ok(()),
+        }
+    }
+
     /// return (min, max) that can ever be returned from value. These could potentially be 0 and u64::MAX
     pub fn range(&self) -> (u128, u128) {
         let low = self.steps.iter().map(|(_, y)| *y).min().unwrap().u128();
@@ -366,8 +649,35 @@ impl PiecewiseLinear {
         (low, high)
     }
 
+    /// return (min, max) that value returns for any x in [from, to].
+    /// The extremes are either on the window bounds or on one of the steps within the window.
+    pub fn value_range(&self, from: u64, to: u64) -> (Uint128, Uint128) {
+        let (start, end) = (self.value(from), self.value(to));
+        self.steps
+            .iter()
+            .filter(|(x, _)| *x > from && *x < to)
+            .fold((start.min(end), start.max(end)), |(low, high), (_, y)| {
+                (low.min(*y), high.max(*y))
+            })
+    }
+
+    /// return the time between the first and the last step, None if there are no steps
+    pub fn total_vest_duration(&self) -> Option<u64> {
+        let (first, _) = self.steps.first()?;
+        let (last, _) = self.steps.last()?;
+        Some(last.saturating_sub(*first))
+    }
+
+    /// return the x of the first step followed by a different value, None if it never changes
+    pub fn vest_start_time(&self) -> Option<u64> {
+        self.steps
+            .windows(2)
+            .find(|w| w[0].1 != w[1].1)
+            .map(|w| w[0].0)
+    }
+
     /// adds two piecewise linear curves and returns the result
-    pub fn combine(&self, other: &PiecewiseLinear) -> PiecewiseLinear {
+    pub fn combine(&self, other: &PiecewiseLinear) -> Result<PiecewiseLinear, CurveError> {
         // collect x-coordinates for combined curve
         let mut x: Vec<_> = self
             .steps
@@ -379,12 +689,99 @@ impl PiecewiseLinear {
         x.dedup();
 
         // map to full coordinates
-        PiecewiseLinear {
-            steps: x
-                .into_iter()
-                .map(|x| (x, self.value(x) + other.value(x)))
-                .collect(),
+        let steps = x
+            .into_iter()
+            .map(|x| {
+                let y = self
+                    .value(x)
+                    .checked_add(other.value(x))
+                    .map_err(|_| CurveError::Overflow {})?;
+                Ok((x, y))
+            })
+            .collect::<Result<_, CurveError>>()?;
+        Ok(PiecewiseLinear { steps })
+    }
+
+    /// inserts a step at `x`, or replaces the value of the step already there.
+    /// The curve is left unchanged if the result would not be valid.
+    pub fn add_step_at(&mut self, x: u64, y: Uint128) -> Result<(), CurveError> {
+        let mut steps = self.steps.clone();
+        match steps.binary_search_by_key(&x, |&(step_x, _)| step_x) {
+            Ok(idx) => steps[idx].1 = y,
+            Err(idx) => steps.insert(idx, (x, y)),
+        }
+        let curve = PiecewiseLinear { steps };
+        curve.validate()?;
+        *self = curve;
+        Ok(())
+    }
+
+    /// removes the step at `x`. Fails if there is none, or if it is the last step of the curve.
+    pub fn remove_step_at(&mut self, x: u64) -> Result<(), CurveError> {
+        let idx = self
+            .steps
+            .binary_search_by_key(&x, |&(step_x, _)| step_x)
+            .map_err(|_| CurveError::StepNotFound {})?;
+        if self.steps.len() == 1 {
+            return Err(CurveError::MissingSteps);
         }
+        self.steps.remove(idx);
+        Ok(())
+    }
+
+    /// lossy compression, merging adjacent steps whose values differ by at most `tolerance`.
+    /// Each such pair is replaced by the step with the lower value. Chains of steps are only
+    /// merged as long as all of their original values stay within `tolerance` of the lowest one,
+    /// so no step changes by more than `tolerance`.
+    /// The tolerance must not be larger than 1% of the maximum value of the curve.
+    pub fn compress_similar(&self, tolerance: Uint128) -> Result<PiecewiseLinear, CurveError> {
+        self.validate()?;
+        if tolerance.u128() > self.range().1 / 100 {
+            return Err(CurveError::ToleranceTooLarge {});
+        }
+
+        let mut steps: Vec<(u64, Uint128)> = Vec::with_capacity(self.steps.len());
+        // highest original value merged into the last step, which holds the lowest one
+        let mut merged_max = Uint128::zero();
+        for &(x, y) in &self.steps {
+            match steps.last_mut() {
+                Some(last) if merged_max.max(y) - last.1.min(y) <= tolerance => {
+                    if y < last.1 {
+                        *last = (x, y);
+                    }
+                    merged_max = merged_max.max(y);
+                }
+                _ => {
+                    steps.push((x, y));
+                    merged_max = y;
+                }
+            }
+        }
+        Ok(PiecewiseLinear { steps })
+    }
+
+    /// lossy compression of a monotonic decreasing curve down to at most `max_steps` steps.
+    /// Repeatedly removes the step followed by the smallest decrease and postpones that decrease
+    /// to the next step, so the result never has a lower value than the original curve.
+    /// The last step is always kept, so the curve still ends at the same value.
+    pub fn simplify_conservative(&self, max_steps: usize) -> Result<PiecewiseLinear, CurveError> {
+        self.validate_monotonic_decreasing()?;
+        if self.steps.len() > max_steps && max_steps < 2 {
+            return Err(CurveError::TooComplex);
+        }
+
+        let mut steps = self.steps.clone();
+        while steps.len() > max_steps {
+            // the last pair cannot be merged without changing the final value
+            let (i, _) = steps[..steps.len() - 1]
+                .windows(2)
+                .enumerate()
+                .min_by_key(|(_, pair)| pair[0].1 - pair[1].1)
+                .unwrap();
+            let y = steps.remove(i).1;
+            steps[i].1 = y;
+        }
+        Ok(PiecewiseLinear { steps })
     }
 }
 
@@ -716,11 +1113,11 @@ mod tests {
         x_values: [u64; LEN],
         expected_size: usize,
     ) {
-        let combined = curve1.combine(curve2);
+        let combined = curve1.combine(curve2).unwrap();
 
         assert_eq!(
             combined,
-            curve2.combine(curve1),
+            curve2.combine(curve1).unwrap(),
             "combine should be commutative"
         );
 
@@ -763,6 +1160,53 @@ mod tests {
         test_combine(&sl, &sl, [0, 10, 20, 50, 100, 110, 120], 2);
     }
 
+    #[test]
+    fn test_extreme_coordinates() {
+        let xs = [0, 1, u64::MAX / 2, u64::MAX - 1, u64::MAX];
+        let ys = [0, 1, u128::MAX / 2, u128::MAX];
+        let mut curves = vec![];
+        for (&x1, &x2) in xs.iter().flat_map(|x1| xs.iter().map(move |x2| (x1, x2))) {
+            for (&y1, &y2) in ys.iter().flat_map(|y1| ys.iter().map(move |y2| (y1, y2))) {
+                if x1 < x2 {
+                    curves.push(Curve::saturating_linear((x1, y1), (x2, y2)));
+                }
+            }
+        }
+
+        // evaluating never panics and stays within the range of the curve
+        for curve in &curves {
+            let (low, high) = curve.range();
+            for x in xs.iter().chain(&[u64::MAX / 3, u64::MAX - 2]) {
+                let value = curve.value(*x).u128();
+                assert!(low <= value && value <= high);
+            }
+        }
+
+        // combining either works or reports an overflow
+        for (curve1, curve2) in curves.iter().zip(curves.iter().rev()) {
+            let fits = curve1.range().1.checked_add(curve2.range().1).is_some();
+            match curve1.combine(curve2) {
+                Ok(combined) => {
+                    // interpolating the sum may round differently than the single curves
+                    for x in xs {
+                        let combined = combined.value(x).u128();
+                        let sum = (curve1.value(x) + curve2.value(x)).u128();
+                        assert!(combined.max(sum) - combined.min(sum) <= 1);
+                    }
+                }
+                Err(err) => {
+                    assert!(!fits);
+                    assert_eq!(err, CurveError::Overflow {});
+                }
+            }
+        }
+        let max = Curve::constant(u128::MAX);
+        assert_eq!(
+            max.combine(&Curve::saturating_linear((0, 1), (u64::MAX, 0))),
+            Err(CurveError::Overflow {})
+        );
+    }
+
     #[test]
     fn test_complexity_validation() {
         let curve = Curve::constant(6);
@@ -794,4 +1238,491 @@ mod tests {
         curve.validate_complexity(3).unwrap();
         curve.validate_complexity(4).unwrap();
     }
+
+    #[test_case(&[(10, 1000), (20, 995), (30, 500), (40, 0)], 5, &[(20, 995), (30, 500), (40, 0)]; "merges similar steps to lower value")]
+    #[test_case(&[(10, 0), (20, 4), (30, 1000), (40, 1003)], 5, &[(10, 0), (30, 1000)]; "merges increasing steps")]
+    #[test_case(&[(10, 1000), (20, 999), (30, 998), (40, 0)], 2, &[(30, 998), (40, 0)]; "merges chains")]
+    #[test_case(&[(10, 1000), (20, 998), (30, 996), (40, 994), (50, 0)], 2, &[(20, 998), (40, 994), (50, 0)]; "chains stay within tolerance of original steps")]
+    #[test_case(&[(10, 1000), (20, 994), (30, 0)], 5, &[(10, 1000), (20, 994), (30, 0)]; "keeps steps outside tolerance")]
+    #[test_case(&[(10, 1000), (20, 1000)], 0, &[(10, 1000)]; "zero tolerance merges equal steps")]
+    fn test_compress_similar(steps: &[(u64, u128)], tolerance: u128, expected: &[(u64, u128)]) {
+        let to_curve = |steps: &[(u64, u128)]| PiecewiseLinear {
+            steps: steps.iter().map(|(x, y)| (*x, Uint128::new(*y))).collect(),
+        };
+        let compressed = to_curve(steps)
+            .compress_similar(Uint128::new(tolerance))
+            .unwrap();
+        assert_eq!(compressed, to_curve(expected));
+        compressed.validate().unwrap();
+    }
+
+    #[test]
+    fn test_compress_similar_max_deviation() {
+        // slowly decreasing with some noise, where merging into the previous merge would drift
+        let steps: Vec<_> = (1..=200u64)
+            .map(|i| {
+                (
+                    i * 10,
+                    Uint128::new(100_000 - i as u128 * 3 + (i as u128 * 37 % 11)),
+                )
+            })
+            .collect();
+        let curve = PiecewiseLinear {
+            steps: steps.clone(),
+        };
+        let tolerance = Uint128::new(20);
+        let compressed = curve.compress_similar(tolerance).unwrap();
+        assert!(compressed.steps.len() < steps.len() / 2);
+
+        // every original step is merged into one of the kept steps around it, which is at most
+        // `tolerance` lower
+        for (x, y) in steps {
+            let idx = compressed.steps.partition_point(|&(kept_x, _)| kept_x < x);
+            let around =
+                &compressed.steps[idx.saturating_sub(1)..(idx + 1).min(compressed.steps.len())];
+            assert!(
+                around
+                    .iter()
+                    .any(|&(_, kept_y)| kept_y <= y && y - kept_y <= tolerance),
+                "step at {} moved too far",
+                x
+            );
+        }
+    }
+
+    #[test]
+    fn test_compress_similar_tolerance_too_large() {
+        let curve = PiecewiseLinear {
+            steps: vec![(10, Uint128::new(1000)), (20, Uint128::new(0))],
+        };
+        // 1% of 1000 is fine
+        curve.compress_similar(Uint128::new(10)).unwrap();
+        assert_eq!(
+            curve.compress_similar(Uint128::new(11)).unwrap_err(),
+            CurveError::ToleranceTooLarge {}
+        );
+    }
+
+    #[test_case(Curve::constant(100), 0, 1000, (100, 100); "constant")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 0, 50, (1000, 1000); "saturating before start")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 120, 150, (500, 800); "saturating clamped to window")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 50, 300, (0, 1000); "saturating window covers curve")]
+    #[test_case(Curve::saturating_linear((100, 0), (200, 1000)), 150, 120, (200, 500); "saturating reversed window")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 200), (40, 0)]), 15, 35, (100, 500); "piecewise includes inner steps")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 200), (40, 0)]), 22, 28, (260, 440); "piecewise between steps")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 200), (40, 0)]), 20, 30, (200, 500); "piecewise on steps")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 200), (40, 0)]), 50, 60, (0, 0); "piecewise after end")]
+    fn test_value_range(curve: Curve, from: u64, to: u64, (min, max): (u128, u128)) {
+        let expected = (Uint128::new(min), Uint128::new(max));
+        assert_eq!(curve.value_range(from, to), expected);
+        assert_eq!(curve.min_in_range(from, to), expected.0);
+        assert_eq!(curve.max_in_range(from, to), expected.1);
+    }
+
+    #[test_case(Curve::constant(0), 0, u64::MAX, true; "zero constant")]
+    #[test_case(Curve::constant(1), 0, 10, false; "non-zero constant")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 200, 300, true; "saturating fully vested")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 199, 300, false; "saturating vesting at start")]
+    #[test_case(Curve::saturating_linear((100, 0), (200, 1000)), 0, 100, true; "increasing saturating before start")]
+    #[test_case(Curve::saturating_linear((100, 0), (200, 1000)), 0, 101, false; "increasing saturating in window")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 0)]), 30, 40, true; "piecewise after end")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 0)]), 40, 30, true; "piecewise reversed window")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 0)]), 25, 40, false; "piecewise vesting at start")]
+    #[test_case(pl(&[(10, 0), (20, 0), (30, 5)]), 0, 20, true; "piecewise zero before increase")]
+    #[test_case(pl(&[(10, 0), (20, 0), (30, 5)]), 0, 22, false; "piecewise step in window")]
+    fn test_is_zero_at_range(curve: Curve, from: u64, to: u64, expected: bool) {
+        assert_eq!(curve.is_zero_at_range(from, to), expected);
+    }
+
+    #[test_case(Curve::constant(100), None, None; "constant")]
+    #[test_case(Curve::saturating_linear((100, 1000), (250, 0)), Some(150), Some(100); "saturating")]
+    #[test_case(Curve::saturating_linear((100, 1000), (250, 1000)), Some(150), None; "flat saturating")]
+    #[test_case(pl(&[(10, 100)]), Some(0), None; "piecewise single step")]
+    #[test_case(pl(&[(10, 100), (40, 0)]), Some(30), Some(10); "piecewise two steps")]
+    #[test_case(pl(&[(10, 100), (20, 100), (30, 50), (40, 0)]), Some(30), Some(20); "piecewise flat start")]
+    #[test_case(pl(&[(10, 100), (20, 100), (30, 100)]), Some(20), None; "piecewise flat")]
+    fn test_vest_duration_and_start(curve: Curve, duration: Option<u64>, start: Option<u64>) {
+        assert_eq!(curve.total_vest_duration(), duration);
+        assert_eq!(curve.vest_start_time(), start);
+    }
+
+    #[test_case(Curve::constant(100), 100, Some(0); "constant reached")]
+    #[test_case(Curve::constant(100), 99, None; "constant unreachable")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 1000, Some(0); "saturating before start")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 500, Some(150); "saturating middle")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 333, Some(167); "saturating rounded")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 0, Some(200); "saturating end")]
+    #[test_case(Curve::saturating_linear((0, 10), (3, 0)), 5, Some(2); "saturating uneven division")]
+    #[test_case(Curve::saturating_linear((100, 0), (200, 1000)), 0, Some(0); "increasing saturating")]
+    #[test_case(pl(&[(10, 100), (20, 100), (30, 50), (40, 0)]), 99, Some(21); "piecewise after flat segment")]
+    #[test_case(pl(&[(10, 100), (20, 100), (30, 50), (40, 0)]), 50, Some(30); "piecewise exact step")]
+    #[test_case(pl(&[(10, 100), (20, 100), (30, 50), (40, 0)]), 0, Some(40); "piecewise last step")]
+    #[test_case(pl(&[(10, 100), (20, 50)]), 49, None; "piecewise unreachable")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 0)]), 50, Some(29); "piecewise not monotonic")]
+    fn test_first_time_at_or_below(curve: Curve, target: u128, expected: Option<u64>) {
+        assert_eq!(curve.first_time_at_or_below(Uint128::new(target)), expected);
+    }
+
+    #[test_case(Curve::constant(100), 100, Some(0); "constant reached")]
+    #[test_case(Curve::constant(100), 101, None; "constant unreachable")]
+    #[test_case(Curve::saturating_linear((100, 0), (200, 1000)), 0, Some(0); "saturating before start")]
+    #[test_case(Curve::saturating_linear((100, 0), (200, 1000)), 1, Some(101); "saturating rounded")]
+    #[test_case(Curve::saturating_linear((100, 0), (200, 1000)), 1000, Some(200); "saturating end")]
+    #[test_case(Curve::saturating_linear((100, 0), (200, 1000)), 1001, None; "saturating unreachable")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 0)), 1001, None; "decreasing saturating")]
+    #[test_case(pl(&[(10, 0), (20, 0), (30, 5)]), 1, Some(22); "piecewise after flat segment")]
+    #[test_case(pl(&[(10, 0), (20, 0), (30, 5)]), 5, Some(30); "piecewise exact step")]
+    fn test_first_time_at_or_above(curve: Curve, target: u128, expected: Option<u64>) {
+        assert_eq!(curve.first_time_at_or_above(Uint128::new(target)), expected);
+    }
+
+    #[test]
+    fn test_first_time_consistent_with_value() {
+        let curves = [
+            Curve::constant(77),
+            Curve::saturating_linear((17, 1000), (4711, 3)),
+            Curve::saturating_linear((5, 1), (1_000_000, 10u128.pow(30))),
+            pl(&[(10, 100), (20, 100), (37, 51), (40, 7), (41, 0)]),
+            pl(&[(3, 0), (10, 999), (20, 999), (33, 1000)]),
+        ];
+        for curve in curves {
+            let (low, high) = curve.range();
+            let targets = [
+                low,
+                high,
+                low / 2 + high / 2,
+                low / 3 + high / 3 * 2,
+                low + 1,
+            ];
+            for target in targets.map(Uint128::new) {
+                if let Some(x) = curve.first_time_at_or_below(target) {
+                    assert!(curve.value(x) <= target);
+                    assert!(x == 0 || curve.value(x - 1) > target);
+                } else {
+                    assert!(curve.min_in_range(0, u64::MAX) > target);
+                }
+                if let Some(x) = curve.first_time_at_or_above(target) {
+                    assert!(curve.value(x) >= target);
+                    assert!(x == 0 || curve.value(x - 1) < target);
+                } else {
+                    assert!(curve.max_in_range(0, u64::MAX) < target);
+                }
+            }
+        }
+    }
+
+    fn steps(steps: &[(u64, u128)]) -> PiecewiseLinear {
+        PiecewiseLinear {
+            steps: steps.iter().map(|(x, y)| (*x, Uint128::new(*y))).collect(),
+        }
+    }
+
+    #[test]
+    fn test_add_step_at() {
+        let mut curve = steps(&[(10, 100), (30, 50)]);
+
+        // keeps the steps sorted
+        curve.add_step_at(20, Uint128::new(80)).unwrap();
+        assert_eq!(curve, steps(&[(10, 100), (20, 80), (30, 50)]));
+        curve.add_step_at(5, Uint128::new(120)).unwrap();
+        curve.add_step_at(40, Uint128::new(0)).unwrap();
+        assert_eq!(
+            curve,
+            steps(&[(5, 120), (10, 100), (20, 80), (30, 50), (40, 0)])
+        );
+
+        // an existing step is replaced instead of duplicated
+        curve.add_step_at(20, Uint128::new(70)).unwrap();
+        assert_eq!(
+            curve,
+            steps(&[(5, 120), (10, 100), (20, 70), (30, 50), (40, 0)])
+        );
+        curve.validate_monotonic_decreasing().unwrap();
+
+        // x = 0 is not a valid step, the curve stays unchanged
+        let err = curve.add_step_at(0, Uint128::new(150)).unwrap_err();
+        assert_eq!(err, CurveError::PointsOutOfOrder);
+        assert_eq!(curve.steps.len(), 5);
+
+        // works on an empty curve
+        let mut curve = steps(&[]);
+        curve.add_step_at(10, Uint128::new(5)).unwrap();
+        assert_eq!(curve, steps(&[(10, 5)]));
+    }
+
+    #[test]
+    fn test_remove_step_at() {
+        let mut curve = steps(&[(10, 100), (20, 80), (30, 50)]);
+
+        curve.remove_step_at(20).unwrap();
+        assert_eq!(curve, steps(&[(10, 100), (30, 50)]));
+
+        // unknown steps are rejected
+        let err = curve.remove_step_at(20).unwrap_err();
+        assert_eq!(err, CurveError::StepNotFound {});
+        let err = curve.remove_step_at(15).unwrap_err();
+        assert_eq!(err, CurveError::StepNotFound {});
+
+        // the last step cannot be removed
+        curve.remove_step_at(10).unwrap();
+        assert_eq!(curve, steps(&[(30, 50)]));
+        let err = curve.remove_step_at(30).unwrap_err();
+        assert_eq!(err, CurveError::MissingSteps);
+        assert_eq!(curve, steps(&[(30, 50)]));
+    }
+
+    #[test]
+    fn test_piecewise_without_steps() {
+        let curve = PiecewiseLinear { steps: vec![] };
+        assert_eq!(curve.total_vest_duration(), None);
+        assert_eq!(curve.vest_start_time(), None);
+    }
+
+    #[test]
+    fn test_vest_duration_of_unvalidated_curves() {
+        // curves deserialized without validation may have their points out of order
+        let sat = Curve::SaturatingLinear(SaturatingLinear {
+            min_x: 200,
+            min_y: Uint128::new(1000),
+            max_x: 100,
+            max_y: Uint128::zero(),
+        });
+        assert_eq!(sat.total_vest_duration(), Some(0));
+        assert_eq!(pl(&[(40, 100), (10, 0)]).total_vest_duration(), Some(0));
+    }
+
+    #[test]
+    fn test_shift_right() {
+        let constant = Curve::constant(50);
+        assert_eq!(constant.shift_right(100), constant);
+
+        let sl = Curve::saturating_linear((0, 100), (30, 0));
+        let shifted = sl.shift_right(1000);
+        assert_eq!(shifted, Curve::saturating_linear((1000, 100), (1030, 0)));
+        for x in [0, 10, 25, 30, 100] {
+            assert_eq!(shifted.value(x + 1000), sl.value(x));
+        }
+
+        let piecewise = pl(&[(10, 100), (20, 50), (40, 0)]);
+        let shifted = piecewise.shift_right(5);
+        assert_eq!(shifted, pl(&[(15, 100), (25, 50), (45, 0)]));
+        for x in [0, 10, 15, 20, 30, 40, 50] {
+            assert_eq!(shifted.value(x + 5), piecewise.value(x));
+        }
+    }
+
+    fn pl(steps: &[(u64, u128)]) -> Curve {
+        Curve::PiecewiseLinear(PiecewiseLinear {
+            steps: steps.iter().map(|(x, y)| (*x, Uint128::new(*y))).collect(),
+        })
+    }
+
+    #[test_case(Curve::constant(100), 100, 100; "constant")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 100)), 100, 1000; "saturating")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 200)]), 0, 500; "piecewise")]
+    fn test_validate_bounded_in_bounds(curve: Curve, min: u128, max: u128) {
+        curve
+            .validate_bounded(Uint128::new(min), Uint128::new(max))
+            .unwrap();
+    }
+
+    #[test_case(Curve::constant(100), 101, 200, 100; "constant")]
+    #[test_case(Curve::saturating_linear((100, 1000), (200, 100)), 200, 1000, 100; "saturating")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 50)]), 100, 500, 50; "piecewise")]
+    fn test_validate_bounded_below(curve: Curve, min: u128, max: u128, value: u128) {
+        let (min, max) = (Uint128::new(min), Uint128::new(max));
+        assert_eq!(
+            curve.validate_bounded(min, max).unwrap_err(),
+            CurveError::OutOfBounds {
+                value: Uint128::new(value),
+                min,
+                max
+            }
+        );
+    }
+
+    #[test_case(Curve::constant(100), 0, 99, 100; "constant")]
+    #[test_case(Curve::saturating_linear((100, 0), (200, 1000)), 0, 999, 1000; "saturating")]
+    #[test_case(pl(&[(10, 100), (20, 500), (30, 50)]), 50, 499, 500; "piecewise")]
+    fn test_validate_bounded_above(curve: Curve, min: u128, max: u128, value: u128) {
+        let (min, max) = (Uint128::new(min), Uint128::new(max));
+        assert_eq!(
+            curve.validate_bounded(min, max).unwrap_err(),
+            CurveError::OutOfBounds {
+                value: Uint128::new(value),
+                min,
+                max
+            }
+        );
+    }
+
+    fn test_pointwise(a: &Curve, b: &Curve, expected_min: &Curve, expected_max: &Curve) {
+        let (min, max) = (a.min(b), a.max(b));
+        assert_eq!(&min, expected_min);
+        assert_eq!(&max, expected_max);
+        assert_eq!(min, b.min(a), "min should be commutative");
+        assert_eq!(max, b.max(a), "max should be commutative");
+        min.validate().unwrap();
+        max.validate().unwrap();
+    }
+
+    #[test]
+    fn test_min_max_crossing_curves() {
+        let decreasing = Curve::saturating_linear((10, 100), (110, 0));
+        let increasing = Curve::saturating_linear((10, 0), (110, 100));
+        test_pointwise(
+            &decreasing,
+            &increasing,
+            &pl(&[(10, 0), (60, 50), (110, 0)]),
+            &pl(&[(10, 100), (60, 50), (110, 100)]),
+        );
+
+        // two crossings, one of them between steps of both curves
+        let a = pl(&[(10, 1000), (20, 800), (40, 0)]);
+        let b = pl(&[(10, 500), (30, 700), (50, 100)]);
+        test_pointwise(
+            &a,
+            &b,
+            &pl(&[(10, 500), (20, 600), (24, 640), (30, 400), (40, 0), (50, 0)]),
+            &pl(&[
+                (10, 1000),
+                (20, 800),
+                (24, 640),
+                (30, 700),
+                (40, 400),
+                (50, 100),
+            ]),
+        );
+        assert!(a.min(&b).size() <= a.size() + b.size() + 1);
+    }
+
+    #[test]
+    fn test_min_max_identical_curves() {
+        let sl = Curve::saturating_linear((10, 100), (110, 0));
+        let as_pl = pl(&[(10, 100), (110, 0)]);
+        test_pointwise(&sl, &sl, &as_pl, &as_pl);
+
+        let curve = pl(&[(10, 50), (20, 70), (30, 100)]);
+        test_pointwise(&curve, &curve, &curve, &curve);
+
+        let c = Curve::constant(10);
+        test_pointwise(&c, &c, &c, &c);
+    }
+
+    #[test]
+    fn test_min_max_constant_and_piecewise() {
+        test_pointwise(
+            &Curve::constant(10),
+            &Curve::constant(20),
+            &Curve::constant(10),
+            &Curve::constant(20),
+        );
+
+        // the constant does not add steps of its own
+        let c = Curve::constant(50);
+        let curve = pl(&[(10, 100), (20, 0), (30, 80)]);
+        test_pointwise(
+            &c,
+            &curve,
+            &pl(&[(10, 50), (15, 50), (20, 0), (26, 48), (30, 50)]),
+            &pl(&[(10, 100), (15, 50), (20, 50), (26, 50), (30, 80)]),
+        );
+
+        // the second crossing is at 26.25, so right after it the result is conservative
+        let (min, max) = (c.min(&curve), c.max(&curve));
+        for x in 0..40 {
+            let (a, b) = (c.value(x), curve.value(x));
+            assert!(min.value(x) <= a.min(b), "min too high at {}", x);
+            assert!(max.value(x) >= a.max(b), "max too low at {}", x);
+        }
+        assert_eq!(min.value(27), Uint128::new(48));
+        assert_eq!(max.value(27), Uint128::new(57));
+    }
+
+    #[test]
+    fn test_min_max_match_values() {
+        // all of these cross each other at integer coordinates
+        let curves = [
+            Curve::constant(400),
+            Curve::saturating_linear((10, 1000), (110, 0)),
+            pl(&[(10, 1000), (20, 800), (40, 0)]),
+            pl(&[(10, 500), (30, 700), (50, 100)]),
+            pl(&[(20, 0), (60, 400), (100, 0)]),
+        ];
+        for a in &curves {
+            for b in &curves {
+                let (min, max) = (a.min(b), a.max(b));
+                for x in 0..=130 {
+                    let (a, b) = (a.value(x), b.value(x));
+                    assert_eq!(min.value(x), a.min(b), "min at {}", x);
+                    assert_eq!(max.value(x), a.max(b), "max at {}", x);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_saturating_sub() {
+        // subtracting a part of a sum gives back the other part
+        let a = pl(&[(10, 1000), (20, 800), (40, 0)]);
+        let b = Curve::saturating_linear((10, 500), (50, 100));
+        let sum = a.combine(&b).unwrap();
+        assert_eq!(
+            sum.saturating_sub(&b),
+            pl(&[(10, 1000), (20, 800), (40, 0), (50, 0)])
+        );
+
+        // the result is floored at 0, with a step where the curves cross
+        let curve = Curve::saturating_linear((10, 100), (110, 0));
+        let diff = curve.saturating_sub(&Curve::constant(40));
+        assert_eq!(diff, pl(&[(10, 60), (70, 0), (110, 0)]));
+        for x in 0..=120 {
+            assert_eq!(
+                diff.value(x),
+                curve.value(x).saturating_sub(Uint128::new(40))
+            );
+        }
+        assert_eq!(
+            Curve::constant(10).saturating_sub(&Curve::constant(20)),
+            Curve::constant(0)
+        );
+    }
+
+    #[test]
+    fn test_simplify_conservative() {
+        let curve = pl(&[
+            (10, 1000),
+            (20, 990),
+            (30, 600),
+            (40, 590),
+            (50, 300),
+            (60, 0),
+        ]);
+        let simplified = curve.simplify_conservative(4).unwrap();
+        assert_eq!(simplified, pl(&[(20, 1000), (40, 600), (50, 300), (60, 0)]));
+        for x in (0..70).step_by(5) {
+            assert!(simplified.value(x) >= curve.value(x));
+        }
+
+        // can simplify down to two steps, keeping the end
+        let simplified = curve.simplify_conservative(2).unwrap();
+        assert_eq!(simplified, pl(&[(50, 1000), (60, 0)]));
+        assert_eq!(
+            curve.simplify_conservative(1).unwrap_err(),
+            CurveError::TooComplex
+        );
+
+        // curves simple enough are left unchanged
+        assert_eq!(curve.simplify_conservative(6).unwrap(), curve);
+        let sl = Curve::saturating_linear((10, 100), (20, 0));
+        assert_eq!(sl.simplify_conservative(2).unwrap(), sl);
+
+        // only decreasing curves can be simplified
+        assert_eq!(
+            pl(&[(10, 0), (20, 10), (30, 20)])
+                .simplify_conservative(2)
+                .unwrap_err(),
+            CurveError::MonotonicIncreasing
+        );
+    }
 }
@@ -44,6 +44,19 @@ impl ScalableCurve {
             .validate_monotonic_decreasing()
     }
 
+    /// return the first and the last x of the curve, None for constants
+    pub fn time_range(&self) -> Option<(u64, u64)> {
+        match self {
+            ScalableCurve::Constant { .. } => None,
+            ScalableCurve::ScalableLinear(s) => Some((s.min_x, s.max_x)),
+            ScalableCurve::ScalablePiecewise(p) => {
+                let first = p.steps.first()?.0;
+                let last = p.steps.last()?.0;
+                Some((first, last))
+            }
+        }
+    }
+
     /// create a linear scalable function based on 2 points where x is time and y is value
     pub fn linear((min_x, min_percent): (u64, u64), (max_x, max_percent): (u64, u64)) -> Self {
         ScalableCurve::ScalableLinear(ScalableLinear {
@@ -155,4 +168,27 @@ mod test {
             })
         );
     }
+
+    #[test]
+    fn time_range() {
+        let constant = ScalableCurve::Constant {
+            ratio: Decimal::percent(50),
+        };
+        assert_eq!(constant.time_range(), None);
+
+        let linear = ScalableCurve::linear((10000, 100), (20000, 0));
+        assert_eq!(linear.time_range(), Some((10000, 20000)));
+
+        let piecewise = ScalableCurve::ScalablePiecewise(ScalablePiecewise {
+            steps: vec![
+                (10000, Decimal::percent(100)),
+                (20000, Decimal::percent(70)),
+                (25000, Decimal::zero()),
+            ],
+        });
+        assert_eq!(piecewise.time_range(), Some((10000, 25000)));
+
+        let empty = ScalableCurve::ScalablePiecewise(ScalablePiecewise { steps: vec![] });
+        assert_eq!(empty.time_range(), None);
+    }
 }